@@ -0,0 +1,14 @@
+;; Tree-sitter definition queries for C# duplicate detection
+;; Definitions of the same kind and name in the same body are duplicates. Parameter lists
+;; are qualifiers, so overloads are not duplicates. Types are not checked because partial
+;; classes are declared more than once.
+
+(method_declaration
+  name: (identifier) @name
+  parameters: (parameter_list) @qualifier) @definition.method
+
+(constructor_declaration
+  name: (identifier) @name
+  parameters: (parameter_list) @qualifier) @definition.constructor
+
+(property_declaration name: (identifier) @name) @definition.property
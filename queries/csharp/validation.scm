@@ -0,0 +1,70 @@
+;; Tree-sitter validation queries for C# semantic editing
+;; Focus on misplacements that tree-sitter-c-sharp accepts but the compiler rejects
+
+;; CRITICAL: Methods must be declared inside a class, struct, interface, or record
+(namespace_declaration
+  body: (declaration_list
+          (method_declaration) @invalid.method.outside.class))
+
+;; Top-level local functions cannot have access modifiers, so these are misplaced methods
+(global_statement
+  (local_function_statement
+    (modifier) @_modifier) @invalid.method.outside.class
+  (#any-of? @_modifier "public" "private" "protected" "internal"))
+
+;; CRITICAL: Using directives cannot appear inside type bodies
+(class_declaration
+  body: (declaration_list
+          (using_directive) @invalid.using.in.type.body))
+
+(struct_declaration
+  body: (declaration_list
+          (using_directive) @invalid.using.in.type.body))
+
+(interface_declaration
+  body: (declaration_list
+          (using_directive) @invalid.using.in.type.body))
+
+(record_declaration
+  body: (declaration_list
+          (using_directive) @invalid.using.in.type.body))
+
+;; Using directives must come before all other declarations
+(compilation_unit
+  [(class_declaration)
+   (struct_declaration)
+   (interface_declaration)
+   (record_declaration)
+   (enum_declaration)
+   (delegate_declaration)
+   (namespace_declaration)
+   (global_statement)]
+  (using_directive) @invalid.using.after.declarations)
+
+(namespace_declaration
+  body: (declaration_list
+          [(class_declaration)
+           (struct_declaration)
+           (interface_declaration)
+           (record_declaration)
+           (enum_declaration)
+           (delegate_declaration)
+           (namespace_declaration)]
+          (using_directive) @invalid.using.after.declarations))
+
+;; Namespaces can only be nested inside other namespaces
+(class_declaration
+  body: (declaration_list
+          (namespace_declaration) @invalid.namespace.in.type.body))
+
+(struct_declaration
+  body: (declaration_list
+          (namespace_declaration) @invalid.namespace.in.type.body))
+
+(interface_declaration
+  body: (declaration_list
+          (namespace_declaration) @invalid.namespace.in.type.body))
+
+(record_declaration
+  body: (declaration_list
+          (namespace_declaration) @invalid.namespace.in.type.body))
@@ -0,0 +1,12 @@
+;; Tree-sitter definition queries for Go duplicate detection
+;; Definitions of the same kind and name in the same file are duplicates. Method receivers
+;; are qualifiers, and a package may declare any number of init functions.
+
+((function_declaration name: (identifier) @name) @definition.function
+ (#not-eq? @name "init"))
+
+(method_declaration
+  receiver: (parameter_list) @qualifier
+  name: (field_identifier) @name) @definition.method
+
+(type_declaration (type_spec name: (type_identifier) @name)) @definition.type
@@ -0,0 +1,3 @@
+;; Import declarations organized by the organize_imports tool
+
+(import_declaration) @import
@@ -0,0 +1,47 @@
+;; Tree-sitter validation queries for Go semantic editing
+;; Functions inside struct field lists and imports inside function bodies do not parse,
+;; so syntax validation already rejects them. These cover misplacements that
+;; tree-sitter-go accepts but the Go compiler rejects.
+
+;; CRITICAL: Imports must come before all other top-level declarations
+(source_file
+  [(function_declaration)
+   (method_declaration)
+   (type_declaration)
+   (var_declaration)
+   (const_declaration)]
+  (import_declaration) @invalid.import.after.declarations)
+
+;; init takes no arguments
+((function_declaration
+  name: (identifier) @_name
+  parameters: (parameter_list (_))) @invalid.entrypoint.with.parameters
+ (#eq? @_name "init"))
+
+;; init returns no values
+((function_declaration
+  name: (identifier) @_name
+  result: (_)) @invalid.entrypoint.with.results
+ (#eq? @_name "init"))
+
+;; main is only the entrypoint in package main; elsewhere it is an ordinary function
+((source_file
+  (package_clause (package_identifier) @_package)
+  (function_declaration
+    name: (identifier) @_name
+    parameters: (parameter_list (_))) @invalid.entrypoint.with.parameters)
+ (#eq? @_package "main")
+ (#eq? @_name "main"))
+
+((source_file
+  (package_clause (package_identifier) @_package)
+  (function_declaration
+    name: (identifier) @_name
+    result: (_)) @invalid.entrypoint.with.results)
+ (#eq? @_package "main")
+ (#eq? @_name "main"))
+
+;; init cannot be called explicitly
+((call_expression
+  function: (identifier) @_name) @invalid.init.call
+ (#eq? @_name "init"))
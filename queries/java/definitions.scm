@@ -0,0 +1,18 @@
+;; Tree-sitter definition queries for Java duplicate detection
+;; Definitions of the same kind and name in the same body are duplicates. Parameter lists
+;; are qualifiers, so overloads are not duplicates.
+
+(method_declaration
+  name: (identifier) @name
+  parameters: (formal_parameters) @qualifier) @definition.method
+
+(constructor_declaration
+  name: (identifier) @name
+  parameters: (formal_parameters) @qualifier) @definition.constructor
+
+[(class_declaration name: (identifier) @name)
+ (interface_declaration name: (identifier) @name)
+ (enum_declaration name: (identifier) @name)
+ (record_declaration name: (identifier) @name)] @definition.type
+
+(field_declaration declarator: (variable_declarator name: (identifier) @name)) @definition.field
@@ -0,0 +1,67 @@
+;; Tree-sitter validation queries for Java semantic editing
+;; Focus on misplacements that tree-sitter-java accepts but javac rejects
+
+;; CRITICAL: Methods must be declared inside a class, interface, enum, or record body
+(program
+  (method_declaration) @invalid.method.outside.class)
+
+;; CRITICAL: Imports belong at the top of the file, not inside method bodies
+;; (imports inside class bodies do not parse, so syntax validation rejects them)
+(block
+  (import_declaration) @invalid.import.in.function.body)
+
+;; Imports must come before all type declarations
+(program
+  [(class_declaration)
+   (interface_declaration)
+   (enum_declaration)
+   (record_declaration)
+   (annotation_type_declaration)]
+  (import_declaration) @invalid.import.after.declarations)
+
+;; The package declaration must come first
+(program
+  [(import_declaration)
+   (class_declaration)
+   (interface_declaration)
+   (enum_declaration)
+   (record_declaration)
+   (annotation_type_declaration)]
+  (package_declaration) @invalid.package.not.first)
+
+;; Local type declarations must be inside a block, not the direct body of a statement
+(if_statement
+  consequence: [(class_declaration)
+                (interface_declaration)
+                (enum_declaration)
+                (record_declaration)] @invalid.type.as.statement.body)
+
+(if_statement
+  alternative: [(class_declaration)
+                (interface_declaration)
+                (enum_declaration)
+                (record_declaration)] @invalid.type.as.statement.body)
+
+(for_statement
+  body: [(class_declaration)
+         (interface_declaration)
+         (enum_declaration)
+         (record_declaration)] @invalid.type.as.statement.body)
+
+(enhanced_for_statement
+  body: [(class_declaration)
+         (interface_declaration)
+         (enum_declaration)
+         (record_declaration)] @invalid.type.as.statement.body)
+
+(while_statement
+  body: [(class_declaration)
+         (interface_declaration)
+         (enum_declaration)
+         (record_declaration)] @invalid.type.as.statement.body)
+
+(do_statement
+  body: [(class_declaration)
+         (interface_declaration)
+         (enum_declaration)
+         (record_declaration)] @invalid.type.as.statement.body)
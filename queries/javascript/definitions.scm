@@ -0,0 +1,19 @@
+;; Tree-sitter definition queries for JavaScript duplicate detection
+;; Definitions of the same kind and name under the same parent are duplicates.
+;; Accessor and static keywords are qualifiers, so getter/setter pairs are not duplicates.
+
+[(function_declaration name: (identifier) @name)
+ (generator_function_declaration name: (identifier) @name)] @definition.function
+
+(export_statement
+  declaration: [(function_declaration name: (identifier) @name)
+                (generator_function_declaration name: (identifier) @name)]) @definition.function
+
+(class_declaration name: (identifier) @name) @definition.class
+
+(export_statement
+  declaration: (class_declaration name: (identifier) @name)) @definition.class
+
+(method_definition name: (property_identifier) @name) @definition.method
+
+(method_definition ["get" "set" "static"] @qualifier name: (property_identifier) @name) @definition.method
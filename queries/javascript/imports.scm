@@ -0,0 +1,4 @@
+;; Import statements organized by the organize_imports tool
+;; Side-effect imports like `import "./polyfill"` are left out, since their order matters
+
+(import_statement (import_clause)) @import
@@ -0,0 +1,7 @@
+;; Tree-sitter definition queries for Python duplicate detection
+;; Definitions of the same kind and name in the same block are duplicates. Decorated
+;; definitions (@overload, @x.setter) are wrapped in decorated_definition and never collide.
+
+(function_definition name: (identifier) @name) @definition.function
+
+(class_definition name: (identifier) @name) @definition.class
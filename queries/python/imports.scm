@@ -0,0 +1,5 @@
+;; Import statements organized by the organize_imports tool
+
+[(future_import_statement)
+ (import_statement)
+ (import_from_statement)] @import
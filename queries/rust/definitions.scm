@@ -0,0 +1,45 @@
+;; Tree-sitter definition queries for Rust duplicate detection
+;; Definitions of the same kind and name under the same parent are duplicates.
+;; Attributes are qualifiers so #[cfg(...)] variants of an item are not duplicates.
+
+(function_item name: (identifier) @name) @definition.function
+
+[(struct_item name: (type_identifier) @name)
+ (enum_item name: (type_identifier) @name)
+ (union_item name: (type_identifier) @name)
+ (trait_item name: (type_identifier) @name)
+ (type_item name: (type_identifier) @name)
+ (mod_item name: (identifier) @name)] @definition.type
+
+[(const_item name: (identifier) @name)
+ (static_item name: (identifier) @name)] @definition.value
+
+(field_declaration name: (field_identifier) @name) @definition.field
+
+(enum_variant name: (identifier) @name) @definition.variant
+
+((attribute_item)+ @qualifier
+ .
+ (function_item name: (identifier) @name) @definition.function)
+
+((attribute_item)+ @qualifier
+ .
+ [(struct_item name: (type_identifier) @name)
+  (enum_item name: (type_identifier) @name)
+  (union_item name: (type_identifier) @name)
+  (trait_item name: (type_identifier) @name)
+  (type_item name: (type_identifier) @name)
+  (mod_item name: (identifier) @name)] @definition.type)
+
+((attribute_item)+ @qualifier
+ .
+ [(const_item name: (identifier) @name)
+  (static_item name: (identifier) @name)] @definition.value)
+
+((attribute_item)+ @qualifier
+ .
+ (field_declaration name: (field_identifier) @name) @definition.field)
+
+((attribute_item)+ @qualifier
+ .
+ (enum_variant name: (identifier) @name) @definition.variant)
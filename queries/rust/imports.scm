@@ -0,0 +1,3 @@
+;; Import statements organized by the organize_imports tool
+
+(use_declaration) @import
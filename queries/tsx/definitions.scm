@@ -0,0 +1,27 @@
+;; Tree-sitter definition queries for TSX duplicate detection
+;; Definitions of the same kind and name under the same parent are duplicates. Overload
+;; signatures are separate node types, and interfaces, enums, and namespaces can merge,
+;; so only implementations, classes, and type aliases are checked.
+
+[(function_declaration name: (identifier) @name)
+ (generator_function_declaration name: (identifier) @name)] @definition.function
+
+(export_statement
+  declaration: [(function_declaration name: (identifier) @name)
+                (generator_function_declaration name: (identifier) @name)]) @definition.function
+
+[(class_declaration name: (type_identifier) @name)
+ (abstract_class_declaration name: (type_identifier) @name)] @definition.class
+
+(export_statement
+  declaration: [(class_declaration name: (type_identifier) @name)
+                (abstract_class_declaration name: (type_identifier) @name)]) @definition.class
+
+(type_alias_declaration name: (type_identifier) @name) @definition.type
+
+(export_statement
+  declaration: (type_alias_declaration name: (type_identifier) @name)) @definition.type
+
+(method_definition name: (property_identifier) @name) @definition.method
+
+(method_definition ["get" "set" "static"] @qualifier name: (property_identifier) @name) @definition.method
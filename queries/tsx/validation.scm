@@ -0,0 +1,40 @@
+;; Tree-sitter validation queries for TSX semantic editing
+;; Focus on the React corruption modes agents produce most often.
+;; Unclosed or mismatched JSX elements do not parse, so syntax validation already
+;; rejects them (and scoped validation pins them to the edit that introduced them).
+
+;; CRITICAL: Statements pasted directly into JSX children render as text
+;; Imports and exports only count with a quoted module or a trailing `;`, `=`, `{`, or `(`,
+;; so prose like "export your data" or "import photos from your phone" is left alone.
+((jsx_text) @invalid.statement.in.jsx
+ (#match? @invalid.statement.in.jsx "(?m)^\\s*((const|let|var)\\s+[\\w\\[{][^=\\n]*=|return\\s*(\\(|;|$)|import\\s.+\\sfrom\\s*['\"][^'\"\\n]+['\"]\\s*;?\\s*$|export\\s+(default\\s+)?(async\\s+)?(const|let|var|function|class|interface|type|enum)\\b[^\\n]*[;={(]\\s*$)"))
+
+;; CRITICAL: Hooks must be called inside a function component or custom hook
+(program
+  (expression_statement
+    (call_expression
+      function: (identifier) @_hook) @invalid.hook.outside.component)
+  (#match? @_hook "^use[A-Z]"))
+
+(program
+  (lexical_declaration
+    (variable_declarator
+      value: (call_expression
+               function: (identifier) @_hook) @invalid.hook.outside.component))
+  (#match? @_hook "^use[A-Z]"))
+
+;; Hooks cannot be called from class component methods
+(method_definition
+  body: (statement_block
+          (expression_statement
+            (call_expression
+              function: (identifier) @_hook) @invalid.hook.in.class.component))
+  (#match? @_hook "^use[A-Z]"))
+
+(method_definition
+  body: (statement_block
+          (lexical_declaration
+            (variable_declarator
+              value: (call_expression
+                       function: (identifier) @_hook) @invalid.hook.in.class.component)))
+  (#match? @_hook "^use[A-Z]"))
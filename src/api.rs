@@ -0,0 +1,220 @@
+//! # Library API
+//!
+//! One-call helpers for Rust programs that embed the editing engine without going through MCP.
+//! They detect the language from the file extension and run the same [`Editor`] pipeline as
+//! the MCP tools, with default formatting and validation; use [`Editor`] directly for more
+//! control.
+//!
+//! - [`apply_edit`]: Edit a file, writing it only if the edit is valid
+//! - [`preview_edit`]: Report the edit that would be made, without writing anything
+//! - [`search`]: Find an anchor, with the syntax node `replace_node` would select there
+//! - [`query`]: Find the captures of a tree-sitter query
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use semantic_code_edit_mcp::api;
+//! use semantic_code_edit_mcp::selector::{Operation, Selector};
+//!
+//! let selector = Selector::builder(Operation::ReplaceNode, "fn main()").build()?;
+//! let report = api::apply_edit("src/main.rs", selector, "fn main() {}")?;
+//! if !report.is_valid() {
+//!     eprintln!("{}", report.message);
+//! }
+//!
+//! for found in api::search("src/main.rs", "fn main()")? {
+//!     println!("{}:{} {:?}", found.line, found.column, found.node_kind);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use serde::Serialize;
+use tree_sitter::{Query, QueryCursor, StreamingIterator, Tree};
+
+use crate::editor::{EditReport, Editor};
+use crate::error::SemanticEditError;
+use crate::filesystem::{FileOperations, StdFileOperations};
+use crate::languages::{LanguageCommon, LanguageName, LanguageRegistry};
+use crate::selector::Selector;
+
+/// One place a search matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub path: PathBuf,
+    /// 1-based line and column of the match
+    pub line: usize,
+    pub column: usize,
+    /// Query capture name, for query searches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    /// Kind of the syntax node matched, or selected by the anchor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_kind: Option<String>,
+    /// 1-based lines spanned by that node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_lines: Option<(usize, usize)>,
+    /// First line of the matched text
+    pub text: String,
+}
+
+/// Edit a file, writing it only if the edit is valid
+///
+/// The edit was written if [`EditReport::is_valid`]; otherwise the report's message says why
+/// it was refused.
+pub fn apply_edit(
+    path: impl AsRef<Path>,
+    selector: Selector,
+    content: impl Into<String>,
+) -> Result<EditReport> {
+    let editor = open(path.as_ref(), selector, content.into())?;
+    let encoding = editor.encoding();
+    let (report, output) = editor.commit_report()?;
+    if let Some(output) = output {
+        StdFileOperations.write_encoded(report.edit.file_path.clone(), output, encoding)?;
+    }
+    Ok(report.edit)
+}
+
+/// Report the edit that would be made to a file, with its diff, without writing anything
+pub fn preview_edit(
+    path: impl AsRef<Path>,
+    selector: Selector,
+    content: impl Into<String>,
+) -> Result<EditReport> {
+    let editor = open(path.as_ref(), selector, content.into())?;
+    let (report, _) = editor.preview_report()?;
+    Ok(report.edit)
+}
+
+/// Each occurrence of an anchor in a file, with the syntax node `replace_node` would select
+/// there
+pub fn search(path: impl AsRef<Path>, anchor: &str) -> Result<Vec<SearchMatch>> {
+    let path = path.as_ref();
+    let (_, source, tree) = parse_file(registry()?, path, None)?;
+    Ok(anchor_matches(path, &source, &tree, anchor))
+}
+
+/// Each capture of a tree-sitter query in a file
+pub fn query(path: impl AsRef<Path>, query: &str) -> Result<Vec<SearchMatch>> {
+    let path = path.as_ref();
+    let (language, source, tree) = parse_file(registry()?, path, None)?;
+    let query = compile_query(language, query)?;
+    Ok(query_matches(path, &source, &tree, &query))
+}
+
+/// The language registry shared by the helpers, whose languages are built on first use
+fn registry() -> Result<&'static LanguageRegistry> {
+    static REGISTRY: OnceLock<LanguageRegistry> = OnceLock::new();
+    if let Some(registry) = REGISTRY.get() {
+        return Ok(registry);
+    }
+    let registry = LanguageRegistry::new()?;
+    Ok(REGISTRY.get_or_init(|| registry))
+}
+
+fn open(path: &Path, selector: Selector, content: String) -> Result<Editor<'static>> {
+    let path = std::path::absolute(path)?;
+    let language = registry()?.get_language_with_hint(&path, None)?;
+    Editor::open(content, selector, language, path, None, &StdFileOperations)
+}
+
+/// Read and parse a file as the given language, or the one detected from its extension
+pub(crate) fn parse_file<'a>(
+    registry: &'a LanguageRegistry,
+    path: &Path,
+    language: Option<LanguageName>,
+) -> Result<(&'a LanguageCommon, String, Tree)> {
+    let language = registry.get_language_with_hint(path, language)?;
+    let (_, source) = StdFileOperations.read_encoded(path)?;
+    let tree = language
+        .parser()?
+        .parse(&source, None)
+        .ok_or(SemanticEditError::TreeSitterParseError)?;
+    Ok((language, source, tree))
+}
+
+pub(crate) fn compile_query(language: &LanguageCommon, query: &str) -> Result<Query> {
+    Ok(
+        Query::new(language.tree_sitter_language(), query).map_err(|error| {
+            SemanticEditError::InvalidTreeSitterQuery {
+                query: error.to_string(),
+            }
+        })?,
+    )
+}
+
+/// Each occurrence of an anchor, with the node `replace_node` would select there
+pub(crate) fn anchor_matches(
+    path: &Path,
+    source: &str,
+    tree: &Tree,
+    anchor: &str,
+) -> Vec<SearchMatch> {
+    // replace_node selects by the first line of the anchor
+    let anchor = anchor.trim().lines().next().unwrap_or_default().trim();
+    if anchor.is_empty() {
+        return Vec::new();
+    }
+    source
+        .match_indices(anchor)
+        .map(|(start, _)| {
+            let end = start + anchor.len();
+            let node = tree
+                .root_node()
+                .named_descendant_for_byte_range(start, end)
+                .or_else(|| tree.root_node().descendant_for_byte_range(start, end));
+            let (line, column) = line_and_column(source, start);
+            SearchMatch {
+                path: path.to_path_buf(),
+                line,
+                column,
+                capture: None,
+                node_kind: node.map(|node| node.kind().to_string()),
+                node_lines: node
+                    .map(|node| (node.start_position().row + 1, node.end_position().row + 1)),
+                text: first_line(&source[start..]).to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Each capture of a query
+pub(crate) fn query_matches(
+    path: &Path,
+    source: &str,
+    tree: &Tree,
+    query: &Query,
+) -> Vec<SearchMatch> {
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(query, tree.root_node(), source.as_bytes());
+    let mut matches = Vec::new();
+    while let Some((query_match, index)) = captures.next() {
+        let capture = query_match.captures[*index];
+        let node = capture.node;
+        matches.push(SearchMatch {
+            path: path.to_path_buf(),
+            line: node.start_position().row + 1,
+            column: node.start_position().column + 1,
+            capture: Some(query.capture_names()[capture.index as usize].to_string()),
+            node_kind: Some(node.kind().to_string()),
+            node_lines: Some((node.start_position().row + 1, node.end_position().row + 1)),
+            text: first_line(&source[node.byte_range()]).to_string(),
+        });
+    }
+    matches
+}
+
+/// 1-based line and column of a byte offset
+fn line_and_column(source: &str, byte: usize) -> (usize, usize) {
+    let before = &source[..byte];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (before.matches('\n').count() + 1, byte - line_start + 1)
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim_end()
+}
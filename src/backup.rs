@@ -0,0 +1,114 @@
+//! # Backups
+//!
+//! Optional backup copies of files written by `commit_staged`, taken before the
+//! original content is overwritten.
+//!
+//! ## Strategies
+//!
+//! - **`none`**: No backup is written (the default)
+//! - **`sibling`**: Copy the original to `<file>.bak` next to the file
+//! - **`directory`**: Copy the original into `.semantic-edit/backups/<timestamp>/`
+//!   under the session context (or the file's directory when no context is set),
+//!   preserving the file's path relative to that root
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::filesystem::FileOperations;
+
+/// Directory (relative to the backup root) that holds timestamped backups
+pub const BACKUP_DIRECTORY: &str = ".semantic-edit/backups";
+
+/// How to back up a file before `commit_staged` overwrites it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStrategy {
+    /// Do not write backups
+    #[default]
+    None,
+    /// Write `<file>.bak` next to the original file
+    Sibling,
+    /// Write a copy into `.semantic-edit/backups/<timestamp>/`
+    Directory,
+}
+
+impl BackupStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupStrategy::None => "none",
+            BackupStrategy::Sibling => "sibling",
+            BackupStrategy::Directory => "directory",
+        }
+    }
+
+    /// Parse a strategy name as accepted by the `SEMANTIC_EDIT_BACKUP` environment variable
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "none" | "off" => Some(BackupStrategy::None),
+            "sibling" | "bak" => Some(BackupStrategy::Sibling),
+            "directory" | "dir" => Some(BackupStrategy::Directory),
+            _ => None,
+        }
+    }
+
+    /// Where a backup of `file_path` would be written, if this strategy writes one
+    ///
+    /// `root` is used by the directory strategy; it falls back to the file's parent.
+    pub fn backup_path(&self, file_path: &Path, root: Option<&Path>) -> Option<PathBuf> {
+        match self {
+            BackupStrategy::None => None,
+            BackupStrategy::Sibling => {
+                let mut file_name = file_path.file_name()?.to_os_string();
+                file_name.push(".bak");
+                Some(file_path.with_file_name(file_name))
+            }
+            BackupStrategy::Directory => {
+                let root = root.or_else(|| file_path.parent())?;
+                let relative = file_path
+                    .strip_prefix(root)
+                    .ok()
+                    .map(Path::to_path_buf)
+                    .or_else(|| file_path.file_name().map(PathBuf::from))?;
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_millis())
+                    .unwrap_or_default();
+                Some(
+                    root.join(BACKUP_DIRECTORY)
+                        .join(timestamp.to_string())
+                        .join(relative),
+                )
+            }
+        }
+    }
+
+    /// Copy the current content of `file_path` to its backup location
+    ///
+    /// Returns the backup path, or None if this strategy does not write backups
+    /// or the file does not exist yet.
+    pub fn backup(
+        &self,
+        file_operations: &dyn FileOperations,
+        file_path: &Path,
+        root: Option<&Path>,
+    ) -> Result<Option<PathBuf>> {
+        let Some(backup_path) = self.backup_path(file_path, root) else {
+            return Ok(None);
+        };
+
+        if !file_operations.exists(file_path) {
+            return Ok(None);
+        }
+
+        let original = file_operations.read_bytes(file_path)?;
+        if let Some(parent) = backup_path.parent() {
+            file_operations.create_dir_all(parent)?;
+        }
+        file_operations.write_bytes(backup_path.clone(), original)?;
+        Ok(Some(backup_path))
+    }
+}
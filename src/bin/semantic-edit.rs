@@ -0,0 +1,21 @@
+//! Command-line front-end for semantic code editing
+//!
+//! Runs the same edit pipeline as the MCP server from shell scripts and CI. See
+//! [`semantic_code_edit_mcp::cli`] for the subcommands.
+
+use clap::Parser;
+use semantic_code_edit_mcp::cli::Cli;
+use std::io;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    env_logger::init();
+    match Cli::parse().run(&mut io::stdin(), &mut io::stdout()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("error: {error:#}");
+            ExitCode::from(2)
+        }
+    }
+}
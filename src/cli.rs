@@ -0,0 +1,459 @@
+//! Command-line front-end for the editing engine.
+//!
+//! The `semantic-edit` binary runs the same [`Editor`] pipeline as the MCP tools, so shell
+//! scripts and CI get the same validated edits. Subcommands:
+//! - `apply`: Edit a file, writing it only if the edit is valid
+//! - `preview`: Show the diff an edit would make without writing anything
+//! - `search`: List where an anchor or tree-sitter query matches, with the syntax node an
+//!   anchor selects
+//! - `format`: Format files with their language's formatter, or check that they are formatted
+//! - `validate`: Check files for syntax errors and validation rule violations
+//! - `lsp`: Serve the [language server](crate::lsp) over standard input and output
+//! - `record`: Run tool calls on a file and save them as a [snapshot test
+//!   fixture](crate::snapshot)
+//!
+//! Every subcommand prints human-readable text, or JSON with `--json`. The exit status is 0
+//! on success, 1 when an edit is refused, nothing matches, a file needs formatting, a file is
+//! invalid, or a language client exits without shutting the server down, and 2 for errors
+//! such as unreadable files.
+
+use std::io::{BufReader, Read, Write};
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub use crate::api::SearchMatch;
+use crate::api::{anchor_matches, compile_query, parse_file, query_matches};
+use crate::editor::{Editor, FormatLimits, FormatMode, ResultFormat};
+use crate::editorconfig::EditorConfig;
+use crate::error::SemanticEditError;
+use crate::filesystem::{FileOperations, StdFileOperations};
+use crate::languages::traits::FormatContext;
+use crate::languages::{LanguageName, LanguageRegistry};
+use crate::lsp::LanguageServer;
+use crate::selector::{Operation, Selector};
+use crate::snapshot::FixtureRecorder;
+use crate::tools::helpers::render;
+use crate::validation::{ValidationLevel, Violation};
+
+/// Semantic code editing from the command line
+#[derive(Debug, Parser)]
+#[command(name = "semantic-edit", version)]
+pub struct Cli {
+    /// Print JSON instead of human-readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Apply an edit to a file, writing it only if the edit is valid
+    Apply(EditArgs),
+    /// Show the diff an edit would make, without writing anything
+    Preview(EditArgs),
+    /// List where an anchor or tree-sitter query matches in files
+    Search(SearchArgs),
+    /// Format files with their language's formatter
+    Format(FormatArgs),
+    /// Check files for syntax errors and validation rule violations
+    Validate(ValidateArgs),
+    /// Serve the Language Server Protocol over standard input and output
+    Lsp,
+    /// Run MCP tool calls on a file and save them as a snapshot test fixture
+    Record(RecordArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct EditArgs {
+    /// File to edit
+    pub file: PathBuf,
+    /// insert_before, insert_after, insert_after_node, replace_range, replace_exact,
+    /// replace_node, rewrite_pattern, add_attribute, or add_doc_comment
+    #[arg(long, value_parser = parse_name::<Operation>)]
+    pub operation: Operation,
+    /// Text that locates the edit
+    #[arg(long)]
+    pub anchor: String,
+    /// Text that ends the range for replace_range
+    #[arg(long)]
+    pub end: Option<String>,
+    /// Stop the replace_range right before the end text, keeping it
+    #[arg(long, requires = "end")]
+    pub end_exclusive: bool,
+    /// Replace the doc comments and attributes above the node too, for replace_node
+    #[arg(long)]
+    pub include_leading_trivia: bool,
+    /// Content to insert or replace with. Read from standard input unless this or
+    /// --content-file is given
+    #[arg(long, conflicts_with = "content_file")]
+    pub content: Option<String>,
+    /// File to read the content from
+    #[arg(long)]
+    pub content_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub language: LanguageArg,
+    /// always, never, or edited_region
+    #[arg(long, value_parser = parse_name::<FormatMode>)]
+    pub format_mode: Option<FormatMode>,
+    /// strict, warn, or off
+    #[arg(long, value_parser = parse_name::<ValidationLevel>)]
+    pub validation: Option<ValidationLevel>,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Files to search
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    /// Text to find, reporting the syntax node replace_node would select at each match
+    #[arg(long, required_unless_present = "query", conflicts_with = "query")]
+    pub anchor: Option<String>,
+    /// Tree-sitter query whose captures are reported
+    #[arg(long)]
+    pub query: Option<String>,
+    #[command(flatten)]
+    pub language: LanguageArg,
+}
+
+#[derive(Debug, Args)]
+pub struct FormatArgs {
+    /// Files to format
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    /// Report files that are not formatted instead of writing them
+    #[arg(long)]
+    pub check: bool,
+    #[command(flatten)]
+    pub language: LanguageArg,
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// Files to validate
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    #[command(flatten)]
+    pub language: LanguageArg,
+}
+
+#[derive(Debug, Args)]
+pub struct RecordArgs {
+    /// Fixture directory to create, such as tests/snapshots/rust/insert_after
+    pub fixture: PathBuf,
+    /// File the tool calls edit, copied into the fixture as its input
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+    /// JSON file with the tool calls, an array of `{"name", "arguments"}` objects. Read from
+    /// standard input unless given
+    #[arg(long)]
+    pub calls: Option<PathBuf>,
+    /// Replace the fixture if the directory already has one
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct LanguageArg {
+    /// Language to use instead of detecting it from the file extension
+    #[arg(long = "language", value_parser = parse_language)]
+    pub name: Option<LanguageName>,
+}
+
+/// The outcome of formatting one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormatResult {
+    pub path: PathBuf,
+    /// Whether formatting changes the file
+    pub changed: bool,
+    pub written: bool,
+    /// Why the file was left as is, such as a missing formatter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// The outcome of validating one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidateResult {
+    pub path: PathBuf,
+    pub language: LanguageName,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+}
+
+impl Cli {
+    /// Run the command, writing its results to `stdout` and reading edit content from
+    /// `stdin` if it was not given otherwise
+    ///
+    /// Returns whether the command succeeded, as described in the [module docs](self).
+    pub fn run(self, stdin: &mut dyn Read, stdout: &mut dyn Write) -> Result<bool> {
+        let registry = LanguageRegistry::new()?;
+        let format = if self.json {
+            ResultFormat::Json
+        } else {
+            ResultFormat::Text
+        };
+        match self.command {
+            Command::Apply(args) => apply(&registry, args, true, format, stdin, stdout),
+            Command::Preview(args) => apply(&registry, args, false, format, stdin, stdout),
+            Command::Search(args) => search(&registry, args, format, stdout),
+            Command::Format(args) => format_files(&registry, args, format, stdout),
+            Command::Validate(args) => validate(&registry, args, format, stdout),
+            Command::Lsp => LanguageServer::new()?.serve(&mut BufReader::new(stdin), stdout),
+            Command::Record(args) => record(args, format, stdin, stdout),
+        }
+    }
+}
+
+fn apply(
+    registry: &LanguageRegistry,
+    args: EditArgs,
+    write: bool,
+    format: ResultFormat,
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> Result<bool> {
+    let EditArgs {
+        file,
+        operation,
+        anchor,
+        end,
+        end_exclusive,
+        include_leading_trivia,
+        content,
+        content_file,
+        language,
+        format_mode,
+        validation,
+    } = args;
+    let content = match (content, content_file) {
+        (Some(content), _) => content,
+        (None, Some(content_file)) => std::fs::read_to_string(content_file)?,
+        (None, None) => {
+            let mut content = String::new();
+            stdin.read_to_string(&mut content)?;
+            content
+        }
+    };
+    let file = std::path::absolute(file)?;
+    let language = registry.get_language_with_hint(&file, language.name)?;
+    let editor = Editor::open(
+        content,
+        Selector {
+            operation,
+            anchor,
+            end,
+            end_inclusive: !end_exclusive,
+            include_leading_trivia,
+        },
+        language,
+        file,
+        None,
+        &StdFileOperations,
+    )?
+    .with_format_mode(format_mode.unwrap_or_default())
+    .with_validation_level(validation.unwrap_or_default());
+
+    if !write {
+        let (report, staged) = editor.preview_report()?;
+        writeln!(stdout, "{}", render(&report, format)?)?;
+        return Ok(staged.is_some());
+    }
+
+    let encoding = editor.encoding();
+    let write_check = editor.write_check();
+    let (mut report, output) = editor.commit_report()?;
+    if let Some(output) = output {
+        let path = report.edit.file_path.clone();
+        StdFileOperations.write_encoded(path.clone(), output.clone(), encoding)?;
+        report.verification = write_check.verify(&output, StdFileOperations.read_encoded(&path));
+        report.applied = true;
+    }
+    writeln!(stdout, "{}", render(&report, format)?)?;
+    Ok(report.applied)
+}
+
+fn record(
+    args: RecordArgs,
+    format: ResultFormat,
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> Result<bool> {
+    let calls = match args.calls {
+        Some(calls) => std::fs::read_to_string(calls)?,
+        None => {
+            let mut calls = String::new();
+            stdin.read_to_string(&mut calls)?;
+            calls
+        }
+    };
+    let mut recorder = FixtureRecorder::new(args.fixture).with_overwrite(args.force);
+    if let Some(input) = args.input {
+        recorder = recorder.with_input(input);
+    }
+    let recorded = recorder.record(serde_json::from_str(&calls)?)?;
+    writeln!(stdout, "{}", render(&recorded, format)?)?;
+    Ok(true)
+}
+
+fn search(
+    registry: &LanguageRegistry,
+    args: SearchArgs,
+    format: ResultFormat,
+    stdout: &mut dyn Write,
+) -> Result<bool> {
+    let mut matches = Vec::new();
+    for path in &args.files {
+        let (language, source, tree) = parse_file(registry, path, args.language.name)?;
+        match (&args.anchor, &args.query) {
+            (Some(anchor), _) => matches.extend(anchor_matches(path, &source, &tree, anchor)),
+            (None, Some(query)) => {
+                let query = compile_query(language, query)?;
+                matches.extend(query_matches(path, &source, &tree, &query));
+            }
+            (None, None) => return Err(anyhow!("either --anchor or --query is required")),
+        }
+    }
+
+    match format {
+        ResultFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&matches)?)?,
+        ResultFormat::Text => {
+            for found in &matches {
+                write!(
+                    stdout,
+                    "{}:{}:{}:",
+                    found.path.display(),
+                    found.line,
+                    found.column
+                )?;
+                if let Some(capture) = &found.capture {
+                    write!(stdout, " @{capture}")?;
+                }
+                if let (Some(kind), Some((start, end))) = (&found.node_kind, found.node_lines) {
+                    write!(stdout, " {kind} (lines {start}-{end})")?;
+                }
+                writeln!(stdout, " {}", found.text)?;
+            }
+        }
+    }
+    Ok(!matches.is_empty())
+}
+
+fn format_files(
+    registry: &LanguageRegistry,
+    args: FormatArgs,
+    format: ResultFormat,
+    stdout: &mut dyn Write,
+) -> Result<bool> {
+    let mut results = Vec::new();
+    for path in &args.files {
+        let path = std::path::absolute(path)?;
+        let language = registry.get_language_with_hint(&path, args.language.name)?;
+        let (encoding, source) = StdFileOperations.read_encoded(&path)?;
+        let editorconfig = EditorConfig::load(&StdFileOperations, &path);
+        let context = FormatContext {
+            indentation: editorconfig.indentation(),
+            file_path: Some(&path),
+            limits: FormatLimits::default(),
+            progress: None,
+        };
+        let formatted = match language.editor().format_code(&source, &context) {
+            Ok(formatted) => editorconfig.normalize(formatted),
+            Err(error) => match error.downcast_ref::<SemanticEditError>() {
+                Some(skipped) if skipped.skips_formatting() => {
+                    results.push(FormatResult {
+                        path,
+                        changed: false,
+                        written: false,
+                        skipped: Some(skipped.to_string()),
+                    });
+                    continue;
+                }
+                _ => return Err(error),
+            },
+        };
+        let changed = formatted != source;
+        let written = changed && !args.check;
+        if written {
+            StdFileOperations.write_encoded(path.clone(), formatted, encoding)?;
+        }
+        results.push(FormatResult {
+            path,
+            changed,
+            written,
+            skipped: None,
+        });
+    }
+
+    match format {
+        ResultFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&results)?)?,
+        ResultFormat::Text => {
+            for result in &results {
+                let path = result.path.display();
+                match (&result.skipped, result.changed, result.written) {
+                    (Some(reason), _, _) => writeln!(stdout, "skipped {path}: {reason}")?,
+                    (None, true, true) => writeln!(stdout, "formatted {path}")?,
+                    (None, true, false) => writeln!(stdout, "would format {path}")?,
+                    (None, false, _) => {}
+                }
+            }
+        }
+    }
+    Ok(!args.check || results.iter().all(|result| !result.changed))
+}
+
+fn validate(
+    registry: &LanguageRegistry,
+    args: ValidateArgs,
+    format: ResultFormat,
+    stdout: &mut dyn Write,
+) -> Result<bool> {
+    let mut results = Vec::new();
+    for path in &args.files {
+        let (language, source, tree) = parse_file(registry, path, args.language.name)?;
+        let failure = Editor::validate_source(language, &tree, &source);
+        results.push(ValidateResult {
+            path: path.clone(),
+            language: language.name(),
+            valid: failure.is_none(),
+            message: failure.as_ref().map(|failure| failure.message.clone()),
+            violations: failure
+                .map(|failure| failure.violations)
+                .unwrap_or_default(),
+        });
+    }
+
+    match format {
+        ResultFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&results)?)?,
+        ResultFormat::Text => {
+            for result in &results {
+                match &result.message {
+                    None => writeln!(stdout, "{}: ok", result.path.display())?,
+                    Some(message) => writeln!(stdout, "{}:\n{message}", result.path.display())?,
+                }
+            }
+        }
+    }
+    Ok(results.iter().all(|result| result.valid))
+}
+
+/// Parse a snake_case name the way the MCP tools deserialize it
+fn parse_name<T: DeserializeOwned>(name: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|error| error.to_string())
+}
+
+fn parse_language(name: &str) -> Result<LanguageName, String> {
+    match parse_name(name)? {
+        // unknown names deserialize to plain text, which has to be asked for by name
+        LanguageName::Other if name != "other" => Err(format!("unknown language `{name}`")),
+        language => Ok(language),
+    }
+}
@@ -0,0 +1,497 @@
+//! # Codemods
+//!
+//! Rules in the YAML format of [ast-grep](https://ast-grep.github.io), each rewriting every
+//! match of a code pattern:
+//!
+//! ```yaml
+//! id: unwrap-to-expect
+//! language: rust
+//! message: Prefer expect, which says what went wrong
+//! rule:
+//!   pattern: $VALUE.unwrap()
+//! fix: $VALUE.expect("TODO")
+//! ```
+//!
+//! A pattern is code in the rule's language. `$NAME` matches any one node, `$$$NAME` matches
+//! any number of nodes, such as a list of arguments or statements, and a metavariable used
+//! twice must match the same code both times. `$_` and `$$$` match without capturing. The
+//! fix is the replacement, with the captured code in place of its metavariables. Rules
+//! without a fix only report their matches.
+//!
+//! Only `pattern` rules are supported: the relational and composite rules of ast-grep
+//! (`inside`, `has`, `all`, `any`...), `constraints`, and `transform` are rejected rather
+//! than ignored, so a rule never rewrites more than it says.
+//!
+//! A file may hold several rules separated by `---`. Rules are read from a file or from
+//! every `.yml` and `.yaml` file in a directory, by default [`RULE_DIRECTORY`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow, bail};
+use serde::{Deserialize, Deserializer};
+use tree_sitter::{Node, Tree};
+
+use crate::languages::{LanguageCommon, LanguageName};
+
+/// Directory, relative to the session context, that rules are read from by default
+pub const RULE_DIRECTORY: &str = ".semantic-edit/rules";
+
+/// Identifiers standing in for metavariables while a pattern is parsed, chosen to be valid
+/// identifiers in every supported language
+const SINGLE_PLACEHOLDER: &str = "__sg_single_";
+const MULTI_PLACEHOLDER: &str = "__sg_multi_";
+
+/// A rewrite rule in the ast-grep format
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodemodRule {
+    pub id: String,
+    #[serde(deserialize_with = "deserialize_language")]
+    pub language: LanguageName,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Accepted for compatibility with ast-grep rule files, and otherwise unused
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// Accepted for compatibility with ast-grep rule files, and otherwise unused
+    #[serde(default)]
+    pub note: Option<String>,
+    pub rule: PatternRule,
+    /// Replacement for each match, or None to only report matches
+    #[serde(default)]
+    pub fix: Option<String>,
+}
+
+/// The part of an ast-grep rule that finds matches, of which only `pattern` is supported
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternRule {
+    pub pattern: String,
+}
+
+/// A match of a rule in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule_id: String,
+    /// 1-based line the match starts on, in the file as it was before the rule ran
+    pub line: usize,
+    /// Whether the match was rewritten, which it is unless the rule has no fix
+    pub fixed: bool,
+}
+
+/// A file rewritten by the rules
+#[derive(Debug, Clone)]
+pub struct Rewrite {
+    pub output: String,
+    pub matches: Vec<RuleMatch>,
+    /// Syntax errors in the output that were not in the source
+    pub new_errors: usize,
+}
+
+impl CodemodRule {
+    /// Load every rule in a rule file, or in the `.yml` and `.yaml` files of a directory
+    pub fn load(path: &Path) -> Result<Vec<Self>> {
+        let files = if path.is_dir() {
+            let mut files = std::fs::read_dir(path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<PathBuf>>>()?;
+            files.retain(|file| {
+                file.extension()
+                    .is_some_and(|extension| extension == "yml" || extension == "yaml")
+            });
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut rules = Vec::new();
+        for file in files {
+            let source = std::fs::read_to_string(&file).map_err(|error| {
+                anyhow!("could not read rules from {}: {error}", file.display())
+            })?;
+            for document in serde_yaml::Deserializer::from_str(&source) {
+                let rule = Self::deserialize(document).map_err(|error| {
+                    anyhow!(
+                        "invalid rule in {}: {error}. Rules need an `id`, a `language`, and a \
+                        `rule` with a `pattern`",
+                        file.display()
+                    )
+                })?;
+                rule.check()
+                    .map_err(|error| anyhow!("invalid rule in {}: {error}", file.display()))?;
+                rules.push(rule);
+            }
+        }
+        if rules.is_empty() {
+            bail!("no rules found in {}", path.display());
+        }
+        Ok(rules)
+    }
+
+    /// Refuse rules that cannot work: unsupported languages, and fixes using metavariables
+    /// the pattern does not capture
+    fn check(&self) -> Result<()> {
+        let id = &self.id;
+        if self.language == LanguageName::Other {
+            bail!("rule `{id}` is for a language that is not supported");
+        }
+        let mut captured = Vec::new();
+        substitute(&self.rule.pattern, |_, name| {
+            captured.push(name.to_string());
+            String::new()
+        });
+        if let Some(fix) = &self.fix {
+            let mut missing = Vec::new();
+            substitute(fix, |sigil, name| {
+                if !captured.iter().any(|captured| captured == name) {
+                    missing.push(format!("{sigil}{name}"));
+                }
+                String::new()
+            });
+            if !missing.is_empty() {
+                bail!(
+                    "the fix of rule `{id}` uses {}, which its pattern does not capture",
+                    missing.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrite a source file with the rules for its language, applied one after another, or
+    /// None if none of them match
+    pub fn rewrite_all(
+        rules: &[Self],
+        source: &str,
+        language: &LanguageCommon,
+    ) -> Result<Option<Rewrite>> {
+        let mut output = source.to_string();
+        let mut matches = Vec::new();
+        for rule in rules.iter().filter(|rule| rule.language == language.name()) {
+            let (rewritten, rule_matches) = rule.rewrite(&output, language)?;
+            output = rewritten;
+            matches.extend(rule_matches);
+        }
+        if matches.is_empty() {
+            return Ok(None);
+        }
+
+        let new_errors =
+            syntax_errors(&output, language)?.saturating_sub(syntax_errors(source, language)?);
+        Ok(Some(Rewrite {
+            output,
+            matches,
+            new_errors,
+        }))
+    }
+
+    /// Rewrite every outermost match of the rule in the source
+    fn rewrite(&self, source: &str, language: &LanguageCommon) -> Result<(String, Vec<RuleMatch>)> {
+        let pattern = Pattern::parse(&self.rule.pattern, language)
+            .map_err(|error| anyhow!("rule `{}`: {error}", self.id))?;
+        let tree = parse(source, language)?;
+
+        let mut found = Vec::new();
+        find_matches(
+            pattern.root(),
+            &pattern.source,
+            tree.root_node(),
+            source,
+            &mut found,
+        );
+
+        let mut output = String::with_capacity(source.len());
+        let mut end = 0;
+        let mut matches = Vec::new();
+        for (node, bindings) in found {
+            matches.push(RuleMatch {
+                rule_id: self.id.clone(),
+                line: node.start_position().row + 1,
+                fixed: self.fix.is_some(),
+            });
+            if let Some(fix) = &self.fix {
+                output.push_str(&source[end..node.start_byte()]);
+                output.push_str(&fill_fix(fix, &bindings, indentation(source, node)));
+                end = node.end_byte();
+            }
+        }
+        output.push_str(&source[end..]);
+        Ok((output, matches))
+    }
+}
+
+/// A pattern parsed in a rule's language, with its metavariables as placeholder identifiers
+struct Pattern {
+    source: String,
+    tree: Tree,
+}
+
+impl Pattern {
+    fn parse(pattern: &str, language: &LanguageCommon) -> Result<Self> {
+        let source = substitute(pattern.trim(), |sigil, name| {
+            let placeholder = if sigil == "$$$" {
+                MULTI_PLACEHOLDER
+            } else {
+                SINGLE_PLACEHOLDER
+            };
+            format!("{placeholder}{name}")
+        });
+        let tree = parse(&source, language)?;
+        let pattern = Self { source, tree };
+        if pattern.root().has_error() {
+            bail!(
+                "the pattern `{}` is not valid {}",
+                pattern.source,
+                language.name()
+            );
+        }
+        Ok(pattern)
+    }
+
+    /// The node the pattern stands for: the innermost node spanning all of it, below the
+    /// nodes a grammar wraps a snippet in, like a source file or an expression statement
+    fn root(&self) -> Node<'_> {
+        let whole = self.source.trim_end_matches(';');
+        let mut node = self.tree.root_node();
+        loop {
+            let mut cursor = node.walk();
+            let children = node
+                .named_children(&mut cursor)
+                .filter(|child| !child.is_extra())
+                .collect::<Vec<_>>();
+            match children.as_slice() {
+                [child] if text(*child, &self.source).trim_end_matches(';') == whole => {
+                    node = *child;
+                }
+                _ => return node,
+            }
+        }
+    }
+}
+
+/// Metavariable captures: the code each named metavariable matched
+type Bindings = HashMap<String, String>;
+
+/// A metavariable in a parsed pattern
+enum Metavariable<'a> {
+    Single(&'a str),
+    Multi(&'a str),
+}
+
+/// The metavariable a pattern node stands for, if it is one
+fn metavariable<'a>(node: Node<'_>, pattern: &'a str) -> Option<Metavariable<'a>> {
+    let text = text(node, pattern);
+    if !text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    if let Some(name) = text.strip_prefix(MULTI_PLACEHOLDER) {
+        Some(Metavariable::Multi(name))
+    } else {
+        text.strip_prefix(SINGLE_PLACEHOLDER)
+            .map(Metavariable::Single)
+    }
+}
+
+/// Collect the outermost nodes matching the pattern, in source order
+fn find_matches<'tree>(
+    pattern: Node<'_>,
+    pattern_source: &str,
+    node: Node<'tree>,
+    source: &str,
+    found: &mut Vec<(Node<'tree>, Bindings)>,
+) {
+    let mut bindings = Bindings::new();
+    if match_node(pattern, pattern_source, node, source, &mut bindings) {
+        found.push((node, bindings));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        find_matches(pattern, pattern_source, child, source, found);
+    }
+}
+
+fn match_node(
+    pattern: Node<'_>,
+    pattern_source: &str,
+    node: Node<'_>,
+    source: &str,
+    bindings: &mut Bindings,
+) -> bool {
+    match metavariable(pattern, pattern_source) {
+        Some(Metavariable::Single(name)) => return bind(bindings, name, text(node, source)),
+        // outside of a list of nodes, a multiple metavariable matches its one node
+        Some(Metavariable::Multi(name)) => return bind(bindings, name, text(node, source)),
+        None => {}
+    }
+    if pattern.kind_id() != node.kind_id() || node.is_extra() {
+        return false;
+    }
+    let pattern_children = children(pattern);
+    let children = children(node);
+    if pattern_children.is_empty() {
+        return children.is_empty() && text(pattern, pattern_source) == text(node, source);
+    }
+    match_children(
+        &pattern_children,
+        pattern_source,
+        &children,
+        source,
+        bindings,
+    )
+}
+
+fn match_children(
+    patterns: &[Node<'_>],
+    pattern_source: &str,
+    nodes: &[Node<'_>],
+    source: &str,
+    bindings: &mut Bindings,
+) -> bool {
+    let Some((pattern, rest)) = patterns.split_first() else {
+        return nodes.is_empty();
+    };
+
+    if let Some(Metavariable::Multi(name)) = metavariable(*pattern, pattern_source) {
+        // try each number of nodes the metavariable could stand for, fewest first
+        for count in 0..=nodes.len() {
+            let mut attempt = bindings.clone();
+            let captured = match (nodes.first(), count.checked_sub(1)) {
+                (Some(first), Some(last)) => &source[first.start_byte()..nodes[last].end_byte()],
+                _ => "",
+            };
+            if bind(&mut attempt, name, captured)
+                && match_children(rest, pattern_source, &nodes[count..], source, &mut attempt)
+            {
+                *bindings = attempt;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    let Some((node, remaining)) = nodes.split_first() else {
+        return false;
+    };
+    let mut attempt = bindings.clone();
+    if match_node(*pattern, pattern_source, *node, source, &mut attempt)
+        && match_children(rest, pattern_source, remaining, source, &mut attempt)
+    {
+        *bindings = attempt;
+        return true;
+    }
+    false
+}
+
+/// Capture the code a metavariable matched, or check that it matches its earlier capture
+fn bind(bindings: &mut Bindings, name: &str, captured: &str) -> bool {
+    // `$_`, `$$$`, and other names starting with an underscore match without capturing
+    if name.is_empty() || name.starts_with('_') {
+        return true;
+    }
+    match bindings.get(name) {
+        Some(earlier) => earlier == captured,
+        None => {
+            bindings.insert(name.to_string(), captured.to_string());
+            true
+        }
+    }
+}
+
+/// The children of a node that take part in matching: comments and the empty nodes that
+/// error recovery inserts are left out
+fn children(node: Node<'_>) -> Vec<Node<'_>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+/// The fix for a match, with captured code in place of its metavariables and its lines
+/// after the first indented like the line the match starts on
+fn fill_fix(fix: &str, bindings: &Bindings, indentation: &str) -> String {
+    let fix = fix
+        .trim_end_matches('\n')
+        .replace('\n', &format!("\n{indentation}"));
+    substitute(&fix, |_, name| {
+        bindings.get(name).cloned().unwrap_or_default()
+    })
+}
+
+/// The whitespace at the start of the line a node starts on
+fn indentation<'a>(source: &'a str, node: Node<'_>) -> &'a str {
+    let line_start = source[..node.start_byte()]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line = &source[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Replace each metavariable, `$NAME` or `$$$NAME`, with what `replace` returns given its
+/// sigil and name. A `$` not followed by a metavariable name is kept as it is.
+fn substitute(text: &str, mut replace: impl FnMut(&str, &str) -> String) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('$') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let sigil = if rest.starts_with("$$$") { "$$$" } else { "$" };
+        let after = &rest[sigil.len()..];
+        let name_length = after
+            .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_length];
+        if sigil == "$$$" || !name.is_empty() {
+            output.push_str(&replace(sigil, name));
+            rest = &after[name_length..];
+        } else {
+            output.push('$');
+            rest = &rest[1..];
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn parse(source: &str, language: &LanguageCommon) -> Result<Tree> {
+    language
+        .parser()?
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("could not parse as {}", language.name()))
+}
+
+fn syntax_errors(source: &str, language: &LanguageCommon) -> Result<usize> {
+    let tree = parse(source, language)?;
+    Ok(language.editor().collect_errors(&tree, source).len())
+}
+
+fn text<'a>(node: Node<'_>, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+/// Languages by the names ast-grep rules use for them, in any case
+fn deserialize_language<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<LanguageName, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Ok(match name.to_lowercase().as_str() {
+        "rust" | "rs" => LanguageName::Rust,
+        "javascript" | "js" | "jsx" => LanguageName::Javascript,
+        "typescript" | "ts" => LanguageName::Typescript,
+        "tsx" => LanguageName::Tsx,
+        "python" | "py" => LanguageName::Python,
+        "go" | "golang" => LanguageName::Go,
+        "cpp" | "c++" | "cc" | "cxx" => LanguageName::Cpp,
+        "c" => LanguageName::C,
+        "java" => LanguageName::Java,
+        "php" => LanguageName::Php,
+        "csharp" | "c#" | "cs" => LanguageName::CSharp,
+        "ruby" | "rb" => LanguageName::Ruby,
+        "json" => LanguageName::Json,
+        "jsonc" => LanguageName::Jsonc,
+        "toml" => LanguageName::Toml,
+        _ => LanguageName::Other,
+    })
+}
@@ -0,0 +1,346 @@
+//! # Comby templates
+//!
+//! Match and rewrite templates in the style of [comby](https://comby.dev), used by the
+//! `rewrite_pattern` operation. Templates match text with balanced delimiters rather than
+//! syntax trees, so they work the same in every language, including plain text and files
+//! too large to parse.
+//!
+//! A template is literal text with holes:
+//! - `:[name]` matches any text, as little as possible, in which `()`, `[]`, and `{}` are
+//!   balanced and string literals are whole. At the start of a template it stays on the
+//!   line the match starts on, and at the end of a template it matches to the end of the
+//!   line, except inside delimiters
+//! - `:[[name]]` matches a word: letters, digits, and `_`
+//! - `:[name.]` matches text without whitespace, with balanced delimiters
+//! - `:[name\n]` matches the rest of a line, including its newline
+//! - `:[ name]` matches whitespace
+//!
+//! A hole used twice must match the same text both times, and `:[_]` matches without
+//! capturing. Whitespace in a template matches any amount of whitespace, including none.
+//! The rewrite template puts the text each hole matched in place of the same holes.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// A parsed match or rewrite template
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+    tokens: Vec<Token>,
+}
+
+/// A match of a template in a source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateMatch {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// The text each named hole matched
+    pub holes: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Literal(String),
+    Whitespace(String),
+    Hole { name: String, kind: HoleKind },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HoleKind {
+    Any,
+    Word,
+    NonSpace,
+    Line,
+    Space,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut rest = template;
+        while let Some(c) = rest.chars().next() {
+            if let Some((hole, after)) = parse_hole(rest) {
+                tokens.push(hole);
+                rest = after;
+            } else if c.is_whitespace() {
+                let end = rest
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Whitespace(rest[..end].to_string()));
+                rest = &rest[end..];
+            } else {
+                match tokens.last_mut() {
+                    Some(Token::Literal(literal)) => literal.push(c),
+                    _ => tokens.push(Token::Literal(c.to_string())),
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        Self {
+            source: template.to_string(),
+            tokens,
+        }
+    }
+
+    /// Names of the holes that capture text, in order of first use
+    pub fn hole_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for token in &self.tokens {
+            if let Token::Hole { name, .. } = token {
+                if name != "_" && !names.contains(&name.as_str()) {
+                    names.push(name.as_str());
+                }
+            }
+        }
+        names
+    }
+
+    /// Whether the template has literal text to anchor its matches, which a template of only
+    /// holes and whitespace does not
+    pub fn has_literal(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Literal(_)))
+    }
+
+    /// Every match of the template in the source, leftmost first, without overlaps
+    pub fn matches(&self, source: &str) -> Vec<TemplateMatch> {
+        // whitespace around a template only makes matches start or end on whitespace
+        let tokens = trim_whitespace(&self.tokens);
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start < source.len() {
+            let mut holes = HashMap::new();
+            match match_tokens(tokens, source, start, &mut holes, true) {
+                Some(end) if end > start => {
+                    matches.push(TemplateMatch {
+                        start_byte: start,
+                        end_byte: end,
+                        holes,
+                    });
+                    start = end;
+                }
+                _ => start += source[start..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        matches
+    }
+
+    /// The template with each hole replaced by the text it matched elsewhere
+    pub fn fill(&self, holes: &HashMap<String, String>) -> String {
+        self.tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(text) | Token::Whitespace(text) => text.as_str(),
+                Token::Hole { name, .. } => holes.get(name).map_or("", String::as_str),
+            })
+            .collect()
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// A hole at the start of `text`, and the text after it
+fn parse_hole(text: &str) -> Option<(Token, &str)> {
+    let rest = text.strip_prefix(":[")?;
+    let (inner, after, kind) = if let Some(word) = rest.strip_prefix('[') {
+        let end = word.find("]]")?;
+        (&word[..end], &word[end + 2..], HoleKind::Word)
+    } else {
+        let end = rest.find(']')?;
+        let inner = &rest[..end];
+        let after = &rest[end + 1..];
+        if let Some(name) = inner.strip_suffix('.') {
+            (name, after, HoleKind::NonSpace)
+        } else if let Some(name) = inner.strip_suffix("\\n") {
+            (name, after, HoleKind::Line)
+        } else if let Some(name) = inner.strip_prefix(' ') {
+            (name, after, HoleKind::Space)
+        } else {
+            (inner, after, HoleKind::Any)
+        }
+    };
+    let valid = !inner.is_empty() && inner.chars().all(|c| c.is_alphanumeric() || c == '_');
+    valid.then(|| {
+        (
+            Token::Hole {
+                name: inner.to_string(),
+                kind,
+            },
+            after,
+        )
+    })
+}
+
+fn trim_whitespace(tokens: &[Token]) -> &[Token] {
+    let is_whitespace = |token: &Token| matches!(token, Token::Whitespace(_));
+    let start = tokens
+        .iter()
+        .position(|token| !is_whitespace(token))
+        .unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(|token| !is_whitespace(token))
+        .map_or(start, |index| index + 1);
+    &tokens[start..end]
+}
+
+/// Match tokens at a position, returning where the match ends. `first` is whether the
+/// tokens start the template.
+fn match_tokens(
+    tokens: &[Token],
+    source: &str,
+    position: usize,
+    holes: &mut HashMap<String, String>,
+    first: bool,
+) -> Option<usize> {
+    let Some((token, rest)) = tokens.split_first() else {
+        return Some(position);
+    };
+    let remaining = &source[position..];
+    match token {
+        Token::Literal(text) => {
+            remaining.starts_with(text.as_str()).then_some(())?;
+            match_tokens(rest, source, position + text.len(), holes, false)
+        }
+        Token::Whitespace(_) => {
+            let length = remaining.len() - remaining.trim_start().len();
+            match_tokens(rest, source, position + length, holes, false)
+        }
+        Token::Hole { name, kind } => {
+            let mut try_end = |end: usize| {
+                let mut attempt = holes.clone();
+                if !bind(&mut attempt, name, &source[position..end]) {
+                    return None;
+                }
+                let matched = match_tokens(rest, source, end, &mut attempt, false)?;
+                *holes = attempt;
+                Some(matched)
+            };
+            match kind {
+                HoleKind::Word => {
+                    let length = remaining
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(remaining.len());
+                    (1..=length)
+                        .rev()
+                        .find_map(|length| try_end(position + length))
+                }
+                HoleKind::Space => {
+                    let length = remaining.len() - remaining.trim_start().len();
+                    try_end(position + length)
+                }
+                HoleKind::Line => {
+                    let length = remaining
+                        .find('\n')
+                        .map_or(remaining.len(), |index| index + 1);
+                    try_end(position + length)
+                }
+                HoleKind::Any | HoleKind::NonSpace => {
+                    let scope = HoleScope {
+                        stop_at_whitespace: *kind == HoleKind::NonSpace,
+                        first,
+                        last: rest.is_empty(),
+                    };
+                    balanced_ends(source, position, scope, try_end)
+                }
+            }
+        }
+    }
+}
+
+/// Capture a hole's text, or check that it matches the hole's earlier capture
+fn bind(holes: &mut HashMap<String, String>, name: &str, text: &str) -> bool {
+    if name == "_" {
+        return true;
+    }
+    match holes.get(name) {
+        Some(earlier) => earlier == text,
+        None => {
+            holes.insert(name.to_string(), text.to_string());
+            true
+        }
+    }
+}
+
+/// Where a hole may end
+#[derive(Debug, Clone, Copy)]
+struct HoleScope {
+    /// The hole matches no whitespace outside delimiters
+    stop_at_whitespace: bool,
+    /// The hole starts the match, so it stays on its line outside delimiters
+    first: bool,
+    /// The hole ends the match, so it extends to the end of its line outside delimiters
+    last: bool,
+}
+
+/// Try each position a hole starting at `start` could end at, nearest first, returning the
+/// first that `try_end` accepts: every position outside the delimiters and string literals
+/// opened within the hole, up to a closing delimiter the hole did not open
+fn balanced_ends(
+    source: &str,
+    start: usize,
+    scope: HoleScope,
+    mut try_end: impl FnMut(usize) -> Option<usize>,
+) -> Option<usize> {
+    let mut open = Vec::new();
+    let mut position = start;
+    loop {
+        let next = source[position..].chars().next();
+        let outside = open.is_empty();
+        let at_line_end = matches!(next, None | Some('\n' | ')' | ']' | '}'));
+        let at_whitespace = next.is_some_and(char::is_whitespace);
+        if outside && (!scope.last || at_line_end || (scope.stop_at_whitespace && at_whitespace)) {
+            if let Some(end) = try_end(position) {
+                return Some(end);
+            }
+        }
+        let c = next?;
+        if outside && ((scope.stop_at_whitespace && at_whitespace) || (scope.first && c == '\n')) {
+            return None;
+        }
+        match c {
+            '(' => open.push(')'),
+            '[' => open.push(']'),
+            '{' => open.push('}'),
+            ')' | ']' | '}' => {
+                if open.last() != Some(&c) {
+                    return None;
+                }
+                open.pop();
+            }
+            '"' | '`' | '\'' => {
+                if let Some(length) = string_length(&source[position..], c) {
+                    position += length;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        position += c.len_utf8();
+    }
+}
+
+/// The length of the string literal at the start of `text`, opened by `quote`, if it is
+/// one. Single quotes only count as a character literal closed on the same line within a
+/// few characters, so that Rust lifetimes and apostrophes in comments are not taken for
+/// strings.
+fn string_length(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        if quote == '\'' && (index > 8 || c == '\n') {
+            return None;
+        }
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return Some(index + c.len_utf8()),
+            _ => {}
+        }
+    }
+    None
+}
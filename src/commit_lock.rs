@@ -0,0 +1,127 @@
+//! # Commit locks
+//!
+//! Advisory locks that serialize commits to the same file across sessions and server
+//! instances. A commit holds the lock from re-reading the file until its write is done, so
+//! two committers cannot both pass the stale-file check and overwrite each other.
+//!
+//! Each lock file also records who committed to the file last. When a commit is refused
+//! because the file changed since it was staged, this tells whether another session made
+//! the change.
+//!
+//! Commits to several files lock them all with [`CommitLocks`], in the order of their
+//! canonical paths, so sessions committing to the same files cannot deadlock by each holding
+//! a lock the other waits for.
+//!
+//! Locks live next to the session store, so server instances sharing a store also share
+//! locks, or in the system temp directory when sessions are not persisted.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Directory, next to the session store or in the temp directory, that holds lock files
+pub const LOCK_DIRECTORY: &str = "semantic-edit-locks";
+
+/// The most recent commit to a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastCommit {
+    /// Session and server process that committed, e.g. `default (process 1234)`
+    pub committer: String,
+    /// Content hash of the file as written
+    pub content_hash: u64,
+}
+
+/// An exclusive advisory lock on committing to one file, released when dropped
+#[derive(Debug)]
+pub struct CommitLock {
+    file: File,
+}
+
+impl CommitLock {
+    /// Wait until no other session is committing to `path`, then lock it
+    pub fn acquire(lock_directory: &Path, path: &Path) -> Result<Self> {
+        fs::create_dir_all(lock_directory)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path(lock_directory, path))?;
+        file.lock()?;
+        Ok(Self { file })
+    }
+
+    /// The last commit recorded for the file, if any
+    pub fn last_commit(&self) -> Option<LastCommit> {
+        let mut content = String::new();
+        (&self.file).seek(SeekFrom::Start(0)).ok()?;
+        (&self.file).read_to_string(&mut content).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Record a commit to the file
+    pub fn record(&self, last_commit: &LastCommit) -> Result<()> {
+        self.file.set_len(0)?;
+        (&self.file).seek(SeekFrom::Start(0))?;
+        (&self.file).write_all(serde_json::to_string(last_commit)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Commit locks on several files, held together
+#[derive(Debug)]
+pub struct CommitLocks {
+    /// Each lock, by canonical path
+    locks: BTreeMap<PathBuf, CommitLock>,
+}
+
+impl CommitLocks {
+    /// Lock every path, waiting for each in the order of their canonical paths, so another
+    /// session locking some of the same files in a different order cannot deadlock with this
+    /// one. Paths to the same file share a lock.
+    pub fn acquire<'path>(
+        lock_directory: &Path,
+        paths: impl IntoIterator<Item = &'path Path>,
+    ) -> Result<Self> {
+        let paths = paths
+            .into_iter()
+            .map(canonical_path)
+            .collect::<BTreeSet<_>>();
+        let mut locks = BTreeMap::new();
+        for path in paths {
+            let lock = CommitLock::acquire(lock_directory, &path)?;
+            locks.insert(path, lock);
+        }
+        Ok(Self { locks })
+    }
+
+    /// The lock on `path`, if it is one of the locked paths
+    pub fn get(&self, path: &Path) -> Option<&CommitLock> {
+        self.locks.get(&canonical_path(path))
+    }
+}
+
+/// The path with symbolic links and relative components resolved, or as it is if it cannot
+/// be resolved
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Lock file for a path, named by a hash that is stable across builds so that different
+/// server versions agree on it
+fn lock_path(lock_directory: &Path, path: &Path) -> PathBuf {
+    let path = canonical_path(path);
+    // FNV-1a
+    let hash = path
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+        });
+    lock_directory.join(format!("{hash:016x}.lock"))
+}
@@ -6,12 +6,19 @@
 //! ## Architecture
 //!
 //! The Editor is decomposed into focused submodules:
+//! - `builder`: Fluent construction of editors
 //! - `validator`: Syntax and context validation
 //! - `formatter`: Language-specific code formatting
+//! - `near_matches`: Suggestions of the lines an anchor that is not found nearly matches
 //! - `diff_generator`: Diff generation and efficiency metrics
 //! - `edit`: Individual edit operations
 //! - `edit_iterator`: Iterator for multiple edit locations
 //! - `edit_position`: Edit position tracking
+//! - `idempotence`: Detection of edits that were already applied
+//! - `parse_limits`: Size and time limits beyond which files are edited as text
+//! - `report`: Structured preview and commit reports
+//! - `separators`: Separator fix-ups for edits to comma- and semicolon-separated lists
+//! - `word_diff`: Word-level intra-line diff highlighting
 //!
 //! ## Features
 //!
@@ -19,6 +26,13 @@
 //! - **Staged Operations**: Support for multi-step workflows
 //! - **Validation**: Two-layer validation prevents file corruption
 //! - **Smart Diffs**: Clean diffs with efficiency metrics
+//! - **EditorConfig**: Indentation, line endings, final newline, and charset follow
+//!   `.editorconfig`
+//! - **Large Files**: Files over the [`ParseLimits`] are edited as text, with a warning
+//! - **Unparseable Files**: Edits refused in files that do not parse as their language at
+//!   all, like a file detected as the wrong language, are applied as text, with a warning
+//! - **Notebooks**: One code cell of a Jupyter notebook is edited at a time, and written back
+//!   into the notebook
 //!
 //! ## Example
 //!
@@ -27,52 +41,209 @@
 //!
 //! let editor = Editor::new(content, selector, language, file_path, None)?;
 //!
+//! // Or read the file through a FileOperations implementation
+//! let editor = Editor::open(content, selector, language, file_path, None, &file_operations)?;
+//!
+//! // Or reuse the content and syntax tree of an unchanged file from a SourceCache
+//! let editor = Editor::open_cached(
+//!     content, selector, language, file_path, None, &file_operations, &source_cache,
+//! )?;
+//!
 //! // Preview changes
 //! let (preview_msg, staged_op) = editor.preview()?;
 //!
 //! // Or commit directly
 //! let (message, output, path) = editor.commit()?;
+//!
+//! // Or edit a code cell of a notebook, chosen by index or by the anchor it contains
+//! let editor = Editor::open_notebook_cell(
+//!     content, selector, &language_registry, None, file_path, None, &file_operations,
+//! )?;
+//!
+//! // Or gather the source and options with a builder
+//! let editor = Editor::builder(content, selector, language)
+//!     .with_file_path(file_path)
+//!     .with_format_mode(FormatMode::Never)
+//!     .build()?;
+//!
+//! // Edit a string without touching the filesystem
+//! let editor = Editor::from_source(source_code, content, selector, language)?;
+//! let new_source = editor.commit_to_string()?;
 //! ```
 
+mod anchor_matches;
+mod builder;
 mod diff_generator;
 mod edit;
 mod edit_iterator;
 mod edit_position;
 mod formatter;
+mod idempotence;
+mod near_matches;
+mod parse_limits;
+mod report;
+mod separators;
 mod validator;
+mod word_diff;
+mod write_check;
 
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Placeholder path reported for editors created from in-memory source
+pub const IN_MEMORY_PATH: &str = "<in-memory>";
 
+/// How many of an ambiguous anchor's candidates [`Editor::alternatives_report`] previews
+const MAX_ALTERNATIVES: usize = 5;
+
+/// Diffs of the alternatives [`Editor::alternatives_report`] previews are capped at this
+/// many lines
+const ALTERNATIVE_DIFF_LINES: usize = 20;
+
+use crate::editorconfig::EditorConfig;
+use crate::encoding::TextEncoding;
 use crate::error::SemanticEditError;
+use crate::filesystem::{FileOperations, StdFileOperations};
+use crate::notebook::{Notebook, NotebookCell};
+use crate::validation::{CustomQueries, RuleSettings, ValidationLevel, Violation};
 use anyhow::{Result, anyhow};
-use diff_generator::DiffGenerator;
+pub use builder::EditorBuilder;
+pub use diff_generator::{Diff, DiffGenerator, DiffOptions};
 use edit::Edit;
 use edit_iterator::EditIterator;
 use formatter::Formatter;
+pub use formatter::{FormatLimits, FormatMode};
+pub use idempotence::already_applied;
+pub use parse_limits::ParseLimits;
 use ropey::Rope;
-use tree_sitter::Tree;
-use validator::Validator;
+use tree_sitter::{Node, ParseOptions, Tree};
+pub use validator::ValidationFailure;
+use validator::{EditedLines, Validator};
+pub use write_check::WriteCheck;
 
-pub use edit_position::EditPosition;
+pub use edit_position::{EditPosition, LineColumn};
+pub use report::{
+    Alternative, AlternativesReport, AnchorAmbiguity, AnchorMatch, CommitReport, DiffHunk,
+    DiffLine, EditEfficiency, EditReport, FailedCandidate, PreviewReport, ResultFormat, TargetNode,
+};
 
 use crate::{
-    languages::{LanguageCommon, LanguageRegistry},
-    selector::Selector,
-    state::StagedOperation,
+    languages::{LanguageCommon, LanguageName, LanguageRegistry, traits::FormatContext},
+    progress::Progress,
+    selector::{Operation, Selector},
+    state::{SourceCache, SourceFingerprint, StagedOperation},
 };
 
+/// The diff between a file and a rewrite of it made outside an edit, like a codemod's
+pub fn file_diff(source_code: &str, output: &str, diff_options: &DiffOptions) -> Diff {
+    DiffGenerator::diff(source_code, output, "", diff_options)
+}
+
+/// Whether a tree is nothing but syntax errors, as when a file is parsed as the wrong
+/// language. Such a tree cannot locate nodes or tell a broken edit from the file's own errors.
+///
+/// A file that stops partway through a construct, like a function missing its closing brace,
+/// is also wrapped in one error, but the parser fails only once there. Wrong-language files
+/// make it fail again inside the error, so at least two errors are needed.
+fn unparseable(tree: &Tree) -> bool {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let all_errors = root.is_error()
+        || root
+            .children(&mut cursor)
+            .all(|child| child.is_error() || child.is_missing());
+    root.has_error() && all_errors && error_count(root, 2) >= 2
+}
+
+/// How many error and missing nodes a tree has, counting no further than `limit`
+fn error_count(node: Node<'_>, limit: usize) -> usize {
+    if !node.has_error() {
+        return 0;
+    }
+    let mut count = usize::from(node.is_error() || node.is_missing());
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if count >= limit {
+            break;
+        }
+        count += error_count(child, limit - count);
+    }
+    count
+}
+
 pub struct Editor<'language> {
     content: String,
     selector: Selector,
     file_path: PathBuf,
     language: &'language LanguageCommon,
     source_code: String,
-    tree: Tree,
+    /// Parsed when the edit runs, unless it came from a cache or the file is edited as text
+    tree: Option<Tree>,
+    /// Why the file is edited as text, if it is over the parse limits or does not parse
+    text_only: Option<String>,
     rope: Rope,
     staged_edit: Option<EditPosition>,
+    encoding: TextEncoding,
+    fingerprint: SourceFingerprint,
+    diff_options: DiffOptions,
+    format_mode: FormatMode,
+    validation_level: ValidationLevel,
+    /// Per-rule overrides for context validation
+    rules: RuleSettings,
+    /// Project-supplied validation queries, applied on top of the built-in ones
+    custom_queries: Option<Arc<CustomQueries>>,
+    /// Conventions from `.editorconfig` files that apply to the file
+    editorconfig: EditorConfig,
+    format_limits: FormatLimits,
+    parse_limits: ParseLimits,
+    /// Progress of the request the edit is made for
+    progress: Progress,
+    /// Where a tree parsed for the edit is kept for later editors of the same file
+    source_cache: Option<&'language SourceCache>,
+    /// The notebook cell being edited, if the file is a Jupyter notebook
+    notebook_cell: Option<NotebookCell>,
+    /// The match of the anchor to edit, by index, if the edit is limited to one
+    candidate: Option<usize>,
+}
+
+/// The result of running the edit pipeline
+#[derive(Debug, Default)]
+struct EditOutcome {
+    message: String,
+    output: Option<String>,
+    target: Option<EditPosition>,
+    /// The node a node operation resolved its anchor to
+    target_node: Option<TargetNode>,
+    /// The string literal or comment the anchor matched inside
+    enclosing_literal: Option<TargetNode>,
+    /// Which candidate was edited and why, if the anchor matched more than once
+    ambiguity: Option<AnchorAmbiguity>,
+    /// Every match of the anchor, if it matched more than once
+    candidates: Vec<AnchorMatch>,
+    /// Validation warning for an edit accepted at the warn level or violating downgraded rules
+    warning: Option<String>,
+    violations: Vec<Violation>,
+    /// Why an insert looks like a duplicate of the text next to it
+    duplicate: Option<String>,
+    /// The lines a `replace_range` replaced, and why it ends where it does
+    span: Option<String>,
+    /// Every location the edit was tried at, if it failed at all of them
+    attempts: Vec<FailedCandidate>,
 }
 
 impl<'language> Editor<'language> {
+    /// Start building an editor for an edit, given the file or source code to edit and any
+    /// options with the builder's methods
+    pub fn builder<'files>(
+        content: impl Into<String>,
+        selector: Selector,
+        language: &'language LanguageCommon,
+    ) -> EditorBuilder<'language, 'files> {
+        EditorBuilder::new(content.into(), selector, language)
+    }
+
     pub fn new(
         content: String,
         selector: Selector,
@@ -80,29 +251,136 @@ impl<'language> Editor<'language> {
         file_path: PathBuf,
         staged_edit: Option<EditPosition>,
     ) -> Result<Self> {
-        let source_code = std::fs::read_to_string(&file_path)?;
-        let mut parser = language.tree_sitter_parser()?;
-        let tree = parser.parse(&source_code, None).ok_or_else(|| {
-            anyhow!(
-                "Unable to parse {} as {}",
-                file_path.display(),
-                language.name()
-            )
-        })?;
+        Self::open(
+            content,
+            selector,
+            language,
+            file_path,
+            staged_edit,
+            &StdFileOperations,
+        )
+    }
+
+    /// Create an editor for a file read through the given file operations
+    pub fn open(
+        content: String,
+        selector: Selector,
+        language: &'language LanguageCommon,
+        file_path: PathBuf,
+        staged_edit: Option<EditPosition>,
+        file_operations: &dyn FileOperations,
+    ) -> Result<Self> {
+        let (encoding, source_code) = file_operations.read_encoded(&file_path)?;
+        let fingerprint = SourceFingerprint::new(&file_path, &source_code);
+        let editorconfig = EditorConfig::load(file_operations, &file_path);
+        let content = editorconfig.reindent(&content);
+        Ok(Self {
+            encoding,
+            fingerprint,
+            staged_edit,
+            ..Self::with_source(content, selector, language, file_path, source_code)?
+        }
+        .with_editorconfig(editorconfig))
+    }
+
+    /// Create an editor for source code held in memory, without reading or writing any file
+    ///
+    /// Use [`Editor::commit_to_string`] to get the edited source.
+    pub fn from_source(
+        source_code: &str,
+        content: String,
+        selector: Selector,
+        language: &'language LanguageCommon,
+    ) -> Result<Self> {
+        Self::with_source(
+            content,
+            selector,
+            language,
+            PathBuf::from(IN_MEMORY_PATH),
+            source_code.to_string(),
+        )
+    }
+
+    /// Create an editor for a file read through a [`SourceCache`], reusing the content and
+    /// syntax tree from an earlier read if the file has not changed since
+    pub fn open_cached(
+        content: String,
+        selector: Selector,
+        language: &'language LanguageCommon,
+        file_path: PathBuf,
+        staged_edit: Option<EditPosition>,
+        file_operations: &dyn FileOperations,
+        source_cache: &'language SourceCache,
+    ) -> Result<Self> {
+        let cached = source_cache.read(file_operations, &file_path)?;
+        let editorconfig = EditorConfig::load(file_operations, &file_path);
+        let content = editorconfig.reindent(&content);
+        let tree = cached
+            .tree
+            .filter(|(language_name, _)| *language_name == language.name())
+            .map(|(_, tree)| tree);
+        let editor = Self::with_tree(
+            content,
+            selector,
+            language,
+            file_path,
+            cached.source_code,
+            tree,
+        )?;
+        Ok(Self {
+            encoding: cached.encoding,
+            fingerprint: cached.fingerprint,
+            staged_edit,
+            source_cache: Some(source_cache),
+            ..editor
+        }
+        .with_editorconfig(editorconfig))
+    }
+
+    fn with_source(
+        content: String,
+        selector: Selector,
+        language: &'language LanguageCommon,
+        file_path: PathBuf,
+        source_code: String,
+    ) -> Result<Self> {
+        Self::with_tree(content, selector, language, file_path, source_code, None)
+    }
+
+    /// Create an editor for source code, which is parsed when the edit runs unless its tree
+    /// is already known
+    fn with_tree(
+        content: String,
+        selector: Selector,
+        language: &'language LanguageCommon,
+        file_path: PathBuf,
+        source_code: String,
+        tree: Option<Tree>,
+    ) -> Result<Self> {
         let rope = Rope::from_str(&source_code);
+        let fingerprint = SourceFingerprint::from_source(&source_code);
 
-        // Validate anchor exists if using anchor-based selector
-        if !selector.anchor.is_empty() && !source_code.contains(&selector.anchor) {
-            return Err(anyhow!(
-                "Anchor text '{}' not found in file {}",
-                selector.anchor,
-                file_path.display()
-            ));
+        // Validate anchor exists if using anchor-based selector; templates are matched later
+        if !selector.anchor.is_empty()
+            && !matches!(selector.operation, Operation::RewritePattern)
+            && !source_code.contains(&selector.anchor)
+        {
+            let near = near_matches::near_matches(
+                &source_code,
+                &selector.anchor,
+                near_matches::SUGGESTION_COUNT,
+            );
+            return Err(SemanticEditError::AnchorNotFound {
+                suggestions: near_matches::describe(&near),
+                anchor: selector.anchor,
+                path: file_path.display().to_string(),
+            }
+            .into());
         }
 
         // For ReplaceNode operations, validate that the new content is syntactically valid
         if matches!(selector.operation, crate::selector::Operation::ReplaceNode) {
-            let mut temp_parser = language.tree_sitter_parser()?;
+            let mut temp_parser = language.parser()?;
             if let Some(parsed_tree) = temp_parser.parse(&content, None) {
                 if parsed_tree.root_node().has_error() {
                     return Err(anyhow!(
@@ -121,16 +399,90 @@ impl<'language> Editor<'language> {
             selector,
             language,
             tree,
+            text_only: None,
             file_path,
             source_code,
+            fingerprint,
             rope,
-            staged_edit,
+            staged_edit: None,
+            encoding: TextEncoding::default(),
+            diff_options: DiffOptions::default(),
+            format_mode: FormatMode::default(),
+            validation_level: ValidationLevel::default(),
+            rules: RuleSettings::default(),
+            custom_queries: None,
+            editorconfig: EditorConfig::default(),
+            format_limits: FormatLimits::default(),
+            parse_limits: ParseLimits::default(),
+            progress: Progress::default(),
+            source_cache: None,
+            notebook_cell: None,
+            candidate: None,
+        })
+    }
+
+    /// Create an editor for one code cell of a Jupyter notebook, chosen by its index or as
+    /// the only code cell containing the anchor
+    ///
+    /// The cell is edited in the notebook's kernel language, or `language_hint` if given, and
+    /// the edit's output is the whole notebook with the cell's new source.
+    pub fn open_notebook_cell(
+        content: String,
+        selector: Selector,
+        language_registry: &'language LanguageRegistry,
+        language_hint: Option<LanguageName>,
+        file_path: PathBuf,
+        cell: Option<usize>,
+        file_operations: &dyn FileOperations,
+    ) -> Result<Self> {
+        let (encoding, source_code) = file_operations.read_encoded(&file_path)?;
+        let fingerprint = SourceFingerprint::new(&file_path, &source_code);
+        let notebook = Notebook::parse(&source_code)
+            .map_err(|error| anyhow!("{}: {error}", file_path.display()))?;
+        let index = match cell {
+            Some(index) => index,
+            None => notebook.find_cell(
+                &selector.anchor,
+                matches!(selector.operation, Operation::RewritePattern),
+            )?,
+        };
+        let cell_source = notebook.cell_source(index)?;
+        let language = language_registry.get_language(
+            language_hint
+                .or_else(|| notebook.language())
+                .unwrap_or(LanguageName::Python),
+        )?;
+
+        // conventions and formatter configuration are those of the cell's language
+        let source_path = file_path.with_extension(
+            language
+                .file_extensions()
+                .first()
+                .copied()
+                .unwrap_or_default(),
+        );
+        let editorconfig = EditorConfig::load(file_operations, &source_path);
+        let content = editorconfig.reindent(&content);
+        Ok(Self {
+            encoding,
+            fingerprint,
+            editorconfig,
+            notebook_cell: Some(NotebookCell {
+                notebook,
+                index,
+                source_path,
+            }),
+            ..Self::with_source(content, selector, language, file_path, cell_source)?
         })
     }
 
+    /// Recreate the editor for a staged operation, reading the file through `source_cache`
+    /// if one is given
     pub fn from_staged_operation(
         staged_operation: StagedOperation,
         language_registry: &'language LanguageRegistry,
+        file_operations: &dyn FileOperations,
+        source_cache: Option<&'language SourceCache>,
     ) -> Result<Self> {
         let StagedOperation {
             selector,
@@ -138,26 +490,172 @@ impl<'language> Editor<'language> {
             file_path,
             language_name,
             edit_position,
+            diff_options,
+            format_mode,
+            validation_level,
+            rules,
+            cell,
+            candidate,
+            ..
         } = staged_operation;
-        let language = language_registry.get_language(language_name)?;
-        Self::new(content, selector, language, file_path, edit_position)
+        let editor = match (cell, source_cache) {
+            (Some(cell), _) => Self {
+                staged_edit: edit_position,
+                ..Self::open_notebook_cell(
+                    content,
+                    selector,
+                    language_registry,
+                    Some(language_name),
+                    file_path,
+                    Some(cell),
+                    file_operations,
+                )?
+            },
+            (None, Some(source_cache)) => Self::open_cached(
+                content,
+                selector,
+                language_registry.get_language(language_name)?,
+                file_path,
+                edit_position,
+                file_operations,
+                source_cache,
+            )?,
+            (None, None) => Self::open(
+                content,
+                selector,
+                language_registry.get_language(language_name)?,
+                file_path,
+                edit_position,
+                file_operations,
+            )?,
+        };
+        Ok(editor
+            .with_diff_options(diff_options)
+            .with_format_mode(format_mode)
+            .with_validation_level(validation_level)
+            .with_rules(rules)
+            .with_candidate(candidate))
     }
 
-    fn prevalidate(&self) -> Option<String> {
-        self.validate_tree(&self.tree, &self.source_code)
-            .map(|errors| {
-                format!(
-                    "Syntax error found prior to edit, not attempting.
-Suggestion: Pause and show your human collaborator this context:\n\n{errors}"
-                )
-            })
+    /// Set how diffs in previews and commit results are rendered
+    pub fn with_diff_options(mut self, diff_options: DiffOptions) -> Self {
+        self.diff_options = diff_options;
+        self
+    }
+
+    /// Set how much of the file is formatted after the edit
+    pub fn with_format_mode(mut self, format_mode: FormatMode) -> Self {
+        self.format_mode = format_mode;
+        self
+    }
+
+    /// Set how strictly edits are validated
+    pub fn with_validation_level(mut self, validation_level: ValidationLevel) -> Self {
+        self.validation_level = validation_level;
+        self
+    }
+
+    /// Disable or downgrade individual context validation rules
+    pub fn with_rules(mut self, rules: RuleSettings) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set how long formatters may run and how much they may output
+    pub fn with_format_limits(mut self, format_limits: FormatLimits) -> Self {
+        self.format_limits = format_limits;
+        self
+    }
+
+    /// Report formatter runs to a request's progress, and stop them if it is cancelled
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Edit the file as text if it is larger or slower to parse than these limits
+    pub fn with_parse_limits(mut self, parse_limits: ParseLimits) -> Self {
+        self.parse_limits = parse_limits;
+        self
+    }
+
+    /// Validate edits against project-supplied queries as well as the built-in ones
+    pub fn with_custom_queries(mut self, custom_queries: Option<Arc<CustomQueries>>) -> Self {
+        self.custom_queries = custom_queries;
+        self
+    }
+
+    /// Edit only this match of the anchor, by its index among the matches in source order,
+    /// as listed in a report's candidates
+    pub fn with_candidate(mut self, candidate: Option<usize>) -> Self {
+        self.candidate = candidate;
+        self
+    }
+
+    /// Follow the indentation, line endings, final newline, and charset from `.editorconfig`
+    ///
+    /// Inserted content is expected to be reindented already, since it is validated when
+    /// the editor is created.
+    fn with_editorconfig(mut self, editorconfig: EditorConfig) -> Self {
+        self.encoding = editorconfig.charset().unwrap_or(self.encoding);
+        self.editorconfig = editorconfig;
+        self
+    }
+
+    /// The file content the edit is made to, or the cell's source for a notebook cell
+    pub fn source_code(&self) -> &str {
+        &self.source_code
+    }
+
+    /// The encoding the edited file will be written in
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// A check that the file, once the commit writes it, reads back as the edit's output
+    pub fn write_check(&self) -> WriteCheck<'language> {
+        WriteCheck::new(self.language, self.encoding, self.notebook_cell.is_some())
+    }
+
+    /// Fingerprint of the file content this editor was created from
+    pub fn fingerprint(&self) -> SourceFingerprint {
+        self.fingerprint
     }
 
-    fn validate_tree(&self, tree: &Tree, content: &str) -> Option<String> {
-        Validator::validate(self.language, tree, content)
+    /// Validate an edited tree, ignoring problems that were already present outside the edit
+    fn validate_tree(
+        &self,
+        tree: &Tree,
+        content: &str,
+        lines: &EditedLines,
+    ) -> Option<ValidationFailure> {
+        // files edited as text have no tree and are never validated
+        let original = self.tree.as_ref()?;
+        match self.validation_level {
+            ValidationLevel::Off => None,
+            ValidationLevel::Strict | ValidationLevel::Warn => Validator::validate_edit(
+                self.language,
+                self.custom_queries
+                    .as_deref()
+                    .map_or(&[], |queries| queries.for_language(self.language.name())),
+                &self.rules,
+                (original, &self.source_code),
+                (tree, content),
+                lines,
+            ),
+        }
     }
 
     pub fn validate(language: &LanguageCommon, tree: &Tree, content: &str) -> Option<String> {
+        Validator::validate(language, tree, content).map(|failure| failure.message)
+    }
+
+    /// Validate a whole file, returning its syntax errors and rule violations if it has any
+    pub fn validate_source(
+        language: &LanguageCommon,
+        tree: &Tree,
+        content: &str,
+    ) -> Option<ValidationFailure> {
         Validator::validate(language, tree, content)
     }
 
@@ -165,76 +663,504 @@ Suggestion: Pause and show your human collaborator this context:\n\n{errors}"
         EditIterator::new(self)
     }
 
-    fn edit(&mut self) -> Result<(String, Option<String>)> {
-        if let Some(prevalidation_failure) = self.prevalidate() {
-            return Ok((prevalidation_failure, None));
-        };
+    /// Parse the source for the edit, or decide to edit it as text if it is over the parse
+    /// limits or cannot be parsed
+    fn parse_source(&mut self) -> Result<()> {
+        if self.text_only.is_some() {
+            return Ok(());
+        }
+        let ParseLimits {
+            max_file_bytes,
+            timeout,
+            ..
+        } = self.parse_limits;
+        if self.source_code.len() > max_file_bytes {
+            self.tree = None;
+            self.text_only = Some(format!(
+                "{} is {} bytes, over the {max_file_bytes} byte limit for parsing",
+                self.file_path.display(),
+                self.source_code.len()
+            ));
+            return Ok(());
+        }
+        if self.tree.is_none() {
+            let started = Instant::now();
+            let mut timed_out = |_: &_| started.elapsed() > timeout;
+            let source = self.source_code.as_bytes();
+            let tree = self.language.parser()?.parse_with_options(
+                &mut |byte, _| &source[byte.min(source.len())..],
+                None,
+                Some(ParseOptions::new().progress_callback(&mut timed_out)),
+            );
+            match tree {
+                Some(tree) => {
+                    if let Some(source_cache) = self.source_cache {
+                        source_cache.store_tree(
+                            &self.file_path,
+                            &self.fingerprint,
+                            self.language.name(),
+                            &tree,
+                        )?;
+                    }
+                    self.tree = Some(tree);
+                }
+                None if started.elapsed() > timeout => {
+                    self.text_only = Some(format!(
+                        "parsing {} took longer than the {timeout:?} limit",
+                        self.file_path.display()
+                    ));
+                }
+                None => {
+                    self.text_only = Some(format!(
+                        "{} could not be parsed as {}",
+                        self.file_path.display(),
+                        self.language.name()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 
+    fn edit(&mut self) -> Result<EditOutcome> {
+        self.parse_source()?;
+        let candidates =
+            anchor_matches::candidates(&self.selector, &self.source_code, self.tree.as_ref());
         let mut failed_edits = vec![];
         for edit in self.edit_iterator() {
             match edit {
                 Ok(mut edit) => {
                     edit.apply()?;
                     if edit.is_valid() {
-                        return Ok((edit.message(), edit.output()));
+                        return Ok(EditOutcome {
+                            message: edit.message(),
+                            output: edit.output(),
+                            target: Some(edit.position.with_coordinates(&self.rope)),
+                            span: self.span(&edit),
+                            target_node: edit.target_node.take(),
+                            enclosing_literal: self.enclosing_literal(&edit),
+                            ambiguity: self.ambiguity(&candidates, &edit, &failed_edits, false),
+                            candidates,
+                            warning: edit.take_warning(),
+                            violations: edit.take_violations(),
+                            duplicate: self.duplicate(&edit),
+                            attempts: Vec::new(),
+                        });
                     }
 
                     failed_edits.push(edit);
                 }
 
-                Err(message) => return Ok((message, None)),
+                Err(error) => return Err(error.into()),
             }
         }
 
-        failed_edits
+        // with validation set to warn, fall back to the first edit that only failed validation
+        if let Some(index) = failed_edits.iter().position(|edit| edit.has_fallback()) {
+            let ambiguity = self.ambiguity(&candidates, &failed_edits[index], &failed_edits, true);
+            let edit = &mut failed_edits[index];
+            return Ok(EditOutcome {
+                message: format!("Applied {} operation", self.selector.operation_name()),
+                output: edit.take_fallback_output(),
+                target: Some(edit.position.with_coordinates(&self.rope)),
+                span: self.span(edit),
+                target_node: edit.target_node.take(),
+                enclosing_literal: self.enclosing_literal(edit),
+                ambiguity,
+                candidates,
+                warning: edit.take_warning(),
+                violations: edit.take_violations(),
+                duplicate: self.duplicate(edit),
+                attempts: Vec::new(),
+            });
+        }
+
+        let attempts = failed_edits
+            .iter()
+            .map(|edit| FailedCandidate {
+                candidate: edit.anchor_index,
+                target: edit.position.with_coordinates(&self.rope),
+                reason: edit.failure_summary(),
+            })
+            .collect();
+        let refusal = failed_edits
             .first_mut()
-            .map(|edit| (edit.message(), None))
-            .ok_or_else(|| anyhow::Error::from(SemanticEditError::NoValidEditLocations))
+            .map(|edit| EditOutcome {
+                message: edit.message(),
+                violations: edit.take_violations(),
+                candidates,
+                attempts,
+                ..EditOutcome::default()
+            })
+            .ok_or_else(|| anyhow::Error::from(SemanticEditError::NoValidEditLocations));
+        if self.text_only.is_none() && self.tree.as_ref().is_some_and(unparseable) {
+            return self.edit_as_text(refusal);
+        }
+        refusal
     }
 
-    pub fn preview(mut self) -> Result<(String, Option<StagedOperation>)> {
-        let (message, output) = self.edit()?;
-        if let Some(output) = &output {
-            let mut preview = String::new();
+    /// Retry an edit refused in a file that does not parse as its language at all as text.
+    /// Operations that need a tree fail saying why there is none, and edits that are refused
+    /// as text too keep their original refusal.
+    fn edit_as_text(&mut self, refusal: Result<EditOutcome>) -> Result<EditOutcome> {
+        let tree = self.tree.take();
+        self.text_only = Some(format!(
+            "{} does not parse as {} at all, so it may be in another language",
+            self.file_path.display(),
+            self.language.name()
+        ));
+        match self.edit() {
+            Ok(outcome) if outcome.output.is_none() => {
+                self.tree = tree;
+                self.text_only = None;
+                refusal
+            }
+            outcome => outcome,
+        }
+    }
 
-            preview.push_str(&format!("STAGED: {}\n\n", self.selector.operation_name()));
-            preview.push_str(&self.diff(output));
+    /// Which of the anchor's candidates the edit is at and why it was chosen, if the anchor
+    /// matched more than once
+    fn ambiguity(
+        &self,
+        candidates: &[AnchorMatch],
+        edit: &Edit<'_, '_>,
+        failed_edits: &[Edit<'_, '_>],
+        fallback: bool,
+    ) -> Option<AnchorAmbiguity> {
+        let failed = failed_edits
+            .iter()
+            .filter_map(|edit| edit.anchor_index)
+            .collect::<Vec<_>>();
+        anchor_matches::ambiguity(
+            candidates,
+            edit.anchor_index?,
+            &failed,
+            fallback,
+            self.candidate.is_some(),
+        )
+    }
 
-            Ok((preview, Some(self.into())))
+    /// The lines a `replace_range` edit replaces, and why it ends where it does
+    fn span(&self, edit: &Edit<'_, '_>) -> Option<String> {
+        let span_reason = edit.span_reason?;
+        let position = edit.position.with_coordinates(&self.rope);
+        let (start, end) = (position.start?.line, position.end?.line);
+        Some(if start == end {
+            format!("line {start}, ending at {span_reason}")
         } else {
-            Ok((message, None))
+            format!("lines {start}-{end}, ending at {span_reason}")
+        })
+    }
+
+    /// Why an insert looks like a second copy of the text next to it, if it does
+    fn duplicate(&self, edit: &Edit<'_, '_>) -> Option<String> {
+        let inserts = matches!(
+            self.selector.operation,
+            Operation::InsertBefore | Operation::InsertAfter | Operation::InsertAfterNode
+        );
+        if !inserts || edit.position.end_byte.is_some() {
+            return None;
+        }
+        idempotence::adjacent_duplicate(&self.source_code, edit.position.start_byte, &edit.content)
+    }
+
+    /// The string literal or comment the edit's anchor match lies inside, if it does
+    fn enclosing_literal(&self, edit: &Edit<'_, '_>) -> Option<TargetNode> {
+        let range = anchor_matches::anchor_ranges(&self.selector, &self.source_code)
+            .into_iter()
+            .nth(edit.anchor_index?)?;
+        let strings = !matches!(
+            self.language.name(),
+            LanguageName::Json | LanguageName::Jsonc | LanguageName::Toml | LanguageName::Other
+        );
+        anchor_matches::enclosing_literal(self.tree.as_ref()?, &range, strings)
+    }
+
+    /// Run the edit pipeline and summarize the result
+    fn report(&mut self) -> Result<(EditReport, Option<String>)> {
+        let EditOutcome {
+            message,
+            output,
+            target,
+            target_node,
+            enclosing_literal,
+            ambiguity,
+            candidates,
+            warning,
+            violations,
+            duplicate,
+            span,
+            attempts,
+        } = self.edit()?;
+        let diff = output.as_deref().map(|output| self.diff(output));
+        let report = EditReport {
+            operation: self.selector.operation_name().to_string(),
+            file_path: self.file_path.clone(),
+            cell: self.notebook_cell.as_ref().map(|cell| cell.index),
+            target,
+            target_node,
+            enclosing_literal,
+            candidates,
+            ambiguity,
+            message,
+            warning,
+            violations,
+            duplicate,
+            attempts,
+            span,
+            efficiency: diff.as_ref().and_then(|diff| diff.efficiency),
+            hunks: diff
+                .as_ref()
+                .map(|diff| diff.hunks.clone())
+                .unwrap_or_default(),
+            diff: diff.map(|diff| diff.to_string()),
+        };
+        Ok((report, output))
+    }
+
+    pub fn preview(self) -> Result<(String, Option<StagedOperation>)> {
+        let (report, staged_operation) = self.preview_report()?;
+        Ok((report.to_string(), staged_operation))
+    }
+
+    /// Preview the edit, returning a structured report instead of text
+    pub fn preview_report(mut self) -> Result<(PreviewReport, Option<StagedOperation>)> {
+        let (edit, output) = self.report()?;
+        let staged = output.is_some();
+        let report = PreviewReport {
+            staged,
+            edit,
+            target_changed: false,
+            dirty: None,
+            already_applied: false,
+            detected_language: None,
+        };
+        let target = report.edit.target;
+        let duplicate = report.edit.duplicate.clone();
+        Ok((
+            report,
+            staged.then(|| StagedOperation {
+                target,
+                duplicate,
+                ..self.into()
+            }),
+        ))
+    }
+
+    /// Preview the edit at each of the anchor's first few candidates without staging any, or
+    /// None if the anchor matches fewer than two locations
+    ///
+    /// Each preview has a short diff, so that the candidate to edit can be chosen by
+    /// comparing them and passed to [`Self::with_candidate`].
+    pub fn alternatives_report(&mut self) -> Result<Option<AlternativesReport>> {
+        self.parse_source()?;
+        let candidates =
+            anchor_matches::candidates(&self.selector, &self.source_code, self.tree.as_ref());
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let (requested, diff_options) = (self.candidate, self.diff_options);
+        self.diff_options = DiffOptions {
+            context_lines: diff_options.context_lines.min(1),
+            max_lines: ALTERNATIVE_DIFF_LINES,
+            efficiency_min_lines: usize::MAX,
+            ..diff_options
+        };
+        let alternatives = self.alternatives(&candidates);
+        self.candidate = requested;
+        self.diff_options = diff_options;
+        let alternatives = alternatives?;
+
+        Ok(Some(AlternativesReport {
+            operation: self.selector.operation_name().to_string(),
+            file_path: self.file_path.clone(),
+            cell: self.notebook_cell.as_ref().map(|cell| cell.index),
+            match_count: candidates.len(),
+            default_candidate: alternatives
+                .iter()
+                .find(|alternative| alternative.diff.is_some())
+                .map(|alternative| alternative.candidate.index),
+            alternatives,
+        }))
+    }
+
+    /// The edit previewed at each of the first [`MAX_ALTERNATIVES`] candidates
+    fn alternatives(&mut self, candidates: &[AnchorMatch]) -> Result<Vec<Alternative>> {
+        let mut alternatives = Vec::new();
+        for candidate in candidates.iter().take(MAX_ALTERNATIVES) {
+            self.progress.check_cancelled()?;
+            self.candidate = Some(candidate.index);
+            let alternative = match self.report() {
+                Ok((report, _)) => Alternative {
+                    candidate: candidate.clone(),
+                    target_node: report.target_node,
+                    enclosing_literal: report.enclosing_literal,
+                    message: report.message,
+                    warning: report.warning,
+                    diff: report.diff,
+                },
+                Err(error) => match error.downcast_ref::<SemanticEditError>() {
+                    Some(
+                        SemanticEditError::NoValidEditLocations
+                        | SemanticEditError::EditNotApplied { .. },
+                    ) => Alternative {
+                        candidate: candidate.clone(),
+                        target_node: None,
+                        enclosing_literal: None,
+                        message: error.to_string(),
+                        warning: None,
+                        diff: None,
+                    },
+                    _ => return Err(error),
+                },
+            };
+            alternatives.push(alternative);
         }
+        Ok(alternatives)
     }
 
-    fn diff(&self, output: &str) -> String {
-        DiffGenerator::generate_diff(&self.source_code, output, &self.content)
+    fn diff(&self, output: &str) -> Diff {
+        DiffGenerator::diff(&self.source_code, output, &self.content, &self.diff_options)
     }
 
     pub fn format_code(&self, source: &str) -> Result<String> {
-        Formatter::format_code(self.language, source)
+        Formatter::format_code(self.language, source, &self.format_context())
+    }
+
+    fn format_context(&self) -> FormatContext<'_> {
+        FormatContext {
+            indentation: self.editorconfig.indentation(),
+            // editors for in-memory source have no project to take configuration from
+            file_path: Some(match &self.notebook_cell {
+                Some(cell) => cell.source_path.as_path(),
+                None => self.file_path.as_path(),
+            })
+            .filter(|path| path.is_absolute()),
+            limits: self.format_limits,
+            progress: Some(&self.progress),
+        }
     }
 
-    pub fn commit(mut self) -> Result<(String, Option<String>, PathBuf)> {
-        let (mut message, output) = self.edit()?;
-        if let Some(output) = &output {
-            let diff = self.diff(output);
+    /// Format edit output according to the format mode, returning it with a warning if it
+    /// could not be formatted because no formatter is available
+    ///
+    /// `edited_lines` are the 0-based lines of `output` covered by the edit. If formatting
+    /// only that region does not produce valid syntax, the output is left unformatted.
+    ///
+    /// Unless validation is strict and the original source parsed cleanly, formatter failures
+    /// leave the output unformatted, since generated or partial files often cannot be formatted.
+    /// Line endings and the final newline are then normalized as `.editorconfig` says.
+    fn format_output(
+        &self,
+        output: String,
+        edited_lines: RangeInclusive<usize>,
+    ) -> Result<(String, Option<String>)> {
+        // None keeps the unformatted output, which is then used without copying it
+        let formatted = match self.format_mode {
+            FormatMode::Always => self.format_code(&output).map(Some),
+            FormatMode::Never => Ok(None),
+            FormatMode::EditedRegion => {
+                let spliced = Formatter::format_region(
+                    self.language,
+                    &output,
+                    &self.format_context(),
+                    edited_lines,
+                )?;
+                let valid = self
+                    .parse(&spliced, None)
+                    .is_some_and(|tree| !tree.root_node().has_error());
+                Ok(valid.then_some(spliced))
+            }
+        };
 
-            message = format!(
-                "{} operation result:\n{}\n\n{diff}",
-                self.selector.operation_name(),
-                message,
-            );
+        let (formatted, warning) = match formatted {
+            Err(error) => match error.downcast_ref::<SemanticEditError>() {
+                Some(SemanticEditError::Cancelled) => return Err(error),
+                Some(skipped) if skipped.skips_formatting() => (
+                    output,
+                    Some(format!(
+                        "⚠️ WARNING: {skipped}, so this edit has not been formatted."
+                    )),
+                ),
+                _ if !self.validation_level.is_strict()
+                    || self
+                        .tree
+                        .as_ref()
+                        .is_none_or(|tree| tree.root_node().has_error()) =>
+                {
+                    (output, None)
+                }
+                _ => return Err(error),
+            },
+            Ok(formatted) => (formatted.unwrap_or(output), None),
+        };
+        Ok((self.editorconfig.normalize(formatted), warning))
+    }
+
+    pub fn commit(self) -> Result<(String, Option<String>, PathBuf)> {
+        let (report, output) = self.commit_report()?;
+        let file_path = report.edit.file_path.clone();
+        Ok((report.to_string(), output, file_path))
+    }
+
+    /// Apply the edit, returning a structured report and the new file content
+    ///
+    /// The report is not marked as applied; the caller is responsible for writing
+    /// the output and updating the report accordingly.
+    pub fn commit_report(mut self) -> Result<(CommitReport, Option<String>)> {
+        let (edit, output) = self.report()?;
+        let output = output.map(|output| self.file_output(output)).transpose()?;
+        Ok((CommitReport::new(edit), output))
+    }
+
+    /// Apply the edit and return the new source, without writing anything
+    ///
+    /// Returns [`SemanticEditError::EditNotApplied`] with the failure message if no valid
+    /// edit was found.
+    pub fn commit_to_string(mut self) -> Result<String> {
+        let EditOutcome {
+            message, output, ..
+        } = self.edit()?;
+        let output = output
+            .ok_or_else(|| anyhow::Error::from(SemanticEditError::EditNotApplied { message }))?;
+        self.file_output(output)
+    }
+
+    /// The content to write for edited source: for a notebook cell, the whole notebook with
+    /// the cell's new source
+    fn file_output(&self, output: String) -> Result<String> {
+        match &self.notebook_cell {
+            Some(cell) => cell.notebook.with_cell_source(cell.index, &output),
+            None => Ok(output),
         }
-        Ok((message, output, self.file_path))
     }
 
     fn parse(&self, output: &str, old_tree: Option<&Tree>) -> Option<Tree> {
-        let mut parser = match self.language.tree_sitter_parser() {
+        let mut parser = match self.language.parser() {
             Ok(parser) => parser,
             Err(_) => return None, // Cannot parse without a valid parser
         };
         parser.parse(output, old_tree)
     }
+
+    /// Parse edited source held in a rope, reading its chunks in place rather than copying
+    /// them into one string
+    fn parse_rope(&self, rope: &Rope, old_tree: Option<&Tree>) -> Option<Tree> {
+        let mut parser = self.language.parser().ok()?;
+        parser.parse_with_options(
+            &mut |byte, _| {
+                if byte >= rope.len_bytes() {
+                    return &[][..];
+                }
+                let (chunk, chunk_start, _, _) = rope.chunk_at_byte(byte);
+                &chunk.as_bytes()[byte - chunk_start..]
+            },
+            old_tree,
+            None,
+        )
+    }
 }
 
 impl From<Editor<'_>> for StagedOperation {
@@ -245,6 +1171,13 @@ impl From<Editor<'_>> for StagedOperation {
             file_path,
             language,
             staged_edit,
+            fingerprint,
+            diff_options,
+            format_mode,
+            validation_level,
+            rules,
+            notebook_cell,
+            candidate,
             ..
         } = value;
         Self {
@@ -253,6 +1186,16 @@ impl From<Editor<'_>> for StagedOperation {
             file_path,
             language_name: language.name(),
             edit_position: staged_edit,
+            source_fingerprint: Some(fingerprint),
+            diff_options,
+            format_mode,
+            validation_level,
+            rules,
+            dirty: None,
+            cell: notebook_cell.map(|cell| cell.index),
+            candidate,
+            target: None,
+            duplicate: None,
         }
     }
 }
@@ -0,0 +1,183 @@
+//! Every location an anchor matches.
+//!
+//! This module finds each place in the source an edit's anchor matches, in the order the
+//! edit iterator tries them, so that when an anchor is ambiguous the report can list every
+//! match as a candidate a later edit can pick by index, and say which one was edited and why. It also finds the string
+//! literal or comment a match lies inside, since an anchor matching there is usually a
+//! mis-targeted edit.
+
+use std::ops::Range;
+
+use tree_sitter::{Node, Tree};
+
+use super::report::{AnchorAmbiguity, AnchorMatch, TargetNode};
+use crate::comby::Template;
+use crate::selector::{Operation, Selector};
+
+/// Matches listed in an ambiguity report are trimmed to this many characters
+const MAX_SNIPPET_CHARS: usize = 120;
+
+/// Byte ranges of the anchor's matches, in source order
+///
+/// These are the matches the edit iterator numbers its candidate edits by: node and declaration
+/// operations search for the first line of the anchor, pattern rewrites for the template, and the other
+/// operations for the whole anchor.
+pub(super) fn anchor_ranges(selector: &Selector, source: &str) -> Vec<Range<usize>> {
+    let anchor = match selector.operation {
+        Operation::RewritePattern => {
+            return Template::parse(&selector.anchor)
+                .matches(source)
+                .into_iter()
+                .map(|template_match| template_match.start_byte..template_match.end_byte)
+                .collect();
+        }
+        Operation::ReplaceNode
+        | Operation::InsertAfterNode
+        | Operation::AddAttribute
+        | Operation::AddDocComment => selector
+            .anchor
+            .trim()
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim(),
+        _ => selector.anchor.as_str(),
+    };
+    if anchor.is_empty() {
+        return Vec::new();
+    }
+    source
+        .match_indices(anchor)
+        .map(|(start, matched)| start..start + matched.len())
+        .collect()
+}
+
+/// Every match of an anchor that matches more than once, or nothing if it matches once
+///
+/// Each candidate is numbered by its index in source order, which an edit can be limited to
+/// with [`Editor::with_candidate`](super::Editor::with_candidate), and names the smallest
+/// syntax node covering the match, which node operations edit.
+pub(super) fn candidates(
+    selector: &Selector,
+    source: &str,
+    tree: Option<&Tree>,
+) -> Vec<AnchorMatch> {
+    let ranges = anchor_ranges(selector, source);
+    if ranges.len() < 2 {
+        return Vec::new();
+    }
+    ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, range)| AnchorMatch {
+            index,
+            line: source[..range.start].matches('\n').count() + 1,
+            node_kind: tree
+                .and_then(|tree| {
+                    tree.root_node()
+                        .named_descendant_for_byte_range(range.start, range.end)
+                })
+                .map(|node| node.kind().to_string()),
+            text: snippet(source, range.start),
+        })
+        .collect()
+}
+
+/// Which of an ambiguous anchor's candidates was edited and why, or None if the anchor
+/// matches only once
+///
+/// `failed` holds the candidates, by index, whose edits were tried first and were not valid.
+pub(super) fn ambiguity(
+    candidates: &[AnchorMatch],
+    chosen: usize,
+    failed: &[usize],
+    fallback: bool,
+    requested: bool,
+) -> Option<AnchorAmbiguity> {
+    if chosen >= candidates.len() {
+        return None;
+    }
+    let mut failed_lines = failed
+        .iter()
+        .filter(|&&index| index != chosen)
+        .filter_map(|&index| candidates.get(index).map(|candidate| candidate.line))
+        .collect::<Vec<_>>();
+    failed_lines.sort_unstable();
+    failed_lines.dedup();
+    let reason = if requested {
+        format!("candidate {chosen} was requested")
+    } else if fallback {
+        "no match produced a valid edit, so the first edit that only failed validation was \
+         applied because validation is set to warn"
+            .to_string()
+    } else if failed_lines.is_empty() {
+        "it is the first match, and the edit there is valid".to_string()
+    } else {
+        format!(
+            "it is the first match where the edit is valid; edits at line {} were not",
+            failed_lines
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    Some(AnchorAmbiguity { chosen, reason })
+}
+
+/// The string literal or comment an anchor match lies inside, if it does
+///
+/// A match covering a whole literal or comment targets it on purpose, so only matches
+/// strictly inside one count, and matches in an interpolation within a string are code.
+/// String literals are only looked for if `strings` is set, since in data languages like
+/// JSON they are what edits change.
+pub(super) fn enclosing_literal(
+    tree: &Tree,
+    range: &Range<usize>,
+    strings: bool,
+) -> Option<TargetNode> {
+    let is_literal = |node: &Node<'_>| {
+        let kind = node.kind();
+        kind.contains("comment") || (strings && kind.contains("string"))
+    };
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(range.start, range.end)?;
+    let mut literal = None;
+    loop {
+        let kind = node.kind();
+        if kind.contains("interpolation") || kind.contains("substitution") {
+            return None;
+        }
+        if is_literal(&node) {
+            literal = Some(node);
+        } else if literal.is_some() {
+            break;
+        }
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+    literal
+        .filter(|literal| literal.byte_range() != *range)
+        .map(TargetNode::from)
+}
+
+/// The line a match starts on, trimmed and shortened to fit on one line
+fn snippet(source: &str, start: usize) -> String {
+    let line_start = source[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let line = source[line_start..]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if line.chars().count() > MAX_SNIPPET_CHARS {
+        let mut text = line.chars().take(MAX_SNIPPET_CHARS).collect::<String>();
+        text.push('…');
+        text
+    } else {
+        line.to_string()
+    }
+}
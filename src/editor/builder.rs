@@ -0,0 +1,207 @@
+//! Fluent construction of editors.
+//!
+//! This module provides the `EditorBuilder` returned by [`Editor::builder`], which gathers an
+//! edit's source, file operations, and options, so new options do not change the signatures
+//! of the editor's constructors.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+
+use super::{
+    DiffOptions, EditPosition, Editor, FormatLimits, FormatMode, IN_MEMORY_PATH, ParseLimits,
+};
+use crate::filesystem::{FileOperations, StdFileOperations};
+use crate::languages::LanguageCommon;
+use crate::progress::Progress;
+use crate::selector::Selector;
+use crate::state::SourceCache;
+use crate::validation::{CustomQueries, RuleSettings, ValidationLevel};
+
+/// Builder for [`Editor`]s
+///
+/// The editor edits the file at [`with_file_path`](Self::with_file_path), read through the
+/// [file operations](Self::with_file_operations) or a [source cache](Self::with_source_cache),
+/// or the source code given to [`with_source`](Self::with_source), which takes precedence.
+pub struct EditorBuilder<'language, 'files> {
+    content: String,
+    selector: Selector,
+    language: &'language LanguageCommon,
+    file_path: Option<PathBuf>,
+    source_code: Option<String>,
+    file_operations: &'files dyn FileOperations,
+    source_cache: Option<&'language SourceCache>,
+    staged_edit: Option<EditPosition>,
+    candidate: Option<usize>,
+    diff_options: DiffOptions,
+    format_mode: FormatMode,
+    validation_level: ValidationLevel,
+    rules: RuleSettings,
+    format_limits: FormatLimits,
+    parse_limits: ParseLimits,
+    custom_queries: Option<Arc<CustomQueries>>,
+    progress: Progress,
+}
+
+impl<'language, 'files> EditorBuilder<'language, 'files> {
+    pub(super) fn new(
+        content: String,
+        selector: Selector,
+        language: &'language LanguageCommon,
+    ) -> Self {
+        Self {
+            content,
+            selector,
+            language,
+            file_path: None,
+            source_code: None,
+            file_operations: &StdFileOperations,
+            source_cache: None,
+            staged_edit: None,
+            candidate: None,
+            diff_options: DiffOptions::default(),
+            format_mode: FormatMode::default(),
+            validation_level: ValidationLevel::default(),
+            rules: RuleSettings::default(),
+            format_limits: FormatLimits::default(),
+            parse_limits: ParseLimits::default(),
+            custom_queries: None,
+            progress: Progress::default(),
+        }
+    }
+
+    /// Set the file to edit
+    pub fn with_file_path(mut self, file_path: impl Into<PathBuf>) -> Self {
+        self.file_path = Some(file_path.into());
+        self
+    }
+
+    /// Edit source code held in memory instead of reading the file
+    pub fn with_source(mut self, source_code: impl Into<String>) -> Self {
+        self.source_code = Some(source_code.into());
+        self
+    }
+
+    /// Read the file, and its `.editorconfig`, through these file operations
+    pub fn with_file_operations(mut self, file_operations: &'files dyn FileOperations) -> Self {
+        self.file_operations = file_operations;
+        self
+    }
+
+    /// Reuse the content and syntax tree of the file from a cache if it has not changed
+    pub fn with_source_cache(mut self, source_cache: &'language SourceCache) -> Self {
+        self.source_cache = Some(source_cache);
+        self
+    }
+
+    /// Edit the position an earlier preview found instead of searching for it again
+    pub fn with_staged_edit(mut self, staged_edit: EditPosition) -> Self {
+        self.staged_edit = Some(staged_edit);
+        self
+    }
+
+    /// Edit only this match of the anchor, by its index among the matches in source order
+    pub fn with_candidate(mut self, candidate: usize) -> Self {
+        self.candidate = Some(candidate);
+        self
+    }
+
+    /// Set how diffs in previews and commit results are rendered
+    pub fn with_diff_options(mut self, diff_options: DiffOptions) -> Self {
+        self.diff_options = diff_options;
+        self
+    }
+
+    /// Set how much of the file is formatted after the edit
+    pub fn with_format_mode(mut self, format_mode: FormatMode) -> Self {
+        self.format_mode = format_mode;
+        self
+    }
+
+    /// Set how strictly edits are validated
+    pub fn with_validation_level(mut self, validation_level: ValidationLevel) -> Self {
+        self.validation_level = validation_level;
+        self
+    }
+
+    /// Disable or downgrade individual context validation rules
+    pub fn with_rules(mut self, rules: RuleSettings) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set how long formatters may run and how much they may output
+    pub fn with_format_limits(mut self, format_limits: FormatLimits) -> Self {
+        self.format_limits = format_limits;
+        self
+    }
+
+    /// Edit the file as text if it is larger or slower to parse than these limits
+    pub fn with_parse_limits(mut self, parse_limits: ParseLimits) -> Self {
+        self.parse_limits = parse_limits;
+        self
+    }
+
+    /// Validate edits against project-supplied queries as well as the built-in ones
+    pub fn with_custom_queries(mut self, custom_queries: Arc<CustomQueries>) -> Self {
+        self.custom_queries = Some(custom_queries);
+        self
+    }
+
+    /// Report formatter runs to a request's progress, and stop them if it is cancelled
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Build the editor, reading the file unless source code was given
+    pub fn build(self) -> Result<Editor<'language>> {
+        let editor = match (self.source_code, self.file_path) {
+            (Some(source_code), file_path) => Editor {
+                staged_edit: self.staged_edit,
+                ..Editor::with_source(
+                    self.content,
+                    self.selector,
+                    self.language,
+                    file_path.unwrap_or_else(|| PathBuf::from(IN_MEMORY_PATH)),
+                    source_code,
+                )?
+            },
+            (None, Some(file_path)) => match self.source_cache {
+                Some(source_cache) => Editor::open_cached(
+                    self.content,
+                    self.selector,
+                    self.language,
+                    file_path,
+                    self.staged_edit,
+                    self.file_operations,
+                    source_cache,
+                )?,
+                None => Editor::open(
+                    self.content,
+                    self.selector,
+                    self.language,
+                    file_path,
+                    self.staged_edit,
+                    self.file_operations,
+                )?,
+            },
+            (None, None) => {
+                return Err(anyhow!(
+                    "an editor needs a file path or source code to edit"
+                ));
+            }
+        };
+        Ok(editor
+            .with_diff_options(self.diff_options)
+            .with_format_mode(self.format_mode)
+            .with_validation_level(self.validation_level)
+            .with_rules(self.rules)
+            .with_format_limits(self.format_limits)
+            .with_parse_limits(self.parse_limits)
+            .with_custom_queries(self.custom_queries)
+            .with_progress(self.progress)
+            .with_candidate(self.candidate))
+    }
+}
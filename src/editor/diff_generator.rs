@@ -9,20 +9,28 @@
 //! - Calculates edit efficiency (percentage of lines changed)
 //! - Provides helpful tips for large edits with low efficiency
 //! - Optimized for AI consumption with clear formatting
+//! - Configurable context lines, line numbers, and side-by-side rendering
+//! - Optional word-level highlighting within changed lines
+//! - Capping of huge diffs, omitting the middle with a summary line
+//! - A structured [`Diff`] with hunks and line counts, displayed as the rendered text
 //!
 //! ## Edit Efficiency
 //!
-//! The module calculates what percentage of the content was actually changed:
-//! - High efficiency (>30%): Most of the content is being modified
-//! - Low efficiency (<30%): Only a small portion is changed, suggests using targeted edits
+//! The module calculates what percentage of the content was actually changed, counting
+//! added, removed, and modified lines from the patch hunks:
+//! - High efficiency (at or above `efficiency_tip_percent`, default 30%): Most of the content is being modified
+//! - Low efficiency (below the threshold): Only a small portion is changed, suggests using targeted edits
+//!
+//! Efficiency is only reported when the content is longer than `efficiency_min_lines` (default 10).
 //!
 //! ## Example
 //!
 //! ```ignore
-//! use semantic_code_edit_mcp::editor::diff_generator::DiffGenerator;
+//! use semantic_code_edit_mcp::editor::diff_generator::{DiffGenerator, DiffOptions};
 //!
-//! let diff = DiffGenerator::generate_diff(original, modified, content_patch);
-//! println!("{}", diff);
+//! let diff = DiffGenerator::diff(original, modified, content_patch, &DiffOptions::default());
+//! println!("{} lines added, {} removed in {} hunks", diff.added, diff.removed, diff.hunks.len());
+//! println!("{diff}");
 //! // Output:
 //! // Edit efficiency: 15%
 //! // 💡 TIP: For focused changes like this, you might try targeted insert/replace operations
@@ -32,66 +40,506 @@
 //! // +new line
 //! ```
 
-use diffy::{DiffOptions, Patch, PatchFormatter};
-use std::collections::BTreeSet;
+use super::report::{DiffHunk, DiffLine, EditEfficiency};
+use super::word_diff;
+use diffy::{DiffOptions as PatchOptions, Hunk, HunkRange, Line, Patch, PatchFormatter};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Width of each column in side-by-side rendering
+const SIDE_BY_SIDE_COLUMN_WIDTH: usize = 60;
+
+/// Default maximum number of rendered diff lines before the middle is omitted
+pub const DEFAULT_MAX_DIFF_LINES: usize = 400;
+
+/// Default content length, in lines, above which edit efficiency is reported
+pub const DEFAULT_EFFICIENCY_MIN_LINES: usize = 10;
+
+/// Default efficiency percentage below which a targeted-edit tip is shown
+pub const DEFAULT_EFFICIENCY_TIP_PERCENT: usize = 30;
+
+/// Rendering options for diffs shown in previews and commit results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DiffOptions {
+    /// Number of unchanged lines shown around each change
+    pub context_lines: usize,
+    /// Prefix each diff line with its original and new line numbers
+    pub line_numbers: bool,
+    /// Render the original and new content in two columns
+    pub side_by_side: bool,
+    /// Merge each changed line with its replacement, marking changed words
+    /// as `[-removed-]` and `{+added+}`
+    pub word_diff: bool,
+    /// Maximum number of diff lines to render. Longer diffs keep their beginning and end
+    /// and summarize the omitted middle. `0` renders the full diff.
+    pub max_lines: usize,
+    /// Report edit efficiency only when the content is longer than this many lines
+    pub efficiency_min_lines: usize,
+    /// Suggest targeted operations when edit efficiency is below this percentage
+    pub efficiency_tip_percent: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            context_lines: 3,
+            line_numbers: false,
+            side_by_side: false,
+            word_diff: false,
+            max_lines: DEFAULT_MAX_DIFF_LINES,
+            efficiency_min_lines: DEFAULT_EFFICIENCY_MIN_LINES,
+            efficiency_tip_percent: DEFAULT_EFFICIENCY_TIP_PERCENT,
+        }
+    }
+}
+
+impl DiffOptions {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These options with diff capping disabled
+    pub fn uncapped(self) -> Self {
+        Self {
+            max_lines: 0,
+            ..self
+        }
+    }
+
+    fn create_patch<'a>(&self, source_code: &'a str, output: &'a str) -> Patch<'a, str> {
+        PatchOptions::new()
+            .set_context_len(self.context_lines)
+            .create_patch(source_code, output)
+    }
+}
 
 /// Handles diff generation and formatting
 pub struct DiffGenerator;
 
 impl DiffGenerator {
-    /// Generates a formatted diff between source and output
-    pub fn generate_diff(source_code: &str, output: &str, content_patch: &str) -> String {
-        let diff_patch = DiffOptions::new().create_patch(source_code, output);
-        let formatter = PatchFormatter::new().missing_newline_message(false);
+    /// Diffs source and output, with the diff's hunks, line counts, and edit efficiency
+    /// alongside its rendering with the given options
+    pub fn diff(
+        source_code: &str,
+        output: &str,
+        content_patch: &str,
+        options: &DiffOptions,
+    ) -> Diff {
+        let diff_patch = options.create_patch(source_code, output);
+        let efficiency = Self::efficiency(&diff_patch, content_patch, options);
+        let hunks = Self::patch_hunks(&diff_patch);
+        let (mut added, mut removed) = (0, 0);
+        for line in hunks.iter().flat_map(|hunk| &hunk.lines) {
+            match line {
+                DiffLine::Insert(_) => added += 1,
+                DiffLine::Delete(_) => removed += 1,
+                DiffLine::Context(_) => {}
+            }
+        }
 
-        // Get the diff string and clean it up for AI consumption
-        let diff_output = formatter.fmt_patch(&diff_patch).to_string();
-        let lines: Vec<&str> = diff_output.lines().collect();
         let mut cleaned_diff = String::new();
-
-        let content_line_count = content_patch.lines().count();
-        if content_line_count > 10 {
-            let changed_lines = Self::calculate_changed_lines(&diff_patch, content_line_count);
-
-            let changed_fraction = (changed_lines * 100) / content_line_count;
-
-            cleaned_diff.push_str(&format!("Edit efficiency: {changed_fraction}%\n",));
-            if changed_fraction < 30 {
+        if let Some(EditEfficiency { percent, .. }) = efficiency {
+            cleaned_diff.push_str(&format!("Edit efficiency: {percent}%\n",));
+            if percent < options.efficiency_tip_percent {
                 cleaned_diff.push_str("💡 TIP: For focused changes like this, you might try targeted insert/replace operations for easier review and iteration\n");
             };
             cleaned_diff.push('\n');
         }
 
         cleaned_diff.push_str("===DIFF===\n");
-        for line in lines {
+        let mut rendered = String::new();
+        if options.side_by_side {
+            Self::render_side_by_side(&diff_patch, options.line_numbers, &mut rendered);
+        } else if options.line_numbers || options.word_diff {
+            Self::render_lines(&diff_patch, options, &mut rendered);
+        } else {
+            Self::render_unified(&diff_patch, &mut rendered);
+        }
+        Self::cap_lines(&rendered, options.max_lines, &mut cleaned_diff);
+
+        // Remove trailing newline to avoid extra spacing
+        if cleaned_diff.ends_with('\n') {
+            cleaned_diff.pop();
+        }
+        Diff {
+            hunks,
+            added,
+            removed,
+            efficiency,
+            rendered: cleaned_diff,
+        }
+    }
+
+    /// Generates a formatted diff between source and output using the given rendering options
+    pub fn generate_diff(
+        source_code: &str,
+        output: &str,
+        content_patch: &str,
+        options: &DiffOptions,
+    ) -> String {
+        Self::diff(source_code, output, content_patch, options).rendered
+    }
+
+    /// Renders the patch as a unified diff without headers
+    fn render_unified(patch: &Patch<'_, str>, out: &mut String) {
+        let formatter = PatchFormatter::new().missing_newline_message(false);
+
+        // Get the diff string and clean it up for AI consumption
+        let diff_output = formatter.fmt_patch(patch).to_string();
+        for line in diff_output.lines() {
             // Skip ALL diff headers: file headers, hunk headers (line numbers), and any metadata
             if line.starts_with("---") || line.starts_with("+++") || line.starts_with("@@") {
                 // Skip "\ No newline at end of file" messages
                 continue;
             }
-            cleaned_diff.push_str(line);
-            cleaned_diff.push('\n');
+            out.push_str(line);
+            out.push('\n');
         }
+    }
 
-        // Remove trailing newline to avoid extra spacing
-        if cleaned_diff.ends_with('\n') {
-            cleaned_diff.pop();
+    /// Renders the patch line by line, optionally with line numbers and word-level highlighting
+    ///
+    /// With word diff enabled, each removed line that is directly replaced by an added line
+    /// is rendered once with a `~` prefix and intra-line change markers.
+    fn render_lines(patch: &Patch<'_, str>, options: &DiffOptions, out: &mut String) {
+        for hunk in patch.hunks() {
+            let (mut old, mut new) = first_lines(hunk);
+            let mut deleted = vec![];
+            let mut inserted = vec![];
+
+            for line in hunk.lines() {
+                match line {
+                    Line::Delete(text) => {
+                        deleted.push((old, *text));
+                        old += 1;
+                    }
+                    Line::Insert(text) => {
+                        inserted.push((new, *text));
+                        new += 1;
+                    }
+                    Line::Context(text) => {
+                        flush_changed_lines(out, &mut deleted, &mut inserted, options);
+                        push_line(out, Some(old), Some(new), ' ', text, options);
+                        old += 1;
+                        new += 1;
+                    }
+                }
+            }
+            flush_changed_lines(out, &mut deleted, &mut inserted, options);
+        }
+    }
+
+    /// Renders the patch with original content on the left and new content on the right
+    ///
+    /// Markers between the columns follow `diff --side-by-side`: `|` for changed lines,
+    /// `<` for removed lines, and `>` for added lines.
+    fn render_side_by_side(patch: &Patch<'_, str>, line_numbers: bool, out: &mut String) {
+        for hunk in patch.hunks() {
+            let (mut old, mut new) = first_lines(hunk);
+            let mut deleted = vec![];
+            let mut inserted = vec![];
+
+            for line in hunk.lines() {
+                match line {
+                    Line::Delete(text) => {
+                        deleted.push((old, *text));
+                        old += 1;
+                    }
+                    Line::Insert(text) => {
+                        inserted.push((new, *text));
+                        new += 1;
+                    }
+                    Line::Context(text) => {
+                        flush_changed_rows(out, &mut deleted, &mut inserted, line_numbers);
+                        push_side_by_side_row(
+                            out,
+                            Some((old, text)),
+                            Some((new, text)),
+                            ' ',
+                            line_numbers,
+                        );
+                        old += 1;
+                        new += 1;
+                    }
+                }
+            }
+            flush_changed_rows(out, &mut deleted, &mut inserted, line_numbers);
+        }
+    }
+
+    /// Copies rendered diff lines, replacing the middle with a summary when there are more
+    /// than `max_lines` of them
+    fn cap_lines(rendered: &str, max_lines: usize, out: &mut String) {
+        let lines: Vec<&str> = rendered.lines().collect();
+        if max_lines == 0 || lines.len() <= max_lines {
+            out.push_str(rendered);
+            return;
+        }
+
+        let head = max_lines.div_ceil(2);
+        let tail = max_lines - head;
+        let omitted = lines.len() - max_lines;
+        for line in &lines[..head] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "… {omitted} diff lines omitted (use view_staged_diff or raise diff_options.max_lines to see them) …\n"
+        ));
+        for line in &lines[lines.len() - tail..] {
+            out.push_str(line);
+            out.push('\n');
         }
-        cleaned_diff
     }
 
-    /// Calculates the number of changed lines in a patch
-    pub fn calculate_changed_lines(patch: &Patch<'_, str>, content_line_count: usize) -> usize {
-        let mut changed_line_numbers = BTreeSet::new();
+    /// Calculates edit efficiency for content patches longer than `efficiency_min_lines`
+    pub fn efficiency(
+        patch: &Patch<'_, str>,
+        content_patch: &str,
+        options: &DiffOptions,
+    ) -> Option<EditEfficiency> {
+        let content_lines = content_patch.lines().count();
+        if content_lines <= options.efficiency_min_lines {
+            return None;
+        }
+
+        let counts = Self::count_changes(patch);
+        let changed_lines = (counts.added + counts.modified).min(content_lines);
+        Some(EditEfficiency {
+            content_lines,
+            changed_lines,
+            percent: (changed_lines * 100) / content_lines,
+            added: counts.added,
+            removed: counts.removed,
+            modified: counts.modified,
+        })
+    }
+
+    /// Converts the diff between source and output into structured hunks
+    pub fn hunks(source_code: &str, output: &str, options: &DiffOptions) -> Vec<DiffHunk> {
+        Self::patch_hunks(&options.create_patch(source_code, output))
+    }
+
+    fn patch_hunks(patch: &Patch<'_, str>) -> Vec<DiffHunk> {
+        patch
+            .hunks()
+            .iter()
+            .map(|hunk| DiffHunk {
+                old_start: hunk.old_range().start(),
+                old_lines: hunk.old_range().len(),
+                new_start: hunk.new_range().start(),
+                new_lines: hunk.new_range().len(),
+                lines: hunk
+                    .lines()
+                    .iter()
+                    .map(|line| match line {
+                        Line::Context(text) => DiffLine::Context(text.to_string()),
+                        Line::Delete(text) => DiffLine::Delete(text.to_string()),
+                        Line::Insert(text) => DiffLine::Insert(text.to_string()),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Calculates edit efficiency between source and output
+    pub fn calculate_efficiency(
+        source_code: &str,
+        output: &str,
+        content_patch: &str,
+        options: &DiffOptions,
+    ) -> Option<EditEfficiency> {
+        let patch = options.create_patch(source_code, output);
+        Self::efficiency(&patch, content_patch, options)
+    }
+
+    /// Counts added, removed, and modified lines in a patch
+    ///
+    /// Within each run of changes, removed lines that are replaced by added lines count as
+    /// modified; the remainder count as purely added or removed.
+    pub fn count_changes(patch: &Patch<'_, str>) -> ChangeCounts {
+        let mut counts = ChangeCounts::default();
 
         for hunk in patch.hunks() {
-            // old_range().range() returns a std::ops::Range<usize> that's properly 0-indexed
-            for line_num in hunk.old_range().range() {
-                if line_num < content_line_count {
-                    changed_line_numbers.insert(line_num);
+            let (mut deleted, mut inserted) = (0, 0);
+            for line in hunk.lines() {
+                match line {
+                    Line::Delete(_) => deleted += 1,
+                    Line::Insert(_) => inserted += 1,
+                    Line::Context(_) => {
+                        counts.add_run(deleted, inserted);
+                        (deleted, inserted) = (0, 0);
+                    }
                 }
             }
+            counts.add_run(deleted, inserted);
         }
-        changed_line_numbers.len()
+        counts
+    }
+}
+
+/// A diff between a file and its edited version
+///
+/// Displays as the rendered diff shown in previews and commit results, while its hunks, line
+/// counts, and efficiency are available without parsing that text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diff {
+    /// The changed regions, with every line of each, regardless of `max_lines`
+    pub hunks: Vec<DiffHunk>,
+    /// Lines inserted, counting each replaced line once here and once in `removed`
+    pub added: usize,
+    /// Lines deleted
+    pub removed: usize,
+    /// How much of the submitted content changed the file, for content longer than
+    /// `efficiency_min_lines`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<EditEfficiency>,
+    #[serde(skip)]
+    rendered: String,
+}
+
+impl Diff {
+    /// Whether source and output are identical
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rendered)
     }
 }
+
+/// Line-level change counts for a patch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    pub added: usize,
+    pub removed: usize,
+    pub modified: usize,
+}
+
+impl ChangeCounts {
+    fn add_run(&mut self, deleted: usize, inserted: usize) {
+        let modified = deleted.min(inserted);
+        self.modified += modified;
+        self.added += inserted - modified;
+        self.removed += deleted - modified;
+    }
+}
+
+/// The 1-based line numbers of the first old and new lines in a hunk
+fn first_lines(hunk: &Hunk<'_, str>) -> (usize, usize) {
+    let first = |range: HunkRange| {
+        if range.is_empty() {
+            range.start() + 1
+        } else {
+            range.start()
+        }
+    };
+    (first(hunk.old_range()), first(hunk.new_range()))
+}
+
+fn line_number(number: Option<usize>) -> String {
+    number.map_or_else(|| " ".repeat(5), |number| format!("{number:>5}"))
+}
+
+/// Writes pending removed and added lines, merging replaced lines when word diff is enabled
+fn flush_changed_lines(
+    out: &mut String,
+    deleted: &mut Vec<(usize, &str)>,
+    inserted: &mut Vec<(usize, &str)>,
+    options: &DiffOptions,
+) {
+    let paired = if options.word_diff {
+        deleted.len().min(inserted.len())
+    } else {
+        0
+    };
+
+    for ((old, old_text), (new, new_text)) in deleted.iter().zip(inserted.iter()).take(paired) {
+        let merged = word_diff::highlight(
+            old_text.trim_end_matches('\n'),
+            new_text.trim_end_matches('\n'),
+        );
+        push_line(out, Some(*old), Some(*new), '~', &merged, options);
+    }
+    for (old, text) in &deleted[paired..] {
+        push_line(out, Some(*old), None, '-', text, options);
+    }
+    for (new, text) in &inserted[paired..] {
+        push_line(out, None, Some(*new), '+', text, options);
+    }
+    deleted.clear();
+    inserted.clear();
+}
+
+fn push_line(
+    out: &mut String,
+    old: Option<usize>,
+    new: Option<usize>,
+    sign: char,
+    text: &str,
+    options: &DiffOptions,
+) {
+    if options.line_numbers {
+        out.push_str(&format!("{} {} ", line_number(old), line_number(new)));
+    }
+    out.push(sign);
+    out.push_str(text.trim_end_matches('\n'));
+    out.push('\n');
+}
+
+/// Pairs up pending removed and added lines as side-by-side rows
+fn flush_changed_rows<'a>(
+    out: &mut String,
+    deleted: &mut Vec<(usize, &'a str)>,
+    inserted: &mut Vec<(usize, &'a str)>,
+    line_numbers: bool,
+) {
+    for row in 0..deleted.len().max(inserted.len()) {
+        let left = deleted.get(row).copied();
+        let right = inserted.get(row).copied();
+        let marker = match (left, right) {
+            (Some(_), Some(_)) => '|',
+            (Some(_), None) => '<',
+            _ => '>',
+        };
+        push_side_by_side_row(out, left, right, marker, line_numbers);
+    }
+    deleted.clear();
+    inserted.clear();
+}
+
+fn push_side_by_side_row(
+    out: &mut String,
+    left: Option<(usize, &str)>,
+    right: Option<(usize, &str)>,
+    marker: char,
+    line_numbers: bool,
+) {
+    let cell = |side: Option<(usize, &str)>| {
+        let text = side
+            .map(|(_, text)| text.trim_end_matches('\n').replace('\t', "    "))
+            .unwrap_or_default();
+        if line_numbers {
+            format!("{} {text}", line_number(side.map(|(number, _)| number)))
+        } else {
+            text
+        }
+    };
+
+    let width = SIDE_BY_SIDE_COLUMN_WIDTH;
+    let left = cell(left);
+    let left = if left.chars().count() > width {
+        let truncated: String = left.chars().take(width - 1).collect();
+        format!("{truncated}…")
+    } else {
+        left
+    };
+    let right = cell(right);
+    out.push_str(format!("{left:<width$} {marker} {right}").trim_end());
+    out.push('\n');
+}
@@ -2,7 +2,8 @@
 //!
 //! This module provides the `Edit` struct which represents a single editing operation
 //! with validation and application capabilities. Features include:
-//! - Rope-based text manipulation for efficiency
+//! - Rope-based text manipulation, parsed straight from the rope's chunks so each candidate
+//!   copies the source into a string only once
 //! - Tree-sitter integration for AST awareness
 //! - Position tracking and validation
 //! - Content modification with syntax preservation
@@ -14,18 +15,31 @@ use anyhow::Result;
 use ropey::Rope;
 use tree_sitter::{InputEdit, Point, Tree};
 
-use super::{EditPosition, Editor};
+use super::{EditPosition, Editor, TargetNode, validator::EditedLines};
+use crate::validation::{ValidationLevel, Violation};
 
 #[derive(Clone)]
 pub(super) struct Edit<'editor, 'language> {
     pub(super) editor: &'editor Editor<'language>,
-    pub(super) tree: Tree,
+    /// None for files edited as text, which are neither parsed nor validated
+    pub(super) tree: Option<Tree>,
     pub(super) rope: Rope,
     pub(super) content: Cow<'editor, str>,
     pub(super) position: EditPosition,
+    /// Which match of the anchor the edit is at, counting from 0, if it was searched for
+    pub(super) anchor_index: Option<usize>,
+    /// The node a node operation resolved its anchor to
+    pub(super) target_node: Option<TargetNode>,
+    /// Why a `replace_range` ends where it does
+    pub(super) span_reason: Option<&'static str>,
     pub(super) valid: bool,
     pub(super) message: Option<String>,
     pub(super) output: Option<String>,
+    /// Validation problems that did not block the edit
+    pub(super) warning: Option<String>,
+    /// Unformatted output for an edit that failed validation at the warn level
+    pub(super) fallback_output: Option<String>,
+    pub(super) violations: Vec<Violation>,
 }
 
 impl<'editor, 'language> Edit<'editor, 'language> {
@@ -35,10 +49,16 @@ impl<'editor, 'language> Edit<'editor, 'language> {
             tree: editor.tree.clone(),
             rope: editor.rope.clone(),
             position,
+            anchor_index: None,
+            target_node: None,
+            span_reason: None,
             content: Cow::Borrowed(&editor.content),
             valid: false,
             message: None,
             output: None,
+            warning: None,
+            fallback_output: None,
+            violations: vec![],
         }
     }
 
@@ -47,6 +67,21 @@ impl<'editor, 'language> Edit<'editor, 'language> {
         self
     }
 
+    pub fn with_anchor_index(mut self, anchor_index: usize) -> Self {
+        self.anchor_index = Some(anchor_index);
+        self
+    }
+
+    pub fn with_target_node(mut self, target_node: TargetNode) -> Self {
+        self.target_node = Some(target_node);
+        self
+    }
+
+    pub fn with_span_reason(mut self, span_reason: &'static str) -> Self {
+        self.span_reason = Some(span_reason);
+        self
+    }
+
     pub fn with_content(mut self, content: String) -> Self {
         self.content = Cow::Owned(content);
         self
@@ -70,6 +105,7 @@ impl<'editor, 'language> Edit<'editor, 'language> {
         let EditPosition {
             start_byte,
             end_byte,
+            ..
         } = self.position;
 
         let start_char = self.rope.byte_to_char(start_byte);
@@ -91,7 +127,12 @@ impl<'editor, 'language> Edit<'editor, 'language> {
         let new_end_byte = start_byte + content.len();
         let new_end_position = self.byte_to_point(new_end_byte);
 
-        self.tree.edit(&InputEdit {
+        let Some(tree) = self.tree.as_mut() else {
+            self.apply_text_only();
+            return Ok(());
+        };
+
+        tree.edit(&InputEdit {
             start_byte,
             old_end_byte,
             new_end_byte,
@@ -100,46 +141,148 @@ impl<'editor, 'language> Edit<'editor, 'language> {
             new_end_position,
         });
 
-        let output = self.rope.to_string();
-
-        if let Some(tree) = self.editor.parse(&output, Some(&self.tree)) {
-            self.tree = tree;
+        if let Some(tree) = self.editor.parse_rope(&self.rope, Some(tree)) {
+            self.tree = Some(tree);
         } else {
             self.message = Some("Unable to parse result so no changes were made. The file is still in a good state. Try a different edit".into());
             return Ok(());
         }
 
-        if let Some(message) = self.validate(&output) {
-            self.message = Some(message);
+        // validation and diffs need contiguous text, so this is the only copy of the edited source
+        let output = self.rope.to_string();
+
+        let edited_lines = EditedLines {
+            start: start_position.row,
+            old_end: old_end_position.row,
+            new_end: new_end_position.row,
+        };
+        let failure = self
+            .tree
+            .as_ref()
+            .and_then(|tree| self.editor.validate_tree(tree, &output, &edited_lines));
+        if let Some(failure) = failure.as_ref().filter(|failure| failure.blocking) {
+            let errors = &failure.message;
+            self.message = Some(self.invalid_message(errors, &output, failure.had_errors));
+            self.violations = failure.violations.clone();
+            if self.editor.validation_level == ValidationLevel::Warn {
+                let warning = format!(
+                    "⚠️ WARNING: this edit fails validation, but it was staged anyway because \
+validation is set to warn. It has not been formatted. Review it carefully before committing.\n\n\
+{errors}"
+                );
+                self.warning = Some(warning);
+                self.fallback_output = Some(output);
+            }
         } else {
+            if let Some(failure) = failure {
+                self.warning = Some(format!(
+                    "⚠️ WARNING: this edit has validation findings that do not block it. Review \
+them before committing.\n\n{}",
+                    failure.message
+                ));
+                self.violations = failure.violations;
+            }
             self.valid = true;
             self.message = Some(format!(
                 "Applied {} operation",
                 self.editor.selector.operation_name()
             ));
 
-            self.output = Some(self.editor.format_code(&output)?);
+            let first_line = self.rope.byte_to_line(start_byte);
+            let last_line = self
+                .rope
+                .byte_to_line(new_end_byte.saturating_sub(1).max(start_byte));
+            let (output, format_warning) =
+                self.editor.format_output(output, first_line..=last_line)?;
+            self.output = Some(output);
+            if let Some(format_warning) = format_warning {
+                self.warning = Some(match self.warning.take() {
+                    Some(warning) => format!("{warning}\n\n{format_warning}"),
+                    None => format_warning,
+                });
+            }
         }
 
         Ok(())
     }
 
-    fn validate(&mut self, output: &str) -> Option<String> {
-        let errors = self.editor.validate_tree(&self.tree, output)?;
+    /// Accept an edit to a file over the parse limits as is, warning that it was not checked
+    fn apply_text_only(&mut self) {
+        let reason = self
+            .editor
+            .text_only
+            .as_deref()
+            .unwrap_or("the file has not been parsed");
+        self.valid = true;
+        self.message = Some(format!(
+            "Applied {} operation",
+            self.editor.selector.operation_name()
+        ));
+        self.warning = Some(format!(
+            "⚠️ WARNING: {reason}, so this edit was applied as text without being parsed, \
+validated, or formatted. Review it carefully before committing."
+        ));
+        self.output = Some(self.editor.editorconfig.normalize(self.rope.to_string()));
+    }
+
+    fn invalid_message(&self, errors: &str, output: &str, had_errors: bool) -> String {
         let diff = self.editor.diff(output);
-        Some(format!(
-            "This edit would result in invalid syntax, but the file is still in a valid state. \
-No change was performed.
+        let state = if had_errors {
+            ". The file already had syntax errors, so only the ones this edit adds are listed."
+        } else {
+            ", but the file is still in a valid state."
+        };
+        format!(
+            "This edit would result in invalid syntax{state} No change was performed.
 Suggestion: Try a different change.\n
 {errors}\n\n{diff}"
-        ))
+        )
     }
 
     pub(crate) fn message(&mut self) -> String {
         self.message.take().unwrap_or_default()
     }
 
+    /// Why this edit failed, in a line: its validation findings, or the first line of its
+    /// message
+    pub(crate) fn failure_summary(&self) -> String {
+        if self.violations.is_empty() {
+            return self
+                .message
+                .as_deref()
+                .and_then(|message| message.lines().next())
+                .unwrap_or("no change was made")
+                .to_string();
+        }
+        let mut findings = Vec::<String>::new();
+        for violation in &self.violations {
+            let finding = format!("{} at {}", violation.message, violation.location());
+            if !findings.contains(&finding) {
+                findings.push(finding);
+            }
+        }
+        findings.join("; ")
+    }
+
     pub(crate) fn output(&mut self) -> Option<String> {
         self.output.take()
     }
+
+    /// Whether this edit only failed validation and can be staged anyway at the warn level
+    pub(crate) fn has_fallback(&self) -> bool {
+        self.fallback_output.is_some()
+    }
+
+    /// Take the unformatted output of an edit that only failed validation
+    pub(crate) fn take_fallback_output(&mut self) -> Option<String> {
+        self.fallback_output.take()
+    }
+
+    pub(crate) fn take_warning(&mut self) -> Option<String> {
+        self.warning.take()
+    }
+
+    pub(crate) fn take_violations(&mut self) -> Vec<Violation> {
+        std::mem::take(&mut self.violations)
+    }
 }
@@ -6,24 +6,38 @@
 //! - Smart location selection and ranking
 //! - Fallback handling for ambiguous matches
 //! - Integration with selector targeting system
-//! - Support for all operation types
+//! - Support for all operation types, with attributes added by each language's editor
+//! - Inserts spaced from their surroundings once, from where they go, rather than tried with
+//!   each kind of spacing, except that content running into a word is tried as written first
+//! - At most [`ParseLimits::max_candidates`](super::ParseLimits) candidates, each of which is
+//!   parsed and validated
+//! - Inserts after a node placed after its whole declaration, with the declaration's
+//!   attributes and trailing comment, and node replacements optionally spanning the
+//!   declaration's leading comments and attributes
+//! - Range replacements from each anchor to the nearest `end` after it, preferring the
+//!   nearest one at the same nesting level so the range does not cut through other code
 
 use std::iter::Iterator;
 
-use tree_sitter::Tree;
+use tree_sitter::{Node, Tree};
 
 use crate::{
-    editor::EditPosition,
-    selector::{Operation, Selector},
+    comby::Template,
+    editor::{EditPosition, TargetNode},
+    error::SemanticEditError,
+    languages::traits::{DeclarationEdit, LanguageEditor},
+    selector::{Operation, Selector, SelectorIssue},
 };
 
+use super::separators::{self, InsertSide, SeparatedEdit};
 use super::{Edit, Editor};
 
 pub(super) struct EditIterator<'editor, 'language> {
     editor: &'editor Editor<'language>,
     selector: &'editor Selector,
     source_code: &'editor str,
-    tree: &'editor Tree,
+    /// None for files edited as text because they are over the parse limits
+    tree: Option<&'editor Tree>,
     staged_edit: Option<&'editor EditPosition>,
     edits: Option<Vec<Edit<'editor, 'language>>>,
     current_index: usize,
@@ -42,112 +56,314 @@ impl<'editor, 'language> EditIterator<'editor, 'language> {
             editor,
             selector,
             source_code,
-            tree,
+            tree: tree.as_ref(),
             staged_edit: staged_edit.as_ref(),
             edits: None,
             current_index: 0,
         }
     }
 
-    fn find_edits(&self) -> Result<Vec<Edit<'editor, 'language>>, String> {
+    fn find_edits(&self) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
         let source_code: &str = self.source_code;
-        let tree: &Tree = self.tree;
         self.selector.validate()?;
         let Selector {
             operation,
             anchor,
             end,
+            end_inclusive,
+            include_leading_trivia,
         } = &self.selector;
+        let tree = || {
+            self.tree.ok_or_else(|| SemanticEditError::EditNotApplied {
+                message: format!(
+                    "{} needs a syntax tree, but {}. Use insert_before, insert_after, \
+                     replace_exact, or replace_range instead.",
+                    operation.as_str(),
+                    self.editor
+                        .text_only
+                        .as_deref()
+                        .unwrap_or("the file has not been parsed")
+                ),
+            })
+        };
 
         match operation {
             Operation::InsertBefore => self.find_insert_positions(anchor, true, source_code),
             Operation::InsertAfter => self.find_insert_positions(anchor, false, source_code),
-            Operation::InsertAfterNode => {
-                self.find_after_ast_insert_positions(anchor, source_code, tree)
+            Operation::InsertAfterNode => self.find_after_ast_insert_positions(anchor, tree()?),
+            Operation::ReplaceRange => {
+                self.find_range_matches(anchor, end.as_deref(), *end_inclusive)
             }
-            Operation::ReplaceRange => self.find_range_matches(anchor, end.as_deref(), source_code),
             Operation::ReplaceExact => self.find_exact_matches(anchor, source_code),
-            Operation::ReplaceNode => self.select_ast_node(anchor, source_code, tree),
+            Operation::ReplaceNode => {
+                self.select_ast_node(anchor, tree()?, *include_leading_trivia)
+            }
+            Operation::RewritePattern => self.find_template_matches(anchor, source_code),
+            Operation::AddAttribute => self.find_declaration_edits(
+                anchor,
+                tree()?,
+                "the attribute or decorator to add",
+                |editor, node, content| editor.add_attribute(node, source_code, content),
+            ),
+            Operation::AddDocComment => self.find_declaration_edits(
+                anchor,
+                tree()?,
+                "the documentation to add",
+                |editor, node, content| editor.add_doc_comment(node, source_code, content),
+            ),
         }
     }
 
-    fn ensure_text_ranges_loaded(&mut self) -> Result<(), String> {
+    fn ensure_text_ranges_loaded(&mut self) -> Result<(), SemanticEditError> {
         if self.edits.is_none() {
-            self.edits = Some(self.find_edits()?);
+            let mut edits = self.fix_separators(self.find_edits()?);
+            if let Some(candidate) = self.editor.candidate {
+                let count = edits
+                    .iter()
+                    .filter_map(|edit| edit.anchor_index)
+                    .max()
+                    .map_or(0, |last| last + 1);
+                edits.retain(|edit| edit.anchor_index == Some(candidate));
+                if edits.is_empty() {
+                    return Err(SemanticEditError::CandidateNotFound { candidate, count });
+                }
+            }
+            edits.truncate(self.editor.parse_limits.max_candidates.max(1));
+            self.edits = Some(edits);
         }
         Ok(())
     }
 
+    /// Where each match of the anchor starts
+    fn anchor_positions(
+        &self,
+        anchor: &str,
+    ) -> Result<Vec<(usize, &'editor str)>, SemanticEditError> {
+        let from_positions: Vec<_> = self.source_code.match_indices(anchor).collect();
+        if from_positions.is_empty() {
+            return Err(self.anchor_not_found(anchor));
+        }
+        Ok(from_positions)
+    }
+
+    /// Where each match of a range's `end` starts
+    fn end_positions(
+        &self,
+        anchor: &str,
+        end: &str,
+    ) -> Result<Vec<(usize, &'editor str)>, SemanticEditError> {
+        let to_positions: Vec<_> = self.source_code.match_indices(end).collect();
+        if to_positions.is_empty() {
+            return Err(SemanticEditError::RangeNotFound {
+                anchor: anchor.to_string(),
+                end: end.to_string(),
+                path: self.path(),
+            });
+        }
+        Ok(to_positions)
+    }
+
+    fn anchor_not_found(&self, anchor: &str) -> SemanticEditError {
+        SemanticEditError::AnchorNotFound {
+            anchor: anchor.to_string(),
+            path: self.path(),
+            suggestions: String::new(),
+        }
+    }
+
+    fn path(&self) -> String {
+        self.editor.file_path.display().to_string()
+    }
+
     fn build_edit(&self, start_byte: usize) -> Edit<'editor, 'language> {
-        Edit::new(
-            self.editor,
-            EditPosition {
-                start_byte,
-                end_byte: None,
-            },
-        )
+        Edit::new(self.editor, EditPosition::new(start_byte, None))
     }
 
-    /// Add spacing and newline variations to a list of edits
-    ///
-    /// This consolidates the common pattern of generating additional edit variations
-    /// with space and newline prefixes/suffixes for better placement options.
-    fn add_spacing_variations(&self, edits: &mut Vec<Edit<'editor, 'language>>, before: bool) {
-        let original_edits = edits.clone();
-        for edit in &original_edits {
-            if before {
-                edits.push(edit.clone().with_content(format!("{} ", &edit.content)));
-                edits.push(edit.clone().with_content(format!("{}\n", &edit.content)));
-            } else {
-                edits.push(edit.clone().with_content(format!(" {}", &edit.content)));
-                edits.push(edit.clone().with_content(format!("\n{}", &edit.content)));
+    /// Separate each insert's content from the text it is inserted next to, as its position
+    /// calls for, so that each position is parsed and validated once, or twice when the
+    /// content may run into a word on purpose
+    fn space_inserts(
+        &self,
+        edits: Vec<Edit<'editor, 'language>>,
+        before: bool,
+    ) -> Vec<Edit<'editor, 'language>> {
+        let mut spaced_edits = Vec::with_capacity(edits.len());
+        for mut edit in edits {
+            match spaced_insert(
+                self.source_code,
+                edit.position.start_byte,
+                &edit.content,
+                before,
+            ) {
+                Spacing::AsWritten => {}
+                Spacing::Spaced(content) => edit.content = content.into(),
+                Spacing::IfInvalid(content) => {
+                    let spaced = edit.clone().with_content(content);
+                    spaced_edits.push(edit);
+                    edit = spaced;
+                }
+            }
+            spaced_edits.push(edit);
+        }
+        spaced_edits
+    }
+
+    /// Fix up the separators around inserts and replacements in comma- and
+    /// semicolon-separated lists, see [`separators`]. Inserts that are not whole elements
+    /// are tried as written first, and with their separator only if that is not valid.
+    fn fix_separators(
+        &self,
+        edits: Vec<Edit<'editor, 'language>>,
+    ) -> Vec<Edit<'editor, 'language>> {
+        let Some(tree) = self.tree else {
+            return edits;
+        };
+        let side = match self.selector.operation {
+            Operation::InsertBefore => Some(InsertSide::Before),
+            Operation::InsertAfter | Operation::InsertAfterNode => Some(InsertSide::After),
+            Operation::ReplaceExact | Operation::ReplaceNode | Operation::ReplaceRange => None,
+            Operation::RewritePattern | Operation::AddAttribute | Operation::AddDocComment => {
+                return edits;
+            }
+        };
+        let mut fixed_edits = Vec::with_capacity(edits.len());
+        for mut edit in edits {
+            let EditPosition {
+                start_byte,
+                end_byte,
+                ..
+            } = edit.position;
+            match (side, end_byte) {
+                (Some(side), None) => {
+                    match separators::fix_insert(
+                        tree,
+                        self.source_code,
+                        start_byte,
+                        &edit.content,
+                        side,
+                    ) {
+                        Some(content) if separators::is_whole_element(&edit.content, side) => {
+                            edit.content = content.into();
+                        }
+                        Some(content) => {
+                            let separated = edit.clone().with_content(content);
+                            fixed_edits.push(edit);
+                            edit = separated;
+                        }
+                        None => {}
+                    }
+                }
+                (None, Some(end_byte)) => {
+                    if let Some(SeparatedEdit { range, content }) = separators::fix_replacement(
+                        tree,
+                        self.source_code,
+                        start_byte..end_byte,
+                        &edit.content,
+                    ) {
+                        edit.position.start_byte = range.start;
+                        edit.position.end_byte = Some(range.end);
+                        edit.content = content.into();
+                    }
+                }
+                _ => {}
             }
+            fixed_edits.push(edit);
         }
+        fixed_edits
     }
 
+    /// Inserts after the declaration each match of the anchor belongs to, with its
+    /// attributes and any comment on its last line, see
+    /// [`crate::languages::LanguageCommon::logical_unit`]
     fn find_after_ast_insert_positions(
         &self,
         anchor: &str,
-        source_code: &str,
         tree: &Tree,
-    ) -> Result<Vec<Edit<'editor, 'language>>, String> {
-        let mut edits = self
-            .select_ast_node(anchor, source_code, tree)?
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
+        let edits = self
+            .anchor_nodes(anchor, tree)?
             .into_iter()
-            .filter_map(|edit| {
-                edit.position
-                    .end_byte
-                    .map(|start_byte| self.build_edit(start_byte))
+            .map(|(anchor_index, node)| {
+                let unit = self.editor.language.logical_unit(node);
+                self.build_edit(unit.end_byte)
+                    .with_anchor_index(anchor_index)
+                    .with_target_node(TargetNode::from(unit.declaration))
             })
             .collect::<Vec<_>>();
 
-        self.add_spacing_variations(&mut edits, false);
-        Ok(edits)
+        Ok(self.space_inserts(edits, false))
     }
 
     fn find_explicit_range(
         &self,
         anchor: &str,
         end: &str,
-        source_code: &str,
-    ) -> Result<Vec<Edit<'editor, 'language>>, String> {
+        end_inclusive: bool,
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
+        let to_positions = self.end_positions(anchor, end)?;
+        let end_byte = |to_byte: usize| {
+            if end_inclusive {
+                to_byte + end.len()
+            } else {
+                to_byte
+            }
+        };
         let mut ranges = Vec::new();
 
-        for (from_byte, _) in from_positions(source_code, anchor)? {
-            for (to_byte, _) in to_positions(source_code, end)? {
-                if to_byte >= from_byte + anchor.len() {
-                    ranges.push(
-                        self.build_edit(from_byte)
-                            .with_end_byte(to_byte + end.len()),
-                    );
+        for (anchor_index, (from_byte, _)) in self.anchor_positions(anchor)?.into_iter().enumerate()
+        {
+            let mut ends = to_positions
+                .iter()
+                .map(|(to_byte, _)| *to_byte)
+                .filter(|to_byte| *to_byte >= from_byte + anchor.len());
+            let Some(nearest) = ends.next() else {
+                continue;
+            };
+            let range = |to_byte: usize, span_reason| {
+                self.build_edit(from_byte)
+                    .with_end_byte(end_byte(to_byte))
+                    .with_anchor_index(anchor_index)
+                    .with_span_reason(span_reason)
+            };
+            let Some(tree) = self.tree else {
+                ranges.push(range(nearest, "the nearest `end` after the anchor"));
+                continue;
+            };
+            let same_level = [nearest]
+                .into_iter()
+                .chain(ends)
+                .find(|to_byte| same_nesting_level(tree, from_byte, end_byte(*to_byte)));
+            match same_level {
+                Some(to_byte) if to_byte == nearest => ranges.push(range(
+                    nearest,
+                    "the nearest `end` after the anchor, at the same nesting level",
+                )),
+                Some(to_byte) => {
+                    ranges.push(range(
+                        to_byte,
+                        "the nearest `end` after the anchor at the same nesting level; nearer \
+                         ones would cut through other code",
+                    ));
+                    ranges.push(range(
+                        nearest,
+                        "the nearest `end` after the anchor, which is at a different nesting \
+                         level; the nearest one at the same level did not leave the file valid",
+                    ));
                 }
+                None => ranges.push(range(
+                    nearest,
+                    "the nearest `end` after the anchor; no `end` is at the anchor's nesting \
+                     level, so the range cuts through other code",
+                )),
             }
         }
 
         if ranges.is_empty() {
-            Err(format!(
-                "No valid range found from \"{anchor}\" to \"{end}\""
-            ))
+            Err(SemanticEditError::RangeNotFound {
+                anchor: anchor.to_string(),
+                end: end.to_string(),
+                path: self.path(),
+            })
         } else {
             Ok(ranges)
         }
@@ -158,23 +374,24 @@ impl<'editor, 'language> EditIterator<'editor, 'language> {
         anchor: &str,
         before: bool,
         source_code: &str,
-    ) -> Result<Vec<Edit<'editor, 'language>>, String> {
-        let mut edits = source_code
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
+        let edits = source_code
             .match_indices(anchor)
-            .map(|(byte_offset, _)| {
+            .enumerate()
+            .map(|(anchor_index, (byte_offset, _))| {
                 self.build_edit(if before {
                     byte_offset
                 } else {
                     byte_offset + anchor.len()
                 })
+                .with_anchor_index(anchor_index)
             })
             .collect::<Vec<_>>();
 
         if edits.is_empty() {
-            Err(format!("Anchor text \"{anchor}\" not found in source"))
+            Err(self.anchor_not_found(anchor))
         } else {
-            self.add_spacing_variations(&mut edits, before);
-            Ok(edits)
+            Ok(self.space_inserts(edits, before))
         }
     }
 
@@ -182,61 +399,226 @@ impl<'editor, 'language> EditIterator<'editor, 'language> {
         &self,
         exact_text: &str,
         source_code: &str,
-    ) -> Result<Vec<Edit<'editor, 'language>>, String> {
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
         let positions = source_code
             .match_indices(exact_text)
-            .map(|(start_byte, matched)| {
+            .enumerate()
+            .map(|(anchor_index, (start_byte, matched))| {
                 self.build_edit(start_byte)
                     .with_end_byte(start_byte + matched.len())
+                    .with_anchor_index(anchor_index)
             })
             .collect::<Vec<_>>();
 
         if positions.is_empty() {
-            Err(format!("Exact text \"{exact_text}\" not found in source"))
+            Err(self.anchor_not_found(exact_text))
         } else {
             Ok(positions)
         }
     }
 
+    fn find_template_matches(
+        &self,
+        anchor: &str,
+        source_code: &str,
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
+        let template = Template::parse(anchor);
+        if !template.has_literal() {
+            return Err(SemanticEditError::SelectorInvalid {
+                issues: vec![SelectorIssue::invalid(
+                    "anchor",
+                    format!(
+                        "Template \"{template}\" has no literal text, so it would match \
+                         anything. Add the code around its holes."
+                    ),
+                )],
+            });
+        }
+        let rewrite = Template::parse(&self.editor.content);
+        let captured = template.hole_names();
+        let unknown = rewrite
+            .hole_names()
+            .into_iter()
+            .filter(|name| !captured.contains(name))
+            .map(|name| format!(":[{name}]"))
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(SemanticEditError::SelectorInvalid {
+                issues: vec![SelectorIssue::invalid(
+                    "content",
+                    format!(
+                        "The content uses {}, which the anchor template does not capture",
+                        unknown.join(", ")
+                    ),
+                )],
+            });
+        }
+
+        let edits = template
+            .matches(source_code)
+            .into_iter()
+            .enumerate()
+            .map(|(anchor_index, template_match)| {
+                self.build_edit(template_match.start_byte)
+                    .with_end_byte(template_match.end_byte)
+                    .with_content(rewrite.fill(&template_match.holes))
+                    .with_anchor_index(anchor_index)
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            Err(self.anchor_not_found(anchor))
+        } else {
+            Ok(edits)
+        }
+    }
+
+    /// Edits adding to the declaration containing each match of the anchor, as `add`
+    /// computes them with the language's editor from the content
+    fn find_declaration_edits<F>(
+        &self,
+        anchor: &str,
+        tree: &Tree,
+        content_needed: &str,
+        add: F,
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError>
+    where
+        F: for<'tree> Fn(
+            &dyn LanguageEditor,
+            Node<'tree>,
+            &str,
+        ) -> Result<DeclarationEdit<'tree>, String>,
+    {
+        let operation = self.selector.operation;
+        let content = self.editor.content.trim();
+        if content.is_empty() {
+            return Err(SemanticEditError::SelectorInvalid {
+                issues: vec![SelectorIssue::required(
+                    "content",
+                    format!("{operation} needs {content_needed} as content"),
+                )],
+            });
+        }
+        let anchor = anchor.trim().lines().next().unwrap_or_default().trim();
+
+        let mut edits = Vec::new();
+        let mut reasons = Vec::new();
+        for (anchor_index, (from, anchor)) in self.anchor_positions(anchor)?.into_iter().enumerate()
+        {
+            let Some(node) = tree
+                .root_node()
+                .named_descendant_for_byte_range(from, from + anchor.len())
+            else {
+                continue;
+            };
+            match add(self.editor.language.editor(), node, content) {
+                Ok(DeclarationEdit {
+                    declaration,
+                    range,
+                    text,
+                }) => {
+                    let edit = self.build_edit(range.start);
+                    let edit = if range.is_empty() {
+                        edit
+                    } else {
+                        edit.with_end_byte(range.end)
+                    };
+                    edits.push(
+                        edit.with_content(text)
+                            .with_anchor_index(anchor_index)
+                            .with_target_node(TargetNode::from(declaration)),
+                    );
+                }
+                Err(reason) => {
+                    if !reasons.contains(&reason) {
+                        reasons.push(reason);
+                    }
+                }
+            }
+        }
+
+        if edits.is_empty() {
+            Err(SemanticEditError::EditNotApplied {
+                message: format!(
+                    "Cannot {operation} in this {} file: {}",
+                    self.editor.language,
+                    reasons.join("; ")
+                ),
+            })
+        } else {
+            Ok(edits)
+        }
+    }
+
     fn find_range_matches(
         &self,
         anchor: &str,
         end: Option<&str>,
-        source_code: &str,
-    ) -> Result<Vec<Edit<'editor, 'language>>, String> {
+        end_inclusive: bool,
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
         if let Some(end) = end {
-            self.find_explicit_range(anchor, end, source_code)
+            self.find_explicit_range(anchor, end, end_inclusive)
         } else {
-            Err("end is required for range replacement".to_string())
+            Err(SemanticEditError::SelectorInvalid {
+                issues: vec![SelectorIssue::required(
+                    "end",
+                    "End is required for range replacement",
+                )],
+            })
         }
     }
 
+    /// The node containing each match of the anchor, starting at its declaration's first
+    /// documentation comment or attribute if `include_leading_trivia` is set
     fn select_ast_node(
         &self,
         anchor: &str,
-        source_code: &str,
         tree: &Tree,
-    ) -> Result<Vec<Edit<'editor, 'language>>, String> {
+        include_leading_trivia: bool,
+    ) -> Result<Vec<Edit<'editor, 'language>>, SemanticEditError> {
+        Ok(self
+            .anchor_nodes(anchor, tree)?
+            .into_iter()
+            .map(|(anchor_index, node)| {
+                let (start_byte, node) = if include_leading_trivia {
+                    let unit = self.editor.language.logical_unit(node);
+                    (unit.start_byte, unit.declaration)
+                } else {
+                    (node.start_byte(), node)
+                };
+                self.build_edit(start_byte)
+                    .with_end_byte(node.end_byte())
+                    .with_anchor_index(anchor_index)
+                    .with_target_node(TargetNode::from(node))
+            })
+            .collect())
+    }
+
+    /// The smallest node containing each match of the first line of the anchor, with the
+    /// match's index
+    fn anchor_nodes<'tree>(
+        &self,
+        anchor: &str,
+        tree: &'tree Tree,
+    ) -> Result<Vec<(usize, Node<'tree>)>, SemanticEditError> {
         let anchor = anchor.trim().lines().next().unwrap_or_default();
 
-        Ok(from_positions(source_code, anchor.trim())?
+        Ok(self
+            .anchor_positions(anchor.trim())?
             .into_iter()
-            .filter_map(|(from, anchor)| {
+            .enumerate()
+            .filter_map(|(anchor_index, (from, anchor))| {
                 let from_end = from + anchor.len();
                 tree.root_node()
                     .named_descendant_for_byte_range(from, from_end)
                     .or_else(|| tree.root_node().descendant_for_byte_range(from, from_end))
-                    .map(|node| {
-                        self.build_edit(node.start_byte())
-                            .with_end_byte(node.end_byte())
-                    })
+                    .map(|node| (anchor_index, node))
             })
             .collect())
     }
 }
 
 impl<'editor, 'language> Iterator for EditIterator<'editor, 'language> {
-    type Item = Result<Edit<'editor, 'language>, String>;
+    type Item = Result<Edit<'editor, 'language>, SemanticEditError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // If we have a staged edit, return it first and only once
@@ -253,7 +635,7 @@ impl<'editor, 'language> Iterator for EditIterator<'editor, 'language> {
         let text_ranges = match self.edits.as_ref() {
             Some(edits) => edits,
             None => {
-                return Some(Err("EditIterator in invalid state".to_string()));
+                return Some(Err(SemanticEditError::EditIteratorInvalidState));
             }
         };
 
@@ -268,18 +650,67 @@ impl<'editor, 'language> Iterator for EditIterator<'editor, 'language> {
     }
 }
 
-fn from_positions<'a>(source_code: &'a str, anchor: &str) -> Result<Vec<(usize, &'a str)>, String> {
-    let from_positions: Vec<_> = source_code.match_indices(anchor).collect();
-    if from_positions.is_empty() {
-        return Err(format!("From text \"{anchor}\" not found in source"));
-    }
-    Ok(from_positions)
+/// How inserted content is spaced from the text it goes next to
+enum Spacing {
+    /// The content is inserted as written
+    AsWritten,
+    /// The content is inserted with this spacing
+    Spaced(String),
+    /// The content is inserted as written, or with this spacing if that is not valid, since
+    /// running into an adjacent word may be what it is for, as in extending an identifier
+    IfInvalid(String),
 }
 
-fn to_positions<'a>(source_code: &'a str, end: &str) -> Result<Vec<(usize, &'a str)>, String> {
-    let to_positions: Vec<_> = source_code.match_indices(end).collect();
-    if to_positions.is_empty() {
-        return Err(format!("To text \"{end}\" not found in source"));
+/// How `content` is spaced when inserted at `at`, before the anchor when `before` and after
+/// it otherwise:
+/// - Content that already starts or ends with whitespace, or goes next to whitespace, is
+///   left alone
+/// - Content inserted at the start or end of the anchor's line goes on its own line, with
+///   the anchor line's indentation
+/// - Otherwise, a space keeps it from running into an adjacent word, if it is not valid
+///   without one
+fn spaced_insert(source: &str, at: usize, content: &str, before: bool) -> Spacing {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let line_start = source[..at].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = source[at..]
+        .find('\n')
+        .map_or(source.len(), |newline| at + newline);
+    let indent = &source[line_start..]
+        [..source[line_start..].len() - source[line_start..].trim_start_matches([' ', '\t']).len()];
+    if before {
+        let next = source[at..].chars().next();
+        if content.ends_with(char::is_whitespace) || next.is_none_or(char::is_whitespace) {
+            Spacing::AsWritten
+        } else if source[line_start..at].trim().is_empty() {
+            Spacing::Spaced(format!("{content}\n{indent}"))
+        } else if content.ends_with(is_word) && next.is_some_and(is_word) {
+            Spacing::IfInvalid(format!("{content} "))
+        } else {
+            Spacing::AsWritten
+        }
+    } else {
+        let previous = source[..at].chars().next_back();
+        if content.starts_with(char::is_whitespace) || previous.is_none_or(char::is_whitespace) {
+            Spacing::AsWritten
+        } else if source[at..line_end].trim().is_empty() {
+            Spacing::Spaced(format!("\n{indent}{content}"))
+        } else if content.starts_with(is_word) && previous.is_some_and(is_word) {
+            Spacing::IfInvalid(format!(" {content}"))
+        } else {
+            Spacing::AsWritten
+        }
     }
-    Ok(to_positions)
+}
+
+/// Whether replacing `start..end` leaves every other node whole: no child of the smallest
+/// node containing the range starts before it and ends inside it, or the other way around
+fn same_nesting_level(tree: &Tree, start: usize, end: usize) -> bool {
+    let Some(parent) = tree.root_node().descendant_for_byte_range(start, end) else {
+        return false;
+    };
+    let mut cursor = parent.walk();
+    parent.children(&mut cursor).all(|child| {
+        let cuts = |byte: usize| child.start_byte() < byte && byte < child.end_byte();
+        !cuts(start) && !cuts(end)
+    })
 }
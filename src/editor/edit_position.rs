@@ -7,11 +7,73 @@
 //! - Serialization support for staging operations
 //! - Integration with rope-based text manipulation
 //! - UTF-8 safe positioning
+//! - Line and column coordinates for clients that display or map positions in editor buffers
 
+use ropey::Rope;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Copy)]
 pub struct EditPosition {
     pub start_byte: usize,
     pub end_byte: Option<usize>, // None for insert, Some for replace
+    /// Line and column of `start_byte`, in reports and staged operations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<LineColumn>,
+    /// Line and column of `end_byte`, in reports and staged operations of replacements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<LineColumn>,
+}
+
+impl EditPosition {
+    /// An edit at `start_byte`, replacing up to `end_byte` if given
+    pub fn new(start_byte: usize, end_byte: Option<usize>) -> Self {
+        Self {
+            start_byte,
+            end_byte,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// This position with its line and column coordinates in `source`
+    pub fn with_coordinates(self, source: &Rope) -> Self {
+        Self {
+            start: LineColumn::at(source, self.start_byte),
+            end: self
+                .end_byte
+                .and_then(|end_byte| LineColumn::at(source, end_byte)),
+            ..self
+        }
+    }
+}
+
+/// A 1-based line and column in source, with the column counted both in bytes and in the
+/// UTF-16 code units editors and language servers count positions in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+    pub utf16_column: usize,
+}
+
+impl LineColumn {
+    /// The line and column of a byte offset, or None if it is past the end of the source or
+    /// not on a character boundary
+    pub fn at(source: &Rope, byte: usize) -> Option<Self> {
+        if byte > source.len_bytes() {
+            return None;
+        }
+        let char_index = source.try_byte_to_char(byte).ok()?;
+        if source.char_to_byte(char_index) != byte {
+            return None;
+        }
+        let line = source.byte_to_line(byte);
+        let line_start = source.line_to_char(line);
+        Some(Self {
+            line: line + 1,
+            column: byte - source.line_to_byte(line) + 1,
+            utf16_column: source.char_to_utf16_cu(char_index) - source.char_to_utf16_cu(line_start)
+                + 1,
+        })
+    }
 }
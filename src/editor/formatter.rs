@@ -8,6 +8,11 @@
 //! - Supports all languages with custom formatters (Rust, Python, TOML, etc.)
 //! - Falls back to no-op formatting for languages without formatters
 //! - Provides clear error messages when formatting fails
+//! - Optionally keeps only the formatting changes within the edited lines
+//! - Limits how long formatter processes run and how much they output, leaving edits
+//!   unformatted with a warning when a formatter exceeds them
+//! - Honors project formatter configuration (`rustfmt.toml`, `taplo.toml`, `.prettierrc`)
+//!   found above the edited file, falling back to the indentation from `.editorconfig`
 //!
 //! ## Example
 //!
@@ -15,22 +20,86 @@
 //! use semantic_code_edit_mcp::editor::formatter::Formatter;
 //!
 //! // Format code using language-specific formatter
-//! match Formatter::format_code(language, source_code) {
+//! match Formatter::format_code(language, source_code, &FormatContext::default()) {
 //!     Ok(formatted) => println!("Formatted code: {}", formatted),
 //!     Err(e) => eprintln!("Formatting failed: {}", e),
 //! }
 //! ```
 
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::error::SemanticEditError;
 use crate::languages::LanguageCommon;
+use crate::languages::traits::FormatContext;
 use anyhow::{Result, anyhow};
+use diffy::{DiffOptions as PatchOptions, Line};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How much of a file is formatted after an edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatMode {
+    /// Format the whole file (the default)
+    #[default]
+    Always,
+    /// Never format, keeping the edit exactly as written
+    Never,
+    /// Only keep formatting changes that touch the lines covered by the edit
+    EditedRegion,
+}
+
+impl FormatMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FormatMode::Always => "always",
+            FormatMode::Never => "never",
+            FormatMode::EditedRegion => "edited_region",
+        }
+    }
+}
+
+/// Default time a formatter process may run
+pub const DEFAULT_FORMAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default amount of output a formatter process may write
+pub const DEFAULT_FORMAT_MAX_OUTPUT_BYTES: usize = 32 * 1024 * 1024;
+
+/// Limits on formatter processes, which are killed when they exceed them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatLimits {
+    pub timeout: Duration,
+    pub max_output_bytes: usize,
+}
+
+impl Default for FormatLimits {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_FORMAT_TIMEOUT,
+            max_output_bytes: DEFAULT_FORMAT_MAX_OUTPUT_BYTES,
+        }
+    }
+}
 
 /// Handles code formatting for different languages
 pub struct Formatter;
 
 impl Formatter {
     /// Formats source code using language-specific formatter
-    pub fn format_code(language: &LanguageCommon, source: &str) -> Result<String> {
-        language.editor().format_code(source).map_err(|e| {
+    pub fn format_code(
+        language: &LanguageCommon,
+        source: &str,
+        context: &FormatContext,
+    ) -> Result<String> {
+        language.editor().format_code(source, context).map_err(|e| {
+            // a missing or misbehaving formatter says nothing about the edit, and neither does
+            // a cancelled request, so these are passed on as is
+            if e.downcast_ref().is_some_and(|error: &SemanticEditError| {
+                error.skips_formatting() || matches!(error, SemanticEditError::Cancelled)
+            }) {
+                return e;
+            }
             anyhow!(
                 "The formatter has encountered the following error making \
                  that change, so the file has not been modified. The tool has \
@@ -40,4 +109,94 @@ impl Formatter {
             )
         })
     }
+
+    /// Formats source code, keeping only the changes that touch the given 0-based lines
+    ///
+    /// The whole file is formatted and the resulting changes outside the region are discarded,
+    /// so untouched code keeps its existing formatting.
+    pub fn format_region(
+        language: &LanguageCommon,
+        source: &str,
+        context: &FormatContext,
+        lines: RangeInclusive<usize>,
+    ) -> Result<String> {
+        let formatted = Self::format_code(language, source, context)?;
+        Ok(splice_region(source, &formatted, lines))
+    }
+}
+
+/// Applies the changes from `source` to `formatted` that overlap `lines`, leaving the rest of `source` as-is
+fn splice_region(source: &str, formatted: &str, lines: RangeInclusive<usize>) -> String {
+    let patch = PatchOptions::new()
+        .set_context_len(0)
+        .create_patch(source, formatted);
+    let source_lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(source.len());
+    let mut next_line = 0;
+
+    for hunk in patch.hunks() {
+        let old_range = hunk.old_range();
+        // an empty range starts at the line before the insertion point
+        let (start, overlaps) = if old_range.is_empty() {
+            let start = old_range.start();
+            (start, *lines.start() <= start && start <= lines.end() + 1)
+        } else {
+            let start = old_range.start() - 1;
+            let end = start + old_range.len() - 1;
+            (start, start <= *lines.end() && end >= *lines.start())
+        };
+        let end = start + old_range.len();
+        let inserted: Vec<&str> = hunk
+            .lines()
+            .iter()
+            .filter_map(|line| match line {
+                Line::Insert(text) => Some(*text),
+                _ => None,
+            })
+            .collect();
+
+        out.extend(source_lines[next_line..start].iter().copied());
+        if inserted.len() == old_range.len() {
+            // a line-for-line rewrite, so each line can be kept or reformatted on its own
+            for (index, formatted_line) in inserted.into_iter().enumerate() {
+                if lines.contains(&(start + index)) {
+                    out.push_str(formatted_line);
+                } else {
+                    out.push_str(source_lines[start + index]);
+                }
+            }
+        } else if overlaps {
+            out.extend(inserted);
+        } else {
+            out.extend(source_lines[start..end].iter().copied());
+        }
+        next_line = end;
+    }
+
+    out.extend(source_lines[next_line..].iter().copied());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splice_keeps_changes_inside_region_only() {
+        let source = "a  =  1\nb  =  2\nc  =  3\n";
+        let formatted = "a = 1\nb = 2\nc = 3\n";
+        assert_eq!(
+            splice_region(source, formatted, 1..=1),
+            "a  =  1\nb = 2\nc  =  3\n"
+        );
+        assert_eq!(splice_region(source, formatted, 0..=2), formatted);
+    }
+
+    #[test]
+    fn splice_applies_insertions_at_region_boundary() {
+        let source = "a\nb\nc\n";
+        let formatted = "a\nb\n\nc\n";
+        assert_eq!(splice_region(source, formatted, 1..=1), formatted);
+        assert_eq!(splice_region(source, formatted, 0..=0), source);
+    }
 }
@@ -0,0 +1,188 @@
+//! Detection of edits that were already applied.
+//!
+//! An agent that retries an edit after a timeout cannot tell whether the first attempt
+//! landed, and inserting again duplicates the block. This module checks whether an edit's
+//! content is already where the edit would put it. Features include:
+//! - Inserts are applied when the content is next to the anchor, or after its node
+//! - Replacements are applied when the content is in the file and the anchor only appears
+//!   inside it, which is also the case once the anchor itself has been replaced
+//! - Whitespace is ignored, since formatting may have reflowed the content
+//! - Pattern rewrites, and attributes and documentation, which are never duplicated, are
+//!   not checked
+//!
+//! Without the check, an insert whose content is identical to the text right next to where
+//! it goes is still flagged as a likely duplicate, see [`adjacent_duplicate`].
+
+use std::ops::Range;
+
+use crate::languages::LanguageCommon;
+use crate::selector::{Operation, Selector};
+
+/// Why the edit is already applied to `source`, or None if it is not
+pub fn already_applied(
+    language: &LanguageCommon,
+    selector: &Selector,
+    content: &str,
+    source: &str,
+) -> Option<String> {
+    let content = content.trim();
+    if content.is_empty() {
+        return None;
+    }
+    let line = |byte: usize| source[..byte].matches('\n').count() + 1;
+    match selector.operation {
+        Operation::InsertBefore => source
+            .match_indices(&selector.anchor)
+            .find(|(start, _)| ends_with_ignoring_whitespace(&source[..*start], content))
+            .map(|(start, _)| {
+                format!(
+                    "the content is already right before the anchor on line {}",
+                    line(start)
+                )
+            }),
+        Operation::InsertAfter => source
+            .match_indices(&selector.anchor)
+            .map(|(start, anchor)| start + anchor.len())
+            .find(|end| starts_with_ignoring_whitespace(&source[*end..], content))
+            .map(|end| {
+                format!(
+                    "the content already follows the anchor on line {}",
+                    line(end)
+                )
+            }),
+        Operation::InsertAfterNode => {
+            let tree = language.parser().ok()?.parse(source, None)?;
+            node_anchor_ranges(selector, source)
+                .into_iter()
+                .filter_map(|range| {
+                    tree.root_node()
+                        .named_descendant_for_byte_range(range.start, range.end)
+                })
+                .map(|node| language.logical_unit(node))
+                .find(|unit| starts_with_ignoring_whitespace(&source[unit.end_byte..], content))
+                .map(|unit| {
+                    format!(
+                        "the content already follows the `{}` on line {}",
+                        unit.declaration.kind(),
+                        line(unit.end_byte)
+                    )
+                })
+        }
+        Operation::ReplaceExact | Operation::ReplaceRange | Operation::ReplaceNode => {
+            let placed = find_ignoring_whitespace(source, content);
+            let first = placed.first()?;
+            let anchors = match selector.operation {
+                Operation::ReplaceNode => node_anchor_ranges(selector, source),
+                _ => source
+                    .match_indices(&selector.anchor)
+                    .map(|(start, anchor)| start..start + anchor.len())
+                    .collect(),
+            };
+            anchors
+                .iter()
+                .all(|anchor| {
+                    placed
+                        .iter()
+                        .any(|placed| placed.start <= anchor.start && anchor.end <= placed.end)
+                })
+                .then(|| {
+                    format!(
+                        "the content is already on line {}, and the anchor only appears inside it",
+                        line(first.start)
+                    )
+                })
+        }
+        Operation::RewritePattern | Operation::AddAttribute | Operation::AddDocComment => None,
+    }
+}
+
+/// Why inserting `content` at `at` looks like inserting it a second time: the text right
+/// before or after `at`, apart from whitespace, is identical to it
+pub(super) fn adjacent_duplicate(source: &str, at: usize, content: &str) -> Option<String> {
+    let content = content.trim();
+    if content.is_empty() {
+        return None;
+    }
+    let line = |byte: usize| source[..byte].matches('\n').count() + 1;
+    let before = source[..at].trim_end();
+    let after_start = at + (source[at..].len() - source[at..].trim_start().len());
+    if source[after_start..].starts_with(content) {
+        Some(format!(
+            "the inserted content is identical to the text right after it, on line {}",
+            line(after_start)
+        ))
+    } else if before.ends_with(content) {
+        Some(format!(
+            "the inserted content is identical to the text right before it, on line {}",
+            line(before.len() - content.len())
+        ))
+    } else {
+        None
+    }
+}
+
+/// Matches of the first line of the anchor, which node operations search for
+fn node_anchor_ranges(selector: &Selector, source: &str) -> Vec<Range<usize>> {
+    let anchor = selector
+        .anchor
+        .trim()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if anchor.is_empty() {
+        return Vec::new();
+    }
+    source
+        .match_indices(anchor)
+        .map(|(start, anchor)| start..start + anchor.len())
+        .collect()
+}
+
+/// The length of the prefix of `text` that matches `pattern`, ignoring whitespace in both,
+/// or None if `text` does not start with it
+fn prefix_ignoring_whitespace(text: &str, pattern: &str) -> Option<usize> {
+    let mut text_chars = text.char_indices().filter(|(_, c)| !c.is_whitespace());
+    let mut end = 0;
+    for expected in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let (index, actual) = text_chars.next()?;
+        if actual != expected {
+            return None;
+        }
+        end = index + actual.len_utf8();
+    }
+    Some(end)
+}
+
+fn starts_with_ignoring_whitespace(text: &str, prefix: &str) -> bool {
+    prefix_ignoring_whitespace(text, prefix).is_some()
+}
+
+fn ends_with_ignoring_whitespace(text: &str, suffix: &str) -> bool {
+    let mut text_chars = text.chars().rev().filter(|c| !c.is_whitespace());
+    suffix
+        .chars()
+        .rev()
+        .filter(|c| !c.is_whitespace())
+        .all(|expected| text_chars.next() == Some(expected))
+}
+
+/// Byte ranges of `source` matching `pattern`, ignoring whitespace
+fn find_ignoring_whitespace(source: &str, pattern: &str) -> Vec<Range<usize>> {
+    let Some(first) = pattern.chars().find(|c| !c.is_whitespace()) else {
+        return Vec::new();
+    };
+    let mut ranges = Vec::new();
+    let mut searched = 0;
+    while let Some(offset) = source[searched..].find(first) {
+        let start = searched + offset;
+        match prefix_ignoring_whitespace(&source[start..], pattern) {
+            Some(length) => {
+                ranges.push(start..start + length);
+                searched = start + length;
+            }
+            None => searched = start + first.len_utf8(),
+        }
+    }
+    ranges
+}
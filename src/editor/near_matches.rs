@@ -0,0 +1,119 @@
+//! Near matches for anchors that are not in the source.
+//!
+//! This module finds the lines an anchor most nearly matches, so that an edit whose anchor
+//! has a typo or stale text fails with the lines it probably meant. Lines are compared with
+//! runs of whitespace collapsed, by the edit distance between the anchor and the closest
+//! substring of the lines, so anchors covering part of a line are matched as well as whole
+//! lines. Anchors spanning several lines are compared with as many consecutive lines.
+
+/// How many near matches are suggested
+pub const SUGGESTION_COUNT: usize = 3;
+
+/// The least similarity, from 0 to 1, for a line to be suggested
+const MIN_SIMILARITY: f64 = 0.6;
+
+/// Anchors longer than this are not searched for, since the search is quadratic in it
+const MAX_ANCHOR_CHARS: usize = 400;
+
+/// Sources larger than this are not searched, so that failing edits to huge files stay fast
+const MAX_SOURCE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Lines of source that nearly match an anchor
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearMatch {
+    /// 1-based line the match starts on
+    pub line: usize,
+    /// The matching lines, trimmed
+    pub text: String,
+    pub similarity: f64,
+}
+
+/// The lines most similar to an anchor, most similar first
+pub fn near_matches(source: &str, anchor: &str, limit: usize) -> Vec<NearMatch> {
+    let anchor_lines = anchor
+        .lines()
+        .map(normalize)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let needle = anchor_lines.join("\n").chars().collect::<Vec<_>>();
+    if needle.is_empty() || needle.len() > MAX_ANCHOR_CHARS || source.len() > MAX_SOURCE_BYTES {
+        return Vec::new();
+    }
+    let max_distance = ((1.0 - MIN_SIMILARITY) * needle.len() as f64).floor() as usize;
+
+    let raw_lines = source.lines().collect::<Vec<_>>();
+    let lines = raw_lines
+        .iter()
+        .map(|line| normalize(line))
+        .collect::<Vec<_>>();
+    let window = anchor_lines.len();
+    let mut matches = Vec::new();
+    for start in 0..lines.len() {
+        if lines[start].is_empty() {
+            continue;
+        }
+        let end = (start + window).min(lines.len());
+        let haystack = lines[start..end].join("\n").chars().collect::<Vec<_>>();
+        if let Some(distance) = substring_distance(&needle, &haystack, max_distance) {
+            matches.push(NearMatch {
+                line: start + 1,
+                text: raw_lines[start..end]
+                    .iter()
+                    .map(|line| line.trim())
+                    .collect::<Vec<_>>()
+                    .join(" ⏎ "),
+                similarity: 1.0 - distance as f64 / needle.len() as f64,
+            });
+        }
+    }
+    matches.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then(a.line.cmp(&b.line))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// The near matches as "Did you mean" lines to add to an error message, or nothing if there
+/// are none
+pub fn describe(matches: &[NearMatch]) -> String {
+    if matches.is_empty() {
+        return String::new();
+    }
+    let mut description = String::from("\n\nDid you mean:");
+    for near in matches {
+        description.push_str(&format!("\n  line {}: {}", near.line, near.text));
+    }
+    description
+}
+
+/// A line with its runs of whitespace collapsed to single spaces
+fn normalize(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The fewest edits that turn `needle` into some substring of `haystack`, if that is at
+/// most `max_distance`
+fn substring_distance(needle: &[char], haystack: &[char], max_distance: usize) -> Option<usize> {
+    // a match may start anywhere in the haystack, so the first row costs nothing
+    let mut previous = vec![0; haystack.len() + 1];
+    let mut current = vec![0; haystack.len() + 1];
+    for (i, &needle_char) in needle.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &haystack_char) in haystack.iter().enumerate() {
+            let substitution = previous[j] + usize::from(needle_char != haystack_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        // every later row is at least this row's minimum
+        if current.iter().min().is_some_and(|&min| min > max_distance) {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous
+        .iter()
+        .copied()
+        .min()
+        .filter(|&distance| distance <= max_distance)
+}
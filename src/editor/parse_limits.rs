@@ -0,0 +1,41 @@
+//! Limits on parsing the file being edited.
+//!
+//! Very large or pathological files can take tree-sitter a long time to parse, and their
+//! validation queries take longer still. Files over these limits are edited as plain text:
+//! - Text operations (`insert_before`, `insert_after`, `replace_exact`, `replace_range`,
+//!   `rewrite_pattern`) are applied without parsing, validating, or formatting, with a
+//!   warning saying so
+//! - Syntax-aware operations (`insert_after_node`, `replace_node`) are refused
+//!
+//! Each candidate location of an edit is parsed and validated in turn, so the number of
+//! candidates tried for one edit is limited too.
+
+use std::time::Duration;
+
+/// Default size of the largest file that is parsed
+pub const DEFAULT_MAX_PARSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default time parsing a file may take before it is edited as text instead
+pub const DEFAULT_PARSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default number of candidate locations tried for one edit
+pub const DEFAULT_MAX_CANDIDATES: usize = 64;
+
+/// Limits beyond which a file is edited as text instead of parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_file_bytes: usize,
+    pub timeout: Duration,
+    /// Candidate locations tried for one edit, in order, before giving up
+    pub max_candidates: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: DEFAULT_MAX_PARSE_BYTES,
+            timeout: DEFAULT_PARSE_TIMEOUT,
+            max_candidates: DEFAULT_MAX_CANDIDATES,
+        }
+    }
+}
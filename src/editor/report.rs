@@ -0,0 +1,613 @@
+//! Structured reports for preview and commit results.
+//!
+//! This module provides serde-serializable reports so programmatic MCP clients can
+//! consume edit results without parsing the human-readable text. Features include:
+//! - `EditReport` with target range, diff hunks, and efficiency metrics
+//! - `TargetNode` naming the syntax node a node operation resolved to
+//! - `AnchorMatch` candidates for an anchor that matched more than once, and
+//!   `AnchorAmbiguity` saying which one was edited
+//! - `PreviewReport` and `CommitReport` wrappers for the tool workflows, including edits that
+//!   were already applied
+//! - `AlternativesReport` previewing the edit at each of an ambiguous anchor's candidates
+//! - `ResultFormat` for choosing between text and JSON tool output
+//! - Text rendering that matches the tools' human-readable output
+
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::EditPosition;
+use crate::git::GitCommit;
+use crate::validation::Violation;
+
+/// Output format for tool results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultFormat {
+    /// Human-readable text (the default)
+    #[default]
+    Text,
+    /// A JSON document containing the structured report
+    Json,
+}
+
+impl ResultFormat {
+    pub fn is_text(&self) -> bool {
+        matches!(self, ResultFormat::Text)
+    }
+}
+
+/// A single line within a diff hunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "text", rename_all = "snake_case")]
+pub enum DiffLine {
+    Context(String),
+    Delete(String),
+    Insert(String),
+}
+
+/// A contiguous region of changes, with 1-based line numbers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+/// How much of the submitted content actually changed the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditEfficiency {
+    pub content_lines: usize,
+    /// Submitted lines that differ from the original (added plus modified)
+    pub changed_lines: usize,
+    pub percent: usize,
+    /// Lines inserted without replacing an original line
+    pub added: usize,
+    /// Original lines deleted without a replacement
+    pub removed: usize,
+    /// Original lines replaced by a different line
+    pub modified: usize,
+}
+
+/// The syntax node a node operation resolved its anchor to, or the declaration an attribute
+/// was added to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetNode {
+    /// The node's tree-sitter kind, such as `function_item`
+    pub kind: String,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// 1-based line the node starts on
+    pub start_line: usize,
+    /// 1-based line the node ends on
+    pub end_line: usize,
+}
+
+impl From<tree_sitter::Node<'_>> for TargetNode {
+    fn from(node: tree_sitter::Node<'_>) -> Self {
+        Self {
+            kind: node.kind().to_string(),
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+        }
+    }
+}
+
+impl Display for TargetNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` ", self.kind)?;
+        if self.start_line == self.end_line {
+            write!(f, "line {}", self.start_line)?;
+        } else {
+            write!(f, "lines {}–{}", self.start_line, self.end_line)?;
+        }
+        write!(f, " (bytes {}–{})", self.start_byte, self.end_byte)
+    }
+}
+
+/// A location an anchor matched, which an edit can be limited to by its index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorMatch {
+    /// Position among the anchor's matches in source order, counting from 0
+    pub index: usize,
+    /// 1-based line the match starts on
+    pub line: usize,
+    /// Kind of the smallest syntax node covering the match, if the file was parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_kind: Option<String>,
+    /// The line the match starts on, trimmed
+    pub text: String,
+}
+
+impl Display for AnchorMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] line {}", self.index, self.line)?;
+        if let Some(node_kind) = &self.node_kind {
+            write!(f, " `{node_kind}`")?;
+        }
+        write!(f, ": {}", self.text)
+    }
+}
+
+/// A location the editor tried an edit at, when every location failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedCandidate {
+    /// Which match of the anchor the location is at, counting from 0, if it was searched for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<usize>,
+    /// The byte range and line and column coordinates the edit was tried at
+    pub target: EditPosition,
+    /// Why the edit failed there
+    pub reason: String,
+}
+
+impl Display for FailedCandidate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.target.start {
+            Some(start) => write!(f, "line {}, column {}", start.line, start.column)?,
+            None => write!(f, "byte {}", self.target.start_byte)?,
+        }
+        if let Some(candidate) = self.candidate {
+            write!(f, " (candidate {candidate})")?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// Which of an anchor's candidates was edited, when it matched more than once
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnchorAmbiguity {
+    /// Index of the candidate that was edited
+    pub chosen: usize,
+    /// Why that candidate was edited
+    pub reason: String,
+}
+
+/// The result of running an edit through the validation and formatting pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditReport {
+    pub operation: String,
+    pub file_path: PathBuf,
+    /// The edited cell, counting from 0, if the file is a Jupyter notebook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    /// Byte range the edit was applied to, if a valid edit was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<EditPosition>,
+    /// The node a node operation replaced or inserted after, if a valid edit was found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_node: Option<TargetNode>,
+    /// The string literal or comment the anchor matched inside, which usually means the edit
+    /// is mis-targeted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enclosing_literal: Option<TargetNode>,
+    /// Every match of the anchor, if it matched more than once
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<AnchorMatch>,
+    /// Which candidate was edited and why, if the anchor matched more than once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambiguity: Option<AnchorAmbiguity>,
+    /// Result or failure message, including any validation errors
+    pub message: String,
+    /// Validation problems in an edit that was accepted anyway because validation, or each
+    /// violated rule, is set to warn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Structured syntax errors and rule violations behind a validation failure or warning
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+    /// Why an insert looks like a second copy of the text right next to it, which
+    /// commit_staged then refuses without `acknowledge_duplicate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<String>,
+    /// Every location the edit was tried at and why it failed there, if it failed at all of
+    /// them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<FailedCandidate>,
+    /// The lines a `replace_range` replaced, and which `end` it stopped at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<EditEfficiency>,
+    pub hunks: Vec<DiffHunk>,
+    /// Human-readable diff, if a valid edit was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+impl EditReport {
+    /// Whether a valid edit was found
+    pub fn is_valid(&self) -> bool {
+        self.diff.is_some()
+    }
+
+    /// The operation, and the notebook cell it edits, if any
+    pub(crate) fn operation_description(&self) -> String {
+        match self.cell {
+            Some(cell) => format!("{} in cell {cell}", self.operation),
+            None => self.operation.clone(),
+        }
+    }
+}
+
+/// Result of staging (or retargeting) an operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewReport {
+    pub staged: bool,
+    #[serde(flatten)]
+    pub edit: EditReport,
+    /// The target file changed on disk after the operation was staged
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub target_changed: bool,
+    /// Why the target file is risky to edit, such as uncommitted git changes or conflict
+    /// markers, if it is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dirty: Option<String>,
+    /// The edit's content is already in place, so nothing was staged, see
+    /// [`already_applied`](super::already_applied)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub already_applied: bool,
+    /// The language the file was edited as and why, if it was detected from the file's
+    /// content because its extension is unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_language: Option<String>,
+}
+
+impl PreviewReport {
+    /// The report of an idempotent edit that was already applied, saying why
+    pub fn already_applied(
+        operation: &str,
+        file_path: PathBuf,
+        cell: Option<usize>,
+        reason: String,
+    ) -> Self {
+        Self {
+            staged: false,
+            edit: EditReport {
+                operation: operation.to_string(),
+                file_path,
+                cell,
+                target: None,
+                target_node: None,
+                enclosing_literal: None,
+                candidates: Vec::new(),
+                ambiguity: None,
+                message: reason,
+                warning: None,
+                violations: Vec::new(),
+                duplicate: None,
+                attempts: Vec::new(),
+                span: None,
+                efficiency: None,
+                hunks: Vec::new(),
+                diff: None,
+            },
+            target_changed: false,
+            dirty: None,
+            already_applied: true,
+            detected_language: None,
+        }
+    }
+}
+
+impl Display for PreviewReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.edit.diff {
+            Some(diff) if self.staged => {
+                write!(f, "STAGED: {}", self.edit.operation_description())?;
+                if let Some(target_node) = &self.edit.target_node {
+                    write!(f, " targeting {target_node}")?;
+                }
+                f.write_str("\n\n")?;
+                if let Some(span) = &self.edit.span {
+                    write!(f, "RANGE: replacing {span}.\n\n")?;
+                }
+                if let Some(language) = &self.detected_language {
+                    write!(
+                        f,
+                        "LANGUAGE: edited as {language}. Pass `language` to edit it as another \
+language.\n\n"
+                    )?;
+                }
+                if self.target_changed {
+                    write!(
+                        f,
+                        "⚠️ WARNING: {} changed on disk after this operation was staged, so \
+commit_staged will refuse it. Use retarget_staged or stage_operation again to stage the edit \
+against the current content.\n\n",
+                        self.edit.file_path.display()
+                    )?;
+                }
+                if let Some(dirty) = &self.dirty {
+                    write!(
+                        f,
+                        "⚠️ WARNING: {} {dirty}. Check that this edit does not build on \
+unfinished work or conflict markers. commit_staged will refuse it unless called with \
+`acknowledge_dirty: true`.\n\n",
+                        self.edit.file_path.display()
+                    )?;
+                }
+                if let Some(duplicate) = &self.edit.duplicate {
+                    write!(
+                        f,
+                        "⚠️ DUPLICATE: {duplicate}, so this edit may be a repeat of one that was \
+already applied. commit_staged will refuse it unless called with `acknowledge_duplicate: true`; \
+stage_operation with `idempotent: true` skips edits that were already applied.\n\n"
+                    )?;
+                }
+                if let Some(literal) = &self.edit.enclosing_literal {
+                    write!(
+                        f,
+                        "⚠️ CAUTION: the anchor matched inside {literal}, so this edit changes \
+{} rather than code. Anchors there are usually mis-targeted; check that this is the match you \
+meant.\n\n",
+                        if literal.kind.contains("comment") {
+                            "a comment"
+                        } else {
+                            "the text of a string"
+                        }
+                    )?;
+                }
+                if let Some(ambiguity) = &self.edit.ambiguity {
+                    write!(
+                        f,
+                        "⚠️ AMBIGUOUS ANCHOR: the anchor matches {} locations. Editing candidate \
+{} on line {} because {}. Pass another candidate's index as `candidate` to retarget_staged to \
+edit it instead:",
+                        self.edit.candidates.len(),
+                        ambiguity.chosen,
+                        self.edit.candidates[ambiguity.chosen].line,
+                        ambiguity.reason
+                    )?;
+                    for candidate in &self.edit.candidates {
+                        let marker = if candidate.index == ambiguity.chosen {
+                            "→"
+                        } else {
+                            " "
+                        };
+                        write!(f, "\n{marker} {candidate}")?;
+                    }
+                    f.write_str("\n\n")?;
+                }
+                if let Some(warning) = &self.edit.warning {
+                    write!(f, "{warning}\n\n")?;
+                }
+                f.write_str(diff)
+            }
+            _ if self.already_applied => write!(
+                f,
+                "ALREADY APPLIED: {} was not staged because {}.",
+                self.edit.operation_description(),
+                self.edit.message
+            ),
+            _ => {
+                f.write_str(&self.edit.message)?;
+                if self.edit.attempts.len() > 1 {
+                    write!(
+                        f,
+                        "\n\nThe edit failed at each of the {} locations tried:",
+                        self.edit.attempts.len()
+                    )?;
+                    for attempt in &self.edit.attempts {
+                        write!(f, "\n  {attempt}")?;
+                    }
+                }
+                if !self.edit.candidates.is_empty() {
+                    write!(
+                        f,
+                        "\n\nThe anchor matches {} locations. To edit one of them, stage the \
+operation again with its index as `candidate`:",
+                        self.edit.candidates.len()
+                    )?;
+                    for candidate in &self.edit.candidates {
+                        write!(f, "\n  {candidate}")?;
+                    }
+                }
+                if let Some(language) = &self.detected_language {
+                    write!(
+                        f,
+                        "\n\nThe file was edited as {language}. Pass `language` to edit it as \
+another language."
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The edit previewed at one of an ambiguous anchor's candidates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alternative {
+    #[serde(flatten)]
+    pub candidate: AnchorMatch,
+    /// The node a node operation would replace or insert after here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_node: Option<TargetNode>,
+    /// The string literal or comment the match lies inside
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enclosing_literal: Option<TargetNode>,
+    /// Result or failure message for the edit here
+    pub message: String,
+    /// Validation problems the edit here would be accepted with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// A short diff of the edit here, if it is valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// The edit previewed at each of an ambiguous anchor's candidates, none of them staged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternativesReport {
+    pub operation: String,
+    pub file_path: PathBuf,
+    /// The edited cell, counting from 0, if the file is a Jupyter notebook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    /// How many locations the anchor matches, which may be more than the alternatives listed
+    pub match_count: usize,
+    /// The candidate staging without a `candidate` would edit, if any edit is valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_candidate: Option<usize>,
+    pub alternatives: Vec<Alternative>,
+}
+
+impl Display for AlternativesReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let operation = match self.cell {
+            Some(cell) => format!("{} in cell {cell}", self.operation),
+            None => self.operation.clone(),
+        };
+        write!(
+            f,
+            "ALTERNATIVES: the {operation} anchor matches {} locations in {}. Nothing was \
+staged; call stage_operation again with the index of the edit you want as `candidate`.",
+            self.match_count,
+            self.file_path.display()
+        )?;
+        if self.alternatives.len() < self.match_count {
+            write!(
+                f,
+                " Only the first {} are shown; use a longer anchor to reach the others.",
+                self.alternatives.len()
+            )?;
+        }
+        if let Some(default_candidate) = self.default_candidate {
+            write!(
+                f,
+                " → marks candidate {default_candidate}, which is edited if no candidate is given."
+            )?;
+        }
+        for alternative in &self.alternatives {
+            let marker = if Some(alternative.candidate.index) == self.default_candidate {
+                "→"
+            } else {
+                " "
+            };
+            write!(f, "\n\n{marker} {}", alternative.candidate)?;
+            if let Some(target_node) = &alternative.target_node {
+                write!(f, "\n  targets {target_node}")?;
+            }
+            if let Some(literal) = &alternative.enclosing_literal {
+                write!(f, "\n  ⚠️ inside {literal}")?;
+            }
+            match &alternative.diff {
+                Some(diff) => {
+                    if let Some(warning) = &alternative.warning {
+                        write!(f, "\n  {warning}")?;
+                    }
+                    write!(f, "\n{diff}")?;
+                }
+                None => write!(f, "\n  ❌ {}", alternative.message)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Result of committing a staged operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitReport {
+    pub applied: bool,
+    pub dry_run: bool,
+    #[serde(flatten)]
+    pub edit: EditReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<PathBuf>,
+    /// Ref holding the file's content from before the edit, if the session takes git snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_snapshot: Option<String>,
+    /// The git commit holding the edit, if the session commits edits to git
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<GitCommit>,
+    /// Final file content, returned for dry runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// How the file read back after writing differs from the edit's output, if it does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<String>,
+}
+
+impl CommitReport {
+    pub fn new(edit: EditReport) -> Self {
+        Self {
+            applied: false,
+            dry_run: false,
+            edit,
+            backup_path: None,
+            git_snapshot: None,
+            git_commit: None,
+            content: None,
+            verification: None,
+        }
+    }
+}
+
+impl Display for CommitReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.edit.diff {
+            Some(diff) => {
+                write!(
+                    f,
+                    "{} operation result:\n{}\n\n",
+                    self.edit.operation_description(),
+                    self.edit.message
+                )?;
+                if let Some(warning) = &self.edit.warning {
+                    write!(f, "{warning}\n\n")?;
+                }
+                f.write_str(diff)?;
+            }
+            None => f.write_str(&self.edit.message)?,
+        }
+
+        if let Some(content) = self.content.as_deref().filter(|_| self.dry_run) {
+            write!(
+                f,
+                "\n\nDRY RUN: {} was not modified. Final content:\n\n{content}",
+                self.edit.file_path.display()
+            )?;
+        }
+
+        if let Some(verification) = &self.verification {
+            write!(
+                f,
+                "\n\n⚠️ VERIFICATION FAILED: the edit was written, but {verification}. Check the \
+file before editing it further."
+            )?;
+        }
+
+        if let Some(backup_path) = &self.backup_path {
+            write!(
+                f,
+                "\n\nBackup of the original written to {}",
+                backup_path.display()
+            )?;
+        }
+
+        if let Some(git_snapshot) = &self.git_snapshot {
+            write!(
+                f,
+                "\n\nContent before the edit saved in git as {git_snapshot}. Restore it with \
+`git checkout {git_snapshot} -- <path>`"
+            )?;
+        }
+
+        if let Some(git_commit) = &self.git_commit {
+            write!(
+                f,
+                "\n\nCommitted to git as {}: {}",
+                git_commit.hash, git_commit.subject
+            )?;
+        }
+
+        Ok(())
+    }
+}
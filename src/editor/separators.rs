@@ -0,0 +1,258 @@
+//! Separator fix-ups for edits to comma- and semicolon-separated lists.
+//!
+//! Deleting an element of a list like a JSON object, an argument list, or a TOML array
+//! leaves its comma behind, and content written for the middle of a list misses one at its
+//! end, so otherwise correct edits fail validation over a single character. This module
+//! adjusts an edit to whole elements of a list so the list stays well formed. Features
+//! include:
+//! - Deleting elements removes the separator after them, or the one before the last element,
+//!   keeping whether the list ends with a separator
+//! - Replacements drop separators the content repeats from the text around it, and restore
+//!   ones the replaced text started or ended with
+//! - Inserts of whole elements next to an element, or next to the separator after or before
+//!   one, get the separator that keeps them apart from the elements around them, while inserts
+//!   within a line may extend the element instead, so they are tried as written first
+//! - Only lists between brackets are changed, and only edits covering whole elements, so
+//!   separators like the `;` in a C `for` are left alone
+
+use std::ops::Range;
+
+use tree_sitter::{Node, Tree};
+
+const SEPARATORS: &[&str] = &[",", ";"];
+const OPENING_BRACKETS: &[&str] = &["{", "[", "(", "<"];
+
+/// An edit with its separators fixed up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SeparatedEdit {
+    pub(super) range: Range<usize>,
+    pub(super) content: String,
+}
+
+/// Where content is inserted relative to a list element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum InsertSide {
+    Before,
+    After,
+}
+
+/// A bracketed list, with its separator
+struct List<'tree> {
+    children: Vec<Node<'tree>>,
+    separator: &'static str,
+}
+
+impl<'tree> List<'tree> {
+    fn of(node: Node<'tree>) -> Option<Self> {
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.children(&mut cursor).collect();
+        let opens = children
+            .first()
+            .is_some_and(|first| OPENING_BRACKETS.contains(&first.kind()));
+        let closes = children.len() >= 2 && !children[children.len() - 1].is_named();
+        let separator = children.iter().find_map(|child| {
+            SEPARATORS
+                .iter()
+                .find(|separator| child.kind() == **separator)
+        })?;
+        (opens && closes).then_some(Self {
+            children,
+            separator,
+        })
+    }
+
+    fn is_separator(&self, index: usize) -> bool {
+        self.children
+            .get(index)
+            .is_some_and(|child| child.kind() == self.separator)
+    }
+
+    fn is_element(&self, index: usize) -> bool {
+        self.children
+            .get(index)
+            .is_some_and(|child| child.is_named() && !child.is_extra())
+    }
+
+    /// Whether children `from..=to` are all elements, separators, or comments
+    fn is_inner(&self, from: usize, to: usize) -> bool {
+        from > 0
+            && to + 1 < self.children.len()
+            && self.children[from..=to]
+                .iter()
+                .all(|child| child.is_named() || child.kind() == self.separator)
+    }
+}
+
+/// The replacement of `range` with `content`, with the separators around it fixed up, if
+/// the range covers whole elements of a list and they need fixing
+pub(super) fn fix_replacement(
+    tree: &Tree,
+    source: &str,
+    range: Range<usize>,
+    content: &str,
+) -> Option<SeparatedEdit> {
+    let text = &source[range.clone()];
+    let start = range.start + (text.len() - text.trim_start().len());
+    let end = range.end - (text.len() - text.trim_end().len());
+    if start >= end {
+        return None;
+    }
+
+    let node = tree.root_node().descendant_for_byte_range(start, end)?;
+    let container = std::iter::successors(Some(node), Node::parent)
+        .find(|node| node.start_byte() != start || node.end_byte() != end)?;
+    let list = List::of(container)?;
+    let first = list.children.iter().position(|c| c.start_byte() == start)?;
+    let last = list.children.iter().position(|c| c.end_byte() == end)?;
+    if first > last || !list.is_inner(first, last) {
+        return None;
+    }
+    let children = &list.children;
+    let separator = list.separator;
+    let starts_with_separator = list.is_separator(first);
+    let ends_with_separator = list.is_separator(last);
+    let element_before = (1..first).any(|index| list.is_element(index));
+    let element_after = (last + 1..children.len() - 1).any(|index| list.is_element(index));
+
+    let fixed = if content.trim().is_empty() {
+        if starts_with_separator {
+            return None;
+        }
+        let separator_after = if ends_with_separator {
+            Some(last)
+        } else {
+            list.is_separator(last + 1).then_some(last + 1)
+        };
+        // the deletion still covers all of the range it was asked for
+        let covering = |deleted: Range<usize>| SeparatedEdit {
+            range: deleted.start.min(range.start)..deleted.end.max(range.end),
+            content: String::new(),
+        };
+        match separator_after {
+            Some(index) if children[index + 1].is_named() => {
+                covering(start..children[index + 1].start_byte())
+            }
+            Some(index) => covering(children[first - 1].end_byte()..children[index].end_byte()),
+            None if list.is_separator(first - 1) => covering(children[first - 1].start_byte()..end),
+            None => covering(children[first - 1].end_byte()..end),
+        }
+    } else {
+        let mut content = content.to_string();
+        let trimmed = content.trim_end();
+        if trimmed.ends_with(separator) && !ends_with_separator {
+            content.replace_range(trimmed.len() - separator.len()..trimmed.len(), "");
+        } else if !trimmed.ends_with(separator) && ends_with_separator && element_after {
+            content.insert_str(trimmed.len(), separator);
+        }
+        let leading = content.len() - content.trim_start().len();
+        if content[leading..].starts_with(separator) && !starts_with_separator {
+            content.replace_range(leading..leading + separator.len(), "");
+        } else if !content[leading..].starts_with(separator)
+            && starts_with_separator
+            && element_before
+        {
+            content.insert_str(leading, separator);
+        }
+        SeparatedEdit {
+            range: range.clone(),
+            content,
+        }
+    };
+
+    let unchanged = fixed.range == range && fixed.content == content;
+    (!unchanged).then_some(fixed)
+}
+
+/// Whether inserted content is a whole list element of its own, rather than text that may
+/// extend the element it goes next to: it goes on a line of its own
+pub(super) fn is_whole_element(content: &str, side: InsertSide) -> bool {
+    match side {
+        InsertSide::After => content[..content.len() - content.trim_start().len()].contains('\n'),
+        InsertSide::Before => content[content.trim_end().len()..].contains('\n'),
+    }
+}
+
+/// Content inserted at `at`, with the separator between it and the list element or
+/// separator it goes next to, if it goes next to one and needs fixing
+pub(super) fn fix_insert(
+    tree: &Tree,
+    source: &str,
+    at: usize,
+    content: &str,
+    side: InsertSide,
+) -> Option<String> {
+    if content.trim().is_empty() {
+        return None;
+    }
+    let node = tree.root_node().descendant_for_byte_range(at, at)?;
+    let (separator, next_to_separator) =
+        std::iter::successors(Some(node), Node::parent).find_map(|node| {
+            let list = List::of(node)?;
+            let index = list.children.iter().position(|child| match side {
+                InsertSide::After => child.end_byte() == at,
+                InsertSide::Before => child.start_byte() == at,
+            })?;
+            // content next to a separator goes between it and the element on its other side
+            let beyond = match side {
+                InsertSide::After => index + 1,
+                InsertSide::Before => index.checked_sub(1)?,
+            };
+            if list.is_element(index) {
+                Some((list.separator, false))
+            } else if list.is_separator(index) && list.is_element(beyond) {
+                Some((list.separator, true))
+            } else {
+                None
+            }
+        })?;
+
+    let mut fixed = content.to_string();
+    let leading = fixed.len() - fixed.trim_start().len();
+    let trailing = fixed.trim_end().len();
+    match (side, next_to_separator) {
+        (InsertSide::After, false) => {
+            if fixed[..trailing].ends_with(separator) {
+                fixed.replace_range(trailing - separator.len()..trailing, "");
+            }
+            if !fixed[leading..].starts_with(separator) {
+                fixed.insert_str(0, separator);
+            }
+        }
+        (InsertSide::Before, true) => {
+            if fixed[..trailing].ends_with(separator) {
+                fixed.replace_range(trailing - separator.len()..trailing, "");
+            }
+            if !fixed[leading..].starts_with(separator) {
+                // keep the content apart from the separator as the next element is
+                let gap = if leading == 0 {
+                    let after = &source[at + separator.len()..];
+                    &after[..after.len() - after.trim_start().len()]
+                } else {
+                    ""
+                };
+                fixed.insert_str(leading, &format!("{separator}{gap}"));
+            }
+        }
+        (InsertSide::Before, false) | (InsertSide::After, true) => {
+            if fixed[leading..].starts_with(separator) {
+                fixed.replace_range(leading..leading + separator.len(), "");
+            }
+            let trailing = fixed.trim_end().len();
+            if !fixed[..trailing].ends_with(separator) {
+                // keep the element apart from the content as it was from what came before
+                let gap = if trailing == fixed.len() && side == InsertSide::Before {
+                    &source[source[..at].trim_end().len()..at]
+                } else {
+                    ""
+                };
+                fixed.insert_str(trailing, &format!("{separator}{gap}"));
+            }
+            if side == InsertSide::After && fixed.len() == fixed.trim_start().len() {
+                // and apart from the separator before it as the next element was
+                let after = &source[at..];
+                fixed.insert_str(0, &after[..after.len() - after.trim_start().len()]);
+            }
+        }
+    }
+    (fixed != content).then_some(fixed)
+}
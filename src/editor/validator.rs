@@ -3,8 +3,16 @@
 //! This module provides syntax and context validation for code edits across all supported languages.
 //! It implements a two-layer validation approach:
 //!
-//! 1. **Syntax Validation**: Uses tree-sitter to detect syntax errors in the parsed AST
+//! 1. **Syntax Validation**: Uses tree-sitter to detect syntax errors in the parsed AST, after
+//!    checking for merge conflict markers, which are reported on their own rather than as the
+//!    syntax errors they cause
 //! 2. **Context Validation**: Language-specific semantic rules (e.g., no functions in struct fields)
+//!    and duplicate definitions introduced by an edit
+//!
+//! Edits are validated with [`Validator::validate_edit`], which only reports problems the edit
+//! introduced, so an unrelated pre-existing syntax error does not block every edit to a file.
+//! For languages whose parser stops at the first syntax error, errors past one the file
+//! already has are looked for in the syntax tree.
 //!
 //! ## Example
 //!
@@ -12,59 +20,304 @@
 //! use semantic_code_edit_mcp::editor::validator::Validator;
 //!
 //! // Validate code before applying edits
-//! if let Some(error_msg) = Validator::validate(language, tree, content) {
-//!     println!("Validation failed: {}", error_msg);
+//! if let Some(failure) = Validator::validate(language, tree, content) {
+//!     println!("Validation failed: {}", failure.message);
 //! }
 //! ```
 
-use crate::{languages::LanguageCommon, validation::ContextValidator};
+use crate::languages::{LanguageCommon, traits::collect_errors};
+use crate::validation::{
+    ContextValidator, ContextViolation, DefinitionValidator, RuleSetting, RuleSettings,
+    SYNTAX_RULE, Severity, ValidationResult, Violation, conflict_marker_rows,
+    conflict_marker_violations,
+};
 use std::collections::BTreeSet;
-use tree_sitter::Tree;
+use std::ops::Range;
+use tree_sitter::{Point, Query, Tree};
+
+/// Why a tree failed validation
+#[derive(Debug, Clone)]
+pub struct ValidationFailure {
+    /// Human-readable description of the problems
+    pub message: String,
+    pub violations: Vec<Violation>,
+    /// Whether the edit must be refused, as opposed to accepted with a warning because
+    /// every violated rule is downgraded to `warn`
+    pub blocking: bool,
+    /// Whether the file already had syntax errors before the edit
+    pub had_errors: bool,
+}
 
 /// Handles syntax and context validation for code edits
 pub struct Validator;
 
 impl Validator {
     /// Validates a tree against language-specific rules
-    /// Returns None if valid, Some(error_message) if invalid
-    pub fn validate(language: &LanguageCommon, tree: &Tree, content: &str) -> Option<String> {
+    /// Returns None if valid, Some(failure) if invalid
+    pub fn validate(
+        language: &LanguageCommon,
+        tree: &Tree,
+        content: &str,
+    ) -> Option<ValidationFailure> {
+        let markers = conflict_marker_rows(content);
+        if !markers.is_empty() {
+            return Some(Self::conflict_failure(content, &markers));
+        }
+
         let errors = language.editor().collect_errors(tree, content);
         if errors.is_empty() {
             if let Some(query) = language.validation_query() {
                 let validation_result = ContextValidator::validate_tree(tree, query, content);
 
                 if !validation_result.is_valid {
-                    return Some(validation_result.format_errors());
+                    return Some(Self::context_failure(&validation_result, true));
                 }
             }
 
             return None;
         }
 
+        Some(Self::syntax_failure(content, errors))
+    }
+
+    /// Validates an edited tree against the tree before the edit, using the language's
+    /// built-in query plus any `custom` project queries, with context rules enforced as
+    /// configured in `rules`
+    /// Returns None unless the edit introduced new problems, and only reports those
+    pub fn validate_edit(
+        language: &LanguageCommon,
+        custom: &[Query],
+        rules: &RuleSettings,
+        (before, before_content): (&Tree, &str),
+        (tree, content): (&Tree, &str),
+        lines: &EditedLines,
+    ) -> Option<ValidationFailure> {
+        let editor = language.editor();
+        let before_errors = editor.collect_errors(before, before_content);
+        let had_errors = !before_errors.is_empty();
+        let existing = lines.existing_rows(
+            before_errors.into_iter().map(|range| range.start.row),
+            before,
+        );
+        let mut errors = editor
+            .collect_errors(tree, content)
+            .into_iter()
+            .filter(|range| !existing.contains(&range.start.row))
+            .collect::<Vec<_>>();
+        // errors after the one the file already has are only in the syntax tree, for
+        // languages whose errors stop at the first
+        if had_errors && let Some(later) = editor.collect_later_errors(tree, content) {
+            let existing = lines.existing_rows(
+                editor
+                    .collect_later_errors(before, before_content)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|range| range.start.row),
+                before,
+            );
+            errors.extend(
+                later
+                    .into_iter()
+                    .filter(|range| !existing.contains(&range.start.row)),
+            );
+        }
+
+        // markers the edit added, or syntax errors on marker lines, are reported as conflicts,
+        // since the syntax errors would only describe the markers
+        let markers = conflict_marker_rows(content);
+        let existing_markers = lines.map_rows(conflict_marker_rows(before_content));
+        if markers.iter().any(|row| !existing_markers.contains(row))
+            || errors
+                .iter()
+                .any(|range| markers.contains(&range.start.row))
+        {
+            return Some(ValidationFailure {
+                had_errors,
+                ..Self::conflict_failure(content, &markers)
+            });
+        }
+        if !errors.is_empty() {
+            return Some(ValidationFailure {
+                had_errors,
+                ..Self::syntax_failure(content, errors)
+            });
+        }
+
+        let queries = language
+            .validation_query()
+            .iter()
+            .copied()
+            .chain(custom)
+            .collect::<Vec<_>>();
+        if queries.is_empty() && language.definition_query().is_none() {
+            return None;
+        }
+
+        let existing = lines.map_rows(
+            Self::context_violations(language, &queries, before, before_content)
+                .violations
+                .iter()
+                .map(|violation| violation.node.start_position().row),
+        );
+        let mut validation_result = Self::context_violations(language, &queries, tree, content);
+        let setting =
+            |violation: &ContextViolation| rules.setting(&violation.rule, violation.severity);
+        validation_result.violations.retain(|violation| {
+            !existing.contains(&violation.node.start_position().row)
+                && setting(violation) != RuleSetting::Off
+        });
+
+        let (warnings, errors) = std::mem::take(&mut validation_result.violations)
+            .into_iter()
+            .partition::<Vec<_>, _>(|violation| setting(violation) == RuleSetting::Warn);
+        let blocking = !errors.is_empty();
+        validation_result.violations = if blocking { errors } else { warnings };
+        validation_result.is_valid = !blocking;
+
+        (!validation_result.violations.is_empty()).then(|| ValidationFailure {
+            had_errors,
+            ..Self::context_failure(&validation_result, blocking)
+        })
+    }
+
+    /// Context rule violations plus any duplicate definitions
+    fn context_violations<'tree, 'source>(
+        language: &LanguageCommon,
+        queries: &[&Query],
+        tree: &'tree Tree,
+        content: &'source str,
+    ) -> ValidationResult<'tree, 'source> {
+        let mut validation_result =
+            ContextValidator::validate_queries(tree, queries.iter().copied(), content);
+        if let Some(query) = language.definition_query() {
+            let duplicates = DefinitionValidator::find_duplicates(tree, query, content);
+            validation_result.is_valid &= duplicates.is_empty();
+            validation_result.violations.extend(duplicates);
+        }
+        validation_result
+    }
+
+    fn context_failure(
+        validation_result: &ValidationResult<'_, '_>,
+        blocking: bool,
+    ) -> ValidationFailure {
+        ValidationFailure {
+            message: validation_result.format_errors(),
+            violations: validation_result.to_violations(),
+            blocking,
+            had_errors: false,
+        }
+    }
+
+    fn syntax_failure(content: &str, mut errors: Vec<Range<Point>>) -> ValidationFailure {
+        errors.sort_by_key(|range| (range.start, range.end));
+        errors.dedup();
+        let violations = errors
+            .into_iter()
+            .map(|range| Violation {
+                rule: SYNTAX_RULE.to_string(),
+                severity: Severity::Error,
+                message: "Syntax error".to_string(),
+                suggestion: None,
+                start: range.start.into(),
+                end: range.end.into(),
+            })
+            .collect::<Vec<_>>();
+
+        ValidationFailure {
+            message: Self::format_violations(content, "===SYNTAX ERRORS===\n", &violations),
+            violations,
+            blocking: true,
+            had_errors: false,
+        }
+    }
+
+    fn conflict_failure(content: &str, rows: &[usize]) -> ValidationFailure {
+        let violations = conflict_marker_violations(content, rows);
+        let heading = "===MERGE CONFLICT MARKERS===
+The file has unresolved merge conflict markers left over from a git merge. They are not code \
+to fix: keep the intended side of each conflict and delete its `<<<<<<<`, `=======`, and \
+`>>>>>>>` lines, or edit code outside the conflict.\n";
+        ValidationFailure {
+            message: Self::format_violations(content, heading, &violations),
+            violations,
+            blocking: true,
+            had_errors: false,
+        }
+    }
+
+    fn format_violations(content: &str, heading: &str, violations: &[Violation]) -> String {
+        let lines_with_errors = violations
+            .iter()
+            .map(|violation| violation.start.line - 1)
+            .collect::<BTreeSet<_>>();
         let context_lines = 3;
-        let lines_with_errors = errors.into_iter().collect::<BTreeSet<_>>();
         let context_lines = lines_with_errors
             .iter()
             .copied()
             .flat_map(|line| line.saturating_sub(context_lines)..line + context_lines)
             .collect::<BTreeSet<_>>();
-        Some(
-            std::iter::once(String::from("===SYNTAX ERRORS===\n"))
-                .chain(
-                    content
-                        .lines()
-                        .enumerate()
-                        .filter(|(index, _)| context_lines.contains(index))
-                        .map(|(index, line)| {
-                            let display_index = index + 1;
-                            if lines_with_errors.contains(&index) {
-                                format!("{display_index:>4} ->⎸{line}\n")
-                            } else {
-                                format!("{display_index:>4}   ⎸{line}\n")
-                            }
-                        }),
-                )
-                .collect(),
-        )
+        std::iter::once(heading.to_string())
+            .chain(
+                violations.iter().map(|violation| {
+                    format!("{} at {}\n", violation.message, violation.location())
+                }),
+            )
+            .chain(
+                content
+                    .lines()
+                    .enumerate()
+                    .filter(|(index, _)| context_lines.contains(index))
+                    .map(|(index, line)| {
+                        let display_index = index + 1;
+                        if lines_with_errors.contains(&index) {
+                            format!("{display_index:>4} ->⎸{line}\n")
+                        } else {
+                            format!("{display_index:>4}   ⎸{line}\n")
+                        }
+                    }),
+            )
+            .collect()
+    }
+}
+
+/// The 0-based rows an edit replaced, used to line up problems before and after the edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditedLines {
+    /// First row touched by the edit
+    pub start: usize,
+    /// Last row of the replaced text before the edit
+    pub old_end: usize,
+    /// Last row of the inserted text after the edit
+    pub new_end: usize,
+}
+
+impl EditedLines {
+    /// Map error rows from before the edit, dropping errors whose syntax tree node spans the
+    /// edit, since the edit may have changed them
+    fn existing_rows(&self, rows: impl Iterator<Item = usize>, before: &Tree) -> BTreeSet<usize> {
+        let spanning = collect_errors(before)
+            .into_iter()
+            .filter(|node| {
+                node.start_position().row <= self.old_end && node.end_position().row >= self.start
+            })
+            .map(|node| node.start_position().row)
+            .collect::<BTreeSet<_>>();
+        self.map_rows(rows.filter(|row| !spanning.contains(row)))
+    }
+
+    /// Map rows from before the edit to rows after it, dropping rows the edit replaced
+    fn map_rows(&self, rows: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        rows.into_iter()
+            .filter_map(|row| {
+                if row < self.start {
+                    Some(row)
+                } else if row > self.old_end {
+                    Some(row - self.old_end + self.new_end)
+                } else {
+                    None
+                }
+            })
+            .collect()
     }
 }
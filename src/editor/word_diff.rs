@@ -0,0 +1,143 @@
+//! Word-level intra-line diff highlighting.
+//!
+//! This module marks exactly which tokens changed between two versions of a line,
+//! using `[-removed-]` and `{+added+}` markers in the style of `git diff --word-diff`.
+//! Features include:
+//! - Tokenization into words, whitespace runs, and individual punctuation
+//! - Longest-common-subsequence matching of tokens
+//! - Fallback to whole-line markers for very long lines
+
+/// Lines with more tokens than this are marked as a whole rather than token by token
+const MAX_TOKENS: usize = 512;
+
+/// Render `new` relative to `old`, wrapping removed tokens in `[-…-]` and added tokens in `{+…+}`
+pub fn highlight(old: &str, new: &str) -> String {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+
+    if old_tokens.len() > MAX_TOKENS || new_tokens.len() > MAX_TOKENS {
+        let mut out = String::new();
+        push_marked(&mut out, "[-", old, "-]");
+        push_marked(&mut out, "{+", new, "+}");
+        return out;
+    }
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new_tokens.len() + 1]; old_tokens.len() + 1];
+    for i in (0..old_tokens.len()).rev() {
+        for j in (0..new_tokens.len()).rev() {
+            lcs[i][j] = if old_tokens[i] == new_tokens[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let mut removed = String::new();
+    let mut added = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_tokens.len() || j < new_tokens.len() {
+        if i < old_tokens.len() && j < new_tokens.len() && old_tokens[i] == new_tokens[j] {
+            flush(&mut out, &mut removed, &mut added);
+            out.push_str(old_tokens[i]);
+            i += 1;
+            j += 1;
+        } else if j < new_tokens.len() && (i == old_tokens.len() || lcs[i][j + 1] >= lcs[i + 1][j])
+        {
+            added.push_str(new_tokens[j]);
+            j += 1;
+        } else {
+            removed.push_str(old_tokens[i]);
+            i += 1;
+        }
+    }
+    flush(&mut out, &mut removed, &mut added);
+    out
+}
+
+fn flush(out: &mut String, removed: &mut String, added: &mut String) {
+    push_marked(out, "[-", removed, "-]");
+    push_marked(out, "{+", added, "+}");
+    removed.clear();
+    added.clear();
+}
+
+fn push_marked(out: &mut String, open: &str, text: &str, close: &str) {
+    if !text.is_empty() {
+        out.push_str(open);
+        out.push_str(text);
+        out.push_str(close);
+    }
+}
+
+/// Split a line into identifier-like words, whitespace runs, and single other characters
+fn tokenize(line: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let class = char_class(c);
+        if class != CharClass::Other {
+            while let Some(&(_, next)) = chars.peek() {
+                if char_class(next) != class {
+                    break;
+                }
+                chars.next();
+            }
+        }
+        let end = chars
+            .peek()
+            .map_or(line.len(), |&(next_index, _)| next_index);
+        tokens.push(&line[index..end]);
+    }
+
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Word,
+    Whitespace,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else if c.is_whitespace() {
+        CharClass::Whitespace
+    } else {
+        CharClass::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_only_changed_tokens() {
+        assert_eq!(
+            highlight("let timeout = 10;", "let timeout = 30;"),
+            "let timeout = [-10-]{+30+};"
+        );
+    }
+
+    #[test]
+    fn marks_insertions_and_removals() {
+        assert_eq!(highlight("foo(a, b)", "foo(a, b, c)"), "foo(a, b{+, c+})");
+        assert_eq!(highlight("pub fn run()", "fn run()"), "[-pub -]fn run()");
+    }
+
+    #[test]
+    fn identical_lines_are_unmarked() {
+        assert_eq!(highlight("same line", "same line"), "same line");
+    }
+
+    #[test]
+    fn tokenizes_words_whitespace_and_punctuation() {
+        assert_eq!(tokenize("a_b  (c)"), vec!["a_b", "  ", "(", "c", ")"]);
+    }
+}
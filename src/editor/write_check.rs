@@ -0,0 +1,106 @@
+//! Verification of committed files.
+//!
+//! This module reads a file back after a commit writes it and compares it with the edit's
+//! output, so a commit reports when the disk does not hold what was written.
+
+use anyhow::Result;
+
+use crate::encoding::TextEncoding;
+use crate::languages::LanguageCommon;
+
+/// Checks that a written file reads back as the edit's output
+///
+/// Taken from an [`Editor`](super::Editor) before it is consumed by committing, so the file
+/// can be read back once it is written. A difference means something between the edit and
+/// the disk changed the content: a lossy encoding, a formatter or editor running on save, or
+/// another process writing the file at the same time.
+#[derive(Debug, Clone, Copy)]
+pub struct WriteCheck<'language> {
+    language: &'language LanguageCommon,
+    encoding: TextEncoding,
+    /// Notebooks are written as JSON, which the cell's language cannot parse
+    notebook: bool,
+}
+
+impl<'language> WriteCheck<'language> {
+    pub(crate) fn new(
+        language: &'language LanguageCommon,
+        encoding: TextEncoding,
+        notebook: bool,
+    ) -> Self {
+        Self {
+            language,
+            encoding,
+            notebook,
+        }
+    }
+
+    /// Compare what was read back after writing with the output that was written
+    ///
+    /// Returns why they differ, or None if the file holds exactly the expected output in its
+    /// original encoding.
+    pub fn verify(
+        &self,
+        expected: &str,
+        read_back: Result<(TextEncoding, String)>,
+    ) -> Option<String> {
+        let (encoding, written) = match read_back {
+            Ok(read_back) => read_back,
+            Err(error) => return Some(format!("the file could not be read back: {error}")),
+        };
+
+        if encoding != self.encoding {
+            return Some(format!(
+                "the file was written as {} but reads back as {}",
+                self.encoding.as_str(),
+                encoding.as_str()
+            ));
+        }
+
+        if written == expected {
+            return None;
+        }
+
+        let mut discrepancy = match first_difference(expected, &written) {
+            Some((line, expected_line, written_line)) => format!(
+                "the file on disk differs from the edit's output at line {line}: expected {:?}, \
+                 found {:?}",
+                expected_line.unwrap_or("<end of file>"),
+                written_line.unwrap_or("<end of file>"),
+            ),
+            None => "the file on disk differs from the edit's output in its line endings or final newline".into(),
+        };
+        if !self.notebook && self.parses(expected) && !self.parses(&written) {
+            discrepancy.push_str(", and the file on disk no longer parses");
+        }
+        Some(discrepancy)
+    }
+
+    fn parses(&self, source: &str) -> bool {
+        let Ok(mut parser) = self.language.parser() else {
+            return true;
+        };
+        parser
+            .parse(source, None)
+            .is_none_or(|tree| !tree.root_node().has_error())
+    }
+}
+
+/// The first line, numbered from 1, where two texts differ, with each text's version of it
+///
+/// Returns None if they have the same lines, so only their line endings or final newline differ.
+fn first_difference<'a>(
+    expected: &'a str,
+    written: &'a str,
+) -> Option<(usize, Option<&'a str>, Option<&'a str>)> {
+    let mut expected_lines = expected.lines();
+    let mut written_lines = written.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), written_lines.next()) {
+            (None, None) => return None,
+            (Some(expected), Some(written)) if expected == written => line += 1,
+            (expected, written) => return Some((line, expected, written)),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! # EditorConfig
+//!
+//! Reads the [EditorConfig](https://editorconfig.org) properties that apply to a file, so
+//! edits follow the project's declared conventions instead of guessed ones.
+//!
+//! ## Supported Properties
+//!
+//! - **`indent_style`, `indent_size`, `tab_width`**: leading indentation of inserted content
+//!   is converted to the configured style, and formatters that support it (JSON, TOML, Rust)
+//!   indent with it
+//! - **`end_of_line`**: line endings of edited files are normalized to `lf`, `crlf`, or `cr`
+//! - **`insert_final_newline`**: edited files end with a newline, or with none
+//! - **`charset`**: edited files are written as `utf-8`, `utf-8-bom`, `utf-16le`, or
+//!   `utf-16be` (`latin1` is not supported and leaves the encoding as read)
+//!
+//! `.editorconfig` files are read from the file's directory and each parent up to one marked
+//! `root = true`, with closer files and later sections taking precedence.
+
+use std::path::Path;
+
+use globset::GlobBuilder;
+
+use crate::encoding::TextEncoding;
+use crate::filesystem::FileOperations;
+
+/// Indentation width used when `indent_style = space` gives no size
+const DEFAULT_INDENT_SIZE: usize = 4;
+
+/// How code is indented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indentation {
+    Tabs,
+    Spaces(usize),
+}
+
+impl Indentation {
+    /// One level of indentation
+    pub fn unit(&self) -> String {
+        match self {
+            Indentation::Tabs => "\t".into(),
+            Indentation::Spaces(width) => " ".repeat(*width),
+        }
+    }
+}
+
+/// A line ending style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfLine {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl EndOfLine {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndOfLine::Lf => "\n",
+            EndOfLine::CrLf => "\r\n",
+            EndOfLine::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndentStyle {
+    Tab,
+    Space,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndentSize {
+    Columns(usize),
+    /// Same as `tab_width`
+    Tab,
+}
+
+/// The EditorConfig properties that apply to one file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    indent_style: Option<IndentStyle>,
+    indent_size: Option<IndentSize>,
+    tab_width: Option<usize>,
+    end_of_line: Option<EndOfLine>,
+    insert_final_newline: Option<bool>,
+    charset: Option<TextEncoding>,
+}
+
+/// A parsed `.editorconfig` file
+#[derive(Debug, Default)]
+struct ConfigFile {
+    root: bool,
+    /// Section globs with their `key = value` properties, in file order
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl EditorConfig {
+    /// The properties that apply to `path`, read through `file_operations` so that sandbox
+    /// restrictions and in-memory files apply. Unreadable `.editorconfig` files are skipped.
+    pub fn load(file_operations: &dyn FileOperations, path: &Path) -> Self {
+        let mut files = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            let Ok(content) = file_operations.read_file(&dir.join(".editorconfig")) else {
+                continue;
+            };
+            let file = ConfigFile::parse(&content);
+            let root = file.root;
+            files.push((dir, file));
+            if root {
+                break;
+            }
+        }
+
+        let mut config = Self::default();
+        for (dir, file) in files.iter().rev() {
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            for (glob, properties) in &file.sections {
+                if section_matches(glob, relative) {
+                    for (key, value) in properties {
+                        config.set(key, value);
+                    }
+                }
+            }
+        }
+        config
+    }
+
+    /// The configured indentation, if `indent_style` is set
+    pub fn indentation(&self) -> Option<Indentation> {
+        match self.indent_style? {
+            IndentStyle::Tab => Some(Indentation::Tabs),
+            IndentStyle::Space => Some(Indentation::Spaces(self.indent_width())),
+        }
+    }
+
+    /// The configured line ending
+    pub fn end_of_line(&self) -> Option<EndOfLine> {
+        self.end_of_line
+    }
+
+    /// Whether files should end with a newline, if configured
+    pub fn insert_final_newline(&self) -> Option<bool> {
+        self.insert_final_newline
+    }
+
+    /// The configured encoding, if it is one that can be written
+    pub fn charset(&self) -> Option<TextEncoding> {
+        self.charset
+    }
+
+    /// Convert the leading indentation of each line of inserted content to the configured
+    /// style, leaving content unchanged if no style is configured
+    pub fn reindent(&self, content: &str) -> String {
+        let Some(indentation) = self.indentation() else {
+            return content.to_string();
+        };
+        let width = self.indent_width();
+        content
+            .split_inclusive('\n')
+            .map(|line| {
+                let rest = line.trim_start_matches([' ', '\t']);
+                let leading = &line[..line.len() - rest.len()];
+                // measure the indentation in columns, with tabs advancing to the next stop
+                let columns = leading.chars().fold(0, |columns, c| match c {
+                    '\t' => (columns / width + 1) * width,
+                    _ => columns + 1,
+                });
+                let indent = match indentation {
+                    Indentation::Tabs => {
+                        format!(
+                            "{}{}",
+                            "\t".repeat(columns / width),
+                            " ".repeat(columns % width)
+                        )
+                    }
+                    Indentation::Spaces(_) => " ".repeat(columns),
+                };
+                format!("{indent}{rest}")
+            })
+            .collect()
+    }
+
+    /// Apply the configured line endings and final newline to edited output
+    pub fn normalize(&self, mut output: String) -> String {
+        // output that already has the configured endings is kept without copying it
+        let needs_conversion =
+            |end_of_line: &EndOfLine| end_of_line.as_str() != "\n" || output.contains('\r');
+        if let Some(end_of_line) = self.end_of_line.filter(needs_conversion) {
+            output = output
+                .replace("\r\n", "\n")
+                .replace('\r', "\n")
+                .replace('\n', end_of_line.as_str());
+        }
+        match self.insert_final_newline {
+            Some(true) if !output.is_empty() && !output.ends_with(['\r', '\n']) => {
+                let end_of_line = self
+                    .end_of_line
+                    .map(|end_of_line| end_of_line.as_str())
+                    .or_else(|| output.contains("\r\n").then_some("\r\n"))
+                    .unwrap_or("\n");
+                output.push_str(end_of_line);
+            }
+            Some(false) => {
+                let trimmed = output.trim_end_matches(['\r', '\n']).len();
+                output.truncate(trimmed);
+            }
+            _ => {}
+        }
+        output
+    }
+
+    fn indent_width(&self) -> usize {
+        match self.indent_size {
+            Some(IndentSize::Columns(columns)) => columns,
+            Some(IndentSize::Tab) | None => self.tab_width.unwrap_or(DEFAULT_INDENT_SIZE),
+        }
+        .max(1)
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        let unset = value == "unset";
+        match key {
+            "indent_style" => {
+                self.indent_style = match value {
+                    "tab" => Some(IndentStyle::Tab),
+                    "space" => Some(IndentStyle::Space),
+                    _ if unset => None,
+                    _ => self.indent_style,
+                }
+            }
+            "indent_size" => {
+                self.indent_size = match value {
+                    "tab" => Some(IndentSize::Tab),
+                    _ if unset => None,
+                    _ => value
+                        .parse()
+                        .ok()
+                        .map(IndentSize::Columns)
+                        .or(self.indent_size),
+                }
+            }
+            "tab_width" => {
+                self.tab_width = if unset {
+                    None
+                } else {
+                    value.parse().ok().or(self.tab_width)
+                }
+            }
+            "end_of_line" => {
+                self.end_of_line = match value {
+                    "lf" => Some(EndOfLine::Lf),
+                    "crlf" => Some(EndOfLine::CrLf),
+                    "cr" => Some(EndOfLine::Cr),
+                    _ if unset => None,
+                    _ => self.end_of_line,
+                }
+            }
+            "insert_final_newline" => {
+                self.insert_final_newline = match value {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    _ if unset => None,
+                    _ => self.insert_final_newline,
+                }
+            }
+            "charset" => {
+                self.charset = match value {
+                    "utf-8" => Some(TextEncoding::Utf8),
+                    "utf-8-bom" => Some(TextEncoding::Utf8Bom),
+                    "utf-16le" => Some(TextEncoding::Utf16Le),
+                    "utf-16be" => Some(TextEncoding::Utf16Be),
+                    // latin1 cannot be written, so keep the encoding the file was read with
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ConfigFile {
+    fn parse(content: &str) -> Self {
+        let mut file = Self::default();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(glob) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                file.sections.push((glob.to_string(), Vec::new()));
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim().to_ascii_lowercase();
+            match file.sections.last_mut() {
+                Some((_, properties)) => properties.push((key, value)),
+                None if key == "root" => file.root = value == "true",
+                None => {}
+            }
+        }
+        file
+    }
+}
+
+/// Whether a section glob matches a path relative to its `.editorconfig` file. Globs without
+/// a `/` match file names in any directory, while others are anchored to the file's directory.
+fn section_matches(glob: &str, relative: &Path) -> bool {
+    let glob = match glob.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if glob.contains('/') => glob.to_string(),
+        None => format!("**/{glob}"),
+    };
+    GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .build()
+        .is_ok_and(|glob| glob.compile_matcher().is_match(relative))
+}
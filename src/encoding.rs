@@ -0,0 +1,104 @@
+//! # Text Encoding
+//!
+//! Source files are always edited as UTF-8 internally. This module detects a
+//! byte-order mark when a file is read, strips it before parsing, and restores
+//! it (re-encoding as needed) when the edited content is written back.
+//!
+//! ## Supported Encodings
+//!
+//! - **UTF-8** without a byte-order mark (the default)
+//! - **UTF-8 with BOM**, common for Windows-originated sources
+//! - **UTF-16LE / UTF-16BE**, detected by their byte-order marks
+//!
+//! ## Example
+//!
+//! ```rust
+//! use semantic_code_edit_mcp::encoding::TextEncoding;
+//!
+//! let (encoding, text) = TextEncoding::decode(b"\xEF\xBB\xBFfn main() {}").unwrap();
+//! assert_eq!(encoding, TextEncoding::Utf8Bom);
+//! assert_eq!(text, "fn main() {}");
+//! assert_eq!(encoding.encode(&text), b"\xEF\xBB\xBFfn main() {}");
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// The on-disk encoding of a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl TextEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf8Bom => "utf-8 with bom",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+        }
+    }
+
+    /// Detect the encoding of `bytes` and decode them to a UTF-8 string without the BOM
+    ///
+    /// Returns None if the bytes are not valid in the detected encoding.
+    pub fn decode(bytes: &[u8]) -> Option<(Self, String)> {
+        if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+            let text = std::str::from_utf8(rest).ok()?;
+            Some((TextEncoding::Utf8Bom, text.to_string()))
+        } else if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+            decode_utf16(rest, u16::from_le_bytes).map(|text| (TextEncoding::Utf16Le, text))
+        } else if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+            decode_utf16(rest, u16::from_be_bytes).map(|text| (TextEncoding::Utf16Be, text))
+        } else {
+            let text = std::str::from_utf8(bytes).ok()?;
+            Some((TextEncoding::Utf8, text.to_string()))
+        }
+    }
+
+    /// Encode `text` in this encoding, restoring the byte-order mark if there was one
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            TextEncoding::Utf8 => text.as_bytes().to_vec(),
+            TextEncoding::Utf8Bom => [UTF8_BOM, text.as_bytes()].concat(),
+            TextEncoding::Utf16Le => UTF16_LE_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+            TextEncoding::Utf16Be => UTF16_BE_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+        }
+    }
+}
+
+impl Display for TextEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Option<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| from_bytes([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16(&units).ok()
+}
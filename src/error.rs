@@ -2,10 +2,22 @@
 //!
 //! This module defines a comprehensive error hierarchy that replaces all
 //! Result<T, String> usages throughout the codebase with proper typed errors.
+//!
+//! [`ToolError`] classifies the errors tools fail with into a [`ToolErrorKind`], which the
+//! MCP server reports as the error's code and in its `data`, so clients can tell a missing
+//! anchor from a refused edit or a stale file without parsing messages. A malformed selector
+//! also lists its [`SelectorIssue`]s in the `data`, each naming the field it is about.
 
+use std::fmt::Display;
 use std::sync::PoisonError;
+
+use fieldwork::Fieldwork;
+use serde::Serialize;
+use serde_json::{Value, json};
 use thiserror::Error;
 
+use crate::selector::SelectorIssue;
+
 /// The comprehensive error type for all semantic code editing operations
 #[derive(Error, Debug)]
 pub enum SemanticEditError {
@@ -16,6 +28,21 @@ pub enum SemanticEditError {
     #[error("language parser not available for {language}")]
     ParserUnavailable { language: String },
 
+    #[error("could not initialize {language} support: {message}")]
+    LanguageInitFailed { language: String, message: String },
+
+    #[error("no {language} formatter is available (tried {tried})")]
+    FormatterUnavailable { language: String, tried: String },
+
+    #[error("`{formatter}` did not finish formatting within {timeout:?} and was stopped")]
+    FormatterTimedOut {
+        formatter: String,
+        timeout: std::time::Duration,
+    },
+
+    #[error("`{formatter}` wrote more than {max_bytes} bytes of output and was stopped")]
+    FormatterOutputTooLarge { formatter: String, max_bytes: usize },
+
     /// File and I/O errors
     #[error("file not found: {path}")]
     FileNotFound { path: String },
@@ -27,6 +54,31 @@ pub enum SemanticEditError {
     Io(#[from] std::io::Error),
 
     /// Edit and validation errors
+    #[error("Anchor text '{anchor}' not found in file {path}{suggestions}")]
+    AnchorNotFound {
+        anchor: String,
+        path: String,
+        /// "Did you mean" lines listing the nearest matches, if any
+        suggestions: String,
+    },
+
+    #[error(
+        "no range from '{anchor}' to '{end}' found in file {path}: `end` does not occur after the anchor"
+    )]
+    RangeNotFound {
+        anchor: String,
+        end: String,
+        path: String,
+    },
+
+    #[error("invalid selector:\n{}", display_issues(.issues))]
+    SelectorInvalid { issues: Vec<SelectorIssue> },
+
+    #[error(
+        "candidate {candidate} does not exist: the anchor has {count} candidate(s), numbered from 0"
+    )]
+    CandidateNotFound { candidate: usize, count: usize },
+
     #[error("no valid edit locations found for selector")]
     NoValidEditLocations,
 
@@ -46,6 +98,12 @@ pub enum SemanticEditError {
     #[error("invalid tree-sitter query: {query}")]
     InvalidTreeSitterQuery { query: String },
 
+    #[error("invalid custom validation query `{path}`: {message}")]
+    InvalidCustomQuery { path: String, message: String },
+
+    #[error("invalid embedded query `{path}`: {message}")]
+    InvalidEmbeddedQuery { path: String, message: String },
+
     /// Cache and state errors
     #[error("file cache is poisoned")]
     FileCachePoisoned,
@@ -59,14 +117,66 @@ pub enum SemanticEditError {
     #[error("no operation is currently staged")]
     OperationNotStaged,
 
+    #[error(
+        "file `{path}` has changed since the operation was staged, so no changes were made. Use stage_operation again to preview the edit against the current content"
+    )]
+    StaleFile { path: String },
+
+    #[error(
+        "file `{path}` was modified by another session, {committer}, since the operation was staged, so no changes were made. Use stage_operation again to preview the edit on top of their changes"
+    )]
+    ModifiedByOtherSession { path: String, committer: String },
+
+    #[error("edit could not be applied: {message}")]
+    EditNotApplied { message: String },
+
     #[error("operation not acknowledged")]
     OperationNotAcknowledged,
 
+    #[error(
+        "`{path}` {reason}, so the staged edit was not committed. Review it with view_staged_diff, then call commit_staged with `acknowledge_dirty: true` to commit it anyway"
+    )]
+    DirtyFileNotAcknowledged { path: String, reason: String },
+
+    #[error(
+        "in `{path}`, {reason}, so the staged edit was not committed in case it repeats one already applied. Review it with view_staged_diff, then call commit_staged with `acknowledge_duplicate: true` to commit it anyway"
+    )]
+    DuplicateNotAcknowledged { path: String, reason: String },
+
+    #[error("the request was cancelled by the client")]
+    Cancelled,
+
+    #[error(
+        "`{path}` was not written because the server is in read-only mode. Use stage_operation and view_staged_diff to preview edits"
+    )]
+    ReadOnly { path: String },
+
+    #[error(
+        "session export version {version} is not supported; this server reads version {supported}"
+    )]
+    UnsupportedSessionExport { version: u32, supported: u32 },
+
+    #[error("access to `{path}` is not allowed because {reason}")]
+    PathNotAllowed { path: String, reason: String },
+
     #[error(
         "no context found for session `{session_id}`. Use set_context first or provide an absolute path"
     )]
     ContextNotFound { session_id: String },
 
+    #[error(
+        "no workspace root named `{name}` is registered for this session. Available roots: {available}"
+    )]
+    UnknownRoot { name: String, available: String },
+
+    #[error(
+        "invalid workspace root name `{name}`: names must be at least two characters of letters, digits, `-` or `_`"
+    )]
+    InvalidRootName { name: String },
+
+    #[error("`{path}` must give a path relative to the workspace root `{root}`")]
+    AbsoluteRootPath { path: String, root: String },
+
     /// UTF-8 and text boundary errors
     #[error("invalid UTF-8 boundary at byte position {position}")]
     InvalidUtf8Boundary { position: usize },
@@ -79,6 +189,44 @@ pub enum SemanticEditError {
     },
 }
 
+impl SemanticEditError {
+    /// Whether this error means the formatter could not be used, so that edits are left
+    /// unformatted instead of refused
+    pub fn skips_formatting(&self) -> bool {
+        matches!(
+            self,
+            SemanticEditError::FormatterUnavailable { .. }
+                | SemanticEditError::FormatterTimedOut { .. }
+                | SemanticEditError::FormatterOutputTooLarge { .. }
+        )
+    }
+
+    /// The path the error is about, if it is about one file
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            SemanticEditError::FileNotFound { path }
+            | SemanticEditError::InvalidEncoding { path }
+            | SemanticEditError::AnchorNotFound { path, .. }
+            | SemanticEditError::RangeNotFound { path, .. }
+            | SemanticEditError::StaleFile { path }
+            | SemanticEditError::ModifiedByOtherSession { path, .. }
+            | SemanticEditError::DirtyFileNotAcknowledged { path, .. }
+            | SemanticEditError::DuplicateNotAcknowledged { path, .. }
+            | SemanticEditError::ReadOnly { path }
+            | SemanticEditError::PathNotAllowed { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+fn display_issues(issues: &[SelectorIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl<T> From<PoisonError<T>> for SemanticEditError {
     fn from(_: PoisonError<T>) -> Self {
         SemanticEditError::FileCachePoisoned
@@ -87,3 +235,155 @@ impl<T> From<PoisonError<T>> for SemanticEditError {
 
 /// Type alias for Results using our error type
 pub type Result<T> = std::result::Result<T, SemanticEditError>;
+
+/// What kind of failure a tool call ended in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorKind {
+    /// The arguments are malformed or name something that does not exist, like a root
+    InvalidParams,
+    /// The anchor does not occur in the file
+    AnchorNotFound,
+    FileNotFound,
+    /// No valid edit was found, or the edit would leave the file invalid
+    ValidationFailed,
+    /// The file changed after the operation was staged
+    StaleFile,
+    /// There is no staged operation to act on
+    NotStaged,
+    /// The edit needs to be acknowledged, for example because the file has uncommitted changes
+    NotAcknowledged,
+    /// The server is read-only or the path is outside the sandbox
+    Forbidden,
+    Cancelled,
+    Internal,
+}
+
+impl ToolErrorKind {
+    /// The kind of failure a [`SemanticEditError`] is
+    pub fn of(error: &SemanticEditError) -> Self {
+        use SemanticEditError as E;
+        match error {
+            E::AnchorNotFound { .. } | E::RangeNotFound { .. } => Self::AnchorNotFound,
+            E::FileNotFound { .. } => Self::FileNotFound,
+            E::Io(error) if error.kind() == std::io::ErrorKind::NotFound => Self::FileNotFound,
+            E::NoValidEditLocations
+            | E::SyntaxValidationFailed { .. }
+            | E::InvalidSyntaxResult { .. }
+            | E::EditNotApplied { .. } => Self::ValidationFailed,
+            E::StaleFile { .. } | E::ModifiedByOtherSession { .. } => Self::StaleFile,
+            E::OperationNotStaged => Self::NotStaged,
+            E::OperationNotAcknowledged
+            | E::DirtyFileNotAcknowledged { .. }
+            | E::DuplicateNotAcknowledged { .. } => Self::NotAcknowledged,
+            E::ReadOnly { .. } | E::PathNotAllowed { .. } => Self::Forbidden,
+            E::Cancelled => Self::Cancelled,
+            E::UnsupportedLanguage { .. }
+            | E::SelectorInvalid { .. }
+            | E::CandidateNotFound { .. }
+            | E::InvalidTreeSitterQuery { .. }
+            | E::UnsupportedSessionExport { .. }
+            | E::ContextNotFound { .. }
+            | E::UnknownRoot { .. }
+            | E::InvalidRootName { .. }
+            | E::AbsoluteRootPath { .. }
+            | E::InvalidUtf8Boundary { .. }
+            | E::TextRangeOutOfBounds { .. } => Self::InvalidParams,
+            _ => Self::Internal,
+        }
+    }
+
+    /// The JSON-RPC error code reported for this kind of failure. Kinds without a standard
+    /// code share codes in the range JSON-RPC leaves to servers, and are told apart by the
+    /// `kind` in the error's data
+    pub fn code(self) -> i32 {
+        match self {
+            Self::InvalidParams | Self::AnchorNotFound => -32602,
+            // MCP's code for a missing resource
+            Self::FileNotFound => -32002,
+            Self::ValidationFailed => -32001,
+            Self::StaleFile | Self::NotStaged | Self::NotAcknowledged => -32003,
+            Self::Forbidden => -32004,
+            Self::Cancelled => -32800,
+            Self::Internal => -32603,
+        }
+    }
+}
+
+/// A tool call's failure, classified so clients can act on it
+#[derive(Debug, Error, Fieldwork)]
+#[error("{message}")]
+#[fieldwork(get)]
+pub struct ToolError {
+    #[fieldwork(get(copy))]
+    kind: ToolErrorKind,
+    message: String,
+    /// The file the failure is about, if it is about one
+    path: Option<String>,
+    /// What is wrong with the selector, if that is why the call failed
+    issues: Vec<SelectorIssue>,
+}
+
+impl ToolError {
+    /// A failure to understand a tool call's arguments
+    pub fn invalid_params(message: impl Display) -> Self {
+        Self {
+            kind: ToolErrorKind::InvalidParams,
+            message: message.to_string(),
+            path: None,
+            issues: Vec::new(),
+        }
+    }
+
+    /// The JSON-RPC error code to report
+    pub fn code(&self) -> i32 {
+        self.kind.code()
+    }
+
+    /// The structured payload reported as the JSON-RPC error's `data`
+    pub fn data(&self) -> Value {
+        let mut data = json!({ "kind": self.kind });
+        if let Some(path) = &self.path {
+            data["path"] = json!(path);
+        }
+        if !self.issues.is_empty() {
+            data["issues"] = json!(self.issues);
+        }
+        data
+    }
+}
+
+impl From<anyhow::Error> for ToolError {
+    fn from(error: anyhow::Error) -> Self {
+        let cause = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<SemanticEditError>());
+        let kind = match cause {
+            Some(cause) => ToolErrorKind::of(cause),
+            None if error.chain().any(|cause| {
+                cause
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|error| error.kind() == std::io::ErrorKind::NotFound)
+            }) =>
+            {
+                ToolErrorKind::FileNotFound
+            }
+            None => ToolErrorKind::Internal,
+        };
+        Self {
+            kind,
+            message: error.to_string(),
+            path: cause.and_then(SemanticEditError::path).map(String::from),
+            issues: match cause {
+                Some(SemanticEditError::SelectorInvalid { issues }) => issues.clone(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl From<SemanticEditError> for ToolError {
+    fn from(error: SemanticEditError) -> Self {
+        anyhow::Error::from(error).into()
+    }
+}
@@ -2,10 +2,17 @@
 //!
 //! This module provides an abstraction layer for filesystem operations,
 //! enabling dependency injection for testing and different deployment scenarios.
+//! All file reads and writes made by the editor go through [`FileOperations`], so
+//! [`TestFileOperations`] can serve as a fully in-memory filesystem.
 
+use crate::encoding::TextEncoding;
+use crate::error::SemanticEditError;
+use crate::sandbox::PathSandbox;
 use anyhow::Result;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
 
 /// Error type for TestFileOperations safe methods
 #[derive(Debug)]
@@ -24,8 +31,76 @@ impl<T> From<PoisonError<T>> for TestFileOperationsError {
 /// This trait allows injecting different file system implementations
 /// for production vs testing scenarios, following the dependency inversion principle.
 pub trait FileOperations: Send + Sync {
+    /// Read the raw bytes of the file at the given path
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+
+    /// Whether a file exists at the given path
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Modification time and size of the file at the given path, if known
+    ///
+    /// Lets caches tell a file is unchanged without reading it. Implementations that cannot
+    /// provide this return None, and callers fall back to comparing content.
+    fn stamp(&self, _path: &Path) -> Option<FileStamp> {
+        None
+    }
+
+    /// Read a UTF-8 file at the given path
+    fn read_file(&self, path: &Path) -> Result<String> {
+        let bytes = self.read_bytes(path)?;
+        String::from_utf8(bytes).map_err(|_| {
+            anyhow::Error::from(SemanticEditError::InvalidEncoding {
+                path: path.display().to_string(),
+            })
+        })
+    }
+
+    /// Read a file, detecting its encoding
+    fn read_encoded(&self, path: &Path) -> Result<(TextEncoding, String)> {
+        if !self.exists(path) {
+            return Err(SemanticEditError::FileNotFound {
+                path: path.display().to_string(),
+            }
+            .into());
+        }
+
+        let bytes = self.read_bytes(path)?;
+        TextEncoding::decode(&bytes).ok_or_else(|| {
+            anyhow::Error::from(SemanticEditError::InvalidEncoding {
+                path: path.display().to_string(),
+            })
+        })
+    }
+
     /// Write content to a file at the given path
     fn write_file(&self, path: PathBuf, content: String) -> Result<()>;
+
+    /// Write raw bytes to a file at the given path
+    ///
+    /// Used when the content must be re-encoded (e.g. to restore a byte-order mark).
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()>;
+
+    /// Create a directory and any missing parents, so files can be written into it
+    ///
+    /// Implementations without real directories, like in-memory ones, need not do anything.
+    fn create_dir_all(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    /// Write content to a file, re-encoding it in the file's original encoding
+    fn write_encoded(&self, path: PathBuf, content: String, encoding: TextEncoding) -> Result<()> {
+        match encoding {
+            TextEncoding::Utf8 => self.write_file(path, content),
+            encoding => self.write_bytes(path, encoding.encode(&content)),
+        }
+    }
+}
+
+/// Modification time and size of a file, for noticing changes without reading it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub modified: SystemTime,
+    pub len: u64,
 }
 
 /// Standard filesystem operations using std::fs
@@ -35,28 +110,188 @@ pub trait FileOperations: Send + Sync {
 pub struct StdFileOperations;
 
 impl FileOperations for StdFileOperations {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        std::fs::read(path).map_err(Into::into)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(FileStamp {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+
     fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
         std::fs::write(path, content).map_err(Into::into)
     }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        std::fs::write(path, content).map_err(Into::into)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        std::fs::create_dir_all(path).map_err(Into::into)
+    }
+}
+
+/// File operations that serve unsaved editor buffers in place of on-disk content
+///
+/// Reads of a path with a buffer return the buffer; all other reads and every write
+/// go to the wrapped file operations.
+pub struct OverlayFileOperations<'a> {
+    inner: &'a dyn FileOperations,
+    buffers: HashMap<PathBuf, String>,
+}
+
+impl<'a> OverlayFileOperations<'a> {
+    pub fn new(inner: &'a dyn FileOperations, buffers: HashMap<PathBuf, String>) -> Self {
+        Self { inner, buffers }
+    }
+
+    /// Whether the given path is served from an unsaved buffer
+    pub fn has_buffer(&self, path: &Path) -> bool {
+        self.buffers.contains_key(path)
+    }
+}
+
+impl FileOperations for OverlayFileOperations<'_> {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        match self.buffers.get(path) {
+            Some(buffer) => Ok(buffer.clone().into_bytes()),
+            None => self.inner.read_bytes(path),
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.has_buffer(path) || self.inner.exists(path)
+    }
+
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        // buffers change without touching the file
+        if self.has_buffer(path) {
+            return None;
+        }
+        self.inner.stamp(path)
+    }
+
+    fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
+        self.inner.write_file(path, content)
+    }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        self.inner.write_bytes(path, content)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        self.inner.create_dir_all(path)
+    }
+}
+
+/// File operations that refuse to read or write outside a [`PathSandbox`]'s allowed paths,
+/// and refuse every write in read-only mode
+///
+/// Checking here rather than only where tools resolve paths means staged operations and
+/// backups are held to the same allowlist.
+pub struct SandboxedFileOperations {
+    inner: Box<dyn FileOperations>,
+    sandbox: PathSandbox,
+}
+
+impl SandboxedFileOperations {
+    pub fn new(inner: Box<dyn FileOperations>, sandbox: PathSandbox) -> Self {
+        Self { inner, sandbox }
+    }
+
+    pub fn sandbox(&self) -> &PathSandbox {
+        &self.sandbox
+    }
+
+    pub fn set_sandbox(&mut self, sandbox: PathSandbox) {
+        self.sandbox = sandbox;
+    }
+}
+
+impl FileOperations for SandboxedFileOperations {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.sandbox.check_allowed(path)?;
+        self.inner.read_bytes(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        // files outside the sandbox are reported missing, so their existence is not leaked
+        self.sandbox.check_allowed(path).is_ok() && self.inner.exists(path)
+    }
+
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        // without a stamp, callers read the file, which checks the sandbox
+        self.sandbox.check_allowed(path).ok()?;
+        self.inner.stamp(path)
+    }
+
+    fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
+        self.sandbox.check_writable(&path)?;
+        self.inner.write_file(path, content)
+    }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        self.sandbox.check_writable(&path)?;
+        self.inner.write_bytes(path, content)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        self.sandbox.check_writable(path)?;
+        self.inner.create_dir_all(path)
+    }
 }
 
-/// Test filesystem operations that capture writes in memory
+/// In-memory filesystem operations that also capture writes
 ///
-/// This implementation captures all write operations for testing purposes,
-/// allowing tests to verify what would be written without side effects.
+/// Files are only visible if they were added with [`TestFileOperations::add_file`] or
+/// written through this instance, so nothing is read from or written to disk. All write
+/// operations are captured, allowing tests to verify what would be written without side effects.
 #[derive(Debug, Default, Clone)]
 pub struct TestFileOperations {
     captured_writes: Arc<Mutex<Vec<(PathBuf, String)>>>,
+    files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
 }
 
 impl TestFileOperations {
-    /// Create a new test file operations instance
+    /// Create a new test file operations instance with no files
     pub fn new() -> Self {
         Self {
             captured_writes: Arc::new(Mutex::new(Vec::new())),
+            files: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Add a file to the in-memory filesystem without capturing it as a write
+    pub fn add_file(&self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        self.files
+            .lock()
+            .expect("Mutex not poisoned")
+            .insert(path.into(), content.into());
+    }
+
+    /// Builder-style variant of [`TestFileOperations::add_file`]
+    pub fn with_file(self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) -> Self {
+        self.add_file(path, content);
+        self
+    }
+
+    /// Get the current content of an in-memory file, if it exists
+    pub fn file_content(&self, path: &Path) -> Option<Vec<u8>> {
+        self.files
+            .lock()
+            .expect("Mutex not poisoned")
+            .get(path)
+            .cloned()
+    }
+
     /// Get all captured write operations
     pub fn get_captured_writes(&self) -> Vec<(PathBuf, String)> {
         self.captured_writes
@@ -129,26 +364,73 @@ impl TestFileOperations {
 }
 
 impl FileOperations for TestFileOperations {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.file_content(path).ok_or_else(|| {
+            anyhow::Error::from(SemanticEditError::FileNotFound {
+                path: path.display().to_string(),
+            })
+        })
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files
+            .lock()
+            .expect("Mutex not poisoned")
+            .contains_key(path)
+    }
+
     fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
+        self.add_file(path.clone(), content.clone());
         self.captured_writes
             .lock()
             .expect("Mutex not poisoned")
             .push((path, content));
         Ok(())
     }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        let text = TextEncoding::decode(&content)
+            .map(|(_, text)| text)
+            .unwrap_or_else(|| String::from_utf8_lossy(&content).into_owned());
+        self.add_file(path.clone(), content);
+        self.captured_writes
+            .lock()
+            .expect("Mutex not poisoned")
+            .push((path, text));
+        Ok(())
+    }
 }
 
 // Implement FileOperations for Arc<TestFileOperations> to support shared ownership in tests
 impl<T: FileOperations + ?Sized> FileOperations for std::sync::Arc<T> {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        (**self).read_bytes(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        (**self).exists(path)
+    }
+
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        (**self).stamp(path)
+    }
+
     fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
         (**self).write_file(path, content)
     }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        (**self).write_bytes(path, content)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        (**self).create_dir_all(path)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_file_operations_captures_writes() {
@@ -187,4 +469,20 @@ mod tests {
         ops.clear_captures();
         assert_eq!(ops.write_count(), 0);
     }
+
+    #[test]
+    fn test_file_operations_reads_added_and_written_files() {
+        let path = PathBuf::from("src/lib.rs");
+        let ops = TestFileOperations::new().with_file(&path, "fn main() {}");
+
+        assert!(ops.exists(&path));
+        assert_eq!(ops.read_file(&path).unwrap(), "fn main() {}");
+        assert_eq!(ops.write_count(), 0);
+
+        ops.write_file(path.clone(), "fn other() {}".to_string())
+            .unwrap();
+        assert_eq!(ops.read_file(&path).unwrap(), "fn other() {}");
+        assert!(!ops.exists(Path::new("missing.rs")));
+        assert!(ops.read_bytes(Path::new("missing.rs")).is_err());
+    }
 }
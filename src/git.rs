@@ -0,0 +1,341 @@
+//! # Git integration
+//!
+//! Sessions can opt into two uses of git when `commit_staged` writes a file:
+//! - **Auto-commit**: every written file is committed, leaving an audit trail of agent edits
+//!   that `git revert` can undo one at a time. Each commit holds only the edited file, so
+//!   changes the user has staged in the index are left out of it, and its message names the
+//!   file, the selector, and the diff stat. A codemod's files are committed together. Files
+//!   that already had uncommitted changes are written but not committed, since the commit
+//!   would hold the user's changes as well as the edit, see [`has_uncommitted_changes`]
+//! - **Snapshots**: the file's content is saved under `refs/semantic-edit/snapshots/` before
+//!   it is overwritten, so uncommitted work can be recovered after a bad edit
+//!
+//! Regardless of either, [`dirty_reason`] flags files with uncommitted changes or merge
+//! conflicts when an edit to them is staged.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::editor::{DiffLine, EditReport};
+use crate::selector::Selector;
+use crate::validation::conflict_marker_rows;
+
+/// Longest anchor quoted in a commit message before it is shortened
+const MAX_ANCHOR_CHARS: usize = 72;
+
+/// Prefix of the refs holding snapshots of files taken before they were overwritten
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/semantic-edit/snapshots/";
+
+/// Index file, inside the git directory, that snapshot trees are built in
+const SNAPSHOT_INDEX: &str = "semantic-edit-snapshot.index";
+
+/// Identity that snapshot commits are made under, so they work without a configured identity
+const SNAPSHOT_AUTHOR: &str = "semantic-edit";
+const SNAPSHOT_EMAIL: &str = "semantic-edit@localhost";
+
+/// A git commit made for an applied edit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitCommit {
+    /// Abbreviated hash of the commit
+    pub hash: String,
+    /// First line of the commit message
+    pub subject: String,
+}
+
+/// Commit a written file to the git repository containing it, with a message describing the
+/// edit
+pub fn commit_edit(
+    file_path: &Path,
+    report: &EditReport,
+    selector: &Selector,
+) -> Result<GitCommit> {
+    let (directory, relative_path) = locate(file_path)?;
+    let subject = format!(
+        "semantic-edit: {} in {}",
+        report.operation,
+        relative_path.display()
+    );
+    let message = format!("{subject}\n\n{}", describe(report, selector));
+    let file_path = file_path.as_os_str();
+    let directory = directory.as_path();
+    git(directory, [OsStr::new("add"), OsStr::new("--"), file_path])?;
+    // with a path, only that file is committed, whatever else is staged
+    git(
+        directory,
+        [
+            OsStr::new("commit"),
+            OsStr::new("--quiet"),
+            OsStr::new("--message"),
+            OsStr::new(&message),
+            OsStr::new("--"),
+            file_path,
+        ],
+    )?;
+    let hash = git(directory, ["rev-parse", "--short", "HEAD"])?;
+    Ok(GitCommit { hash, subject })
+}
+
+/// Commit the files written by a codemod to the git repository containing them, in one
+/// commit naming the rules and the files
+pub fn commit_codemod(
+    file_paths: &[PathBuf],
+    rules: &[String],
+    matches: usize,
+) -> Result<GitCommit> {
+    let first = file_paths
+        .first()
+        .ok_or_else(|| anyhow!("the codemod wrote no files"))?;
+    let (directory, _) = locate(first)?;
+    let subject = format!(
+        "semantic-edit: codemod {} in {} file{}",
+        rules.join(", "),
+        file_paths.len(),
+        if file_paths.len() == 1 { "" } else { "s" }
+    );
+    let mut message = format!(
+        "{subject}\n\nRewrote {matches} match{}:\n",
+        if matches == 1 { "" } else { "es" }
+    );
+    for file_path in file_paths {
+        let (_, relative_path) = locate(file_path)?;
+        message.push_str(&format!("- {}\n", relative_path.display()));
+    }
+    let paths = file_paths.iter().map(|file_path| file_path.as_os_str());
+    let directory = directory.as_path();
+    git(
+        directory,
+        [OsStr::new("add"), OsStr::new("--")]
+            .into_iter()
+            .chain(paths.clone()),
+    )?;
+    git(
+        directory,
+        [
+            OsStr::new("commit"),
+            OsStr::new("--quiet"),
+            OsStr::new("--message"),
+            OsStr::new(&message),
+            OsStr::new("--"),
+        ]
+        .into_iter()
+        .chain(paths),
+    )?;
+    let hash = git(directory, ["rev-parse", "--short", "HEAD"])?;
+    Ok(GitCommit { hash, subject })
+}
+
+/// Save a file's current content in the git repository containing it, before it is
+/// overwritten, returning the ref that holds it
+///
+/// The content is saved as a commit holding only that file, on top of `HEAD`, under
+/// [`SNAPSHOT_REF_PREFIX`]. Nothing in the working tree, the index, or any branch changes, and
+/// the file can be restored with `git checkout <ref> -- <path>` even if it was never committed.
+pub fn snapshot(file_path: &Path) -> Result<String> {
+    let (directory, relative_path) = locate(file_path)?;
+    let directory = directory.as_path();
+    let blob = git(
+        directory,
+        [
+            OsStr::new("hash-object"),
+            OsStr::new("-w"),
+            OsStr::new("--"),
+            file_path.as_os_str(),
+        ],
+    )?;
+
+    // the tree is built in an index of its own, leaving the user's index alone
+    let index = directory.join(git(directory, ["rev-parse", "--git-path", SNAPSHOT_INDEX])?);
+    let tree = (|| {
+        run(git_command(directory)
+            .env("GIT_INDEX_FILE", &index)
+            .args(["update-index", "--add", "--cacheinfo"])
+            .arg(format!(
+                "{},{blob},{}",
+                file_mode(file_path),
+                relative_path.to_string_lossy().replace('\\', "/")
+            )))?;
+        run(git_command(directory)
+            .env("GIT_INDEX_FILE", &index)
+            .arg("write-tree"))
+    })();
+    let _ = std::fs::remove_file(&index);
+    let tree = tree?;
+
+    let mut commit_tree = git_command(directory);
+    commit_tree
+        .env("GIT_AUTHOR_NAME", SNAPSHOT_AUTHOR)
+        .env("GIT_AUTHOR_EMAIL", SNAPSHOT_EMAIL)
+        .env("GIT_COMMITTER_NAME", SNAPSHOT_AUTHOR)
+        .env("GIT_COMMITTER_EMAIL", SNAPSHOT_EMAIL)
+        .args(["commit-tree", &tree, "-m"])
+        .arg(format!(
+            "semantic-edit snapshot of {} before an edit",
+            relative_path.display()
+        ));
+    // repositories without commits yet have no HEAD to build on
+    if let Ok(head) = git(directory, ["rev-parse", "--verify", "--quiet", "HEAD"]) {
+        commit_tree.args(["-p", &head]);
+    }
+    let commit = run(&mut commit_tree)?;
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let reference = format!(
+        "{SNAPSHOT_REF_PREFIX}{millis}-{}",
+        &blob[..blob.len().min(8)]
+    );
+    git(directory, ["update-ref", &reference, &commit])?;
+    Ok(reference)
+}
+
+/// Whether a file in a git repository differs from its committed content, in the working tree
+/// or the index, so that committing it would commit more than an edit made to it now
+pub fn has_uncommitted_changes(file_path: &Path) -> bool {
+    status_code(file_path).is_some()
+}
+
+/// Why a file is risky to edit, if it is: it has uncommitted changes or unresolved conflicts
+/// in git, or its content holds merge conflict markers that an edit would bake in
+///
+/// Files outside a git repository, or without git installed, are only checked for markers.
+pub fn dirty_reason(file_path: &Path, source: &str) -> Option<String> {
+    let mut reasons = Vec::new();
+    if let Some(code) = status_code(file_path) {
+        reasons.push(
+            if matches!(
+                code.as_str(),
+                "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU"
+            ) {
+                "has unresolved merge conflicts in git".to_string()
+            } else {
+                "has uncommitted changes in git".to_string()
+            },
+        );
+    }
+    if let Some(row) = conflict_marker_rows(source).first() {
+        reasons.push(format!(
+            "contains merge conflict markers (line {})",
+            row + 1
+        ));
+    }
+    (!reasons.is_empty()).then(|| reasons.join(" and "))
+}
+
+/// The two-letter `git status` code of a tracked file with uncommitted changes, or None if it
+/// has none or is not in a git repository
+fn status_code(file_path: &Path) -> Option<String> {
+    // not trimmed by `run`, since a leading space means the change is not staged
+    let output = git_command(file_path.parent()?)
+        .args(["status", "--porcelain=v1", "--untracked-files=no", "--"])
+        .arg(file_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .get(..2)
+        .map(String::from)
+}
+
+/// The directory to run git in for a file, and the file's path relative to the top of its
+/// repository
+fn locate(file_path: &Path) -> Result<(PathBuf, PathBuf)> {
+    let directory = file_path
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no parent directory", file_path.display()))?;
+    let top_level = git(directory, ["rev-parse", "--show-toplevel"])?;
+    let relative_path = std::fs::canonicalize(file_path)?
+        .strip_prefix(std::fs::canonicalize(&top_level)?)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| file_path.to_path_buf());
+    Ok((directory.to_path_buf(), relative_path))
+}
+
+/// The git file mode of a file: executable or not
+fn file_mode(file_path: &Path) -> &'static str {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if std::fs::metadata(file_path)
+            .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        {
+            return "100755";
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = file_path;
+    "100644"
+}
+
+/// The body of a commit message: the selector and the diff stat
+fn describe(report: &EditReport, selector: &Selector) -> String {
+    let mut body = format!("Anchor: `{}`\n", shorten(&selector.anchor));
+    if let Some(end) = &selector.end {
+        body.push_str(&format!("End: `{}`\n", shorten(end)));
+    }
+    let lines = report.hunks.iter().flat_map(|hunk| &hunk.lines);
+    let insertions = lines
+        .clone()
+        .filter(|line| matches!(line, DiffLine::Insert(_)))
+        .count();
+    let deletions = lines
+        .filter(|line| matches!(line, DiffLine::Delete(_)))
+        .count();
+    body.push_str(&format!(
+        "Diff stat: {insertions} insertion{}(+), {deletions} deletion{}(-)\n",
+        if insertions == 1 { "" } else { "s" },
+        if deletions == 1 { "" } else { "s" },
+    ));
+    body
+}
+
+/// The first line of anchor text, cut to a length that fits a commit message
+fn shorten(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() <= MAX_ANCHOR_CHARS && !text.trim().contains('\n') {
+        return line.to_string();
+    }
+    let shortened: String = line.chars().take(MAX_ANCHOR_CHARS).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+/// Run git in a directory, returning its trimmed output or its error message
+fn git<I, S>(directory: &Path, args: I) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    run(git_command(directory).args(args))
+}
+
+fn git_command(directory: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(directory);
+    command
+}
+
+fn run(command: &mut Command) -> Result<String> {
+    let output = command
+        .output()
+        .map_err(|error| anyhow!("could not run git: {error}"))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let message = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        Err(anyhow!("git failed: {message}"))
+    }
+}
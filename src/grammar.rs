@@ -0,0 +1,289 @@
+//! # Grammar references
+//!
+//! The node kinds and field names of a language's compiled tree-sitter grammar, with the
+//! validation and definition queries that refer to each one. Used by the `language_reference`
+//! tool, so that query selectors and custom validation rules can be written against the
+//! grammar the server parses with rather than a grammar repository of another version.
+//!
+//! Query files are scanned for the node kinds their patterns match, including anonymous
+//! nodes like `"fn"` and supertypes like `(expression/identifier)`, and the fields they name
+//! as `field:` or `!field`. Predicate arguments, such as the strings in `(#eq? @name "x")`,
+//! are not node kinds and are skipped.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::languages::{LanguageCommon, LanguageName};
+use crate::validation::CustomQueries;
+
+/// A node kind of a grammar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeKind {
+    pub name: String,
+    /// Named kinds are matched as `(kind)`, anonymous ones, like keywords and punctuation,
+    /// as `"kind"`
+    pub named: bool,
+    /// Supertypes, like `_expression`, are hidden from trees but can be matched in queries
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub supertype: bool,
+    /// Paths of the queries whose patterns match this kind
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_by: Vec<String>,
+}
+
+/// A field name of a grammar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrammarField {
+    pub name: String,
+    /// Paths of the queries whose patterns name this field
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_by: Vec<String>,
+}
+
+/// The node kinds and fields of a language's grammar, sorted by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrammarReference {
+    pub language: LanguageName,
+    pub node_kinds: Vec<NodeKind>,
+    pub fields: Vec<GrammarField>,
+}
+
+impl GrammarReference {
+    /// The grammar of a language, with references from its built-in queries and a project's
+    /// custom validation queries
+    pub fn new(language: &LanguageCommon, custom_queries: Option<&CustomQueries>) -> Self {
+        let mut queries = language
+            .embedded_queries()
+            .iter()
+            .map(|query| (query.path.to_string(), query.source.to_string()))
+            .collect::<Vec<_>>();
+        if let Some(custom_queries) = custom_queries {
+            queries.extend(
+                custom_queries
+                    .sources_for_language(language.name())
+                    .iter()
+                    .map(|(path, source)| (path.display().to_string(), source.clone())),
+            );
+        }
+        let mut kind_references = BTreeMap::<(String, bool), Vec<String>>::new();
+        let mut field_references = BTreeMap::<String, Vec<String>>::new();
+        for (path, source) in &queries {
+            let references = QueryReferences::scan(source);
+            for kind in references.kinds {
+                kind_references.entry(kind).or_default().push(path.clone());
+            }
+            for field in references.fields {
+                field_references
+                    .entry(field)
+                    .or_default()
+                    .push(path.clone());
+            }
+        }
+
+        let grammar = language.tree_sitter_language();
+        let mut node_kinds = BTreeMap::new();
+        for id in 0..grammar.node_kind_count() as u16 {
+            let supertype = grammar.node_kind_is_supertype(id);
+            if !grammar.node_kind_is_visible(id) && !supertype {
+                continue;
+            }
+            let Some(name) = grammar.node_kind_for_id(id) else {
+                continue;
+            };
+            let named = grammar.node_kind_is_named(id);
+            // aliases give several ids the same kind
+            node_kinds
+                .entry((name.to_string(), named))
+                .or_insert_with(|| NodeKind {
+                    name: name.to_string(),
+                    named,
+                    supertype,
+                    referenced_by: kind_references
+                        .get(&(name.to_string(), named))
+                        .cloned()
+                        .unwrap_or_default(),
+                });
+        }
+        let fields = (1..=grammar.field_count() as u16)
+            .filter_map(|id| grammar.field_name_for_id(id))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|name| GrammarField {
+                name: name.to_string(),
+                referenced_by: field_references.get(name).cloned().unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            language: language.name(),
+            node_kinds: node_kinds.into_values().collect(),
+            fields,
+        }
+    }
+
+    /// Only the node kinds and fields some query refers to
+    pub fn referenced_only(mut self) -> Self {
+        self.node_kinds
+            .retain(|kind| !kind.referenced_by.is_empty());
+        self.fields.retain(|field| !field.referenced_by.is_empty());
+        self
+    }
+}
+
+impl Display for GrammarReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let named = self.node_kinds.iter().filter(|kind| kind.named).count();
+        write!(
+            f,
+            "{} grammar: {} node kinds ({named} named) and {} fields. Queries that refer to a \
+kind or field are listed after it.",
+            self.language,
+            self.node_kinds.len(),
+            self.fields.len()
+        )?;
+
+        let references = |referenced_by: &[String]| {
+            if referenced_by.is_empty() {
+                String::new()
+            } else {
+                format!("  ({})", referenced_by.join(", "))
+            }
+        };
+        f.write_str("\n\nNamed node kinds:")?;
+        for kind in self.node_kinds.iter().filter(|kind| kind.named) {
+            let supertype = if kind.supertype { " [supertype]" } else { "" };
+            write!(
+                f,
+                "\n  {}{supertype}{}",
+                kind.name,
+                references(&kind.referenced_by)
+            )?;
+        }
+        f.write_str("\n\nAnonymous node kinds:")?;
+        for kind in self.node_kinds.iter().filter(|kind| !kind.named) {
+            write!(f, "\n  {:?}{}", kind.name, references(&kind.referenced_by))?;
+        }
+        f.write_str("\n\nFields:")?;
+        for field in &self.fields {
+            write!(f, "\n  {}:{}", field.name, references(&field.referenced_by))?;
+        }
+        Ok(())
+    }
+}
+
+/// The node kinds, each with whether it is named, and the fields a query's patterns use
+#[derive(Debug, Default)]
+struct QueryReferences {
+    kinds: BTreeSet<(String, bool)>,
+    fields: BTreeSet<String>,
+}
+
+impl QueryReferences {
+    fn scan(source: &str) -> Self {
+        let chars = source.chars().collect::<Vec<_>>();
+        let mut references = Self::default();
+        // for each open bracket, whether it opens a predicate like `(#eq? ...)`
+        let mut brackets = Vec::<bool>::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let in_predicate = brackets.iter().any(|&predicate| predicate);
+            match chars[i] {
+                ';' => {
+                    while i < chars.len() && chars[i] != '\n' {
+                        i += 1;
+                    }
+                }
+                '"' => {
+                    let mut literal = String::new();
+                    i += 1;
+                    while i < chars.len() && chars[i] != '"' {
+                        if chars[i] == '\\' && i + 1 < chars.len() {
+                            i += 1;
+                            literal.push(match chars[i] {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => other,
+                            });
+                        } else {
+                            literal.push(chars[i]);
+                        }
+                        i += 1;
+                    }
+                    if !in_predicate {
+                        references.kinds.insert((literal, false));
+                    }
+                    i += 1;
+                }
+                '(' => {
+                    i += 1;
+                    while i < chars.len() && chars[i].is_whitespace() {
+                        i += 1;
+                    }
+                    let predicate = chars.get(i) == Some(&'#');
+                    brackets.push(predicate);
+                    if !predicate && !in_predicate {
+                        let kind = identifier(&chars, &mut i);
+                        if !kind.is_empty() && kind != "_" {
+                            references.kinds.insert((kind, true));
+                        }
+                        // `(supertype/subtype)`
+                        if chars.get(i) == Some(&'/') {
+                            i += 1;
+                            let subtype = identifier(&chars, &mut i);
+                            if !subtype.is_empty() {
+                                references.kinds.insert((subtype, true));
+                            }
+                        }
+                    }
+                }
+                '[' => {
+                    brackets.push(false);
+                    i += 1;
+                }
+                ')' | ']' => {
+                    brackets.pop();
+                    i += 1;
+                }
+                '@' => {
+                    i += 1;
+                    while i < chars.len()
+                        && (chars[i].is_alphanumeric() || "_.-".contains(chars[i]))
+                    {
+                        i += 1;
+                    }
+                }
+                '!' if !in_predicate => {
+                    i += 1;
+                    let field = identifier(&chars, &mut i);
+                    if !field.is_empty() {
+                        references.fields.insert(field);
+                    }
+                }
+                c if (c.is_alphanumeric() || c == '_') && !in_predicate => {
+                    let word = identifier(&chars, &mut i);
+                    let mut next = i;
+                    while next < chars.len() && chars[next].is_whitespace() {
+                        next += 1;
+                    }
+                    if chars.get(next) == Some(&':') {
+                        references.fields.insert(word);
+                        i = next + 1;
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+        references
+    }
+}
+
+/// The identifier starting at `i`, advancing past it
+fn identifier(chars: &[char], i: &mut usize) -> String {
+    let start = *i;
+    while *i < chars.len() && (chars[*i].is_alphanumeric() || chars[*i] == '_') {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
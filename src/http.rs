@@ -0,0 +1,618 @@
+//! # HTTP transport
+//!
+//! Serves MCP over streamable HTTP, so one server can be deployed as a network service shared
+//! by several agent clients instead of being spawned by each of them over stdio.
+//!
+//! ## Protocol
+//!
+//! - Clients POST one JSON-RPC message at a time to `/mcp`. Requests are answered with a JSON
+//!   body, or with an SSE stream holding the response when the client accepts only
+//!   `text/event-stream`. Notifications and responses are acknowledged with `202 Accepted`
+//! - Updates to subscribed resources are sent in an SSE stream ahead of the response to the
+//!   session's next request, if the client accepts `text/event-stream`. So are progress
+//!   notifications for the request, since responses are written only once they are complete
+//! - A `notifications/cancelled` POSTed while a request in the same session runs cancels it
+//! - `initialize` starts a session, returned in the `Mcp-Session-Id` header. Later requests
+//!   must send that header back, and `DELETE /mcp` ends the session
+//! - The server never sends messages of its own, so `GET /mcp` is refused with `405` unless
+//!   it asks to upgrade to a WebSocket
+//!
+//! A WebSocket on `/mcp` carries one JSON-RPC message per text message, and each connection
+//! is its own session, so IDE plugins can hold one connection per window instead of spawning
+//! a server process for each. Updates to subscribed resources and progress notifications are
+//! sent as soon as they are noticed. Messages are read one at a time, so a request cannot be
+//! cancelled over its WebSocket while it runs.
+//!
+//! Each MCP session or WebSocket connection is a separate editing session with its own context
+//! and staged operation, persisted in the session store like the stdio session. Tool calls from
+//! all clients run one at a time.
+//!
+//! ## Security
+//!
+//! Requests must be addressed to an allowed host, and requests from a browser must come from
+//! an origin on one, guarding local servers against DNS rebinding: after rebinding, a page
+//! sends its own domain name as both `Host` and `Origin`, which is not allowed. Loopback names
+//! and addresses are allowed, plus any hosts configured for a server reached over the network.
+//! When a token is configured, every request and WebSocket upgrade must carry it as
+//! `Authorization: Bearer <token>`, which is compared in constant time.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use fieldwork::Fieldwork;
+use mcplease::types::{Info, McpRequest, McpResponse};
+use serde_json::Value;
+
+use crate::progress::{Cancellations, Notify, Progress};
+use crate::resources::Subscriptions;
+use crate::server::{self, parse_message};
+use crate::state::SemanticEditTools;
+
+mod websocket;
+
+/// Path of the MCP endpoint
+pub const MCP_PATH: &str = "/mcp";
+
+/// Header carrying the session ID
+pub const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Default size of the largest request body accepted
+pub const DEFAULT_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// Largest request line and headers accepted
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// Idle keep-alive connections are closed after this long
+const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// An MCP server listening for streamable HTTP clients
+#[derive(Debug, Fieldwork)]
+pub struct HttpServer {
+    #[fieldwork(skip)]
+    listener: TcpListener,
+    /// Bearer token every request must carry, if any
+    #[fieldwork(get, with)]
+    token: Option<String>,
+    /// Size of the largest request body accepted
+    #[fieldwork(get(copy), with)]
+    max_body_bytes: usize,
+    /// Host names requests may be addressed to and come from besides loopback ones, such as
+    /// the name of the machine the server is reached at over the network
+    #[fieldwork(get, with)]
+    allowed_hosts: Vec<String>,
+    /// Sessions that have not ended, with their resource subscriptions
+    #[fieldwork(skip)]
+    sessions: Mutex<HashMap<String, Subscriptions>>,
+    /// Requests being answered, which other connections can cancel
+    #[fieldwork(skip)]
+    cancellations: Cancellations,
+}
+
+/// A parsed HTTP request
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// An HTTP response to write
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+/// What reading the next request from a connection produced
+enum Incoming {
+    Request(Request),
+    /// The request was malformed or too large, and the connection closes after this response
+    Rejected(Response),
+    Closed,
+}
+
+impl HttpServer {
+    /// Listen on an address, such as `127.0.0.1:8080`
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+            token: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            allowed_hosts: Vec::new(),
+            sessions: Mutex::new(HashMap::new()),
+            cancellations: Cancellations::default(),
+        })
+    }
+
+    /// The address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept clients until the listener fails, serving each connection on its own thread
+    pub fn serve(
+        &self,
+        state: &mut SemanticEditTools,
+        server_info: Info,
+        instructions: Option<&'static str>,
+    ) -> Result<()> {
+        let state = Mutex::new(state);
+        thread::scope(|scope| -> Result<()> {
+            for stream in self.listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        log::error!("Error accepting connection: {error}");
+                        continue;
+                    }
+                };
+                let state = &state;
+                let server_info = &server_info;
+                scope.spawn(move || {
+                    if let Err(error) =
+                        self.serve_connection(stream, state, server_info, instructions)
+                    {
+                        log::debug!("Connection closed: {error}");
+                    }
+                });
+            }
+            Ok(())
+        })
+    }
+
+    fn serve_connection(
+        &self,
+        stream: TcpStream,
+        state: &Mutex<&mut SemanticEditTools>,
+        server_info: &Info,
+        instructions: Option<&'static str>,
+    ) -> io::Result<()> {
+        stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        loop {
+            let request = match read_request(&mut reader, self.max_body_bytes)? {
+                Incoming::Request(request) => request,
+                Incoming::Rejected(response) => return response.write_to(&mut writer, true),
+                Incoming::Closed => return Ok(()),
+            };
+            if websocket::is_upgrade(&request) {
+                if let Some(refusal) = self.refusal(&request) {
+                    return refusal.write_to(&mut writer, true);
+                }
+                return self.serve_websocket(
+                    request,
+                    reader,
+                    writer,
+                    state,
+                    server_info,
+                    instructions,
+                );
+            }
+            let close = request
+                .header("connection")
+                .is_some_and(|connection| connection.eq_ignore_ascii_case("close"));
+            let response = self.handle(request, state, server_info, instructions);
+            response.write_to(&mut writer, close)?;
+            if close {
+                return Ok(());
+            }
+        }
+    }
+
+    fn handle(
+        &self,
+        request: Request,
+        state: &Mutex<&mut SemanticEditTools>,
+        server_info: &Info,
+        instructions: Option<&'static str>,
+    ) -> Response {
+        if let Some(refusal) = self.refusal(&request) {
+            return refusal;
+        }
+
+        match request.method.as_str() {
+            "POST" => self.handle_post(request, state, server_info, instructions),
+            "DELETE" => match self.session(&request) {
+                Ok(session_id) => {
+                    self.sessions().remove(&session_id);
+                    Response::text(200, "Session ended")
+                }
+                Err(response) => response,
+            },
+            _ => Response::text(405, "Method not allowed")
+                .with_header("Allow", "POST, DELETE".into()),
+        }
+    }
+
+    /// The response refusing a request for the wrong path, to or from a host that is not
+    /// allowed, or without the token, if it is refused
+    fn refusal(&self, request: &Request) -> Option<Response> {
+        if request.path.split('?').next() != Some(MCP_PATH) {
+            return Some(Response::text(404, "Not found"));
+        }
+        let host_allowed = request
+            .header("host")
+            .is_some_and(|host| self.is_allowed_host(host_name(host)));
+        let origin_allowed = request.header("origin").is_none_or(|origin| {
+            origin
+                .split_once("://")
+                .is_some_and(|(_, origin)| self.is_allowed_host(host_name(origin)))
+        });
+        if !host_allowed || !origin_allowed {
+            return Some(Response::text(403, "Host or Origin is not allowed"));
+        }
+        if let Some(token) = &self.token {
+            let authorized = request
+                .header("authorization")
+                .and_then(|authorization| authorization.strip_prefix("Bearer "))
+                .is_some_and(|presented| constant_time_eq(presented.trim(), token));
+            if !authorized {
+                return Some(
+                    Response::text(401, "Missing or invalid bearer token")
+                        .with_header("WWW-Authenticate", "Bearer".into()),
+                );
+            }
+        }
+        None
+    }
+
+    fn handle_post(
+        &self,
+        request: Request,
+        state: &Mutex<&mut SemanticEditTools>,
+        server_info: &Info,
+        instructions: Option<&'static str>,
+    ) -> Response {
+        let mcp_request = match parse_message(&request.body) {
+            Ok(Some(mcp_request)) => mcp_request,
+            Ok(None) => {
+                if let Some(session_id) = request.header(SESSION_HEADER) {
+                    self.cancellations.observe(session_id, &request.body);
+                }
+                return Response::accepted();
+            }
+            Err(response) => return Response::json(400, &response),
+        };
+
+        let session_id = if mcp_request.method == "initialize" {
+            let session_id = new_session_id();
+            self.sessions()
+                .insert(session_id.clone(), Subscriptions::default());
+            session_id
+        } else {
+            match self.session(&request) {
+                Ok(session_id) => session_id,
+                Err(response) => return response,
+            }
+        };
+
+        // progress is sent in the event stream ahead of the response
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let notify: Notify = {
+            let reported = Arc::clone(&reported);
+            Arc::new(move |notification| {
+                reported
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(notification);
+            })
+        };
+        let id = mcp_request.id.clone();
+        let cancelled = self.cancellations.flag(&session_id, &id);
+        let progress = Progress::new(&mcp_request, notify, cancelled);
+        // the sessions stay unlocked while the tool runs, so other clients are not held up
+        let Some(mut subscriptions) = self.sessions().get(&session_id).cloned() else {
+            self.cancellations.finish(&session_id, &id);
+            return Response::text(404, "Unknown or ended session");
+        };
+        let (response, notifications) = execute(
+            mcp_request,
+            &session_id,
+            state,
+            &mut subscriptions,
+            progress,
+            server_info,
+            instructions,
+        );
+        // a session deleted while the tool ran stays deleted
+        if let Some(session) = self.sessions().get_mut(&session_id) {
+            *session = subscriptions;
+        }
+        self.cancellations.finish(&session_id, &id);
+        let mut messages =
+            std::mem::take(&mut *reported.lock().unwrap_or_else(PoisonError::into_inner));
+        messages.extend(notifications);
+
+        // notifications can only be sent in an event stream
+        let stream = request.accepts_only_event_stream()
+            || (!messages.is_empty() && request.accepts("text/event-stream"));
+        let response = if stream {
+            messages.push(serde_json::to_value(&response).unwrap_or_default());
+            Response::event_stream(&messages)
+        } else {
+            Response::json(200, &response)
+        };
+        response.with_header(SESSION_HEADER, session_id)
+    }
+
+    /// The session a request belongs to, or the response refusing it
+    fn session(&self, request: &Request) -> Result<String, Response> {
+        let Some(session_id) = request.header(SESSION_HEADER) else {
+            return Err(Response::text(400, "Missing Mcp-Session-Id header"));
+        };
+        if !self.sessions().contains_key(session_id) {
+            return Err(Response::text(404, "Unknown or ended session"));
+        }
+        Ok(session_id.to_string())
+    }
+
+    /// Whether a host name is a loopback one or one of the allowed hosts
+    fn is_allowed_host(&self, host: &str) -> bool {
+        host.eq_ignore_ascii_case("localhost")
+            || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+            || self
+                .allowed_hosts
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Subscriptions>> {
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn accepts(&self, media_type: &str) -> bool {
+        self.header("accept")
+            .is_some_and(|accept| accept.contains(media_type))
+    }
+
+    fn accepts_only_event_stream(&self) -> bool {
+        self.accepts("text/event-stream") && !self.accepts("application/json")
+    }
+}
+
+impl Response {
+    fn text(status: u16, text: &str) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", "text/plain; charset=utf-8".into())],
+            body: text.as_bytes().to_vec(),
+        }
+    }
+
+    fn json(status: u16, response: &McpResponse) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", "application/json".into())],
+            body: serde_json::to_vec(response).unwrap_or_default(),
+        }
+    }
+
+    fn event_stream(messages: &[Value]) -> Self {
+        let events: String = messages
+            .iter()
+            .map(|message| format!("event: message\ndata: {message}\n\n"))
+            .collect();
+        Self {
+            status: 200,
+            headers: vec![
+                ("Content-Type", "text/event-stream".into()),
+                ("Cache-Control", "no-cache".into()),
+            ],
+            body: events.into_bytes(),
+        }
+    }
+
+    fn accepted() -> Self {
+        Self {
+            status: 202,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    fn with_header(mut self, name: &'static str, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    fn write_to(&self, writer: &mut impl Write, close: bool) -> io::Result<()> {
+        let mut head = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        // interim responses such as 101 Switching Protocols have no body
+        if self.status >= 200 {
+            head.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        if close {
+            head.push_str("Connection: close\r\n");
+        }
+        head.push_str("\r\n");
+        writer.write_all(head.as_bytes())?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        101 => "Switching Protocols",
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        426 => "Upgrade Required",
+        431 => "Request Header Fields Too Large",
+        501 => "Not Implemented",
+        _ => "",
+    }
+}
+
+/// Read the next request on a connection
+fn read_request(reader: &mut BufReader<TcpStream>, max_body_bytes: usize) -> io::Result<Incoming> {
+    let mut head_bytes = 0;
+    let mut line = String::new();
+    let mut lines = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .by_ref()
+            .take((MAX_HEADER_BYTES - head_bytes + 1) as u64)
+            .read_line(&mut line)?;
+        if read == 0 {
+            return Ok(if lines.is_empty() {
+                Incoming::Closed
+            } else {
+                Incoming::Rejected(Response::text(400, "Incomplete request"))
+            });
+        }
+        head_bytes += read;
+        if head_bytes > MAX_HEADER_BYTES {
+            return Ok(Incoming::Rejected(Response::text(431, "Headers too large")));
+        }
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed.is_empty() {
+            // blank lines before the request line are allowed
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        lines.push(trimmed.to_string());
+    }
+
+    let mut request_line = lines[0].split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Ok(Incoming::Rejected(Response::text(
+            400,
+            "Malformed request line",
+        )));
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers: Vec::new(),
+        body: Vec::new(),
+    };
+    for header in &lines[1..] {
+        let Some((name, value)) = header.split_once(':') else {
+            return Ok(Incoming::Rejected(Response::text(400, "Malformed header")));
+        };
+        request
+            .headers
+            .push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    if request.header("transfer-encoding").is_some() {
+        return Ok(Incoming::Rejected(Response::text(
+            501,
+            "Chunked request bodies are not supported",
+        )));
+    }
+    let length = match request.header("content-length").map(str::parse::<usize>) {
+        None if request.method == "POST" => {
+            return Ok(Incoming::Rejected(Response::text(411, "Length required")));
+        }
+        None => 0,
+        Some(Ok(length)) => length,
+        Some(Err(_)) => {
+            return Ok(Incoming::Rejected(Response::text(
+                400,
+                "Invalid Content-Length",
+            )));
+        }
+    };
+    if length > max_body_bytes {
+        return Ok(Incoming::Rejected(Response::text(
+            413,
+            "Request body too large",
+        )));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(Incoming::Request(request))
+}
+
+/// Run a request in a session, waiting for other clients' requests to finish first, and
+/// reporting its progress to `progress`
+///
+/// Returns the response, and notifications for the session's subscribed resources that changed.
+fn execute(
+    mcp_request: McpRequest,
+    session_id: &str,
+    state: &Mutex<&mut SemanticEditTools>,
+    subscriptions: &mut Subscriptions,
+    progress: Progress,
+    server_info: &Info,
+    instructions: Option<&'static str>,
+) -> (McpResponse, Vec<Value>) {
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    state.set_default_session_id(session_id);
+    state.set_progress(progress);
+    let response = server::handle(
+        mcp_request,
+        &mut state,
+        subscriptions,
+        server_info,
+        instructions,
+    );
+    state.set_progress(Progress::default());
+    let notifications = if subscriptions.is_empty() {
+        Vec::new()
+    } else {
+        server::updated_notifications(&state, subscriptions)
+    };
+    (response, notifications)
+}
+
+/// The host name in a `Host` header or an origin without its scheme, without its port or
+/// path, and without the brackets around an IPv6 address
+fn host_name(authority: &str) -> &str {
+    let authority = authority.split('/').next().unwrap_or_default().trim();
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        return bracketed.split(']').next().unwrap_or_default();
+    }
+    authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _port)| host)
+}
+
+/// Whether two tokens are equal, comparing every byte so the time taken does not tell how
+/// much of a guess was right
+fn constant_time_eq(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// A random 128-bit session ID, in hex
+fn new_session_id() -> String {
+    let mut bytes = [0; 16];
+    getrandom::fill(&mut bytes).expect("the operating system random number generator failed");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,339 @@
+//! WebSocket connections to the MCP endpoint.
+//!
+//! A `GET /mcp` asking to upgrade becomes a WebSocket (RFC 6455) carrying one JSON-RPC message
+//! per text message. Requests are answered in order with a text message holding the response,
+//! and the connection is its own session for as long as it stays open. Updates to subscribed
+//! resources are sent as notifications after each request and while the client is idle, and
+//! progress notifications as the request reports them.
+
+use std::io::{self, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use mcplease::types::Info;
+
+use super::{HttpServer, Request, Response, execute, new_session_id, parse_message};
+use crate::progress::{Notify, Progress};
+use crate::resources::Subscriptions;
+use crate::server;
+use crate::state::SemanticEditTools;
+
+/// Appended to the client's key to prove the server speaks WebSocket
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// Close codes sent when the server ends a connection
+const CLOSE_NORMAL: u16 = 1000;
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+const CLOSE_UNSUPPORTED_DATA: u16 = 1003;
+const CLOSE_INVALID_DATA: u16 = 1007;
+const CLOSE_TOO_BIG: u16 = 1009;
+
+/// One frame read from the client
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+/// Whether a request asks to upgrade to a WebSocket
+pub(super) fn is_upgrade(request: &Request) -> bool {
+    request.method == "GET"
+        && request
+            .header("upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
+
+impl HttpServer {
+    /// Complete the handshake and answer messages until the client closes the connection
+    pub(super) fn serve_websocket(
+        &self,
+        request: Request,
+        mut reader: BufReader<TcpStream>,
+        mut writer: TcpStream,
+        state: &Mutex<&mut SemanticEditTools>,
+        server_info: &Info,
+        instructions: Option<&'static str>,
+    ) -> io::Result<()> {
+        let key = match (
+            request.header("sec-websocket-key"),
+            request.header("sec-websocket-version"),
+        ) {
+            (Some(key), Some("13")) => key,
+            _ => {
+                return Response::text(426, "WebSocket version 13 with a key is required")
+                    .with_header("Sec-WebSocket-Version", "13".into())
+                    .write_to(&mut writer, true);
+            }
+        };
+        Response {
+            status: 101,
+            headers: vec![
+                ("Upgrade", "websocket".into()),
+                ("Connection", "Upgrade".into()),
+                ("Sec-WebSocket-Accept", accept_key(key)),
+            ],
+            body: Vec::new(),
+        }
+        .write_to(&mut writer, false)?;
+
+        // connections stay open while idle, for as long as the client keeps them
+        reader.get_ref().set_read_timeout(None)?;
+        let session_id = new_session_id();
+        log::debug!("WebSocket session {session_id} opened");
+
+        let notify: Notify = {
+            let stream = writer.try_clone()?;
+            Arc::new(move |notification| {
+                let notification = notification.to_string();
+                if let Err(error) = write_frame(&mut &stream, OPCODE_TEXT, notification.as_bytes())
+                {
+                    log::debug!("Error writing notification: {error}");
+                }
+            })
+        };
+        let mut subscriptions = Subscriptions::default();
+        let mut message = Vec::new();
+        let mut message_opcode = None;
+        loop {
+            // while waiting for the client, check on the resources it subscribed to
+            while !subscriptions.is_empty()
+                && reader.buffer().is_empty()
+                && !data_arrives(reader.get_ref(), server::POLL_INTERVAL)?
+            {
+                let notifications = {
+                    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                    state.set_default_session_id(session_id.as_str());
+                    server::updated_notifications(&state, &mut subscriptions)
+                };
+                for notification in notifications {
+                    write_frame(
+                        &mut writer,
+                        OPCODE_TEXT,
+                        notification.to_string().as_bytes(),
+                    )?;
+                }
+            }
+
+            let Some(frame) = read_frame(&mut reader, self.max_body_bytes)? else {
+                return close(&mut writer, CLOSE_TOO_BIG);
+            };
+            match frame.opcode {
+                OPCODE_TEXT | OPCODE_BINARY if message_opcode.is_none() => {
+                    message_opcode = Some(frame.opcode);
+                }
+                OPCODE_CONTINUATION if message_opcode.is_some() => {}
+                OPCODE_CLOSE => return close(&mut writer, CLOSE_NORMAL),
+                OPCODE_PING => {
+                    write_frame(&mut writer, OPCODE_PONG, &frame.payload)?;
+                    continue;
+                }
+                OPCODE_PONG => continue,
+                _ => return close(&mut writer, CLOSE_PROTOCOL_ERROR),
+            }
+
+            if message.len() + frame.payload.len() > self.max_body_bytes {
+                return close(&mut writer, CLOSE_TOO_BIG);
+            }
+            message.extend_from_slice(&frame.payload);
+            if !frame.fin {
+                continue;
+            }
+            if message_opcode.take() == Some(OPCODE_BINARY) {
+                return close(&mut writer, CLOSE_UNSUPPORTED_DATA);
+            }
+            if std::str::from_utf8(&message).is_err() {
+                return close(&mut writer, CLOSE_INVALID_DATA);
+            }
+
+            let (response, notifications) = match parse_message(&message) {
+                Ok(Some(mcp_request)) => {
+                    let progress = Progress::new(&mcp_request, Arc::clone(&notify), Arc::default());
+                    let (response, notifications) = execute(
+                        mcp_request,
+                        &session_id,
+                        state,
+                        &mut subscriptions,
+                        progress,
+                        server_info,
+                        instructions,
+                    );
+                    (Some(response), notifications)
+                }
+                Ok(None) => (None, Vec::new()),
+                Err(response) => (Some(*response), Vec::new()),
+            };
+            message.clear();
+            if let Some(response) = response {
+                let response = serde_json::to_vec(&response).unwrap_or_default();
+                write_frame(&mut writer, OPCODE_TEXT, &response)?;
+            }
+            for notification in notifications {
+                write_frame(
+                    &mut writer,
+                    OPCODE_TEXT,
+                    notification.to_string().as_bytes(),
+                )?;
+            }
+        }
+    }
+}
+
+/// Whether the client sends anything, or closes the connection, within `timeout`
+fn data_arrives(stream: &TcpStream, timeout: Duration) -> io::Result<bool> {
+    stream.set_read_timeout(Some(timeout))?;
+    let arrived = match stream.peek(&mut [0]) {
+        Ok(_) => Ok(true),
+        Err(error)
+            if matches!(
+                error.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(error) => Err(error),
+    };
+    stream.set_read_timeout(None)?;
+    arrived
+}
+
+/// The `Sec-WebSocket-Accept` value answering a client's key
+fn accept_key(key: &str) -> String {
+    BASE64.encode(sha1(format!("{}{ACCEPT_GUID}", key.trim()).as_bytes()))
+}
+
+/// Read a frame, or `None` if its payload is larger than `max_payload`
+///
+/// Frames from clients must be masked, and unmasked frames are refused.
+fn read_frame(reader: &mut impl Read, max_payload: usize) -> io::Result<Option<Frame>> {
+    let mut header = [0; 2];
+    reader.read_exact(&mut header)?;
+    let fin = header[0] & 0x80 != 0;
+    let opcode = header[0] & 0x0F;
+    if header[1] & 0x80 == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "client frames must be masked",
+        ));
+    }
+    let length = match header[1] & 0x7F {
+        126 => {
+            let mut length = [0; 2];
+            reader.read_exact(&mut length)?;
+            u64::from(u16::from_be_bytes(length))
+        }
+        127 => {
+            let mut length = [0; 8];
+            reader.read_exact(&mut length)?;
+            u64::from_be_bytes(length)
+        }
+        length => u64::from(length),
+    };
+    let Some(length) = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= max_payload)
+    else {
+        return Ok(None);
+    };
+
+    let mut mask = [0; 4];
+    reader.read_exact(&mut mask)?;
+    let mut payload = vec![0; length];
+    reader.read_exact(&mut payload)?;
+    for (index, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+    Ok(Some(Frame {
+        fin,
+        opcode,
+        payload,
+    }))
+}
+
+/// Write an unfragmented, unmasked frame
+fn write_frame(writer: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Send a close frame with a status code, ending the connection
+fn close(writer: &mut impl Write, code: u16) -> io::Result<()> {
+    write_frame(writer, OPCODE_CLOSE, &code.to_be_bytes())
+}
+
+/// SHA-1 digest, needed only for the handshake
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
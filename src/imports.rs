@@ -0,0 +1,576 @@
+//! # Import organization
+//!
+//! Sorting and grouping of the imports at the top level of a file, for the `organize_imports`
+//! tool. Each language's `imports.scm` query captures its import statements as `@import`, and
+//! every run of them separated only by whitespace is rewritten in a deterministic order, in
+//! groups separated by a blank line:
+//!
+//! - Rust: `std`, `core`, and `alloc`; other crates; `crate`, `super`, and `self`; then
+//!   re-exports like `pub use`
+//! - Python: `__future__`; the standard library; third-party packages; relative imports, with
+//!   `import x` before `from x import y` in each group
+//! - JavaScript and TypeScript: packages, then relative paths
+//! - Go: the standard library, then other modules, merged into one `import ( ... )` block
+//! - Java: static imports, then the rest
+//!
+//! Within a group, imports are sorted by the path they import, and exact duplicates are
+//! dropped. Their text is otherwise kept as written. Comments, attributes, and any other code
+//! end a run, as does a comment on an import's own line, so comments stay with what they
+//! describe and imports are not moved past code that may depend on their order.
+
+use anyhow::{Result, anyhow};
+use tree_sitter::{Node, QueryCursor, StreamingIterator, Tree};
+
+use crate::languages::{LanguageCommon, LanguageName};
+
+/// Top-level Python standard library modules
+const PYTHON_STDLIB: &[&str] = &[
+    "abc",
+    "aifc",
+    "argparse",
+    "array",
+    "ast",
+    "asynchat",
+    "asyncio",
+    "asyncore",
+    "atexit",
+    "audioop",
+    "base64",
+    "bdb",
+    "binascii",
+    "bisect",
+    "builtins",
+    "bz2",
+    "cProfile",
+    "calendar",
+    "cgi",
+    "cgitb",
+    "chunk",
+    "cmath",
+    "cmd",
+    "code",
+    "codecs",
+    "codeop",
+    "collections",
+    "colorsys",
+    "compileall",
+    "concurrent",
+    "configparser",
+    "contextlib",
+    "contextvars",
+    "copy",
+    "copyreg",
+    "crypt",
+    "csv",
+    "ctypes",
+    "curses",
+    "dataclasses",
+    "datetime",
+    "dbm",
+    "decimal",
+    "difflib",
+    "dis",
+    "distutils",
+    "doctest",
+    "email",
+    "encodings",
+    "ensurepip",
+    "enum",
+    "errno",
+    "faulthandler",
+    "fcntl",
+    "filecmp",
+    "fileinput",
+    "fnmatch",
+    "fractions",
+    "ftplib",
+    "functools",
+    "gc",
+    "genericpath",
+    "getopt",
+    "getpass",
+    "gettext",
+    "glob",
+    "graphlib",
+    "grp",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "idlelib",
+    "imaplib",
+    "imghdr",
+    "imp",
+    "importlib",
+    "inspect",
+    "io",
+    "ipaddress",
+    "itertools",
+    "json",
+    "keyword",
+    "lib2to3",
+    "linecache",
+    "locale",
+    "logging",
+    "lzma",
+    "mailbox",
+    "mailcap",
+    "marshal",
+    "math",
+    "mimetypes",
+    "mmap",
+    "modulefinder",
+    "msilib",
+    "msvcrt",
+    "multiprocessing",
+    "netrc",
+    "nis",
+    "nntplib",
+    "nt",
+    "ntpath",
+    "nturl2path",
+    "numbers",
+    "opcode",
+    "operator",
+    "optparse",
+    "os",
+    "ossaudiodev",
+    "pathlib",
+    "pdb",
+    "pickle",
+    "pickletools",
+    "pipes",
+    "pkgutil",
+    "platform",
+    "plistlib",
+    "poplib",
+    "posix",
+    "posixpath",
+    "pprint",
+    "profile",
+    "pstats",
+    "pty",
+    "pwd",
+    "py_compile",
+    "pyclbr",
+    "pydoc",
+    "pydoc_data",
+    "pyexpat",
+    "queue",
+    "quopri",
+    "random",
+    "re",
+    "readline",
+    "reprlib",
+    "resource",
+    "rlcompleter",
+    "runpy",
+    "sched",
+    "secrets",
+    "select",
+    "selectors",
+    "shelve",
+    "shlex",
+    "shutil",
+    "signal",
+    "site",
+    "smtpd",
+    "smtplib",
+    "sndhdr",
+    "socket",
+    "socketserver",
+    "spwd",
+    "sqlite3",
+    "sre_compile",
+    "sre_constants",
+    "sre_parse",
+    "ssl",
+    "stat",
+    "statistics",
+    "string",
+    "stringprep",
+    "struct",
+    "subprocess",
+    "sunau",
+    "symtable",
+    "sys",
+    "sysconfig",
+    "syslog",
+    "tabnanny",
+    "tarfile",
+    "telnetlib",
+    "tempfile",
+    "termios",
+    "textwrap",
+    "threading",
+    "time",
+    "timeit",
+    "tkinter",
+    "token",
+    "tokenize",
+    "tomllib",
+    "trace",
+    "traceback",
+    "tracemalloc",
+    "tty",
+    "turtle",
+    "turtledemo",
+    "types",
+    "typing",
+    "unicodedata",
+    "unittest",
+    "urllib",
+    "uu",
+    "uuid",
+    "venv",
+    "warnings",
+    "wave",
+    "weakref",
+    "webbrowser",
+    "winreg",
+    "winsound",
+    "wsgiref",
+    "xdrlib",
+    "xml",
+    "xmlrpc",
+    "zipapp",
+    "zipfile",
+    "zipimport",
+    "zlib",
+    "zoneinfo",
+];
+
+/// A file's source with its imports organized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrganizedImports {
+    pub output: String,
+    /// How many runs of imports were reordered
+    pub runs: usize,
+}
+
+/// An import statement, or for Go an import spec, with where it sorts
+#[derive(Debug)]
+struct Import {
+    group: u8,
+    key: String,
+    text: String,
+}
+
+/// Whether organize_imports supports a language
+pub fn supports(language: &LanguageCommon) -> bool {
+    language.import_query().is_some()
+}
+
+/// The source with its top-level imports organized, or None if they already are
+///
+/// Fails for languages organize_imports does not support, and if the organized imports would
+/// not parse.
+pub fn organize_imports(
+    language: &LanguageCommon,
+    source: &str,
+) -> Result<Option<OrganizedImports>> {
+    let query = language.import_query().ok_or_else(|| {
+        anyhow!(
+            "organizing imports is not supported for {} files",
+            language.name()
+        )
+    })?;
+    let mut parser = language.parser()?;
+    let mut parse = |source: &str| {
+        parser
+            .parse(source, None)
+            .ok_or_else(|| anyhow!("could not parse as {}", language.name()))
+    };
+    let tree = parse(source)?;
+
+    let mut imports = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+    while let Some(query_match) = matches.next() {
+        imports.extend(
+            query_match
+                .captures
+                .iter()
+                .filter(|capture| query.capture_names()[capture.index as usize] == "import")
+                .map(|capture| capture.node.id()),
+        );
+    }
+
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut replacements = Vec::new();
+    for run in runs(&tree, source, &imports) {
+        let range = run[0].start_byte()..run[run.len() - 1].end_byte();
+        let Some(organized) = organize_run(language.name(), source, &run, newline) else {
+            continue;
+        };
+        if organized != source[range.clone()] {
+            replacements.push((range, organized));
+        }
+    }
+    if replacements.is_empty() {
+        return Ok(None);
+    }
+
+    let mut output = source.to_string();
+    for (range, organized) in replacements.iter().rev() {
+        output.replace_range(range.clone(), organized);
+    }
+    let errors = |tree: &Tree, source: &str| language.editor().collect_errors(tree, source).len();
+    if errors(&parse(&output)?, &output) > errors(&tree, source) {
+        return Err(anyhow!("organizing the imports would add syntax errors"));
+    }
+    Ok(Some(OrganizedImports {
+        output,
+        runs: replacements.len(),
+    }))
+}
+
+/// Runs of top-level imports separated only by whitespace
+///
+/// Imports with a comment or attribute just above them or a comment on their line are left
+/// where they are, and end the run before them.
+fn runs<'tree>(tree: &'tree Tree, source: &str, imports: &[usize]) -> Vec<Vec<Node<'tree>>> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    // anonymous children are separators, like the newlines between Go declarations
+    let children = root.named_children(&mut cursor).collect::<Vec<_>>();
+    let mut runs = Vec::new();
+    let mut run = Vec::<Node<'tree>>::new();
+    for (index, &child) in children.iter().enumerate() {
+        let attached_above = index
+            .checked_sub(1)
+            .map(|previous| children[previous])
+            .is_some_and(|previous| {
+                (previous.kind().contains("comment") || previous.kind() == "attribute_item")
+                    && child.start_position().row <= previous.end_position().row + 1
+            });
+        let comment_on_line = children.get(index + 1).is_some_and(|next| {
+            next.kind().contains("comment") && next.start_position().row == child.end_position().row
+        });
+        let movable = imports.contains(&child.id())
+            && !child.has_error()
+            && !attached_above
+            && !comment_on_line;
+        let adjacent = run.last().is_none_or(|last| {
+            source[last.end_byte()..child.start_byte()]
+                .trim()
+                .is_empty()
+        });
+        if !movable || !adjacent {
+            runs.push(std::mem::take(&mut run));
+        }
+        if movable {
+            run.push(child);
+        }
+    }
+    runs.push(run);
+    runs.retain(|run| !run.is_empty());
+    runs
+}
+
+/// A run of imports in organized order, or None if it cannot be organized
+fn organize_run(
+    language: LanguageName,
+    source: &str,
+    run: &[Node<'_>],
+    newline: &str,
+) -> Option<String> {
+    if language == LanguageName::Go {
+        return organize_go(source, run, newline);
+    }
+    let imports = run
+        .iter()
+        .map(|&node| {
+            let text = &source[node.byte_range()];
+            let (group, key) = match language {
+                LanguageName::Rust => rust_import(text),
+                LanguageName::Python => python_import(node, text),
+                LanguageName::Java => java_import(text),
+                _ => script_import(node, source),
+            };
+            Import {
+                group,
+                key,
+                text: text.to_string(),
+            }
+        })
+        .collect();
+    Some(
+        grouped(imports)
+            .iter()
+            .map(|group| group.join(newline))
+            .collect::<Vec<_>>()
+            .join(&format!("{newline}{newline}")),
+    )
+}
+
+/// Go import declarations merged into one block, or None if they hold comments, which the
+/// merge would lose
+fn organize_go(source: &str, run: &[Node<'_>], newline: &str) -> Option<String> {
+    let mut imports = Vec::new();
+    for &declaration in run {
+        if has_comment(declaration) {
+            return None;
+        }
+        let mut cursor = declaration.walk();
+        for child in declaration.named_children(&mut cursor) {
+            let specs = match child.kind() {
+                "import_spec" => vec![child],
+                "import_spec_list" => {
+                    let mut cursor = child.walk();
+                    child
+                        .named_children(&mut cursor)
+                        .filter(|spec| spec.kind() == "import_spec")
+                        .collect()
+                }
+                _ => vec![],
+            };
+            for spec in specs {
+                let path = spec
+                    .child_by_field_name("path")
+                    .map_or("", |path| &source[path.byte_range()])
+                    .trim_matches(['"', '`']);
+                let first_element = path.split('/').next().unwrap_or_default();
+                imports.push(Import {
+                    group: u8::from(first_element.contains('.')),
+                    key: path.to_string(),
+                    text: source[spec.byte_range()].to_string(),
+                });
+            }
+        }
+    }
+
+    let groups = grouped(imports);
+    if let [group] = groups.as_slice() {
+        if let [spec] = group.as_slice() {
+            return Some(format!("import {spec}"));
+        }
+    }
+    let specs = groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|spec| format!("\t{spec}"))
+                .collect::<Vec<_>>()
+                .join(newline)
+        })
+        .collect::<Vec<_>>()
+        .join(&format!("{newline}{newline}"));
+    Some(format!("import ({newline}{specs}{newline})"))
+}
+
+/// Imports sorted into their groups, in order, without duplicates
+fn grouped(mut imports: Vec<Import>) -> Vec<Vec<String>> {
+    imports.sort_by(|a, b| {
+        a.group
+            .cmp(&b.group)
+            .then_with(|| a.key.cmp(&b.key))
+            .then_with(|| a.text.cmp(&b.text))
+    });
+    let mut groups = Vec::<Vec<String>>::new();
+    let mut last_group = None;
+    for import in imports {
+        if last_group != Some(import.group) {
+            groups.push(Vec::new());
+            last_group = Some(import.group);
+        }
+        let group = groups.last_mut().expect("a group was just pushed");
+        if group.last() != Some(&import.text) {
+            group.push(import.text);
+        }
+    }
+    groups
+}
+
+/// The group and sort key of a Rust `use` declaration
+fn rust_import(text: &str) -> (u8, String) {
+    let path = text
+        .split_once("use")
+        .map_or(text, |(_, path)| path)
+        .trim()
+        .trim_end_matches(';')
+        .trim_end()
+        .trim_start_matches("::");
+    let root = path
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or_default();
+    let group = if text.starts_with("pub") {
+        3
+    } else {
+        match root {
+            "std" | "core" | "alloc" => 0,
+            "crate" | "super" | "self" => 2,
+            _ => 1,
+        }
+    };
+    (group, path.to_string())
+}
+
+/// The group and sort key of a Python import statement
+fn python_import(node: Node<'_>, text: &str) -> (u8, String) {
+    if node.kind() == "future_import_statement" {
+        return (0, String::new());
+    }
+    let (from, module) = match text.strip_prefix("from") {
+        Some(module) => (true, module),
+        None => (false, text.trim_start_matches("import")),
+    };
+    let module = module
+        .trim_start()
+        .chars()
+        .take_while(|&c| c.is_alphanumeric() || c == '_' || c == '.')
+        .collect::<String>();
+    let top_level = module.split('.').next().unwrap_or_default();
+    let group = if module.starts_with('.') {
+        3
+    } else if PYTHON_STDLIB.binary_search(&top_level).is_ok() {
+        1
+    } else {
+        2
+    };
+    // plain imports sort before `from` imports
+    (
+        group,
+        format!("{}{}", u8::from(from), module.to_lowercase()),
+    )
+}
+
+/// The group and sort key of a Java import declaration
+fn java_import(text: &str) -> (u8, String) {
+    let path = text.trim_start_matches("import").trim_start();
+    let (group, path) = match path
+        .strip_prefix("static")
+        .filter(|path| path.starts_with(char::is_whitespace))
+    {
+        Some(path) => (0, path.trim_start()),
+        None => (1, path),
+    };
+    (group, path.trim_end_matches(';').trim_end().to_string())
+}
+
+/// The group and sort key of a JavaScript or TypeScript import statement
+fn script_import(node: Node<'_>, source: &str) -> (u8, String) {
+    let path = node
+        .child_by_field_name("source")
+        .map_or("", |path| &source[path.byte_range()])
+        .trim_matches(['"', '\'']);
+    let group = match path.chars().next() {
+        Some('.') => 1,
+        _ => 0,
+    };
+    (group, path.to_lowercase())
+}
+
+/// Whether a node contains a comment
+fn has_comment(node: Node<'_>) -> bool {
+    let mut cursor = node.walk();
+    node.kind().contains("comment") || node.children(&mut cursor).any(|child| has_comment(child))
+}
@@ -4,11 +4,20 @@
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .c and .h files
 //! - Standardized language configuration using LanguageBuilder
-//! - Default editor for basic operations
+//! - clang-format integration using the project's `.clang-format`
 
-use super::{LanguageBuilder, LanguageName};
+use super::{LanguageBuilder, LanguageName, clang_format::ClangFormatEditor};
 use anyhow::Result;
 
+/// File extensions detected as C
+pub const FILE_EXTENSIONS: &[&str] = &["c", "h"];
+
 pub fn language() -> Result<super::LanguageCommon> {
-    LanguageBuilder::new(LanguageName::C, &["c", "h"], tree_sitter_c::LANGUAGE.into()).build()
+    LanguageBuilder::new(
+        LanguageName::C,
+        FILE_EXTENSIONS,
+        tree_sitter_c::LANGUAGE.into(),
+    )
+    .with_editor(Box::new(ClangFormatEditor::new("c")))
+    .build()
 }
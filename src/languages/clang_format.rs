@@ -0,0 +1,46 @@
+//! clang-format integration for C, C++, and Java.
+//!
+//! Source is piped through `clang-format` with the project's `.clang-format` or `_clang-format`
+//! file, found by clang-format itself from the edited file's path. Files in projects without
+//! one are left as they are rather than reformatted to clang-format's default style, as is
+//! all code when clang-format is not installed.
+
+use super::{
+    traits::{FormatContext, LanguageEditor},
+    utils::{command_succeeds, run_formatter},
+};
+use anyhow::Result;
+use std::{ffi::OsString, process::Command, sync::OnceLock};
+
+/// Whether clang-format is installed, checked once per process
+static CLANG_FORMAT: OnceLock<bool> = OnceLock::new();
+
+/// Editor that formats with clang-format
+pub struct ClangFormatEditor {
+    /// Extension telling clang-format the language of code that is not in a file
+    extension: &'static str,
+}
+
+impl ClangFormatEditor {
+    pub fn new(extension: &'static str) -> Self {
+        Self { extension }
+    }
+}
+
+impl LanguageEditor for ClangFormatEditor {
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        if !*CLANG_FORMAT.get_or_init(|| command_succeeds("clang-format", &["--version"])) {
+            return Ok(source.to_string());
+        }
+        let mut command = Command::new("clang-format");
+        command.args(["--style=file", "--fallback-style=none"]);
+        // clang-format looks for the configuration above the assumed file
+        let mut assume_filename = OsString::from("--assume-filename=");
+        match context.file_path {
+            Some(file_path) => assume_filename.push(file_path),
+            None => assume_filename.push(format!("stdin.{}", self.extension)),
+        }
+        command.arg(assume_filename);
+        run_formatter(command, source, context)
+    }
+}
@@ -4,16 +4,21 @@
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .cpp, .cc, .cxx, .c++, .hpp, .hh, .hxx files
 //! - Standardized language configuration using LanguageBuilder
-//! - Default editor for basic operations
+//! - clang-format integration using the project's `.clang-format`
 
-use super::{LanguageBuilder, LanguageName};
+use super::{Attributes, LanguageBuilder, LanguageName, clang_format::ClangFormatEditor};
 use anyhow::Result;
 
+/// File extensions detected as C++
+pub const FILE_EXTENSIONS: &[&str] = &["cpp", "cxx", "cc", "c++", "hpp", "hxx", "h++"];
+
 pub fn language() -> Result<super::LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Cpp,
-        &["cpp", "cxx", "cc", "c++", "hpp", "hxx", "h++"],
+        FILE_EXTENSIONS,
         tree_sitter_cpp::LANGUAGE.into(),
     )
+    .with_editor(Box::new(ClangFormatEditor::new("cpp")))
+    .with_attributes(Attributes::Within(&["attribute_declaration"]))
     .build()
 }
@@ -5,15 +5,23 @@
 //! - Support for .cs files
 //! - Standardized language configuration using LanguageBuilder
 //! - Default editor for basic operations
+//! - Validation queries for C# semantic correctness
 
-use super::{LanguageBuilder, LanguageName};
+use super::queries::embedded_query;
+use super::{Attributes, LanguageBuilder, LanguageName};
 use anyhow::Result;
 
+/// File extensions detected as C#
+pub const FILE_EXTENSIONS: &[&str] = &["cs"];
+
 pub fn language() -> Result<super::LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::CSharp,
-        &["cs"],
+        FILE_EXTENSIONS,
         tree_sitter_c_sharp::LANGUAGE.into(),
     )
+    .with_validation_query(embedded_query!("csharp/validation.scm"))
+    .with_definition_query(embedded_query!("csharp/definitions.scm"))
+    .with_attributes(Attributes::Within(&["attribute_list"]))
     .build()
 }
@@ -0,0 +1,87 @@
+//! Decorators for Python, JavaScript, TypeScript, and TSX declarations.
+//!
+//! Decorators are written `@name` or `@name(arguments)` in each of these languages, and
+//! tree-sitter parses them all as `decorator` nodes, so adding one works the same way:
+//! - A new decorator goes above the declaration's existing decorators, making it the
+//!   outermost
+//! - A declaration already decorated with the same name is left alone, since decorators
+//!   take arguments that cannot be merged
+//! - Python decorators live on the `decorated_definition` around the declaration, and those
+//!   of exported classes may be written before `export`
+
+use tree_sitter::Node;
+
+use super::traits::{DeclarationEdit, enclosing_declaration};
+
+/// Python declarations that take decorators
+pub const PYTHON_DECLARATIONS: &[&str] = &["function_definition", "class_definition"];
+
+/// JavaScript, TypeScript, and TSX declarations that take decorators
+pub const SCRIPT_DECLARATIONS: &[&str] = &[
+    "class_declaration",
+    "abstract_class_declaration",
+    "class",
+    "method_definition",
+    "public_field_definition",
+    "field_definition",
+];
+
+/// Nodes wrapping a declaration that may hold some of its decorators
+const WRAPPERS: &[&str] = &["decorated_definition", "export_statement"];
+
+/// Add `decorator`, with or without its `@`, to the nearest declaration of one of
+/// `declarations` containing `node`
+pub fn add_decorator<'tree>(
+    node: Node<'tree>,
+    source: &str,
+    decorator: &str,
+    declarations: &[&str],
+) -> Result<DeclarationEdit<'tree>, String> {
+    let decorator = decorator.trim();
+    let decorator = if decorator.starts_with('@') {
+        decorator.to_string()
+    } else {
+        format!("@{decorator}")
+    };
+    let declaration = enclosing_declaration(node, declarations)
+        .ok_or_else(|| "no decorated declaration, like a class, contains the anchor".to_string())?;
+    let wrapper = declaration
+        .parent()
+        .filter(|parent| WRAPPERS.contains(&parent.kind()));
+
+    let mut existing = wrapper
+        .into_iter()
+        .chain([declaration])
+        .flat_map(|node| {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .filter(|child| child.kind() == "decorator")
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    existing.sort_by_key(Node::start_byte);
+
+    let name = decorator_name(&decorator);
+    if existing
+        .iter()
+        .any(|existing| decorator_name(&source[existing.byte_range()]) == name)
+    {
+        return Err(format!(
+            "the `{}` is already decorated with @{name}; use replace_node to change its \
+             arguments",
+            declaration.kind()
+        ));
+    }
+
+    let at = existing.first().map_or_else(
+        || wrapper.unwrap_or(declaration).start_byte(),
+        Node::start_byte,
+    );
+    Ok(DeclarationEdit::insert(declaration, source, at, &decorator))
+}
+
+/// A decorator's name, like `app.route` for `@app.route("/")`
+fn decorator_name(decorator: &str) -> &str {
+    let decorator = decorator.trim_start_matches('@');
+    decorator[..decorator.find(['(', '<']).unwrap_or(decorator.len())].trim()
+}
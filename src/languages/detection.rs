@@ -0,0 +1,196 @@
+//! Detection of a file's language from its content.
+//!
+//! Files without a known extension, like `Gemfile`, `.eslintrc`, or a script run through a
+//! shebang, are parsed with each of a short list of grammars, and the one that parses the
+//! file with the fewest errors is used, if it parses enough of it. Features include:
+//! - A prioritized list of grammars for the kinds of files that commonly lack extensions,
+//!   each with the syntax a file must use to count as written in it
+//! - Shebang lines naming an interpreter, which decide the language outright, or rule
+//!   detection out for interpreters without a grammar, like `sh`
+//! - A confidence threshold, so prose and unknown formats stay plain text
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+use tree_sitter::Node;
+
+use super::{LanguageName, LanguageRegistry};
+
+/// Grammars tried on files without a known extension, in order of preference when several
+/// parse a file equally well, each with the node kinds that show a file is written in it.
+/// Permissive grammars parse prose too, as calls of identifiers, so a file only counts as
+/// one of these languages if it has one of its definitions, assignments, or literals.
+pub const DETECTION_ORDER: &[(LanguageName, &[&str])] = &[
+    (LanguageName::Json, &["object", "array"]),
+    (
+        LanguageName::Toml,
+        &["table", "table_array_element", "pair"],
+    ),
+    (
+        LanguageName::Python,
+        &[
+            "import_statement",
+            "import_from_statement",
+            "function_definition",
+            "class_definition",
+            "assignment",
+            "string",
+        ],
+    ),
+    (
+        LanguageName::Javascript,
+        &[
+            "import_statement",
+            "export_statement",
+            "function_declaration",
+            "class_declaration",
+            "lexical_declaration",
+            "variable_declaration",
+            "assignment_expression",
+            "string",
+        ],
+    ),
+    (
+        LanguageName::Ruby,
+        &[
+            "method",
+            "class",
+            "module",
+            "assignment",
+            "do_block",
+            "simple_symbol",
+            "string",
+        ],
+    ),
+];
+
+/// Share of a file's bytes that must parse without errors for a grammar to be chosen
+pub const MIN_CONFIDENCE: f64 = 0.95;
+
+/// A language chosen for a file from its content
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LanguageGuess {
+    pub language: LanguageName,
+    /// Share of the file's bytes that parse without errors, from 0 to 1
+    pub confidence: f64,
+    /// Whether the language was named by the file's shebang line
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub shebang: bool,
+}
+
+impl Display for LanguageGuess {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.shebang {
+            write!(f, "{}, from the file's shebang line", self.language)
+        } else {
+            write!(
+                f,
+                "{}, since {:.0}% of the file parses as {}",
+                self.language,
+                self.confidence * 100.0,
+                self.language
+            )
+        }
+    }
+}
+
+impl LanguageRegistry {
+    /// Guess the language of a file without a known extension from its content
+    ///
+    /// Returns None if no grammar parses enough of it, or its shebang names an interpreter
+    /// without a grammar.
+    pub fn detect_language_from_source(&self, source: &str) -> Option<LanguageGuess> {
+        if source.trim().is_empty() {
+            return None;
+        }
+        if let Some(interpreter) = shebang_interpreter(source) {
+            let language = interpreter_language(interpreter)?;
+            self.get_language(language).ok()?;
+            return Some(LanguageGuess {
+                language,
+                confidence: 1.0,
+                shebang: true,
+            });
+        }
+
+        DETECTION_ORDER
+            .iter()
+            .filter_map(|&(language, evidence)| {
+                let tree = self
+                    .get_language(language)
+                    .ok()?
+                    .parser()
+                    .ok()?
+                    .parse(source, None)?;
+                let root = tree.root_node();
+                let confidence = 1.0 - error_bytes(root) as f64 / source.len() as f64;
+                (confidence >= MIN_CONFIDENCE && has_kind(root, evidence))
+                    .then(|| (error_nodes(root), confidence, language))
+            })
+            // fewest errors first, and the earliest grammar among equals
+            .min_by_key(|&(errors, _, _)| errors)
+            .map(|(_, confidence, language)| LanguageGuess {
+                language,
+                confidence,
+                shebang: false,
+            })
+    }
+}
+
+/// The program a `#!` line runs, without its directory or version, skipping `env`
+fn shebang_interpreter(source: &str) -> Option<&str> {
+    let line = source.strip_prefix("#!")?.lines().next()?;
+    let mut words = line
+        .split_whitespace()
+        .map(|word| word.rsplit('/').next().unwrap_or(word));
+    let program = match words.next()? {
+        "env" => words.find(|word| !word.starts_with('-'))?,
+        program => program,
+    };
+    Some(program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+}
+
+fn interpreter_language(interpreter: &str) -> Option<LanguageName> {
+    match interpreter {
+        "python" => Some(LanguageName::Python),
+        "node" | "nodejs" | "deno" | "bun" => Some(LanguageName::Javascript),
+        "ruby" => Some(LanguageName::Ruby),
+        "php" => Some(LanguageName::Php),
+        _ => None,
+    }
+}
+
+/// Whether a tree has a node of one of the kinds, outside its errors
+fn has_kind(node: Node<'_>, kinds: &[&str]) -> bool {
+    if node.is_error() {
+        return false;
+    }
+    if kinds.contains(&node.kind()) {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| has_kind(child, kinds))
+}
+
+/// How many bytes lie inside error nodes
+fn error_bytes(node: Node<'_>) -> usize {
+    if node.is_error() {
+        return node.byte_range().len();
+    }
+    if !node.has_error() {
+        return 0;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).map(error_bytes).sum()
+}
+
+/// How many error and missing nodes a tree has
+fn error_nodes(node: Node<'_>) -> usize {
+    if !node.has_error() {
+        return 0;
+    }
+    let mut cursor = node.walk();
+    usize::from(node.is_error() || node.is_missing())
+        + node.children(&mut cursor).map(error_nodes).sum::<usize>()
+}
@@ -0,0 +1,175 @@
+//! Documentation comments for declarations.
+//!
+//! Each language's documentation tools look for documentation in one place, which this
+//! module puts it:
+//! - Rust `///` comments go above the item's attributes
+//! - Python docstrings go inside, as the first statement of the function or class body
+//! - JavaScript and TypeScript `/** */` blocks go above decorators and `export`
+//!
+//! Documentation is given as plain text, or already written as the comment or docstring.
+//! A declaration that is already documented is left alone.
+
+use tree_sitter::Node;
+
+use super::traits::{DeclarationEdit, enclosing_declaration};
+
+/// Python declarations that take docstrings
+pub const PYTHON_DECLARATIONS: &[&str] = &["function_definition", "class_definition"];
+
+/// JavaScript, TypeScript, and TSX declarations documented with JSDoc
+pub const SCRIPT_DECLARATIONS: &[&str] = &[
+    "function_declaration",
+    "generator_function_declaration",
+    "class_declaration",
+    "abstract_class_declaration",
+    "method_definition",
+    "method_signature",
+    "abstract_method_signature",
+    "public_field_definition",
+    "field_definition",
+    "property_signature",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+    "lexical_declaration",
+    "variable_declaration",
+];
+
+/// `text` as `///` lines, unless it is already written as them
+pub fn line_doc_comment(text: &str) -> String {
+    let text = text.trim();
+    if text.starts_with("///") {
+        return text.to_string();
+    }
+    text.lines()
+        .map(|line| match line.trim_end() {
+            "" => "///".to_string(),
+            line => format!("/// {line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Add a `/** */` block above the nearest declaration of one of `declarations` containing
+/// `node`, and above the `export` of an exported one
+pub fn add_block_doc_comment<'tree>(
+    node: Node<'tree>,
+    source: &str,
+    text: &str,
+    declarations: &[&str],
+) -> Result<DeclarationEdit<'tree>, String> {
+    let declaration = enclosing_declaration(node, declarations).ok_or_else(|| {
+        "no documented declaration, like a function, contains the anchor".to_string()
+    })?;
+    let target = declaration
+        .parent()
+        .filter(|parent| parent.kind() == "export_statement")
+        .unwrap_or(declaration);
+    if target
+        .prev_named_sibling()
+        .is_some_and(|comment| is_doc_comment(comment, source, "/**"))
+    {
+        return Err(already_documented(declaration));
+    }
+    Ok(DeclarationEdit::insert(
+        declaration,
+        source,
+        target.start_byte(),
+        &block_doc_comment(text),
+    ))
+}
+
+/// Add a docstring as the first statement of the body of the Python function or class
+/// containing `node`
+pub fn add_docstring<'tree>(
+    node: Node<'tree>,
+    source: &str,
+    text: &str,
+) -> Result<DeclarationEdit<'tree>, String> {
+    let declaration = enclosing_declaration(node, PYTHON_DECLARATIONS)
+        .ok_or_else(|| "no function or class contains the anchor".to_string())?;
+    let first = declaration
+        .child_by_field_name("body")
+        .and_then(|body| body.named_child(0))
+        .ok_or_else(|| format!("the `{}` has no body", declaration.kind()))?;
+    let first_statement = (first.kind() == "expression_statement")
+        .then(|| first.named_child(0))
+        .flatten();
+    if first_statement.is_some_and(|expression| expression.kind() == "string") {
+        return Err(already_documented(declaration));
+    }
+    let line_start = source[..first.start_byte()]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    if !source[line_start..first.start_byte()].trim().is_empty() {
+        return Err(format!(
+            "the `{}` body is on the same line as its header; move it to its own line first",
+            declaration.kind()
+        ));
+    }
+    Ok(DeclarationEdit::insert(
+        declaration,
+        source,
+        first.start_byte(),
+        &docstring(text),
+    ))
+}
+
+/// Whether `node` is a comment starting with `marker`, like `///` or `/**`
+pub fn is_doc_comment(node: Node, source: &str, marker: &str) -> bool {
+    let text = &source[node.byte_range()];
+    node.kind().contains("comment")
+        && text.starts_with(marker)
+        // `////` and `/***/` are ordinary comments
+        && !text[marker.len()..].starts_with(&marker[marker.len() - 1..])
+}
+
+/// The error for a declaration that already has documentation
+pub fn already_documented(declaration: Node) -> String {
+    format!(
+        "the `{}` is already documented; edit its documentation with replace_range instead",
+        declaration.kind()
+    )
+}
+
+/// `text` as a `/** */` block, unless it is already written as one
+fn block_doc_comment(text: &str) -> String {
+    let text = text.trim();
+    if text.starts_with("/**") {
+        return text.to_string();
+    }
+    let lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+    if let [line] = lines.as_slice() {
+        return format!("/** {line} */");
+    }
+    let body = lines
+        .iter()
+        .map(|line| match *line {
+            "" => " *".to_string(),
+            line => format!(" * {line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("/**\n{body}\n */")
+}
+
+/// `text` as a docstring, unless it is already written as one
+fn docstring(text: &str) -> String {
+    let text = text.trim();
+    let quoted = ["\"\"\"", "'''", "r\"\"\"", "r'''"];
+    if quoted.iter().any(|quote| text.starts_with(quote)) {
+        return text.to_string();
+    }
+    let quote = if text.contains("\"\"\"") {
+        "'''"
+    } else {
+        "\"\"\""
+    };
+    if text.contains('\n') {
+        // PEP 257: the summary on the opening line, the closing quotes on their own line
+        let lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+        format!("{quote}{}\n{quote}", lines.join("\n"))
+    } else {
+        format!("{quote}{text}{quote}")
+    }
+}
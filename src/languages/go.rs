@@ -1,14 +1,150 @@
-//! Go language support with tree-sitter parsing.
+//! Go language support with gofmt integration.
 //!
 //! This module provides Go-specific editing capabilities including:
+//! - gofmt integration for code formatting, or goimports when configured
+//! - Syntax validation with `gofmt -e` when gofmt is installed
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .go files
-//! - Standardized language configuration using LanguageBuilder
-//! - Default editor for basic operations
+//! - Validation queries for Go semantic correctness
 
-use super::{LanguageBuilder, LanguageName};
-use anyhow::Result;
+use super::queries::embedded_query;
+use super::{
+    LanguageBuilder, LanguageName,
+    traits::{self, FormatContext, LanguageEditor},
+    utils::run_formatter,
+};
+use anyhow::{Result, anyhow};
+use std::{
+    ops::Range,
+    path::Path,
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+use tree_sitter::{Point, Tree};
+
+/// Environment variable choosing the Go formatter, `gofmt` (the default) or `goimports`
+pub const GO_FORMATTER_ENV: &str = "SEMANTIC_EDIT_GO_FORMATTER";
+
+/// Prefix gofmt gives errors in source read from stdin
+const STDIN_PREFIX: &str = "<standard input>:";
+
+/// Whether each tool is installed, checked once per process
+static GOFMT: OnceLock<bool> = OnceLock::new();
+static GOIMPORTS: OnceLock<bool> = OnceLock::new();
+
+/// File extensions detected as Go
+pub const FILE_EXTENSIONS: &[&str] = &["go"];
 
 pub fn language() -> Result<super::LanguageCommon> {
-    LanguageBuilder::new(LanguageName::Go, &["go"], tree_sitter_go::LANGUAGE.into()).build()
+    let formatter = match std::env::var(GO_FORMATTER_ENV).as_deref() {
+        Ok("gofmt") | Err(_) => GoFormatter::Gofmt,
+        Ok("goimports") => GoFormatter::Goimports,
+        Ok(other) => {
+            return Err(anyhow!(
+                "{GO_FORMATTER_ENV} must be `gofmt` or `goimports`, not `{other}`"
+            ));
+        }
+    };
+
+    LanguageBuilder::new(
+        LanguageName::Go,
+        FILE_EXTENSIONS,
+        tree_sitter_go::LANGUAGE.into(),
+    )
+    .with_editor(Box::new(GoEditor { formatter }))
+    .with_validation_query(embedded_query!("go/validation.scm"))
+    .with_definition_query(embedded_query!("go/definitions.scm"))
+    .with_import_query(embedded_query!("go/imports.scm"))
+    .build()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GoFormatter {
+    Gofmt,
+    Goimports,
+}
+
+impl GoFormatter {
+    fn is_installed(self) -> bool {
+        match self {
+            Self::Gofmt => *GOFMT.get_or_init(|| command_exists("gofmt")),
+            Self::Goimports => *GOIMPORTS.get_or_init(|| command_exists("goimports")),
+        }
+    }
+}
+
+/// Whether `program` can be run. Go tools have no version flag and exit with an error after
+/// printing their usage, so only starting the process is checked.
+fn command_exists(program: &str) -> bool {
+    Command::new(program)
+        .arg("-h")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+struct GoEditor {
+    formatter: GoFormatter,
+}
+
+impl LanguageEditor for GoEditor {
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        if !self.formatter.is_installed() {
+            return Ok(source.to_string());
+        }
+        let command = match self.formatter {
+            GoFormatter::Gofmt => Command::new("gofmt"),
+            GoFormatter::Goimports => {
+                let mut command = Command::new("goimports");
+                // resolves imports against the packages next to the file
+                if let Some(dir) = context.file_path.and_then(Path::parent) {
+                    command.arg("-srcdir").arg(dir);
+                }
+                command
+            }
+        };
+        run_formatter(command, source, context)
+    }
+
+    fn collect_errors(&self, tree: &Tree, content: &str) -> Vec<Range<Point>> {
+        let tree_sitter_errors = || {
+            traits::collect_errors(tree)
+                .into_iter()
+                .map(|node| node.start_position()..node.end_position())
+                .collect()
+        };
+        if !GoFormatter::Gofmt.is_installed() {
+            return tree_sitter_errors();
+        }
+        let mut command = Command::new("gofmt");
+        command.arg("-e");
+        match run_formatter(command, content, &FormatContext::default()) {
+            Ok(_) => vec![],
+            Err(error) => {
+                let errors: Vec<_> = error.to_string().lines().filter_map(gofmt_error).collect();
+                // no positions means gofmt failed for another reason than the syntax
+                if errors.is_empty() {
+                    tree_sitter_errors()
+                } else {
+                    errors
+                }
+            }
+        }
+    }
+}
+
+/// The position of an error line like `<standard input>:3:5: expected ';', found x`, whose
+/// line and byte column are 1-based
+fn gofmt_error(line: &str) -> Option<Range<Point>> {
+    let mut parts = line.strip_prefix(STDIN_PREFIX)?.splitn(3, ':');
+    let row = parts.next()?.parse::<usize>().ok()?.checked_sub(1)?;
+    let column = parts.next()?.parse::<usize>().ok()?.saturating_sub(1);
+    Some(
+        Point { row, column }..Point {
+            row,
+            column: column + 1,
+        },
+    )
 }
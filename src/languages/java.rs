@@ -4,16 +4,71 @@
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .java files
 //! - Standardized language configuration using LanguageBuilder
-//! - Default editor for basic operations
+//! - clang-format integration using the project's `.clang-format`, or google-java-format
+//!   when configured
+//! - Validation queries for Java semantic correctness
 
-use super::{LanguageBuilder, LanguageName};
-use anyhow::Result;
+use super::queries::embedded_query;
+use super::{
+    Attributes, LanguageBuilder, LanguageName,
+    clang_format::ClangFormatEditor,
+    traits::{FormatContext, LanguageEditor},
+    utils::{command_succeeds, run_formatter},
+};
+use anyhow::{Result, anyhow};
+use std::{process::Command, sync::OnceLock};
+
+/// Environment variable choosing the Java formatter, `clang-format` (the default) or
+/// `google-java-format`
+pub const JAVA_FORMATTER_ENV: &str = "SEMANTIC_EDIT_JAVA_FORMATTER";
+
+/// Whether google-java-format is installed, checked once per process
+static GOOGLE_JAVA_FORMAT: OnceLock<bool> = OnceLock::new();
+
+/// File extensions detected as Java
+pub const FILE_EXTENSIONS: &[&str] = &["java"];
 
 pub fn language() -> Result<super::LanguageCommon> {
+    let editor: Box<dyn LanguageEditor> = match std::env::var(JAVA_FORMATTER_ENV).as_deref() {
+        Ok("clang-format") | Err(_) => Box::new(ClangFormatEditor::new("java")),
+        Ok("google-java-format") => Box::new(GoogleJavaFormatEditor),
+        Ok(other) => {
+            return Err(anyhow!(
+                "{JAVA_FORMATTER_ENV} must be `clang-format` or `google-java-format`, not `{other}`"
+            ));
+        }
+    };
+
     LanguageBuilder::new(
         LanguageName::Java,
-        &["java"],
+        FILE_EXTENSIONS,
         tree_sitter_java::LANGUAGE.into(),
     )
+    .with_editor(editor)
+    .with_validation_query(embedded_query!("java/validation.scm"))
+    .with_definition_query(embedded_query!("java/definitions.scm"))
+    .with_import_query(embedded_query!("java/imports.scm"))
+    .with_attributes(Attributes::Within(&[
+        "marker_annotation",
+        "annotation",
+        "modifiers",
+    ]))
     .build()
 }
+
+/// Editor that formats with google-java-format, which applies Google style whatever the
+/// project's configuration
+struct GoogleJavaFormatEditor;
+
+impl LanguageEditor for GoogleJavaFormatEditor {
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        if !*GOOGLE_JAVA_FORMAT
+            .get_or_init(|| command_succeeds("google-java-format", &["--version"]))
+        {
+            return Ok(source.to_string());
+        }
+        let mut command = Command::new("google-java-format");
+        command.arg("-");
+        run_formatter(command, source, context)
+    }
+}
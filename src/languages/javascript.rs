@@ -4,17 +4,27 @@
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .js and .mjs files
 //! - Standardized language configuration using LanguageBuilder
-//! - Default editor for basic operations
+//! - Formatting with the project's prettier, when available
 
-use crate::languages::{LanguageBuilder, LanguageCommon, LanguageName};
+use crate::languages::queries::embedded_query;
+use crate::languages::{
+    Attributes, LanguageBuilder, LanguageCommon, LanguageName, prettier::PrettierEditor,
+};
 use anyhow::Result;
 
+/// File extensions detected as JavaScript
+pub const FILE_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs"];
+
 pub fn language() -> Result<LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Javascript,
-        &["js", "jsx", "mjs", "cjs"],
+        FILE_EXTENSIONS,
         tree_sitter_javascript::LANGUAGE.into(),
     )
-    .with_validation_query(include_str!("../../queries/javascript/validation.scm"))
+    .with_editor(Box::new(PrettierEditor::new("babel")))
+    .with_validation_query(embedded_query!("javascript/validation.scm"))
+    .with_definition_query(embedded_query!("javascript/definitions.scm"))
+    .with_import_query(embedded_query!("javascript/imports.scm"))
+    .with_attributes(Attributes::Within(&["decorator"]))
     .build()
 }
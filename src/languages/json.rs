@@ -1,30 +1,79 @@
 //! JSON language support with intelligent formatting and validation.
 //!
 //! This module provides JSON-specific editing capabilities including:
-//! - Smart indentation detection (tabs, 2-space, 4-space, or custom)
+//! - Formatting with the project's prettier when available
+//! - Otherwise indentation from the project's `.prettierrc` or `.editorconfig`, or detected
+//!   from the file (tabs, 2-space, 4-space, or custom)
 //! - Syntax validation using serde_json
 //! - Format preservation based on existing code style
 //! - Tree-sitter parsing for AST-aware operations
+//! - JSON with comments (JSONC), as in `tsconfig.json` and VS Code settings, validated with
+//!   its comments and trailing commas allowed and never reformatted in a way that drops them
 
-use super::{LanguageBuilder, LanguageCommon, LanguageName, traits::LanguageEditor};
+use super::queries::embedded_query;
+use super::{
+    LanguageBuilder, LanguageCommon, LanguageName, prettier,
+    traits::{FormatContext, LanguageEditor, collect_errors},
+};
+use crate::editorconfig;
 use anyhow::Result;
 use jsonformat::Indentation;
 use serde_json::Value;
 use std::collections::BTreeMap;
-use tree_sitter::Tree;
+use std::ops::Range;
+use std::path::Path;
+use tree_sitter::{Node, Point, Tree};
+
+/// File extensions detected as JSON
+pub const FILE_EXTENSIONS: &[&str] = &["json"];
+
+/// File extensions detected as JSON with comments
+pub const JSONC_FILE_EXTENSIONS: &[&str] = &["jsonc"];
 
 pub fn language() -> Result<LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Json,
-        &["json"],
+        FILE_EXTENSIONS,
         tree_sitter_json::LANGUAGE.into(),
     )
     .with_editor(Box::new(JsonEditor::new()))
-    .with_validation_query(include_str!("../../queries/json/validation.scm"))
+    .with_validation_query(embedded_query!("json/validation.scm"))
     .build()
 }
 
-pub struct JsonEditor;
+/// JSON with comments and trailing commas, parsed with the JSON grammar
+pub fn jsonc_language() -> Result<LanguageCommon> {
+    LanguageBuilder::new(
+        LanguageName::Jsonc,
+        JSONC_FILE_EXTENSIONS,
+        tree_sitter_json::LANGUAGE.into(),
+    )
+    .with_editor(Box::new(JsonEditor::jsonc()))
+    .with_validation_query(embedded_query!("json/validation.scm"))
+    .build()
+}
+
+/// Whether a `.json` file is conventionally JSON with comments: TypeScript and JavaScript
+/// project configuration, and VS Code and dev container settings
+pub fn is_jsonc_path(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let in_vscode = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == ".vscode");
+    name.ends_with(".json")
+        && (name.starts_with("tsconfig")
+            || name.starts_with("jsconfig")
+            || name == "devcontainer.json"
+            || in_vscode)
+}
+
+pub struct JsonEditor {
+    /// Whether comments and trailing commas are allowed, as in JSONC
+    comments: bool,
+}
 
 impl Default for JsonEditor {
     fn default() -> Self {
@@ -34,12 +83,39 @@ impl Default for JsonEditor {
 
 impl JsonEditor {
     pub fn new() -> Self {
-        Self
+        Self { comments: false }
+    }
+
+    /// An editor for JSON with comments and trailing commas
+    pub fn jsonc() -> Self {
+        Self { comments: true }
     }
 }
 
 impl LanguageEditor for JsonEditor {
-    fn format_code(&self, source: &str) -> Result<String> {
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        if self.comments {
+            // jsonformat drops comments, so without prettier JSONC is left as it is
+            return Ok(
+                prettier::format(source, context, "jsonc")?.unwrap_or_else(|| source.to_string())
+            );
+        }
+        if let Some(formatted) = prettier::format(source, context, "json")? {
+            return Ok(formatted);
+        }
+
+        let custom;
+        match prettier_indentation(context).or(context.indentation) {
+            Some(editorconfig::Indentation::Tabs) => {
+                return Ok(jsonformat::format(source, Indentation::Tab));
+            }
+            Some(indentation) => {
+                custom = indentation.unit();
+                return Ok(jsonformat::format(source, Indentation::Custom(&custom)));
+            }
+            None => {}
+        }
+
         let mut tab_count = 0;
         let mut space_counts = BTreeMap::<usize, usize>::new();
         let mut last_indentation = 0;
@@ -59,8 +135,6 @@ impl LanguageEditor for JsonEditor {
             }
         }
 
-        let custom;
-
         let indentation_style = match space_counts
             .into_iter()
             .map(|(k, v)| (Some(k), v))
@@ -80,12 +154,120 @@ impl LanguageEditor for JsonEditor {
         Ok(jsonformat::format(source, indentation_style))
     }
 
-    fn collect_errors(&self, _tree: &Tree, content: &str) -> Vec<usize> {
+    fn collect_later_errors(&self, tree: &Tree, content: &str) -> Option<Vec<Range<Point>>> {
+        // serde_json stops at the first syntax error
+        let strict = self.comments.then(|| without_comments(content));
+        Some(
+            collect_errors(tree)
+                .into_iter()
+                .filter(|error| {
+                    strict
+                        .as_deref()
+                        .is_none_or(|strict| !is_trailing_comma(*error, content, strict))
+                })
+                .map(|error| error.start_position()..error.end_position())
+                .collect(),
+        )
+    }
+
+    fn collect_errors(&self, _tree: &Tree, content: &str) -> Vec<Range<Point>> {
+        let strict;
+        let content = if self.comments {
+            strict = without_comments(content);
+            &strict
+        } else {
+            content
+        };
         match serde_json::from_str::<Value>(content) {
             Ok(_) => vec![],
             Err(e) => {
-                vec![e.line().saturating_sub(1)]
+                // serde_json lines and columns are 1-based
+                let start = Point {
+                    row: e.line().saturating_sub(1),
+                    column: e.column().saturating_sub(1),
+                };
+                vec![
+                    start..Point {
+                        column: start.column + 1,
+                        ..start
+                    },
+                ]
             }
         }
     }
 }
+
+/// Whether a syntax tree error is only a trailing comma, which JSONC allows: the text up to
+/// the error's end ends with a comma that [`without_comments`] blanks out
+fn is_trailing_comma(error: Node<'_>, content: &str, strict: &str) -> bool {
+    let text = content[error.start_byte()..error.end_byte()].trim();
+    let before = content[..error.end_byte()].trim_end();
+    (text.is_empty() || text == ",")
+        && before.ends_with(',')
+        && strict.as_bytes()[before.len() - 1] == b' '
+}
+
+/// JSONC as strict JSON, with its comments and trailing commas blanked out so errors are
+/// reported where they are in the original
+fn without_comments(content: &str) -> String {
+    let mut bytes = content.as_bytes().to_vec();
+    let mut commas = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], bytes.get(index + 1)) {
+            (b'"', _) => {
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+            }
+            (b'/', Some(b'/')) => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    bytes[index] = b' ';
+                    index += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                let end = content[index + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| index + 2 + end + 2);
+                for byte in &mut bytes[index..end] {
+                    if !byte.is_ascii_whitespace() {
+                        *byte = b' ';
+                    }
+                }
+                index = end;
+                continue;
+            }
+            (b',', _) => commas.push(index),
+            _ => {}
+        }
+        index += 1;
+    }
+    for comma in commas {
+        let next = bytes[comma + 1..]
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace());
+        if matches!(next, Some(b'}' | b']')) {
+            bytes[comma] = b' ';
+        }
+    }
+    // whole comments and commas were replaced with spaces, so this is still UTF-8
+    String::from_utf8(bytes).unwrap_or_else(|_| content.to_string())
+}
+
+/// Indentation from a JSON `.prettierrc`. Prettier's YAML and JavaScript configuration
+/// formats are not read.
+fn prettier_indentation(context: &FormatContext) -> Option<editorconfig::Indentation> {
+    let config = context.find_config(&[".prettierrc", ".prettierrc.json"])?;
+    let config: Value = serde_json::from_str(&std::fs::read_to_string(config).ok()?).ok()?;
+    let use_tabs = config.get("useTabs").and_then(Value::as_bool);
+    let tab_width = config.get("tabWidth").and_then(Value::as_u64);
+    match (use_tabs, tab_width) {
+        (Some(true), _) => Some(editorconfig::Indentation::Tabs),
+        (None, None) => None,
+        (_, width) => Some(editorconfig::Indentation::Spaces(
+            width.map_or(2, |width| width as usize),
+        )),
+    }
+}
@@ -0,0 +1,187 @@
+//! Declarations as logical units, with their attributes and comments.
+//!
+//! A declaration's attributes, decorators, annotations, and documentation comments belong
+//! to it, even though tree-sitter parses some of them as separate nodes, so node-relative
+//! inserts treat them as one unit. Features include:
+//! - Per-language attribute syntax, written before the declaration like Rust's `#[...]` or
+//!   inside it like Java annotations and Python decorators
+//! - Comments directly above a declaration, without a blank line between, as its
+//!   documentation
+//! - A comment after a declaration on its last line, as part of it
+
+use std::ops::Range;
+
+use tree_sitter::Node;
+
+use super::LanguageCommon;
+
+/// The nodes a language writes attributes, decorators, or annotations as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Attributes {
+    /// The language has no attributes
+    #[default]
+    None,
+    /// Nodes before the declaration they belong to, like Rust's `#[derive(Debug)]`
+    Before(&'static [&'static str]),
+    /// Nodes at the start of the declaration they belong to, or of a node within it, like
+    /// Java's annotations inside `modifiers`. Ones that do not start their parent, like
+    /// TypeScript's decorators on class members, come before the declaration instead.
+    Within(&'static [&'static str]),
+}
+
+impl Attributes {
+    fn kinds(self) -> &'static [&'static str] {
+        match self {
+            Self::None => &[],
+            Self::Before(kinds) | Self::Within(kinds) => kinds,
+        }
+    }
+}
+
+/// A declaration with the attributes and comments that belong to it
+#[derive(Debug, Clone, Copy)]
+pub struct LogicalUnit<'tree> {
+    /// The declaration itself, or the node the unit was found from if it is not part of one
+    pub declaration: Node<'tree>,
+    /// Bytes from its first attribute or documentation comment to the end of any comment
+    /// on its last line
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+impl LogicalUnit<'_> {
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start_byte..self.end_byte
+    }
+}
+
+impl LanguageCommon {
+    /// The declaration `node` belongs to, as a unit with its attributes and comments
+    ///
+    /// A node inside an attribute, or a comment directly above a declaration, belongs to
+    /// that declaration. Any other node is its own declaration.
+    pub fn logical_unit<'tree>(&self, node: Node<'tree>) -> LogicalUnit<'tree> {
+        let attributes = self.attributes();
+        let kinds = attributes.kinds();
+        let is_attribute = |node: &Node<'_>| kinds.contains(&node.kind());
+        let attribute = std::iter::successors(Some(node), Node::parent)
+            .filter(is_attribute)
+            .last();
+
+        let mut declaration = match attribute {
+            Some(attribute) if starts_declaration(attributes, attribute, &is_attribute) => {
+                std::iter::successors(attribute.parent(), Node::parent)
+                    .find(|node| !is_attribute(node))
+                    .unwrap_or(attribute)
+            }
+            Some(attribute) => {
+                std::iter::successors(attribute.next_named_sibling(), Node::next_named_sibling)
+                    .find(|node| !is_attribute(node) && !is_comment(node))
+                    .unwrap_or(attribute)
+            }
+            None => node,
+        };
+        if is_comment(&declaration) && !trails_code(declaration) {
+            declaration = attached_declaration(declaration, &is_attribute).unwrap_or(declaration);
+        }
+        // a declaration wrapped together with its attributes, like Python's
+        // `decorated_definition`, belongs to the wrapper
+        if let Attributes::Within(_) = attributes {
+            while let Some(parent) = declaration.parent().filter(|parent| {
+                parent.end_byte() == declaration.end_byte()
+                    && declaration.prev_named_sibling().is_some()
+                    && std::iter::successors(
+                        declaration.prev_named_sibling(),
+                        Node::prev_named_sibling,
+                    )
+                    .all(|node| is_attribute(&node) || is_comment(&node))
+                    && parent
+                        .named_child(0)
+                        .is_some_and(|first| is_attribute(&first))
+            }) {
+                declaration = parent;
+            }
+        }
+
+        let mut start = declaration;
+        while let Some(previous) = start.prev_named_sibling() {
+            let leading_attribute = is_attribute(&previous);
+            let documentation = is_comment(&previous)
+                && !trails_code(previous)
+                && last_row(previous) + 1 >= start.start_position().row;
+            if !leading_attribute && !documentation {
+                break;
+            }
+            start = previous;
+        }
+        let end_byte = declaration
+            .next_named_sibling()
+            .filter(|next| is_comment(next) && trails_code(*next))
+            .map_or(declaration.end_byte(), |comment| comment.end_byte());
+
+        LogicalUnit {
+            declaration,
+            start_byte: start.start_byte(),
+            end_byte,
+        }
+    }
+}
+
+/// Whether an attribute is inside the declaration it belongs to, rather than before it
+fn starts_declaration(
+    attributes: Attributes,
+    attribute: Node<'_>,
+    is_attribute: &impl Fn(&Node<'_>) -> bool,
+) -> bool {
+    let Attributes::Within(_) = attributes else {
+        return false;
+    };
+    let first = std::iter::successors(Some(attribute), Node::prev_named_sibling)
+        .take_while(|node| is_attribute(node) || is_comment(node))
+        .last()
+        .unwrap_or(attribute);
+    attribute
+        .parent()
+        .is_some_and(|parent| parent.start_byte() == first.start_byte())
+}
+
+/// The row a node's last character is on, which for line comments that include their
+/// newline is the row before their end position
+fn last_row(node: Node<'_>) -> usize {
+    let end = node.end_position();
+    if end.column == 0 && end.row > node.start_position().row {
+        end.row - 1
+    } else {
+        end.row
+    }
+}
+
+fn is_comment(node: &Node<'_>) -> bool {
+    node.kind().ends_with("comment")
+}
+
+/// Whether a comment is on the same line as the end of the code before it
+fn trails_code(comment: Node<'_>) -> bool {
+    comment
+        .prev_sibling()
+        .is_some_and(|previous| last_row(previous) == comment.start_position().row)
+}
+
+/// The declaration a comment documents: the first node after it and any further comments
+/// and attributes, if no blank line separates them
+fn attached_declaration<'tree>(
+    comment: Node<'tree>,
+    is_attribute: &impl Fn(&Node<'_>) -> bool,
+) -> Option<Node<'tree>> {
+    let mut previous = comment;
+    while let Some(next) = previous.next_named_sibling() {
+        if next.start_position().row > last_row(previous) + 1 {
+            return None;
+        }
+        if !is_comment(&next) && !is_attribute(&next) {
+            return Some(next);
+        }
+        previous = next;
+    }
+    None
+}
@@ -13,6 +13,10 @@
 //! - **Data**: JSON, TOML
 //! - **Generic**: Plain text
 //!
+//! Each language other than plain text is a cargo feature named after its module (`rust`,
+//! `python`, `csharp`, ...), all enabled by the default `all-languages` feature. Files in a
+//! language that is not compiled in are edited as plain text.
+//!
 //! ## Key Components
 //!
 //! - [`LanguageRegistry`]: Central registry for all supported languages
@@ -24,25 +28,67 @@
 //!
 //! - **Standardized configuration**: Consistent setup across all languages
 //! - **Custom editors**: Language-specific formatting and validation
-//! - **Validation queries**: Tree-sitter queries for semantic validation
-//! - **Auto-detection**: File extension-based language detection
-//! - **Performance**: Shared utilities and optimizations
+//! - **Lazy initialization**: Each language is built, and its validation queries compiled,
+//!   the first time it is used
+//! - **Validation queries**: Tree-sitter queries for semantic validation, compiled once per
+//!   process
+//! - **Auto-detection**: File extension-based language detection, falling back to the
+//!   [content](detection) of files without a known extension
+//! - **Performance**: Shared utilities and optimizations, with pooled parsers reused across
+//!   edits
+//! - **Logical units**: Each language's attribute syntax, so node operations treat a
+//!   declaration with its attributes and documentation comments as [one unit](logical_unit)
 
+#[cfg(feature = "c")]
 pub mod c;
+#[cfg(any(feature = "c", feature = "cpp", feature = "java"))]
+pub mod clang_format;
+#[cfg(feature = "cpp")]
 pub mod cpp;
+#[cfg(feature = "csharp")]
 pub mod csharp;
+#[cfg(any(
+    feature = "javascript",
+    feature = "python",
+    feature = "tsx",
+    feature = "typescript"
+))]
+pub mod decorators;
+pub mod detection;
+pub mod doc_comments;
+#[cfg(feature = "go")]
 pub mod go;
+#[cfg(feature = "java")]
 pub mod java;
+#[cfg(feature = "javascript")]
 pub mod javascript;
+#[cfg(feature = "json")]
 pub mod json;
+pub mod logical_unit;
+pub mod parser_pool;
+#[cfg(feature = "php")]
 pub mod php;
 pub mod plain;
+#[cfg(any(
+    feature = "javascript",
+    feature = "json",
+    feature = "tsx",
+    feature = "typescript"
+))]
+pub mod prettier;
+#[cfg(feature = "python")]
 pub mod python;
+pub mod queries;
+#[cfg(feature = "ruby")]
 pub mod ruby;
+#[cfg(feature = "rust")]
 pub mod rust;
+#[cfg(feature = "toml")]
 pub mod toml;
 pub mod traits;
+#[cfg(feature = "tsx")]
 pub mod tsx;
+#[cfg(feature = "typescript")]
 pub mod typescript;
 pub mod utils;
 
@@ -53,20 +99,57 @@ use std::{
     collections::HashMap,
     fmt::{self, Display, Formatter},
     path::Path,
+    sync::{Arc, OnceLock},
 };
 use tree_sitter::{Language, Parser, Query};
 
 use crate::error::SemanticEditError;
+pub use detection::LanguageGuess;
+pub use logical_unit::{Attributes, LogicalUnit};
+pub use queries::EmbeddedQuery;
 
+use crate::languages::parser_pool::{ParserPool, PooledParser};
 use crate::languages::traits::{DefaultEditor, LanguageEditor};
 
 /// Registry to manage all supported languages
+///
+/// Languages are registered with a constructor and built on first use, so a session that
+/// only edits one language does not load every grammar. Each language keeps a pool of
+/// parsers that editors check out and return, so parsers are reused across tool calls
+/// instead of created for every parse.
 #[derive(Debug)]
 pub struct LanguageRegistry {
-    languages: HashMap<LanguageName, LanguageCommon>,
+    languages: HashMap<LanguageName, LazyLanguage>,
     extensions: HashMap<&'static str, LanguageName>,
 }
 
+/// A registered language, built by its constructor the first time it is requested
+struct LazyLanguage {
+    constructor: fn() -> Result<LanguageCommon>,
+    /// The built language, or the constructor's error, which is kept rather than retried
+    built: OnceLock<Result<LanguageCommon, String>>,
+}
+
+impl LazyLanguage {
+    fn get(&self, name: LanguageName) -> Result<&LanguageCommon, SemanticEditError> {
+        self.built
+            .get_or_init(|| (self.constructor)().map_err(|error| format!("{error:#}")))
+            .as_ref()
+            .map_err(|message| SemanticEditError::LanguageInitFailed {
+                language: name.to_string(),
+                message: message.clone(),
+            })
+    }
+}
+
+impl fmt::Debug for LazyLanguage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyLanguage")
+            .field("built", &self.built)
+            .finish()
+    }
+}
+
 #[derive(fieldwork::Fieldwork)]
 #[fieldwork(get)]
 pub struct LanguageCommon {
@@ -76,7 +159,18 @@ pub struct LanguageCommon {
     #[fieldwork(rename = tree_sitter_language)]
     language: Language,
     editor: Box<dyn LanguageEditor>,
-    validation_query: Option<Query>,
+    validation_query: Option<Arc<Query>>,
+    /// Captures definitions for duplicate detection, see [`crate::validation::DefinitionValidator`]
+    definition_query: Option<Arc<Query>>,
+    /// Captures top-level imports as `@import`, see [`crate::imports`]
+    import_query: Option<Arc<Query>>,
+    /// How declarations are annotated, see [`Self::logical_unit`]
+    #[fieldwork(get(copy))]
+    attributes: Attributes,
+    /// Parsers reused across editors, see [`Self::parser`]
+    parser_pool: ParserPool,
+    /// Sources of the embedded queries, for listing what they refer to
+    embedded_queries: Vec<EmbeddedQuery>,
 }
 
 impl fmt::Debug for LanguageCommon {
@@ -86,6 +180,10 @@ impl fmt::Debug for LanguageCommon {
             .field("file_extensions", &self.file_extensions)
             .field("language", &self.language)
             .field("validation_query", &self.validation_query)
+            .field("definition_query", &self.definition_query)
+            .field("import_query", &self.import_query)
+            .field("attributes", &self.attributes)
+            .field("parser_pool", &self.parser_pool)
             .finish()
     }
 }
@@ -101,7 +199,10 @@ pub struct LanguageBuilder {
     file_extensions: &'static [&'static str],
     language: Language,
     editor: Option<Box<dyn LanguageEditor>>,
-    validation_query_content: Option<&'static str>,
+    validation_query: Option<EmbeddedQuery>,
+    definition_query: Option<EmbeddedQuery>,
+    import_query: Option<EmbeddedQuery>,
+    attributes: Attributes,
 }
 
 impl std::fmt::Debug for LanguageBuilder {
@@ -111,7 +212,10 @@ impl std::fmt::Debug for LanguageBuilder {
             .field("file_extensions", &self.file_extensions)
             .field("language", &self.language)
             .field("editor", &"<Box<dyn LanguageEditor>>")
-            .field("validation_query_content", &self.validation_query_content)
+            .field("validation_query", &self.validation_query)
+            .field("definition_query", &self.definition_query)
+            .field("import_query", &self.import_query)
+            .field("attributes", &self.attributes)
             .finish()
     }
 }
@@ -128,7 +232,10 @@ impl LanguageBuilder {
             file_extensions,
             language,
             editor: None,
-            validation_query_content: None,
+            validation_query: None,
+            definition_query: None,
+            import_query: None,
+            attributes: Attributes::None,
         }
     }
 
@@ -139,27 +246,56 @@ impl LanguageBuilder {
     }
 
     /// Add a validation query from embedded content
-    pub fn with_validation_query(mut self, query_content: &'static str) -> Self {
-        self.validation_query_content = Some(query_content);
+    pub fn with_validation_query(mut self, query: impl Into<EmbeddedQuery>) -> Self {
+        self.validation_query = Some(query.into());
+        self
+    }
+
+    /// Add a duplicate definition query from embedded content
+    pub fn with_definition_query(mut self, query: impl Into<EmbeddedQuery>) -> Self {
+        self.definition_query = Some(query.into());
+        self
+    }
+
+    /// Add a query capturing the imports organize_imports sorts from embedded content
+    pub fn with_import_query(mut self, query: impl Into<EmbeddedQuery>) -> Self {
+        self.import_query = Some(query.into());
+        self
+    }
+
+    /// Set the nodes declarations are annotated with, which node-relative inserts keep
+    /// together with the declaration
+    pub fn with_attributes(mut self, attributes: Attributes) -> Self {
+        self.attributes = attributes;
         self
     }
 
     /// Build the final LanguageCommon configuration
     pub fn build(self) -> Result<LanguageCommon> {
-        let validation_query = if let Some(content) = self.validation_query_content {
-            Some(tree_sitter::Query::new(&self.language, content)?)
-        } else {
-            None
-        };
+        let compile = |query| queries::compile(self.name, &self.language, query);
+        let validation_query = self.validation_query.map(compile).transpose()?;
+        let definition_query = self.definition_query.map(compile).transpose()?;
+        let import_query = self.import_query.map(compile).transpose()?;
+        let embedded_queries = self
+            .validation_query
+            .into_iter()
+            .chain(self.definition_query)
+            .chain(self.import_query)
+            .collect();
 
         Ok(LanguageCommon {
             name: self.name,
             file_extensions: self.file_extensions,
+            parser_pool: ParserPool::new(self.language.clone()),
             language: self.language,
             editor: self
                 .editor
                 .unwrap_or_else(|| Box::new(DefaultEditor::new())),
             validation_query,
+            definition_query,
+            import_query,
+            attributes: self.attributes,
+            embedded_queries,
         })
     }
 }
@@ -175,6 +311,13 @@ pub fn simple_language(
 }
 
 impl LanguageCommon {
+    /// Check out a parser from this language's pool. It returns to the pool when dropped,
+    /// so hold it only as long as it is needed.
+    pub fn parser(&self) -> Result<PooledParser<'_>> {
+        self.parser_pool.checkout()
+    }
+
+    /// Create a parser outside the pool, for callers that need to keep one
     pub fn tree_sitter_parser(&self) -> Result<Parser> {
         let mut parser = Parser::new();
         parser.set_language(self.tree_sitter_language())?;
@@ -197,6 +340,7 @@ impl LanguageCommon {
 pub enum LanguageName {
     Rust,
     Json,
+    Jsonc,
     Toml,
     Javascript,
     Typescript,
@@ -217,6 +361,7 @@ impl LanguageName {
         match self {
             LanguageName::Rust => "rust",
             LanguageName::Json => "json",
+            LanguageName::Jsonc => "jsonc",
             LanguageName::Toml => "toml",
             LanguageName::Javascript => "javascript",
             LanguageName::Typescript => "typescript",
@@ -247,39 +392,127 @@ impl LanguageRegistry {
             extensions: HashMap::new(),
         };
 
-        registry.register_language(json::language()?);
-        registry.register_language(rust::language()?);
-        registry.register_language(toml::language()?);
-        registry.register_language(typescript::language()?);
-        registry.register_language(tsx::language()?);
-        registry.register_language(javascript::language()?);
-        registry.register_language(python::language()?);
-        registry.register_language(go::language()?);
-        registry.register_language(cpp::language()?);
-        registry.register_language(c::language()?);
-        registry.register_language(java::language()?);
-        registry.register_language(php::language()?);
-        registry.register_language(csharp::language()?);
-        registry.register_language(ruby::language()?);
-        registry.register_language(plain::language()?);
+        #[cfg(feature = "json")]
+        registry.register_lazy(LanguageName::Json, json::FILE_EXTENSIONS, json::language);
+        #[cfg(feature = "json")]
+        registry.register_lazy(
+            LanguageName::Jsonc,
+            json::JSONC_FILE_EXTENSIONS,
+            json::jsonc_language,
+        );
+        #[cfg(feature = "rust")]
+        registry.register_lazy(LanguageName::Rust, rust::FILE_EXTENSIONS, rust::language);
+        #[cfg(feature = "toml")]
+        registry.register_lazy(LanguageName::Toml, toml::FILE_EXTENSIONS, toml::language);
+        #[cfg(feature = "typescript")]
+        registry.register_lazy(
+            LanguageName::Typescript,
+            typescript::FILE_EXTENSIONS,
+            typescript::language,
+        );
+        #[cfg(feature = "tsx")]
+        registry.register_lazy(LanguageName::Tsx, tsx::FILE_EXTENSIONS, tsx::language);
+        #[cfg(feature = "javascript")]
+        registry.register_lazy(
+            LanguageName::Javascript,
+            javascript::FILE_EXTENSIONS,
+            javascript::language,
+        );
+        #[cfg(feature = "python")]
+        registry.register_lazy(
+            LanguageName::Python,
+            python::FILE_EXTENSIONS,
+            python::language,
+        );
+        #[cfg(feature = "go")]
+        registry.register_lazy(LanguageName::Go, go::FILE_EXTENSIONS, go::language);
+        #[cfg(feature = "cpp")]
+        registry.register_lazy(LanguageName::Cpp, cpp::FILE_EXTENSIONS, cpp::language);
+        #[cfg(feature = "c")]
+        registry.register_lazy(LanguageName::C, c::FILE_EXTENSIONS, c::language);
+        #[cfg(feature = "java")]
+        registry.register_lazy(LanguageName::Java, java::FILE_EXTENSIONS, java::language);
+        #[cfg(feature = "php")]
+        registry.register_lazy(LanguageName::Php, php::FILE_EXTENSIONS, php::language);
+        #[cfg(feature = "csharp")]
+        registry.register_lazy(
+            LanguageName::CSharp,
+            csharp::FILE_EXTENSIONS,
+            csharp::language,
+        );
+        #[cfg(feature = "ruby")]
+        registry.register_lazy(LanguageName::Ruby, ruby::FILE_EXTENSIONS, ruby::language);
+        registry.register_lazy(LanguageName::Other, plain::FILE_EXTENSIONS, plain::language);
 
         Ok(registry)
     }
 
+    /// Register a language that has already been built
     pub fn register_language(&mut self, language: LanguageCommon) {
         let name = language.name();
         for extension in language.file_extensions() {
             self.extensions.insert(extension, name);
         }
-        self.languages.insert(name, language);
+        let built = OnceLock::new();
+        let _ = built.set(Ok(language));
+        self.languages.insert(
+            name,
+            LazyLanguage {
+                constructor: || unreachable!("language was registered already built"),
+                built,
+            },
+        );
+    }
+
+    /// Register a language by its constructor, which runs the first time the language is
+    /// requested. The extensions must match the ones the constructor builds with.
+    pub fn register_lazy(
+        &mut self,
+        name: LanguageName,
+        file_extensions: &'static [&'static str],
+        constructor: fn() -> Result<LanguageCommon>,
+    ) {
+        for extension in file_extensions {
+            self.extensions.insert(extension, name);
+        }
+        self.languages.insert(
+            name,
+            LazyLanguage {
+                constructor,
+                built: OnceLock::new(),
+            },
+        );
     }
 
+    /// Names of all registered languages, without building them
+    pub fn language_names(&self) -> impl Iterator<Item = LanguageName> + '_ {
+        self.languages.keys().copied()
+    }
+
+    /// Whether a language has been built yet
+    pub fn is_initialized(&self, name: LanguageName) -> bool {
+        self.languages
+            .get(&name)
+            .is_some_and(|language| language.built.get().is_some())
+    }
+
+    /// All registered languages, building any that have not been used yet. Fails on the
+    /// first language that cannot be built, such as one with an invalid embedded query.
+    pub fn languages(&self) -> Result<Vec<&LanguageCommon>, SemanticEditError> {
+        self.languages
+            .iter()
+            .map(|(name, language)| language.get(*name))
+            .collect()
+    }
+
+    /// Get a language, building it on first use
     pub fn get_language(&self, name: LanguageName) -> Result<&LanguageCommon, SemanticEditError> {
         self.languages
             .get(&name)
             .ok_or(SemanticEditError::ParserUnavailable {
                 language: name.to_string(),
-            })
+            })?
+            .get(name)
     }
 
     pub fn get_language_with_hint(
@@ -295,6 +528,10 @@ impl LanguageRegistry {
     }
 
     pub fn detect_language_from_path(&self, file_path: &Path) -> Option<LanguageName> {
+        #[cfg(feature = "json")]
+        if json::is_jsonc_path(file_path) {
+            return Some(LanguageName::Jsonc);
+        }
         let extension = file_path.extension()?.to_str()?;
         self.extensions.get(extension).copied()
     }
@@ -0,0 +1,110 @@
+//! # Parser Pool
+//!
+//! Reusable tree-sitter parsers for one language. Creating a parser and loading its language
+//! costs more than most incremental parses, and an edit parses the file once per candidate
+//! position, so each language keeps its idle parsers for the next checkout.
+//!
+//! Parsers are checked out with [`ParserPool::checkout`] and returned to the pool when the
+//! [`PooledParser`] guard is dropped. The pool is thread-safe, and holds at most
+//! [`MAX_IDLE_PARSERS`] idle parsers; any beyond that are dropped when returned.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::Result;
+use tree_sitter::{Language, Parser};
+
+/// Idle parsers kept per language, enough for a few concurrent tool calls
+pub const MAX_IDLE_PARSERS: usize = 8;
+
+/// A pool of parsers for one language
+pub struct ParserPool {
+    language: Language,
+    idle: Mutex<Vec<Parser>>,
+}
+
+impl fmt::Debug for ParserPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParserPool")
+            .field("language", &self.language)
+            .field("idle", &self.idle_parsers())
+            .finish()
+    }
+}
+
+impl ParserPool {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Take an idle parser, or create one if there is none
+    pub fn checkout(&self) -> Result<PooledParser<'_>> {
+        let parser = match self.lock().pop() {
+            Some(parser) => parser,
+            None => {
+                let mut parser = Parser::new();
+                parser.set_language(&self.language)?;
+                parser
+            }
+        };
+        Ok(PooledParser {
+            pool: self,
+            parser: Some(parser),
+        })
+    }
+
+    /// Number of parsers waiting to be checked out
+    pub fn idle_parsers(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn checkin(&self, mut parser: Parser) {
+        // a parse that was stopped early leaves state that would resume in the next parse
+        parser.reset();
+        let mut idle = self.lock();
+        if idle.len() < MAX_IDLE_PARSERS {
+            idle.push(parser);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Parser>> {
+        // a panic while holding the lock cannot leave the list of parsers inconsistent
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A parser checked out of a [`ParserPool`], returned to it when dropped
+pub struct PooledParser<'pool> {
+    pool: &'pool ParserPool,
+    parser: Option<Parser>,
+}
+
+impl Deref for PooledParser<'_> {
+    type Target = Parser;
+
+    fn deref(&self) -> &Parser {
+        self.parser
+            .as_ref()
+            .expect("parser is present until dropped")
+    }
+}
+
+impl DerefMut for PooledParser<'_> {
+    fn deref_mut(&mut self) -> &mut Parser {
+        self.parser
+            .as_mut()
+            .expect("parser is present until dropped")
+    }
+}
+
+impl Drop for PooledParser<'_> {
+    fn drop(&mut self) {
+        if let Some(parser) = self.parser.take() {
+            self.pool.checkin(parser);
+        }
+    }
+}
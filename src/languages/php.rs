@@ -6,14 +6,18 @@
 //! - Standardized language configuration using LanguageBuilder
 //! - Default editor for basic operations
 
-use super::{LanguageBuilder, LanguageName};
+use super::{Attributes, LanguageBuilder, LanguageName};
 use anyhow::Result;
 
+/// File extensions detected as PHP
+pub const FILE_EXTENSIONS: &[&str] = &["php"];
+
 pub fn language() -> Result<super::LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Php,
-        &["php"],
+        FILE_EXTENSIONS,
         tree_sitter_php::LANGUAGE_PHP.into(),
     )
+    .with_attributes(Attributes::Within(&["attribute_list"]))
     .build()
 }
@@ -3,12 +3,186 @@
 //! This module provides fallback language support for unrecognized file types:
 //! - Basic tree-sitter parsing for text operations
 //! - No specific file extensions (used as fallback)
-//! - Minimal editor for plain text editing
+//! - Bracket and quote balance checking, so edits to config-ish files get sanity-checked
 //! - Standardized language configuration using LanguageBuilder
 
-use super::{LanguageBuilder, LanguageName};
-use anyhow::Result;
+use std::ops::Range;
+
+use super::{LanguageBuilder, LanguageName, traits::LanguageEditor};
+use anyhow::{Result, anyhow};
+use tree_sitter::{Point, Tree};
+
+/// Environment variable overriding the delimiters checked in plain text files
+pub const PLAIN_DELIMITERS_ENV: &str = "SEMANTIC_EDIT_PLAIN_DELIMITERS";
+
+/// Delimiters checked unless overridden. Parentheses are left out because prose is full of
+/// unbalanced ones, like `1)` list markers.
+pub const DEFAULT_PLAIN_DELIMITERS: &str = r#"[]{}"""#;
+
+/// No extensions, since plain text is the fallback for unknown files
+pub const FILE_EXTENSIONS: &[&str] = &[];
 
 pub fn language() -> Result<super::LanguageCommon> {
-    LanguageBuilder::new(LanguageName::Other, &[], tree_sitter_plain::LANGUAGE.into()).build()
+    let checker = match std::env::var(PLAIN_DELIMITERS_ENV) {
+        Ok(spec) => DelimiterChecker::parse(&spec)?,
+        Err(_) => DelimiterChecker::default(),
+    };
+
+    LanguageBuilder::new(
+        LanguageName::Other,
+        FILE_EXTENSIONS,
+        tree_sitter_plain::LANGUAGE.into(),
+    )
+    .with_editor(Box::new(PlainEditor { checker }))
+    .build()
+}
+
+struct PlainEditor {
+    checker: DelimiterChecker,
+}
+
+impl LanguageEditor for PlainEditor {
+    fn collect_errors(&self, _tree: &Tree, content: &str) -> Vec<Range<Point>> {
+        self.checker.check(content)
+    }
+}
+
+/// Checks that brackets and quotes are balanced in plain text
+///
+/// Delimiters are configured as pairs of characters, e.g. `[]{}""`. A pair of identical
+/// characters is a quote, which must be closed on the line it was opened. A backslash
+/// escapes the next character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelimiterChecker {
+    brackets: Vec<(char, char)>,
+    quotes: Vec<char>,
+}
+
+impl Default for DelimiterChecker {
+    fn default() -> Self {
+        Self::parse(DEFAULT_PLAIN_DELIMITERS).expect("default delimiters are valid")
+    }
+}
+
+impl DelimiterChecker {
+    /// Parse delimiter pairs such as `()[]{}""`; an empty spec disables checking
+    pub fn parse(spec: &str) -> Result<Self> {
+        let chars = spec.chars().collect::<Vec<_>>();
+        if chars.len() % 2 != 0 {
+            return Err(anyhow!(
+                "{PLAIN_DELIMITERS_ENV} must be pairs of opening and closing characters, \
+like `[]{{}}\"\"`, but got `{spec}`"
+            ));
+        }
+
+        let mut checker = Self {
+            brackets: vec![],
+            quotes: vec![],
+        };
+        for pair in chars.chunks(2) {
+            if pair[0] == pair[1] {
+                checker.quotes.push(pair[0]);
+            } else {
+                checker.brackets.push((pair[0], pair[1]));
+            }
+        }
+        Ok(checker)
+    }
+
+    /// Ranges of unclosed or unmatched delimiters
+    pub fn check(&self, content: &str) -> Vec<Range<Point>> {
+        let mut errors = vec![];
+        let mut open_brackets: Vec<(char, Point)> = vec![];
+        let mut open_quote: Option<(char, Point)> = None;
+        let mut escaped = false;
+        let mut point = Point::new(0, 0);
+
+        for c in content.chars() {
+            let start = point;
+            point = if c == '\n' {
+                Point::new(point.row + 1, 0)
+            } else {
+                Point::new(point.row, point.column + c.len_utf8())
+            };
+
+            if std::mem::take(&mut escaped) {
+                continue;
+            }
+
+            match open_quote {
+                _ if c == '\\' => escaped = true,
+                Some((quote, _)) if c == quote => open_quote = None,
+                Some((_, quote_start)) if c == '\n' => {
+                    errors.push(quote_start..start);
+                    open_quote = None;
+                }
+                Some(_) => {}
+                None if self.quotes.contains(&c) => open_quote = Some((c, start)),
+                None => {
+                    if let Some(&(_, close)) = self.brackets.iter().find(|(open, _)| *open == c) {
+                        open_brackets.push((close, start));
+                    } else if self.brackets.iter().any(|(_, close)| *close == c) {
+                        if open_brackets.last().is_some_and(|(close, _)| *close == c) {
+                            open_brackets.pop();
+                        } else {
+                            errors.push(start..point);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((_, quote_start)) = open_quote {
+            errors.push(quote_start..point);
+        }
+        errors.extend(
+            open_brackets
+                .into_iter()
+                .map(|(_, start)| start..Point::new(start.row, start.column + 1)),
+        );
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(checker: &DelimiterChecker, content: &str) -> Vec<usize> {
+        checker
+            .check(content)
+            .into_iter()
+            .map(|range| range.start.row)
+            .collect()
+    }
+
+    #[test]
+    fn balanced_content_passes() {
+        let checker = DelimiterChecker::default();
+        assert!(
+            rows(
+                &checker,
+                "[section]\nlist = [1, {a: \"]\"}]\n1) don't worry\n"
+            )
+            .is_empty()
+        );
+        assert!(rows(&checker, "path = \"C:\\\\dir\\\"\"\n").is_empty());
+    }
+
+    #[test]
+    fn unbalanced_delimiters_are_reported_where_they_occur() {
+        let checker = DelimiterChecker::default();
+        assert_eq!(rows(&checker, "a = [1, 2\nb = 3\n"), [0]);
+        assert_eq!(rows(&checker, "a = 1]\n"), [0]);
+        assert_eq!(rows(&checker, "a = \"x\nb = \"y\"\n"), [0]);
+        assert_eq!(rows(&checker, "a = {[}]\n"), [0, 0]);
+    }
+
+    #[test]
+    fn delimiters_are_configurable() {
+        let checker = DelimiterChecker::parse("()''").unwrap();
+        assert_eq!(rows(&checker, "f(a\nx = 'y\n[\n"), [1, 0]);
+        assert!(rows(&DelimiterChecker::parse("").unwrap(), "([{\"").is_empty());
+        assert!(DelimiterChecker::parse("(").is_err());
+    }
 }
@@ -0,0 +1,98 @@
+//! Prettier integration for the web languages.
+//!
+//! JavaScript, TypeScript, TSX, JSON, and JSON with comments are formatted with the project's own prettier:
+//! - `node_modules/.bin/prettier` in the edited file's directory or one of its parents
+//! - Otherwise a prettier `npx` can run without installing anything (global or cached)
+//! - Prettier reads the project's `.prettierrc`, `.prettierignore`, and `.editorconfig` itself
+//!
+//! The JavaScript and TypeScript editors also add decorators and JSDoc comments, see
+//! [`super::decorators`] and [`super::doc_comments`].
+//!
+//! When prettier is not available, formatting leaves code as it is.
+
+#[cfg(any(feature = "javascript", feature = "typescript"))]
+use super::{decorators, doc_comments, traits::DeclarationEdit};
+use super::{
+    traits::{FormatContext, LanguageEditor},
+    utils::{command_succeeds, run_formatter},
+};
+use anyhow::Result;
+use std::{path::Path, process::Command, sync::OnceLock};
+
+/// Whether `npx` can run prettier offline, checked once per process
+static NPX_PRETTIER: OnceLock<bool> = OnceLock::new();
+
+/// Arguments that make `npx` run an installed prettier and never download one
+const NPX_ARGS: [&str; 4] = ["--offline", "--no", "--", "prettier"];
+
+/// Editor that formats with prettier using the given parser, e.g. `babel` or `typescript`
+pub struct PrettierEditor {
+    parser: &'static str,
+}
+
+impl PrettierEditor {
+    pub fn new(parser: &'static str) -> Self {
+        Self { parser }
+    }
+}
+
+impl LanguageEditor for PrettierEditor {
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        Ok(format(source, context, self.parser)?.unwrap_or_else(|| source.to_string()))
+    }
+
+    #[cfg(any(feature = "javascript", feature = "typescript"))]
+    fn add_attribute<'tree>(
+        &self,
+        node: tree_sitter::Node<'tree>,
+        source: &str,
+        attribute: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        decorators::add_decorator(node, source, attribute, decorators::SCRIPT_DECLARATIONS)
+    }
+
+    #[cfg(any(feature = "javascript", feature = "typescript"))]
+    fn add_doc_comment<'tree>(
+        &self,
+        node: tree_sitter::Node<'tree>,
+        source: &str,
+        text: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        doc_comments::add_block_doc_comment(node, source, text, doc_comments::SCRIPT_DECLARATIONS)
+    }
+}
+
+/// Format with prettier, or None if prettier is not available
+pub fn format(source: &str, context: &FormatContext, parser: &str) -> Result<Option<String>> {
+    let Some(mut command) = prettier_command(context) else {
+        return Ok(None);
+    };
+    command.args(["--parser", parser]);
+    if let Some(file_path) = context.file_path {
+        // lets prettier find the project's configuration and ignore file
+        command.arg("--stdin-filepath").arg(file_path);
+    }
+    if let Some(dir) = context
+        .file_path
+        .and_then(Path::parent)
+        .filter(|dir| dir.is_dir())
+    {
+        command.current_dir(dir);
+    }
+
+    run_formatter(command, source, context).map(Some)
+}
+
+fn prettier_command(context: &FormatContext) -> Option<Command> {
+    if let Some(local) = context.find_config(&["node_modules/.bin/prettier"]) {
+        return Some(Command::new(local));
+    }
+
+    let npx_prettier = *NPX_PRETTIER
+        .get_or_init(|| command_succeeds("npx", &[NPX_ARGS.as_slice(), &["--version"]].concat()));
+    npx_prettier.then(|| {
+        let mut command = Command::new("npx");
+        command.args(NPX_ARGS);
+        command
+    })
+}
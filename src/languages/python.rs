@@ -1,24 +1,45 @@
 //! Python language support with rustpython-parser validation.
 //!
 //! This module provides Python-specific editing capabilities including:
-//! - rustpython-parser integration for syntax validation
+//! - rustpython-parser integration for syntax validation, including assignments to
+//!   expressions that cannot be assigned to, which the parser accepts
+//! - Formatting with `ruff format` or `black` when installed, preferring black when the
+//!   project's `pyproject.toml` configures it
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for both .py and .pyi files
+//! - Adding decorators and docstrings to functions and classes
 //! - Validation queries for Python semantic correctness
 
+use crate::languages::queries::embedded_query;
 use crate::languages::{
-    LanguageBuilder, LanguageCommon, LanguageName, traits::LanguageEditor, utils::LineConverter,
+    Attributes, LanguageBuilder, LanguageCommon, LanguageName, decorators, doc_comments,
+    traits::{DeclarationEdit, FormatContext, LanguageEditor, collect_errors},
+    utils::{LineConverter, command_succeeds, run_formatter},
 };
 use anyhow::Result;
+use rustpython_ast::{Expr, Ranged, Visitor};
+use rustpython_parser::text_size::TextRange;
+use std::{fs, ops::Range, path::Path, process::Command, sync::OnceLock};
+use tree_sitter::Point;
+
+/// Whether each formatter is installed, checked once per process
+static RUFF: OnceLock<bool> = OnceLock::new();
+static BLACK: OnceLock<bool> = OnceLock::new();
+
+/// File extensions detected as Python
+pub const FILE_EXTENSIONS: &[&str] = &["py", "pyi"];
 
 pub fn language() -> Result<LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Python,
-        &["py", "pyi"],
+        FILE_EXTENSIONS,
         tree_sitter_python::LANGUAGE.into(),
     )
     .with_editor(Box::new(PythonEditor))
-    .with_validation_query(include_str!("../../queries/python/validation.scm"))
+    .with_validation_query(embedded_query!("python/validation.scm"))
+    .with_definition_query(embedded_query!("python/definitions.scm"))
+    .with_import_query(embedded_query!("python/imports.scm"))
+    .with_attributes(Attributes::Within(&["decorator"]))
     .build()
 }
 
@@ -37,15 +58,183 @@ impl Default for PythonEditor {
 }
 
 impl LanguageEditor for PythonEditor {
-    fn collect_errors(&self, _tree: &tree_sitter::Tree, content: &str) -> Vec<usize> {
-        if let Some(err) =
-            rustpython_parser::parse(content, rustpython_parser::Mode::Module, "anonymous.py").err()
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        let Some(formatter) = PythonFormatter::for_project(context) else {
+            return Ok(source.to_string());
+        };
+        let mut command = formatter.command(context.file_path);
+        if let Some(dir) = context
+            .file_path
+            .and_then(Path::parent)
+            .filter(|dir| dir.is_dir())
         {
-            let converter = LineConverter::new(content);
-            let byte_offset = usize::from(err.offset);
-            vec![converter.offset_to_line(byte_offset)]
+            command.current_dir(dir);
+        }
+        run_formatter(command, source, context)
+    }
+
+    fn collect_errors(&self, _tree: &tree_sitter::Tree, content: &str) -> Vec<Range<Point>> {
+        let converter = LineConverter::new(content);
+        let points = |start: usize, end: usize| {
+            converter.offset_to_point(start)..converter.offset_to_point(end)
+        };
+        match rustpython_parser::parse(content, rustpython_parser::Mode::Module, "anonymous.py") {
+            Err(err) => {
+                let byte_offset = usize::from(err.offset);
+                vec![points(byte_offset, byte_offset + 1)]
+            }
+            Ok(module) => {
+                let mut targets = InvalidTargets::default();
+                if let rustpython_ast::Mod::Module(module) = module {
+                    for statement in module.body {
+                        targets.visit_stmt(statement);
+                    }
+                }
+                targets
+                    .ranges
+                    .into_iter()
+                    .map(|range| points(range.start().into(), range.end().into()))
+                    .collect()
+            }
+        }
+    }
+
+    fn collect_later_errors(
+        &self,
+        tree: &tree_sitter::Tree,
+        _content: &str,
+    ) -> Option<Vec<Range<Point>>> {
+        // rustpython stops at the first syntax error
+        Some(
+            collect_errors(tree)
+                .into_iter()
+                .map(|node| node.start_position()..node.end_position())
+                .collect(),
+        )
+    }
+
+    fn add_attribute<'tree>(
+        &self,
+        node: tree_sitter::Node<'tree>,
+        source: &str,
+        attribute: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        decorators::add_decorator(node, source, attribute, decorators::PYTHON_DECLARATIONS)
+    }
+
+    fn add_doc_comment<'tree>(
+        &self,
+        node: tree_sitter::Node<'tree>,
+        source: &str,
+        text: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        doc_comments::add_docstring(node, source, text)
+    }
+}
+
+/// Finds assignments to expressions that cannot be assigned to, like `1 = x` or `f() += 1`,
+/// which the parser accepts but Python refuses to compile
+#[derive(Default)]
+struct InvalidTargets {
+    ranges: Vec<TextRange>,
+}
+
+impl InvalidTargets {
+    /// Check an assignment target, which may unpack into several targets unless it is
+    /// augmented or annotated
+    fn check(&mut self, target: &Expr, unpacking: bool) {
+        match target {
+            Expr::Name(_) | Expr::Attribute(_) | Expr::Subscript(_) => {}
+            Expr::Tuple(tuple) if unpacking => tuple
+                .elts
+                .iter()
+                .for_each(|target| self.check(target, true)),
+            Expr::List(list) if unpacking => {
+                list.elts.iter().for_each(|target| self.check(target, true))
+            }
+            Expr::Starred(starred) if unpacking => self.check(&starred.value, true),
+            target => self.ranges.push(target.range()),
+        }
+    }
+}
+
+impl Visitor for InvalidTargets {
+    fn visit_stmt_assign(&mut self, node: rustpython_ast::StmtAssign) {
+        for target in &node.targets {
+            self.check(target, true);
+        }
+        self.generic_visit_stmt_assign(node);
+    }
+
+    fn visit_stmt_aug_assign(&mut self, node: rustpython_ast::StmtAugAssign) {
+        self.check(&node.target, false);
+        self.generic_visit_stmt_aug_assign(node);
+    }
+
+    fn visit_stmt_ann_assign(&mut self, node: rustpython_ast::StmtAnnAssign) {
+        self.check(&node.target, false);
+        self.generic_visit_stmt_ann_assign(node);
+    }
+
+    fn visit_stmt_for(&mut self, node: rustpython_ast::StmtFor) {
+        self.check(&node.target, true);
+        self.generic_visit_stmt_for(node);
+    }
+
+    fn visit_stmt_async_for(&mut self, node: rustpython_ast::StmtAsyncFor) {
+        self.check(&node.target, true);
+        self.generic_visit_stmt_async_for(node);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PythonFormatter {
+    Ruff,
+    Black,
+}
+
+impl PythonFormatter {
+    /// The installed formatter the project uses: black if `pyproject.toml` configures it,
+    /// otherwise ruff, falling back to whichever is installed
+    fn for_project(context: &FormatContext) -> Option<Self> {
+        let prefers_black = context
+            .find_config(&["pyproject.toml"])
+            .and_then(|pyproject| fs::read_to_string(pyproject).ok())
+            .is_some_and(|pyproject| pyproject.contains("[tool.black]"));
+        let order = if prefers_black {
+            [Self::Black, Self::Ruff]
         } else {
-            vec![]
+            [Self::Ruff, Self::Black]
+        };
+        order.into_iter().find(|formatter| formatter.is_installed())
+    }
+
+    fn is_installed(self) -> bool {
+        match self {
+            Self::Ruff => *RUFF.get_or_init(|| command_succeeds("ruff", &["--version"])),
+            Self::Black => *BLACK.get_or_init(|| command_succeeds("black", &["--version"])),
+        }
+    }
+
+    /// A command formatting stdin, naming the file so the formatter finds the project's
+    /// configuration and treats `.pyi` stubs as such
+    fn command(self, file_path: Option<&Path>) -> Command {
+        let mut command = match self {
+            Self::Ruff => {
+                let mut command = Command::new("ruff");
+                command.arg("format");
+                command
+            }
+            Self::Black => {
+                let mut command = Command::new("black");
+                command.arg("--quiet");
+                command
+            }
+        };
+        if let Some(file_path) = file_path {
+            command.arg("--stdin-filename").arg(file_path);
         }
+        command.arg("-");
+        command
     }
 }
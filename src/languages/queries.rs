@@ -0,0 +1,88 @@
+//! Embedded tree-sitter queries.
+//!
+//! Each language's `.scm` files are compiled once per process and shared by every
+//! [`LanguageRegistry`](super::LanguageRegistry). Features include:
+//! - `embedded_query!` to embed a file from `queries/` along with its path
+//! - A global cache of compiled queries keyed by language and source
+//! - Compile errors that name the offending file, position, and pattern line
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use tree_sitter::{Language, Query, QueryError};
+
+use super::LanguageName;
+use crate::error::SemanticEditError;
+
+/// A query source compiled into the binary, with the path it was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedQuery {
+    pub path: &'static str,
+    pub source: &'static str,
+}
+
+impl From<&'static str> for EmbeddedQuery {
+    fn from(source: &'static str) -> Self {
+        Self {
+            path: "<inline>",
+            source,
+        }
+    }
+}
+
+/// Embed a query file from the `queries/` directory, e.g. `embedded_query!("rust/validation.scm")`
+// unused when only plain text is compiled in
+#[allow(unused_macros)]
+macro_rules! embedded_query {
+    ($path:literal) => {
+        $crate::languages::queries::EmbeddedQuery {
+            path: concat!("queries/", $path),
+            source: include_str!(concat!("../../queries/", $path)),
+        }
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use embedded_query;
+
+type QueryCache = Mutex<HashMap<(LanguageName, &'static str), Arc<Query>>>;
+
+static COMPILED: LazyLock<QueryCache> = LazyLock::new(Default::default);
+
+/// Compile an embedded query, reusing an earlier compilation of the same source
+pub(crate) fn compile(
+    name: LanguageName,
+    language: &Language,
+    query: EmbeddedQuery,
+) -> Result<Arc<Query>, SemanticEditError> {
+    let key = (name, query.source);
+    if let Some(compiled) = COMPILED
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&key).cloned())
+    {
+        return Ok(compiled);
+    }
+
+    let compiled = Arc::new(Query::new(language, query.source).map_err(|error| {
+        SemanticEditError::InvalidEmbeddedQuery {
+            path: query.path.to_string(),
+            message: describe_error(query.source, &error),
+        }
+    })?);
+    if let Ok(mut cache) = COMPILED.lock() {
+        cache.insert(key, Arc::clone(&compiled));
+    }
+    Ok(compiled)
+}
+
+/// Describe a query compile error, quoting the line of the pattern it occurred in
+pub(crate) fn describe_error(source: &str, error: &QueryError) -> String {
+    let line = source.lines().nth(error.row).unwrap_or_default().trim();
+    format!(
+        "{:?} error at line {}, column {}: {}\n    {line}",
+        error.kind,
+        error.row + 1,
+        error.column + 1,
+        error.message
+    )
+}
@@ -9,10 +9,13 @@
 use super::{LanguageBuilder, LanguageName};
 use anyhow::Result;
 
+/// File extensions detected as Ruby
+pub const FILE_EXTENSIONS: &[&str] = &["rb"];
+
 pub fn language() -> Result<super::LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Ruby,
-        &["rb"],
+        FILE_EXTENSIONS,
         tree_sitter_ruby::LANGUAGE.into(),
     )
     .build()
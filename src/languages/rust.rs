@@ -1,59 +1,417 @@
 //! Rust language support with rustfmt integration.
 //!
 //! This module provides Rust-specific editing capabilities including:
-//! - rustfmt integration for code formatting (edition 2024)
+//! - rustfmt integration for code formatting, using the project's `rustfmt.toml` and the
+//!   edition from `Cargo.toml` (2024 if neither is found), or else indenting as
+//!   `.editorconfig` says
+//! - In-process formatting with prettyplease when rustfmt is not installed, for files without
+//!   comments it would drop
 //! - Tree-sitter parsing for AST-aware operations
 //! - Validation queries for semantic correctness
+//! - Adding attributes to items, merging derives and lint levels into the item's existing
+//!   `#[derive(...)]` or `#[allow(...)]`, and `///` documentation above them
 //! - Native support for Rust syntax and idioms
 
-use super::{LanguageBuilder, LanguageCommon, LanguageName, traits::LanguageEditor};
-use anyhow::{Result, anyhow};
-use std::{
-    io::{Read, Write},
-    process::{Command, Stdio},
+use super::doc_comments::{already_documented, is_doc_comment, line_doc_comment};
+use super::queries::embedded_query;
+use super::{
+    Attributes, LanguageBuilder, LanguageCommon, LanguageName,
+    traits::{DeclarationEdit, FormatContext, LanguageEditor, enclosing_declaration},
+    utils::{command_succeeds, run_formatter},
 };
+use crate::editorconfig::Indentation;
+use crate::error::SemanticEditError;
+use anyhow::{Result, anyhow};
+use std::{fs, path::Path, process::Command, sync::OnceLock};
+use tree_sitter::{Node, Parser};
+
+/// Edition used when neither `rustfmt.toml` nor `Cargo.toml` names one
+const DEFAULT_EDITION: &str = "2024";
+
+/// Environment variable listing the formatters to try in order, separated by commas
+pub const RUST_FORMATTER_ENV: &str = "SEMANTIC_EDIT_RUST_FORMATTER";
+
+/// Formatters tried unless overridden
+pub const DEFAULT_RUST_FORMATTERS: &str = "rustfmt,prettyplease";
+
+/// Whether rustfmt is installed, checked once per process
+static RUSTFMT: OnceLock<bool> = OnceLock::new();
+
+/// Items, fields, and variants that take attributes
+const ATTRIBUTE_TARGETS: &[&str] = &[
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "function_item",
+    "function_signature_item",
+    "impl_item",
+    "trait_item",
+    "mod_item",
+    "type_item",
+    "const_item",
+    "static_item",
+    "macro_definition",
+    "use_declaration",
+    "field_declaration",
+    "enum_variant",
+];
+
+/// Items that can derive traits
+const DERIVE_TARGETS: &[&str] = &["struct_item", "enum_item", "union_item"];
+
+/// Attributes taking a list that another attribute of the same name merges into
+const LIST_ATTRIBUTES: &[&str] = &["derive", "allow", "warn", "deny", "expect"];
+
+/// File extensions detected as Rust
+pub const FILE_EXTENSIONS: &[&str] = &["rs"];
 
 pub fn language() -> Result<LanguageCommon> {
+    let formatters = std::env::var(RUST_FORMATTER_ENV)
+        .unwrap_or_else(|_| DEFAULT_RUST_FORMATTERS.into())
+        .split(',')
+        .map(|name| match name.trim() {
+            "rustfmt" => Ok(RustFormatter::Rustfmt),
+            "prettyplease" => Ok(RustFormatter::Prettyplease),
+            other => Err(anyhow!(
+                "{RUST_FORMATTER_ENV} must list `rustfmt` and `prettyplease`, \
+                 separated by commas, not `{other}`"
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
     LanguageBuilder::new(
         LanguageName::Rust,
-        &["rs"],
+        FILE_EXTENSIONS,
         tree_sitter_rust::LANGUAGE.into(),
     )
-    .with_editor(Box::new(RustEditor))
-    .with_validation_query(include_str!("../../queries/rust/validation.scm"))
+    .with_editor(Box::new(RustEditor { formatters }))
+    .with_validation_query(embedded_query!("rust/validation.scm"))
+    .with_definition_query(embedded_query!("rust/definitions.scm"))
+    .with_import_query(embedded_query!("rust/imports.scm"))
+    .with_attributes(Attributes::Before(&["attribute_item"]))
     .build()
 }
 
-struct RustEditor;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RustFormatter {
+    Rustfmt,
+    Prettyplease,
+}
+
+impl RustFormatter {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RustFormatter::Rustfmt => "rustfmt",
+            RustFormatter::Prettyplease => "prettyplease",
+        }
+    }
+}
+
+struct RustEditor {
+    formatters: Vec<RustFormatter>,
+}
 
 impl LanguageEditor for RustEditor {
-    fn format_code(&self, source: &str) -> Result<String> {
-        let mut child = Command::new("rustfmt")
-            .args(["--emit", "stdout", "--edition", "2024"])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        for formatter in &self.formatters {
+            match formatter {
+                RustFormatter::Rustfmt => {
+                    if *RUSTFMT.get_or_init(|| command_succeeds("rustfmt", &["--version"])) {
+                        return rustfmt(source, context);
+                    }
+                }
+                RustFormatter::Prettyplease => {
+                    if let Some(formatted) = prettyplease(source)? {
+                        return Ok(formatted);
+                    }
+                }
+            }
+        }
+        let tried: Vec<_> = self.formatters.iter().map(RustFormatter::as_str).collect();
+        Err(SemanticEditError::FormatterUnavailable {
+            language: "Rust".into(),
+            tried: tried.join(", "),
+        }
+        .into())
+    }
+
+    fn add_attribute<'tree>(
+        &self,
+        node: Node<'tree>,
+        source: &str,
+        attribute: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        let attribute = attribute.trim();
+        let attribute = if attribute.starts_with("#[") {
+            attribute.to_string()
+        } else {
+            format!("#[{attribute}]")
+        };
+        let (path, list) = attribute_parts(&attribute)
+            .ok_or_else(|| format!("`{attribute}` is not an attribute like `#[derive(Debug)]`"))?;
+        // a derive inside a struct, like on a field, is meant for the struct
+        let item = if path == "derive" {
+            attributed_item(node, DERIVE_TARGETS)
+                .ok_or_else(|| "no struct, enum, or union contains the anchor".to_string())?
+        } else {
+            attributed_item(node, ATTRIBUTE_TARGETS)
+                .ok_or_else(|| "no item, field, or variant contains the anchor".to_string())?
+        };
 
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(source.as_bytes())?;
-            drop(stdin);
+        // attributes are the siblings before the item, among its doc comments
+        let mut existing = Vec::new();
+        let mut sibling = item.prev_sibling();
+        while let Some(node) = sibling {
+            match node.kind() {
+                "attribute_item" => existing.push(node),
+                "line_comment" | "block_comment" => {}
+                _ => break,
+            }
+            sibling = node.prev_sibling();
         }
+        existing.reverse();
 
-        let mut stdout = String::new();
-        if let Some(mut out) = child.stdout.take() {
-            out.read_to_string(&mut stdout)?;
+        let already = || format!("the `{}` already has `{attribute}`", item.kind());
+        for node in &existing {
+            let text = &source[node.byte_range()];
+            if squeeze(text) == squeeze(&attribute) {
+                return Err(already());
+            }
+            let Some((existing_path, Some(existing_list))) = attribute_parts(text) else {
+                continue;
+            };
+            let Some(list) =
+                list.filter(|_| existing_path == path && LIST_ATTRIBUTES.contains(&path.as_str()))
+            else {
+                continue;
+            };
+            let present = list_entries(existing_list);
+            let added = list_entries(list)
+                .into_iter()
+                .filter(|entry| !present.contains(entry))
+                .collect::<Vec<_>>();
+            if added.is_empty() {
+                return Err(already());
+            }
+            // append to the list, keeping its layout
+            let close = node.start_byte() + text.rfind(')').unwrap_or(text.len());
+            let end = node.start_byte() + source[node.start_byte()..close].trim_end().len();
+            let text = match source[..end].chars().next_back() {
+                Some('(') => added.join(", "),
+                Some(',') => format!(" {},", added.join(", ")),
+                _ => format!(", {}", added.join(", ")),
+            };
+            return Ok(DeclarationEdit {
+                declaration: item,
+                range: end..close,
+                text,
+            });
         }
 
-        let mut stderr = String::new();
-        if let Some(mut err) = child.stderr.take() {
-            err.read_to_string(&mut stderr)?;
+        // derives conventionally come first, other attributes last
+        let at = existing
+            .first()
+            .filter(|_| path == "derive")
+            .map_or(item.start_byte(), Node::start_byte);
+        Ok(DeclarationEdit::insert(item, source, at, &attribute))
+    }
+
+    fn add_doc_comment<'tree>(
+        &self,
+        node: Node<'tree>,
+        source: &str,
+        text: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        let item = attributed_item(node, ATTRIBUTE_TARGETS)
+            .ok_or_else(|| "no item, field, or variant contains the anchor".to_string())?;
+
+        // documentation goes above the item's attributes
+        let mut at = item.start_byte();
+        let mut sibling = item.prev_sibling();
+        while let Some(node) = sibling {
+            match node.kind() {
+                "attribute_item" => at = node.start_byte(),
+                "line_comment" | "block_comment" => {
+                    if is_doc_comment(node, source, "///") || is_doc_comment(node, source, "/**") {
+                        return Err(already_documented(item));
+                    }
+                }
+                _ => break,
+            }
+            sibling = node.prev_sibling();
         }
+        Ok(DeclarationEdit::insert(
+            item,
+            source,
+            at,
+            &line_doc_comment(text),
+        ))
+    }
+}
 
-        if child.wait()?.success() {
-            Ok(stdout)
-        } else {
-            Err(anyhow!(stderr))
+/// The item of one of `kinds` containing `node`, or that the attribute or doc comment
+/// containing `node` belongs to
+fn attributed_item<'tree>(node: Node<'tree>, kinds: &[&str]) -> Option<Node<'tree>> {
+    let mut attribute = Some(node);
+    while let Some(current) = attribute {
+        if matches!(
+            current.kind(),
+            "attribute_item" | "line_comment" | "block_comment"
+        ) {
+            break;
         }
+        attribute = current.parent();
     }
+    let Some(attribute) = attribute else {
+        return enclosing_declaration(node, kinds);
+    };
+    let mut sibling = attribute.next_sibling();
+    while let Some(next) = sibling {
+        if !matches!(
+            next.kind(),
+            "attribute_item" | "line_comment" | "block_comment"
+        ) {
+            return enclosing_declaration(next, kinds);
+        }
+        sibling = next.next_sibling();
+    }
+    None
+}
+
+/// The path of an attribute like `#[derive(Debug, Clone)]` and its list, `Debug, Clone`, if
+/// it has one
+fn attribute_parts(attribute: &str) -> Option<(String, Option<&str>)> {
+    let inner = attribute
+        .trim()
+        .strip_prefix("#[")?
+        .strip_suffix(']')?
+        .trim();
+    match inner.find('(') {
+        Some(open) if inner.ends_with(')') => Some((
+            squeeze(&inner[..open]),
+            Some(&inner[open + 1..inner.len() - 1]),
+        )),
+        _ => Some((squeeze(inner), None)),
+    }
+}
+
+/// The entries of an attribute's list, split on top-level commas and without whitespace
+fn list_entries(list: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut depth = 0usize;
+    let mut entry = String::new();
+    for character in list.chars() {
+        match character {
+            '(' | '[' | '<' => depth += 1,
+            ')' | ']' | '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                entries.push(std::mem::take(&mut entry));
+                continue;
+            }
+            _ => {}
+        }
+        entry.push(character);
+    }
+    entries.push(entry);
+    entries
+        .iter()
+        .map(|entry| squeeze(entry))
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Text without whitespace, for comparing attributes however they are laid out
+fn squeeze(text: &str) -> String {
+    text.split_whitespace().collect()
+}
+
+/// Format with rustfmt, configured as the project is
+fn rustfmt(source: &str, context: &FormatContext) -> Result<String> {
+    let mut command = Command::new("rustfmt");
+    command.args(["--emit", "stdout"]);
+    let config = context.find_config(&["rustfmt.toml", ".rustfmt.toml"]);
+    let config_sets_edition = config.as_ref().is_some_and(|config| {
+        fs::read_to_string(config).is_ok_and(|config| toml_key(&config, "edition").is_some())
+    });
+    if !config_sets_edition {
+        let edition = context
+            .file_path
+            .and_then(cargo_edition)
+            .unwrap_or_else(|| DEFAULT_EDITION.to_string());
+        command.args(["--edition", &edition]);
+    }
+    match (&config, context.indentation) {
+        // command-line options would override the project's configuration
+        (Some(config), _) => {
+            command.arg("--config-path").arg(config);
+        }
+        (None, Some(Indentation::Tabs)) => {
+            command.args(["--config", "hard_tabs=true"]);
+        }
+        (None, Some(Indentation::Spaces(width))) => {
+            command.args(["--config", &format!("hard_tabs=false,tab_spaces={width}")]);
+        }
+        (None, None) => {}
+    }
+    run_formatter(command, source, context)
+}
+
+/// Format in-process with prettyplease, or None if the source has comments that it would
+/// drop, since syn keeps only doc comments
+fn prettyplease(source: &str) -> Result<Option<String>> {
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_rust::LANGUAGE.into())?;
+    let has_plain_comments = parser
+        .parse(source, None)
+        .is_none_or(|tree| has_plain_comment(tree.root_node(), source));
+    if has_plain_comments {
+        return Ok(None);
+    }
+    let file = syn::parse_file(source)?;
+    Ok(Some(prettyplease::unparse(&file)))
+}
+
+fn has_plain_comment(node: Node, source: &str) -> bool {
+    if matches!(node.kind(), "line_comment" | "block_comment") {
+        let text = &source[node.byte_range()];
+        let doc = (text.starts_with("///") && !text.starts_with("////"))
+            || text.starts_with("//!")
+            || (text.starts_with("/**") && !text.starts_with("/***") && text != "/**/")
+            || text.starts_with("/*!");
+        return !doc;
+    }
+    node.children(&mut node.walk())
+        .any(|child| has_plain_comment(child, source))
+}
+
+/// The edition of the nearest `Cargo.toml` above `file_path`, following
+/// `edition.workspace = true` up to the workspace manifest
+fn cargo_edition(file_path: &Path) -> Option<String> {
+    let mut inherits = false;
+    for dir in file_path.ancestors().skip(1) {
+        let Ok(manifest) = fs::read_to_string(dir.join("Cargo.toml")) else {
+            continue;
+        };
+        if inherits && !manifest.contains("[workspace") {
+            continue;
+        }
+        match toml_key(&manifest, "edition") {
+            Some(edition) => return Some(edition),
+            None if manifest.contains("edition.workspace = true") => inherits = true,
+            None if !inherits => return None,
+            None => {}
+        }
+    }
+    None
+}
+
+/// The string value of a top-level `key = "value"` line. Manifests and rustfmt configuration
+/// are only scanned, not parsed, so that a malformed file never blocks formatting.
+fn toml_key(toml: &str, key: &str) -> Option<String> {
+    toml.lines().find_map(|line| {
+        let (name, value) = line.split_once('=')?;
+        (name.trim() == key)
+            .then(|| value.trim().trim_matches(['"', '\'']).to_string())
+            .filter(|value| !value.is_empty())
+    })
 }
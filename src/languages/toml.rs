@@ -1,21 +1,28 @@
 //! TOML language support with taplo formatting and validation.
 //!
 //! This module provides TOML-specific editing capabilities including:
-//! - Taplo formatter integration for code formatting
+//! - Taplo formatter integration for code formatting, honoring the project's `taplo.toml`
 //! - Validation using taplo for syntax correctness
 //! - Tree-sitter parsing for AST-aware operations
 //! - Line-based conversion utilities for editing
 
 use crate::languages::{
-    LanguageBuilder, LanguageCommon, LanguageName, traits::LanguageEditor, utils::LineConverter,
+    LanguageBuilder, LanguageCommon, LanguageName,
+    traits::{FormatContext, LanguageEditor},
+    utils::LineConverter,
 };
 use anyhow::Result;
-use tree_sitter::Tree;
+use std::ops::Range;
+use taplo::formatter::OptionsIncomplete;
+use tree_sitter::{Point, Tree};
+
+/// File extensions detected as TOML
+pub const FILE_EXTENSIONS: &[&str] = &["toml"];
 
 pub fn language() -> Result<LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Toml,
-        &["toml"],
+        FILE_EXTENSIONS,
         tree_sitter_toml_ng::LANGUAGE.into(),
     )
     .with_editor(Box::new(TomlEditor::new()))
@@ -37,24 +44,37 @@ impl TomlEditor {
 }
 
 impl LanguageEditor for TomlEditor {
-    fn format_code(&self, source: &str) -> Result<String> {
-        Ok(taplo::formatter::format(
-            source,
-            taplo::formatter::Options::default(),
-        ))
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        let mut options = taplo::formatter::Options::default();
+        if let Some(indentation) = context.indentation {
+            options.indent_string = indentation.unit();
+        }
+        if let Some(config) = context.find_config(&["taplo.toml", ".taplo.toml"]) {
+            options.update(taplo_formatting(&std::fs::read_to_string(config)?)?);
+        }
+        Ok(taplo::formatter::format(source, options))
     }
 
-    fn collect_errors(&self, _tree: &Tree, content: &str) -> Vec<usize> {
+    fn collect_errors(&self, _tree: &Tree, content: &str) -> Vec<Range<Point>> {
         let converter = LineConverter::new(content);
 
         taplo::parser::parse(content)
             .errors
             .into_iter()
-            .flat_map(|error| {
+            .map(|error| {
                 let start_offset = usize::from(error.range.start());
                 let end_offset = usize::from(error.range.end());
-                converter.range_to_lines(start_offset, end_offset)
+                converter.offset_to_point(start_offset)..converter.offset_to_point(end_offset)
             })
             .collect()
     }
 }
+
+/// The `[formatting]` options of a `taplo.toml`
+fn taplo_formatting(config: &str) -> Result<OptionsIncomplete> {
+    let config = serde_json::to_value(taplo::parser::parse(config).into_dom())?;
+    Ok(match config.get("formatting") {
+        Some(formatting) => serde_json::from_value(formatting.clone())?,
+        None => OptionsIncomplete::default(),
+    })
+}
@@ -5,10 +5,45 @@
 //! - LanguageEditor trait for custom language support
 //! - DefaultEditor providing basic tree-sitter validation
 //! - Formatting and error collection interfaces
+//! - Adding attributes, decorators, and documentation comments to declarations, for
+//!   languages that have them
+//! - FormatContext, so formatters can honor project configuration
 //! - Extensible design for adding new languages
 
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
-use tree_sitter::{Node, Tree};
+use tree_sitter::{Node, Point, Tree};
+
+use crate::editor::FormatLimits;
+use crate::editorconfig::Indentation;
+use crate::progress::Progress;
+
+/// What a formatter knows about the file it is formatting
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FormatContext<'a> {
+    /// Indentation from `.editorconfig`, for formatters without a configuration of their own
+    pub indentation: Option<Indentation>,
+    /// The file being formatted, used to discover project formatter configuration
+    pub file_path: Option<&'a Path>,
+    /// Limits on formatter processes
+    pub limits: FormatLimits,
+    /// Progress of the request the file is formatted for, which stops formatters if cancelled
+    pub progress: Option<&'a Progress>,
+}
+
+impl FormatContext<'_> {
+    /// The nearest file with one of these names in the directory of the file being formatted
+    /// or one of its parents, e.g. `rustfmt.toml`
+    pub fn find_config(&self, names: &[&str]) -> Option<PathBuf> {
+        self.file_path?
+            .ancestors()
+            .skip(1)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|path| path.is_file())
+    }
+}
 
 /// Default editor implementation with basic tree-sitter validation
 #[derive(Debug, Clone)]
@@ -28,18 +63,131 @@ impl Default for DefaultEditor {
 
 /// Trait for language-specific operations like validation and formatting
 pub trait LanguageEditor: Send + Sync {
-    /// Collect syntax error line numbers from a tree-sitter parse tree
-    fn collect_errors(&self, tree: &Tree, _content: &str) -> Vec<usize> {
+    /// Collect syntax error ranges (0-based rows, byte columns) from a tree-sitter parse tree
+    fn collect_errors(&self, tree: &Tree, _content: &str) -> Vec<Range<Point>> {
         collect_errors(tree)
             .into_iter()
-            .map(|node| node.start_position().row)
+            .map(|node| node.start_position()..node.end_position())
             .collect()
     }
 
-    /// Format code according to language conventions
-    fn format_code(&self, source: &str) -> Result<String> {
+    /// Syntax errors found by the tree-sitter parse, for languages whose `collect_errors`
+    /// stops at the first error in a file, or None if it reports them all. Edits to a file
+    /// with an error are also checked against these, so errors they add after it are found.
+    fn collect_later_errors(&self, _tree: &Tree, _content: &str) -> Option<Vec<Range<Point>>> {
+        None
+    }
+
+    /// Format code according to language conventions and the project's formatter
+    /// configuration, if the formatter supports it
+    fn format_code(&self, source: &str, _context: &FormatContext) -> Result<String> {
         Ok(source.to_string())
     }
+
+    /// The edit adding `attribute`, such as a derive or decorator, to the declaration
+    /// containing `node`, merging it into an attribute the declaration already has where the
+    /// language allows, or why it cannot be added
+    fn add_attribute<'tree>(
+        &self,
+        _node: Node<'tree>,
+        _source: &str,
+        _attribute: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        Err("this language has no attributes or decorators to add".into())
+    }
+
+    /// The edit documenting the declaration containing `node` with `text`, as a comment or
+    /// docstring placed where the language's documentation tools look for it, or why it
+    /// cannot be added
+    fn add_doc_comment<'tree>(
+        &self,
+        _node: Node<'tree>,
+        _source: &str,
+        _text: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        Err("this language has no documentation comments to add".into())
+    }
+}
+
+/// An attribute, decorator, or documentation added to a declaration, see
+/// [`LanguageEditor::add_attribute`] and [`LanguageEditor::add_doc_comment`]
+#[derive(Debug, Clone)]
+pub struct DeclarationEdit<'tree> {
+    /// The declaration being added to
+    pub declaration: Node<'tree>,
+    /// Bytes replaced: an empty range to insert, or part of an attribute being merged into
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl<'tree> DeclarationEdit<'tree> {
+    /// Insert `lines` on their own lines before `at`, indented like the line `at` is on
+    ///
+    /// The lines are inserted at the end of the line before, rather than before `at`, so that
+    /// validation does not count the line `at` is on as edited.
+    pub fn insert(declaration: Node<'tree>, source: &str, at: usize, lines: &str) -> Self {
+        let line_start = source[..at].rfind('\n').map_or(0, |newline| newline + 1);
+        let indent = &source[line_start..at];
+        if !indent.trim().is_empty() {
+            // something else precedes the declaration on its line
+            return Self {
+                declaration,
+                range: at..at,
+                text: format!("{lines} "),
+            };
+        }
+        let newline = if source[..line_start].ends_with("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let indented = lines.lines().map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{line}")
+            }
+        });
+        let (at, text) = match line_start.checked_sub(newline.len()) {
+            Some(previous_line_end) => (
+                previous_line_end,
+                indented
+                    .map(|line| format!("{newline}{line}"))
+                    .collect::<String>(),
+            ),
+            None => (
+                0,
+                indented
+                    .map(|line| format!("{line}{newline}"))
+                    .collect::<String>(),
+            ),
+        };
+        Self {
+            declaration,
+            range: at..at,
+            text,
+        }
+    }
+}
+
+/// The nearest of `node` and its ancestors whose kind is one of `kinds`, or that wraps one,
+/// like an `export` statement or decorated definition
+pub fn enclosing_declaration<'tree>(node: Node<'tree>, kinds: &[&str]) -> Option<Node<'tree>> {
+    let mut node = Some(node);
+    while let Some(current) = node {
+        if kinds.contains(&current.kind()) {
+            return Some(current);
+        }
+        let wrapped = ["declaration", "definition"]
+            .into_iter()
+            .filter_map(|field| current.child_by_field_name(field))
+            .find(|wrapped| kinds.contains(&wrapped.kind()));
+        if wrapped.is_some() {
+            return wrapped;
+        }
+        node = current.parent();
+    }
+    None
 }
 
 impl LanguageEditor for DefaultEditor {
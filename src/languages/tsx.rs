@@ -3,19 +3,32 @@
 //! This module provides TSX-specific editing capabilities including:
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .tsx files
-//! - Specialized TSX editor for React/JSX syntax
+//! - Specialized TSX editor for React/JSX syntax, formatting with the project's prettier
+//!   and adding decorators and JSDoc comments
 //! - Standardized language configuration using LanguageBuilder
+//! - Validation queries for common React/JSX mistakes
 
-use crate::languages::{LanguageBuilder, LanguageCommon, LanguageName, traits::LanguageEditor};
+use crate::languages::queries::embedded_query;
+use crate::languages::{
+    Attributes, LanguageBuilder, LanguageCommon, LanguageName, decorators, doc_comments, prettier,
+    traits::{DeclarationEdit, FormatContext, LanguageEditor},
+};
 use anyhow::Result;
 
+/// File extensions detected as TSX
+pub const FILE_EXTENSIONS: &[&str] = &["tsx"];
+
 pub fn language() -> Result<LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Tsx,
-        &["tsx"],
+        FILE_EXTENSIONS,
         tree_sitter_typescript::LANGUAGE_TSX.into(),
     )
     .with_editor(Box::new(TypescriptEditor::new()))
+    .with_validation_query(embedded_query!("tsx/validation.scm"))
+    .with_definition_query(embedded_query!("tsx/definitions.scm"))
+    .with_import_query(embedded_query!("tsx/imports.scm"))
+    .with_attributes(Attributes::Within(&["decorator"]))
     .build()
 }
 
@@ -33,4 +46,26 @@ impl TypescriptEditor {
     }
 }
 
-impl LanguageEditor for TypescriptEditor {}
+impl LanguageEditor for TypescriptEditor {
+    fn format_code(&self, source: &str, context: &FormatContext) -> Result<String> {
+        Ok(prettier::format(source, context, "typescript")?.unwrap_or_else(|| source.to_string()))
+    }
+
+    fn add_attribute<'tree>(
+        &self,
+        node: tree_sitter::Node<'tree>,
+        source: &str,
+        attribute: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        decorators::add_decorator(node, source, attribute, decorators::SCRIPT_DECLARATIONS)
+    }
+
+    fn add_doc_comment<'tree>(
+        &self,
+        node: tree_sitter::Node<'tree>,
+        source: &str,
+        text: &str,
+    ) -> Result<DeclarationEdit<'tree>, String> {
+        doc_comments::add_block_doc_comment(node, source, text, doc_comments::SCRIPT_DECLARATIONS)
+    }
+}
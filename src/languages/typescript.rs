@@ -4,17 +4,27 @@
 //! - Tree-sitter parsing for AST-aware operations
 //! - Support for .ts files
 //! - Standardized language configuration using LanguageBuilder
-//! - Default editor for basic operations
+//! - Formatting with the project's prettier, when available
 
-use crate::languages::{LanguageBuilder, LanguageCommon, LanguageName};
+use crate::languages::queries::embedded_query;
+use crate::languages::{
+    Attributes, LanguageBuilder, LanguageCommon, LanguageName, prettier::PrettierEditor,
+};
 use anyhow::Result;
 
+/// File extensions detected as TypeScript
+pub const FILE_EXTENSIONS: &[&str] = &["ts"];
+
 pub fn language() -> Result<LanguageCommon> {
     LanguageBuilder::new(
         LanguageName::Typescript,
-        &["ts"],
+        FILE_EXTENSIONS,
         tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
     )
-    .with_validation_query(include_str!("../../queries/typescript/validation.scm"))
+    .with_editor(Box::new(PrettierEditor::new("typescript")))
+    .with_validation_query(embedded_query!("typescript/validation.scm"))
+    .with_definition_query(embedded_query!("typescript/definitions.scm"))
+    .with_import_query(embedded_query!("typescript/imports.scm"))
+    .with_attributes(Attributes::Within(&["decorator"]))
     .build()
 }
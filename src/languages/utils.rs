@@ -1,6 +1,143 @@
 //! # Utilities Module
 //!
-//! Common utilities for language processing, including text position handling.
+//! Common utilities for language processing, including text position handling and running
+//! external formatters.
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use tree_sitter::Point;
+
+use crate::error::SemanticEditError;
+use crate::languages::traits::FormatContext;
+use crate::progress::Progress;
+
+/// How often a running formatter is checked for having finished or exceeded its limits
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Run a formatter that reads source on stdin and writes the formatted code to stdout,
+/// returning its stderr as the error if it fails. The process is killed if it runs longer
+/// or writes more than the context's limits allow, or if its request is cancelled.
+pub fn run_formatter(
+    mut command: Command,
+    source: &str,
+    context: &FormatContext,
+) -> Result<String> {
+    let limits = &context.limits;
+    let program = Path::new(command.get_program());
+    let formatter = program
+        .file_name()
+        .unwrap_or(program.as_os_str())
+        .to_string_lossy()
+        .into_owned();
+    let cancelled = || context.progress.is_some_and(Progress::is_cancelled);
+    if let Some(progress) = context.progress {
+        progress.check_cancelled()?;
+        progress.advance(None, format!("Running {formatter}"));
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // stdin is written and the output read on their own threads, so that a formatter
+    // that writes before reading all of its input cannot deadlock against us
+    let too_large = Arc::new(AtomicBool::new(false));
+    let max_bytes = limits.max_output_bytes;
+    let stdin = child.stdin.take().map(|mut stdin| {
+        let source = source.to_string();
+        thread::spawn(move || stdin.write_all(source.as_bytes()))
+    });
+    let stdout = child.stdout.take().map(|stdout| {
+        let too_large = Arc::clone(&too_large);
+        thread::spawn(move || read_capped(stdout, max_bytes, &too_large))
+    });
+    let stderr = child.stderr.take().map(|stderr| {
+        let too_large = Arc::clone(&too_large);
+        thread::spawn(move || read_capped(stderr, max_bytes, &too_large))
+    });
+
+    let deadline = Instant::now() + limits.timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if too_large.load(Ordering::Relaxed) || Instant::now() >= deadline || cancelled() {
+            child.kill()?;
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let Some(status) = status else {
+        // the readers are left behind, since processes the formatter started may still
+        // hold its output open
+        return Err(if cancelled() {
+            SemanticEditError::Cancelled
+        } else if too_large.load(Ordering::Relaxed) {
+            SemanticEditError::FormatterOutputTooLarge {
+                formatter,
+                max_bytes,
+            }
+        } else {
+            SemanticEditError::FormatterTimedOut {
+                formatter,
+                timeout: limits.timeout,
+            }
+        }
+        .into());
+    };
+
+    let written = stdin.map(|stdin| stdin.join().expect("stdin writer panicked"));
+    let stdout = stdout.map(|stdout| stdout.join().expect("stdout reader panicked"));
+    let stderr = stderr.map(|stderr| stderr.join().expect("stderr reader panicked"));
+    if too_large.load(Ordering::Relaxed) {
+        return Err(SemanticEditError::FormatterOutputTooLarge {
+            formatter,
+            max_bytes,
+        }
+        .into());
+    }
+
+    if status.success() {
+        // output for partial input must not replace the file
+        written.transpose()?;
+        Ok(String::from_utf8(stdout.transpose()?.unwrap_or_default())?)
+    } else {
+        let stderr = stderr.transpose()?.unwrap_or_default();
+        Err(anyhow!(String::from_utf8_lossy(&stderr).into_owned()))
+    }
+}
+
+/// Read a stream to its end, stopping early and flagging `too_large` if it exceeds
+/// `max_bytes`
+fn read_capped(stream: impl Read, max_bytes: usize, too_large: &AtomicBool) -> io::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    stream.take(max_bytes as u64 + 1).read_to_end(&mut buffer)?;
+    if buffer.len() > max_bytes {
+        too_large.store(true, Ordering::Relaxed);
+    }
+    Ok(buffer)
+}
+
+/// Whether `program` runs successfully with `args`, e.g. `--version`
+pub fn command_succeeds(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
 
 /// Converts byte offsets to line numbers in text
 ///
@@ -75,6 +212,15 @@ impl LineConverter {
         }
     }
 
+    /// Convert a byte offset to a tree-sitter point (0-indexed row, byte column)
+    pub fn offset_to_point(&self, offset: usize) -> Point {
+        let row = self.offset_to_line(offset);
+        Point {
+            row,
+            column: offset.saturating_sub(self.newline_positions[row]),
+        }
+    }
+
     /// Convert a text range to a line range (0-indexed)
     ///
     /// Returns a `Range<usize>` containing the start and end line numbers.
@@ -125,4 +271,13 @@ mod tests {
         assert_eq!(converter.offset_to_line(7), 1); // "second" (after \r\n)
         assert_eq!(converter.offset_to_line(15), 2); // "third" (after \r\n)
     }
+
+    #[test]
+    fn offsets_to_points() {
+        let converter = LineConverter::new("first\nsecond");
+
+        assert_eq!(converter.offset_to_point(0), Point { row: 0, column: 0 });
+        assert_eq!(converter.offset_to_point(3), Point { row: 0, column: 3 });
+        assert_eq!(converter.offset_to_point(8), Point { row: 1, column: 2 });
+    }
 }
@@ -15,11 +15,33 @@
 //!
 //! ## Core Components
 //!
+//! - [`api`]: One-call helpers for embedding the engine in Rust programs
+//! - [`backup`]: Optional backups of files before they are overwritten
+//! - [`cli`]: Command-line front-end for the `semantic-edit` binary
+//! - [`codemod`]: ast-grep style pattern rules rewriting many files at once
+//! - [`comby`]: Comby-style match and rewrite templates for the `rewrite_pattern` operation
+//! - [`commit_lock`]: Advisory locks serializing commits to a file across sessions
 //! - [`editor`]: Main editing engine with validation and formatting
+//! - [`editorconfig`]: Project conventions read from `.editorconfig` files
+//! - [`encoding`]: Byte-order mark detection and preservation
+//! - [`grammar`]: Node kinds and fields of each language's grammar, and the queries using them
+//! - [`git`]: Optional commits of applied edits, and snapshots of overwritten files, in git
+//! - `http`: Streamable HTTP transport for serving several clients over the network (`http` feature)
+//! - [`imports`]: Sorting and grouping of import statements for the `organize_imports` tool
 //! - [`languages`]: Language-specific parsers and editors
+//! - [`lsp`]: Language server running the editing engine for IDEs
+//! - [`notebook`]: Cell-by-cell editing of Jupyter notebooks
+//! - [`outline`]: Outlines of the definitions in a file
+//! - [`progress`]: Progress notifications and cancellation for long operations
+//! - [`prompts`]: MCP prompts guiding common editing workflows
+//! - [`resources`]: MCP resources for staged diffs and file outlines
+//! - [`sandbox`]: Optional restrictions on the paths the server may access
 //! - [`selector`]: Code targeting system for precise edits
+//! - [`server`]: MCP message handling shared by the stdio and network transports
+//! - [`snapshot`]: Recording of tool calls as snapshot test fixtures
 //! - [`tools`]: MCP tools for code operations
 //! - [`validation`]: Syntax and semantic validation
+//! - [`watcher`]: Cache invalidation when files change outside the server
 //! - [`state`]: Session and cache management
 //! - [`error`]: Comprehensive error handling
 //!
@@ -39,11 +61,34 @@
 #![allow(clippy::collapsible_if)]
 #![deny(dead_code)]
 
+pub mod api;
+pub mod backup;
+pub mod cli;
+pub mod codemod;
+pub mod comby;
+pub mod commit_lock;
 pub mod editor;
+pub mod editorconfig;
+pub mod encoding;
 pub mod error;
 pub mod filesystem;
+pub mod git;
+pub mod grammar;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod imports;
 pub mod languages;
+pub mod lsp;
+pub mod notebook;
+pub mod outline;
+pub mod progress;
+pub mod prompts;
+pub mod resources;
+pub mod sandbox;
 pub mod selector;
+pub mod server;
+pub mod snapshot;
 pub mod state;
 pub mod tools;
 pub mod validation;
+pub mod watcher;
@@ -0,0 +1,404 @@
+//! # Language server
+//!
+//! A minimal [Language Server Protocol](https://microsoft.github.io/language-server-protocol/)
+//! server over stdio, run by `semantic-edit lsp`, so IDE users get the same validated edits as
+//! agents. It runs the same [`Editor`] pipeline as the MCP tools and the command line.
+//!
+//! ## Protocol
+//!
+//! - `textDocument/didOpen`, `didChange`, and `didClose` keep the text of open documents, which
+//!   edits and formatting use in place of the files on disk. Documents are synced in full
+//! - `textDocument/formatting` formats a document with its language's formatter and
+//!   `.editorconfig`, returning one edit replacing the document, or none if it is formatted
+//! - `workspace/executeCommand` runs [`PREVIEW_COMMAND`] and [`APPLY_COMMAND`]. Both take one
+//!   argument with the document's `uri`, the selector's `operation`, `anchor`, and `end`, the
+//!   `content`, and optionally `format_mode` and `validation`, as `stage_operation` does.
+//!   Preview returns the structured preview report. Apply sends the edit to the client in a
+//!   `workspace/applyEdit` request, and returns the commit report once the client answers,
+//!   with `applied` saying whether the client applied it
+//!
+//! Files are never written by the server: edits reach disk when the client saves them.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::editor::{CommitReport, Editor, FormatLimits, FormatMode};
+use crate::editorconfig::EditorConfig;
+use crate::error::SemanticEditError;
+use crate::filesystem::{FileOperations, OverlayFileOperations, StdFileOperations};
+use crate::languages::LanguageRegistry;
+use crate::languages::traits::FormatContext;
+use crate::selector::Selector;
+use crate::validation::ValidationLevel;
+
+/// Command previewing an edit
+pub const PREVIEW_COMMAND: &str = "semantic-edit.preview";
+
+/// Command applying an edit through the client
+pub const APPLY_COMMAND: &str = "semantic-edit.apply";
+
+/// JSON-RPC error code for a message that is not valid JSON
+const PARSE_ERROR: i32 = -32700;
+
+/// JSON-RPC error code for an unknown request method
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// JSON-RPC error code for missing or malformed parameters
+const INVALID_PARAMS: i32 = -32602;
+
+/// LSP error code for a request that was valid but failed
+const REQUEST_FAILED: i32 = -32803;
+
+/// The argument of the edit commands
+#[derive(Debug, Deserialize)]
+struct EditCommand {
+    uri: String,
+    #[serde(flatten)]
+    selector: Selector,
+    content: String,
+    #[serde(default)]
+    format_mode: FormatMode,
+    #[serde(default)]
+    validation: ValidationLevel,
+}
+
+/// A language server session with one client
+pub struct LanguageServer {
+    registry: LanguageRegistry,
+    /// Text of the documents open in the client
+    documents: HashMap<PathBuf, String>,
+    /// Apply commands waiting for the client to answer their `workspace/applyEdit`, by the id
+    /// of that request, with the id of the command request
+    pending: HashMap<u64, (Value, CommitReport)>,
+    next_request_id: u64,
+    shutdown: bool,
+}
+
+impl LanguageServer {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            registry: LanguageRegistry::new()?,
+            documents: HashMap::new(),
+            pending: HashMap::new(),
+            next_request_id: 1,
+            shutdown: false,
+        })
+    }
+
+    /// Answer messages from `input` until the client exits or closes it
+    ///
+    /// Returns whether the client asked the server to shut down before exiting.
+    pub fn serve(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> Result<bool> {
+        while let Some(message) = read_message(input)? {
+            let messages = match serde_json::from_slice::<Value>(&message) {
+                Ok(message) if message["method"] == "exit" => return Ok(self.shutdown),
+                Ok(message) => self.handle(message),
+                Err(error) => vec![error_response(Value::Null, PARSE_ERROR, error)],
+            };
+            for message in messages {
+                write_message(output, &message)?;
+            }
+        }
+        Ok(false)
+    }
+
+    /// Handle one message from the client, returning the messages to send it
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let Some(method) = message["method"].as_str() else {
+            return self.handle_response(&message).into_iter().collect();
+        };
+        let params = &message["params"];
+        let Some(id) = message.get("id").cloned() else {
+            if let Err(error) = self.notification(method, params) {
+                log::warn!("{method}: {error:#}");
+            }
+            return Vec::new();
+        };
+        match method {
+            "initialize" => vec![response(id, capabilities())],
+            "shutdown" => {
+                self.shutdown = true;
+                vec![response(id, Value::Null)]
+            }
+            "textDocument/formatting" => vec![match self.format(params) {
+                Ok(edits) => response(id, edits),
+                Err(error) => error_response(id, REQUEST_FAILED, format!("{error:#}")),
+            }],
+            "workspace/executeCommand" => self.execute_command(id, params),
+            _ => vec![error_response(
+                id,
+                METHOD_NOT_FOUND,
+                format!("unknown method {method}"),
+            )],
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Result<()> {
+        let document = &params["textDocument"];
+        match method {
+            "textDocument/didOpen" => {
+                let text = document["text"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("no text"))?;
+                self.documents
+                    .insert(document_path(document)?, text.to_string());
+            }
+            "textDocument/didChange" => {
+                // documents are synced in full, so the last change holds the whole text
+                let changes = params["contentChanges"].as_array();
+                let text = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                    .ok_or_else(|| anyhow!("no text"))?;
+                self.documents
+                    .insert(document_path(document)?, text.to_string());
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&document_path(document)?);
+            }
+            _ => log::trace!("ignoring {method}"),
+        }
+        Ok(())
+    }
+
+    /// Finish the apply command waiting for the client's answer to a `workspace/applyEdit`
+    fn handle_response(&mut self, message: &Value) -> Option<Value> {
+        let (id, mut report) = self.pending.remove(&message["id"].as_u64()?)?;
+        report.applied = message["result"]["applied"].as_bool().unwrap_or(false);
+        Some(report_response(id, &report))
+    }
+
+    /// File operations reading open documents in place of the files on disk
+    fn file_operations(&self, path: &Path) -> OverlayFileOperations<'static> {
+        let buffers = self
+            .documents
+            .get(path)
+            .map(|text| (path.to_path_buf(), text.clone()));
+        OverlayFileOperations::new(&StdFileOperations, buffers.into_iter().collect())
+    }
+
+    /// The edits formatting a document
+    fn format(&self, params: &Value) -> Result<Value> {
+        let path = document_path(&params["textDocument"])?;
+        let file_operations = self.file_operations(&path);
+        let language = self.registry.get_language_with_hint(&path, None)?;
+        let (_, source) = file_operations.read_encoded(&path)?;
+        let editorconfig = EditorConfig::load(&file_operations, &path);
+        let context = FormatContext {
+            indentation: editorconfig.indentation(),
+            file_path: Some(&path),
+            limits: FormatLimits::default(),
+            progress: None,
+        };
+        let formatted = match language.editor().format_code(&source, &context) {
+            Ok(formatted) => editorconfig.normalize(formatted),
+            Err(error) => match error.downcast_ref::<SemanticEditError>() {
+                Some(skipped) if skipped.skips_formatting() => {
+                    log::info!("not formatting {}: {skipped}", path.display());
+                    source.clone()
+                }
+                _ => return Err(error),
+            },
+        };
+        if formatted == source {
+            return Ok(json!([]));
+        }
+        Ok(json!([replace_all(&source, formatted)]))
+    }
+
+    fn execute_command(&mut self, id: Value, params: &Value) -> Vec<Value> {
+        let command = params["command"].as_str().unwrap_or_default();
+        if command != PREVIEW_COMMAND && command != APPLY_COMMAND {
+            return vec![error_response(
+                id,
+                INVALID_PARAMS,
+                format!("unknown command {command}"),
+            )];
+        }
+        let argument = params["arguments"].get(0).cloned().unwrap_or_default();
+        let edit = match serde_json::from_value::<EditCommand>(argument) {
+            Ok(edit) => edit,
+            Err(error) => return vec![error_response(id, INVALID_PARAMS, error)],
+        };
+        let result = if command == PREVIEW_COMMAND {
+            self.preview(edit)
+                .map(|report| vec![response(id.clone(), report)])
+        } else {
+            self.apply(id.clone(), edit)
+        };
+        result
+            .unwrap_or_else(|error| vec![error_response(id, REQUEST_FAILED, format!("{error:#}"))])
+    }
+
+    fn editor(&self, edit: EditCommand) -> Result<(Editor<'_>, String)> {
+        let path = uri_to_path(&edit.uri)?;
+        let language = self.registry.get_language_with_hint(&path, None)?;
+        let editor = Editor::open(
+            edit.content,
+            edit.selector,
+            language,
+            path.clone(),
+            None,
+            &self.file_operations(&path),
+        )?
+        .with_format_mode(edit.format_mode)
+        .with_validation_level(edit.validation);
+        Ok((editor, edit.uri))
+    }
+
+    fn preview(&self, edit: EditCommand) -> Result<Value> {
+        let (editor, _) = self.editor(edit)?;
+        let (report, _) = editor.preview_report()?;
+        Ok(serde_json::to_value(report)?)
+    }
+
+    /// Ask the client to apply an edit, or answer the command if the edit was refused
+    fn apply(&mut self, id: Value, edit: EditCommand) -> Result<Vec<Value>> {
+        let (editor, uri) = self.editor(edit)?;
+        let source = editor.source_code().to_string();
+        let (report, output) = editor.commit_report()?;
+        let Some(output) = output else {
+            return Ok(vec![report_response(id, &report)]);
+        };
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": "workspace/applyEdit",
+            "params": {
+                "label": report.edit.operation_description(),
+                "edit": { "changes": { uri: [replace_all(&source, output)] } },
+            },
+        });
+        self.pending.insert(request_id, (id, report));
+        Ok(vec![request])
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // full text on every change
+            "textDocumentSync": 1,
+            "documentFormattingProvider": true,
+            "executeCommandProvider": { "commands": [PREVIEW_COMMAND, APPLY_COMMAND] },
+        },
+        "serverInfo": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn report_response(id: Value, report: &CommitReport) -> Value {
+    response(id, serde_json::to_value(report).unwrap_or_default())
+}
+
+fn error_response(id: Value, code: i32, message: impl ToString) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.to_string() },
+    })
+}
+
+/// A text edit replacing the whole of `source` with `text`
+fn replace_all(source: &str, text: String) -> Value {
+    json!({
+        "range": { "start": { "line": 0, "character": 0 }, "end": end_position(source) },
+        "newText": text,
+    })
+}
+
+/// The LSP position of the end of `source`, whose characters are counted in UTF-16 code units
+fn end_position(source: &str) -> Value {
+    let last_line = &source[source.rfind('\n').map_or(0, |newline| newline + 1)..];
+    json!({
+        "line": source.matches('\n').count(),
+        "character": last_line.encode_utf16().count(),
+    })
+}
+
+fn document_path(document: &Value) -> Result<PathBuf> {
+    uri_to_path(
+        document["uri"]
+            .as_str()
+            .ok_or_else(|| anyhow!("no document uri"))?,
+    )
+}
+
+/// The path of a `file:` URI
+fn uri_to_path(uri: &str) -> Result<PathBuf> {
+    let path = uri
+        .strip_prefix("file://")
+        .ok_or_else(|| anyhow!("{uri} is not a file: URI"))?;
+    // the authority is empty or localhost for local files
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| after.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(escaped) => {
+                bytes.push(escaped);
+                rest = &after[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = after;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes)?;
+    // Windows paths start with a drive letter after the slash: file:///C:/project
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] if cfg!(windows) => &path[1..],
+        _ => &path,
+    };
+    Ok(PathBuf::from(path))
+}
+
+/// Read one message, framed by its `Content-Length` header, or None at the end of the input
+fn read_message(input: &mut dyn BufRead) -> Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = length.ok_or_else(|| anyhow!("message without a Content-Length header"))?;
+    let mut message = vec![0; length];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+fn write_message(output: &mut dyn Write, message: &Value) -> Result<()> {
+    let message = serde_json::to_vec(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n", message.len())?;
+    output.write_all(&message)?;
+    output.flush()?;
+    Ok(())
+}
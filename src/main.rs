@@ -6,7 +6,8 @@
 //!
 //! ## Usage
 //!
-//! The server communicates via JSON-RPC over stdin/stdout and provides tools for:
+//! The server communicates via JSON-RPC over stdin/stdout, or over streamable HTTP when
+//! `SEMANTIC_EDIT_HTTP` is set to an address to listen on, and provides tools for:
 //! - Staging code operations with preview
 //! - Retargeting operations
 //! - Committing changes to files
@@ -16,8 +17,15 @@
 #![allow(clippy::collapsible_if)]
 
 use mcplease::server_info;
-use semantic_code_edit_mcp::{state::SemanticEditTools, tools::Tools};
+use semantic_code_edit_mcp::{
+    backup::BackupStrategy,
+    sandbox::{PathSandbox, SandboxMode, SymlinkPolicy},
+    server,
+    state::SemanticEditTools,
+};
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
 
 const INSTRUCTIONS: &str = "Semantic code editing with tree-sitter. Use stage_operation to preview changes, retarget_staged to adjust targeting, and commit_staged to apply.";
 
@@ -30,6 +38,145 @@ fn main() {
     )
     .expect("Failed to initialize SemanticEditTools");
 
-    mcplease::run::<Tools, _>(&mut state, server_info!(), Some(INSTRUCTIONS))
+    if let Ok(backup) = env::var("SEMANTIC_EDIT_BACKUP") {
+        let backup_strategy = BackupStrategy::from_name(&backup)
+            .expect("SEMANTIC_EDIT_BACKUP must be one of: none, sibling, directory");
+        state.set_backup_strategy(backup_strategy);
+    }
+
+    let mut sandbox = PathSandbox::default();
+    if let Ok(mode) = env::var("SEMANTIC_EDIT_SANDBOX") {
+        sandbox = sandbox.with_mode(
+            SandboxMode::from_name(&mode)
+                .expect("SEMANTIC_EDIT_SANDBOX must be one of: off, context"),
+        );
+    }
+    if let Some(paths) = env::var_os("SEMANTIC_EDIT_ALLOWED_PATHS") {
+        sandbox = sandbox.with_allowed_paths(
+            env::split_paths(&paths)
+                .filter(|path| !path.as_os_str().is_empty())
+                .map(|path| PathBuf::from(&*shellexpand::tilde(&path.to_string_lossy())))
+                .collect(),
+        );
+    }
+    if let Ok(symlinks) = env::var("SEMANTIC_EDIT_SYMLINKS") {
+        sandbox = sandbox.with_symlinks(
+            SymlinkPolicy::from_name(&symlinks)
+                .expect("SEMANTIC_EDIT_SYMLINKS must be one of: follow, deny"),
+        );
+    }
+    if let Ok(read_only) = env::var("SEMANTIC_EDIT_READ_ONLY") {
+        let read_only = match read_only.trim().to_ascii_lowercase().as_str() {
+            "" | "0" | "false" | "no" | "off" => false,
+            "1" | "true" | "yes" | "on" => true,
+            _ => panic!("SEMANTIC_EDIT_READ_ONLY must be true or false"),
+        };
+        sandbox = sandbox.with_read_only(read_only);
+    }
+    state.set_sandbox(sandbox);
+
+    if let Ok(watch) = env::var("SEMANTIC_EDIT_WATCH") {
+        let watch = match watch.trim().to_ascii_lowercase().as_str() {
+            "0" | "false" | "no" | "off" => false,
+            "" | "1" | "true" | "yes" | "on" => true,
+            _ => panic!("SEMANTIC_EDIT_WATCH must be true or false"),
+        };
+        state.set_watch_files(watch);
+    }
+
+    if let Ok(megabytes) = env::var("SEMANTIC_EDIT_CACHE_MAX_MB") {
+        let megabytes: usize = megabytes
+            .parse()
+            .expect("SEMANTIC_EDIT_CACHE_MAX_MB must be a whole number of megabytes");
+        state
+            .set_cache_max_bytes((megabytes > 0).then(|| megabytes.saturating_mul(1024 * 1024)))
+            .expect("Failed to configure cache size");
+    }
+
+    let mut format_limits = state.format_limits();
+    if let Ok(seconds) = env::var("SEMANTIC_EDIT_FORMAT_TIMEOUT_SECS") {
+        let seconds = seconds
+            .parse()
+            .expect("SEMANTIC_EDIT_FORMAT_TIMEOUT_SECS must be a whole number of seconds");
+        format_limits.timeout = Duration::from_secs(seconds);
+    }
+    if let Ok(megabytes) = env::var("SEMANTIC_EDIT_FORMAT_MAX_OUTPUT_MB") {
+        let megabytes: usize = megabytes
+            .parse()
+            .expect("SEMANTIC_EDIT_FORMAT_MAX_OUTPUT_MB must be a whole number of megabytes");
+        format_limits.max_output_bytes = megabytes.saturating_mul(1024 * 1024);
+    }
+    state.set_format_limits(format_limits);
+
+    let mut parse_limits = state.parse_limits();
+    if let Ok(megabytes) = env::var("SEMANTIC_EDIT_MAX_PARSE_MB") {
+        let megabytes: usize = megabytes
+            .parse()
+            .expect("SEMANTIC_EDIT_MAX_PARSE_MB must be a whole number of megabytes");
+        parse_limits.max_file_bytes = megabytes.saturating_mul(1024 * 1024);
+    }
+    if let Ok(seconds) = env::var("SEMANTIC_EDIT_PARSE_TIMEOUT_SECS") {
+        let seconds = seconds
+            .parse()
+            .expect("SEMANTIC_EDIT_PARSE_TIMEOUT_SECS must be a whole number of seconds");
+        parse_limits.timeout = Duration::from_secs(seconds);
+    }
+    if let Ok(candidates) = env::var("SEMANTIC_EDIT_MAX_CANDIDATES") {
+        parse_limits.max_candidates = candidates
+            .parse()
+            .expect("SEMANTIC_EDIT_MAX_CANDIDATES must be a whole number");
+    }
+    state.set_parse_limits(parse_limits);
+
+    if let Ok(days) = env::var("SEMANTIC_EDIT_SESSION_TTL_DAYS") {
+        let days = days
+            .parse()
+            .expect("SEMANTIC_EDIT_SESSION_TTL_DAYS must be a whole number of days");
+        state.set_session_ttl_days(days);
+    }
+    if state.session_ttl_days() > 0 {
+        let max_age = Duration::from_secs(state.session_ttl_days().saturating_mul(24 * 60 * 60));
+        state
+            .prune_sessions(max_age)
+            .expect("Failed to prune stale sessions");
+    }
+
+    if let Ok(address) = env::var("SEMANTIC_EDIT_HTTP") {
+        return serve_http(&mut state, &address);
+    }
+
+    server::run(&mut state, server_info!(), Some(INSTRUCTIONS)).expect("Failed to run MCP server")
+}
+
+#[cfg(feature = "http")]
+fn serve_http(state: &mut SemanticEditTools, address: &str) {
+    use semantic_code_edit_mcp::http::HttpServer;
+
+    env_logger::init();
+    let server = HttpServer::bind(address)
+        .expect("Failed to listen on SEMANTIC_EDIT_HTTP")
+        .with_token(env::var("SEMANTIC_EDIT_HTTP_TOKEN").ok())
+        .with_allowed_hosts(
+            env::var("SEMANTIC_EDIT_HTTP_ALLOWED_HOSTS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|host| !host.is_empty())
+                .map(String::from)
+                .collect(),
+        );
+    log::info!(
+        "Serving MCP at http://{}/mcp",
+        server
+            .local_addr()
+            .expect("Failed to read listening address")
+    );
+    server
+        .serve(state, server_info!(), Some(INSTRUCTIONS))
         .expect("Failed to run MCP server")
 }
+
+#[cfg(not(feature = "http"))]
+fn serve_http(_state: &mut SemanticEditTools, _address: &str) {
+    panic!("SEMANTIC_EDIT_HTTP requires building with the http feature");
+}
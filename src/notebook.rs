@@ -0,0 +1,180 @@
+//! # Jupyter notebooks
+//!
+//! Notebooks (`.ipynb`) are JSON documents holding a list of cells. They are edited one code
+//! cell at a time: the cell is chosen by its index, or as the only code cell containing the
+//! anchor, and its source is edited, validated, and formatted in the notebook's language like
+//! any other file. The new source is then written back into the cell, leaving the other
+//! cells, their outputs, and the notebook's metadata as they were.
+//!
+//! Notebooks are written back with sorted keys and the file's own indentation, the way
+//! Jupyter writes them, so that an edit only changes the lines of the edited cell.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::ser::{PrettyFormatter, Serializer};
+
+use crate::comby::Template;
+use crate::languages::LanguageName;
+
+/// Extension of Jupyter notebook files
+pub const NOTEBOOK_EXTENSION: &str = "ipynb";
+
+/// Whether a file is a Jupyter notebook, judging by its extension
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == NOTEBOOK_EXTENSION)
+}
+
+/// A parsed Jupyter notebook
+#[derive(Debug, Clone)]
+pub struct Notebook {
+    document: Value,
+    /// Spaces per indentation level in the file, which is 1 for notebooks Jupyter wrote
+    indent: usize,
+}
+
+/// A code cell being edited, and the notebook its new source is written back into
+#[derive(Debug, Clone)]
+pub struct NotebookCell {
+    pub notebook: Notebook,
+    /// Index of the cell among all of the notebook's cells, counting from 0
+    pub index: usize,
+    /// The notebook's path with the extension of the cell's language, given to formatters so
+    /// they find the project's configuration and format the source as code
+    pub source_path: PathBuf,
+}
+
+impl Notebook {
+    pub fn parse(source: &str) -> Result<Self> {
+        let document: Value = serde_json::from_str(source)
+            .map_err(|error| anyhow!("not a valid Jupyter notebook: {error}"))?;
+        if !document.get("cells").is_some_and(Value::is_array) {
+            bail!("not a valid Jupyter notebook: it has no list of cells");
+        }
+        let indent = source
+            .lines()
+            .nth(1)
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .filter(|indent| *indent > 0)
+            .unwrap_or(1);
+        Ok(Self { document, indent })
+    }
+
+    /// The language of the notebook's code cells, from its kernel's metadata
+    pub fn language(&self) -> Option<LanguageName> {
+        let metadata = self.document.get("metadata")?;
+        let name = metadata
+            .pointer("/kernelspec/language")
+            .or_else(|| metadata.pointer("/language_info/name"))?
+            .as_str()?;
+        serde_json::from_value(Value::String(name.to_lowercase())).ok()
+    }
+
+    /// The number of cells, of every type
+    pub fn cell_count(&self) -> usize {
+        self.cells().len()
+    }
+
+    /// The source of a code cell
+    pub fn cell_source(&self, index: usize) -> Result<String> {
+        let cells = self.cells();
+        let cell = cells.get(index).ok_or_else(|| {
+            anyhow!(
+                "the notebook has no cell {index}. It has {} cells, numbered from 0",
+                cells.len()
+            )
+        })?;
+        match cell.get("cell_type").and_then(Value::as_str) {
+            Some("code") => Ok(source_text(cell)),
+            cell_type => Err(anyhow!(
+                "cell {index} is a {} cell, and only code cells can be edited",
+                cell_type.unwrap_or("untyped")
+            )),
+        }
+    }
+
+    /// The index of the only code cell whose source contains `anchor`, or matches it when
+    /// the anchor is a `rewrite_pattern` template
+    pub fn find_cell(&self, anchor: &str, template: bool) -> Result<usize> {
+        let template = template.then(|| Template::parse(anchor));
+        let matches = self
+            .cells()
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.get("cell_type").and_then(Value::as_str) == Some("code"))
+            .filter(|(_, cell)| {
+                let source = source_text(cell);
+                match &template {
+                    Some(template) => !template.matches(&source).is_empty(),
+                    None => source.contains(anchor),
+                }
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        match matches.as_slice() {
+            [index] => Ok(*index),
+            [] => Err(anyhow!(
+                "Anchor text '{anchor}' not found in any code cell of the notebook"
+            )),
+            indices => Err(anyhow!(
+                "Anchor text '{anchor}' is in more than one code cell ({}). Pass `cell` with \
+the index of the one to edit",
+                indices
+                    .iter()
+                    .map(|index| format!("cell {index}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// The notebook, serialized, with the source of a code cell replaced
+    ///
+    /// The cell keeps whether its source ended with a newline, since formatters add one, and
+    /// the form its source was stored in: one string, or a list of lines.
+    pub fn with_cell_source(&self, index: usize, source: &str) -> Result<String> {
+        let original = self.cell_source(index)?;
+        let source = if original.ends_with('\n') {
+            source
+        } else {
+            source.trim_end_matches('\n')
+        };
+
+        let mut document = self.document.clone();
+        let cell = &mut document["cells"][index];
+        cell["source"] = if cell["source"].is_string() {
+            Value::String(source.to_string())
+        } else {
+            source
+                .split_inclusive('\n')
+                .map(|line| Value::String(line.to_string()))
+                .collect()
+        };
+
+        let indent = " ".repeat(self.indent);
+        let mut output = Vec::new();
+        let mut serializer = Serializer::with_formatter(
+            &mut output,
+            PrettyFormatter::with_indent(indent.as_bytes()),
+        );
+        document.serialize(&mut serializer)?;
+        output.push(b'\n');
+        Ok(String::from_utf8(output)?)
+    }
+
+    fn cells(&self) -> &[Value] {
+        self.document["cells"].as_array().map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A cell's source, which notebooks store either as one string or as a list of lines
+fn source_text(cell: &Value) -> String {
+    match cell.get("source") {
+        Some(Value::String(source)) => source.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
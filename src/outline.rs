@@ -0,0 +1,96 @@
+//! # Symbol outlines
+//!
+//! A compact map of the definitions in a file — functions, types, fields, and so on — with
+//! the lines each one spans, built from each language's definitions query. Used by
+//! `open_files` to locate named symbols and to give agents a navigation map next to a file's
+//! content.
+
+use std::fmt::{self, Display, Formatter};
+
+use tree_sitter::Tree;
+
+use crate::languages::LanguageCommon;
+use crate::validation::DefinitionValidator;
+
+/// A definition in a file's outline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Definition kind as captured by the query, e.g. `function` or `type`
+    pub kind: String,
+    pub name: String,
+    /// 1-based, inclusive line span
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Number of outline entries this one is nested in
+    pub depth: usize,
+}
+
+/// The definitions in a file, in source order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outline(Vec<OutlineEntry>);
+
+impl Outline {
+    /// Outline a parsed file, or None if the language has no definitions query
+    pub fn new(language: &LanguageCommon, tree: &Tree, source_code: &str) -> Option<Self> {
+        let query = language.definition_query()?;
+        let mut definitions = DefinitionValidator::definitions(tree, query, source_code);
+        definitions.sort_by_key(|definition| definition.node.start_byte());
+
+        let mut entries = Vec::with_capacity(definitions.len());
+        let mut enclosing: Vec<tree_sitter::Range> = Vec::new();
+        for definition in definitions {
+            let range = definition.node.range();
+            while enclosing
+                .last()
+                .is_some_and(|parent| parent.end_byte < range.end_byte)
+            {
+                enclosing.pop();
+            }
+            let start = range.start_point;
+            let end = range.end_point;
+            // a node ending at the start of a line does not include that line
+            let end_row = if end.column == 0 && end.row > start.row {
+                end.row - 1
+            } else {
+                end.row
+            };
+            entries.push(OutlineEntry {
+                kind: definition.kind,
+                name: definition.name,
+                start_line: start.row + 1,
+                end_line: end_row + 1,
+                depth: enclosing.len(),
+            });
+            enclosing.push(range);
+        }
+        Some(Self(entries))
+    }
+
+    pub fn entries(&self) -> &[OutlineEntry] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Every definition with this name
+    pub fn find<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a OutlineEntry> {
+        self.0.iter().filter(move |entry| entry.name == name)
+    }
+}
+
+impl Display for Outline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for entry in &self.0 {
+            let indent = "  ".repeat(entry.depth);
+            let lines = if entry.start_line == entry.end_line {
+                format!("line {}", entry.start_line)
+            } else {
+                format!("lines {}-{}", entry.start_line, entry.end_line)
+            };
+            writeln!(f, "{indent}{} {} ({lines})", entry.kind, entry.name)?;
+        }
+        Ok(())
+    }
+}
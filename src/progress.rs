@@ -0,0 +1,153 @@
+//! # Progress and cancellation
+//!
+//! Long operations, such as opening many files or running an external formatter, report
+//! their progress through the [`Progress`] handle of the request they serve. Clients that send
+//! a `progressToken` in a request's `_meta` receive `notifications/progress` for it, and a
+//! `notifications/cancelled` naming the request stops it at its next check.
+//!
+//! [`Cancellations`] tracks the requests a transport has read but not yet answered, so that
+//! cancellations can be noticed while the request they name is still running.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use mcplease::types::McpRequest;
+use serde_json::{Value, json};
+
+use crate::error::SemanticEditError;
+
+/// Sends a notification to the client
+pub type Notify = Arc<dyn Fn(Value) + Send + Sync>;
+
+/// Reports a request's progress and tells whether the client cancelled it
+///
+/// The default handle reports nothing and is never cancelled.
+#[derive(Clone, Default)]
+pub struct Progress {
+    /// The token the client asked for progress under, and where to send it
+    reporter: Option<(Value, Notify)>,
+    /// Steps reported so far, since progress must increase with every notification
+    steps: Arc<AtomicU64>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("token", &self.reporter.as_ref().map(|(token, _)| token))
+            .field("steps", &self.steps)
+            .field("cancelled", &self.cancelled)
+            .finish()
+    }
+}
+
+impl Progress {
+    /// Progress for a request, sent through `notify` if the request asked for it, and
+    /// cancelled once `cancelled` is set
+    pub fn new(request: &McpRequest, notify: Notify, cancelled: Arc<AtomicBool>) -> Self {
+        let token = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("progressToken"))
+            .filter(|token| token.is_string() || token.is_number())
+            .cloned();
+        Self {
+            reporter: token.map(|token| (token, notify)),
+            steps: Arc::default(),
+            cancelled,
+        }
+    }
+
+    /// Report that one more step of the operation is done, out of `total` if known
+    pub fn advance(&self, total: Option<u64>, message: impl Into<String>) {
+        let step = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+        let Some((token, notify)) = &self.reporter else {
+            return;
+        };
+        let mut params = json!({
+            "progressToken": token,
+            "progress": step,
+            "message": message.into(),
+        });
+        if let Some(total) = total {
+            params["total"] = total.into();
+        }
+        notify(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": params,
+        }));
+    }
+
+    /// Whether the client cancelled the request
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fail with [`SemanticEditError::Cancelled`] if the client cancelled the request
+    pub fn check_cancelled(&self) -> Result<(), SemanticEditError> {
+        if self.is_cancelled() {
+            Err(SemanticEditError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Requests that have been read but not yet answered, keyed by session and request ID, with
+/// flags that are set when they are cancelled
+#[derive(Debug, Default)]
+pub struct Cancellations {
+    requests: Mutex<HashMap<(String, String), Arc<AtomicBool>>>,
+}
+
+impl Cancellations {
+    /// Note a message as it is read: requests are tracked until they are
+    /// [finished](Self::finish), and `notifications/cancelled` cancels the request it names
+    pub fn observe(&self, session_id: &str, message: &[u8]) {
+        let Ok(message) = serde_json::from_slice::<Value>(message) else {
+            return;
+        };
+        match (
+            message.get("method").and_then(Value::as_str),
+            message.get("id"),
+        ) {
+            (Some("notifications/cancelled"), None) => {
+                let request_id = message
+                    .get("params")
+                    .and_then(|params| params.get("requestId"));
+                if let Some(request_id) = request_id {
+                    if let Some(cancelled) = self.requests().get(&key(session_id, request_id)) {
+                        log::debug!("request {request_id} cancelled by the client");
+                        cancelled.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+            (Some(_), Some(id)) => {
+                self.requests().entry(key(session_id, id)).or_default();
+            }
+            _ => {}
+        }
+    }
+
+    /// The flag that is set when a request is cancelled
+    pub fn flag(&self, session_id: &str, id: &Value) -> Arc<AtomicBool> {
+        Arc::clone(self.requests().entry(key(session_id, id)).or_default())
+    }
+
+    /// Stop tracking a request once it has been answered
+    pub fn finish(&self, session_id: &str, id: &Value) {
+        self.requests().remove(&key(session_id, id));
+    }
+
+    fn requests(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), Arc<AtomicBool>>> {
+        self.requests.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn key(session_id: &str, id: &Value) -> (String, String) {
+    (session_id.to_string(), id.to_string())
+}
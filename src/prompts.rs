@@ -0,0 +1,222 @@
+//! # MCP prompts
+//!
+//! Guided workflows that clients can offer as slash commands or templates. Each prompt spells
+//! out the tool calls for a common task in the order they should be made, so that less
+//! capable models drive the stage → preview → retarget → commit workflow correctly:
+//! - `refactor_function`: rewrite one function in place
+//! - `add_test_for_function`: add a test next to the code it covers
+//! - `apply_codemod`: make the same change across many files, one staged edit at a time
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+/// A prompt as listed by `prompts/list`
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompt {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// An argument a prompt is filled in with
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptArgument {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+/// A prompt filled in with its arguments, as returned by `prompts/get`
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptMessages {
+    pub description: &'static str,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptMessage {
+    pub role: &'static str,
+    pub content: PromptContent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptContent {
+    pub r#type: &'static str,
+    pub text: String,
+}
+
+const fn argument(name: &'static str, description: &'static str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name,
+        description,
+        required,
+    }
+}
+
+/// Every prompt the server offers
+pub fn list() -> Vec<Prompt> {
+    vec![
+        Prompt {
+            name: "refactor_function",
+            description: "Rewrite one function in place, previewing the edit before committing it",
+            arguments: vec![
+                argument("file_path", "File containing the function", true),
+                argument("function", "Name of the function to refactor", true),
+                argument("goal", "What the refactoring should achieve", true),
+            ],
+        },
+        Prompt {
+            name: "add_test_for_function",
+            description: "Add a test for a function next to the code it covers, or in a test file",
+            arguments: vec![
+                argument("file_path", "File containing the function", true),
+                argument("function", "Name of the function to test", true),
+                argument(
+                    "test_file",
+                    "File to add the test to, if tests live in a separate file",
+                    false,
+                ),
+            ],
+        },
+        Prompt {
+            name: "apply_codemod",
+            description: "Make the same change across many files, one staged edit at a time",
+            arguments: vec![
+                argument(
+                    "files",
+                    "Glob pattern of the files to change, e.g. src/**/*.rs",
+                    true,
+                ),
+                argument("change", "The change to make in each file", true),
+            ],
+        },
+    ]
+}
+
+/// Fill in a prompt with its arguments
+pub fn get(name: &str, arguments: &HashMap<String, String>) -> Result<PromptMessages> {
+    let prompt = list()
+        .into_iter()
+        .find(|prompt| prompt.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = list().iter().map(|prompt| prompt.name).collect();
+            anyhow!("unknown prompt `{name}`. Prompts are {}", names.join(", "))
+        })?;
+    for argument in prompt.arguments.iter().filter(|argument| argument.required) {
+        if arguments
+            .get(argument.name)
+            .is_none_or(|value| value.trim().is_empty())
+        {
+            return Err(anyhow!(
+                "prompt `{name}` needs the `{}` argument: {}",
+                argument.name,
+                argument.description
+            ));
+        }
+    }
+    let value = |name: &str| arguments.get(name).map_or("", |value| value.trim());
+
+    let text = match prompt.name {
+        "refactor_function" => {
+            refactor_function(value("file_path"), value("function"), value("goal"))
+        }
+        "add_test_for_function" => add_test_for_function(
+            value("file_path"),
+            value("function"),
+            arguments
+                .get("test_file")
+                .map(|test_file| test_file.trim())
+                .filter(|test_file| !test_file.is_empty()),
+        ),
+        _ => apply_codemod(value("files"), value("change")),
+    };
+
+    Ok(PromptMessages {
+        description: prompt.description,
+        messages: vec![PromptMessage {
+            role: "user",
+            content: PromptContent {
+                r#type: "text",
+                text,
+            },
+        }],
+    })
+}
+
+/// How to review a staged edit and commit it, shared by every workflow
+const REVIEW_AND_COMMIT: &str = "\
+Read the preview that stage_operation returns before going on:
+- If the edit was refused, fix the content it complains about and call stage_operation again.
+- If the right content landed in the wrong place, call retarget_staged with a corrected \
+`operation` and `anchor` instead of staging the content again.
+- If the preview was summarized, call view_staged_diff to see every changed line.
+Only when the diff shows exactly the intended change, call commit_staged with \
+`acknowledge: true`.";
+
+fn refactor_function(file_path: &str, function: &str, goal: &str) -> String {
+    format!(
+        "Refactor the function `{function}` in `{file_path}`. Goal: {goal}
+
+1. Call open_files with `file_paths: [\"{file_path}\"]` and `symbol: \"{function}\"` to read \
+the function and the lines around it.
+2. Write the complete new version of the function, keeping its signature unless the goal \
+requires changing it. If the signature changes, note every caller that will need updating.
+3. Call stage_operation with `file_path: \"{file_path}\"`, `operation: \"replace_node\"`, \
+`anchor` set to the function's first line exactly as it appears in the file (for example \
+its `fn {function}(` or `def {function}(` line), and `content` set to the whole new function.
+
+{REVIEW_AND_COMMIT}
+
+4. If the signature changed, repeat steps 1-3 for each caller, one staged edit at a time."
+    )
+}
+
+fn add_test_for_function(file_path: &str, function: &str, test_file: Option<&str>) -> String {
+    let target = test_file.unwrap_or(file_path);
+    let placement = match test_file {
+        Some(test_file) => format!(
+            "Call open_files with `file_paths: [\"{test_file}\"]` and `outline: true` to see \
+the existing tests, and follow their naming, imports, and helpers."
+        ),
+        None => format!(
+            "Look at the outline from step 1 for an existing test module or test functions in \
+`{file_path}`, and follow their naming and helpers. If there are none, add the test the \
+way this language and project usually do."
+        ),
+    };
+    format!(
+        "Add a test for the function `{function}` in `{file_path}`.
+
+1. Call open_files with `file_paths: [\"{file_path}\"]`, `symbol: \"{function}\"`, and \
+`outline: true` to read the function and see what surrounds it.
+2. {placement}
+3. Write a test covering the function's normal behavior and at least one edge case or error \
+path.
+4. Call stage_operation with `file_path: \"{target}\"` and `operation: \"insert_after_node\"`, \
+with `anchor` set to the first line of the last existing test (or of the definition the test \
+should follow), and `content` set to the new test.
+
+{REVIEW_AND_COMMIT}
+
+5. Run the project's tests if you can, and fix the test with another staged edit if it fails."
+    )
+}
+
+fn apply_codemod(files: &str, change: &str) -> String {
+    format!(
+        "Apply this change across the files matching `{files}`: {change}
+
+1. Call open_files with `file_paths: [\"{files}\"]` and `outline: true` to read the matching \
+files. If the response says more files matched than were opened, work through these first and \
+open the rest afterwards.
+2. List every place that needs the change before editing anything.
+3. For each place, one at a time, since only one edit can be staged at once:
+   a. Call stage_operation with that file's `file_path`. Use `operation: \"replace_exact\"` \
+with the exact text to change as `anchor` for small changes, or `operation: \"replace_node\"` \
+with the first line of the enclosing definition to rewrite it whole.
+   b. {REVIEW_AND_COMMIT}
+4. Afterwards, open the files again to check that every place was changed and nothing else was."
+    )
+}
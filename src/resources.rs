@@ -0,0 +1,189 @@
+//! # MCP resources
+//!
+//! Read-only views that clients can read, or subscribe to for live previews, instead of
+//! polling tools:
+//! - `staged://current`: the full diff of the session's staged operation or codemod
+//! - `outline://<path>`: the outline of a file's definitions. Relative paths resolve against
+//!   the session context, like tool paths
+//!
+//! [`Subscriptions`] remembers what a client last saw of each resource it subscribed to, so
+//! transports can notify it when that changes.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+use crate::editor::{DiffOptions, ResultFormat};
+use crate::filesystem::FileOperations;
+use crate::outline::Outline;
+use crate::state::SemanticEditTools;
+use crate::tools::helpers::render;
+use crate::tools::{CodemodReport, ToolHelpers};
+
+/// URI of the staged operation's diff
+pub const STAGED_URI: &str = "staged://current";
+
+/// URI prefix of file outlines
+pub const OUTLINE_PREFIX: &str = "outline://";
+
+/// A resource as listed by `resources/list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    pub mime_type: &'static str,
+}
+
+/// A family of resources as listed by `resources/templates/list`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    pub description: String,
+    pub mime_type: &'static str,
+}
+
+/// The resources every session has
+pub fn list() -> Vec<Resource> {
+    vec![Resource {
+        uri: STAGED_URI.into(),
+        name: "Staged operation".into(),
+        description: "Full diff of the operation staged in this session".into(),
+        mime_type: "text/plain",
+    }]
+}
+
+/// The families of resources that are read by path
+pub fn templates() -> Vec<ResourceTemplate> {
+    vec![ResourceTemplate {
+        uri_template: format!("{OUTLINE_PREFIX}{{path}}"),
+        name: "File outline".into(),
+        description: "Definitions in a file with the lines they span. Relative paths resolve \
+            against the session context"
+            .into(),
+        mime_type: "text/plain",
+    }]
+}
+
+/// Read a resource's text in the state's default session
+pub fn read(state: &SemanticEditTools, uri: &str) -> Result<String> {
+    if uri == STAGED_URI {
+        return read_staged(state);
+    }
+    if let Some(path) = uri.strip_prefix(OUTLINE_PREFIX) {
+        return read_outline(state, path);
+    }
+    Err(unknown_resource(uri))
+}
+
+fn unknown_resource(uri: &str) -> anyhow::Error {
+    anyhow!("unknown resource `{uri}`. Resources are {STAGED_URI} and {OUTLINE_PREFIX}<path>")
+}
+
+fn read_staged(state: &SemanticEditTools) -> Result<String> {
+    if let Some(staged_codemod) = state.get_staged_codemod(None)? {
+        let mut report = CodemodReport::from_staged(
+            &staged_codemod,
+            &state.session_file_operations(None)?,
+            &DiffOptions::default().uncapped(),
+        )?;
+        report.staged = true;
+        return render(&report, ResultFormat::Text);
+    }
+    let Some(staged_operation) = state.get_staged_operation(None)? else {
+        return Ok("No operation is staged.\n".into());
+    };
+    let diff_options = staged_operation.diff_options.uncapped();
+    let dirty = staged_operation.dirty.clone();
+    let editor = state
+        .create_editor_from_operation(staged_operation)?
+        .with_diff_options(diff_options);
+    let (mut report, _) = editor.preview_report()?;
+    report.target_changed = state.staged_target_changed(None)?;
+    report.dirty = dirty;
+    render(&report, ResultFormat::Text)
+}
+
+fn read_outline(state: &SemanticEditTools, path: &str) -> Result<String> {
+    if path.is_empty() {
+        return Err(anyhow!(
+            "outline resources need a path, as in {OUTLINE_PREFIX}src/main.rs"
+        ));
+    }
+    let path = state.resolve_path(path, None)?;
+    let (_, source) = state.session_file_operations(None)?.read_encoded(&path)?;
+    let language = state
+        .language_registry()
+        .get_language_with_hint(&path, None)?;
+    let tree = language
+        .parser()?
+        .parse(&source, None)
+        .ok_or_else(|| anyhow!("could not parse {} as {}", path.display(), language.name()))?;
+    Ok(match Outline::new(language, &tree, &source) {
+        Some(outline) if outline.is_empty() => "No definitions found.\n".into(),
+        Some(outline) => outline.to_string(),
+        None => format!(
+            "Outlines are not available for {} files.\n",
+            language.name()
+        ),
+    })
+}
+
+/// The resources a client subscribed to, with a fingerprint of what it last saw of each
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+    resources: HashMap<String, u64>,
+}
+
+impl Subscriptions {
+    /// Subscribe to a resource. Outlines of files that do not exist yet can be subscribed to
+    pub fn subscribe(&mut self, state: &SemanticEditTools, uri: &str) -> Result<()> {
+        if uri != STAGED_URI && !uri.starts_with(OUTLINE_PREFIX) {
+            return Err(unknown_resource(uri));
+        }
+        let fingerprint = fingerprint(&read(state, uri));
+        self.resources.insert(uri.to_string(), fingerprint);
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, uri: &str) {
+        self.resources.remove(uri);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// The subscribed resources whose content changed since the last call, or since they were
+    /// subscribed to
+    ///
+    /// A resource that can no longer be read, such as an outline of a deleted file, counts as
+    /// changed once.
+    pub fn changed(&mut self, state: &SemanticEditTools) -> Vec<String> {
+        let mut changed = Vec::new();
+        for (uri, last_seen) in &mut self.resources {
+            let fingerprint = fingerprint(&read(state, uri));
+            if fingerprint != *last_seen {
+                *last_seen = fingerprint;
+                changed.push(uri.clone());
+            }
+        }
+        changed.sort();
+        changed
+    }
+}
+
+fn fingerprint(content: &Result<String>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match content {
+        Ok(content) => content.hash(&mut hasher),
+        Err(error) => error.to_string().hash(&mut hasher),
+    }
+    hasher.finish()
+}
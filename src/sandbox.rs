@@ -0,0 +1,211 @@
+//! # Path sandbox
+//!
+//! Optional restrictions on which files the server may read and write, for operators
+//! running it on behalf of autonomous agents.
+//!
+//! ## Restrictions
+//!
+//! - **Allowed paths**: when any are configured, every read and write must fall under one
+//!   of them, and `set_context` refuses directories outside them. This is enforced by the
+//!   file operations themselves, so it holds however a path reached them
+//! - **`context` mode**: paths given to tools must fall under the session context
+//! - **Read-only mode**: every write is refused, for review-only deployments and demos
+//! - **Symlink policy**: `follow` (the default) allows symlinks as long as their target stays
+//!   inside the sandbox, while `deny` refuses any path through a symlink inside the sandbox
+//!
+//! Symlinks and `..` components are resolved before a path is checked, so neither can be
+//! used to climb out of the sandbox.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use fieldwork::Fieldwork;
+
+use crate::error::SemanticEditError;
+
+/// Whether tool paths are confined to the session context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SandboxMode {
+    /// Paths anywhere are allowed (subject to the allowed paths)
+    #[default]
+    Off,
+    /// Paths must fall under the session context
+    Context,
+}
+
+impl SandboxMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SandboxMode::Off => "off",
+            SandboxMode::Context => "context",
+        }
+    }
+
+    /// Parse a mode name as accepted by the `SEMANTIC_EDIT_SANDBOX` environment variable
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "off" | "none" => Some(SandboxMode::Off),
+            "context" => Some(SandboxMode::Context),
+            _ => None,
+        }
+    }
+}
+
+/// How symlinks inside the sandbox are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Follow symlinks, checking where they lead
+    #[default]
+    Follow,
+    /// Refuse paths that pass through a symlink inside the sandbox
+    Deny,
+}
+
+impl SymlinkPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymlinkPolicy::Follow => "follow",
+            SymlinkPolicy::Deny => "deny",
+        }
+    }
+
+    /// Parse a policy name as accepted by the `SEMANTIC_EDIT_SYMLINKS` environment variable
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "follow" => Some(SymlinkPolicy::Follow),
+            "deny" => Some(SymlinkPolicy::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// Restrictions on the paths the server may access
+///
+/// The default sandbox allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Fieldwork)]
+#[fieldwork(get, with)]
+pub struct PathSandbox {
+    /// Whether tool paths are confined to the session context
+    #[fieldwork(get(copy))]
+    mode: SandboxMode,
+    /// Directories that every read and write must fall under; empty allows all
+    allowed_paths: Vec<PathBuf>,
+    /// How symlinks inside the sandbox are treated
+    #[fieldwork(get(copy))]
+    symlinks: SymlinkPolicy,
+    /// Refuse every write
+    #[fieldwork(get(copy))]
+    read_only: bool,
+}
+
+impl PathSandbox {
+    /// Whether this sandbox restricts anything
+    pub fn is_enabled(&self) -> bool {
+        self.mode == SandboxMode::Context || !self.allowed_paths.is_empty() || self.read_only
+    }
+
+    /// Check a path given to a tool, which must also fall under `context` in `context` mode
+    pub fn check(&self, path: &Path, context: Option<&Path>) -> Result<(), SemanticEditError> {
+        if self.mode == SandboxMode::Context {
+            let context = context.ok_or_else(|| {
+                not_allowed(
+                    path,
+                    "no context is set for this session. Use set_context first".into(),
+                )
+            })?;
+            self.check_within(path, &[context.to_path_buf()], "the session context")?;
+        }
+        self.check_allowed(path)
+    }
+
+    /// Check a path against the allowed paths, regardless of session
+    pub fn check_allowed(&self, path: &Path) -> Result<(), SemanticEditError> {
+        if self.allowed_paths.is_empty() {
+            return Ok(());
+        }
+        self.check_within(path, &self.allowed_paths, "the allowed paths")
+    }
+
+    /// Check that a path may be written, regardless of session
+    pub fn check_writable(&self, path: &Path) -> Result<(), SemanticEditError> {
+        if self.read_only {
+            return Err(SemanticEditError::ReadOnly {
+                path: path.display().to_string(),
+            });
+        }
+        self.check_allowed(path)
+    }
+
+    fn check_within(
+        &self,
+        path: &Path,
+        roots: &[PathBuf],
+        description: &str,
+    ) -> Result<(), SemanticEditError> {
+        let path = absolute(path);
+        let resolved = resolve(&path);
+        let Some(root) = roots
+            .iter()
+            .map(|root| resolve(&absolute(root)))
+            .find(|root| resolved.starts_with(root))
+        else {
+            return Err(not_allowed(&path, format!("it is outside {description}")));
+        };
+
+        if self.symlinks == SymlinkPolicy::Deny {
+            // symlinks above the root, like a symlinked home directory, are not the sandbox's concern
+            let symlink = path.ancestors().find(|ancestor| {
+                fs::symlink_metadata(ancestor).is_ok_and(|metadata| metadata.is_symlink())
+                    && ancestor
+                        .parent()
+                        .is_some_and(|parent| resolve(parent).starts_with(&root))
+            });
+            if let Some(symlink) = symlink {
+                return Err(not_allowed(
+                    &path,
+                    format!("it passes through the symlink `{}`", symlink.display()),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn not_allowed(path: &Path, reason: String) -> SemanticEditError {
+    SemanticEditError::PathNotAllowed {
+        path: path.display().to_string(),
+        reason,
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Where an absolute path actually leads: symlinks and `..` are resolved by the filesystem
+/// for the part of the path that exists, and lexically for the rest
+fn resolve(path: &Path) -> PathBuf {
+    let Some((existing, canonical)) = path
+        .ancestors()
+        .find_map(|ancestor| Some((ancestor, fs::canonicalize(ancestor).ok()?)))
+    else {
+        return normalize(path);
+    };
+    let remainder = path.strip_prefix(existing).unwrap_or(Path::new(""));
+    normalize(&canonical.join(remainder))
+}
+
+/// Remove `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
@@ -8,6 +8,9 @@
 //!
 //! - [`Selector`]: Main selector struct with targeting information and operation type
 //! - [`Operation`]: Types of editing operations (insert, replace, etc.)
+//! - [`SelectorBuilder`]: Checked construction of selectors
+//! - [`SelectorIssue`]: One problem with a malformed selector, with the field it is about and a
+//!   machine-readable [`SelectorIssueCode`]
 //! - Text-based targeting using string patterns
 //! - AST-based targeting using node types and names
 //! - Line/column-based targeting
@@ -17,6 +20,9 @@
 //!
 //! - **Insert operations**: `InsertBefore`, `InsertAfter`, `InsertAfterNode`
 //! - **Replace operations**: `ReplaceRange`, `ReplaceExact`, `ReplaceNode`
+//! - **Rewrite operations**: `RewritePattern`, matching a [comby](crate::comby) template
+//! - **Declaration operations**: `AddAttribute` and `AddDocComment`, adding a derive,
+//!   attribute, decorator, or documentation to a declaration
 //!
 //! ## Examples
 //!
@@ -28,6 +34,8 @@
 //!     operation: Operation::InsertAfter,
 //!     anchor: "function main".to_string(),
 //!     end: None,
+//!     end_inclusive: true,
+//!     include_leading_trivia: false,
 //! };
 //!
 //! // Target a range with start and end
@@ -35,7 +43,21 @@
 //!     operation: Operation::ReplaceRange,
 //!     anchor: "// Start here".to_string(),
 //!     end: Some("// End here".to_string()),
+//!     end_inclusive: true,
+//!     include_leading_trivia: false,
 //! };
+//!
+//! // Or build one, checking that it is well formed, here stopping before the end text
+//! let selector = Selector::builder(Operation::ReplaceRange, "// Start here")
+//!     .with_end("// End here")
+//!     .with_end_inclusive(false)
+//!     .build()?;
+//!
+//! // Replace a function together with its doc comment and attributes
+//! let selector = Selector::builder(Operation::ReplaceNode, "fn parse(")
+//!     .with_include_leading_trivia(true)
+//!     .build()?;
+//! # Ok::<(), anyhow::Error>(())
 //! ```
 
 use std::fmt::Display;
@@ -44,6 +66,8 @@ use anyhow::Result;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::SemanticEditError;
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, Copy)]
 pub enum Operation {
     #[serde(rename = "insert_before")]
@@ -58,6 +82,12 @@ pub enum Operation {
     ReplaceExact,
     #[serde(rename = "replace_node")]
     ReplaceNode,
+    #[serde(rename = "rewrite_pattern")]
+    RewritePattern,
+    #[serde(rename = "add_attribute")]
+    AddAttribute,
+    #[serde(rename = "add_doc_comment")]
+    AddDocComment,
 }
 
 impl Operation {
@@ -69,6 +99,9 @@ impl Operation {
             Operation::ReplaceRange => "replace range",
             Operation::ReplaceExact => "replace exact",
             Operation::ReplaceNode => "replace node",
+            Operation::RewritePattern => "rewrite pattern",
+            Operation::AddAttribute => "add attribute",
+            Operation::AddDocComment => "add doc comment",
         }
     }
 }
@@ -89,9 +122,24 @@ pub struct Selector {
     ///
     /// Replace Operations
     /// - **`replace_exact`** - Replace only the exact anchor text
-    /// - **`replace_node`** - Replace the entire AST node containing the anchor
+    /// - **`replace_node`** - Replace the entire AST node containing the anchor, and with
+    ///   `include_leading_trivia` its doc comments and attributes too
     /// - **`replace_range`** - Replace everything from anchor to end (requires `end` field)
     ///
+    /// Rewrite Operations
+    /// - **`rewrite_pattern`** - Match `anchor` as a comby template, where `:[name]` is a hole
+    ///   matching any balanced text and `:[[name]]` a word, and replace the match with
+    ///   `content`, in which the same holes stand for the text they matched
+    ///
+    /// Declaration Operations
+    /// - **`add_attribute`** - Add `content`, a Rust attribute like `#[derive(Debug)]` or a
+    ///   Python or TypeScript decorator like `@dataclass`, to the declaration containing the
+    ///   anchor. Derives and lint levels merge into the declaration's existing
+    ///   `#[derive(...)]` or `#[allow(...)]`, and attributes it already has are not repeated.
+    /// - **`add_doc_comment`** - Document the declaration containing the anchor with
+    ///   `content`, plain text written as `///` lines in Rust, a docstring in Python, or a
+    ///   `/** */` block in JavaScript and TypeScript, placed above attributes and decorators
+    ///
     /// ## Choosing the Right Operation
     ///
     /// **For adding new code:**
@@ -102,6 +150,11 @@ pub struct Selector {
     /// - Use `replace_exact` for small, precise text changes
     /// - Use `replace_node` for changing entire functions, classes, blocks, or statements
     /// - Use `replace_range` for changing multi-line sections with clear start/end boundaries
+    /// - Use `rewrite_pattern` to reshape code while keeping parts of it, like
+    ///   `foo(:[a], :[b])` → `foo(:[b], :[a])`, in any language
+    /// - Use `add_attribute` rather than inserting text to derive a trait or decorate a
+    ///   class, method, or function
+    /// - Use `add_doc_comment` to document a function, type, or class
     pub operation: Operation,
 
     /// Text to locate in the source code as the target for the operation.
@@ -139,50 +192,225 @@ pub struct Selector {
     /// ```
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end: Option<String>,
+
+    /// Whether a `replace_range` replaces the `end` text too (the default), or stops right
+    /// before it, keeping it in the file.
+    ///
+    /// # Example
+    /// ```json
+    /// {
+    ///   "operation": "replace_range",
+    ///   "anchor": "fn setup() {",
+    ///   "end": "fn teardown() {",
+    ///   "end_inclusive": false
+    /// }
+    /// ```
+    #[serde(
+        default = "default_end_inclusive",
+        skip_serializing_if = "is_inclusive"
+    )]
+    pub end_inclusive: bool,
+
+    /// Whether a `replace_node` also replaces the documentation comments, attributes,
+    /// decorators, and annotations above the node, so they are not left stranded above the
+    /// replacement. The content should then include any of them it keeps.
+    ///
+    /// # Example
+    /// ```json
+    /// {
+    ///   "operation": "replace_node",
+    ///   "anchor": "fn parse(",
+    ///   "include_leading_trivia": true
+    /// }
+    /// ```
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub include_leading_trivia: bool,
+}
+
+fn default_end_inclusive() -> bool {
+    true
+}
+
+fn is_inclusive(end_inclusive: &bool) -> bool {
+    *end_inclusive
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 impl Selector {
+    /// Start building a selector for an operation at an anchor
+    pub fn builder(operation: Operation, anchor: impl Into<String>) -> SelectorBuilder {
+        SelectorBuilder {
+            operation,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        }
+    }
+
     pub fn operation_name(&self) -> &str {
         self.operation.as_str()
     }
 
     /// Validate that the selector is properly formed
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), SemanticEditError> {
         let Self {
             operation,
             anchor,
             end,
+            end_inclusive,
+            include_leading_trivia,
         } = self;
 
-        let mut errors = vec![];
+        let mut issues = vec![];
         if anchor.trim().is_empty() {
-            errors.push("- `anchor` cannot be empty");
+            issues.push(SelectorIssue::required(
+                "anchor",
+                "`anchor` cannot be empty",
+            ));
+        }
+
+        if !end_inclusive && !matches!(operation, Operation::ReplaceRange) {
+            issues.push(SelectorIssue::not_applicable(
+                "end_inclusive",
+                "`end_inclusive` is only relevant for `replace_range` operations",
+            ));
+        }
+
+        if *include_leading_trivia && !matches!(operation, Operation::ReplaceNode) {
+            issues.push(SelectorIssue::not_applicable(
+                "include_leading_trivia",
+                "`include_leading_trivia` is only relevant for `replace_node` operations",
+            ));
         }
 
-        match operation {
+        let extraneous_end = match operation {
             Operation::InsertBefore | Operation::InsertAfter | Operation::InsertAfterNode => {
-                if end.is_some() {
-                    errors.push(
-                        "- End is not relevant for insert operations. Did you mean to `replace`?",
-                    );
-                }
+                Some("End is not relevant for insert operations. Did you mean to `replace`?")
             }
             Operation::ReplaceRange => {
                 if end.is_none() {
-                    errors.push("- End is required for range replacement");
-                }
-            }
-            Operation::ReplaceExact | Operation::ReplaceNode => {
-                if end.is_some() {
-                    errors.push("- `end` is not relevant for `replace_exact` operations. Did you intend to `replace_range`?");
+                    issues.push(SelectorIssue::required(
+                        "end",
+                        "End is required for range replacement",
+                    ));
                 }
+                None
             }
+            Operation::ReplaceExact | Operation::ReplaceNode => Some(
+                "`end` is not relevant for `replace_exact` operations. Did you intend to `replace_range`?",
+            ),
+            Operation::RewritePattern => Some(
+                "`end` is not relevant for `rewrite_pattern` operations. Use holes like `:[body]` in the anchor to span text",
+            ),
+            Operation::AddAttribute | Operation::AddDocComment => Some(
+                "`end` is not relevant for `add_attribute` and `add_doc_comment` operations. The anchor only needs to be inside the declaration",
+            ),
+        };
+        if let (Some(message), Some(_)) = (extraneous_end, end) {
+            issues.push(SelectorIssue::not_applicable("end", message));
         }
 
-        if errors.is_empty() {
+        if issues.is_empty() {
             Ok(())
         } else {
-            Err(errors.join("\n"))
+            Err(SemanticEditError::SelectorInvalid { issues })
         }
     }
 }
+
+/// What is wrong with one field of a selector, so clients can correct it without parsing the
+/// message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectorIssueCode {
+    /// The field is missing or empty, but the operation needs it
+    Required,
+    /// The field does not apply to the operation, and should be dropped
+    NotApplicable,
+    /// The field is present but its value cannot be used
+    Invalid,
+}
+
+/// One problem with a malformed selector
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelectorIssue {
+    /// The argument the problem is with, like `end` or `content`
+    pub field: &'static str,
+    pub code: SelectorIssueCode,
+    pub message: String,
+}
+
+impl SelectorIssue {
+    pub fn required(field: &'static str, message: impl Into<String>) -> Self {
+        Self::new(field, SelectorIssueCode::Required, message)
+    }
+
+    pub fn not_applicable(field: &'static str, message: impl Into<String>) -> Self {
+        Self::new(field, SelectorIssueCode::NotApplicable, message)
+    }
+
+    pub fn invalid(field: &'static str, message: impl Into<String>) -> Self {
+        Self::new(field, SelectorIssueCode::Invalid, message)
+    }
+
+    fn new(field: &'static str, code: SelectorIssueCode, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for SelectorIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "- {}", self.message)
+    }
+}
+
+/// Builder for [`Selector`]s, which checks that the selector is properly formed
+#[derive(Debug, Clone)]
+pub struct SelectorBuilder {
+    operation: Operation,
+    anchor: String,
+    end: Option<String>,
+    end_inclusive: bool,
+    include_leading_trivia: bool,
+}
+
+impl SelectorBuilder {
+    /// Set the text that ends a `replace_range`
+    pub fn with_end(mut self, end: impl Into<String>) -> Self {
+        self.end = Some(end.into());
+        self
+    }
+
+    /// Set whether a `replace_range` replaces its end text, or stops right before it
+    pub fn with_end_inclusive(mut self, end_inclusive: bool) -> Self {
+        self.end_inclusive = end_inclusive;
+        self
+    }
+
+    /// Set whether a `replace_node` replaces the comments and attributes above the node too
+    pub fn with_include_leading_trivia(mut self, include_leading_trivia: bool) -> Self {
+        self.include_leading_trivia = include_leading_trivia;
+        self
+    }
+
+    /// Build the selector, failing with the same message as an edit would if it is malformed
+    pub fn build(self) -> Result<Selector> {
+        let selector = Selector {
+            operation: self.operation,
+            anchor: self.anchor,
+            end: self.end,
+            end_inclusive: self.end_inclusive,
+            include_leading_trivia: self.include_leading_trivia,
+        };
+        selector.validate()?;
+        Ok(selector)
+    }
+}
@@ -0,0 +1,325 @@
+//! # MCP server
+//!
+//! Answers MCP messages for every transport. Tool calls go through mcplease, and this layer
+//! adds the [prompts](crate::prompts), [resources](crate::resources), and resource
+//! subscriptions, which mcplease does not know about.
+//!
+//! `tools/list` includes each tool's examples, which mcplease leaves out.
+//!
+//! Tool calls that fail are answered with an error whose code and `data` give the
+//! [kind of failure](crate::error::ToolErrorKind), rather than mcplease's one code for every
+//! error.
+//!
+//! [`run`] serves stdio. Subscribed resources are checked after every request and every
+//! [`POLL_INTERVAL`] while the client is idle, and the client is sent a
+//! `notifications/resources/updated` for each one that changed. Progress of tool calls is
+//! written as it is reported, and stdin is read while a request runs so that it can be
+//! cancelled.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use env_logger::{Builder, Target};
+use mcplease::traits::Tool;
+use mcplease::types::{ContentResponse, Info, McpError, McpMessage, McpRequest, McpResponse};
+use serde_json::{Value, json};
+
+use crate::error::ToolError;
+use crate::progress::{Cancellations, Notify, Progress};
+use crate::prompts;
+use crate::resources::{self, Subscriptions};
+use crate::state::SemanticEditTools;
+use crate::tools::{self, Tools};
+
+/// How often idle clients' subscribed resources are checked for changes
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// JSON-RPC error code for a message that is not valid JSON-RPC
+const PARSE_ERROR: i32 = -32700;
+
+/// JSON-RPC error code for missing or malformed parameters
+const INVALID_PARAMS: i32 = -32602;
+
+/// MCP error code for a resource that cannot be read
+const RESOURCE_NOT_FOUND: i32 = -32002;
+
+/// The request in a JSON-RPC message, `None` for notifications and responses, or the error
+/// response for a message that is not JSON-RPC
+pub fn parse_message(message: &[u8]) -> Result<Option<McpRequest>, Box<McpResponse>> {
+    let message: Value = serde_json::from_slice(message).map_err(|error| {
+        Box::new(McpResponse::error(
+            Value::Null,
+            PARSE_ERROR,
+            error.to_string(),
+        ))
+    })?;
+    // responses to server requests need no answer, and this server never makes any
+    if message.get("method").is_none() && message.get("id").is_some() {
+        return Ok(None);
+    }
+    match serde_json::from_value(message) {
+        Ok(McpMessage::Request(request)) => Ok(Some(request)),
+        Ok(McpMessage::Notification(notification)) => {
+            log::trace!("received {notification:?}, ignoring");
+            Ok(None)
+        }
+        Err(error) => Err(Box::new(McpResponse::error(
+            Value::Null,
+            PARSE_ERROR,
+            error.to_string(),
+        ))),
+    }
+}
+
+/// Answer a request in the state's default session
+pub fn handle(
+    request: McpRequest,
+    state: &mut SemanticEditTools,
+    subscriptions: &mut Subscriptions,
+    server_info: &Info,
+    instructions: Option<&'static str>,
+) -> McpResponse {
+    let McpRequest {
+        jsonrpc,
+        id,
+        method,
+        params,
+    } = request;
+    let uri = || {
+        params
+            .as_ref()
+            .and_then(|params| params.get("uri"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| "a `uri` parameter is required".to_string())
+    };
+
+    match method.as_str() {
+        "prompts/list" => McpResponse::success(id, json!({ "prompts": prompts::list() })),
+        "prompts/get" => {
+            let name = params
+                .as_ref()
+                .and_then(|params| params.get("name"))
+                .and_then(Value::as_str);
+            let arguments = params
+                .as_ref()
+                .and_then(|params| params.get("arguments"))
+                .cloned()
+                .map(serde_json::from_value::<HashMap<String, String>>)
+                .transpose();
+            match (name, arguments) {
+                (Some(name), Ok(arguments)) => {
+                    match prompts::get(name, &arguments.unwrap_or_default()) {
+                        Ok(prompt) => McpResponse::success(id, prompt),
+                        Err(error) => McpResponse::error(id, INVALID_PARAMS, error.to_string()),
+                    }
+                }
+                (None, _) => {
+                    McpResponse::error(id, INVALID_PARAMS, "a `name` parameter is required".into())
+                }
+                (_, Err(error)) => McpResponse::error(
+                    id,
+                    INVALID_PARAMS,
+                    format!("prompt arguments must be strings: {error}"),
+                ),
+            }
+        }
+        "tools/list" => McpResponse::success(id, json!({ "tools": tools::tools_list() })),
+        "tools/call" => {
+            let result = serde_json::from_value::<Tools>(params.unwrap_or(Value::Null))
+                .map_err(ToolError::invalid_params)
+                .and_then(|tool| tool.execute(state).map_err(ToolError::from));
+            match result {
+                Ok(text) => McpResponse::success(id, ContentResponse::text(text)),
+                Err(error) => tool_error_response(id, &error),
+            }
+        }
+        "resources/list" => McpResponse::success(id, json!({ "resources": resources::list() })),
+        "resources/templates/list" => {
+            McpResponse::success(id, json!({ "resourceTemplates": resources::templates() }))
+        }
+        "resources/read" => match uri() {
+            Ok(uri) => match resources::read(state, uri) {
+                Ok(text) => McpResponse::success(
+                    id,
+                    json!({ "contents": [{ "uri": uri, "mimeType": "text/plain", "text": text }] }),
+                ),
+                Err(error) => McpResponse::error(id, RESOURCE_NOT_FOUND, error.to_string()),
+            },
+            Err(message) => McpResponse::error(id, INVALID_PARAMS, message),
+        },
+        "resources/subscribe" => match uri() {
+            Ok(uri) => match subscriptions.subscribe(state, uri) {
+                Ok(()) => McpResponse::success(id, json!({})),
+                Err(error) => McpResponse::error(id, RESOURCE_NOT_FOUND, error.to_string()),
+            },
+            Err(message) => McpResponse::error(id, INVALID_PARAMS, message),
+        },
+        "resources/unsubscribe" => match uri() {
+            Ok(uri) => {
+                subscriptions.unsubscribe(uri);
+                McpResponse::success(id, json!({}))
+            }
+            Err(message) => McpResponse::error(id, INVALID_PARAMS, message),
+        },
+        _ => {
+            let initialize = method == "initialize";
+            let request = McpRequest {
+                jsonrpc,
+                id,
+                method,
+                params,
+            };
+            let mut response =
+                request.execute::<SemanticEditTools, Tools>(state, instructions, server_info);
+            if initialize {
+                if let Some(capabilities) = response
+                    .result
+                    .as_mut()
+                    .and_then(|result| result.get_mut("capabilities"))
+                    .and_then(Value::as_object_mut)
+                {
+                    capabilities.insert(
+                        "resources".into(),
+                        json!({ "subscribe": true, "listChanged": false }),
+                    );
+                    capabilities.insert("prompts".into(), json!({ "listChanged": false }));
+                }
+            }
+            response
+        }
+    }
+}
+
+/// The response to a failed tool call
+fn tool_error_response(id: Value, error: &ToolError) -> McpResponse {
+    McpResponse {
+        error: Some(McpError {
+            code: error.code(),
+            message: error.to_string(),
+            data: Some(error.data()),
+        }),
+        ..McpResponse::error(id, error.code(), String::new())
+    }
+}
+
+/// A `notifications/resources/updated` for each subscribed resource that changed since it
+/// was last checked
+pub fn updated_notifications(
+    state: &SemanticEditTools,
+    subscriptions: &mut Subscriptions,
+) -> Vec<Value> {
+    subscriptions
+        .changed(state)
+        .into_iter()
+        .map(|uri| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": { "uri": uri },
+            })
+        })
+        .collect()
+}
+
+/// Serve MCP over stdin and stdout until stdin closes
+///
+/// Like `mcplease::run`, this logs to the file named by `MCP_LOG_LOCATION`, if set.
+pub fn run(
+    state: &mut SemanticEditTools,
+    server_info: Info,
+    instructions: Option<&'static str>,
+) -> Result<()> {
+    if let Ok(log_location) = std::env::var("MCP_LOG_LOCATION") {
+        let path = PathBuf::from(&*shellexpand::tilde(&log_location));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let log_file = OpenOptions::new().create(true).append(true).open(path)?;
+        Builder::from_default_env()
+            .target(Target::Pipe(Box::new(log_file)))
+            .init();
+    }
+
+    // stdin is read on its own thread so subscriptions can be checked while it is quiet, and
+    // requests can be cancelled while they run
+    let cancellations = Arc::new(Cancellations::default());
+    let (sender, lines) = mpsc::channel();
+    thread::spawn({
+        let cancellations = Arc::clone(&cancellations);
+        move || {
+            for line in io::stdin().lock().lines() {
+                if let Ok(line) = &line {
+                    cancellations.observe("", line.as_bytes());
+                }
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    let notify: Notify = Arc::new(|notification| {
+        if let Err(error) = write_message(&mut io::stdout(), &notification) {
+            log::error!("Error writing notification: {error}");
+        }
+    });
+    let mut stdout = io::stdout();
+    let mut subscriptions = Subscriptions::default();
+    loop {
+        match lines.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(line)) => {
+                log::trace!("<- {line}");
+                let response = match parse_message(line.as_bytes()) {
+                    Ok(Some(request)) => {
+                        let id = request.id.clone();
+                        let cancelled = cancellations.flag("", &id);
+                        state.set_progress(Progress::new(&request, Arc::clone(&notify), cancelled));
+                        let response = handle(
+                            request,
+                            state,
+                            &mut subscriptions,
+                            &server_info,
+                            instructions,
+                        );
+                        state.set_progress(Progress::default());
+                        cancellations.finish("", &id);
+                        Some(response)
+                    }
+                    Ok(None) => None,
+                    Err(response) => Some(*response),
+                };
+                if let Some(response) = response {
+                    write_message(&mut stdout, &serde_json::to_value(response)?)?;
+                }
+            }
+            Ok(Err(error)) => {
+                log::error!("Error reading line: {error}");
+                break;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if !subscriptions.is_empty() {
+            for notification in updated_notifications(state, &mut subscriptions) {
+                write_message(&mut stdout, &notification)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_message(stdout: &mut impl Write, message: &Value) -> Result<()> {
+    let message = serde_json::to_string(message)?;
+    log::trace!("-> {message}");
+    stdout.write_all(message.as_bytes())?;
+    stdout.write_all(b"\n")?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! Recording of snapshot test fixtures.
+//!
+//! The snapshot tests in `tests/snapshots` replay tool calls against a fixture's input file
+//! and compare the responses, and the file the calls write, with the ones saved in the
+//! fixture. This module runs tool calls the same way and saves what they return as a new
+//! fixture, so a regression case is added by making the calls once instead of writing its
+//! files by hand. Features include:
+//! - The fixture layout the snapshot runner reads: `args.json`, `input.<ext>`,
+//!   `response.txt`, and `output.<ext>`
+//! - Tool calls run against an in-memory copy of the input, so recording never changes it
+//! - Tool calls are checked before anything is written, and existing fixtures are only
+//!   replaced when asked
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow, bail};
+use mcplease::traits::Tool;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::filesystem::TestFileOperations;
+use crate::state::SemanticEditTools;
+use crate::tools::Tools;
+
+/// Session id the snapshot runner runs its tool calls in
+const SNAPSHOT_SESSION: &str = "test";
+
+/// Records tool calls as a snapshot test fixture
+///
+/// ```ignore
+/// let recorded = FixtureRecorder::new("tests/snapshots/python/insert_after")
+///     .with_input("examples/app.py")
+///     .record(serde_json::from_str(&calls)?)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixtureRecorder {
+    fixture: PathBuf,
+    input: Option<PathBuf>,
+    overwrite: bool,
+}
+
+/// A fixture written by [`FixtureRecorder::record`]
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedFixture {
+    pub fixture: PathBuf,
+    /// Number of tool calls recorded
+    pub calls: usize,
+    /// Names of the files written into the fixture directory
+    pub files: Vec<String>,
+    /// The recorded responses, as saved in `response.txt`
+    pub response: String,
+}
+
+impl Display for RecordedFixture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Recorded {} tool call{} into {}: {}",
+            self.calls,
+            if self.calls == 1 { "" } else { "s" },
+            self.fixture.display(),
+            self.files.join(", ")
+        )?;
+        write!(f, "\n{}", self.response)
+    }
+}
+
+impl FixtureRecorder {
+    /// Record into the fixture directory, such as `tests/snapshots/rust/insert_after`
+    pub fn new(fixture: impl Into<PathBuf>) -> Self {
+        Self {
+            fixture: fixture.into(),
+            input: None,
+            overwrite: false,
+        }
+    }
+
+    /// Copy this file into the fixture as its input, which `stage_operation` calls edit
+    pub fn with_input(mut self, input: impl Into<PathBuf>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+
+    /// Replace the fixture if the directory already has one
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Run the tool calls, each a `{"name": ..., "arguments": ...}` object, or an array of
+    /// them, and save them with their responses and output as a fixture
+    pub fn record(&self, calls: Value) -> Result<RecordedFixture> {
+        let mut calls = match calls {
+            Value::Array(calls) => calls,
+            call @ Value::Object(_) => vec![call],
+            other => bail!("tool calls must be an object or an array of objects, not {other}"),
+        };
+        let input = self
+            .input
+            .as_ref()
+            .map(|path| {
+                fs::read(path)
+                    .map(|content| (path, content))
+                    .map_err(|error| anyhow!("failed to read {}: {error}", path.display()))
+            })
+            .transpose()?;
+        let input_name = input.as_ref().map(|(path, _)| fixture_file("input", path));
+
+        // The runner passes the input to stage_operation itself, so the recorded calls
+        // leave it out
+        if input_name.is_some() {
+            for call in calls
+                .iter_mut()
+                .filter(|call| call["name"] == "stage_operation")
+            {
+                if let Some(arguments) = call["arguments"].as_object_mut() {
+                    arguments.remove("file_path");
+                }
+            }
+        }
+        let tools = calls
+            .iter()
+            .map(|call| {
+                let mut call = call.clone();
+                let stages = call["name"] == "stage_operation";
+                if let (true, Some(input_name), Some(arguments)) =
+                    (stages, &input_name, call["arguments"].as_object_mut())
+                {
+                    arguments.insert("file_path".into(), input_name.as_str().into());
+                }
+                serde_json::from_value::<Tools>(call)
+                    .map_err(|error| anyhow!("invalid tool call: {error}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.fixture.join("args.json").exists() && !self.overwrite {
+            bail!(
+                "{} already has a fixture; pass --force to replace it",
+                self.fixture.display()
+            );
+        }
+        fs::create_dir_all(&self.fixture)?;
+        self.remove_previous_files()?;
+
+        let mut files = vec!["args.json".to_string()];
+        let mut args = serde_json::to_string_pretty(&calls)?;
+        args.push('\n');
+        fs::write(self.fixture.join("args.json"), args)?;
+
+        let file_operations = Arc::new(TestFileOperations::new());
+        let output_name = match (&input, &input_name) {
+            (Some((path, content)), Some(input_name)) => {
+                let fixture_input = self.fixture.join(input_name);
+                fs::write(&fixture_input, content)?;
+                file_operations.add_file(fs::canonicalize(&fixture_input)?, content.clone());
+                files.push(input_name.clone());
+                Some(fixture_file("output", path))
+            }
+            _ => None,
+        };
+
+        let mut state =
+            SemanticEditTools::with_file_operations(None, Box::new(Arc::clone(&file_operations)))?;
+        state.set_default_session_id(SNAPSHOT_SESSION);
+        state.set_context(None, self.fixture.clone())?;
+
+        let mut response = String::new();
+        let mut output = None;
+        for tool in tools {
+            response.push_str(&format!(
+                "=== snapshot test tool call: {} ===\n",
+                tool.name()
+            ));
+            match tool.execute(&mut state) {
+                Ok(result) => response.push_str(&result),
+                Err(error) => response.push_str(&error.to_string()),
+            }
+            response.push('\n');
+            output = file_operations.get_last_write_content();
+        }
+
+        fs::write(self.fixture.join("response.txt"), &response)?;
+        files.push("response.txt".into());
+        if let (Some(output), Some(output_name)) = (output, output_name) {
+            fs::write(self.fixture.join(&output_name), output)?;
+            files.push(output_name);
+        }
+
+        Ok(RecordedFixture {
+            fixture: self.fixture.clone(),
+            calls: calls.len(),
+            files,
+            response,
+        })
+    }
+
+    /// Remove the input and output of a fixture being replaced, which may have had another
+    /// extension
+    fn remove_previous_files(&self) -> Result<()> {
+        for entry in fs::read_dir(&self.fixture)? {
+            let path = entry?.path();
+            let stem = path.file_stem().and_then(|stem| stem.to_str());
+            if path.is_file() && matches!(stem, Some("input" | "output")) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The name of a fixture's input or output file, with the extension of the file it copies
+fn fixture_file(stem: &str, path: &Path) -> String {
+    match path.extension() {
+        Some(extension) => format!("{stem}.{}", extension.to_string_lossy()),
+        None => stem.to_string(),
+    }
+}
@@ -8,39 +8,78 @@
 //!
 //! - [`SemanticEditTools`]: Main state container with session and cache management
 //! - [`StagedOperation`]: Represents an operation that can be previewed and committed
+//! - [`SourceFingerprint`]: Detects files that changed between stage and commit
+//! - [`SessionExport`]: A session's state, exported to resume work later
 //! - [`CacheStats`]: Performance statistics for file caching
 //! - [`StatsLruCache`]: LRU cache with performance tracking
+//! - [`SourceCache`]: Source files and syntax trees reused between editor runs
 //!
 //! ## Features
 //!
 //! - **Session isolation**: Separate contexts for different projects
 //! - **File caching**: Configurable LRU cache with performance statistics
+//! - **Source caching**: Files are re-read and re-parsed only when their stamp or content changes
+//! - **File watching**: Session contexts are watched so external edits evict cached files and
+//!   flag staged operations
 //! - **Operation staging**: Preview changes before applying them
+//! - **Session export**: A session's state can be exported and imported to resume work elsewhere
+//! - **Stale session cleanup**: Sessions unused for longer than a TTL are pruned from the store
+//! - **Unsaved buffers**: Edit against a client's unsaved buffer instead of the on-disk file
 //! - **Path resolution**: Context-aware path handling (relative/absolute)
+//! - **Path sandbox**: Optional confinement of tool paths to the session context or an allowlist
+//! - **Read-only mode**: Server-wide or per-session refusal to commit edits
+//! - **Git auto-commit**: Per-session commits of every written file to git
+//! - **Git snapshots**: Per-session saving of files' content in git before they are overwritten
 //! - **Performance monitoring**: Cache hit/miss tracking and reporting
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 use anyhow::Result;
 use fieldwork::Fieldwork;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tree_sitter::Tree;
 
-use crate::editor::EditPosition;
+use crate::backup::BackupStrategy;
+use crate::commit_lock::LOCK_DIRECTORY;
+use crate::editor::{DiffOptions, EditPosition, FormatLimits, FormatMode, ParseLimits};
+use crate::encoding::TextEncoding;
 use crate::error::SemanticEditError;
-use crate::filesystem::{FileOperations, StdFileOperations};
+use crate::filesystem::{
+    FileOperations, FileStamp, OverlayFileOperations, SandboxedFileOperations, StdFileOperations,
+};
 use crate::languages::{LanguageName, LanguageRegistry};
+use crate::progress::Progress;
+use crate::sandbox::PathSandbox;
 use crate::selector::Selector;
+use crate::validation::{CustomQueries, RuleSettings, ValidationLevel};
+use crate::watcher::{ChangedFiles, ContextWatcher};
 use mcplease::session::SessionStore;
 
+/// Each cache holds at most this many bytes of content, unless configured otherwise
+pub const DEFAULT_CACHE_MAX_BYTES: usize = 64 * 1024 * 1024;
+
 /// Cache performance statistics
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
     pub total_requests: u64,
+    /// Approximate bytes currently held by the cache
+    #[serde(default)]
+    pub bytes: usize,
+    /// Byte budget of the cache, if bounded
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
 }
 
 impl CacheStats {
@@ -53,14 +92,29 @@ impl CacheStats {
     }
 }
 
+/// Approximate memory held by a cached value, counted against a cache's byte budget
+pub trait CacheWeight {
+    fn weight(&self) -> usize;
+}
+
+impl CacheWeight for String {
+    fn weight(&self) -> usize {
+        self.len()
+    }
+}
+
 /// LRU cache wrapper that tracks statistics
+///
+/// Entries are evicted once the cache holds `cap` entries or, if a byte budget is set,
+/// once their keys and values weigh more than the budget. Values heavier than the whole
+/// budget are not cached.
 #[derive(Debug)]
-pub struct StatsLruCache {
-    cache: LruCache<String, String>,
+pub struct StatsLruCache<V = String> {
+    cache: LruCache<String, V>,
     stats: CacheStats,
 }
 
-impl StatsLruCache {
+impl<V: CacheWeight> StatsLruCache<V> {
     pub fn new(cap: NonZeroUsize) -> Self {
         Self {
             cache: LruCache::new(cap),
@@ -68,7 +122,22 @@ impl StatsLruCache {
         }
     }
 
-    pub fn get(&mut self, key: &str) -> Option<&String> {
+    /// Bound the total size of cached entries, evicting least recently used ones to fit
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.stats.max_bytes = max_bytes;
+        self.evict_to_budget();
+    }
+
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.stats.max_bytes
+    }
+
+    /// Approximate bytes currently held
+    pub fn bytes(&self) -> usize {
+        self.stats.bytes
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<&V> {
         self.stats.total_requests += 1;
         match self.cache.get(key) {
             Some(value) => {
@@ -82,8 +151,60 @@ impl StatsLruCache {
         }
     }
 
-    pub fn put(&mut self, key: String, value: String) -> Option<String> {
-        self.cache.put(key, value)
+    /// Get a value if it is still valid, evicting it and counting a miss if it is not
+    pub fn get_if(&mut self, key: &str, is_valid: impl FnOnce(&V) -> bool) -> Option<&V> {
+        if self.cache.peek(key).is_some_and(|value| !is_valid(value)) {
+            self.pop(key);
+        }
+        self.get(key)
+    }
+
+    /// Get a value mutably without counting a request or updating recency
+    ///
+    /// Changes must not alter the value's weight.
+    pub fn peek_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.cache.peek_mut(key)
+    }
+
+    /// Insert a value, returning the value it replaced
+    pub fn put(&mut self, key: String, value: V) -> Option<V> {
+        let weight = key.len() + value.weight();
+        if self
+            .stats
+            .max_bytes
+            .is_some_and(|max_bytes| weight > max_bytes)
+        {
+            return self.pop(&key);
+        }
+
+        self.stats.bytes += weight;
+        let replaced = match self.cache.push(key.clone(), value) {
+            Some((evicted_key, evicted)) => {
+                self.stats.bytes -= evicted_key.len() + evicted.weight();
+                (evicted_key == key).then_some(evicted)
+            }
+            None => None,
+        };
+        self.evict_to_budget();
+        replaced
+    }
+
+    pub fn pop(&mut self, key: &str) -> Option<V> {
+        let value = self.cache.pop(key)?;
+        self.stats.bytes -= key.len() + value.weight();
+        Some(value)
+    }
+
+    fn evict_to_budget(&mut self) {
+        let Some(max_bytes) = self.stats.max_bytes else {
+            return;
+        };
+        while self.stats.bytes > max_bytes {
+            let Some((key, value)) = self.cache.pop_lru() else {
+                break;
+            };
+            self.stats.bytes -= key.len() + value.weight();
+        }
     }
 
     pub fn cap(&self) -> NonZeroUsize {
@@ -102,11 +223,137 @@ impl StatsLruCache {
         &self.stats
     }
 
+    /// Reset hit and miss counts, keeping the size figures
     pub fn clear_stats(&mut self) {
-        self.stats = CacheStats::default();
+        self.stats = CacheStats {
+            bytes: self.stats.bytes,
+            max_bytes: self.stats.max_bytes,
+            ..CacheStats::default()
+        };
     }
 }
 
+/// A source file as the editor last read it, along with its syntax tree once parsed
+#[derive(Debug, Clone)]
+pub struct CachedSource {
+    /// Modification time and size when the file was read, if the file operations know them
+    pub stamp: Option<FileStamp>,
+    pub fingerprint: SourceFingerprint,
+    pub encoding: TextEncoding,
+    pub source_code: String,
+    pub tree: Option<(LanguageName, Tree)>,
+}
+
+/// Syntax trees are not counted; they grow with the source, which the budget already bounds
+impl CacheWeight for CachedSource {
+    fn weight(&self) -> usize {
+        self.source_code.len()
+    }
+}
+
+/// Source files read by the editor, keyed by canonical path
+///
+/// Repeated stage → retarget → commit cycles on a file reuse the content and syntax tree
+/// instead of reading and parsing the file each time. An entry is invalidated when the
+/// file's modification time or size changes, or, for content without a stamp such as
+/// unsaved buffers, when the content hash changes.
+#[derive(Debug)]
+pub struct SourceCache {
+    entries: Mutex<StatsLruCache<CachedSource>>,
+}
+
+impl SourceCache {
+    pub fn new(cap: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(StatsLruCache::new(cap)),
+        }
+    }
+
+    /// Read a file through the cache
+    pub fn read(&self, file_operations: &dyn FileOperations, path: &Path) -> Result<CachedSource> {
+        let key = cache_key(path);
+        let stamp = file_operations.stamp(path);
+        // without a stamp, the content has to be read to tell whether it changed
+        let content = match stamp {
+            Some(_) => None,
+            None => Some(file_operations.read_encoded(path)?),
+        };
+        let content_hash = content
+            .as_ref()
+            .map(|(_, source_code)| SourceFingerprint::from_source(source_code).content_hash);
+
+        let mut entries = self.lock()?;
+        let cached = entries.get_if(&key, |cached| match stamp {
+            Some(_) => cached.stamp == stamp,
+            None => Some(cached.fingerprint.content_hash) == content_hash,
+        });
+        if let Some(cached) = cached {
+            return Ok(cached.clone());
+        }
+
+        let (encoding, source_code) = match content {
+            Some(content) => content,
+            None => file_operations.read_encoded(path)?,
+        };
+        let cached = CachedSource {
+            stamp,
+            fingerprint: SourceFingerprint::new(path, &source_code),
+            encoding,
+            source_code,
+            tree: None,
+        };
+        entries.put(key, cached.clone());
+        Ok(cached)
+    }
+
+    /// Remember the syntax tree parsed from a file's cached content
+    pub fn store_tree(
+        &self,
+        path: &Path,
+        fingerprint: &SourceFingerprint,
+        language_name: LanguageName,
+        tree: &Tree,
+    ) -> Result<()> {
+        if let Some(cached) = self.lock()?.peek_mut(&cache_key(path)) {
+            if cached.fingerprint.content_hash == fingerprint.content_hash {
+                cached.tree = Some((language_name, tree.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget a file, e.g. after writing it
+    pub fn invalidate(&self, path: &Path) -> Result<()> {
+        self.lock()?.pop(&cache_key(path));
+        Ok(())
+    }
+
+    /// Bound the total size of cached files
+    pub fn set_max_bytes(&self, max_bytes: Option<usize>) -> Result<()> {
+        self.lock()?.set_max_bytes(max_bytes);
+        Ok(())
+    }
+
+    /// Hit, miss, and size statistics
+    pub fn stats(&self) -> Result<CacheStats> {
+        Ok(self.lock()?.stats().clone())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, StatsLruCache<CachedSource>>> {
+        Ok(self
+            .entries
+            .lock()
+            .map_err(|_| SemanticEditError::CacheMutexPoisoned)?)
+    }
+}
+
+fn cache_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
 // Explanation for the presence of session_id that is currently unused: The intent was initially to
 // have a conversation-unique identifier of some sort in order to isolate state between
 // conversations. However, MCP provides no mechanism to distinguish between conversations, so I
@@ -115,13 +362,63 @@ impl StatsLruCache {
 // until we have a better solution. I still hope to iterate towards isolated sessions, so the code
 // is still written to support that.
 
+/// Sessions unused for this many days are pruned, unless configured otherwise
+pub const DEFAULT_SESSION_TTL_DAYS: u64 = 30;
+
 /// Session data specific to semantic editing operations
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SemanticEditSessionData {
+    /// When the session was first used
+    #[serde(default)]
+    pub created_at: Option<SystemTime>,
+    /// When the session was last read or updated
+    #[serde(default)]
+    pub last_used: Option<SystemTime>,
     /// Current working context path
     pub context_path: Option<PathBuf>,
+    /// Named workspace roots, referenced in tool paths as `name:relative/path`
+    #[serde(default)]
+    pub roots: BTreeMap<String, PathBuf>,
     /// Currently staged operation
     pub staged_operation: Option<StagedOperation>,
+    /// Currently staged codemod, staged in place of an operation
+    #[serde(default)]
+    pub staged_codemod: Option<StagedCodemod>,
+    /// Backup strategy for this session, overriding the server default
+    #[serde(default)]
+    pub backup_strategy: Option<BackupStrategy>,
+    /// Unsaved editor buffers, read in place of the on-disk content of these files
+    #[serde(default)]
+    pub buffers: HashMap<PathBuf, String>,
+    /// How much of each edited file is formatted
+    #[serde(default)]
+    pub format_mode: FormatMode,
+    /// How strictly edits are validated
+    #[serde(default)]
+    pub validation_level: ValidationLevel,
+    /// Per-rule overrides that disable or downgrade individual context validation rules
+    #[serde(default)]
+    pub rules: RuleSettings,
+    /// Refuse to commit edits in this session
+    #[serde(default)]
+    pub read_only: bool,
+    /// Commit each file written by `commit_staged` to git
+    #[serde(default)]
+    pub git_commit: bool,
+    /// Save each file's content in git before `commit_staged` overwrites it
+    #[serde(default)]
+    pub git_snapshot: bool,
+}
+
+/// Format version of [`SessionExport`], bumped when the export changes incompatibly
+pub const SESSION_EXPORT_VERSION: u32 = 1;
+
+/// A session's state as exported by `export_session`, for resuming work after a restart
+/// or on another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub version: u32,
+    pub session: SemanticEditSessionData,
 }
 
 /// Represents a staged operation that can be previewed and committed
@@ -133,11 +430,111 @@ pub struct StagedOperation {
     pub file_path: PathBuf,
     pub language_name: LanguageName,
     pub edit_position: Option<EditPosition>,
+    /// Fingerprint of the source file when the operation was staged
+    #[serde(default)]
+    pub source_fingerprint: Option<SourceFingerprint>,
+    /// How diffs for this operation are rendered
+    #[serde(default)]
+    pub diff_options: DiffOptions,
+    /// How much of the file is formatted, resolved when the operation was staged
+    #[serde(default)]
+    pub format_mode: FormatMode,
+    /// How strictly the edit is validated, resolved when the operation was staged
+    #[serde(default)]
+    pub validation_level: ValidationLevel,
+    /// Per-rule validation overrides, resolved when the operation was staged
+    #[serde(default)]
+    pub rules: RuleSettings,
+    /// Why the file was risky to edit when the operation was staged, such as uncommitted git
+    /// changes or conflict markers; committing then needs an explicit acknowledgement
+    #[serde(default)]
+    pub dirty: Option<String>,
+    /// The notebook cell the operation edits, if the file is a Jupyter notebook
+    #[serde(default)]
+    pub cell: Option<usize>,
+    /// The match of the anchor the operation edits, by index, if it was limited to one
+    #[serde(default)]
+    pub candidate: Option<usize>,
+    /// Where the previewed edit lands, with line and column coordinates, so clients can show
+    /// it and map it to their editor buffers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<EditPosition>,
+    /// Why the previewed insert looked like a second copy of the text next to it; committing
+    /// then needs an explicit acknowledgement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<String>,
+}
+
+/// A codemod staged by `run_codemod`: the files its rules rewrote, committed together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedCodemod {
+    /// Ids of the rules that ran
+    pub rules: Vec<String>,
+    pub files: Vec<StagedRewrite>,
+}
+
+/// A file rewritten by a staged codemod
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedRewrite {
+    pub file_path: PathBuf,
+    /// Fingerprint of the file when the codemod was staged
+    pub source_fingerprint: SourceFingerprint,
+    /// The rewritten content
+    pub output: String,
+    /// Number of matches rewritten in the file
+    pub matches: usize,
+    /// Why the file was risky to edit when the codemod was staged, like
+    /// [`StagedOperation::dirty`]
+    #[serde(default)]
+    pub dirty: Option<String>,
 }
 
 impl StagedOperation {
-    pub fn retarget(&mut self, selector: Selector) {
+    /// Target the operation at another selector, and optionally one of its anchor's matches
+    pub fn retarget(&mut self, selector: Selector, candidate: Option<usize>) {
         self.selector = selector;
+        self.candidate = candidate;
+        self.target = None;
+        self.duplicate = None;
+    }
+}
+
+/// Identifies the content of a source file at a point in time
+///
+/// Used to detect files that changed on disk between staging and committing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceFingerprint {
+    pub content_hash: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl SourceFingerprint {
+    /// Fingerprint the given file content, recording the file's modification time if available
+    pub fn new(file_path: &Path, source_code: &str) -> Self {
+        Self {
+            modified: std::fs::metadata(file_path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            ..Self::from_source(source_code)
+        }
+    }
+
+    /// Fingerprint content that has no backing file
+    pub fn from_source(source_code: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        source_code.hash(&mut hasher);
+        Self {
+            content_hash: hasher.finish(),
+            modified: None,
+        }
+    }
+
+    /// Whether the file content differs from this fingerprint
+    ///
+    /// Modification time alone is not considered a change, so a `touch` does not
+    /// invalidate a staged operation.
+    pub fn is_stale(&self, current: &SourceFingerprint) -> bool {
+        self.content_hash != current.content_hash
     }
 }
 
@@ -149,10 +546,43 @@ pub struct SemanticEditTools {
     session_store: SessionStore<SemanticEditSessionData>,
     language_registry: Arc<LanguageRegistry>,
     file_cache: Arc<Mutex<StatsLruCache>>,
+    /// Source files read by the editor, with their syntax trees
+    source_cache: Arc<SourceCache>,
+    /// File operations, confined to the sandbox's allowed paths
     #[fieldwork(get)]
-    file_operations: Box<dyn FileOperations>,
+    file_operations: SandboxedFileOperations,
+    /// Session used by tool calls that do not name one
+    #[fieldwork(set, with, into)]
+    default_session_id: String,
+    /// Backup strategy used when a session does not set its own
+    #[fieldwork(get(copy), set, with)]
+    backup_strategy: BackupStrategy,
+    /// Compiled project validation queries, keyed by context path
+    #[fieldwork(skip)]
+    custom_queries: Arc<Mutex<HashMap<PathBuf, Arc<CustomQueries>>>>,
+    /// Where sessions are persisted, if anywhere
+    storage_path: Option<PathBuf>,
+    /// Sessions unused for this many days are pruned; 0 keeps sessions forever
+    #[fieldwork(get(copy), set, with)]
+    session_ttl_days: u64,
+    /// Whether session contexts are watched for changes made outside the server
+    #[fieldwork(get(copy), set, with)]
+    watch_files: bool,
+    /// How long formatters may run and how much they may output
+    #[fieldwork(get(copy), set, with)]
+    format_limits: FormatLimits,
+    /// How large a file may be and how long it may take to parse before it is edited as text
+    #[fieldwork(get(copy), set, with)]
+    parse_limits: ParseLimits,
+    /// Watchers for session contexts, keyed by context path
+    #[fieldwork(skip)]
+    watchers: Mutex<HashMap<PathBuf, ContextWatcher>>,
+    /// Files that changed on disk since an operation on them was staged
+    #[fieldwork(skip)]
+    changed_files: Arc<ChangedFiles>,
+    /// Progress of the request being served, set by the transport for each request
     #[fieldwork(set, with)]
-    default_session_id: &'static str,
+    progress: Progress,
 }
 
 impl std::fmt::Debug for SemanticEditTools {
@@ -161,8 +591,18 @@ impl std::fmt::Debug for SemanticEditTools {
             .field("session_store", &self.session_store)
             .field("language_registry", &self.language_registry)
             .field("file_cache", &self.file_cache)
+            .field("source_cache", &self.source_cache)
             .field("file_operations", &"<dyn FileOperations>")
+            .field("sandbox", self.sandbox())
             .field("default_session_id", &self.default_session_id)
+            .field("backup_strategy", &self.backup_strategy)
+            .field("storage_path", &self.storage_path)
+            .field("session_ttl_days", &self.session_ttl_days)
+            .field("watch_files", &self.watch_files)
+            .field("format_limits", &self.format_limits)
+            .field("parse_limits", &self.parse_limits)
+            .field("watchers", &self.watchers)
+            .field("progress", &self.progress)
             .finish()
     }
 }
@@ -175,18 +615,33 @@ impl SemanticEditTools {
         cache_size: Option<NonZeroUsize>,
     ) -> Result<Self> {
         let storage_path = storage_path.map(|s| PathBuf::from(&*shellexpand::tilde(s)));
-        let session_store = SessionStore::new(storage_path)?;
+        let session_store = SessionStore::new(storage_path.clone())?;
         let language_registry = Arc::new(LanguageRegistry::new()?);
         let cache_size =
             cache_size.unwrap_or_else(|| NonZeroUsize::new(50).expect("50 is non-zero"));
-        let file_cache = Arc::new(Mutex::new(StatsLruCache::new(cache_size)));
+        let mut file_cache = StatsLruCache::new(cache_size);
+        file_cache.set_max_bytes(Some(DEFAULT_CACHE_MAX_BYTES));
+        let file_cache = Arc::new(Mutex::new(file_cache));
+        let source_cache = Arc::new(SourceCache::new(cache_size));
+        source_cache.set_max_bytes(Some(DEFAULT_CACHE_MAX_BYTES))?;
 
         Ok(Self {
             session_store,
             language_registry,
             file_cache,
-            file_operations,
-            default_session_id: "default",
+            source_cache,
+            file_operations: SandboxedFileOperations::new(file_operations, PathSandbox::default()),
+            default_session_id: "default".into(),
+            backup_strategy: BackupStrategy::default(),
+            custom_queries: Arc::new(Mutex::new(HashMap::new())),
+            storage_path,
+            session_ttl_days: DEFAULT_SESSION_TTL_DAYS,
+            watch_files: true,
+            format_limits: FormatLimits::default(),
+            parse_limits: ParseLimits::default(),
+            watchers: Mutex::new(HashMap::new()),
+            changed_files: Arc::new(ChangedFiles::default()),
+            progress: Progress::default(),
         })
     }
 
@@ -205,10 +660,84 @@ impl SemanticEditTools {
         Self::new(storage_path, Box::new(StdFileOperations), None)
     }
 
+    /// Restrictions on the paths tools may access
+    pub fn sandbox(&self) -> &PathSandbox {
+        self.file_operations.sandbox()
+    }
+
+    /// Set restrictions on the paths tools may access
+    pub fn set_sandbox(&mut self, sandbox: PathSandbox) -> &mut Self {
+        self.file_operations.set_sandbox(sandbox);
+        self
+    }
+
+    /// Builder-style [`Self::set_sandbox`]
+    pub fn with_sandbox(mut self, sandbox: PathSandbox) -> Self {
+        self.set_sandbox(sandbox);
+        self
+    }
+
+    /// Data for a session, recording that it was used
+    fn session_data(&self, session_id: &str) -> Result<SemanticEditSessionData> {
+        let mut session_data = None;
+        self.update_session(session_id, |data| session_data = Some(data.clone()))?;
+        Ok(session_data.unwrap_or_default())
+    }
+
+    /// Update the data for a session, recording that it was used
+    fn update_session(
+        &self,
+        session_id: &str,
+        fun: impl FnOnce(&mut SemanticEditSessionData),
+    ) -> Result<()> {
+        self.session_store.update(session_id, |data| {
+            let now = SystemTime::now();
+            data.created_at.get_or_insert(now);
+            data.last_used = Some(now);
+            fun(data);
+        })
+    }
+
+    /// Remove sessions that have not been used for `max_age` from the persisted store,
+    /// returning how many were removed
+    ///
+    /// The default session is always kept, and in-memory stores are left alone.
+    pub fn prune_sessions(&mut self, max_age: Duration) -> Result<usize> {
+        let Some(storage_path) = self.storage_path.clone() else {
+            return Ok(0);
+        };
+        let Ok(Value::Object(mut sessions)) =
+            serde_json::from_str::<Value>(&fs::read_to_string(&storage_path)?)
+        else {
+            return Ok(0);
+        };
+
+        let cutoff = SystemTime::now()
+            .checked_sub(max_age)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let count = sessions.len();
+        sessions.retain(|session_id, entry| {
+            // sessions saved before last_used was tracked fall back to the store's own metadata
+            let last_used = ["/data/last_used", "/metadata/last_used"]
+                .into_iter()
+                .find_map(|pointer| entry.pointer(pointer).filter(|value| !value.is_null()))
+                .and_then(|value| serde_json::from_value::<SystemTime>(value.clone()).ok());
+            *session_id == self.default_session_id
+                || last_used.is_none_or(|last_used| last_used >= cutoff)
+        });
+
+        let removed = count - sessions.len();
+        if removed > 0 {
+            fs::write(&storage_path, serde_json::to_string_pretty(&sessions)?)?;
+            self.session_store = SessionStore::new(Some(storage_path))?;
+        }
+        Ok(removed)
+    }
+
     /// Get context for a session
     pub fn get_context(&self, session_id: Option<&str>) -> Result<Option<PathBuf>> {
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
-        let session_data = self.session_store.get_or_create(session_id)?;
+        let session_data = self.session_data(session_id)?;
         Ok(session_data.context_path)
     }
 
@@ -219,18 +748,68 @@ impl SemanticEditTools {
         staged_operation: Option<StagedOperation>,
     ) -> Result<()> {
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
-        self.session_store.update(session_id, |data| {
+        if let Some(staged_operation) = &staged_operation {
+            self.changed_files.clear(&staged_operation.file_path);
+        }
+        self.update_session(session_id, |data| {
+            if staged_operation.is_some() {
+                data.staged_codemod = None;
+            }
             data.staged_operation = staged_operation;
         })
     }
 
+    /// Stage a codemod, replacing any existing staged operation or codemod
+    pub fn stage_codemod(
+        &self,
+        session_id: Option<&str>,
+        staged_codemod: Option<StagedCodemod>,
+    ) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        for rewrite in staged_codemod.iter().flat_map(|codemod| &codemod.files) {
+            self.changed_files.clear(&rewrite.file_path);
+        }
+        self.update_session(session_id, |data| {
+            if staged_codemod.is_some() {
+                data.staged_operation = None;
+            }
+            data.staged_codemod = staged_codemod;
+        })
+    }
+
+    /// Get the currently staged codemod, if any
+    pub fn get_staged_codemod(&self, session_id: Option<&str>) -> Result<Option<StagedCodemod>> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        Ok(self.session_data(session_id)?.staged_codemod)
+    }
+
+    /// Take the staged codemod, removing it from storage
+    pub fn take_staged_codemod(&self, session_id: Option<&str>) -> Result<Option<StagedCodemod>> {
+        let mut staged_codemod = None;
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            staged_codemod = data.staged_codemod.take();
+        })?;
+        Ok(staged_codemod)
+    }
+
+    /// Whether the target file of the staged operation changed on disk since it was staged,
+    /// as seen by the context watcher
+    pub fn staged_target_changed(&self, session_id: Option<&str>) -> Result<bool> {
+        Ok(self
+            .get_staged_operation(session_id)?
+            .is_some_and(|staged_operation| {
+                self.changed_files.contains(&staged_operation.file_path)
+            }))
+    }
+
     /// Get the currently staged operation, if any
     pub fn get_staged_operation(
         &self,
         session_id: Option<&str>,
     ) -> Result<Option<StagedOperation>> {
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
-        let session_data = self.session_store.get_or_create(session_id)?;
+        let session_data = self.session_data(session_id)?;
         Ok(session_data.staged_operation)
     }
 
@@ -241,7 +820,7 @@ impl SemanticEditTools {
     ) -> Result<Option<StagedOperation>> {
         let mut staged_op = None;
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
-        self.session_store.update(session_id, |data| {
+        self.update_session(session_id, |data| {
             staged_op = data.staged_operation.take();
         })?;
         Ok(staged_op)
@@ -257,7 +836,7 @@ impl SemanticEditTools {
         F: FnOnce(&mut StagedOperation),
     {
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
-        self.session_store.update(session_id, |data| {
+        self.update_session(session_id, |data| {
             if let Some(ref mut op) = data.staged_operation {
                 fun(op);
             }
@@ -265,34 +844,451 @@ impl SemanticEditTools {
         self.get_staged_operation(Some(session_id))
     }
 
-    /// Set context path for a session
+    /// Set context path for a session, which must fall under the sandbox's allowed paths
     pub fn set_context(&self, session_id: Option<&str>, path: PathBuf) -> Result<()> {
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.sandbox().check_allowed(&path)?;
 
-        self.session_store.update(session_id, |data| {
+        self.update_session(session_id, |data| {
             data.context_path = Some(path);
         })
     }
 
-    /// Resolve a path relative to session context if needed
-    pub(crate) fn resolve_path(&self, path_str: &str, session_id: Option<&str>) -> Result<PathBuf> {
-        let path = PathBuf::from(&*shellexpand::tilde(path_str));
+    /// Register a named workspace root, which tool paths can reference as
+    /// `name:relative/path`. The first root registered also becomes the session context if
+    /// none is set, while later ones leave it unchanged
+    pub fn add_root(&self, session_id: Option<&str>, name: &str, path: PathBuf) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        if !is_root_name(name) {
+            return Err(anyhow::Error::from(SemanticEditError::InvalidRootName {
+                name: name.to_string(),
+            }));
+        }
+        self.sandbox().check_allowed(&path)?;
+
+        self.update_session(session_id, |data| {
+            data.context_path.get_or_insert_with(|| path.clone());
+            data.roots.insert(name.to_string(), path);
+        })
+    }
+
+    /// Get the named workspace roots of a session
+    pub fn get_roots(&self, session_id: Option<&str>) -> Result<BTreeMap<String, PathBuf>> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        Ok(self.session_data(session_id)?.roots)
+    }
 
-        if path.is_absolute() {
-            return Ok(std::fs::canonicalize(path)?);
+    /// Watch the session context and workspace roots for changes made outside the server, if
+    /// file watching is enabled and they are not watched already
+    pub fn watch_context(&self, session_id: Option<&str>) -> Result<()> {
+        if !self.watch_files {
+            return Ok(());
         }
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        let mut watchers = self
+            .watchers
+            .lock()
+            .map_err(|_| SemanticEditError::CacheMutexPoisoned)?;
+        for path in session_data
+            .context_path
+            .into_iter()
+            .chain(session_data.roots.into_values())
+        {
+            if let Entry::Vacant(entry) = watchers.entry(path) {
+                let watcher = ContextWatcher::new(
+                    entry.key(),
+                    Arc::clone(&self.source_cache),
+                    Arc::clone(&self.changed_files),
+                )?;
+                entry.insert(watcher);
+            }
+        }
+        Ok(())
+    }
 
+    /// Where commit locks are kept: next to the session store, so server instances sharing a
+    /// store also share locks, or in the temp directory when sessions are not persisted
+    pub fn lock_directory(&self) -> PathBuf {
+        self.storage_path
+            .as_deref()
+            .and_then(Path::parent)
+            .map_or_else(std::env::temp_dir, Path::to_path_buf)
+            .join(LOCK_DIRECTORY)
+    }
+
+    /// Identifies a session of this server process in commit records
+    pub fn committer(&self, session_id: Option<&str>) -> String {
         let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        format!("{session_id} (process {})", std::process::id())
+    }
 
-        match self.get_context(Some(session_id))? {
-            Some(context) => Ok(std::fs::canonicalize(context.join(path_str))?),
-            None => Err(anyhow::Error::from(SemanticEditError::ContextNotFound {
-                session_id: session_id.to_string(),
-            })),
+    /// Export a session's context, staged operation, unsaved buffers, and settings
+    pub fn export_session(&self, session_id: Option<&str>) -> Result<SessionExport> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        Ok(SessionExport {
+            version: SESSION_EXPORT_VERSION,
+            session: self.session_data(session_id)?,
+        })
+    }
+
+    /// Replace a session's state with an export
+    ///
+    /// If `context` is given, it replaces the exported context, and the staged operation
+    /// and unsaved buffers under the exported context are moved under it. Every path must
+    /// fall within the sandbox.
+    pub fn import_session(
+        &self,
+        session_id: Option<&str>,
+        export: SessionExport,
+        context: Option<PathBuf>,
+    ) -> Result<()> {
+        if export.version != SESSION_EXPORT_VERSION {
+            return Err(anyhow::Error::from(
+                SemanticEditError::UnsupportedSessionExport {
+                    version: export.version,
+                    supported: SESSION_EXPORT_VERSION,
+                },
+            ));
+        }
+
+        let mut imported = export.session;
+        if let Some(context) = context {
+            let rebase = |path: PathBuf| match imported.context_path.as_deref() {
+                Some(old_context) => match path.strip_prefix(old_context) {
+                    Ok(relative) => context.join(relative),
+                    Err(_) => path,
+                },
+                None => path,
+            };
+            if let Some(staged_operation) = &imported.staged_operation {
+                let file_path = rebase(staged_operation.file_path.clone());
+                imported.staged_operation = Some(StagedOperation {
+                    file_path,
+                    ..staged_operation.clone()
+                });
+            }
+            for rewrite in imported
+                .staged_codemod
+                .iter_mut()
+                .flat_map(|codemod| &mut codemod.files)
+            {
+                rewrite.file_path = rebase(rewrite.file_path.clone());
+            }
+            imported.buffers = std::mem::take(&mut imported.buffers)
+                .into_iter()
+                .map(|(path, buffer)| (rebase(path), buffer))
+                .collect();
+            for root in imported.roots.values_mut() {
+                *root = rebase(root.clone());
+            }
+            imported.context_path = Some(context);
+        }
+
+        let context = imported.context_path.as_deref();
+        for root in context
+            .into_iter()
+            .chain(imported.roots.values().map(PathBuf::as_path))
+        {
+            self.sandbox().check_allowed(root)?;
+        }
+        let staged_path = imported
+            .staged_operation
+            .as_ref()
+            .map(|staged_operation| &staged_operation.file_path);
+        let codemod_paths = imported
+            .staged_codemod
+            .iter()
+            .flat_map(|codemod| &codemod.files)
+            .map(|rewrite| &rewrite.file_path);
+        for path in staged_path
+            .into_iter()
+            .chain(codemod_paths)
+            .chain(imported.buffers.keys())
+        {
+            let root = imported
+                .roots
+                .values()
+                .find(|root| path.starts_with(root))
+                .map(PathBuf::as_path);
+            self.sandbox().check(path, root.or(context))?;
         }
+
+        if let Some(staged_operation) = &imported.staged_operation {
+            self.changed_files.clear(&staged_operation.file_path);
+        }
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            *data = SemanticEditSessionData {
+                created_at: data.created_at,
+                last_used: data.last_used,
+                ..imported
+            };
+        })
     }
 
-    /// Get file cache performance statistics
+    /// Compile the project validation queries for the session context, replacing any
+    /// previously loaded ones, and return how many were loaded
+    pub fn load_custom_queries(&self, session_id: Option<&str>) -> Result<usize> {
+        let Some(context) = self.get_context(session_id)? else {
+            return Ok(0);
+        };
+        let custom_queries = CustomQueries::load(&context, &self.language_registry)?;
+        let count = custom_queries.len();
+        self.custom_queries
+            .lock()
+            .map_err(|_| SemanticEditError::CacheMutexPoisoned)?
+            .insert(context, Arc::new(custom_queries));
+        Ok(count)
+    }
+
+    /// Project validation queries for the session context, loaded on first use
+    pub fn custom_queries(&self, session_id: Option<&str>) -> Result<Option<Arc<CustomQueries>>> {
+        let Some(context) = self.get_context(session_id)? else {
+            return Ok(None);
+        };
+        let mut cache = self
+            .custom_queries
+            .lock()
+            .map_err(|_| SemanticEditError::CacheMutexPoisoned)?;
+        if let Some(custom_queries) = cache.get(&context) {
+            return Ok(Some(custom_queries.clone()));
+        }
+
+        let custom_queries = Arc::new(CustomQueries::load(&context, &self.language_registry)?);
+        cache.insert(context, custom_queries.clone());
+        Ok(Some(custom_queries))
+    }
+
+    /// Set the backup strategy for a session, or None to use the server default
+    pub fn set_session_backup_strategy(
+        &self,
+        session_id: Option<&str>,
+        backup_strategy: Option<BackupStrategy>,
+    ) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.backup_strategy = backup_strategy;
+        })
+    }
+
+    /// Set how much of each edited file is formatted in a session
+    pub fn set_session_format_mode(
+        &self,
+        session_id: Option<&str>,
+        format_mode: FormatMode,
+    ) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.format_mode = format_mode;
+        })
+    }
+
+    /// How much of each edited file is formatted in a session
+    pub fn format_mode(&self, session_id: Option<&str>) -> Result<FormatMode> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        Ok(session_data.format_mode)
+    }
+
+    /// Set how strictly edits are validated in a session
+    pub fn set_session_validation_level(
+        &self,
+        session_id: Option<&str>,
+        validation_level: ValidationLevel,
+    ) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.validation_level = validation_level;
+        })
+    }
+
+    /// How strictly edits are validated in a session
+    pub fn validation_level(&self, session_id: Option<&str>) -> Result<ValidationLevel> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        Ok(session_data.validation_level)
+    }
+
+    /// Replace the per-rule validation overrides of a session
+    pub fn set_session_rules(&self, session_id: Option<&str>, rules: RuleSettings) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.rules = rules;
+        })
+    }
+
+    /// Per-rule validation overrides of a session
+    pub fn rules(&self, session_id: Option<&str>) -> Result<RuleSettings> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        Ok(session_data.rules)
+    }
+
+    /// Make a session read-only, or writable again unless the whole server is read-only
+    pub fn set_session_read_only(&self, session_id: Option<&str>, read_only: bool) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.read_only = read_only;
+        })
+    }
+
+    /// Commit each file written in a session to git, or stop doing so
+    pub fn set_session_git_commit(&self, session_id: Option<&str>, git_commit: bool) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.git_commit = git_commit;
+        })
+    }
+
+    /// Whether files written in a session are committed to git
+    pub fn git_commit(&self, session_id: Option<&str>) -> Result<bool> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        Ok(self.session_data(session_id)?.git_commit)
+    }
+
+    /// Save files' content in git before they are overwritten in a session, or stop doing so
+    pub fn set_session_git_snapshot(
+        &self,
+        session_id: Option<&str>,
+        git_snapshot: bool,
+    ) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| {
+            data.git_snapshot = git_snapshot;
+        })
+    }
+
+    /// Whether files' content is saved in git before they are overwritten in a session
+    pub fn git_snapshot(&self, session_id: Option<&str>) -> Result<bool> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        Ok(self.session_data(session_id)?.git_snapshot)
+    }
+
+    /// Whether edits may not be committed in a session, because either the server or the
+    /// session is read-only
+    pub fn is_read_only(&self, session_id: Option<&str>) -> Result<bool> {
+        if self.sandbox().read_only() {
+            return Ok(true);
+        }
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        Ok(session_data.read_only)
+    }
+
+    /// Record the unsaved buffer content of a file, or None to go back to the on-disk content
+    pub fn set_buffer(
+        &self,
+        session_id: Option<&str>,
+        file_path: &Path,
+        buffer: Option<String>,
+    ) -> Result<()> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        self.update_session(session_id, |data| match buffer {
+            Some(buffer) => {
+                data.buffers.insert(file_path.to_path_buf(), buffer);
+            }
+            None => {
+                data.buffers.remove(file_path);
+            }
+        })
+    }
+
+    /// File operations for a session, reading unsaved buffers in place of on-disk content
+    pub fn session_file_operations(
+        &self,
+        session_id: Option<&str>,
+    ) -> Result<OverlayFileOperations<'_>> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        Ok(OverlayFileOperations::new(
+            &self.file_operations,
+            session_data.buffers,
+        ))
+    }
+
+    /// Back up a file before it is overwritten, according to the effective backup strategy
+    ///
+    /// Returns the path of the backup if one was written.
+    pub fn backup_file(
+        &self,
+        session_id: Option<&str>,
+        file_path: &Path,
+    ) -> Result<Option<PathBuf>> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        let strategy = session_data.backup_strategy.unwrap_or(self.backup_strategy);
+        strategy.backup(
+            &self.file_operations,
+            file_path,
+            session_data.context_path.as_deref(),
+        )
+    }
+
+    /// Resolve a path relative to session context if needed, refusing paths outside the sandbox
+    /// Resolve a tool path, which may be relative to the session context or, written as
+    /// `name:relative/path`, to a named workspace root
+    pub(crate) fn resolve_path(&self, path_str: &str, session_id: Option<&str>) -> Result<PathBuf> {
+        let session_id = session_id.unwrap_or_else(|| self.default_session_id());
+        let session_data = self.session_data(session_id)?;
+        let (context, path_str) =
+            match path_str.split_once(':') {
+                Some((name, relative)) if !session_data.roots.is_empty() && is_root_name(name) => {
+                    let root = session_data.roots.get(name).ok_or_else(|| {
+                        SemanticEditError::UnknownRoot {
+                            name: name.to_string(),
+                            available: session_data
+                                .roots
+                                .keys()
+                                .map(|name| format!("`{name}`"))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        }
+                    })?;
+                    if Path::new(relative).is_absolute() {
+                        return Err(anyhow::Error::from(SemanticEditError::AbsoluteRootPath {
+                            path: path_str.to_string(),
+                            root: name.to_string(),
+                        }));
+                    }
+                    (Some(root.clone()), relative)
+                }
+                _ => (session_data.context_path, path_str),
+            };
+        let path = PathBuf::from(&*shellexpand::tilde(path_str));
+
+        let path = match &context {
+            _ if path.is_absolute() => path,
+            Some(context) => context.join(path_str),
+            None => {
+                return Err(anyhow::Error::from(SemanticEditError::ContextNotFound {
+                    session_id: session_id.to_string(),
+                }));
+            }
+        };
+
+        self.sandbox().check(&path, context.as_deref())?;
+        self.canonicalize(path)
+    }
+
+    /// Canonicalize a path on disk, leaving paths that only exist in the file operations as-is
+    fn canonicalize(&self, path: PathBuf) -> Result<PathBuf> {
+        if !path.exists() && self.file_operations.exists(&path) {
+            return Ok(path);
+        }
+        Ok(std::fs::canonicalize(path)?)
+    }
+
+    /// Bound the bytes held by each of the file and source caches, or None for no bound
+    pub fn set_cache_max_bytes(&self, max_bytes: Option<usize>) -> Result<()> {
+        self.file_cache
+            .lock()
+            .map_err(|_| SemanticEditError::CacheMutexPoisoned)?
+            .set_max_bytes(max_bytes);
+        self.source_cache.set_max_bytes(max_bytes)
+    }
+
+    /// Get file cache performance statistics and memory usage
     pub fn cache_info(&self) -> Result<CacheStats> {
         let cache = self
             .file_cache
@@ -311,3 +1307,12 @@ impl SemanticEditTools {
         Ok(())
     }
 }
+
+/// Whether the part of a path before `:` could name a workspace root, as opposed to a
+/// Windows drive letter or part of a file name
+fn is_root_name(name: &str) -> bool {
+    name.len() >= 2
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
@@ -9,8 +9,15 @@
 //! - [`StageOperation`]: Stage a code editing operation for preview
 //! - [`RetargetStaged`]: Modify the targeting of a staged operation
 //! - [`CommitStaged`]: Execute a staged operation
+//! - [`ViewStagedDiff`]: Show the complete diff of a staged operation or codemod
+//! - [`RunCodemod`]: Rewrite files with ast-grep style rules, staged as one codemod
+//! - [`OrganizeImports`]: Sort and group the imports of files, staged as one codemod
 //! - [`SetContext`]: Set the working directory context for relative paths
 //! - [`OpenFiles`]: Read files with optional diff support
+//! - [`CleanupSessions`]: Prune sessions that have not been used recently
+//! - [`ExportSession`]: Export the session's state to resume it later
+//! - [`ImportSession`]: Resume a session from an export
+//! - [`LanguageReference`]: List a grammar's node kinds and fields, and the queries using them
 //!
 //! ## Workflow
 //!
@@ -18,10 +25,20 @@
 //! 2. **Retarget** (optional): Use `retarget_staged` to adjust targeting
 //! 3. **Commit**: Use `commit_staged` to apply changes
 //!
+//! ## Examples
+//!
+//! Every tool has examples, including calls that recover from failed ones, which
+//! [`tools_list`] adds to the tool's input schema for `tools/list`.
+//!
 //! ## Helper Traits
 //!
 //! - [`ToolHelpers`]: Common functionality shared across tools
 
+use mcplease::traits::{AsToolsList, WithExamples};
+use mcplease::types::Example;
+use serde::Serialize;
+use serde_json::Value;
+
 use crate::state::SemanticEditTools;
 
 // Load helper module from tools/ directory
@@ -31,11 +48,66 @@ pub mod helpers;
 // Re-export ToolHelpers trait
 pub use helpers::ToolHelpers;
 
+// Re-export the codemod report, which commit_staged, view_staged_diff, and resources also render
+pub use run_codemod::CodemodReport;
+
 mcplease::tools!(
     SemanticEditTools,
     (StageOperation, stage_operation, "stage_operation"),
     (RetargetStaged, retarget_staged, "retarget_staged"),
     (CommitStaged, commit_staged, "commit_staged"),
+    (ViewStagedDiff, view_staged_diff, "view_staged_diff"),
+    (RunCodemod, run_codemod, "run_codemod"),
+    (OrganizeImports, organize_imports, "organize_imports"),
     (SetContext, set_context, "set_context"),
-    (OpenFiles, open_files, "open_files")
+    (OpenFiles, open_files, "open_files"),
+    (CleanupSessions, cleanup_sessions, "cleanup_sessions"),
+    (ExportSession, export_session, "export_session"),
+    (ImportSession, import_session, "import_session"),
+    (LanguageReference, language_reference, "language_reference")
 );
+
+/// The tools' schemas as `tools/list` lists them, with each tool's examples in its input
+/// schema's `examples`
+///
+/// mcplease only adds examples to the schemas of tools that have none, so they are added here.
+pub fn tools_list() -> Vec<Value> {
+    Tools::tools_list()
+        .into_iter()
+        .map(|schema| {
+            let mut schema = serde_json::to_value(schema).unwrap_or_default();
+            let name = schema["name"].as_str().unwrap_or_default().to_string();
+            if let Some(input_schema) = schema.get_mut("inputSchema").and_then(Value::as_object_mut)
+            {
+                input_schema.insert("examples".into(), Value::Array(examples(&name)));
+            }
+            schema
+        })
+        .collect()
+}
+
+/// A tool's examples, each its arguments along with a `description` of when to use them
+pub fn examples(tool: &str) -> Vec<Value> {
+    match tool {
+        "stage_operation" => to_values(StageOperation::examples()),
+        "retarget_staged" => to_values(RetargetStaged::examples()),
+        "commit_staged" => to_values(CommitStaged::examples()),
+        "view_staged_diff" => to_values(ViewStagedDiff::examples()),
+        "run_codemod" => to_values(RunCodemod::examples()),
+        "organize_imports" => to_values(OrganizeImports::examples()),
+        "set_context" => to_values(SetContext::examples()),
+        "open_files" => to_values(OpenFiles::examples()),
+        "cleanup_sessions" => to_values(CleanupSessions::examples()),
+        "export_session" => to_values(ExportSession::examples()),
+        "import_session" => to_values(ImportSession::examples()),
+        "language_reference" => to_values(LanguageReference::examples()),
+        _ => Vec::new(),
+    }
+}
+
+fn to_values<T: Serialize>(examples: Vec<Example<T>>) -> Vec<Value> {
+    examples
+        .iter()
+        .filter_map(|example| serde_json::to_value(example).ok())
+        .collect()
+}
@@ -0,0 +1,66 @@
+//! Cleanup sessions tool for pruning stale sessions.
+//!
+//! This module implements the `cleanup_sessions` MCP tool which removes sessions that have
+//! not been used recently from the persisted session store. Features include:
+//! - Defaults to the server's session TTL (`SEMANTIC_EDIT_SESSION_TTL_DAYS`)
+//! - Optional custom age threshold in days
+//! - Always keeps the current session
+
+use std::time::Duration;
+
+use crate::state::SemanticEditTools;
+use anyhow::Result;
+use mcplease::traits::{Tool, WithExamples};
+use mcplease::types::Example;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Remove sessions that have not been used recently from the persisted session store
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "cleanup_sessions")]
+pub struct CleanupSessions {
+    /// Remove sessions unused for more than this many days. Defaults to the server's
+    /// session TTL, which is 30 days unless configured otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub older_than_days: Option<u64>,
+}
+
+impl WithExamples for CleanupSessions {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Prune sessions older than the server's session TTL",
+                item: Self {
+                    older_than_days: None,
+                },
+            },
+            Example {
+                description: "Prune sessions unused for more than a week",
+                item: Self {
+                    older_than_days: Some(7),
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<SemanticEditTools> for CleanupSessions {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let days = match self.older_than_days {
+            Some(days) => days,
+            None if state.session_ttl_days() == 0 => {
+                return Ok(
+                    "Session expiry is disabled on this server. Pass older_than_days to prune \
+sessions anyway."
+                        .to_string(),
+                );
+            }
+            None => state.session_ttl_days(),
+        };
+        let removed =
+            state.prune_sessions(Duration::from_secs(days.saturating_mul(24 * 60 * 60)))?;
+        Ok(format!(
+            "Removed {removed} sessions unused for more than {days} days."
+        ))
+    }
+}
@@ -4,14 +4,43 @@
 //! staged operation, applying the changes to the actual file. Features include:
 //! - Executes the currently staged operation
 //! - Validates the operation exists
-//! - Applies changes to the file system
+//! - Refuses to apply if the file changed since the operation was staged, naming the other
+//!   session if one made the change
+//! - Holds an advisory lock on the file while committing, so concurrent commits cannot
+//!   overwrite each other
+//! - Optionally backs up the original file before overwriting it
+//! - Applies changes to the file system, preserving the file's encoding
+//! - Reads the file back after writing it, reporting any difference from the edit's output,
+//!   such as a formatter rewriting it on save or another process writing it at the same time
+//! - Applies edits made against an unsaved buffer, then stops overlaying that buffer
 //! - Returns success confirmation
-//! - Clears the staged operation after commit
+//! - Clears the staged operation once the file is written, keeping it staged if the commit
+//!   is refused or the write fails
+//! - Text or structured JSON results
+//! - Dry-run mode returning the final content without writing or unstaging
+//! - Refused in read-only mode (except dry runs), leaving the operation staged
+//! - Refused without `acknowledge_dirty` (except dry runs) if the file had uncommitted git
+//!   changes or conflict markers when the operation was staged
+//! - Refused without `acknowledge_duplicate` (except dry runs) if the staged insert is
+//!   identical to the text right next to it, the usual sign of an edit applied twice
+//! - Optionally saves the file's content in git before overwriting it, refusing to write if
+//!   that fails, and commits the written file to git unless it already had uncommitted
+//!   changes, if the session asks for either
+//! - Commits a staged codemod as one transaction: every file is locked, in the order of its
+//!   canonical path, and checked for changes since staging before any is written, so a stale
+//!   file leaves them all untouched and the codemod staged. If a write fails, the files
+//!   already written are restored, and every written file is read back like a single edit
 
+use crate::commit_lock::{CommitLock, CommitLocks, LastCommit};
+use crate::editor::{ResultFormat, WriteCheck};
 use crate::error::SemanticEditError;
-use crate::state::SemanticEditTools;
+use crate::filesystem::FileOperations;
+use crate::git;
+use crate::state::{SemanticEditTools, SourceFingerprint, StagedCodemod};
 use crate::tools::ToolHelpers;
-use anyhow::Result;
+use crate::tools::helpers::render;
+use crate::tools::run_codemod::CodemodReport;
+use anyhow::{Result, anyhow};
 use mcplease::traits::{Tool, WithExamples};
 use mcplease::types::Example;
 use serde::{Deserialize, Serialize};
@@ -23,6 +52,27 @@ pub struct CommitStaged {
     /// Confirm that you want to execute the staged operation
     #[serde(default = "default_acknowledge")]
     pub acknowledge: bool,
+
+    /// Confirm committing an edit to a file that had uncommitted git changes or merge conflict
+    /// markers when the edit was staged. Check the staged diff first: such edits can bake
+    /// unfinished work or conflict markers into the result.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub acknowledge_dirty: bool,
+
+    /// Confirm committing an insert whose content is identical to the text right next to
+    /// where it goes. Such inserts are usually an edit being applied a second time.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub acknowledge_duplicate: bool,
+
+    /// Run the full edit, validation, and formatting pipeline and return the final file
+    /// content without writing anything. The operation stays staged.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+
+    /// Result format: `text` (default) for a human-readable preview, or `json` for a
+    /// structured report with diff hunks, target range, and efficiency metrics
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
     // this is commented out temporarily as an experiment in usability
     // /// Optional session identifier
     // pub session_id: Option<String>,
@@ -34,16 +84,61 @@ fn default_acknowledge() -> bool {
 
 impl WithExamples for CommitStaged {
     fn examples() -> Vec<Example<Self>> {
-        vec![Example {
-            description: "Commit the currently staged operation",
-            item: Self { acknowledge: true },
-        }]
+        vec![
+            Example {
+                description: "Commit the currently staged operation",
+                item: Self {
+                    acknowledge: true,
+                    acknowledge_dirty: false,
+                    acknowledge_duplicate: false,
+                    dry_run: false,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Commit an edit to a file with uncommitted changes, after reviewing the diff",
+                item: Self {
+                    acknowledge: true,
+                    acknowledge_dirty: true,
+                    acknowledge_duplicate: false,
+                    dry_run: false,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Commit the staged operation and get a structured report with \
+                              the backup path or git commit",
+                item: Self {
+                    acknowledge: true,
+                    acknowledge_dirty: false,
+                    acknowledge_duplicate: false,
+                    dry_run: false,
+                    format: ResultFormat::Json,
+                },
+            },
+            Example {
+                description: "Produce the final file content without writing it",
+                item: Self {
+                    acknowledge: true,
+                    acknowledge_dirty: false,
+                    acknowledge_duplicate: false,
+                    dry_run: true,
+                    format: ResultFormat::Text,
+                },
+            },
+        ]
     }
 }
 
 impl Tool<SemanticEditTools> for CommitStaged {
     fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
-        let Self { acknowledge } = self;
+        let Self {
+            acknowledge,
+            acknowledge_dirty,
+            acknowledge_duplicate,
+            dry_run,
+            format,
+        } = self;
 
         if !acknowledge {
             return Err(anyhow::Error::from(
@@ -51,17 +146,352 @@ impl Tool<SemanticEditTools> for CommitStaged {
             ));
         }
 
+        if let Some(staged_codemod) = state.get_staged_codemod(None)? {
+            return commit_codemod(state, staged_codemod, acknowledge_dirty, dry_run, format);
+        }
+
+        if !dry_run && state.is_read_only(None)? {
+            let path = state
+                .get_staged_operation(None)?
+                .map(|staged_operation| staged_operation.file_path.display().to_string())
+                .unwrap_or_default();
+            return Err(anyhow::Error::from(SemanticEditError::ReadOnly { path }));
+        }
+
+        if !dry_run && !acknowledge_dirty {
+            if let Some(staged_operation) = state.get_staged_operation(None)? {
+                if let Some(reason) = staged_operation.dirty {
+                    return Err(anyhow::Error::from(
+                        SemanticEditError::DirtyFileNotAcknowledged {
+                            path: staged_operation.file_path.display().to_string(),
+                            reason,
+                        },
+                    ));
+                }
+            }
+        }
+
+        if !dry_run && !acknowledge_duplicate {
+            if let Some(staged_operation) = state.get_staged_operation(None)? {
+                if let Some(reason) = staged_operation.duplicate {
+                    return Err(anyhow::Error::from(
+                        SemanticEditError::DuplicateNotAcknowledged {
+                            path: staged_operation.file_path.display().to_string(),
+                            reason,
+                        },
+                    ));
+                }
+            }
+        }
+
+        // only unstaged once written, so a refused or failed commit can be retried
         let staged_operation = state
-            .take_staged_operation(None)?
+            .get_staged_operation(None)?
             .ok_or_else(|| anyhow::Error::from(SemanticEditError::OperationNotStaged))?;
 
+        // held until the write is done, so no other session commits to the file in between
+        let lock = if dry_run {
+            None
+        } else {
+            Some(CommitLock::acquire(
+                &state.lock_directory(),
+                &staged_operation.file_path,
+            )?)
+        };
+
+        let selector = staged_operation.selector.clone();
+        let staged_fingerprint = staged_operation.source_fingerprint;
+        let path = staged_operation.file_path.display().to_string();
         let editor = state.create_editor_from_operation(staged_operation)?;
-        let (message, output, output_path) = editor.commit()?;
+        if let Some(staged_fingerprint) = staged_fingerprint {
+            let current = editor.fingerprint();
+            if staged_fingerprint.is_stale(&current) {
+                let committer = state.committer(None);
+                if let Some(last_commit) = lock.as_ref().and_then(CommitLock::last_commit) {
+                    if last_commit.content_hash == current.content_hash
+                        && last_commit.committer != committer
+                    {
+                        return Err(anyhow::Error::from(
+                            SemanticEditError::ModifiedByOtherSession {
+                                path,
+                                committer: last_commit.committer,
+                            },
+                        ));
+                    }
+                }
+                return Err(anyhow::Error::from(SemanticEditError::StaleFile { path }));
+            }
+        }
+
+        let encoding = editor.encoding();
+        let write_check = editor.write_check();
+        let (mut report, output) = editor.commit_report()?;
+        report.dry_run = dry_run;
+
+        if dry_run {
+            report.content = output;
+            return render(&report, format);
+        }
 
         if let Some(output) = output {
-            state.file_operations().write_file(output_path, output)?;
+            let output_path = report.edit.file_path.clone();
+            // checked before writing, since committing the file would commit these too
+            let uncommitted = state.git_commit(None)? && git::has_uncommitted_changes(&output_path);
+            report.backup_path = state.backup_file(None, &output_path)?;
+            if state.git_snapshot(None)? {
+                let snapshot = git::snapshot(&output_path).map_err(|error| {
+                    anyhow!(
+                        "{} was not written because its content could not be saved in git \
+                        first: {error}",
+                        output_path.display()
+                    )
+                })?;
+                report.git_snapshot = Some(snapshot);
+            }
+            let content_hash = SourceFingerprint::from_source(&output).content_hash;
+            state
+                .file_operations()
+                .write_encoded(output_path.clone(), output.clone(), encoding)?;
+            state.take_staged_operation(None)?;
+            report.verification =
+                write_check.verify(&output, state.file_operations().read_encoded(&output_path));
+            if let Some(lock) = &lock {
+                lock.record(&LastCommit {
+                    committer: state.committer(None),
+                    content_hash,
+                })?;
+            }
+            state.source_cache().invalidate(&output_path)?;
+            // the written file now includes the unsaved buffer, so stop overlaying it
+            state.set_buffer(None, &output_path, None)?;
+            report.applied = true;
+
+            // the edit is on disk either way, so a failed git commit is only a warning
+            if state.git_commit(None)? {
+                let committed = if uncommitted {
+                    Err(anyhow!(
+                        "{} already had uncommitted changes, which the commit would have \
+                        included; commit or stash them to keep edits in commits of their own",
+                        output_path.display()
+                    ))
+                } else {
+                    git::commit_edit(&output_path, &report.edit, &selector)
+                };
+                match committed {
+                    Ok(git_commit) => report.git_commit = Some(git_commit),
+                    Err(error) => {
+                        let warning = format!(
+                            "⚠️ WARNING: the edit was written but not committed to git: {error}"
+                        );
+                        report.edit.warning = Some(match report.edit.warning.take() {
+                            Some(existing) => format!("{existing}\n\n{warning}"),
+                            None => warning,
+                        });
+                    }
+                }
+            }
         }
 
-        Ok(message)
+        render(&report, format)
     }
 }
+
+/// Write every file of a staged codemod, or none of them
+fn commit_codemod(
+    state: &mut SemanticEditTools,
+    staged_codemod: StagedCodemod,
+    acknowledge_dirty: bool,
+    dry_run: bool,
+    format: ResultFormat,
+) -> Result<String> {
+    let first_path = || {
+        staged_codemod
+            .files
+            .first()
+            .map(|rewrite| rewrite.file_path.display().to_string())
+            .unwrap_or_default()
+    };
+    if !dry_run && state.is_read_only(None)? {
+        return Err(anyhow::Error::from(SemanticEditError::ReadOnly {
+            path: first_path(),
+        }));
+    }
+    if !dry_run && !acknowledge_dirty {
+        if let Some(rewrite) = staged_codemod
+            .files
+            .iter()
+            .find(|rewrite| rewrite.dirty.is_some())
+        {
+            return Err(anyhow::Error::from(
+                SemanticEditError::DirtyFileNotAcknowledged {
+                    path: rewrite.file_path.display().to_string(),
+                    reason: rewrite.dirty.clone().unwrap_or_default(),
+                },
+            ));
+        }
+    }
+
+    let file_operations = state.session_file_operations(None)?;
+    let mut report =
+        CodemodReport::from_staged(&staged_codemod, &file_operations, &Default::default())?;
+    if dry_run {
+        report.dry_run = true;
+        return render(&report, format);
+    }
+
+    // every lock is held until all files are written, and every file is checked before any
+    // is written
+    let locks = CommitLocks::acquire(
+        &state.lock_directory(),
+        staged_codemod
+            .files
+            .iter()
+            .map(|rewrite| rewrite.file_path.as_path()),
+    )?;
+    let mut encodings = Vec::new();
+    for rewrite in &staged_codemod.files {
+        let (encoding, source) = file_operations.read_encoded(&rewrite.file_path)?;
+        let current = SourceFingerprint::from_source(&source);
+        if rewrite.source_fingerprint.is_stale(&current) {
+            let path = rewrite.file_path.display().to_string();
+            let last_commit = locks
+                .get(&rewrite.file_path)
+                .and_then(CommitLock::last_commit);
+            if let Some(last_commit) = last_commit {
+                if last_commit.content_hash == current.content_hash
+                    && last_commit.committer != state.committer(None)
+                {
+                    return Err(anyhow::Error::from(
+                        SemanticEditError::ModifiedByOtherSession {
+                            path,
+                            committer: last_commit.committer,
+                        },
+                    ));
+                }
+            }
+            return Err(anyhow::Error::from(SemanticEditError::StaleFile { path }));
+        }
+        encodings.push(encoding);
+    }
+
+    // checked before writing, since committing the files would commit these too
+    let uncommitted = if state.git_commit(None)? {
+        staged_codemod
+            .files
+            .iter()
+            .filter(|rewrite| git::has_uncommitted_changes(&rewrite.file_path))
+            .map(|rewrite| rewrite.file_path.display().to_string())
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    for (rewrite, file) in staged_codemod.files.iter().zip(&mut report.files) {
+        file.backup_path = state.backup_file(None, &rewrite.file_path)?;
+        if state.git_snapshot(None)? {
+            let snapshot = git::snapshot(&rewrite.file_path).map_err(|error| {
+                anyhow!(
+                    "no files were written because the content of {} could not be saved in \
+                    git first: {error}",
+                    rewrite.file_path.display()
+                )
+            })?;
+            file.git_snapshot = Some(snapshot);
+        }
+    }
+
+    // every file is written or none is: files written before a failed write are restored
+    let mut written = Vec::new();
+    for (rewrite, encoding) in staged_codemod.files.iter().zip(&encodings) {
+        // what is on disk is restored, not any unsaved buffer the codemod was staged against
+        let original = state.file_operations().read_bytes(&rewrite.file_path)?;
+        written.push((rewrite.file_path.clone(), original));
+        if let Err(error) = file_operations.write_encoded(
+            rewrite.file_path.clone(),
+            rewrite.output.clone(),
+            *encoding,
+        ) {
+            let unrestored = written
+                .into_iter()
+                .filter(|(path, original)| {
+                    file_operations
+                        .write_bytes(path.clone(), original.clone())
+                        .is_err()
+                })
+                .map(|(path, _)| path.display().to_string())
+                .collect::<Vec<_>>();
+            let path = rewrite.file_path.display();
+            return Err(if unrestored.is_empty() {
+                anyhow!("no files were changed, because {path} could not be written: {error}")
+            } else {
+                anyhow!(
+                    "{path} could not be written: {error}. These files were changed and could \
+                    not be restored: {}",
+                    unrestored.join(", ")
+                )
+            });
+        }
+    }
+
+    for ((rewrite, encoding), file) in staged_codemod
+        .files
+        .iter()
+        .zip(encodings)
+        .zip(&mut report.files)
+    {
+        if let Some(lock) = locks.get(&rewrite.file_path) {
+            lock.record(&LastCommit {
+                committer: state.committer(None),
+                content_hash: SourceFingerprint::from_source(&rewrite.output).content_hash,
+            })?;
+        }
+        state.source_cache().invalidate(&rewrite.file_path)?;
+        state.set_buffer(None, &rewrite.file_path, None)?;
+        let registry = state.language_registry();
+        if let Some(language) = registry
+            .detect_language_from_path(&rewrite.file_path)
+            .and_then(|name| registry.get_language(name).ok())
+        {
+            file.verification = WriteCheck::new(language, encoding, false).verify(
+                &rewrite.output,
+                state.file_operations().read_encoded(&rewrite.file_path),
+            );
+        }
+    }
+    // only unstaged once written, so a refused commit can be retried
+    state.take_staged_codemod(None)?;
+    report.applied = true;
+
+    // the files are on disk either way, so a failed git commit is only a warning
+    if state.git_commit(None)? {
+        let file_paths = staged_codemod
+            .files
+            .iter()
+            .map(|rewrite| rewrite.file_path.clone())
+            .collect::<Vec<_>>();
+        let matches = staged_codemod
+            .files
+            .iter()
+            .map(|rewrite| rewrite.matches)
+            .sum();
+        let committed = if uncommitted.is_empty() {
+            git::commit_codemod(&file_paths, &staged_codemod.rules, matches)
+        } else {
+            Err(anyhow!(
+                "{} already had uncommitted changes, which the commit would have included; \
+                commit or stash them to keep edits in commits of their own",
+                uncommitted.join(", ")
+            ))
+        };
+        match committed {
+            Ok(git_commit) => report.git_commit = Some(git_commit),
+            Err(error) => {
+                report.warning = Some(format!(
+                    "⚠️ WARNING: the files were written but not committed to git: {error}"
+                ));
+            }
+        }
+    }
+
+    render(&report, format)
+}
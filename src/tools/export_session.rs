@@ -0,0 +1,58 @@
+//! Export session tool for saving a session's state.
+//!
+//! This module implements the `export_session` MCP tool which returns the current
+//! session's state as JSON, to be passed to `import_session` later. Features include:
+//! - Context path, staged operation, and (optionally) unsaved buffers
+//! - Per-session settings (backup strategy, format mode, validation level, rules, read-only)
+//! - A format version, so exports from incompatible servers are refused on import
+
+use crate::state::SemanticEditTools;
+use anyhow::Result;
+use mcplease::traits::{Tool, WithExamples};
+use mcplease::types::Example;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Export the current session's context, staged operation, unsaved buffers, and settings
+/// as JSON. Pass the result to import_session to resume after a server restart or on
+/// another machine.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "export_session")]
+pub struct ExportSession {
+    /// Whether to include unsaved editor buffers, which can be large. Defaults to true.
+    #[serde(default = "default_include_buffers")]
+    pub include_buffers: bool,
+}
+
+fn default_include_buffers() -> bool {
+    true
+}
+
+impl WithExamples for ExportSession {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Save the session before the server is restarted",
+                item: Self {
+                    include_buffers: true,
+                },
+            },
+            Example {
+                description: "Save the session without unsaved editor buffers",
+                item: Self {
+                    include_buffers: false,
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<SemanticEditTools> for ExportSession {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let mut export = state.export_session(None)?;
+        if !self.include_buffers {
+            export.session.buffers.clear();
+        }
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
+}
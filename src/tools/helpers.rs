@@ -6,33 +6,71 @@
 //! - Editor creation from staged operations
 //! - Shared validation and error handling
 //! - Centralized operation management patterns
+//! - Rendering reports as text or JSON
 
-use crate::editor::Editor;
+use crate::editor::{Editor, ResultFormat};
 use crate::error::SemanticEditError;
 use crate::state::{SemanticEditTools, StagedOperation};
 use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Render a report in the requested result format
+pub fn render<T>(report: &T, format: ResultFormat) -> Result<String>
+where
+    T: Serialize + Display,
+{
+    match format {
+        ResultFormat::Text => Ok(report.to_string()),
+        ResultFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+    }
+}
 
 /// Helper trait providing common functionality across tools
 pub trait ToolHelpers {
     /// Create an Editor from a staged operation, centralizing the common pattern
     /// used in commit_staged.rs and retarget_staged.rs
-    fn create_editor_from_staged(&mut self, session_id: Option<&str>) -> Result<Editor>;
+    fn create_editor_from_staged(&mut self, session_id: Option<&str>) -> Result<Editor<'_>>;
 
     /// Create an Editor from a taken staged operation
-    fn create_editor_from_operation(&self, staged_operation: StagedOperation) -> Result<Editor>;
+    fn create_editor_from_operation(&self, staged_operation: StagedOperation)
+    -> Result<Editor<'_>>;
 }
 
 impl ToolHelpers for SemanticEditTools {
-    fn create_editor_from_staged(&mut self, session_id: Option<&str>) -> Result<Editor> {
+    fn create_editor_from_staged(&mut self, session_id: Option<&str>) -> Result<Editor<'_>> {
         let staged_operation = self
             .get_staged_operation(session_id)?
             .ok_or_else(|| anyhow::Error::from(SemanticEditError::OperationNotStaged))?;
 
-        Editor::from_staged_operation(staged_operation, self.language_registry())
+        let file_operations = self.session_file_operations(session_id)?;
+        Ok(Editor::from_staged_operation(
+            staged_operation,
+            self.language_registry(),
+            &file_operations,
+            Some(self.source_cache()),
+        )?
+        .with_format_limits(self.format_limits())
+        .with_parse_limits(self.parse_limits())
+        .with_progress(self.progress().clone())
+        .with_custom_queries(self.custom_queries(session_id)?))
     }
 
-    fn create_editor_from_operation(&self, staged_operation: StagedOperation) -> Result<Editor> {
-        Editor::from_staged_operation(staged_operation, self.language_registry())
+    fn create_editor_from_operation(
+        &self,
+        staged_operation: StagedOperation,
+    ) -> Result<Editor<'_>> {
+        let file_operations = self.session_file_operations(None)?;
+        Ok(Editor::from_staged_operation(
+            staged_operation,
+            self.language_registry(),
+            &file_operations,
+            Some(self.source_cache()),
+        )?
+        .with_format_limits(self.format_limits())
+        .with_parse_limits(self.parse_limits())
+        .with_progress(self.progress().clone())
+        .with_custom_queries(self.custom_queries(None)?))
     }
 }
 
@@ -63,15 +101,12 @@ mod tests {
     fn create_editor_from_operation_creates_editor_successfully() -> Result<()> {
         use crate::selector::{Operation, Selector};
         use crate::state::StagedOperation;
-        use std::io::Write;
-        use tempfile::NamedTempFile;
-
-        let state = create_test_state()?;
+        use std::path::PathBuf;
 
-        // Create a temporary file with test content
-        let mut temp_file = NamedTempFile::new()?;
-        writeln!(temp_file, "fn test() {{}}")?;
-        let test_path = temp_file.path().to_path_buf();
+        // Create an in-memory file with test content
+        let test_path = PathBuf::from("test.rs");
+        let file_ops = TestFileOperations::new().with_file(&test_path, "fn test() {}\n");
+        let state = SemanticEditTools::with_file_operations(None, Box::new(file_ops))?;
 
         let language = state
             .language_registry()
@@ -82,11 +117,23 @@ mod tests {
                 anchor: "fn test".to_string(),
                 operation: Operation::InsertAfter,
                 end: None,
+                end_inclusive: true,
+                include_leading_trivia: false,
             },
             content: "\n    println!(\"Added!\");".to_string(),
             file_path: test_path,
             language_name: language.name(),
             edit_position: None,
+            source_fingerprint: None,
+            diff_options: Default::default(),
+            format_mode: Default::default(),
+            validation_level: Default::default(),
+            rules: Default::default(),
+            dirty: None,
+            cell: None,
+            candidate: None,
+            target: None,
+            duplicate: None,
         };
 
         let editor = state.create_editor_from_operation(staged_op)?;
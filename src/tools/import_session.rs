@@ -0,0 +1,79 @@
+//! Import session tool for resuming an exported session.
+//!
+//! This module implements the `import_session` MCP tool which replaces the current
+//! session's state with the output of `export_session`. Features include:
+//! - Restores the context, staged operation, unsaved buffers, and settings
+//! - Optional new context path, moving the staged operation and buffers under it
+//! - Refuses exports from incompatible servers and paths outside the sandbox
+//! - Reloads project validation queries for the restored context
+
+use std::path::PathBuf;
+
+use crate::state::{SemanticEditTools, SessionExport};
+use anyhow::Result;
+use mcplease::traits::{Tool, WithExamples};
+use mcplease::types::Example;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Replace the current session's state with the output of export_session
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "import_session")]
+pub struct ImportSession {
+    /// The JSON returned by export_session
+    pub session: String,
+
+    /// Optional directory to use as context instead of the exported one, e.g. where the
+    /// project is checked out on this machine. The staged operation and unsaved buffers
+    /// are moved along with it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl WithExamples for ImportSession {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Resume a session after the server restarted",
+                item: Self {
+                    session: r#"{"version": 1, "session": {"context_path": "/usr/local/projects/cobol", "staged_operation": null}}"#.into(),
+                    path: None,
+                },
+            },
+            Example {
+                description: "Resume a session in a checkout at a different path",
+                item: Self {
+                    session: r#"{"version": 1, "session": {"context_path": "/home/grace/cobol", "staged_operation": null}}"#.into(),
+                    path: Some("/usr/local/projects/cobol".into()),
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<SemanticEditTools> for ImportSession {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let Self { session, path } = self;
+        let export: SessionExport = serde_json::from_str(&session)?;
+        let path = path.map(|path| PathBuf::from(&*shellexpand::tilde(&path)));
+        state.import_session(None, export, path)?;
+
+        let mut response = match state.get_context(None)? {
+            Some(context) => format!("Imported session with context {}.\n", context.display()),
+            None => "Imported session without a context.\n".to_string(),
+        };
+        state.load_custom_queries(None)?;
+        if let Err(error) = state.watch_context(None) {
+            response.push_str(&format!(
+                "File watching is unavailable ({error}); cached files are still checked for changes before use.\n"
+            ));
+        }
+        if let Some(staged_operation) = state.get_staged_operation(None)? {
+            response.push_str(&format!(
+                "An operation on {} is staged. Use view_staged_diff to review it before committing.\n",
+                staged_operation.file_path.display()
+            ));
+        }
+        Ok(response)
+    }
+}
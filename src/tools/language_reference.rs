@@ -0,0 +1,86 @@
+//! Language reference tool for listing a grammar's node kinds and fields.
+//!
+//! This module implements the `language_reference` MCP tool which lists the node kinds and
+//! field names of the tree-sitter grammar the server parses a language with. Features include:
+//! - Named and anonymous node kinds, and supertypes
+//! - Field names
+//! - The built-in and custom validation queries that refer to each kind and field
+//! - Filtering to the kinds and fields some query refers to
+//! - Output as text or structured JSON
+
+use crate::{
+    editor::ResultFormat, grammar::GrammarReference, languages::LanguageName,
+    state::SemanticEditTools, tools::helpers::render,
+};
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// List the node kinds and field names of a language's tree-sitter grammar, and which
+/// validation queries refer to them. Use it to write query selectors, codemod rules, and
+/// custom validation queries against the grammar version this server actually parses with.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "language_reference")]
+pub struct LanguageReference {
+    /// The language whose grammar to list
+    pub language: LanguageName,
+
+    /// Only list the node kinds and fields that a built-in or custom validation query refers
+    /// to. Defaults to false.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub referenced_only: bool,
+
+    /// Result format: `text` (default) for a readable list, or `json` for structured node
+    /// kinds and fields
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
+}
+
+impl WithExamples for LanguageReference {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Look up the node kinds and fields of the Rust grammar before \
+                              writing a custom validation query",
+                item: Self {
+                    language: LanguageName::Rust,
+                    referenced_only: false,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "See which Python node kinds and fields the validation queries \
+                              already check",
+                item: Self {
+                    language: LanguageName::Python,
+                    referenced_only: true,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Get the TypeScript grammar as JSON to check a kind name in a script",
+                item: Self {
+                    language: LanguageName::Typescript,
+                    referenced_only: false,
+                    format: ResultFormat::Json,
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<SemanticEditTools> for LanguageReference {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let custom_queries = state.custom_queries(None)?;
+        let language = state.language_registry().get_language(self.language)?;
+        let mut reference = GrammarReference::new(language, custom_queries.as_deref());
+        if self.referenced_only {
+            reference = reference.referenced_only();
+        }
+        render(&reference, self.format)
+    }
+}
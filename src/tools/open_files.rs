@@ -7,26 +7,39 @@
 //! - Language detection and caching
 //! - Performance metrics and hashing
 //! - Support for both absolute and relative paths
+//! - Unsaved buffers staged with stage_operation are shown in place of the on-disk content
+//! - Partial reads of a line range or a named symbol's span, with a window of context lines
+//! - Paginated, head, and tail reads of large files, reporting how many lines were not shown
+//! - An optional outline of each file's definitions, as a map for navigating it
+//! - Glob patterns like `src/**/*.rs`, honoring `.gitignore` and capped in file count and size
 
 use crate::error::SemanticEditError;
+use crate::filesystem::FileOperations;
 use crate::languages::LanguageName;
+use crate::outline::Outline;
 use crate::state::SemanticEditTools;
 use anyhow::{Result, anyhow};
 use diffy::{DiffOptions, PatchFormatter};
+use globset::GlobBuilder;
+use ignore::WalkBuilder;
 use mcplease::traits::WithExamples;
 use mcplease::types::Example;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Open files for semantic editing
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename = "open_files")]
 pub struct OpenFiles {
     /// Array of file paths to open. Can be a single file or multiple files.
-    /// Each file path may be either absolute or — if session_id is present — relative to the session.
+    /// Each file path may be either absolute or — if session_id is present — relative to the session,
+    /// or `name:relative/path` for a workspace root registered with set_context.
+    /// Glob patterns like `src/**/*.rs` open every matching file not ignored by `.gitignore`,
+    /// up to `max_files` files and `max_bytes` in total.
     file_paths: Vec<String>,
 
     /// Optional language hint. If provided, all files will be parsed as this language type. If not provided, language will be detected from file extensions.
@@ -40,6 +53,84 @@ pub struct OpenFiles {
     /// Optional session identifier
     #[serde(skip_serializing_if = "Option::is_none")]
     session_id: Option<String>,
+
+    /// Optional 1-based, inclusive line range to return instead of the whole file,
+    /// e.g. `120-180` or `42`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<String>,
+
+    /// Optional name of a definition (function, type, constant, field...) to return instead
+    /// of the whole file. Every definition with this name is returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+
+    /// Lines of surrounding context to include around `lines` or `symbol`.
+    /// Defaults to 3 for symbols and 0 for line ranges.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context_lines: Option<usize>,
+
+    /// Optional 1-based line to start a page at, for reading large files in pages.
+    /// Each page reports the offset to continue from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+
+    /// Optional maximum number of lines in a page, starting at `offset` (or line 1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_lines: Option<usize>,
+
+    /// Optional number of lines to return from the start of each file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    head: Option<usize>,
+
+    /// Optional number of lines to return from the end of each file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tail: Option<usize>,
+
+    /// Append an outline of each file's definitions with the lines they span, to find your
+    /// way around a file or pick a `symbol` or `lines` to read next
+    #[serde(default)]
+    outline: bool,
+
+    /// Maximum number of files to open from glob patterns. Defaults to 50.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_files: Option<usize>,
+
+    /// Maximum total size in bytes of the files opened from glob patterns. Defaults to 1 MB.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_bytes: Option<u64>,
+}
+
+/// Files opened from glob patterns unless `max_files` is given
+const DEFAULT_GLOB_MAX_FILES: usize = 50;
+
+/// Total size of the files opened from glob patterns unless `max_bytes` is given
+const DEFAULT_GLOB_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Context lines shown around a symbol unless `context_lines` is given
+const DEFAULT_SYMBOL_CONTEXT_LINES: usize = 3;
+
+/// Part of a file requested instead of the whole file
+#[derive(Debug, Clone, Copy)]
+enum Selection<'a> {
+    Lines {
+        start: usize,
+        end: usize,
+    },
+    Symbol(&'a str),
+    Page {
+        offset: usize,
+        max_lines: Option<usize>,
+    },
+    Head(usize),
+    Tail(usize),
+}
+
+/// A 1-based, inclusive range of lines to show, with what it contains
+#[derive(Debug)]
+struct Excerpt {
+    label: String,
+    start: usize,
+    end: usize,
 }
 
 impl WithExamples for OpenFiles {
@@ -52,6 +143,16 @@ impl WithExamples for OpenFiles {
                     language: None,
                     diff_since: None,
                     session_id: None,
+                    lines: None,
+                    symbol: None,
+                    context_lines: None,
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
                 },
             },
             Example {
@@ -65,6 +166,16 @@ impl WithExamples for OpenFiles {
                     language: None,
                     diff_since: None,
                     session_id: Some("app-name/feature-name".into()),
+                    lines: None,
+                    symbol: None,
+                    context_lines: None,
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
                 },
             },
             Example {
@@ -77,6 +188,111 @@ impl WithExamples for OpenFiles {
                     language: Some(LanguageName::Json),
                     diff_since: None,
                     session_id: None,
+                    lines: None,
+                    symbol: None,
+                    context_lines: None,
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
+                },
+            },
+            Example {
+                description: "Open every Rust file of a module, skipping ignored files",
+                item: Self {
+                    file_paths: vec!["src/parser/**/*.rs".into()],
+                    language: None,
+                    diff_since: None,
+                    session_id: None,
+                    lines: None,
+                    symbol: None,
+                    context_lines: None,
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: Some(20),
+                    max_bytes: None,
+                },
+            },
+            Example {
+                description: "Read only lines 120 to 180 of a large file",
+                item: Self {
+                    file_paths: vec!["src/parser.rs".into()],
+                    language: None,
+                    diff_since: None,
+                    session_id: None,
+                    lines: Some("120-180".into()),
+                    symbol: None,
+                    context_lines: None,
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
+                },
+            },
+            Example {
+                description: "Read only the definition of a function, with 10 lines around it",
+                item: Self {
+                    file_paths: vec!["src/parser.rs".into()],
+                    language: None,
+                    diff_since: None,
+                    session_id: None,
+                    lines: None,
+                    symbol: Some("parse_expression".into()),
+                    context_lines: Some(10),
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
+                },
+            },
+            Example {
+                description: "Read the second page of 500 lines of a very large file",
+                item: Self {
+                    file_paths: vec!["src/generated.rs".into()],
+                    language: None,
+                    diff_since: None,
+                    session_id: None,
+                    lines: None,
+                    symbol: None,
+                    context_lines: None,
+                    offset: Some(501),
+                    max_lines: Some(500),
+                    head: None,
+                    tail: None,
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
+                },
+            },
+            Example {
+                description: "Read the last 50 lines of a log",
+                item: Self {
+                    file_paths: vec!["logs/server.log".into()],
+                    language: None,
+                    diff_since: None,
+                    session_id: None,
+                    lines: None,
+                    symbol: None,
+                    context_lines: None,
+                    offset: None,
+                    max_lines: None,
+                    head: None,
+                    tail: Some(50),
+                    outline: false,
+                    max_files: None,
+                    max_bytes: None,
                 },
             },
         ]
@@ -90,33 +306,105 @@ impl OpenFiles {
             language,
             diff_since,
             session_id,
+            lines,
+            symbol,
+            context_lines,
+            offset,
+            max_lines,
+            head,
+            tail,
+            outline,
+            max_files,
+            max_bytes,
         } = self;
 
         if file_paths.is_empty() {
             return Err(anyhow!("file_paths array cannot be empty"));
         }
 
-        // Validate diff usage
-        if diff_since.is_some() && file_paths.len() > 1 {
+        let paged = offset.is_some() || max_lines.is_some();
+        let modes = [
+            lines.is_some(),
+            symbol.is_some(),
+            paged,
+            head.is_some(),
+            tail.is_some(),
+        ];
+        if modes.into_iter().filter(|mode| *mode).count() > 1 {
             return Err(anyhow!(
-                "diff_since is not supported when opening multiple files. \
-                Please open files individually to use diff tracking, or omit diff_since to open all files fresh."
+                "only one of lines, symbol, offset/max_lines, head, or tail can be given. Provide one of them, or none to open whole files."
             ));
         }
+        if [offset, max_lines, head, tail].contains(&Some(0)) {
+            return Err(anyhow!(
+                "offset, max_lines, head, and tail must be at least 1"
+            ));
+        }
+        let selection = if let Some(lines) = &lines {
+            Some(parse_lines(lines)?)
+        } else if let Some(symbol) = &symbol {
+            Some(Selection::Symbol(symbol))
+        } else if paged {
+            Some(Selection::Page {
+                offset: offset.unwrap_or(1),
+                max_lines,
+            })
+        } else {
+            head.map(Selection::Head).or(tail.map(Selection::Tail))
+        };
 
         let mut response_parts = Vec::new();
         let mut hasher = DefaultHasher::new();
 
-        let file_paths = file_paths
-            .into_iter()
-            .map(|path_str| state.resolve_path(&path_str, session_id.as_deref()))
-            .collect::<Result<Vec<_>, _>>()?;
+        let max_files = max_files.unwrap_or(DEFAULT_GLOB_MAX_FILES);
+        let max_bytes = max_bytes.unwrap_or(DEFAULT_GLOB_MAX_BYTES);
+        let (mut glob_files, mut glob_bytes) = (0, 0);
+        let mut omitted = Vec::new();
+        let mut resolved = Vec::new();
+        for path_str in file_paths {
+            if !is_glob(&path_str) {
+                resolved.push(state.resolve_path(&path_str, session_id.as_deref())?);
+                continue;
+            }
+            let matches = expand_glob(state, &path_str, session_id.as_deref())?;
+            if matches.is_empty() {
+                return Err(anyhow!(
+                    "no files match `{path_str}`. Files ignored by .gitignore are not matched."
+                ));
+            }
+            for path in matches {
+                if resolved.contains(&path) {
+                    continue;
+                }
+                let bytes = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+                if glob_files >= max_files || glob_bytes + bytes > max_bytes {
+                    omitted.push(path);
+                    continue;
+                }
+                glob_files += 1;
+                glob_bytes += bytes;
+                resolved.push(path);
+            }
+        }
+        let file_paths = resolved;
 
+        // Validate diff usage
+        if diff_since.is_some() && file_paths.len() > 1 {
+            return Err(anyhow!(
+                "diff_since is not supported when opening multiple files. \
+                Please open files individually to use diff tracking, or omit diff_since to open all files fresh."
+            ));
+        }
+
+        let progress = state.progress().clone();
+        let total = file_paths.len() as u64;
+        let file_operations = state.session_file_operations(session_id.as_deref())?;
         let mut contents = vec![];
         for file_path in &file_paths {
+            progress.check_cancelled()?;
             // Check for diff request first
             if let Some(since) = &diff_since {
-                let current_content = std::fs::read_to_string(file_path)?;
+                let (_, current_content) = file_operations.read_encoded(file_path)?;
 
                 let cache_key = format!("{}#{}", file_path.display(), since);
                 if let Some(earlier_content) = state
@@ -134,7 +422,7 @@ impl OpenFiles {
                 }
             }
 
-            let content = std::fs::read_to_string(file_path)?;
+            let (_, content) = file_operations.read_encoded(file_path)?;
             content.hash(&mut hasher);
             contents.push((content, file_path.clone()));
         }
@@ -143,12 +431,20 @@ impl OpenFiles {
         let separator = format!("{:010x}", hash % 0x10000000000); // 10 hex chars
 
         for (content, file_path) in contents {
+            progress.check_cancelled()?;
             let language = state
                 .language_registry()
                 .get_language_with_hint(&file_path, language);
 
-            let file_response =
-                generate_file_response(&file_path, &content, &separator, language.ok())?;
+            let file_response = generate_file_response(
+                &file_path,
+                &content,
+                &separator,
+                language.ok(),
+                selection,
+                context_lines,
+                outline,
+            )?;
             response_parts.push(file_response);
 
             // Cache the content for future diff requests
@@ -159,17 +455,95 @@ impl OpenFiles {
                 .lock()
                 .map_err(|_| anyhow::Error::from(SemanticEditError::FileCachePoisoned))?
                 .put(cache_key, content);
+            progress.advance(Some(total), format!("Opened {}", file_path.display()));
         }
 
-        let response = format!(
-            "Separator/version identifier: {}\n\n{}",
-            separator,
-            response_parts.join("\n\n\n")
-        );
+        let mut response = format!("Separator/version identifier: {separator}\n\n");
+        if !omitted.is_empty() {
+            let omitted = omitted
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+            response.push_str(&format!(
+                "Opened {glob_files} files matching glob patterns. {} more matched but were not opened \
+                because of the max_files ({max_files}) or max_bytes ({max_bytes}) limit: {}\n\n",
+                omitted.len(),
+                omitted.join(", ")
+            ));
+        }
+        response.push_str(&response_parts.join("\n\n\n"));
         Ok(response)
     }
 }
 
+pub(crate) fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '[', '{'])
+}
+
+/// Files named by paths or glob patterns, each once, in the order they are named
+pub(crate) fn expand_paths(
+    state: &SemanticEditTools,
+    patterns: &[String],
+    session_id: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut seen = HashSet::new();
+    for pattern in patterns {
+        let matched = if is_glob(pattern) {
+            expand_glob(state, pattern, session_id)?
+        } else {
+            vec![state.resolve_path(pattern, session_id)?]
+        };
+        paths.extend(matched.into_iter().filter(|path| seen.insert(path.clone())));
+    }
+    Ok(paths)
+}
+
+/// Files matching a glob pattern, in path order. The pattern's literal leading directories
+/// are resolved like any other path, and files ignored by `.gitignore`, `.ignore`, or for
+/// being hidden are skipped.
+pub(crate) fn expand_glob(
+    state: &SemanticEditTools,
+    pattern: &str,
+    session_id: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    // split after the last separator that precedes any glob characters, where a root
+    // prefix like `frontend:` counts as a separator
+    let split = pattern
+        .match_indices(['/', ':'])
+        .map(|(index, _)| index + 1)
+        .take_while(|&split| !is_glob(&pattern[..split]))
+        .last()
+        .unwrap_or(0);
+    let (base, glob) = pattern.split_at(split);
+    let base = state.resolve_path(if base.is_empty() { "." } else { base }, session_id)?;
+    let matcher = GlobBuilder::new(glob)
+        .literal_separator(true)
+        .build()
+        .map_err(|error| anyhow!("invalid glob pattern `{pattern}`: {error}"))?
+        .compile_matcher();
+
+    let mut paths = Vec::new();
+    for entry in WalkBuilder::new(&base).require_git(false).build() {
+        let entry = entry?;
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        if entry
+            .path()
+            .strip_prefix(&base)
+            .is_ok_and(|relative| matcher.is_match(relative))
+        {
+            paths.push(entry.into_path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
 fn handle_diff_request(file_path: &Path, current: &str, earlier: &str, since: &str) -> String {
     let new_identifier = hash_content(current);
     let mut diff_options = DiffOptions::new();
@@ -191,23 +565,199 @@ fn handle_diff_request(file_path: &Path, current: &str, earlier: &str, since: &s
     )
 }
 
+/// Parse a line range like `120-180` or `42`
+fn parse_lines(lines: &str) -> Result<Selection<'static>> {
+    let invalid = || {
+        anyhow!(
+            "invalid line range `{lines}`. Use a 1-based, inclusive range like `120-180`, or a single line like `42`."
+        )
+    };
+    let (start, end) = lines.split_once('-').unwrap_or((lines, lines));
+    let start = start.trim().parse::<usize>().map_err(|_| invalid())?;
+    let end = end.trim().parse::<usize>().map_err(|_| invalid())?;
+    if start == 0 || end < start {
+        return Err(invalid());
+    }
+    Ok(Selection::Lines { start, end })
+}
+
+/// The excerpts of a file that a selection asks for
+fn select_excerpts(
+    file_path: &Path,
+    contents: &str,
+    outline: Option<&Outline>,
+    selection: Selection,
+    context_lines: Option<usize>,
+) -> Result<Vec<Excerpt>> {
+    let total = contents.lines().count().max(1);
+    let excerpts = match selection {
+        Selection::Lines { start, .. } | Selection::Page { offset: start, .. } if start > total => {
+            return Err(anyhow!(
+                "line {start} is past the end of {} ({total} lines)",
+                file_path.display()
+            ));
+        }
+        Selection::Lines { start, end } => vec![Excerpt {
+            label: format!("lines {start}-{end}"),
+            start,
+            end,
+        }],
+        Selection::Page { offset, max_lines } => vec![Excerpt {
+            label: "page".into(),
+            start: offset,
+            end: max_lines.map_or(total, |max_lines| offset + max_lines - 1),
+        }],
+        Selection::Head(lines) => vec![Excerpt {
+            label: format!("first {lines} lines"),
+            start: 1,
+            end: lines,
+        }],
+        Selection::Tail(lines) => vec![Excerpt {
+            label: format!("last {lines} lines"),
+            start: total.saturating_sub(lines) + 1,
+            end: total,
+        }],
+        Selection::Symbol(symbol) => {
+            let outline = outline.ok_or_else(|| {
+                anyhow!(
+                    "symbol lookup is not supported for {}. Use lines instead.",
+                    file_path.display()
+                )
+            })?;
+            let excerpts = outline
+                .find(symbol)
+                .map(|entry| Excerpt {
+                    label: format!("{} `{symbol}`", entry.kind),
+                    start: entry.start_line,
+                    end: entry.end_line,
+                })
+                .collect::<Vec<_>>();
+            if excerpts.is_empty() {
+                let mut names = outline
+                    .entries()
+                    .iter()
+                    .map(|entry| entry.name.as_str())
+                    .collect::<Vec<_>>();
+                names.sort_unstable();
+                names.dedup();
+                return Err(anyhow!(
+                    "no definition named `{symbol}` was found in {}. Definitions in this file: {}",
+                    file_path.display(),
+                    names.join(", ")
+                ));
+            }
+            excerpts
+        }
+    };
+
+    let context_lines = match selection {
+        Selection::Lines { .. } => context_lines.unwrap_or(0),
+        Selection::Symbol(_) => context_lines.unwrap_or(DEFAULT_SYMBOL_CONTEXT_LINES),
+        Selection::Page { .. } | Selection::Head(_) | Selection::Tail(_) => 0,
+    };
+    Ok(excerpts
+        .into_iter()
+        .map(|excerpt| Excerpt {
+            start: excerpt.start.saturating_sub(context_lines).max(1),
+            end: (excerpt.end + context_lines).min(total),
+            ..excerpt
+        })
+        .collect())
+}
+
 fn generate_file_response(
     file_path: &Path,
     contents: &str,
     separator: &str,
     language: Option<&crate::languages::LanguageCommon>,
+    selection: Option<Selection>,
+    context_lines: Option<usize>,
+    include_outline: bool,
 ) -> Result<String> {
     let eq = "=".repeat(10);
-    let (syntax_section, docs_section) = if let Some(language) = language {
-        let mut parser = language.tree_sitter_parser()?;
-        let tree = parser.parse(contents, None).ok_or_else(|| {
-            anyhow!(
-                "could not parse {} as {}",
-                file_path.display(),
-                language.name()
-            )
-        })?;
+    let needs_tree =
+        selection.is_none() || matches!(selection, Some(Selection::Symbol(_))) || include_outline;
+    let tree = match language {
+        Some(language) if needs_tree => {
+            let mut parser = language.parser()?;
+            Some(parser.parse(contents, None).ok_or_else(|| {
+                anyhow!(
+                    "could not parse {} as {}",
+                    file_path.display(),
+                    language.name()
+                )
+            })?)
+        }
+        _ => None,
+    };
+    let outline = language
+        .zip(tree.as_ref())
+        .and_then(|(language, tree)| Outline::new(language, tree, contents));
+    let outline_section = if include_outline {
+        let outline = match &outline {
+            Some(outline) if outline.is_empty() => "No definitions found.\n".to_string(),
+            Some(outline) => outline.to_string(),
+            None => "Outlines are not available for this file type.\n".to_string(),
+        };
+        format!(
+            "{eq}{separator} {file_path} OUTLINE {separator}{eq}\n{outline}",
+            file_path = file_path.display()
+        )
+    } else {
+        String::new()
+    };
+
+    if let Some(selection) = selection {
+        // partial reads leave out the syntax tree, which covers the whole file
+        let excerpts = select_excerpts(
+            file_path,
+            contents,
+            outline.as_ref(),
+            selection,
+            context_lines,
+        )?;
+        let total = contents.lines().count();
+        let sections = excerpts
+            .into_iter()
+            .map(|Excerpt { label, start, end }| {
+                let text = contents
+                    .lines()
+                    .skip(start - 1)
+                    .take(end + 1 - start)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                // symbols are read whole, so only report what other selections left unseen
+                let mut unseen = String::new();
+                if !matches!(selection, Selection::Symbol(_)) {
+                    if start > 1 {
+                        unseen.push_str(&format!("{} earlier lines are not shown.\n", start - 1));
+                    }
+                    if end < total {
+                        unseen.push_str(&format!(
+                            "{} more lines follow. To read on, use \"offset\": {}\n",
+                            total - end,
+                            end + 1
+                        ));
+                    }
+                }
+                format!(
+                    "{eq}{separator} {file_path} LINES {start}-{end} OF {total}: {label} {separator}{eq}\n{text}\n{unseen}",
+                    file_path = file_path.display()
+                )
+            })
+            .collect::<String>();
+        return Ok(format!(
+            "{eq}{separator} {file_path} META {separator}{eq}\n\
+             To fetch changed content for this file, use {{\"tool\": \"open_files\", \"file_path\":\
+             \"{file_path}\", \"diff_since\": \"{separator}\"}}\n\
+             {sections}\
+             {outline_section}\
+             {eq}{separator} {file_path} END {separator}{eq}",
+            file_path = file_path.display()
+        ));
+    }
 
+    let (syntax_section, docs_section) = if let Some((language, tree)) = language.zip(tree) {
         let language_docs = language.docs();
         let tree_str = tree.root_node().to_sexp();
         (
@@ -228,6 +778,7 @@ fn generate_file_response(
          \"{file_path}\", \"diff_since\": \"{separator}\"}}\n\
          {eq}{separator} {file_path} CONTENTS {separator}{eq}\n{contents}\n\
          {syntax_section}\
+         {outline_section}\
          {eq}{separator} {file_path} END {separator}{eq}",
         eq = "=".repeat(10),
         file_path = file_path.display()
@@ -0,0 +1,164 @@
+//! Organize imports tool for sorting and grouping import statements.
+//!
+//! This module implements the `organize_imports` MCP tool which sorts and groups the
+//! top-level imports of Rust, Python, JavaScript, TypeScript, Go, and Java files, and stages
+//! the rewrites as one codemod for commit_staged. Features include:
+//! - Files chosen by paths and glob patterns, honoring `.gitignore`
+//! - Conventional groups per language, see [`crate::imports`]
+//! - A diff of each rewritten file
+//! - Files in other languages, or whose organized imports would not parse, are left out
+//!   with the reason
+//! - Warnings for files with uncommitted git changes or conflict markers
+//! - Replaces any staged operation or codemod, unless nothing was rewritten
+//! - Text or structured JSON results
+
+use std::path::PathBuf;
+
+use crate::editor::{DiffOptions, ResultFormat};
+use crate::filesystem::FileOperations;
+use crate::git;
+use crate::imports;
+use crate::state::{SemanticEditTools, SourceFingerprint, StagedCodemod, StagedRewrite};
+use crate::tools::CodemodReport;
+use crate::tools::helpers::render;
+use crate::tools::open_files::{expand_paths, is_glob};
+use crate::tools::run_codemod::{CodemodFile, SkippedFile};
+use anyhow::Result;
+use mcplease::traits::{Tool, WithExamples};
+use mcplease::types::Example;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The name organize_imports rewrites are staged and reported under
+const CODEMOD_NAME: &str = "organize_imports";
+
+/// Sort and group the imports at the top of files, staging the rewrites for review
+///
+/// Imports are grouped by where they come from, like the standard library, other packages,
+/// and the project itself, sorted within each group, and deduplicated. Imports next to
+/// comments or attributes, and side-effect imports, are left in place. Review the diffs,
+/// then write every file with commit_staged.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "organize_imports")]
+pub struct OrganizeImports {
+    /// Files to organize: paths, or glob patterns like `src/**/*.py` that skip files ignored
+    /// by `.gitignore`. Files in languages without import organization are skipped.
+    pub file_paths: Vec<String>,
+
+    /// Optional diff rendering overrides
+    #[serde(default, skip_serializing_if = "DiffOptions::is_default")]
+    pub diff_options: DiffOptions,
+
+    /// Result format: `text` (default) for a human-readable preview, or `json` for a
+    /// structured report with each file's diff hunks
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
+}
+
+impl WithExamples for OrganizeImports {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Sort the imports of a file after adding one",
+                item: Self {
+                    file_paths: vec!["src/handlers.py".into()],
+                    diff_options: DiffOptions::default(),
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Organize the imports of every TypeScript file under src before \
+                              a lint check",
+                item: Self {
+                    file_paths: vec!["src/**/*.ts".into(), "src/**/*.tsx".into()],
+                    diff_options: DiffOptions::default(),
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Organize the imports of two Go files, as a structured report",
+                item: Self {
+                    file_paths: vec!["cmd/server/main.go".into(), "internal/store/db.go".into()],
+                    diff_options: DiffOptions::default(),
+                    format: ResultFormat::Json,
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<SemanticEditTools> for OrganizeImports {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let Self {
+            file_paths,
+            diff_options,
+            format,
+        } = self;
+
+        let named = file_paths
+            .iter()
+            .filter(|pattern| !is_glob(pattern))
+            .map(|path| state.resolve_path(path, None))
+            .collect::<Result<Vec<PathBuf>>>()?;
+        let paths = expand_paths(state, &file_paths, None)?;
+
+        let mut report = CodemodReport {
+            rules: vec![CODEMOD_NAME.to_string()],
+            ..CodemodReport::default()
+        };
+        let mut staged_files = Vec::new();
+        let file_operations = state.session_file_operations(None)?;
+        for path in paths {
+            let language = state
+                .language_registry()
+                .detect_language_from_path(&path)
+                .and_then(|name| state.language_registry().get_language(name).ok())
+                .filter(|language| imports::supports(language));
+            let Some(language) = language else {
+                if named.contains(&path) {
+                    report.skipped.push(SkippedFile {
+                        file_path: path,
+                        reason: "organizing imports is not supported for its language".into(),
+                    });
+                }
+                continue;
+            };
+            let (_, source) = file_operations.read_encoded(&path)?;
+            let organized = match imports::organize_imports(language, &source) {
+                Ok(Some(organized)) => organized,
+                Ok(None) => continue,
+                Err(error) => {
+                    report.skipped.push(SkippedFile {
+                        file_path: path,
+                        reason: error.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let staged_rewrite = StagedRewrite {
+                source_fingerprint: SourceFingerprint::new(&path, &source),
+                matches: organized.runs,
+                output: organized.output,
+                dirty: git::dirty_reason(&path, &source),
+                file_path: path,
+            };
+            report
+                .files
+                .push(CodemodFile::new(&staged_rewrite, &source, &diff_options));
+            staged_files.push(staged_rewrite);
+        }
+
+        if !staged_files.is_empty() {
+            state.stage_codemod(
+                None,
+                Some(StagedCodemod {
+                    rules: report.rules.clone(),
+                    files: staged_files,
+                }),
+            )?;
+            report.staged = true;
+        }
+        render(&report, format)
+    }
+}
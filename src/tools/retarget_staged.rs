@@ -6,9 +6,15 @@
 //! - Preview changes with new targeting
 //! - Validate new selector configuration
 //! - Keep existing content unchanged
-//! - Return updated preview with diff
+//! - Pick one of an ambiguous anchor's candidates by index
+//! - Return updated preview with diff, as text or structured JSON
 
-use crate::{selector::Selector, state::SemanticEditTools, tools::ToolHelpers};
+use crate::{
+    editor::ResultFormat,
+    selector::{Operation, Selector},
+    state::SemanticEditTools,
+    tools::{ToolHelpers, helpers::render},
+};
 
 use crate::error::SemanticEditError;
 use anyhow::Result;
@@ -25,69 +31,122 @@ use serde::{Deserialize, Serialize};
 pub struct RetargetStaged {
     #[serde(flatten)]
     pub selector: Selector,
+
+    /// Optional index of the anchor match to edit, from the `candidates` listed when the
+    /// anchor matches more than once. Defaults to the first match where the edit is valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<usize>,
+
+    /// Result format: `text` (default) for a human-readable preview, or `json` for a
+    /// structured report with diff hunks, target range, and efficiency metrics
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
 }
 
 impl WithExamples for RetargetStaged {
     fn examples() -> Vec<Example<Self>> {
         vec![
-            // Example {
-            //     description: "After staging content to add a struct field, retarget from field_declaration to field_declaration_list for better insertion point",
-            //     item: Self {
-            //         selector: NodeSelector {
-            //             anchor_text: "pub created_at:".into(),
-            //             ancestor_node_type: Some("field_declaration_list".into()),
-            //             position: None,
-            //         },
-            //     },
-            // },
-            // Example {
-            //     description: "Move JSON insertion from inside an object to after the entire object pair",
-            //     item: Self {
-            //         selector: NodeSelector {
-            //             anchor_text: "\"cache\"".into(),
-            //             ancestor_node_type: Some("pair".into()),
-            //             position: None,
-            //         },
-            //     },
-            // },
-            // Example {
-            //     description: "Adjust function insertion from declaration_list to function_item scope",
-            //     item: Self {
-            //         selector: NodeSelector {
-            //             anchor_text: "pub fn validate_email".into(),
-            //             ancestor_node_type: Some("function_item".into()),
-            //             position: None,
-            //         },
-            //     },
-            // },
-            // Example {
-            //     description: "Use exploration mode first to see all targeting options before retargeting",
-            //     item: Self {
-            //         selector: NodeSelector {
-            //             anchor_text: "impl User".into(),
-            //             ancestor_node_type: None,
-            //             position: None,
-            //         },
-            //     },
-            // },
+            Example {
+                description: "The preview edited the first of several matches of `let timeout`; \
+                              edit candidate 2 from the listed candidates instead",
+                item: Self {
+                    selector: Selector {
+                        operation: Operation::ReplaceExact,
+                        anchor: "let timeout = 30;".into(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    candidate: Some(2),
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "The staged struct field landed after one field declaration; \
+                              insert it after the whole struct instead",
+                item: Self {
+                    selector: Selector {
+                        operation: Operation::InsertAfterNode,
+                        anchor: "pub struct User {".into(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    candidate: None,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "A replace_exact of a function's first line left its old body \
+                              behind; replace the whole function node instead",
+                item: Self {
+                    selector: Selector {
+                        operation: Operation::ReplaceNode,
+                        anchor: "pub fn validate_email(".into(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    candidate: None,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Replace everything from a comment to the end of a block, \
+                              returning a structured report",
+                item: Self {
+                    selector: Selector {
+                        operation: Operation::ReplaceRange,
+                        anchor: "// legacy retry loop".into(),
+                        end: Some("return Err(last_error);".into()),
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    candidate: None,
+                    format: ResultFormat::Json,
+                },
+            },
+            Example {
+                description: "commit_staged refused the operation because the file changed \
+                              on disk; stage it again against the current content with the \
+                              same anchor",
+                item: Self {
+                    selector: Selector {
+                        operation: Operation::InsertAfter,
+                        anchor: "use std::collections::HashMap;".into(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    candidate: None,
+                    format: ResultFormat::Text,
+                },
+            },
         ]
     }
 }
 
 impl Tool<SemanticEditTools> for RetargetStaged {
     fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
-        let Self { selector } = self;
+        let Self {
+            selector,
+            candidate,
+            format,
+        } = self;
 
         let staged_operation = state
-            .modify_staged_operation(None, |op| op.retarget(selector))?
+            .modify_staged_operation(None, |op| op.retarget(selector, candidate))?
             .ok_or_else(|| anyhow::Error::from(SemanticEditError::OperationNotStaged))?;
 
+        // the file is unchanged since staging, so it is as risky to edit as it was then
+        let dirty = staged_operation.dirty.clone();
         let editor = state.create_editor_from_operation(staged_operation)?;
-        let (message, staged_operation) = editor.preview()?;
-        if staged_operation.is_some() {
+        let (mut report, staged_operation) = editor.preview_report()?;
+        if let Some(staged_operation) = staged_operation {
             // leave failed operations in place
-            state.stage_operation(None, staged_operation)?;
+            report.dirty = dirty.clone();
+            state.stage_operation(None, Some(staged_operation.with_dirty(dirty)))?;
         }
-        Ok(message)
+        render(&report, format)
     }
 }
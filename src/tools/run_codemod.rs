@@ -0,0 +1,445 @@
+//! Run codemod tool for rewriting many files with ast-grep style rules.
+//!
+//! This module implements the `run_codemod` MCP tool which applies pattern rules across a
+//! set of files and stages every rewrite as one codemod for commit_staged. Features include:
+//! - Rules read from `.semantic-edit/rules` in the session context, or a given file or
+//!   directory, optionally narrowed to some rule ids
+//! - Files chosen by paths and glob patterns, honoring `.gitignore`
+//! - A diff of each rewritten file
+//! - Files the rules would leave with new syntax errors are left out, with the reason
+//! - Matches of rules without a fix are listed rather than rewritten
+//! - Warnings for files with uncommitted git changes or conflict markers
+//! - Replaces any staged operation or codemod, unless nothing was rewritten
+//! - Text or structured JSON results
+
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+use crate::codemod::{CodemodRule, RULE_DIRECTORY};
+use crate::editor::{DiffHunk, DiffOptions, ResultFormat, file_diff};
+use crate::filesystem::FileOperations;
+use crate::git::{self, GitCommit};
+use crate::state::{SemanticEditTools, SourceFingerprint, StagedCodemod, StagedRewrite};
+use crate::tools::helpers::render;
+use crate::tools::open_files::expand_paths;
+use anyhow::{Result, anyhow};
+use mcplease::traits::{Tool, WithExamples};
+use mcplease::types::Example;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Rewrite files with ast-grep style YAML rules, staging every rewrite for review
+///
+/// Each rule has an `id`, a `language`, a `rule` with a `pattern`, and an optional `fix`.
+/// In patterns, `$NAME` matches any one node and `$$$NAME` any number of nodes; the fix uses
+/// the same names for the code they matched. The rewrites are staged together: review the
+/// diffs, then write every file with commit_staged.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "run_codemod")]
+pub struct RunCodemod {
+    /// Rule file, or directory of `.yml` and `.yaml` rule files. Defaults to
+    /// `.semantic-edit/rules` in the session context.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+
+    /// Only run the rules with these ids. Defaults to every rule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rule_ids: Vec<String>,
+
+    /// Files to rewrite: paths, or glob patterns like `src/**/*.rs` that skip files ignored
+    /// by `.gitignore`. Defaults to every file in the session context.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_paths: Vec<String>,
+
+    /// Optional diff rendering overrides
+    #[serde(default, skip_serializing_if = "DiffOptions::is_default")]
+    pub diff_options: DiffOptions,
+
+    /// Result format: `text` (default) for a human-readable preview, or `json` for a
+    /// structured report with each file's diff hunks
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
+}
+
+impl WithExamples for RunCodemod {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Run every rule in .semantic-edit/rules over the whole project",
+                item: Self {
+                    rules: None,
+                    rule_ids: vec![],
+                    file_paths: vec![],
+                    diff_options: DiffOptions::default(),
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Run one rule from a rule file over the Rust files under src",
+                item: Self {
+                    rules: Some("lints/codemods.yml".into()),
+                    rule_ids: vec!["unwrap-to-expect".into()],
+                    file_paths: vec!["src/**/*.rs".into()],
+                    diff_options: DiffOptions::default(),
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Preview a codemod over two files as a structured report with \
+                              every rewritten file's hunks",
+                item: Self {
+                    rules: None,
+                    rule_ids: vec!["deprecated-logger".into()],
+                    file_paths: vec!["src/server.rs".into(), "src/worker.rs".into()],
+                    diff_options: DiffOptions::default(),
+                    format: ResultFormat::Json,
+                },
+            },
+        ]
+    }
+}
+
+/// A file rewritten by a codemod
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodemodFile {
+    pub file_path: PathBuf,
+    /// Number of matches rewritten in the file
+    pub matches: usize,
+    pub hunks: Vec<DiffHunk>,
+    /// Human-readable diff
+    pub diff: String,
+    /// Why the file is risky to edit, such as uncommitted git changes, if it is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dirty: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<PathBuf>,
+    /// Ref holding the file's content from before the codemod, if the session takes git
+    /// snapshots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_snapshot: Option<String>,
+    /// How the file read back after writing differs from the codemod's output, if it does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<String>,
+}
+
+/// A match of a rule without a fix, reported rather than rewritten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportedMatch {
+    pub file_path: PathBuf,
+    pub line: usize,
+    pub rule_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A file the rules matched but that was not rewritten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub file_path: PathBuf,
+    pub reason: String,
+}
+
+/// Result of running, viewing, or committing a codemod
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodemodReport {
+    pub staged: bool,
+    pub applied: bool,
+    pub dry_run: bool,
+    /// Ids of the rules that ran
+    pub rules: Vec<String>,
+    pub files: Vec<CodemodFile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reported: Vec<ReportedMatch>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedFile>,
+    /// The git commit holding the rewritten files, if the session commits edits to git
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<GitCommit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+impl CodemodReport {
+    /// A report of a staged codemod's diffs against the files' current content
+    pub fn from_staged(
+        codemod: &StagedCodemod,
+        file_operations: &dyn FileOperations,
+        diff_options: &DiffOptions,
+    ) -> Result<Self> {
+        let mut report = Self {
+            rules: codemod.rules.clone(),
+            ..Self::default()
+        };
+        for rewrite in &codemod.files {
+            let (_, source) = file_operations.read_encoded(&rewrite.file_path)?;
+            if rewrite
+                .source_fingerprint
+                .is_stale(&SourceFingerprint::from_source(&source))
+            {
+                report.warning = Some(format!(
+                    "⚠️ WARNING: {} changed on disk after this codemod was staged, so \
+commit_staged will refuse it. Use run_codemod again to rewrite the current content.",
+                    rewrite.file_path.display()
+                ));
+            }
+            report
+                .files
+                .push(CodemodFile::new(rewrite, &source, diff_options));
+        }
+        Ok(report)
+    }
+
+    fn match_count(&self) -> usize {
+        self.files.iter().map(|file| file.matches).sum()
+    }
+
+    fn summary(&self) -> String {
+        let matches = self.match_count();
+        let files = self.files.len();
+        format!(
+            "codemod {} rewriting {matches} match{} in {files} file{}",
+            self.rules.join(", "),
+            if matches == 1 { "" } else { "es" },
+            if files == 1 { "" } else { "s" },
+        )
+    }
+}
+
+impl CodemodFile {
+    pub(crate) fn new(rewrite: &StagedRewrite, source: &str, diff_options: &DiffOptions) -> Self {
+        let diff = file_diff(source, &rewrite.output, diff_options);
+        Self {
+            file_path: rewrite.file_path.clone(),
+            matches: rewrite.matches,
+            diff: diff.to_string(),
+            hunks: diff.hunks,
+            dirty: rewrite.dirty.clone(),
+            backup_path: None,
+            git_snapshot: None,
+            verification: None,
+        }
+    }
+}
+
+impl Display for CodemodReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.applied {
+            writeln!(f, "Applied {}:", self.summary())?;
+            for file in &self.files {
+                write!(
+                    f,
+                    "- {} ({} rewritten)",
+                    file.file_path.display(),
+                    file.matches
+                )?;
+                if let Some(backup_path) = &file.backup_path {
+                    write!(f, ", backed up to {}", backup_path.display())?;
+                }
+                if let Some(git_snapshot) = &file.git_snapshot {
+                    write!(f, ", previous content saved in git as {git_snapshot}")?;
+                }
+                f.write_str("\n")?;
+            }
+            for file in &self.files {
+                if let Some(verification) = &file.verification {
+                    writeln!(
+                        f,
+                        "\n⚠️ VERIFICATION FAILED: {} was written, but {verification}. Check the \
+file before editing it further.",
+                        file.file_path.display()
+                    )?;
+                }
+            }
+        } else if self.files.is_empty() {
+            writeln!(
+                f,
+                "No files rewritten by codemod {}. Nothing was staged.",
+                self.rules.join(", ")
+            )?;
+        } else {
+            if self.dry_run {
+                write!(
+                    f,
+                    "DRY RUN of {}. No files were modified.\n\n",
+                    self.summary()
+                )?;
+            } else {
+                write!(f, "STAGED: {}\n\n", self.summary())?;
+            }
+            if let Some(warning) = &self.warning {
+                write!(f, "{warning}\n\n")?;
+            }
+            for file in &self.files {
+                writeln!(f, "=== {} ===", file.file_path.display())?;
+                if let Some(dirty) = &file.dirty {
+                    writeln!(
+                        f,
+                        "⚠️ WARNING: {} {dirty}. commit_staged will refuse the codemod unless \
+called with `acknowledge_dirty: true`.",
+                        file.file_path.display()
+                    )?;
+                }
+                write!(f, "{}\n\n", file.diff.trim_end())?;
+            }
+            if self.staged && !self.dry_run {
+                f.write_str("Use commit_staged to write every file at once.\n")?;
+            }
+        }
+
+        if !self.skipped.is_empty() {
+            f.write_str("\nNot rewritten:\n")?;
+            for skipped in &self.skipped {
+                writeln!(f, "- {}: {}", skipped.file_path.display(), skipped.reason)?;
+            }
+        }
+
+        if !self.reported.is_empty() {
+            f.write_str("\nMatches of rules without a fix:\n")?;
+            for reported in &self.reported {
+                write!(
+                    f,
+                    "- {}:{} {}",
+                    reported.file_path.display(),
+                    reported.line,
+                    reported.rule_id
+                )?;
+                if let Some(message) = &reported.message {
+                    write!(f, ": {message}")?;
+                }
+                f.write_str("\n")?;
+            }
+        }
+
+        if self.applied {
+            if let Some(git_commit) = &self.git_commit {
+                writeln!(
+                    f,
+                    "\nCommitted to git as {}: {}",
+                    git_commit.hash, git_commit.subject
+                )?;
+            }
+            if let Some(warning) = &self.warning {
+                writeln!(f, "\n{warning}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tool<SemanticEditTools> for RunCodemod {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let Self {
+            rules,
+            rule_ids,
+            file_paths,
+            diff_options,
+            format,
+        } = self;
+
+        let rules_path = state.resolve_path(rules.as_deref().unwrap_or(RULE_DIRECTORY), None)?;
+        let mut rules = CodemodRule::load(&rules_path)?;
+        if !rule_ids.is_empty() {
+            if let Some(unknown) = rule_ids
+                .iter()
+                .find(|id| !rules.iter().any(|rule| &rule.id == *id))
+            {
+                return Err(anyhow!(
+                    "no rule `{unknown}` in {}. Its rules are {}",
+                    rules_path.display(),
+                    rules
+                        .iter()
+                        .map(|rule| format!("`{}`", rule.id))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            rules.retain(|rule| rule_ids.contains(&rule.id));
+        }
+
+        let patterns = if file_paths.is_empty() {
+            vec!["**/*".to_string()]
+        } else {
+            file_paths
+        };
+        let paths = expand_paths(state, &patterns, None)?;
+
+        let mut report = CodemodReport {
+            rules: rules.iter().map(|rule| rule.id.clone()).collect(),
+            ..CodemodReport::default()
+        };
+        let mut staged_files = Vec::new();
+        let file_operations = state.session_file_operations(None)?;
+        for path in paths {
+            let Some(language_name) = state.language_registry().detect_language_from_path(&path)
+            else {
+                continue;
+            };
+            if !rules.iter().any(|rule| rule.language == language_name) {
+                continue;
+            }
+            let language = state.language_registry().get_language(language_name)?;
+            let (_, source) = file_operations.read_encoded(&path)?;
+            let Some(rewrite) = CodemodRule::rewrite_all(&rules, &source, language)? else {
+                continue;
+            };
+
+            for rule_match in rewrite
+                .matches
+                .iter()
+                .filter(|rule_match| !rule_match.fixed)
+            {
+                report.reported.push(ReportedMatch {
+                    file_path: path.clone(),
+                    line: rule_match.line,
+                    rule_id: rule_match.rule_id.clone(),
+                    message: rules
+                        .iter()
+                        .find(|rule| rule.id == rule_match.rule_id)
+                        .and_then(|rule| rule.message.clone()),
+                });
+            }
+            if rewrite.output == source {
+                continue;
+            }
+            if rewrite.new_errors > 0 {
+                report.skipped.push(SkippedFile {
+                    file_path: path,
+                    reason: format!(
+                        "the rewrite would add {} syntax error{}",
+                        rewrite.new_errors,
+                        if rewrite.new_errors == 1 { "" } else { "s" }
+                    ),
+                });
+                continue;
+            }
+
+            let staged_rewrite = StagedRewrite {
+                source_fingerprint: SourceFingerprint::new(&path, &source),
+                matches: rewrite
+                    .matches
+                    .iter()
+                    .filter(|rule_match| rule_match.fixed)
+                    .count(),
+                output: rewrite.output,
+                dirty: git::dirty_reason(&path, &source),
+                file_path: path,
+            };
+            report
+                .files
+                .push(CodemodFile::new(&staged_rewrite, &source, &diff_options));
+            staged_files.push(staged_rewrite);
+        }
+
+        if !staged_files.is_empty() {
+            state.stage_codemod(
+                None,
+                Some(StagedCodemod {
+                    rules: report.rules.clone(),
+                    files: staged_files,
+                }),
+            )?;
+            report.staged = true;
+        }
+        render(&report, format)
+    }
+}
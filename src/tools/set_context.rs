@@ -6,11 +6,27 @@
 //! - Enable relative path usage in other tools
 //! - Path validation and canonicalization
 //! - Session-specific context management
+//! - Named workspace roots, so monorepo paths like `frontend:src/app.ts` resolve against
+//!   the right tree without switching context
 //! - Support for tilde expansion
+//! - Optional per-session backup strategy for committed files
+//! - Optional per-session format mode
+//! - Optional per-session validation level
+//! - Optional per-session overrides that disable or downgrade individual validation rules
+//! - Optional per-session read-only mode, allowing previews but refusing commits
+//! - Optional per-session git auto-commit of every file written by `commit_staged`
+//! - Optional per-session git snapshots of files before `commit_staged` overwrites them
+//! - Watching the directory so files changed outside the server are not served stale
+//! - Loading project validation queries from `.semantic-edit/queries/<language>/*.scm`
 
 use std::path::PathBuf;
 
+use crate::backup::BackupStrategy;
+use crate::editor::FormatMode;
 use crate::state::SemanticEditTools;
+use crate::validation::{
+    CUSTOM_QUERY_DIR, RuleOverride, RuleSetting, RuleSettings, ValidationLevel,
+};
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -23,8 +39,63 @@ use serde::{Deserialize, Serialize};
 #[serde(rename = "set_context")]
 pub struct SetContext {
     /// Directory path to set as context.
-    /// Subsequent to calling this, any relative paths will be relative to this directory
+    /// Subsequent to calling this, any relative paths will be relative to this directory.
+    /// Validation queries in `.semantic-edit/queries/<language>/*.scm` under this directory
+    /// are compiled and enforced on top of the built-in rules.
     path: String,
+
+    /// Optional backup strategy for files written by commit_staged in this session.
+    /// `sibling` writes `<file>.bak`, `directory` writes into `.semantic-edit/backups/<timestamp>/`,
+    /// and `none` disables backups. If omitted, the server default is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backup_strategy: Option<BackupStrategy>,
+
+    /// Optional formatting for edited files in this session. `always` (the default) formats
+    /// the whole file, `never` keeps edits exactly as written, and `edited_region` only applies
+    /// formatting changes to the edited lines, leaving untouched code as it was.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format_mode: Option<FormatMode>,
+
+    /// Optional validation strictness for this session. `strict` (the default) refuses edits
+    /// that produce invalid syntax, `warn` stages them with a prominent warning, and `off`
+    /// skips validation entirely. Useful for generated or intentionally partial files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validation_level: Option<ValidationLevel>,
+
+    /// Optional overrides for individual context validation rules, named as reported in
+    /// validation errors, e.g. `[{"rule": "invalid.use.in.function.body", "setting": "warn"}]`.
+    /// `off` ignores the rule, `warn` stages violating edits with a warning, and `error`
+    /// refuses them (the default). Replaces any overrides previously set for this session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rules: Option<Vec<RuleOverride>>,
+
+    /// Optional read-only mode for this session. When true, edits can be staged and
+    /// previewed (including `commit_staged` dry runs) but not committed. A server started
+    /// in read-only mode stays read-only regardless of this setting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    read_only: Option<bool>,
+
+    /// Optional git auto-commit for this session. When true, commit_staged also commits each
+    /// file it writes to git, with a message naming the file, the selector, and the diff stat,
+    /// so every edit can be reviewed and reverted on its own. Only the edited file goes into
+    /// each commit; anything else staged in the index is left alone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_commit: Option<bool>,
+
+    /// Optional git safety snapshots for this session. When true, commit_staged saves each
+    /// file's content in git under `refs/semantic-edit/snapshots/` before overwriting it, so
+    /// uncommitted work can be restored with `git checkout <ref> -- <path>` after a bad edit.
+    /// The working tree, index, and branches are left untouched, and a file that cannot be
+    /// snapshotted is not written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_snapshot: Option<bool>,
+
+    /// Optional name to register this directory under as a workspace root, e.g. `frontend`.
+    /// Paths given to other tools as `frontend:src/app.ts` then resolve against it, so several
+    /// trees of a monorepo can be edited without switching context. The first root registered
+    /// also becomes the context for plain relative paths; later ones leave it unchanged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root: Option<String>,
     // temporarily commented out
     // /// Session identifier can be absolutely any string, as long as it's unlikely to collide with another session, (ie not "claude")
     // /// You will need to provide this to subsequent tool calls, so short and memorable but unique is probably best. Be creative!
@@ -37,25 +108,238 @@ pub struct SetContext {
 
 impl WithExamples for SetContext {
     fn examples() -> Vec<Example<Self>> {
-        vec![Example {
-            description: "setting context to a development project",
-            item: Self {
-                path: "/usr/local/projects/cobol".into(),
-                //                session_id: "GraceHopper1906".into(),
+        vec![
+            Example {
+                description: "setting context to a development project",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: None,
+                    format_mode: None,
+                    validation_level: None,
+                    rules: None,
+                    read_only: None,
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: None,
+                    //                session_id: "GraceHopper1906".into(),
+                },
+            },
+            Example {
+                description: "setting context and keeping .bak copies of every committed file",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: Some(BackupStrategy::Sibling),
+                    format_mode: None,
+                    validation_level: None,
+                    rules: None,
+                    read_only: None,
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: None,
+                },
+            },
+            Example {
+                description: "setting context and only formatting the lines each edit touches",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: None,
+                    format_mode: Some(FormatMode::EditedRegion),
+                    validation_level: None,
+                    rules: None,
+                    read_only: None,
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: None,
+                },
+            },
+            Example {
+                description: "setting context for a project with generated files that do not fully parse",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: None,
+                    format_mode: None,
+                    validation_level: Some(ValidationLevel::Warn),
+                    rules: None,
+                    read_only: None,
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: None,
+                },
+            },
+            Example {
+                description: "setting context and only warning about imports inside function bodies",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: None,
+                    format_mode: None,
+                    validation_level: None,
+                    rules: Some(vec![RuleOverride {
+                        rule: "invalid.use.in.function.body".into(),
+                        setting: RuleSetting::Warn,
+                    }]),
+                    read_only: None,
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: None,
+                },
+            },
+            Example {
+                description: "setting context for a review-only session that previews edits without writing them",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: None,
+                    format_mode: None,
+                    validation_level: None,
+                    rules: None,
+                    read_only: Some(true),
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: None,
+                },
+            },
+            Example {
+                description: "setting context and committing every edit to git for an audit trail",
+                item: Self {
+                    path: "/usr/local/projects/cobol".into(),
+                    backup_strategy: None,
+                    format_mode: None,
+                    validation_level: None,
+                    rules: None,
+                    read_only: None,
+                    git_commit: Some(true),
+                    git_snapshot: None,
+                    root: None,
+                },
+            },
+            Example {
+                description: "registering the backend of a monorepo as a workspace root named `backend`",
+                item: Self {
+                    path: "/usr/local/projects/monorepo/backend".into(),
+                    backup_strategy: None,
+                    format_mode: None,
+                    validation_level: None,
+                    rules: None,
+                    read_only: None,
+                    git_commit: None,
+                    git_snapshot: None,
+                    root: Some("backend".into()),
+                },
             },
-        }]
+        ]
     }
 }
 
 impl Tool<SemanticEditTools> for SetContext {
     fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
-        let Self { path } = self;
+        let Self {
+            path,
+            backup_strategy,
+            format_mode,
+            validation_level,
+            rules,
+            read_only,
+            git_commit,
+            git_snapshot,
+            root,
+        } = self;
         let path = PathBuf::from(&*shellexpand::tilde(&path));
-        let response = format!(
-            "Set context to {path} for session.\n",
-            path = path.display()
-        );
-        state.set_context(None, path)?;
+        let mut response = match &root {
+            Some(name) => {
+                state.add_root(None, name, path.clone())?;
+                format!(
+                    "Registered workspace root `{name}` at {path}. Paths like `{name}:src/main.rs` resolve against it.\n",
+                    path = path.display()
+                )
+            }
+            None => {
+                state.set_context(None, path.clone())?;
+                format!(
+                    "Set context to {path} for session.\n",
+                    path = path.display()
+                )
+            }
+        };
+        let roots = state.get_roots(None)?;
+        if !roots.is_empty() {
+            let roots = roots
+                .iter()
+                .map(|(name, path)| format!("`{name}` ({})", path.display()))
+                .collect::<Vec<_>>();
+            let context = state.get_context(None)?.unwrap_or_default();
+            response.push_str(&format!(
+                "Workspace roots: {}. Paths without a root prefix are relative to {}.\n",
+                roots.join(", "),
+                context.display()
+            ));
+        }
+        if let Err(error) = state.watch_context(None) {
+            response.push_str(&format!(
+                "File watching is unavailable ({error}); cached files are still checked for changes before use.\n"
+            ));
+        }
+        let custom_queries = state.load_custom_queries(None)?;
+        if custom_queries > 0 {
+            response.push_str(&format!(
+                "Loaded {custom_queries} custom validation queries from {CUSTOM_QUERY_DIR}.\n"
+            ));
+        }
+        if let Some(backup_strategy) = backup_strategy {
+            state.set_session_backup_strategy(None, Some(backup_strategy))?;
+            response.push_str(&format!(
+                "Backup strategy set to {}.\n",
+                backup_strategy.as_str()
+            ));
+        }
+        if let Some(format_mode) = format_mode {
+            state.set_session_format_mode(None, format_mode)?;
+            response.push_str(&format!("Format mode set to {}.\n", format_mode.as_str()));
+        }
+        if let Some(validation_level) = validation_level {
+            state.set_session_validation_level(None, validation_level)?;
+            response.push_str(&format!(
+                "Validation level set to {}.\n",
+                validation_level.as_str()
+            ));
+        }
+        if let Some(rules) = rules {
+            let rules = rules.into_iter().collect::<RuleSettings>();
+            let summary = rules
+                .iter()
+                .map(|(rule, setting)| format!("{rule} = {}", setting.as_str()))
+                .collect::<Vec<_>>();
+            response.push_str(&if summary.is_empty() {
+                "Validation rule overrides cleared.\n".to_string()
+            } else {
+                format!("Validation rules set: {}.\n", summary.join(", "))
+            });
+            state.set_session_rules(None, rules)?;
+        }
+        if let Some(read_only) = read_only {
+            state.set_session_read_only(None, read_only)?;
+            response.push_str(if read_only {
+                "Read-only mode enabled: edits can be previewed but not committed.\n"
+            } else if state.is_read_only(None)? {
+                "The server is in read-only mode, so edits still cannot be committed.\n"
+            } else {
+                "Read-only mode disabled.\n"
+            });
+        }
+        if let Some(git_commit) = git_commit {
+            state.set_session_git_commit(None, git_commit)?;
+            response.push_str(if git_commit {
+                "Git auto-commit enabled: each committed edit is also committed to git.\n"
+            } else {
+                "Git auto-commit disabled.\n"
+            });
+        }
+        if let Some(git_snapshot) = git_snapshot {
+            state.set_session_git_snapshot(None, git_snapshot)?;
+            response.push_str(if git_snapshot {
+                "Git snapshots enabled: files are saved in git before they are overwritten.\n"
+            } else {
+                "Git snapshots disabled.\n"
+            });
+        }
         Ok(response)
     }
 }
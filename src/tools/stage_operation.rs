@@ -6,12 +6,29 @@
 //! - Validate edits before committing
 //! - Support for all operation types (insert, replace, etc.)
 //! - Comprehensive examples and documentation
+//! - Text or structured JSON results
+//! - Editing against a client's unsaved buffer instead of the on-disk file
+//! - Reuses the content and syntax tree of files that have not changed since they were last read
+//! - Edits one code cell of a Jupyter notebook, chosen by index or by the anchor it contains
+//! - Edits one of an ambiguous anchor's candidates, chosen by index
+//! - Previews the edit at each of an ambiguous anchor's candidates instead of staging one
+//! - Optionally idempotent, staging nothing when the content is already in place
+//! - Warns about files with uncommitted git changes or merge conflict markers, whose edits then
+//!   need `acknowledge_dirty` to be committed
+//! - Detects the language of files without a known extension from their content, and says
+//!   which language it chose
 
-use crate::editor::Editor;
+use crate::editor::{
+    DiffOptions, Editor, FormatMode, PreviewReport, ResultFormat, already_applied,
+};
+use crate::filesystem::FileOperations;
+use crate::git;
 use crate::languages::LanguageName;
+use crate::notebook::{self, Notebook};
 use crate::selector::{Operation, Selector};
 use crate::state::SemanticEditTools;
-use anyhow::Result;
+use crate::tools::helpers::render;
+use anyhow::{Result, anyhow};
 use mcplease::{
     traits::{Tool, WithExamples},
     types::Example,
@@ -32,15 +49,27 @@ use serde::{Deserialize, Serialize};
 /// Replace_range operations also use `end` to specify the extent:
 /// { "operation": "replace_range", "anchor": "// Start here", "end": "// End here" }
 ///
+/// Rewrite_pattern operations match `anchor` as a comby template and reuse its holes in `content`:
+/// { "operation": "rewrite_pattern", "anchor": "assert(:[cond], :[msg])", "content": "assert(:[msg], :[cond])" }
+///
+/// Add_attribute operations add `content`, a derive, attribute, or decorator, to the
+/// declaration containing the anchor, merging derives into an existing `#[derive(...)]`:
+/// { "operation": "add_attribute", "anchor": "struct Config", "content": "#[derive(Clone)]" }
+///
+/// Add_doc_comment operations document the declaration containing the anchor with `content`:
+/// { "operation": "add_doc_comment", "anchor": "def load", "content": "Load the saved store." }
+///
 /// To delete a syntax node, use one of the `replace` operations and omit `content`
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename = "stage_operation")]
 pub struct StageOperation {
     /// Path to the source file.
-    /// If a session has been configured, this can be a relative path to the session root.
+    /// If a session has been configured, this can be a relative path to the session root,
+    /// or `name:relative/path` for a workspace root registered with set_context.
     pub file_path: String,
 
-    /// Optional language hint. If not provided, language will be detected from file extension.
+    /// Optional language hint. If not provided, language will be detected from file extension,
+    /// or from the file's content if the extension is unknown.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language: Option<LanguageName>,
 
@@ -52,36 +81,110 @@ pub struct StageOperation {
     /// IMPORTANT TIP: To remove code, use `replace` and omit `content`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+
+    /// Optional current content of the file's editor buffer, for clients with unsaved changes.
+    /// The edit is computed against this instead of the on-disk file, and the buffer is used
+    /// for this file in this session until the operation is committed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer: Option<String>,
+
+    /// Result format: `text` (default) for a human-readable preview, or `json` for a
+    /// structured report with diff hunks, target range, and efficiency metrics
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
+
+    /// Optional diff rendering: `context_lines` (default 3), `line_numbers` to show original
+    /// and new line numbers, `side_by_side` for two-column output, and `word_diff` to mark
+    /// changed words within a line as `[-old-]{+new+}`. Diffs longer than `max_lines`
+    /// (default 400, `0` for no limit) are summarized; use view_staged_diff for the full diff.
+    /// `efficiency_min_lines` (default 10) and `efficiency_tip_percent` (default 30) control
+    /// when edit efficiency and the targeted-edit tip are shown.
+    /// These settings are kept with the staged operation and also apply to commit_staged.
+    #[serde(default, skip_serializing_if = "DiffOptions::is_default")]
+    pub diff_options: DiffOptions,
+
+    /// Optional formatting for this edit: `always` formats the whole file, `never` keeps the
+    /// edit exactly as written, and `edited_region` only formats the edited lines.
+    /// Defaults to the session's format mode (see set_context). Also applies to commit_staged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_mode: Option<FormatMode>,
+
+    /// For Jupyter notebooks (`.ipynb`), the index of the code cell to edit, counting all
+    /// cells from 0. Defaults to the only code cell containing `anchor`. The cell is edited
+    /// in the notebook's kernel language unless `language` is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+
+    /// Optional index of the anchor match to edit, from the `candidates` listed when the
+    /// anchor matches more than once. Defaults to the first match where the edit is valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<usize>,
+
+    /// If the anchor matches more than once and no `candidate` is given, nothing is staged;
+    /// instead the edit at each of the first few matches is previewed with a short diff, so
+    /// that one can be staged by passing its index as `candidate`. Defaults to false.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub show_alternatives: bool,
+
+    /// If the content is already where the edit would put it, as when retrying an edit that
+    /// may have been applied before a timeout, stage nothing and report it as already
+    /// applied instead of inserting it twice. Whitespace is ignored when comparing. Not
+    /// checked for `rewrite_pattern`, nor needed for `add_attribute` and `add_doc_comment`,
+    /// which never duplicate. Defaults to false.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub idempotent: bool,
 }
 
 impl WithExamples for StageOperation {
     fn examples() -> Vec<Example<Self>> {
         vec![
-            // more examples to add
-            //
-            // ```json
-            // // Add a new import
-            // {
-            //   "operation": "insert_after",
-            //   "anchor": "use std::collections::HashMap;",
-            //   "content": "use std::fs::File;"
-            // }
-            //
-            // // Replace a function body
-            // {
-            //   "operation": "replace_node",
-            //   "anchor": "fn old_function() {",
-            //   "content": "fn new_function() {\n    println!(\"Updated!\");\n}"
-            // }
-            //
-            // // Change a section of code
-            // {
-            //   "operation": "replace_range",
-            //   "anchor": "// TODO: implement this",
-            //   "end": "return None;",
-            //   "content": "let result = calculate_value();\nreturn Some(result);"
-            // }
-            //
+            Example {
+                description: "Add an import after an existing one",
+                item: Self {
+                    file_path: "src/main.rs".into(),
+                    selector: Selector {
+                        anchor: "use std::collections::HashMap;".into(),
+                        operation: Operation::InsertAfter,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("\nuse std::fs::File;".to_string()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Retry an insertion after a timeout, staging nothing if the first \
+                              attempt already added it",
+                item: Self {
+                    file_path: "src/main.rs".into(),
+                    selector: Selector {
+                        anchor: "use std::collections::HashMap;".into(),
+                        operation: Operation::InsertAfter,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("\nuse std::fs::File;".to_string()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: true,
+                },
+            },
             Example {
                 description: "Insert content after a function declaration",
                 item: Self {
@@ -90,9 +193,19 @@ impl WithExamples for StageOperation {
                         anchor: "fn main() {".into(),
                         operation: Operation::InsertAfter,
                         end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
                     },
                     content: Some("\n    println!(\"Hello, world!\");".to_string()),
                     language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
                 },
             },
             Example {
@@ -103,9 +216,19 @@ impl WithExamples for StageOperation {
                         anchor: "fn hello()".to_string(),
                         operation: Operation::ReplaceNode,
                         end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
                     },
                     content: Some("fn hello() { println!(\"Hello, world!\"); }".to_string()),
                     language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
                 },
             },
             Example {
@@ -116,12 +239,295 @@ impl WithExamples for StageOperation {
                         operation: Operation::ReplaceRange,
                         anchor: "let user =".to_string(),
                         end: Some("return user;".into()),
+                        end_inclusive: true,
+                        include_leading_trivia: false,
                     },
                     content: Some(
                         "let user = User::new();\n    validate_user(&user);\n    return user;"
                             .into(),
                     ),
                     language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Replace the code between two functions, stopping before the \
+                              second and keeping it",
+                item: Self {
+                    file_path: "src/main.rs".into(),
+                    selector: Selector {
+                        operation: Operation::ReplaceRange,
+                        anchor: "fn setup() {".to_string(),
+                        end: Some("fn teardown() {".into()),
+                        end_inclusive: false,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("fn setup() {\n    init_logging();\n}\n\n".into()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Swap the arguments of a call, keeping each argument as it is",
+                item: Self {
+                    file_path: "src/main.rs".into(),
+                    selector: Selector {
+                        operation: Operation::RewritePattern,
+                        anchor: "copy_file(:[from], :[to])".to_string(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("copy_file(:[to], :[from])".into()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Derive a trait for a struct, adding it to the struct's existing \
+                              derive if it has one",
+                item: Self {
+                    file_path: "src/config.rs".into(),
+                    selector: Selector {
+                        operation: Operation::AddAttribute,
+                        anchor: "pub struct Config".to_string(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("#[derive(Clone)]".into()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Add a docstring to a Python function, inside its body where \
+                              help() finds it",
+                item: Self {
+                    file_path: "app/store.py".into(),
+                    selector: Selector {
+                        operation: Operation::AddDocComment,
+                        anchor: "def load(path)".to_string(),
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("Load the store saved at `path`.".into()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Preview a change with line numbers and extra context",
+                item: Self {
+                    file_path: "src/main.rs".into(),
+                    selector: Selector {
+                        anchor: "let timeout =".to_string(),
+                        operation: Operation::ReplaceNode,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("let timeout = Duration::from_secs(30);".to_string()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions {
+                        context_lines: 5,
+                        line_numbers: true,
+                        side_by_side: false,
+                        word_diff: false,
+                        ..DiffOptions::default()
+                    },
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Insert hand-formatted code without running the formatter",
+                item: Self {
+                    file_path: "src/tables.rs".into(),
+                    selector: Selector {
+                        anchor: "const IDENTITY".to_string(),
+                        operation: Operation::InsertAfterNode,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some(
+                        "const SWAP: [[u8; 2]; 2] = [\n    [0, 1],\n    [1, 0],\n];".to_string(),
+                    ),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: Some(FormatMode::Never),
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Edit a code cell of a Jupyter notebook",
+                item: Self {
+                    file_path: "analysis.ipynb".into(),
+                    selector: Selector {
+                        anchor: "df = pd.read_csv(\"data.csv\")".to_string(),
+                        operation: Operation::ReplaceExact,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("df = pd.read_csv(\"data.csv\", parse_dates=[\"date\"])".into()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: Some(2),
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "The anchor `fn proces_order(` was not found and the error \
+                              suggested `line 42: fn process_order(order: &Order) -> Result<()> {`; \
+                              stage again with the suggested text",
+                item: Self {
+                    file_path: "src/orders.rs".into(),
+                    selector: Selector {
+                        anchor: "fn process_order(order: &Order)".to_string(),
+                        operation: Operation::ReplaceNode,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some(
+                        "fn process_order(order: &Order) -> Result<()> {\n    order.validate()?;\n    \
+                         order.submit()\n}"
+                            .to_string(),
+                    ),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "The anchor `retries: 3` matched several places and a failed stage \
+                              listed them as candidates; stage again editing candidate 1",
+                item: Self {
+                    file_path: "config/services.json".into(),
+                    selector: Selector {
+                        anchor: "\"retries\": 3".to_string(),
+                        operation: Operation::ReplaceExact,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("\"retries\": 5".to_string()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: Some(1),
+                    show_alternatives: false,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "`let timeout` appears in several functions; compare the edit at \
+                              each of them before choosing one to stage",
+                item: Self {
+                    file_path: "src/client.rs".into(),
+                    selector: Selector {
+                        anchor: "let timeout = 30;".to_string(),
+                        operation: Operation::ReplaceExact,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("let timeout = 60;".to_string()),
+                    language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: true,
+                    idempotent: false,
+                },
+            },
+            Example {
+                description: "Edit the unsaved content of a file open in an editor, and get a \
+                              structured report",
+                item: Self {
+                    file_path: "src/lib.rs".into(),
+                    selector: Selector {
+                        anchor: "pub mod parser;".to_string(),
+                        operation: Operation::InsertAfter,
+                        end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
+                    },
+                    content: Some("\npub mod lexer;".to_string()),
+                    language: None,
+                    buffer: Some("pub mod ast;\npub mod parser;\n".to_string()),
+                    format: ResultFormat::Json,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
                 },
             },
             Example {
@@ -132,9 +538,19 @@ impl WithExamples for StageOperation {
                         operation: Operation::ReplaceNode,
                         anchor: "fn main() {".to_string(),
                         end: None,
+                        end_inclusive: true,
+                        include_leading_trivia: false,
                     },
                     content: None,
                     language: None,
+                    buffer: None,
+                    format: ResultFormat::Text,
+                    diff_options: DiffOptions::default(),
+                    format_mode: None,
+                    cell: None,
+                    candidate: None,
+                    show_alternatives: false,
+                    idempotent: false,
                 },
             },
         ]
@@ -147,25 +563,142 @@ impl Tool<SemanticEditTools> for StageOperation {
             file_path,
             selector,
             content,
+            buffer,
             language,
+            format,
+            diff_options,
+            format_mode,
+            cell,
+            candidate,
+            show_alternatives,
+            idempotent,
         } = self;
 
         let file_path = state.resolve_path(&file_path, None)?;
+        if let Some(buffer) = buffer {
+            state.set_buffer(None, &file_path, Some(buffer))?;
+        }
 
-        let language = state
-            .language_registry()
-            .get_language_with_hint(&file_path, language)?;
-
-        let editor = Editor::new(
-            content.unwrap_or_default(),
-            selector,
-            language,
-            file_path,
+        let file_operations = state.session_file_operations(None)?;
+        let content = content.unwrap_or_default();
+        let registry = state.language_registry();
+        let detected_language = if language.is_none()
+            && !notebook::is_notebook(&file_path)
+            && registry.detect_language_from_path(&file_path).is_none()
+        {
+            // a file that cannot be read is reported when the editor opens it
+            file_operations
+                .read_encoded(&file_path)
+                .ok()
+                .and_then(|(_, source)| registry.detect_language_from_source(&source))
+        } else {
+            None
+        };
+        let language = language.or(detected_language.map(|guess| guess.language));
+        if idempotent {
+            let (_, source) = file_operations.read_encoded(&file_path)?;
+            let applied = if notebook::is_notebook(&file_path) {
+                applied_in_notebook(state, &source, language, cell, &selector, &content)?
+            } else {
+                let language = state
+                    .language_registry()
+                    .get_language_with_hint(&file_path, language)?;
+                already_applied(language, &selector, &content, &source).map(|reason| (cell, reason))
+            };
+            if let Some((cell, reason)) = applied {
+                let report = PreviewReport::already_applied(
+                    selector.operation_name(),
+                    file_path,
+                    cell,
+                    reason,
+                );
+                return render(&report, format);
+            }
+        }
+        let mut editor = if notebook::is_notebook(&file_path) {
+            Editor::open_notebook_cell(
+                content,
+                selector,
+                state.language_registry(),
+                language,
+                file_path.clone(),
+                cell,
+                &file_operations,
+            )?
+        } else if cell.is_some() {
+            return Err(anyhow!(
+                "`cell` only applies to Jupyter notebooks (.ipynb), and {} is not one",
+                file_path.display()
+            ));
+        } else {
+            let language = state
+                .language_registry()
+                .get_language_with_hint(&file_path, language)?;
+            Editor::open_cached(
+                content,
+                selector,
+                language,
+                file_path.clone(),
+                None,
+                &file_operations,
+                state.source_cache(),
+            )?
+        }
+        .with_diff_options(diff_options)
+        .with_format_mode(match format_mode {
+            Some(format_mode) => format_mode,
+            None => state.format_mode(None)?,
+        })
+        .with_validation_level(state.validation_level(None)?)
+        .with_rules(state.rules(None)?)
+        .with_format_limits(state.format_limits())
+        .with_parse_limits(state.parse_limits())
+        .with_progress(state.progress().clone())
+        .with_custom_queries(state.custom_queries(None)?)
+        .with_candidate(candidate);
+        if show_alternatives && candidate.is_none() {
+            if let Some(report) = editor.alternatives_report()? {
+                return render(&report, format);
+            }
+        }
+        let dirty = git::dirty_reason(&file_path, editor.source_code());
+        let (mut report, staged_operation) = editor.preview_report()?;
+        if report.staged {
+            report.dirty = dirty.clone();
+        }
+        report.detected_language = detected_language.map(|guess| guess.to_string());
+        state.stage_operation(
             None,
+            staged_operation.map(|staged_operation| staged_operation.with_dirty(dirty)),
         )?;
-        let (message, staged_operation) = editor.preview()?;
-        state.stage_operation(None, staged_operation)?;
 
-        Ok(message)
+        render(&report, format)
     }
 }
+
+/// The notebook cell an idempotent edit is already applied to and why: the given `cell`, or
+/// any code cell
+fn applied_in_notebook(
+    state: &SemanticEditTools,
+    source: &str,
+    language: Option<LanguageName>,
+    cell: Option<usize>,
+    selector: &Selector,
+    content: &str,
+) -> Result<Option<(Option<usize>, String)>> {
+    let notebook = Notebook::parse(source)?;
+    let language = state.language_registry().get_language(
+        language
+            .or_else(|| notebook.language())
+            .unwrap_or(LanguageName::Python),
+    )?;
+    let cells = match cell {
+        Some(cell) => vec![cell],
+        None => (0..notebook.cell_count()).collect(),
+    };
+    Ok(cells.into_iter().find_map(|index| {
+        let cell_source = notebook.cell_source(index).ok()?;
+        already_applied(language, selector, content, &cell_source)
+            .map(|reason| (Some(index), reason))
+    }))
+}
@@ -0,0 +1,107 @@
+//! View staged diff tool for inspecting the complete diff of a staged operation.
+//!
+//! This module implements the `view_staged_diff` MCP tool which renders the diff of
+//! the currently staged operation without the size cap applied to previews. Features include:
+//! - Full diff output for edits whose preview was summarized
+//! - Optional diff rendering overrides
+//! - Text or structured JSON results
+//! - Leaves the staged operation unchanged
+//! - Warns when the target file changed on disk after the operation was staged
+//! - Shows the diff of every file of a staged codemod
+
+use crate::editor::{DiffOptions, ResultFormat};
+use crate::error::SemanticEditError;
+use crate::state::SemanticEditTools;
+use crate::tools::ToolHelpers;
+use crate::tools::helpers::render;
+use crate::tools::run_codemod::CodemodReport;
+use anyhow::Result;
+use mcplease::traits::{Tool, WithExamples};
+use mcplease::types::Example;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Show the complete diff of the currently staged operation or codemod
+///
+/// Previews of very large edits are summarized; use this to see every changed line.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename = "view_staged_diff")]
+pub struct ViewStagedDiff {
+    /// Optional diff rendering overrides. Defaults to the options the operation was staged
+    /// with. The diff is never capped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff_options: Option<DiffOptions>,
+
+    /// Result format: `text` (default) for a human-readable preview, or `json` for a
+    /// structured report with diff hunks, target range, and efficiency metrics
+    #[serde(default, skip_serializing_if = "ResultFormat::is_text")]
+    pub format: ResultFormat,
+}
+
+impl WithExamples for ViewStagedDiff {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Show the full diff of the staged operation",
+                item: Self {
+                    diff_options: None,
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "The preview summarized a long diff; review it side by side with \
+                              line numbers before committing",
+                item: Self {
+                    diff_options: Some(DiffOptions {
+                        line_numbers: true,
+                        side_by_side: true,
+                        ..DiffOptions::default()
+                    }),
+                    format: ResultFormat::Text,
+                },
+            },
+            Example {
+                description: "Get the staged diff as structured hunks",
+                item: Self {
+                    diff_options: None,
+                    format: ResultFormat::Json,
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<SemanticEditTools> for ViewStagedDiff {
+    fn execute(self, state: &mut SemanticEditTools) -> Result<String> {
+        let Self {
+            diff_options,
+            format,
+        } = self;
+
+        if let Some(staged_codemod) = state.get_staged_codemod(None)? {
+            let mut report = CodemodReport::from_staged(
+                &staged_codemod,
+                &state.session_file_operations(None)?,
+                &diff_options.unwrap_or_default().uncapped(),
+            )?;
+            report.staged = true;
+            return render(&report, format);
+        }
+
+        let staged_operation = state
+            .get_staged_operation(None)?
+            .ok_or_else(|| anyhow::Error::from(SemanticEditError::OperationNotStaged))?;
+        let diff_options = diff_options
+            .unwrap_or(staged_operation.diff_options)
+            .uncapped();
+
+        let dirty = staged_operation.dirty.clone();
+        let editor = state
+            .create_editor_from_operation(staged_operation)?
+            .with_diff_options(diff_options);
+        let (mut report, _) = editor.preview_report()?;
+        report.target_changed = state.staged_target_changed(None)?;
+        report.dirty = dirty;
+        render(&report, format)
+    }
+}
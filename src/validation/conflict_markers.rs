@@ -0,0 +1,76 @@
+//! Merge conflict marker detection.
+//!
+//! Unresolved `<<<<<<<`/`=======`/`>>>>>>>` markers from a git merge make most languages fail
+//! to parse, and the resulting syntax error dump leads agents to "fix" the markers as if they
+//! were code. Conflicts are detected before syntax errors are reported so they can be described
+//! for what they are.
+
+use super::{Severity, SourcePoint, Violation};
+
+/// Rule id reported for merge conflict markers
+pub const CONFLICT_MARKER_RULE: &str = "conflict_marker";
+
+/// The markers of a conflict: its start, the optional base section, the separator, and its end
+const START: &str = "<<<<<<<";
+const BASE: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const END: &str = ">>>>>>>";
+
+/// The 0-based rows of every marker line in complete conflicts, which open with `<<<<<<<` and
+/// close with `>>>>>>>`, in order
+///
+/// A lone `=======` is left alone, since it is also a Markdown heading underline.
+pub fn conflict_marker_rows(source: &str) -> Vec<usize> {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut rows = Vec::new();
+    let mut row = 0;
+    while row < lines.len() {
+        if !is_marker(lines[row], START) {
+            row += 1;
+            continue;
+        }
+        let Some(end) = (row + 1..lines.len()).find(|&end| is_marker(lines[end], END)) else {
+            break;
+        };
+        rows.push(row);
+        rows.extend(
+            (row + 1..end)
+                .filter(|&inner| is_marker(lines[inner], BASE) || lines[inner].trim() == SEPARATOR),
+        );
+        rows.push(end);
+        row = end + 1;
+    }
+    rows
+}
+
+/// One violation per marker line in `rows`
+pub fn conflict_marker_violations(source: &str, rows: &[usize]) -> Vec<Violation> {
+    let lines = source.lines().collect::<Vec<_>>();
+    rows.iter()
+        .map(|&row| Violation {
+            rule: CONFLICT_MARKER_RULE.to_string(),
+            severity: Severity::Error,
+            message: "Unresolved merge conflict marker".to_string(),
+            suggestion: Some(
+                "Keep the intended side of the conflict and remove its marker lines".to_string(),
+            ),
+            start: SourcePoint {
+                line: row + 1,
+                column: 1,
+            },
+            end: SourcePoint {
+                line: row + 1,
+                column: lines.get(row).map_or(0, |line| line.len()) + 1,
+            },
+        })
+        .collect()
+}
+
+/// Whether a line is a marker: the marker alone, or followed by a space and a label
+///
+/// Markers pasted into indented code are indented too, so leading whitespace is ignored.
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.trim_start()
+        .strip_prefix(marker)
+        .is_some_and(|rest| rest.trim_end().is_empty() || rest.starts_with(' '))
+}
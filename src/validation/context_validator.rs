@@ -10,11 +10,14 @@
 
 use tree_sitter::{Node, Query, QueryCursor, StreamingIterator, Tree};
 
+use super::{Severity, Violation};
+
 /// Tree-sitter based context validator for semantic code editing
 pub struct ContextValidator;
 
 #[derive(Debug)]
 pub struct ValidationResult<'tree, 'source> {
+    /// Whether there are no error-severity violations; warnings and info notes do not count
     pub is_valid: bool,
     pub violations: Vec<ContextViolation<'tree>>,
     pub source_code: &'source str,
@@ -23,8 +26,12 @@ pub struct ValidationResult<'tree, 'source> {
 #[derive(Debug)]
 pub struct ContextViolation<'tree> {
     pub node: Node<'tree>,
+    /// The query capture that matched, e.g. `invalid.function.in.struct.fields`
+    pub rule: String,
+    /// Severity from the capture prefix: `invalid.*`, `warning.*`, or `info.*`
+    pub severity: Severity,
     pub message: String, // Human-readable error
-    pub suggestion: &'static str,
+    pub suggestion: String,
 }
 
 impl ContextValidator {
@@ -34,24 +41,59 @@ impl ContextValidator {
         query: &Query,
         source_code: &'source str,
     ) -> ValidationResult<'tree, 'source> {
-        // Run validation queries against the temporary tree
-        let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(query, tree.root_node(), source_code.as_bytes());
+        Self::validate_queries(tree, [query], source_code)
+    }
 
+    /// Validate against several queries, such as the built-in rules plus project rules
+    pub fn validate_queries<'tree, 'source, 'query>(
+        tree: &'tree Tree,
+        queries: impl IntoIterator<Item = &'query Query>,
+        source_code: &'source str,
+    ) -> ValidationResult<'tree, 'source> {
         let mut violations = Vec::new();
 
-        while let Some(m) = matches.next() {
-            for capture in m.captures {
-                let node = capture.node;
+        for query in queries {
+            // Run validation queries against the temporary tree
+            let mut cursor = QueryCursor::new();
+            let mut matches = cursor.matches(query, tree.root_node(), source_code.as_bytes());
 
-                // Extract violation type from capture name
-                if let Some(violation_type) = Self::extract_violation_type(capture.index, query) {
-                    // Only process "invalid" captures
-                    if violation_type.starts_with("invalid.") {
+            while let Some(m) = matches.next() {
+                // Patterns can override the message and suggestion with #set!
+                let property = |key: &str| {
+                    query
+                        .property_settings(m.pattern_index)
+                        .iter()
+                        .find(|property| &*property.key == key)
+                        .and_then(|property| property.value.as_deref())
+                        .map(str::to_string)
+                };
+
+                for capture in m.captures {
+                    let node = capture.node;
+
+                    // Extract violation type from capture name
+                    let Some(violation_type) = Self::extract_violation_type(capture.index, query)
+                    else {
+                        continue;
+                    };
+
+                    // Only process rule captures (invalid.*, warning.*, info.*), once per node
+                    let Some(severity) = Severity::of_rule(&violation_type) else {
+                        continue;
+                    };
+                    let duplicate = violations.iter().any(|violation: &ContextViolation| {
+                        violation.node == node && violation.rule == violation_type
+                    });
+                    if !duplicate {
                         violations.push(ContextViolation {
                             node,
-                            message: Self::get_violation_message(&violation_type),
-                            suggestion: Self::get_violation_suggestion(&violation_type),
+                            severity,
+                            message: property("message")
+                                .unwrap_or_else(|| Self::get_violation_message(&violation_type)),
+                            suggestion: property("suggestion").unwrap_or_else(|| {
+                                Self::get_violation_suggestion(&violation_type).to_string()
+                            }),
+                            rule: violation_type,
                         });
                     }
                 }
@@ -59,7 +101,9 @@ impl ContextValidator {
         }
 
         ValidationResult {
-            is_valid: violations.is_empty(),
+            is_valid: !violations
+                .iter()
+                .any(|violation| violation.severity.is_error()),
             source_code,
             violations,
         }
@@ -104,6 +148,46 @@ impl ContextValidator {
                 "Items cannot be nested inside other items".to_string()
             }
             "invalid.expression.as.type" => "Expressions cannot be used as types".to_string(),
+            "invalid.import.after.declarations" => {
+                "Imports must come before all other declarations".to_string()
+            }
+            "invalid.entrypoint.with.parameters" => {
+                "init, and main in package main, cannot take parameters".to_string()
+            }
+            "invalid.entrypoint.with.results" => {
+                "init, and main in package main, cannot return values".to_string()
+            }
+            "invalid.init.call" => "init functions cannot be called explicitly".to_string(),
+            "invalid.method.outside.class" => {
+                "Methods must be declared inside a type body".to_string()
+            }
+            "invalid.import.in.function.body" => {
+                "Imports must be at the top of the file".to_string()
+            }
+            "invalid.package.not.first" => {
+                "The package declaration must come first in the file".to_string()
+            }
+            "invalid.type.as.statement.body" => {
+                "Local type declarations must be inside a block".to_string()
+            }
+            "invalid.using.in.type.body" => {
+                "Using directives cannot be placed inside type bodies".to_string()
+            }
+            "invalid.using.after.declarations" => {
+                "Using directives must come before all other declarations".to_string()
+            }
+            "invalid.namespace.in.type.body" => {
+                "Namespaces cannot be declared inside type bodies".to_string()
+            }
+            "invalid.statement.in.jsx" => {
+                "Statements inside JSX children are rendered as text".to_string()
+            }
+            "invalid.hook.outside.component" => {
+                "Hooks can only be called inside function components or custom hooks".to_string()
+            }
+            "invalid.hook.in.class.component" => {
+                "Hooks cannot be called from class component methods".to_string()
+            }
             _ => format!(
                 "Invalid placement: {}",
                 violation_type
@@ -123,12 +207,53 @@ impl ContextValidator {
             | "invalid.trait.in.function.body" => "Move this to module level",
 
             "invalid.use.in.item.body" => "Move use declarations to the top of the file",
+            "invalid.import.after.declarations" => {
+                "Move the import into the import block after the package clause"
+            }
+            "invalid.entrypoint.with.parameters" | "invalid.entrypoint.with.results" => {
+                "Read arguments with os.Args and report results with os.Exit"
+            }
+            "invalid.init.call" => "Move the shared logic into a separately named function",
+            "invalid.method.outside.class" => "Move the method into a class or other type body",
+            "invalid.import.in.function.body"
+            | "invalid.using.in.type.body"
+            | "invalid.using.after.declarations" => "Move this to the top of the file",
+            "invalid.package.not.first" => "Move the package declaration to the top of the file",
+            "invalid.type.as.statement.body" => "Wrap the declaration in a block",
+            "invalid.namespace.in.type.body" => "Move the namespace to the top level",
+            "invalid.statement.in.jsx" => {
+                "Move the statement above the return, or wrap an expression in {}"
+            }
+            "invalid.hook.outside.component" | "invalid.hook.in.class.component" => {
+                "Call the hook at the top level of a function component"
+            }
             _ => "Consider placing this construct in an appropriate context",
         }
     }
 }
 
+impl ContextViolation<'_> {
+    pub fn to_violation(&self) -> Violation {
+        Violation {
+            rule: self.rule.clone(),
+            severity: self.severity,
+            message: self.message.clone(),
+            suggestion: Some(self.suggestion.clone()),
+            start: self.node.start_position().into(),
+            end: self.node.end_position().into(),
+        }
+    }
+}
+
 impl ValidationResult<'_, '_> {
+    /// Serializable form of the violations
+    pub fn to_violations(&self) -> Vec<Violation> {
+        self.violations
+            .iter()
+            .map(ContextViolation::to_violation)
+            .collect()
+    }
+
     /// Find the nearest UTF-8 character boundary
     fn find_utf8_boundary(&self, byte_pos: usize, search_backward: bool) -> usize {
         let bytes = self.source_code.as_bytes();
@@ -150,15 +275,27 @@ impl ValidationResult<'_, '_> {
     }
 
     pub fn format_errors(&self) -> String {
-        if self.is_valid {
+        if self.violations.is_empty() {
             return "✅ All validations passed".to_string();
         }
 
         let mut response = String::new();
-        response.push_str("❌ Invalid placement detected:\n\n");
+        if self.is_valid {
+            response.push_str("⚠️ Validation warnings:\n\n");
+        } else {
+            response.push_str("❌ Invalid placement detected:\n\n");
+        }
 
         for violation in &self.violations {
-            response.push_str(&format!("• {}:\n", violation.message));
+            let severity = match violation.severity {
+                Severity::Error => String::new(),
+                severity => format!("{}: ", severity.as_str()),
+            };
+            response.push_str(&format!(
+                "• {severity}{} at {}:\n",
+                violation.message,
+                violation.to_violation().location()
+            ));
             let parent = violation.node.parent().unwrap_or(violation.node);
 
             // Safe UTF-8 string slicing using byte_range()
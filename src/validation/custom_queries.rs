@@ -0,0 +1,94 @@
+//! Project-supplied validation queries.
+//!
+//! Projects can enforce house rules at edit time (no added `unwrap()`, no `console.log`)
+//! by adding tree-sitter query files under `.semantic-edit/queries/<language>/*.scm` in the
+//! session context directory. Features include:
+//! - Compilation when the context is set, so broken queries are reported immediately
+//! - `invalid.*` captures reported like the built-in rules, and advisory `warning.*` and
+//!   `info.*` captures that are reported without refusing the edit
+//! - Per-pattern `message` and `suggestion` properties set with `#set!`
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tree_sitter::Query;
+
+use crate::error::SemanticEditError;
+use crate::languages::queries::describe_error;
+use crate::languages::{LanguageName, LanguageRegistry};
+
+/// Directory, relative to the session context, that holds per-language query files
+pub const CUSTOM_QUERY_DIR: &str = ".semantic-edit/queries";
+
+/// Compiled custom validation queries for a project, keyed by language
+#[derive(Debug, Default)]
+pub struct CustomQueries {
+    queries: HashMap<LanguageName, Vec<Query>>,
+    /// Each language's query files and their source, in the same order as `queries`
+    sources: HashMap<LanguageName, Vec<(PathBuf, String)>>,
+}
+
+impl CustomQueries {
+    /// Load and compile every query file under `root`, failing on the first invalid one
+    pub fn load(root: &Path, registry: &LanguageRegistry) -> Result<Self> {
+        let mut queries = HashMap::new();
+        let mut sources = HashMap::new();
+
+        for name in registry.language_names() {
+            let dir = root.join(CUSTOM_QUERY_DIR).join(name.to_string());
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            // only languages the project has queries for are built
+            let language = registry.get_language(name)?;
+
+            let mut paths = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "scm"))
+                .collect::<Vec<_>>();
+            paths.sort();
+
+            for path in paths {
+                let source = fs::read_to_string(&path)?;
+                let query =
+                    Query::new(language.tree_sitter_language(), &source).map_err(|error| {
+                        SemanticEditError::InvalidCustomQuery {
+                            path: path.display().to_string(),
+                            message: describe_error(&source, &error),
+                        }
+                    })?;
+                queries
+                    .entry(language.name())
+                    .or_insert_with(Vec::new)
+                    .push(query);
+                sources
+                    .entry(language.name())
+                    .or_insert_with(Vec::new)
+                    .push((path, source));
+            }
+        }
+
+        Ok(Self { queries, sources })
+    }
+
+    /// Custom queries for a language, if any
+    pub fn for_language(&self, language: LanguageName) -> &[Query] {
+        self.queries.get(&language).map_or(&[], Vec::as_slice)
+    }
+
+    /// The query files for a language and their source, if any
+    pub fn sources_for_language(&self, language: LanguageName) -> &[(PathBuf, String)] {
+        self.sources.get(&language).map_or(&[], Vec::as_slice)
+    }
+
+    /// Total number of compiled queries across languages
+    pub fn len(&self) -> usize {
+        self.queries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
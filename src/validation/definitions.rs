@@ -0,0 +1,130 @@
+//! Duplicate definition detection.
+//!
+//! Agents sometimes insert a second copy of a function instead of replacing the first. Each
+//! language's definitions query captures definitions as `@definition.<kind>` with their name
+//! as `@name`, and definitions of the same kind and name under the same parent node are
+//! reported as duplicates. Optional `@qualifier` captures (parameter lists, receivers,
+//! attributes, accessor keywords) tell overloads and conditionally compiled variants apart.
+//!
+//! The same captures locate named symbols for partial reads in `open_files`.
+
+use std::collections::{BTreeSet, HashMap};
+
+use tree_sitter::{Node, Query, QueryCursor, StreamingIterator, Tree};
+
+use super::{ContextViolation, Severity};
+
+/// Rule id reported for duplicate definitions
+pub const DUPLICATE_DEFINITION_RULE: &str = "invalid.duplicate.definition";
+
+/// Finds definitions that share a name with another definition in the same scope
+pub struct DefinitionValidator;
+
+/// A definition captured by a language's definitions query
+#[derive(Debug)]
+pub struct Definition<'tree> {
+    pub node: Node<'tree>,
+    /// Capture suffix, e.g. `function` for `@definition.function`
+    pub kind: String,
+    pub name: String,
+    pub qualifiers: BTreeSet<String>,
+}
+
+impl DefinitionValidator {
+    /// Every definition in the tree, in the order the query matched them
+    pub fn definitions<'tree>(
+        tree: &'tree Tree,
+        query: &Query,
+        source_code: &str,
+    ) -> Vec<Definition<'tree>> {
+        let text = |node: Node| {
+            source_code
+                .get(node.byte_range())
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        // A definition can match several patterns, e.g. with and without its attributes
+        let mut definitions: Vec<Definition<'tree>> = Vec::new();
+        let mut indices = HashMap::<usize, usize>::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, tree.root_node(), source_code.as_bytes());
+        while let Some(m) = matches.next() {
+            let mut definition = None;
+            let mut name = None;
+            let mut qualifiers = BTreeSet::new();
+            for capture in m.captures {
+                let capture_name = query.capture_names()[capture.index as usize];
+                if let Some(kind) = capture_name.strip_prefix("definition.") {
+                    definition = Some((capture.node, kind));
+                } else if capture_name == "name" {
+                    name = Some(text(capture.node));
+                } else if capture_name == "qualifier" {
+                    qualifiers.insert(text(capture.node));
+                }
+            }
+
+            let (Some((node, kind)), Some(name)) = (definition, name) else {
+                continue;
+            };
+            match indices.get(&node.id()) {
+                Some(&index) => definitions[index].qualifiers.extend(qualifiers),
+                None => {
+                    indices.insert(node.id(), definitions.len());
+                    definitions.push(Definition {
+                        node,
+                        kind: kind.to_string(),
+                        name,
+                        qualifiers,
+                    });
+                }
+            }
+        }
+        definitions
+    }
+
+    /// Report every definition that has a duplicate in the same scope
+    pub fn find_duplicates<'tree>(
+        tree: &'tree Tree,
+        query: &Query,
+        source_code: &str,
+    ) -> Vec<ContextViolation<'tree>> {
+        let definitions = Self::definitions(tree, query, source_code);
+        let mut scopes: HashMap<_, Vec<&Definition>> = HashMap::new();
+        for definition in &definitions {
+            let Some(parent) = definition.node.parent() else {
+                continue;
+            };
+            scopes
+                .entry((
+                    parent.id(),
+                    &definition.kind,
+                    &definition.name,
+                    &definition.qualifiers,
+                ))
+                .or_default()
+                .push(definition);
+        }
+
+        let mut violations = scopes
+            .into_values()
+            .filter(|duplicates| duplicates.len() > 1)
+            .flatten()
+            .map(|definition| ContextViolation {
+                node: definition.node,
+                rule: DUPLICATE_DEFINITION_RULE.to_string(),
+                severity: Severity::Error,
+                message: format!(
+                    "`{}` is defined more than once in the same scope",
+                    definition.name
+                ),
+                suggestion: "Replace the existing definition instead of adding another one"
+                    .to_string(),
+            })
+            .collect::<Vec<_>>();
+        violations.sort_by_key(|violation| violation.node.start_byte());
+        violations
+    }
+}
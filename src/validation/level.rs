@@ -0,0 +1,31 @@
+//! Validation strictness levels.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How strictly edits are validated before they can be staged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationLevel {
+    /// Refuse edits that introduce syntax errors or break context rules (the default)
+    #[default]
+    Strict,
+    /// Stage invalid edits with a prominent warning instead of refusing them
+    Warn,
+    /// Skip syntax and context validation entirely
+    Off,
+}
+
+impl ValidationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationLevel::Strict => "strict",
+            ValidationLevel::Warn => "warn",
+            ValidationLevel::Off => "off",
+        }
+    }
+
+    pub fn is_strict(&self) -> bool {
+        matches!(self, ValidationLevel::Strict)
+    }
+}
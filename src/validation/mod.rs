@@ -8,6 +8,13 @@
 //! ## Key Components
 //!
 //! - [`ContextValidator`]: Validates edits against language-specific semantic rules
+//! - [`DefinitionValidator`]: Detects definitions an edit duplicates in the same scope
+//! - [`conflict_marker_rows`]: Finds unresolved merge conflict markers, reported ahead of the
+//!   syntax errors they cause
+//! - [`ValidationLevel`]: Per-session strictness (strict, warn, or off)
+//! - [`RuleSettings`]: Per-session overrides that disable or downgrade individual rules
+//! - [`Violation`]: Serializable syntax errors and rule violations for tool output
+//! - [`CustomQueries`]: Project-supplied validation queries from `.semantic-edit/queries`
 //!
 //! ## Validation Types
 //!
@@ -18,8 +25,23 @@
 //! ## Features
 //!
 //! - **Pre-edit validation**: Prevents invalid operations before applying changes
-//! - **Custom validation queries**: Tree-sitter queries for semantic rules
+//! - **Custom validation queries**: Tree-sitter queries for semantic rules, including
+//!   project-supplied house rules
 //! - **Error reporting**: Detailed error messages with line numbers
 
+mod conflict_markers;
 mod context_validator;
-pub use context_validator::ContextValidator;
+mod custom_queries;
+mod definitions;
+mod level;
+mod rules;
+mod violation;
+pub use conflict_markers::{
+    CONFLICT_MARKER_RULE, conflict_marker_rows, conflict_marker_violations,
+};
+pub use context_validator::{ContextValidator, ContextViolation, ValidationResult};
+pub use custom_queries::{CUSTOM_QUERY_DIR, CustomQueries};
+pub use definitions::{DUPLICATE_DEFINITION_RULE, Definition, DefinitionValidator};
+pub use level::ValidationLevel;
+pub use rules::{RuleOverride, RuleSetting, RuleSettings};
+pub use violation::{SYNTAX_RULE, Severity, SourcePoint, Violation};
@@ -0,0 +1,82 @@
+//! Per-rule validation settings.
+//!
+//! A single overly aggressive context rule should not force a session to turn validation
+//! off entirely. Rules are addressed by their query capture name and can be disabled or
+//! downgraded from refusing the edit to a warning.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::Severity;
+
+/// How a single context validation rule is enforced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSetting {
+    /// Refuse edits that violate the rule (the default)
+    #[default]
+    Error,
+    /// Accept edits that violate the rule, reporting the violation as a warning
+    Warn,
+    /// Ignore the rule
+    Off,
+}
+
+impl RuleSetting {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleSetting::Error => "error",
+            RuleSetting::Warn => "warn",
+            RuleSetting::Off => "off",
+        }
+    }
+}
+
+/// The setting for a single rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RuleOverride {
+    /// Rule name as reported in validation errors, e.g. `invalid.use.in.function.body`
+    pub rule: String,
+    pub setting: RuleSetting,
+}
+
+/// Rule settings keyed by capture name, e.g. `invalid.use.in.function.body`
+///
+/// Rules without an entry are enforced according to their severity: `invalid.*` rules
+/// refuse the edit and `warning.*` and `info.*` rules only warn.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct RuleSettings(BTreeMap<String, RuleSetting>);
+
+impl RuleSettings {
+    /// How a rule with the given severity is enforced
+    pub fn setting(&self, rule: &str, severity: Severity) -> RuleSetting {
+        self.0.get(rule).copied().unwrap_or(if severity.is_error() {
+            RuleSetting::Error
+        } else {
+            RuleSetting::Warn
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, RuleSetting)> {
+        self.0
+            .iter()
+            .map(|(rule, setting)| (rule.as_str(), *setting))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<RuleOverride> for RuleSettings {
+    fn from_iter<T: IntoIterator<Item = RuleOverride>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|RuleOverride { rule, setting }| (rule, setting))
+                .collect(),
+        )
+    }
+}
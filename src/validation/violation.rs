@@ -0,0 +1,96 @@
+//! Serializable validation violations.
+//!
+//! This module provides a structured form of validation failures so programmatic MCP
+//! clients can render violations and decide how to react without parsing text.
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// Rule id reported for syntax errors
+pub const SYNTAX_RULE: &str = "syntax";
+
+/// How serious a violation is, taken from the prefix of the query capture that reported it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// `invalid.*` captures and syntax errors, which refuse the edit
+    #[default]
+    Error,
+    /// `warning.*` captures, reported without refusing the edit
+    Warning,
+    /// `info.*` captures, advisory notes reported without refusing the edit
+    Info,
+}
+
+impl Severity {
+    /// Severity of a capture name, or None for captures that are not rules
+    pub fn of_rule(rule: &str) -> Option<Self> {
+        match rule.split_once('.')?.0 {
+            "invalid" => Some(Severity::Error),
+            "warning" => Some(Severity::Warning),
+            "info" => Some(Severity::Info),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Severity::Error)
+    }
+}
+
+/// A 1-based line and column position in source, with the column counted in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcePoint {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for SourcePoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl From<tree_sitter::Point> for SourcePoint {
+    fn from(point: tree_sitter::Point) -> Self {
+        Self {
+            line: point.row + 1,
+            column: point.column + 1,
+        }
+    }
+}
+
+/// A single syntax error or context rule violation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    /// The rule that was violated: `syntax`, or a context query capture such as
+    /// `invalid.function.in.struct.fields`
+    pub rule: String,
+    #[serde(default)]
+    pub severity: Severity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    pub start: SourcePoint,
+    pub end: SourcePoint,
+}
+
+impl Violation {
+    /// `line:column-line:column` range, collapsed to `line:column` for empty ranges
+    pub fn location(&self) -> String {
+        if self.start == self.end {
+            self.start.to_string()
+        } else {
+            format!("{}-{}", self.start, self.end)
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! # File watching
+//!
+//! Watches session contexts for changes made outside the server, so long-running
+//! instances do not keep serving stale data:
+//!
+//! - Cached source files and syntax trees are evicted as soon as their file changes
+//! - Staged operations whose target file changed are flagged, so `view_staged_diff` can
+//!   warn before `commit_staged` refuses them
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::state::SourceCache;
+
+/// Files that changed on disk, by canonical path
+#[derive(Debug, Default)]
+pub struct ChangedFiles(Mutex<HashSet<PathBuf>>);
+
+impl ChangedFiles {
+    /// Whether the file changed since it was last cleared
+    pub fn contains(&self, path: &Path) -> bool {
+        self.0.lock().is_ok_and(|paths| paths.contains(path))
+    }
+
+    /// Start tracking a file afresh, e.g. when an operation on it is staged
+    pub fn clear(&self, path: &Path) {
+        if let Ok(mut paths) = self.0.lock() {
+            paths.remove(path);
+        }
+    }
+
+    fn insert(&self, path: PathBuf) {
+        if let Ok(mut paths) = self.0.lock() {
+            paths.insert(path);
+        }
+    }
+}
+
+/// Recursively watches a session context until dropped
+pub struct ContextWatcher {
+    root: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl std::fmt::Debug for ContextWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextWatcher")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ContextWatcher {
+    /// Watch `root`, evicting changed files from `source_cache` and recording them in `changed`
+    pub fn new(
+        root: &Path,
+        source_cache: Arc<SourceCache>,
+        changed: Arc<ChangedFiles>,
+    ) -> Result<Self> {
+        // events report paths under the watched path, so watch the canonical one to match
+        // the canonical paths that files are cached and staged under
+        let root = root.canonicalize()?;
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let content_may_have_changed = match event.kind {
+                EventKind::Modify(ModifyKind::Metadata(_)) | EventKind::Access(_) => false,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => true,
+                EventKind::Any | EventKind::Other => true,
+            };
+            if !content_may_have_changed {
+                return;
+            }
+            for path in event.paths {
+                // a poisoned cache is reported by the next tool call that uses it
+                let _ = source_cache.invalidate(&path);
+                changed.insert(path);
+            }
+        })?;
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+        Ok(Self {
+            root,
+            _watcher: watcher,
+        })
+    }
+}
@@ -0,0 +1,122 @@
+//! Tests for the `add_attribute` operation
+
+mod common;
+
+use anyhow::Result;
+use common::apply_operation;
+
+/// The file after adding `attribute` at `anchor`, or the preview if nothing was staged
+fn add_attribute(file_name: &str, source: &str, anchor: &str, attribute: &str) -> Result<String> {
+    apply_operation(file_name, source, "add_attribute", anchor, attribute)
+}
+
+#[test]
+fn rust_derives_merge_into_the_existing_derive() -> Result<()> {
+    let source = "\
+/// Settings
+#[derive(Debug)]
+#[serde(rename_all = \"snake_case\")]
+pub struct Config {
+    name: String,
+}
+";
+    assert_eq!(
+        add_attribute("config.rs", source, "name: String", "derive(Clone, Debug)")?,
+        "\
+/// Settings
+#[derive(Debug, Clone)]
+#[serde(rename_all = \"snake_case\")]
+pub struct Config {
+    name: String,
+}
+"
+    );
+
+    let error = add_attribute("config.rs", source, "pub struct Config", "#[derive(Debug)]")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("already has"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn rust_attributes_go_on_the_enclosing_item() -> Result<()> {
+    let source = "\
+mod app {
+    #[inline]
+    fn run() {
+        start();
+    }
+}
+";
+    assert_eq!(
+        add_attribute("lib.rs", source, "start();", "#[must_use]")?,
+        "\
+mod app {
+    #[inline]
+    #[must_use]
+    fn run() {
+        start();
+    }
+}
+"
+    );
+    // derives are only for structs, enums, and unions
+    let error = add_attribute("lib.rs", source, "fn run", "#[derive(Debug)]")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("no struct, enum, or union"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn python_and_typescript_decorators_are_added_once() -> Result<()> {
+    let python = "\
+class Store:
+    @staticmethod
+    def load(path):
+        return open(path)
+";
+    assert_eq!(
+        add_attribute("store.py", python, "def load", "functools.cache")?,
+        "\
+class Store:
+    @functools.cache
+    @staticmethod
+    def load(path):
+        return open(path)
+"
+    );
+    let error = add_attribute("store.py", python, "return open", "@staticmethod")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("already decorated with @staticmethod"),
+        "{error}"
+    );
+
+    let typescript = "\
+export class Panel {
+  render() {
+    return null;
+  }
+}
+";
+    assert_eq!(
+        add_attribute(
+            "panel.ts",
+            typescript,
+            "class Panel",
+            "@Component({ selector: \"panel\" })"
+        )?,
+        "\
+@Component({ selector: \"panel\" })
+export class Panel {
+  render() {
+    return null;
+  }
+}
+"
+    );
+    Ok(())
+}
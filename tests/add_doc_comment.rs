@@ -0,0 +1,123 @@
+//! Tests for the `add_doc_comment` operation
+
+mod common;
+
+use anyhow::Result;
+use common::apply_operation;
+
+/// The file after documenting the declaration at `anchor`, or the preview if nothing was
+/// staged
+fn add_doc_comment(file_name: &str, source: &str, anchor: &str, text: &str) -> Result<String> {
+    apply_operation(file_name, source, "add_doc_comment", anchor, text)
+}
+
+#[test]
+fn rust_docs_go_above_attributes() -> Result<()> {
+    let source = "\
+impl Store {
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        todo!()
+    }
+}
+";
+    assert_eq!(
+        add_doc_comment(
+            "store.rs",
+            source,
+            // an anchor on an attribute documents the item it belongs to
+            "#[must_use]",
+            "Load the store saved at `path`\n\nPanics if it is corrupt"
+        )?,
+        "\
+impl Store {
+    /// Load the store saved at `path`
+    ///
+    /// Panics if it is corrupt
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        todo!()
+    }
+}
+"
+    );
+
+    let documented = "/// Settings\n#[derive(Debug)]\nstruct Config;\n";
+    let error = add_doc_comment("config.rs", documented, "struct Config", "Configuration")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("already documented"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn python_docstrings_go_inside_the_body() -> Result<()> {
+    let source = "\
+class Store:
+    @staticmethod
+    def load(path):
+        return open(path)
+";
+    assert_eq!(
+        add_doc_comment("store.py", source, "return open", "Load the store at path.")?,
+        "\
+class Store:
+    @staticmethod
+    def load(path):
+        \"\"\"Load the store at path.\"\"\"
+        return open(path)
+"
+    );
+
+    let documented = "def load(path):\n    \"\"\"Load.\"\"\"\n    return open(path)\n";
+    let error = add_doc_comment("store.py", documented, "def load", "Load the store.")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("already documented"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn jsdoc_goes_above_decorators_and_export() -> Result<()> {
+    let source = "\
+@Component({ selector: \"panel\" })
+export class Panel {
+  render(): null {
+    return null;
+  }
+}
+";
+    assert_eq!(
+        add_doc_comment("panel.ts", source, "export class Panel", "A panel")?,
+        "\
+/** A panel */
+@Component({ selector: \"panel\" })
+export class Panel {
+  render(): null {
+    return null;
+  }
+}
+"
+    );
+    assert_eq!(
+        add_doc_comment(
+            "panel.ts",
+            source,
+            "render()",
+            "Render nothing\n@returns null"
+        )?,
+        "\
+@Component({ selector: \"panel\" })
+export class Panel {
+  /**
+   * Render nothing
+   * @returns null
+   */
+  render(): null {
+    return null;
+  }
+}
+"
+    );
+    Ok(())
+}
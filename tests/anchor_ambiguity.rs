@@ -0,0 +1,195 @@
+//! Tests for the candidates listed when an anchor matches more than once
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{AnchorMatch, Editor, PreviewReport, ResultFormat};
+use semantic_code_edit_mcp::error::SemanticEditError;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{RetargetStaged, StageOperation};
+use serde_json::Value;
+use tempfile::TempDir;
+
+const SOURCE: &str = "\
+fn value() -> u32 {
+    1
+}
+
+fn main() {
+    let value = value();
+    println!(\"{value}\");
+}
+";
+
+fn preview(anchor: &str, content: &str) -> Result<PreviewReport> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let selector = Selector::builder(Operation::ReplaceExact, anchor).build()?;
+    let (report, _) =
+        Editor::from_source(SOURCE, content.into(), selector, rust)?.preview_report()?;
+    Ok(report)
+}
+
+fn stage(
+    state: &mut SemanticEditTools,
+    dir: &TempDir,
+    content: &str,
+    candidate: Option<usize>,
+    show_alternatives: bool,
+) -> Result<String> {
+    StageOperation {
+        file_path: dir.path().join("main.rs").display().to_string(),
+        language: None,
+        selector: Selector::builder(Operation::ReplaceExact, "value").build()?,
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Json,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate,
+        show_alternatives,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+#[test]
+fn every_match_is_listed_with_the_chosen_one() -> Result<()> {
+    let report = preview("value", "amount")?;
+    let ambiguity = report
+        .edit
+        .ambiguity
+        .as_ref()
+        .expect("the anchor is ambiguous");
+    let lines = report
+        .edit
+        .candidates
+        .iter()
+        .map(|candidate| candidate.line)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [1, 6, 6, 7]);
+    assert_eq!(ambiguity.chosen, 0);
+    assert_eq!(
+        report.edit.candidates[1],
+        AnchorMatch {
+            index: 1,
+            line: 6,
+            node_kind: Some("identifier".into()),
+            text: "let value = value();".into(),
+        }
+    );
+
+    let text = report.to_string();
+    assert!(
+        text.contains(
+            "the anchor matches 4 locations. Editing candidate 0 on line 1 because it is the \
+first match"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains("\n→ [0] line 1 `identifier`: fn value() -> u32 {"),
+        "{text}"
+    );
+    assert!(
+        text.contains("\n  [3] line 7 `string_content`: println!(\"{value}\");"),
+        "{text}"
+    );
+
+    let report = preview("let value = value();", "let value = 2;")?;
+    assert!(report.edit.ambiguity.is_none());
+    assert!(report.edit.candidates.is_empty());
+    Ok(())
+}
+
+#[test]
+fn skipped_matches_are_explained() -> Result<()> {
+    let report = preview("value", "2")?;
+    let ambiguity = report
+        .edit
+        .ambiguity
+        .as_ref()
+        .expect("the anchor is ambiguous");
+    assert_ne!(ambiguity.chosen, 0);
+    assert!(
+        ambiguity.reason.contains("edits at line 1 were not"),
+        "{}",
+        ambiguity.reason
+    );
+    Ok(())
+}
+
+#[test]
+fn candidates_are_selected_by_index() -> Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let staged: Value = serde_json::from_str(&stage(&mut state, &dir, "amount", None, false)?)?;
+    assert_eq!(staged["ambiguity"]["chosen"], 0);
+    assert_eq!(staged["candidates"][2]["line"], 6);
+
+    let retargeted: Value = serde_json::from_str(
+        &RetargetStaged {
+            selector: Selector::builder(Operation::ReplaceExact, "value").build()?,
+            candidate: Some(2),
+            format: ResultFormat::Json,
+        }
+        .execute(&mut state)?,
+    )?;
+    assert_eq!(retargeted["ambiguity"]["chosen"], 2);
+    assert_eq!(
+        retargeted["ambiguity"]["reason"],
+        "candidate 2 was requested"
+    );
+    assert!(
+        retargeted["diff"]
+            .as_str()
+            .unwrap()
+            .contains("let value = amount();"),
+        "{retargeted}"
+    );
+
+    let missing = stage(&mut state, &dir, "amount", Some(9), false).unwrap_err();
+    assert!(
+        matches!(
+            missing.downcast_ref::<SemanticEditError>(),
+            Some(SemanticEditError::CandidateNotFound {
+                candidate: 9,
+                count: 4
+            })
+        ),
+        "{missing}"
+    );
+    Ok(())
+}
+
+#[test]
+fn alternatives_are_previewed_without_staging() -> Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let report: Value = serde_json::from_str(&stage(&mut state, &dir, "2", None, true)?)?;
+    assert_eq!(report["match_count"], 4);
+    let alternatives = report["alternatives"].as_array().unwrap();
+    assert_eq!(alternatives.len(), 4);
+    assert!(alternatives[0]["diff"].is_null(), "{report}");
+    assert_eq!(report["default_candidate"], alternatives[1]["index"]);
+    assert!(
+        alternatives[1]["diff"]
+            .as_str()
+            .unwrap()
+            .contains("+    let 2 = value();"),
+        "{report}"
+    );
+    assert!(state.get_staged_operation(None)?.is_none());
+
+    // an explicit candidate is staged as usual
+    let staged: Value = serde_json::from_str(&stage(&mut state, &dir, "amount", Some(2), true)?)?;
+    assert_eq!(staged["staged"], true);
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Tests for the near matches suggested when an anchor is not found
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const SOURCE: &str = "\
+fn main() {
+    let total = compute(first, second);
+    println!(\"{total}\");
+}
+
+fn compute(a: u32, b: u32) -> u32 {
+    a + b
+}
+";
+
+fn missing_anchor_error(anchor: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let selector = Selector::builder(Operation::ReplaceExact, anchor).build()?;
+    let error = Editor::from_source(SOURCE, "x".into(), selector, rust)
+        .err()
+        .expect("the anchor is not in the source");
+    Ok(error.to_string())
+}
+
+#[test]
+fn near_matches_are_suggested_with_line_numbers() -> Result<()> {
+    let message = missing_anchor_error("let totl = compute(first, secnd);")?;
+    assert!(
+        message.contains("Did you mean:\n  line 2: let total = compute(first, second);"),
+        "{message}"
+    );
+
+    // whitespace differences are matched exactly, and multi-line anchors by their lines
+    let message = missing_anchor_error("fn compute(a: u32,  b: u32) -> u32 {\n  a - b")?;
+    assert!(
+        message.contains("line 6: fn compute(a: u32, b: u32) -> u32 { ⏎ a + b"),
+        "{message}"
+    );
+    Ok(())
+}
+
+#[test]
+fn suggestions_are_limited_to_similar_lines() -> Result<()> {
+    let message = missing_anchor_error("impl Display for Report")?;
+    assert!(!message.contains("Did you mean"), "{message}");
+
+    let message = missing_anchor_error("compte(")?;
+    let suggested = message
+        .lines()
+        .filter(|line| line.starts_with("  line "))
+        .count();
+    assert!((1..=3).contains(&suggested), "{message}");
+    Ok(())
+}
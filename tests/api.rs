@@ -0,0 +1,73 @@
+//! Tests for the one-call library API
+
+use anyhow::Result;
+use semantic_code_edit_mcp::api;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n\nfn helper() {}\n";
+
+fn selector(operation: Operation, anchor: &str) -> Selector {
+    Selector {
+        operation,
+        anchor: anchor.to_string(),
+        end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
+    }
+}
+
+#[test]
+fn edits_are_previewed_and_applied_only_when_valid() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("main.rs");
+    fs::write(&path, SOURCE)?;
+
+    let preview = api::preview_edit(
+        &path,
+        selector(Operation::ReplaceExact, "let x = 1;"),
+        "let x = 2;",
+    )?;
+    assert!(preview.is_valid(), "{}", preview.message);
+    assert!(preview.diff.unwrap().contains("+    let x = 2;"));
+    assert_eq!(fs::read_to_string(&path)?, SOURCE);
+
+    let refused = api::apply_edit(
+        &path,
+        selector(Operation::ReplaceExact, "let x = 1;"),
+        "let x = ;",
+    )?;
+    assert!(!refused.is_valid());
+    assert_eq!(fs::read_to_string(&path)?, SOURCE);
+
+    let applied = api::apply_edit(
+        &path,
+        selector(Operation::ReplaceNode, "fn helper()"),
+        "fn helper() -> u8 {\n    0\n}",
+    )?;
+    assert!(applied.is_valid(), "{}", applied.message);
+    assert!(fs::read_to_string(&path)?.ends_with("fn helper() -> u8 {\n    0\n}\n"));
+    Ok(())
+}
+
+#[test]
+fn searches_report_anchors_and_query_captures() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("main.rs");
+    fs::write(&path, SOURCE)?;
+
+    let found = api::search(&path, "fn helper()")?;
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].line, found[0].column), (5, 1));
+    assert_eq!(found[0].node_kind.as_deref(), Some("function_item"));
+
+    let names = api::query(&path, "(function_item name: (identifier) @name)")?
+        .into_iter()
+        .map(|found| found.text)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["main", "helper"]);
+
+    assert!(api::query(&path, "(no_such_node)").is_err());
+    Ok(())
+}
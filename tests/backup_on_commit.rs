@@ -0,0 +1,153 @@
+//! Tests for the optional backup strategies applied by commit_staged
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::backup::{BACKUP_DIRECTORY, BackupStrategy};
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::filesystem::{
+    SandboxedFileOperations, StdFileOperations, TestFileOperations,
+};
+use semantic_code_edit_mcp::sandbox::PathSandbox;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const ORIGINAL: &str = "{\"key\": \"before\"}\n";
+
+fn stage_and_commit(state: &mut SemanticEditTools, file_path: &Path) -> Result<String> {
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"before\"".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)?;
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(state)
+}
+
+#[test]
+fn no_backup_by_default() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, ORIGINAL)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let message = stage_and_commit(&mut state, &file_path)?;
+
+    assert!(!message.contains("Backup"));
+    assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn sibling_backup_keeps_original_content() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, ORIGINAL)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_backup_strategy(BackupStrategy::Sibling);
+    let message = stage_and_commit(&mut state, &file_path)?;
+
+    let backup_path = temp_dir.path().join("config.json.bak");
+    assert!(message.contains("config.json.bak"));
+    assert_eq!(fs::read_to_string(backup_path)?, ORIGINAL);
+    assert!(fs::read_to_string(&file_path)?.contains("\"after\""));
+    Ok(())
+}
+
+#[test]
+fn session_directory_backup_overrides_server_default() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let context = fs::canonicalize(temp_dir.path())?;
+    fs::create_dir(context.join("src"))?;
+    let file_path = context.join("src").join("config.json");
+    fs::write(&file_path, ORIGINAL)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_backup_strategy(BackupStrategy::Sibling);
+    state.set_context(None, context.clone())?;
+    state.set_session_backup_strategy(None, Some(BackupStrategy::Directory))?;
+    stage_and_commit(&mut state, &file_path)?;
+
+    assert!(!context.join("src").join("config.json.bak").exists());
+    let timestamp_dir = fs::read_dir(context.join(BACKUP_DIRECTORY))?
+        .next()
+        .expect("one timestamped backup directory")?
+        .path();
+    assert_eq!(
+        fs::read_to_string(timestamp_dir.join("src").join("config.json"))?,
+        ORIGINAL
+    );
+    Ok(())
+}
+
+#[test]
+fn backup_directories_are_created_through_file_operations() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, ORIGINAL)?;
+
+    // a read-only sandbox refuses the backup before any directory is created
+    let sandboxed = SandboxedFileOperations::new(
+        Box::new(StdFileOperations),
+        PathSandbox::default().with_read_only(true),
+    );
+    assert!(
+        BackupStrategy::Directory
+            .backup(&sandboxed, &file_path, Some(temp_dir.path()))
+            .is_err()
+    );
+    assert!(!temp_dir.path().join(BACKUP_DIRECTORY).exists());
+
+    // in-memory file operations leave the disk alone
+    let in_memory = TestFileOperations::new().with_file(&file_path, ORIGINAL);
+    let backup_path = BackupStrategy::Directory
+        .backup(&in_memory, &file_path, Some(temp_dir.path()))?
+        .expect("a backup path");
+    assert_eq!(in_memory.file_content(&backup_path), Some(ORIGINAL.into()));
+    assert!(!temp_dir.path().join(BACKUP_DIRECTORY).exists());
+    Ok(())
+}
+
+#[test]
+fn strategy_names_parse() {
+    assert_eq!(
+        BackupStrategy::from_name("none"),
+        Some(BackupStrategy::None)
+    );
+    assert_eq!(
+        BackupStrategy::from_name("Sibling"),
+        Some(BackupStrategy::Sibling)
+    );
+    assert_eq!(
+        BackupStrategy::from_name("directory"),
+        Some(BackupStrategy::Directory)
+    );
+    assert_eq!(BackupStrategy::from_name("tarball"), None);
+}
@@ -0,0 +1,63 @@
+//! Tests for the selector and editor builders
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::filesystem::TestFileOperations;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n    let y = 2;\n}\n";
+
+#[test]
+fn selector_builder_rejects_malformed_selectors() -> Result<()> {
+    let selector = Selector::builder(Operation::ReplaceRange, "let x")
+        .with_end("let y = 2;")
+        .build()?;
+    assert_eq!(selector.end.as_deref(), Some("let y = 2;"));
+
+    let error = Selector::builder(Operation::ReplaceRange, "let x")
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("End is required"), "{error}");
+
+    let error = Selector::builder(Operation::InsertAfter, "  ")
+        .build()
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("`anchor` cannot be empty"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn editor_builder_edits_files_or_source() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let selector = Selector::builder(Operation::ReplaceExact, "let x = 1;").build()?;
+
+    let file_operations = TestFileOperations::new().with_file("/project/main.rs", SOURCE);
+    let output = Editor::builder("let x = 3;", selector.clone(), rust)
+        .with_file_path("/project/main.rs")
+        .with_file_operations(&file_operations)
+        .with_format_mode(FormatMode::Never)
+        .build()?
+        .commit_to_string()?;
+    assert_eq!(output, "fn main() {\n    let x = 3;\n    let y = 2;\n}\n");
+
+    let output = Editor::builder("let x = 4;", selector.clone(), rust)
+        .with_source(SOURCE)
+        .build()?
+        .commit_to_string()?;
+    assert!(output.contains("let x = 4;"), "{output}");
+
+    let error = Editor::builder("let x = 4;", selector, rust)
+        .build()
+        .err()
+        .unwrap();
+    assert!(
+        error.to_string().contains("file path or source code"),
+        "{error}"
+    );
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! Tests for formatting C, C++, and Java with clang-format or google-java-format
+
+#![cfg(all(unix, feature = "c", feature = "cpp", feature = "java"))]
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::java::JAVA_FORMATTER_ENV;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A stand-in for a formatter that records its name and arguments above the source
+const FAKE_FORMATTER: &str = "#!/bin/sh\necho \"// $(basename \"$0\") $*\"\ncat\n";
+
+fn edit(registry: &LanguageRegistry, file_path: &Path, language: LanguageName) -> Result<String> {
+    let editor = Editor::new(
+        "\n    int y = 2;".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "int x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(language)?,
+        file_path.to_path_buf(),
+        None,
+    )?;
+    editor.commit_to_string()
+}
+
+#[test]
+fn c_family_languages_are_formatted_with_the_configured_tool() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let bin = temp_dir.path().join("bin");
+    fs::create_dir(&bin)?;
+    for tool in ["clang-format", "google-java-format"] {
+        fs::write(bin.join(tool), FAKE_FORMATTER)?;
+        fs::set_permissions(bin.join(tool), fs::Permissions::from_mode(0o755))?;
+    }
+    let path = env::join_paths(
+        std::iter::once(bin).chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )?;
+    // SAFETY: this is the only test in this binary, so no other thread reads the environment
+    unsafe { env::set_var("PATH", path) };
+
+    let registry = LanguageRegistry::new()?;
+    for (file_name, language, source) in [
+        (
+            "main.c",
+            LanguageName::C,
+            "int main() {\n    int x = 1;\n}\n",
+        ),
+        (
+            "main.cpp",
+            LanguageName::Cpp,
+            "int main() {\n    int x = 1;\n}\n",
+        ),
+        (
+            "Main.java",
+            LanguageName::Java,
+            "class Main {\n  void run() {\n    int x = 1;\n  }\n}\n",
+        ),
+    ] {
+        let file_path = temp_dir.path().join(file_name);
+        fs::write(&file_path, source)?;
+        let output = edit(&registry, &file_path, language)?;
+        let expected = format!(
+            "// clang-format --style=file --fallback-style=none --assume-filename={}\n",
+            file_path.display()
+        );
+        assert!(output.starts_with(&expected), "{output}");
+    }
+
+    // SAFETY: as above
+    unsafe { env::set_var(JAVA_FORMATTER_ENV, "google-java-format") };
+    let registry = LanguageRegistry::new()?;
+    let output = edit(
+        &registry,
+        &temp_dir.path().join("Main.java"),
+        LanguageName::Java,
+    )?;
+    assert!(output.starts_with("// google-java-format -\n"), "{output}");
+
+    unsafe { env::set_var(JAVA_FORMATTER_ENV, "eclipse") };
+    // languages are built on first use, which is when the setting is read
+    let error = LanguageRegistry::new()?
+        .get_language(LanguageName::Java)
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("must be `clang-format` or `google-java-format`")
+    );
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Tests for the `semantic-edit` command-line front-end
+
+use anyhow::Result;
+use clap::Parser;
+use semantic_code_edit_mcp::cli::Cli;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+/// Run the command line with `stdin`, returning whether it succeeded and what it printed
+fn run(args: &[&str], stdin: &str) -> Result<(bool, String)> {
+    let cli = Cli::try_parse_from(std::iter::once("semantic-edit").chain(args.iter().copied()))?;
+    let mut stdout = Vec::new();
+    let success = cli.run(&mut stdin.as_bytes(), &mut stdout)?;
+    Ok((success, String::from_utf8(stdout)?))
+}
+
+fn write_source(dir: &TempDir, name: &str, source: &str) -> Result<String> {
+    let path = dir.path().join(name);
+    std::fs::write(&path, source)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[test]
+fn apply_writes_valid_edits_and_refuses_invalid_ones() -> Result<()> {
+    let dir = TempDir::new()?;
+    let file = write_source(&dir, "main.rs", SOURCE)?;
+
+    let (success, _) = run(
+        &[
+            "apply",
+            &file,
+            "--operation",
+            "replace_exact",
+            "--anchor",
+            "let x = 1;",
+            "--format-mode",
+            "never",
+        ],
+        "let x = 2;",
+    )?;
+    assert!(success);
+    assert_eq!(
+        std::fs::read_to_string(&file)?,
+        "fn main() {\n    let x = 2;\n}\n"
+    );
+
+    let (success, output) = run(
+        &[
+            "--json",
+            "apply",
+            &file,
+            "--operation",
+            "insert_after",
+            "--anchor",
+            "let x = 2;",
+            "--content",
+            "\n    let y = ;",
+        ],
+        "",
+    )?;
+    assert!(!success);
+    let report: serde_json::Value = serde_json::from_str(&output)?;
+    assert_eq!(report["applied"], false);
+    assert_eq!(
+        std::fs::read_to_string(&file)?,
+        "fn main() {\n    let x = 2;\n}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn preview_does_not_write() -> Result<()> {
+    let dir = TempDir::new()?;
+    let file = write_source(&dir, "main.rs", SOURCE)?;
+
+    let (success, output) = run(
+        &[
+            "preview",
+            &file,
+            "--operation",
+            "replace_exact",
+            "--anchor",
+            "let x = 1;",
+            "--content",
+            "let x = 2;",
+        ],
+        "",
+    )?;
+    assert!(success);
+    assert!(output.contains("let x = 2;"), "{output}");
+    assert_eq!(std::fs::read_to_string(&file)?, SOURCE);
+    Ok(())
+}
+
+#[test]
+fn search_reports_the_node_an_anchor_selects() -> Result<()> {
+    let dir = TempDir::new()?;
+    let file = write_source(&dir, "main.rs", SOURCE)?;
+
+    let (success, output) = run(&["--json", "search", &file, "--anchor", "fn main()"], "")?;
+    assert!(success);
+    let matches: serde_json::Value = serde_json::from_str(&output)?;
+    assert_eq!(matches[0]["line"], 1);
+    assert_eq!(matches[0]["node_kind"], "function_item");
+    assert_eq!(matches[0]["node_lines"], serde_json::json!([1, 3]));
+
+    let (success, output) = run(
+        &["search", &file, "--query", "(let_declaration) @binding"],
+        "",
+    )?;
+    assert!(success);
+    assert!(
+        output.contains(":2:5: @binding let_declaration (lines 2-2) let x = 1;"),
+        "{output}"
+    );
+
+    let (success, _) = run(&["search", &file, "--anchor", "missing"], "")?;
+    assert!(!success);
+    Ok(())
+}
+
+#[test]
+fn format_check_reports_without_writing() -> Result<()> {
+    let dir = TempDir::new()?;
+    let file = write_source(&dir, "data.json", "{\"a\":1}")?;
+
+    let (success, output) = run(&["format", "--check", &file], "")?;
+    assert!(!success);
+    assert!(output.starts_with("would format"), "{output}");
+    assert_eq!(std::fs::read_to_string(&file)?, "{\"a\":1}");
+
+    let (success, _) = run(&["format", &file], "")?;
+    assert!(success);
+    let formatted = std::fs::read_to_string(&file)?;
+    assert_ne!(formatted, "{\"a\":1}");
+
+    let (success, _) = run(&["format", "--check", &file], "")?;
+    assert!(success);
+    Ok(())
+}
+
+#[test]
+fn validate_reports_invalid_files() -> Result<()> {
+    let dir = TempDir::new()?;
+    let valid = write_source(&dir, "valid.rs", SOURCE)?;
+    let invalid = write_source(&dir, "invalid.rs", "fn main() {\n    let x = ;\n}\n")?;
+
+    let (success, output) = run(&["validate", &valid], "")?;
+    assert!(success);
+    assert!(output.ends_with(": ok\n"), "{output}");
+
+    let (success, output) = run(&["--json", "validate", &valid, &invalid], "")?;
+    assert!(!success);
+    let results: serde_json::Value = serde_json::from_str(&output)?;
+    assert_eq!(results[0]["valid"], true);
+    assert_eq!(results[1]["valid"], false);
+    assert_eq!(results[1]["language"], "rust");
+    Ok(())
+}
+
+#[test]
+fn unknown_languages_are_rejected() {
+    let error = Cli::try_parse_from([
+        "semantic-edit",
+        "validate",
+        "file.txt",
+        "--language",
+        "klingon",
+    ])
+    .unwrap_err();
+    assert!(error.to_string().contains("unknown language `klingon`"));
+}
+
+#[test]
+fn record_saves_tool_calls_as_a_fixture_once() -> Result<()> {
+    let dir = TempDir::new()?;
+    let input = write_source(&dir, "main.rs", SOURCE)?;
+    let fixture = dir.path().join("fixture").to_string_lossy().into_owned();
+    let calls = r#"{"name": "stage_operation", "arguments": {"operation": "replace_exact", "anchor": "let x = 1;", "content": "let x = 2;"}}"#;
+
+    let (success, output) = run(&["record", &fixture, "--input", &input], calls)?;
+    assert!(success);
+    assert!(
+        output.starts_with(&format!(
+            "Recorded 1 tool call into {fixture}: args.json, input.rs, response.txt"
+        )),
+        "{output}"
+    );
+    assert!(output.contains("STAGED: replace exact"), "{output}");
+    assert_eq!(std::fs::read_to_string(&input)?, SOURCE);
+
+    let error = run(&["record", &fixture, "--input", &input], calls).unwrap_err();
+    assert!(
+        error.to_string().contains("pass --force to replace it"),
+        "{error}"
+    );
+    assert!(run(&["record", &fixture, "--input", &input, "--force"], calls)?.0);
+    Ok(())
+}
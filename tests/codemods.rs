@@ -0,0 +1,272 @@
+//! Tests for running ast-grep style rules as staged codemods
+
+mod common;
+
+use anyhow::{Result, anyhow};
+use common::call;
+use semantic_code_edit_mcp::filesystem::{FileOperations, StdFileOperations};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const UNWRAP_RULE: &str = r#"id: unwrap-to-expect
+language: Rust
+message: Prefer expect
+rule:
+  pattern: $VALUE.unwrap()
+fix: $VALUE.expect("TODO")
+"#;
+
+/// Writes to disk, failing for one file name and appending text to everything it writes
+struct FaultyWrites {
+    fail: Option<&'static str>,
+    append: &'static str,
+}
+
+impl FaultyWrites {
+    fn check(&self, path: &Path) -> Result<()> {
+        match self.fail {
+            Some(name) if path.ends_with(name) => Err(anyhow!("disk full")),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FileOperations for FaultyWrites {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        StdFileOperations.read_bytes(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        StdFileOperations.exists(path)
+    }
+
+    fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
+        self.check(&path)?;
+        StdFileOperations.write_file(path, content + self.append)
+    }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        StdFileOperations.write_bytes(path, content)
+    }
+}
+
+fn project(files: &[(&str, &str)]) -> Result<(SemanticEditTools, TempDir)> {
+    project_with(files, Box::new(StdFileOperations))
+}
+
+fn project_with(
+    files: &[(&str, &str)],
+    file_operations: Box<dyn FileOperations>,
+) -> Result<(SemanticEditTools, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    for (path, content) in files {
+        let path = temp_dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, content)?;
+    }
+    let mut state = SemanticEditTools::with_file_operations(None, file_operations)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": temp_dir.path() }),
+    )?;
+    Ok((state, temp_dir))
+}
+
+#[test]
+fn rewrites_are_staged_across_files_and_committed_together() -> Result<()> {
+    let (mut state, temp_dir) = project(&[
+        (".semantic-edit/rules/unwrap.yml", UNWRAP_RULE),
+        ("src/a.rs", "fn a() {\n    let x = parse(s).unwrap();\n}\n"),
+        ("src/b.rs", "fn b() {\n    load().unwrap().len();\n}\n"),
+        ("src/c.rs", "fn c() {}\n"),
+    ])?;
+
+    let preview = call(&mut state, "run_codemod", json!({}))?;
+    assert!(
+        preview.contains("STAGED: codemod unwrap-to-expect rewriting 2 matches in 2 files"),
+        "{preview}"
+    );
+    assert!(
+        preview.contains(r#"+    let x = parse(s).expect("TODO");"#),
+        "{preview}"
+    );
+    assert!(!preview.contains("c.rs"), "{preview}");
+    // nothing is written until the codemod is committed
+    assert!(fs::read_to_string(temp_dir.path().join("src/a.rs"))?.contains("unwrap"));
+
+    let diff = call(&mut state, "view_staged_diff", json!({}))?;
+    assert!(
+        diff.contains(r#"+    load().expect("TODO").len();"#),
+        "{diff}"
+    );
+
+    let result = call(&mut state, "commit_staged", json!({}))?;
+    assert!(
+        result.contains("Applied codemod unwrap-to-expect"),
+        "{result}"
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("src/a.rs"))?,
+        "fn a() {\n    let x = parse(s).expect(\"TODO\");\n}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("src/b.rs"))?,
+        "fn b() {\n    load().expect(\"TODO\").len();\n}\n"
+    );
+    assert!(state.get_staged_codemod(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn metavariables_capture_lists_and_must_match_consistently() -> Result<()> {
+    let rules = r#"id: rename-call
+language: python
+rule:
+  pattern: old_api($$$ARGS)
+fix: new_api($$$ARGS)
+---
+id: self-compare
+language: python
+rule:
+  pattern: $A == $A
+fix: "True"
+"#;
+    let (mut state, temp_dir) = project(&[
+        ("rules.yml", rules),
+        ("main.py", "old_api()\nold_api(1, key=2)\nx == x\nx == y\n"),
+    ])?;
+
+    call(
+        &mut state,
+        "run_codemod",
+        json!({ "rules": "rules.yml", "file_paths": ["*.py"] }),
+    )?;
+    call(&mut state, "commit_staged", json!({}))?;
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("main.py"))?,
+        "new_api()\nnew_api(1, key=2)\nTrue\nx == y\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn rules_without_a_fix_only_report_matches() -> Result<()> {
+    let rule = "id: no-todo\nlanguage: rust\nmessage: Remove todo!()\nrule:\n  pattern: todo!()\n";
+    let (mut state, _temp_dir) = project(&[
+        (".semantic-edit/rules/todo.yaml", rule),
+        ("lib.rs", "fn a() {\n    todo!()\n}\n"),
+    ])?;
+
+    let result = call(&mut state, "run_codemod", json!({}))?;
+    assert!(result.contains("Nothing was staged"), "{result}");
+    assert!(
+        result.contains("lib.rs:2 no-todo: Remove todo!()"),
+        "{result}"
+    );
+    assert!(state.get_staged_codemod(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn stale_files_leave_every_file_untouched() -> Result<()> {
+    let (mut state, temp_dir) = project(&[
+        (".semantic-edit/rules/unwrap.yml", UNWRAP_RULE),
+        ("a.rs", "fn a() { x.unwrap(); }\n"),
+        ("b.rs", "fn b() { y.unwrap(); }\n"),
+    ])?;
+    call(&mut state, "run_codemod", json!({}))?;
+    fs::write(temp_dir.path().join("b.rs"), "fn b() { z.unwrap(); }\n")?;
+
+    let error = call(&mut state, "commit_staged", json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("b.rs"), "{error}");
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("a.rs"))?,
+        "fn a() { x.unwrap(); }\n"
+    );
+    // the codemod stays staged, so it can be committed once the file is restored
+    assert!(state.get_staged_codemod(None)?.is_some());
+    fs::write(temp_dir.path().join("b.rs"), "fn b() { y.unwrap(); }\n")?;
+    call(&mut state, "commit_staged", json!({}))?;
+    assert!(state.get_staged_codemod(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn a_failed_write_restores_the_files_already_written() -> Result<()> {
+    let (mut state, temp_dir) = project_with(
+        &[
+            (".semantic-edit/rules/unwrap.yml", UNWRAP_RULE),
+            ("a.rs", "fn a() { x.unwrap(); }\n"),
+            ("b.rs", "fn b() { y.unwrap(); }\n"),
+        ],
+        Box::new(FaultyWrites {
+            fail: Some("b.rs"),
+            append: "",
+        }),
+    )?;
+    call(
+        &mut state,
+        "run_codemod",
+        json!({ "file_paths": ["a.rs", "b.rs"] }),
+    )?;
+
+    let error = call(&mut state, "commit_staged", json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("no files were changed") && error.contains("disk full"),
+        "{error}"
+    );
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("a.rs"))?,
+        "fn a() { x.unwrap(); }\n"
+    );
+    assert!(state.get_staged_codemod(None)?.is_some());
+    Ok(())
+}
+
+#[test]
+fn written_files_are_read_back() -> Result<()> {
+    let (mut state, _temp_dir) = project_with(
+        &[
+            (".semantic-edit/rules/unwrap.yml", UNWRAP_RULE),
+            ("a.rs", "fn a() { x.unwrap(); }\n"),
+        ],
+        Box::new(FaultyWrites {
+            fail: None,
+            append: "}",
+        }),
+    )?;
+    call(&mut state, "run_codemod", json!({}))?;
+
+    let result = call(&mut state, "commit_staged", json!({}))?;
+    assert!(
+        result.contains("VERIFICATION FAILED: ") && result.contains("no longer parses"),
+        "{result}"
+    );
+    Ok(())
+}
+
+#[test]
+fn unsupported_rules_are_rejected() -> Result<()> {
+    let rule = "id: scoped\nlanguage: rust\nrule:\n  pattern: $X.unwrap()\n  inside:\n    kind: function_item\nfix: $X?\n";
+    let (mut state, temp_dir) = project(&[("rules.yml", rule), ("lib.rs", "fn a() {}\n")])?;
+    let error = call(&mut state, "run_codemod", json!({ "rules": "rules.yml" }))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("unknown field `inside`"), "{error}");
+
+    let rule = "id: typo\nlanguage: rust\nrule:\n  pattern: $X.unwrap()\nfix: $Y?\n";
+    fs::write(temp_dir.path().join("rules.yml"), rule)?;
+    let error = call(&mut state, "run_codemod", json!({ "rules": "rules.yml" }))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("uses $Y"), "{error}");
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Tests for the comby-style `rewrite_pattern` operation
+
+mod common;
+
+use anyhow::Result;
+use common::apply_operation;
+use semantic_code_edit_mcp::comby::Template;
+use semantic_code_edit_mcp::error::SemanticEditError;
+
+fn rewrite(file_name: &str, source: &str, anchor: &str, content: &str) -> Result<String> {
+    apply_operation(file_name, source, "rewrite_pattern", anchor, content)
+}
+
+#[test]
+fn holes_keep_balanced_code_whole() -> Result<()> {
+    let output = rewrite(
+        "main.rs",
+        "fn main() {\n    copy_file(path(\"a, b\", c), dest[0]);\n}\n",
+        "copy_file(:[from], :[to])",
+        "copy_file(:[to], :[from])",
+    )?;
+    assert_eq!(
+        output,
+        "fn main() {\n    copy_file(dest[0], path(\"a, b\", c));\n}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn repeated_holes_must_match_the_same_text() {
+    let template = Template::parse(":[[a]] == :[[a]]");
+    let matches = template.matches("x == y; z == z;");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].holes["a"], "z");
+
+    // whitespace in a template matches any amount of whitespace
+    let template = Template::parse("if (:[cond]) {");
+    let matches = template.matches("if(ready && (a || b))   {");
+    assert_eq!(matches[0].holes["cond"], "ready && (a || b)");
+}
+
+#[test]
+fn templates_rewrite_files_without_a_grammar() -> Result<()> {
+    let output = rewrite(
+        "settings.conf",
+        "name = demo\ntimeout = 30\n",
+        "timeout = :[value\\n]",
+        "timeout_secs = :[value\\n]",
+    )?;
+    assert_eq!(output, "name = demo\ntimeout_secs = 30\n");
+    Ok(())
+}
+
+#[test]
+fn content_holes_must_be_captured_by_the_anchor() -> Result<()> {
+    let error = rewrite(
+        "main.py",
+        "print(a, b)\n",
+        "print(:[args])",
+        "log(:[level], :[args])",
+    )
+    .unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<SemanticEditError>(),
+            Some(SemanticEditError::SelectorInvalid { .. })
+        ),
+        "{error}"
+    );
+    assert!(error.to_string().contains(":[level]"), "{error}");
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Tests for commit_staged dry-run mode
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn dry_run_returns_final_content_without_writing() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    let original = "{\"key\": \"before\"}\n";
+    fs::write(&file_path, original)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"before\"".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+
+    let message = CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: true,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+
+    assert!(message.contains("DRY RUN"));
+    assert!(message.contains("\"key\": \"after\""));
+    assert_eq!(fs::read_to_string(&file_path)?, original);
+    assert!(state.get_staged_operation(None)?.is_some());
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(fs::read_to_string(&file_path)?.contains("\"after\""));
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
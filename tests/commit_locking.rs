@@ -0,0 +1,153 @@
+//! Tests for conflict detection and locking when several sessions commit to one file
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::commit_lock::{CommitLock, CommitLocks};
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+fn stage(state: &mut SemanticEditTools, file_path: &Path, content: &str) -> Result<String> {
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+fn commit(state: &mut SemanticEditTools) -> Result<String> {
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(state)
+}
+
+#[test]
+fn second_committer_is_told_another_session_modified_the_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file = temp_dir.path().join("main.rs");
+    fs::write(&file, SOURCE)?;
+
+    let mut alice =
+        SemanticEditTools::with_standard_operations(None)?.with_default_session_id("alice");
+    let mut bob = SemanticEditTools::with_standard_operations(None)?.with_default_session_id("bob");
+    stage(&mut alice, &file, "\n    let y = 2;")?;
+    stage(&mut bob, &file, "\n    let z = 3;")?;
+
+    commit(&mut alice)?;
+    let error = commit(&mut bob).unwrap_err().to_string();
+    assert!(error.contains("modified by another session"), "{error}");
+    assert!(error.contains("alice (process"), "{error}");
+
+    let content = fs::read_to_string(&file)?;
+    assert!(content.contains("let y = 2;"));
+    assert!(!content.contains("let z = 3;"));
+    Ok(())
+}
+
+#[test]
+fn outside_changes_are_reported_as_stale() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file = temp_dir.path().join("main.rs");
+    fs::write(&file, SOURCE)?;
+
+    let mut alice =
+        SemanticEditTools::with_standard_operations(None)?.with_default_session_id("alice");
+    let mut bob = SemanticEditTools::with_standard_operations(None)?.with_default_session_id("bob");
+    stage(&mut alice, &file, "\n    let y = 2;")?;
+    commit(&mut alice)?;
+
+    stage(&mut bob, &file, "\n    let z = 3;")?;
+    fs::write(&file, fs::read_to_string(&file)? + "\nfn other() {}\n")?;
+    let error = commit(&mut bob).unwrap_err().to_string();
+    assert!(
+        error.contains("has changed since the operation was staged"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn commit_lock_excludes_concurrent_committers() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file = temp_dir.path().join("main.rs");
+    fs::write(&file, SOURCE)?;
+    let lock_directory = temp_dir.path().join("locks");
+
+    let lock = CommitLock::acquire(&lock_directory, &file)?;
+    let (sender, receiver) = mpsc::channel();
+    let waiter = std::thread::spawn({
+        let lock_directory = lock_directory.clone();
+        let file = file.clone();
+        move || -> Result<()> {
+            let _lock = CommitLock::acquire(&lock_directory, &file)?;
+            sender.send(())?;
+            Ok(())
+        }
+    });
+
+    assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+    drop(lock);
+    receiver.recv_timeout(Duration::from_secs(10))?;
+    waiter.join().unwrap()?;
+    Ok(())
+}
+
+#[test]
+fn locking_several_files_in_any_order_cannot_deadlock() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let (a, b) = (temp_dir.path().join("a.rs"), temp_dir.path().join("b.rs"));
+    fs::write(&a, SOURCE)?;
+    fs::write(&b, SOURCE)?;
+    let lock_directory = temp_dir.path().join("locks");
+
+    let (sender, receiver) = mpsc::channel();
+    for paths in [[a.clone(), b.clone()], [b.clone(), a.clone()]] {
+        let lock_directory = lock_directory.clone();
+        let sender = sender.clone();
+        std::thread::spawn(move || -> Result<()> {
+            for _ in 0..50 {
+                let _locks =
+                    CommitLocks::acquire(&lock_directory, paths.iter().map(|p| p.as_path()))?;
+            }
+            sender.send(())?;
+            Ok(())
+        });
+    }
+    receiver.recv_timeout(Duration::from_secs(10))?;
+    receiver.recv_timeout(Duration::from_secs(10))?;
+
+    // two paths to one file share its lock instead of waiting for themselves
+    let same = temp_dir.path().join(".").join("a.rs");
+    let locks = CommitLocks::acquire(&lock_directory, [a.as_path(), same.as_path()])?;
+    assert!(locks.get(&same).is_some());
+    Ok(())
+}
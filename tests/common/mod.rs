@@ -0,0 +1,55 @@
+//! Helpers shared by the integration tests
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::Tools;
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+/// Call a tool by name with JSON arguments, as an MCP client would
+pub fn call(state: &mut SemanticEditTools, name: &str, arguments: Value) -> Result<String> {
+    let tool: Tools = serde_json::from_value(json!({ "name": name, "arguments": arguments }))?;
+    tool.execute(state)
+}
+
+/// The file after staging `operation` at `anchor` with `content` and committing it, or the
+/// preview if nothing was staged
+///
+/// The file is written to a project of its own, and formatting is left off so the output
+/// shows only what the operation did. Errors from staging are returned as they are.
+#[allow(dead_code)] // not every test file edits through a single operation
+pub fn apply_operation(
+    file_name: &str,
+    source: &str,
+    operation: &str,
+    anchor: &str,
+    content: &str,
+) -> Result<String> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join(file_name);
+    fs::write(&file_path, source)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": temp_dir.path() }),
+    )?;
+    let preview = call(
+        &mut state,
+        "stage_operation",
+        json!({
+            "file_path": file_name,
+            "operation": operation,
+            "anchor": anchor,
+            "content": content,
+            "format_mode": "never",
+        }),
+    )?;
+    if state.get_staged_operation(None)?.is_none() {
+        return Ok(preview);
+    }
+    call(&mut state, "commit_staged", json!({}))?;
+    Ok(fs::read_to_string(file_path)?)
+}
@@ -0,0 +1,118 @@
+//! Tests for reporting merge conflict markers instead of the syntax errors they cause
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::StageOperation;
+use semantic_code_edit_mcp::validation::{CONFLICT_MARKER_RULE, conflict_marker_rows};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+const CONFLICTED: &str = "fn main() {
+    let x = 1;
+}
+
+<<<<<<< HEAD
+fn other() {}
+=======
+fn other() -> u8 {
+    1
+}
+>>>>>>> feature
+";
+
+fn stage(
+    source: &str,
+    anchor: &str,
+    content: &str,
+    format: ResultFormat,
+) -> Result<(String, SemanticEditTools, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, source)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    Ok((preview, state, temp_dir))
+}
+
+#[test]
+fn inserted_markers_are_reported_as_conflicts() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(
+        "fn main() {\n    let x = 1;\n}\n",
+        "let x = 1;",
+        "<<<<<<< HEAD\n    let x = 1;\n=======\n    let x = 2;\n>>>>>>> feature",
+        ResultFormat::Json,
+    )?;
+    let preview: Value = serde_json::from_str(&preview)?;
+    assert_eq!(preview["staged"], false);
+    let message = preview["message"].as_str().unwrap();
+    assert!(
+        message.contains("===MERGE CONFLICT MARKERS==="),
+        "{message}"
+    );
+    assert!(!message.contains("===SYNTAX ERRORS==="), "{message}");
+
+    let violations = preview["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 3);
+    assert!(
+        violations
+            .iter()
+            .all(|violation| violation["rule"] == CONFLICT_MARKER_RULE)
+    );
+    assert_eq!(violations[0]["start"]["line"], 2);
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn edits_inside_a_conflict_point_at_the_markers() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(CONFLICTED, "    1", "    2", ResultFormat::Text)?;
+    assert!(
+        preview.contains("===MERGE CONFLICT MARKERS==="),
+        "{preview}"
+    );
+    assert!(preview.contains("5 ->⎸<<<<<<< HEAD"), "{preview}");
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn edits_outside_a_conflict_are_not_blocked_by_it() -> Result<()> {
+    let (preview, state, _temp_dir) =
+        stage(CONFLICTED, "let x = 1;", "let x = 2;", ResultFormat::Text)?;
+    assert!(!preview.contains("MERGE CONFLICT MARKERS"), "{preview}");
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
+
+#[test]
+fn only_complete_conflicts_are_detected() {
+    assert_eq!(conflict_marker_rows(CONFLICTED), [4, 6, 10]);
+    // setext headings and a conflict that never closes are not conflicts
+    assert!(conflict_marker_rows("Title\n=======\n\ntext\n").is_empty());
+    assert!(conflict_marker_rows("<<<<<<< HEAD\n=======\n").is_empty());
+}
@@ -0,0 +1,122 @@
+//! Tests for project-supplied validation queries in `.semantic-edit/queries`
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{StageOperation, Tools};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const NO_UNWRAP: &str = r#"
+((call_expression
+   function: (field_expression
+               field: (field_identifier) @_method)) @invalid.unwrap
+ (#eq? @_method "unwrap")
+ (#set! message "unwrap() is not allowed in this project")
+ (#set! suggestion "Propagate the error with ?"))
+"#;
+
+const SOURCE: &str = "fn main() {\n    let a = parse().unwrap();\n    let b = 1;\n}\n";
+
+fn project(query: &str) -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    let queries = temp_dir.path().join(".semantic-edit/queries/rust");
+    fs::create_dir_all(&queries)?;
+    fs::write(queries.join("no_unwrap.scm"), query)?;
+    fs::write(temp_dir.path().join("main.rs"), SOURCE)?;
+    Ok(temp_dir)
+}
+
+fn set_context(state: &mut SemanticEditTools, path: &Path) -> Result<String> {
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": path },
+    }))?;
+    set_context.execute(state)
+}
+
+fn stage(state: &mut SemanticEditTools, anchor: &str, content: &str) -> Result<String> {
+    StageOperation {
+        file_path: "main.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+#[test]
+fn custom_queries_are_loaded_when_setting_context() -> Result<()> {
+    let temp_dir = project(NO_UNWRAP)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = set_context(&mut state, temp_dir.path())?;
+    assert!(response.contains("Loaded 1 custom validation queries"));
+    Ok(())
+}
+
+#[test]
+fn custom_queries_reject_new_violations() -> Result<()> {
+    let temp_dir = project(NO_UNWRAP)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    set_context(&mut state, temp_dir.path())?;
+
+    let preview = stage(&mut state, "let b = 1;", "let b = other().unwrap();")?;
+    assert!(
+        preview.contains("unwrap() is not allowed in this project"),
+        "{preview}"
+    );
+    assert!(preview.contains("Propagate the error with ?"));
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn existing_violations_do_not_block_unrelated_edits() -> Result<()> {
+    let temp_dir = project(NO_UNWRAP)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    set_context(&mut state, temp_dir.path())?;
+
+    let preview = stage(&mut state, "let b = 1;", "let b = 2;")?;
+    assert!(preview.starts_with("STAGED"), "{preview}");
+    Ok(())
+}
+
+#[test]
+fn invalid_custom_queries_fail_at_context_time() -> Result<()> {
+    let temp_dir = project("((not_a_node) @invalid.oops)")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let error = set_context(&mut state, temp_dir.path()).unwrap_err();
+    assert!(error.to_string().contains("no_unwrap.scm"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn projects_without_custom_queries_use_built_in_rules_only() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = set_context(&mut state, temp_dir.path())?;
+    assert!(!response.contains("custom validation queries"));
+
+    let preview = stage(&mut state, "let b = 1;", "let b = other().unwrap();")?;
+    assert!(preview.starts_with("STAGED"), "{preview}");
+    Ok(())
+}
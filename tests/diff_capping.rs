@@ -0,0 +1,99 @@
+//! Tests for capping huge diffs in previews and viewing the full diff afterwards
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{DiffOptions, ResultFormat};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{StageOperation, ViewStagedDiff};
+use std::fs;
+use tempfile::TempDir;
+
+fn numbered_lines(prefix: &str) -> String {
+    (0..300).map(|n| format!("{prefix} {n}\n")).collect()
+}
+
+fn stage_large_edit(state: &mut SemanticEditTools, diff_options: DiffOptions) -> Result<String> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("notes.txt");
+    fs::write(&file_path, format!("start\n{}end\n", numbered_lines("old")))?;
+
+    let preview = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceRange,
+            anchor: "old 0".into(),
+            end: Some("old 299\n".into()),
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(numbered_lines("new")),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options,
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)?;
+
+    // keep the file around for follow-up tools
+    std::mem::forget(temp_dir);
+    Ok(preview)
+}
+
+#[test]
+fn large_diffs_are_summarized() -> Result<()> {
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = stage_large_edit(&mut state, DiffOptions::default())?;
+
+    let diff = preview.split_once("===DIFF===\n").unwrap().1;
+    assert_eq!(diff.lines().count(), 401);
+    assert!(diff.contains("… 202 diff lines omitted"));
+    assert!(diff.contains("-old 0"));
+    assert!(diff.ends_with("+new 299\n end"));
+    Ok(())
+}
+
+#[test]
+fn max_lines_zero_disables_capping() -> Result<()> {
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = stage_large_edit(
+        &mut state,
+        DiffOptions {
+            max_lines: 0,
+            ..DiffOptions::default()
+        },
+    )?;
+
+    assert!(!preview.contains("omitted"));
+    assert!(preview.contains("-old 150\n"));
+    Ok(())
+}
+
+#[test]
+fn view_staged_diff_shows_full_diff() -> Result<()> {
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = stage_large_edit(
+        &mut state,
+        DiffOptions {
+            max_lines: 20,
+            ..DiffOptions::default()
+        },
+    )?;
+    assert!(preview.contains("… 582 diff lines omitted"));
+
+    let full = ViewStagedDiff {
+        diff_options: None,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(!full.contains("omitted"));
+    assert!(full.contains("-old 150\n"));
+    assert!(full.contains("+new 150\n"));
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
@@ -0,0 +1,199 @@
+//! Tests for configurable diff rendering in stage_operation previews
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{DiffGenerator, DiffLine, DiffOptions, ResultFormat};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::StageOperation;
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "{
+    \"a\": 1,
+    \"b\": 2,
+    \"c\": 3,
+    \"d\": 4,
+    \"e\": 5,
+    \"f\": 6
+}
+";
+
+fn preview(diff_options: DiffOptions) -> Result<String> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("data.json");
+    fs::write(&file_path, SOURCE)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"d\": 4".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"d\": 40".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options,
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)
+}
+
+fn diff_lines(preview: &str) -> Vec<&str> {
+    preview
+        .split_once("===DIFF===\n")
+        .map(|(_, diff)| diff.lines().collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn default_rendering_uses_three_context_lines() -> Result<()> {
+    let preview = preview(DiffOptions::default())?;
+    assert_eq!(
+        diff_lines(&preview),
+        vec![
+            "     \"a\": 1,",
+            "     \"b\": 2,",
+            "     \"c\": 3,",
+            "-    \"d\": 4,",
+            "+    \"d\": 40,",
+            "     \"e\": 5,",
+            "     \"f\": 6",
+            " }",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn context_lines_are_configurable() -> Result<()> {
+    let preview = preview(DiffOptions {
+        context_lines: 0,
+        ..DiffOptions::default()
+    })?;
+    assert_eq!(
+        diff_lines(&preview),
+        vec!["-    \"d\": 4,", "+    \"d\": 40,"]
+    );
+    Ok(())
+}
+
+#[test]
+fn line_numbers_show_original_and_new_positions() -> Result<()> {
+    let preview = preview(DiffOptions {
+        context_lines: 1,
+        line_numbers: true,
+        side_by_side: false,
+        word_diff: false,
+        ..DiffOptions::default()
+    })?;
+    assert_eq!(
+        diff_lines(&preview),
+        vec![
+            "    4     4      \"c\": 3,",
+            "    5       -    \"d\": 4,",
+            "          5 +    \"d\": 40,",
+            "    6     6      \"e\": 5,",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn side_by_side_pairs_changed_lines() -> Result<()> {
+    let preview = preview(DiffOptions {
+        context_lines: 1,
+        line_numbers: false,
+        side_by_side: true,
+        word_diff: false,
+        ..DiffOptions::default()
+    })?;
+    let lines = diff_lines(&preview);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("    \"d\": 4,"));
+    assert!(lines[1].ends_with("|     \"d\": 40,"));
+    assert!(lines[0].contains("   \"c\": 3,"));
+    Ok(())
+}
+
+#[test]
+fn word_diff_marks_changed_tokens() -> Result<()> {
+    let preview = preview(DiffOptions {
+        context_lines: 0,
+        word_diff: true,
+        ..DiffOptions::default()
+    })?;
+    assert_eq!(diff_lines(&preview), vec!["~    \"d\": [-4-]{+40+},"]);
+    Ok(())
+}
+
+#[test]
+fn efficiency_thresholds_are_configurable() -> Result<()> {
+    assert!(!preview(DiffOptions::default())?.contains("Edit efficiency"));
+
+    let preview = preview(DiffOptions {
+        efficiency_min_lines: 0,
+        efficiency_tip_percent: 101,
+        ..DiffOptions::default()
+    })?;
+    assert!(preview.contains("Edit efficiency: 100%"));
+    assert!(preview.contains("TIP"));
+    Ok(())
+}
+
+#[test]
+fn structured_diffs_carry_hunks_and_counts() {
+    let output = SOURCE
+        .replace("\"d\": 4", "\"d\": 40")
+        .replace("    \"f\": 6\n", "");
+    let options = DiffOptions {
+        context_lines: 0,
+        ..DiffOptions::default()
+    };
+    let diff = DiffGenerator::diff(SOURCE, &output, "", &options);
+
+    assert_eq!((diff.added, diff.removed), (1, 2));
+    assert_eq!(diff.hunks.len(), 2);
+    assert_eq!(diff.hunks[0].old_start, 5);
+    assert_eq!(
+        diff.hunks[0].lines,
+        vec![
+            DiffLine::Delete("    \"d\": 4,\n".into()),
+            DiffLine::Insert("    \"d\": 40,\n".into()),
+        ]
+    );
+    assert!(diff.efficiency.is_none());
+    assert_eq!(
+        diff.to_string(),
+        DiffGenerator::generate_diff(SOURCE, &output, "", &options)
+    );
+}
+
+#[test]
+fn capped_diffs_keep_every_hunk() {
+    let source: String = (0..100).map(|i| format!("line {i}\n")).collect();
+    let output = source.replace("line", "row");
+    let options = DiffOptions {
+        max_lines: 10,
+        ..DiffOptions::default()
+    };
+    let diff = DiffGenerator::diff(&source, &output, &output, &options);
+
+    assert!(diff.to_string().contains("diff lines omitted"));
+    assert_eq!((diff.added, diff.removed), (100, 100));
+    assert_eq!(
+        diff.efficiency.map(|efficiency| efficiency.percent),
+        Some(100)
+    );
+    assert!(!diff.is_empty());
+    assert!(DiffGenerator::diff(&source, &source, "", &options).is_empty());
+}
@@ -0,0 +1,151 @@
+//! Tests for duplicate definition detection
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::StageOperation;
+use semantic_code_edit_mcp::validation::DefinitionValidator;
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+/// Names of the definitions reported as duplicates
+fn duplicates(code: &str, language: LanguageName) -> Vec<String> {
+    let registry = LanguageRegistry::new().unwrap();
+    let language = registry.get_language(language).unwrap();
+    let tree = language
+        .tree_sitter_parser()
+        .unwrap()
+        .parse(code, None)
+        .unwrap();
+    assert!(!tree.root_node().has_error(), "{}", tree.root_node());
+    DefinitionValidator::find_duplicates(&tree, language.definition_query().as_ref().unwrap(), code)
+        .into_iter()
+        .map(|violation| violation.message)
+        .collect()
+}
+
+#[test]
+fn rust_duplicates() {
+    let code = "fn a() {}\nfn a() {}\nstruct S { f: u8, f: u8 }\nimpl S {\n    fn new() {}\n    fn new() {}\n}\n";
+    assert_eq!(
+        duplicates(code, LanguageName::Rust),
+        [
+            "`a` is defined more than once in the same scope",
+            "`a` is defined more than once in the same scope",
+            "`f` is defined more than once in the same scope",
+            "`f` is defined more than once in the same scope",
+            "`new` is defined more than once in the same scope",
+            "`new` is defined more than once in the same scope",
+        ]
+    );
+}
+
+#[test]
+fn rust_distinct_definitions_pass() {
+    let code = "#[cfg(unix)]\n#[inline]\nfn a() {}\n#[cfg(not(unix))]\n#[inline]\nfn a() {}\n\
+struct a;\nmod m { fn a() {} }\nimpl S { fn a() {} }\n";
+    assert!(duplicates(code, LanguageName::Rust).is_empty());
+}
+
+#[test]
+fn python_duplicates() {
+    let code = "class A:\n    def f(self):\n        pass\n\n    def f(self):\n        pass\n\n    @property\n    def g(self):\n        pass\n\n    @g.setter\n    def g(self, value):\n        pass\n";
+    assert_eq!(duplicates(code, LanguageName::Python).len(), 2);
+}
+
+#[test]
+fn typescript_duplicates() {
+    let code = "class A {\n  get x() { return 1; }\n  set x(v) {}\n  static y() {}\n  y() {}\n  z() {}\n  z() {}\n}\n\
+function f(a: string): void;\nfunction f(a: any) {}\nexport function g() {}\nfunction g() {}\n";
+    assert_eq!(
+        duplicates(code, LanguageName::Typescript),
+        [
+            "`z` is defined more than once in the same scope",
+            "`z` is defined more than once in the same scope",
+            "`g` is defined more than once in the same scope",
+            "`g` is defined more than once in the same scope",
+        ]
+    );
+}
+
+#[test]
+fn go_duplicates() {
+    let code = "package main\n\ntype A struct{}\ntype B struct{}\n\nfunc init() {}\nfunc init() {}\n\
+func (a *A) M() {}\nfunc (b *B) M() {}\nfunc f() {}\nfunc f() {}\n";
+    assert_eq!(duplicates(code, LanguageName::Go).len(), 2);
+}
+
+#[test]
+fn java_and_csharp_overloads_are_not_duplicates() {
+    let java = "class A {\n  int x;\n  void m(int a) {}\n  void m(String a) {}\n  void n() {}\n  void n() {}\n}\n";
+    assert_eq!(duplicates(java, LanguageName::Java).len(), 2);
+
+    let csharp = "class A {\n  void M(int a) {}\n  void M(string a) {}\n  int P { get; set; }\n  int P { get; set; }\n}\n";
+    assert_eq!(duplicates(csharp, LanguageName::CSharp).len(), 2);
+}
+
+fn stage(source: &str, anchor: &str, content: &str) -> Result<Value> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("lib.rs");
+    fs::write(&file_path, source)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Json,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    Ok(serde_json::from_str(&response)?)
+}
+
+#[test]
+fn inserting_a_second_copy_is_refused() -> Result<()> {
+    let preview = stage(
+        "fn helper() -> u8 {\n    1\n}\n",
+        "fn helper() -> u8 {\n    1\n}\n",
+        "\nfn helper() -> u8 {\n    2\n}\n",
+    )?;
+    assert_eq!(preview["staged"], false);
+    assert_eq!(
+        preview["violations"][0]["rule"],
+        "invalid.duplicate.definition"
+    );
+    assert!(
+        preview["message"]
+            .as_str()
+            .unwrap()
+            .contains("Replace the existing definition")
+    );
+    Ok(())
+}
+
+#[test]
+fn existing_duplicates_do_not_block_other_edits() -> Result<()> {
+    let preview = stage(
+        "fn helper() {}\nfn helper() {}\n\nfn other() {}\n",
+        "fn other() {}\n",
+        "\nfn third() {}\n",
+    )?;
+    assert_eq!(preview["staged"], true);
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Tests for refusing inserts that repeat the text right next to them
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "\
+use std::collections::HashMap;
+use std::fs::File;
+
+fn helper() -> u32 {
+    println!(\"helper called\");
+    1
+}
+";
+
+fn stage(
+    state: &mut SemanticEditTools,
+    dir: &TempDir,
+    operation: Operation,
+    anchor: &str,
+    content: &str,
+) -> Result<Value> {
+    Ok(serde_json::from_str(
+        &StageOperation {
+            file_path: dir.path().join("main.rs").display().to_string(),
+            language: None,
+            selector: Selector::builder(operation, anchor).build()?,
+            content: Some(content.into()),
+            buffer: None,
+            format: ResultFormat::Json,
+            diff_options: Default::default(),
+            format_mode: None,
+            cell: None,
+            candidate: None,
+            show_alternatives: false,
+            idempotent: false,
+        }
+        .execute(state)?,
+    )?)
+}
+
+fn commit(state: &mut SemanticEditTools, acknowledge_duplicate: bool) -> Result<String> {
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(state)
+}
+
+#[test]
+fn inserting_a_statement_twice_is_refused_until_acknowledged() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let preview = stage(
+        &mut state,
+        &dir,
+        Operation::InsertAfterNode,
+        "println!(\"helper called\");",
+        "\n    println!(\"helper called\");",
+    )?;
+    let duplicate = preview["duplicate"].as_str().unwrap_or_default();
+    assert!(
+        duplicate.contains("right before it, on line 5"),
+        "{preview}"
+    );
+
+    let error = commit(&mut state, false).unwrap_err().to_string();
+    assert!(error.contains("acknowledge_duplicate"), "{error}");
+    assert_eq!(fs::read_to_string(dir.path().join("main.rs"))?, SOURCE);
+
+    commit(&mut state, true)?;
+    let committed = fs::read_to_string(dir.path().join("main.rs"))?;
+    assert_eq!(committed.matches("helper called").count(), 2, "{committed}");
+    Ok(())
+}
+
+#[test]
+fn only_identical_neighbours_are_duplicates() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let preview = stage(
+        &mut state,
+        &dir,
+        Operation::InsertAfter,
+        "use std::collections::HashMap;",
+        "\nuse std::fs::File;",
+    )?;
+    let duplicate = preview["duplicate"].as_str().unwrap_or_default();
+    assert!(duplicate.contains("right after it, on line 2"), "{preview}");
+
+    let preview = stage(
+        &mut state,
+        &dir,
+        Operation::InsertAfter,
+        "use std::collections::HashMap;",
+        "\nuse std::io::Read;",
+    )?;
+    assert!(preview.get("duplicate").is_none(), "{preview}");
+    commit(&mut state, false)?;
+    assert!(fs::read_to_string(dir.path().join("main.rs"))?.contains("use std::io::Read;"));
+    Ok(())
+}
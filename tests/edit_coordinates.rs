@@ -0,0 +1,81 @@
+//! Tests for the line and column coordinates of edit positions
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{Editor, LineColumn, ResultFormat};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::StageOperation;
+use serde_json::Value;
+use tempfile::TempDir;
+
+// the emoji is 4 bytes and 2 UTF-16 code units, so byte and UTF-16 columns differ after it
+const SOURCE: &str = "fn main() {\n    let greeting = \"👋\"; let count = 1;\n}\n";
+
+#[test]
+fn reports_give_line_and_column_of_the_target() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let selector = Selector::builder(Operation::ReplaceExact, "let count = 1;").build()?;
+    let (report, _) =
+        Editor::from_source(SOURCE, "let count = 2;".into(), selector, rust)?.preview_report()?;
+
+    let target = report.edit.target.expect("the edit is valid");
+    assert_eq!(
+        target.start,
+        Some(LineColumn {
+            line: 2,
+            column: 28,
+            utf16_column: 26,
+        })
+    );
+    assert_eq!(
+        target.end,
+        Some(LineColumn {
+            line: 2,
+            column: 42,
+            utf16_column: 40,
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn staged_operations_keep_the_target() -> Result<()> {
+    let dir = TempDir::new()?;
+    let file_path = dir.path().join("main.rs");
+    std::fs::write(&file_path, SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let preview: Value = serde_json::from_str(
+        &StageOperation {
+            file_path: file_path.display().to_string(),
+            language: None,
+            selector: Selector::builder(Operation::InsertAfter, "fn main() {").build()?,
+            content: Some("\n    println!(\"start\");".into()),
+            buffer: None,
+            format: ResultFormat::Json,
+            diff_options: Default::default(),
+            format_mode: None,
+            cell: None,
+            candidate: None,
+            show_alternatives: false,
+            idempotent: false,
+        }
+        .execute(&mut state)?,
+    )?;
+    assert_eq!(preview["target"]["start"]["line"], 1);
+    assert_eq!(preview["target"]["start"]["column"], 12);
+    assert!(preview["target"].get("end").is_none());
+
+    let staged = state
+        .get_staged_operation(None)?
+        .expect("the operation is staged");
+    let target = staged.target.expect("the staged operation has its target");
+    assert_eq!(target.start_byte, 11);
+    assert_eq!(target.start.map(|start| start.line), Some(1));
+    let serialized = serde_json::to_value(&staged)?;
+    assert_eq!(serialized["target"]["start"]["utf16_column"], 12);
+    Ok(())
+}
@@ -29,6 +29,8 @@ mod edit_iterator_consolidation_tests {
             operation: Operation::ReplaceExact,
             anchor: "nonexistent_text".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         // This should not panic, even with invalid selectors
@@ -0,0 +1,307 @@
+//! Property tests for edit safety
+//!
+//! Random edits are applied to generated programs in several languages, and to mutated
+//! copies of the snapshot test inputs. Every edit the editor accepts must leave everything
+//! outside the edited range byte-for-byte unchanged, and a file without syntax errors
+//! without any, unless the edit came with a warning. Set `PROPTEST_CASES` to run more cases.
+
+use std::path::Path;
+
+use proptest::prelude::*;
+use proptest::sample::{Index, select};
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use tree_sitter::Node;
+
+const OPERATIONS: &[Operation] = &[
+    Operation::InsertBefore,
+    Operation::InsertAfter,
+    Operation::InsertAfterNode,
+    Operation::ReplaceExact,
+    Operation::ReplaceNode,
+    Operation::ReplaceRange,
+];
+
+/// Content that is not valid anywhere, or only valid in some places
+const BROKEN_CONTENT: &[&str] = &["", "}", "{", "(", "\"unterminated", "é€😀", "\n\n", "    "];
+
+fn identifier() -> impl Strategy<Value = String> {
+    "[a-z]{1,8}".prop_map(|name| format!("n_{name}"))
+}
+
+fn value() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<i32>().prop_map(|n| n.to_string()),
+        "[a-zA-Z é€😀]{0,12}".prop_map(|text| format!("\"{text}\"")),
+    ]
+}
+
+fn rust_program() -> impl Strategy<Value = String> {
+    let item = prop_oneof![
+        (identifier(), identifier(), value()).prop_map(|(name, local, value)| format!(
+            "fn {name}() {{\n    let {local} = {value};\n}}\n"
+        )),
+        (identifier(), identifier())
+            .prop_map(|(name, field)| format!("struct {name} {{\n    {field}: i32,\n}}\n")),
+        (identifier(), any::<u16>())
+            .prop_map(|(name, n)| format!("const {}: u16 = {n};\n", name.to_uppercase())),
+    ];
+    prop::collection::vec(item, 1..6).prop_map(|items| items.join("\n"))
+}
+
+fn python_program() -> impl Strategy<Value = String> {
+    let item = prop_oneof![
+        (identifier(), identifier(), value()).prop_map(|(name, local, value)| format!(
+            "def {name}():\n    {local} = {value}\n    return {local}\n"
+        )),
+        (identifier(), identifier())
+            .prop_map(|(name, field)| format!("class {name}:\n    {field} = None\n")),
+        (identifier(), value()).prop_map(|(name, value)| format!("{name} = {value}\n")),
+    ];
+    prop::collection::vec(item, 1..6).prop_map(|items| items.join("\n"))
+}
+
+fn javascript_program() -> impl Strategy<Value = String> {
+    let item = prop_oneof![
+        (identifier(), identifier(), value()).prop_map(|(name, local, value)| format!(
+            "function {name}() {{\n  const {local} = {value};\n  return {local};\n}}\n"
+        )),
+        (identifier(), value()).prop_map(|(name, value)| format!("let {name} = {value};\n")),
+    ];
+    prop::collection::vec(item, 1..6).prop_map(|items| items.join("\n"))
+}
+
+fn json_document() -> impl Strategy<Value = String> {
+    prop::collection::vec((identifier(), value()), 1..8).prop_map(|pairs| {
+        let pairs: Vec<_> = pairs
+            .iter()
+            .map(|(key, value)| format!("  \"{key}\": {value}"))
+            .collect();
+        format!("{{\n{}\n}}\n", pairs.join(",\n"))
+    })
+}
+
+/// The Rust inputs of the snapshot tests
+fn corpus() -> Vec<String> {
+    let mut files = Vec::new();
+    collect_inputs(Path::new("tests/snapshots"), &mut files);
+    files.sort();
+    assert!(!files.is_empty(), "no snapshot inputs found");
+    files
+}
+
+fn collect_inputs(dir: &Path, files: &mut Vec<String>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_inputs(&path, files);
+        } else if path.file_name().is_some_and(|name| name == "input.rs") {
+            files.push(std::fs::read_to_string(path).unwrap());
+        }
+    }
+}
+
+/// A corpus file with one of its lines deleted, duplicated, or left as is
+fn mutated_corpus() -> impl Strategy<Value = String> {
+    (select(corpus()), any::<Index>(), 0..3usize).prop_map(|(source, line, mutation)| {
+        let mut lines: Vec<&str> = source.split_inclusive('\n').collect();
+        let line = line.index(lines.len());
+        match mutation {
+            0 => {
+                lines.remove(line);
+            }
+            1 => lines.insert(line, lines[line]),
+            _ => {}
+        }
+        lines.concat()
+    })
+}
+
+/// A selector anchored on part of one of the source's lines, and content to edit with
+#[derive(Debug, Clone)]
+struct EditCase {
+    operation: Operation,
+    line: Index,
+    anchor_start: Index,
+    anchor_len: Index,
+    end_line: Index,
+    content: String,
+}
+
+fn edit_case(valid_content: Vec<String>) -> impl Strategy<Value = EditCase> {
+    let content = prop_oneof![
+        3 => select(valid_content),
+        1 => select(BROKEN_CONTENT).prop_map(str::to_string),
+    ];
+    (
+        select(OPERATIONS),
+        any::<Index>(),
+        any::<Index>(),
+        any::<Index>(),
+        any::<Index>(),
+        content,
+    )
+        .prop_map(
+            |(operation, line, anchor_start, anchor_len, end_line, content)| EditCase {
+                operation,
+                line,
+                anchor_start,
+                anchor_len,
+                end_line,
+                content,
+            },
+        )
+}
+
+/// A piece of one of the source's lines, starting and ending on character boundaries
+fn line_part<'a>(source: &'a str, line: &Index, start: &Index, len: &Index) -> Option<&'a str> {
+    let lines: Vec<&str> = source
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    let line = lines[line.index(lines.len())].trim();
+    let boundaries: Vec<usize> = line
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain([line.len()])
+        .collect();
+    let start = start.index(boundaries.len() - 1);
+    let end = start + 1 + len.index(boundaries.len() - start - 1);
+    Some(&line[boundaries[start]..boundaries[end]])
+}
+
+fn error_nodes(node: Node<'_>) -> usize {
+    if !node.has_error() {
+        return 0;
+    }
+    let mut cursor = node.walk();
+    usize::from(node.is_error() || node.is_missing())
+        + node.children(&mut cursor).map(error_nodes).sum::<usize>()
+}
+
+fn check_edit(
+    registry: &LanguageRegistry,
+    language: LanguageName,
+    source: &str,
+    case: &EditCase,
+) -> Result<(), TestCaseError> {
+    let Some(anchor) = line_part(source, &case.line, &case.anchor_start, &case.anchor_len) else {
+        return Ok(());
+    };
+    let mut selector = Selector::builder(case.operation, anchor);
+    if matches!(case.operation, Operation::ReplaceRange) {
+        let end = line_part(source, &case.end_line, &case.anchor_start, &case.anchor_len);
+        selector = selector.with_end(end.unwrap_or(anchor));
+    }
+    // selectors the builder rejects, like whitespace-only anchors, never reach an edit
+    let Ok(selector) = selector.build() else {
+        return Ok(());
+    };
+    let language = registry.get_language(language).unwrap();
+    let Ok(editor) = Editor::from_source(source, case.content.clone(), selector, language) else {
+        return Ok(());
+    };
+    // refusing an edit, or failing with an error, is always safe
+    let Ok((report, Some(output))) = editor.with_format_mode(FormatMode::Never).commit_report()
+    else {
+        return Ok(());
+    };
+
+    let target = report
+        .edit
+        .target
+        .expect("applied edits report their target");
+    let (start, end) = (
+        target.start_byte,
+        target.end_byte.unwrap_or(target.start_byte),
+    );
+    prop_assert!(start <= end && end <= source.len());
+    prop_assert!(source.is_char_boundary(start) && source.is_char_boundary(end));
+    prop_assert!(
+        output.len() >= start + source.len() - end
+            && output.starts_with(&source[..start])
+            && output.ends_with(&source[end..]),
+        "text outside {start}..{end} changed:\n{output}"
+    );
+
+    // errors on lines that already had one are tolerated by design, so a file with syntax
+    // errors, like a mutated corpus file, can gain more there
+    let mut parser = language.parser().unwrap();
+    let before = error_nodes(parser.parse(source, None).unwrap().root_node());
+    if before == 0 && report.edit.warning.is_none() {
+        let after = error_nodes(parser.parse(&output, None).unwrap().root_node());
+        prop_assert_eq!(after, 0, "syntax errors after the edit:\n{}", output);
+    }
+    Ok(())
+}
+
+fn snippets(snippets: &[&str]) -> Vec<String> {
+    snippets.iter().map(|snippet| snippet.to_string()).collect()
+}
+
+proptest! {
+    #[test]
+    fn rust_edits_are_safe(
+        source in prop_oneof![rust_program(), mutated_corpus()],
+        case in edit_case(snippets(&[
+            "fn added() {}",
+            "\n\nfn added() {\n    let x = 1;\n}",
+            "let y = 2;",
+            "struct Added;",
+            "42",
+            "\"text\"",
+            "n_name",
+        ])),
+    ) {
+        check_edit(&LanguageRegistry::new().unwrap(), LanguageName::Rust, &source, &case)?;
+    }
+
+    #[test]
+    fn python_edits_are_safe(
+        source in python_program(),
+        case in edit_case(snippets(&[
+            "def added():\n    pass",
+            "\nx = 1",
+            "y = 2",
+            "42",
+            "'text'",
+            "n_name",
+        ])),
+    ) {
+        check_edit(&LanguageRegistry::new().unwrap(), LanguageName::Python, &source, &case)?;
+    }
+
+    #[test]
+    fn javascript_edits_are_safe(
+        source in javascript_program(),
+        case in edit_case(snippets(&[
+            "function added() {}",
+            "\nconst x = 1;",
+            "let y = 2;",
+            "42",
+            "'text'",
+            "n_name",
+        ])),
+    ) {
+        check_edit(&LanguageRegistry::new().unwrap(), LanguageName::Javascript, &source, &case)?;
+    }
+
+    #[test]
+    fn json_edits_are_safe(
+        source in json_document(),
+        case in edit_case(snippets(&[
+            "\"added\": 1,",
+            ",\n  \"added\": true",
+            "42",
+            "\"text\"",
+            "[1, 2]",
+            "{}",
+        ])),
+    ) {
+        check_edit(&LanguageRegistry::new().unwrap(), LanguageName::Json, &source, &case)?;
+    }
+}
@@ -1,5 +1,6 @@
 use semantic_code_edit_mcp::{
     editor::{EditPosition, Editor},
+    filesystem::StdFileOperations,
     languages::{LanguageName, LanguageRegistry},
     selector::{Operation, Selector},
     state::StagedOperation,
@@ -30,6 +31,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -53,14 +56,27 @@ mod editor_behavior_tests {
                 operation: Operation::InsertAfter,
                 anchor: "test".to_string(),
                 end: None,
+                end_inclusive: true,
+                include_leading_trivia: false,
             },
             content: "// comment".to_string(),
             file_path: file.path().to_path_buf(),
             language_name: LanguageName::Rust,
             edit_position: None,
+            source_fingerprint: None,
+            diff_options: Default::default(),
+            format_mode: Default::default(),
+            validation_level: Default::default(),
+            rules: Default::default(),
+            dirty: None,
+            cell: None,
+            candidate: None,
+            target: None,
+            duplicate: None,
         };
 
-        let editor = Editor::from_staged_operation(staged_op, &language_registry);
+        let editor =
+            Editor::from_staged_operation(staged_op, &language_registry, &StdFileOperations, None);
         assert!(editor.is_ok());
     }
 
@@ -74,6 +90,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "{}".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -105,6 +123,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "{}".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -138,6 +158,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -168,6 +190,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "nonexistent".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor_result = Editor::new(
@@ -198,6 +222,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -254,12 +280,11 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
-        let staged_edit = EditPosition {
-            start_byte: 10,
-            end_byte: None,
-        };
+        let staged_edit = EditPosition::new(10, None);
 
         let editor = Editor::new(
             "// comment".to_string(),
@@ -289,6 +314,8 @@ mod editor_behavior_tests {
             operation: Operation::InsertAfter,
             anchor: "let z = 3;".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
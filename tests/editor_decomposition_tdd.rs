@@ -32,6 +32,8 @@ mod editor_decomposition_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -50,7 +52,7 @@ mod editor_decomposition_tests {
         // The validation logic should be separated from Editor
         // This test documents that validation should be its own concern
         let (message, _) = result.unwrap();
-        assert!(message.contains("Syntax error"));
+        assert!(message.contains("SYNTAX ERRORS"), "{message}");
     }
 
     #[test]
@@ -64,6 +66,8 @@ mod editor_decomposition_tests {
             operation: Operation::InsertAfter,
             anchor: "println".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -98,6 +102,8 @@ mod editor_decomposition_tests {
             operation: Operation::InsertAfter,
             anchor: "let y = 2;".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -130,6 +136,8 @@ mod editor_decomposition_tests {
             operation: Operation::InsertAfter,
             anchor: "}".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -194,6 +202,8 @@ mod editor_decomposition_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -244,6 +254,8 @@ mod editor_decomposition_tests {
             operation: Operation::InsertAfter,
             anchor: "println!(\"hello\");".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -295,6 +307,8 @@ mod editor_integration_tests {
             operation: Operation::InsertAfter,
             anchor: "let x = 42;".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         // All existing Editor methods should continue to work
@@ -337,6 +351,8 @@ mod editor_integration_tests {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -0,0 +1,88 @@
+//! Tests for following .editorconfig conventions when editing
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::editorconfig::{EditorConfig, Indentation};
+use semantic_code_edit_mcp::encoding::TextEncoding;
+use semantic_code_edit_mcp::filesystem::StdFileOperations;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn edit(file_path: &Path, language: LanguageName, anchor: &str, content: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let editor = Editor::new(
+        content.into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(language)?,
+        file_path.to_path_buf(),
+        None,
+    )?;
+    editor.commit_to_string()
+}
+
+#[test]
+fn closer_files_and_later_sections_take_precedence() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let nested = temp_dir.path().join("nested");
+    fs::create_dir(&nested)?;
+    fs::write(
+        temp_dir.path().join(".editorconfig"),
+        "root = true\n\n[*]\nindent_style = space\nindent_size = 2\nend_of_line = lf\n\n[*.py]\nindent_size = 4\n",
+    )?;
+    fs::write(
+        nested.join(".editorconfig"),
+        "[{*.json,*.toml}]\nindent_style = tab\ncharset = utf-8-bom\n",
+    )?;
+
+    let python = EditorConfig::load(&StdFileOperations, &nested.join("main.py"));
+    assert_eq!(python.indentation(), Some(Indentation::Spaces(4)));
+    assert_eq!(python.charset(), None);
+
+    let json = EditorConfig::load(&StdFileOperations, &nested.join("config.json"));
+    assert_eq!(json.indentation(), Some(Indentation::Tabs));
+    assert_eq!(json.charset(), Some(TextEncoding::Utf8Bom));
+
+    let outside = EditorConfig::load(&StdFileOperations, &temp_dir.path().join("config.json"));
+    assert_eq!(outside.indentation(), Some(Indentation::Spaces(2)));
+    Ok(())
+}
+
+#[test]
+fn json_is_formatted_with_the_configured_indentation() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(
+        temp_dir.path().join(".editorconfig"),
+        "root = true\n\n[*.json]\nindent_style = tab\n",
+    )?;
+    let file_path = temp_dir.path().join("config.json");
+    // the file itself is indented with four spaces, which detection alone would keep
+    fs::write(&file_path, "{\n    \"a\": 1\n}\n")?;
+
+    let output = edit(&file_path, LanguageName::Json, "\"a\": 1", ",\n\"b\": 2")?;
+    assert_eq!(output, "{\n\t\"a\": 1,\n\t\"b\": 2\n}\n");
+    Ok(())
+}
+
+#[test]
+fn inserted_content_line_endings_and_final_newline_follow_the_config() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(
+        temp_dir.path().join(".editorconfig"),
+        "root = true\n\n[*.py]\nindent_style = tab\nindent_size = 4\nend_of_line = crlf\ninsert_final_newline = true\n",
+    )?;
+    let file_path = temp_dir.path().join("main.py");
+    fs::write(&file_path, "def main():\n\tx = 1")?;
+
+    let output = edit(&file_path, LanguageName::Python, "x = 1", "\n    y = 2")?;
+    assert_eq!(output, "def main():\r\n\tx = 1\r\n\ty = 2\r\n");
+    Ok(())
+}
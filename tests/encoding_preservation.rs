@@ -0,0 +1,103 @@
+//! Tests that byte-order marks and UTF-16 encodings survive a stage/commit round trip
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::encoding::TextEncoding;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use std::fs;
+use tempfile::TempDir;
+
+fn stage_and_commit(file_name: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join(file_name);
+    fs::write(&file_path, bytes)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    state.set_default_session_id("encoding-test");
+
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"before\"".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+
+    Ok(fs::read(&file_path)?)
+}
+
+#[test]
+fn utf8_bom_is_preserved_on_commit() -> Result<()> {
+    let written = stage_and_commit("bom.json", b"\xEF\xBB\xBF{\"key\": \"before\"}\n")?;
+
+    assert!(written.starts_with(b"\xEF\xBB\xBF"));
+    let (encoding, text) = TextEncoding::decode(&written).unwrap();
+    assert_eq!(encoding, TextEncoding::Utf8Bom);
+    assert!(text.contains("\"after\""));
+    assert!(!text.contains('\u{feff}'));
+    Ok(())
+}
+
+#[test]
+fn utf16le_is_preserved_on_commit() -> Result<()> {
+    let original = TextEncoding::Utf16Le.encode("{\"key\": \"before\"}\n");
+    let written = stage_and_commit("utf16.json", &original)?;
+
+    let (encoding, text) = TextEncoding::decode(&written).unwrap();
+    assert_eq!(encoding, TextEncoding::Utf16Le);
+    assert!(text.contains("\"after\""));
+    Ok(())
+}
+
+#[test]
+fn plain_utf8_is_written_without_bom() -> Result<()> {
+    let written = stage_and_commit("plain.json", b"{\"key\": \"before\"}\n")?;
+
+    assert!(!written.starts_with(b"\xEF\xBB\xBF"));
+    assert_eq!(
+        TextEncoding::decode(&written).unwrap().0,
+        TextEncoding::Utf8
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_rejects_truncated_utf16() {
+    assert!(TextEncoding::decode(b"\xFF\xFEa\x00b").is_none());
+}
+
+#[test]
+fn encode_round_trips_utf16be() {
+    let bytes = TextEncoding::Utf16Be.encode("héllo");
+    assert!(bytes.starts_with(b"\xFE\xFF"));
+    assert_eq!(
+        TextEncoding::decode(&bytes),
+        Some((TextEncoding::Utf16Be, "héllo".to_string()))
+    );
+}
@@ -5,6 +5,7 @@
 
 use semantic_code_edit_mcp::{
     editor::Editor,
+    error::SemanticEditError,
     languages::{LanguageName, LanguageRegistry},
     selector::{Operation, Selector},
 };
@@ -32,6 +33,8 @@ mod editor_error_handling {
             operation: Operation::InsertAfter,
             anchor: "fn main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         // Try to create editor with non-existent file
@@ -57,6 +60,8 @@ mod editor_error_handling {
             operation: Operation::InsertAfter,
             anchor: "nonexistent_anchor".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let result = Editor::new(
@@ -81,6 +86,8 @@ mod editor_error_handling {
             operation: Operation::ReplaceNode,
             anchor: "fn main() {}".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         // Invalid Rust syntax
@@ -170,6 +177,8 @@ mod file_operations_error_handling {
             operation: Operation::InsertAfter,
             anchor: "main".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -185,8 +194,7 @@ mod file_operations_error_handling {
         let result = editor.commit();
 
         // Should handle permission error gracefully
-        if result.is_err() {
-            let err = result.unwrap_err();
+        if let Err(err) = result {
             assert!(
                 err.to_string().contains("Permission denied")
                     || err.to_string().contains("permission")
@@ -209,6 +217,8 @@ mod edit_iterator_error_handling {
             operation: Operation::InsertAfter,
             anchor: "".to_string(), // Empty anchor
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let result = Editor::new(
@@ -237,6 +247,8 @@ mod edit_iterator_error_handling {
             operation: Operation::ReplaceRange,
             anchor: "🎉".to_string(),
             end: Some("🎉".to_string()),
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -251,14 +263,15 @@ mod edit_iterator_error_handling {
         assert!(editor.is_ok());
 
         if let Ok(ed) = editor {
-            let result = ed.commit();
-            assert!(result.is_ok());
-
-            if let Ok((_, Some(output), _)) = result {
-                // Should preserve valid UTF-8
-                assert!(output.is_char_boundary(0));
-                assert!(output.is_char_boundary(output.len()));
-            }
+            // the only `🎉` is the anchor itself, so there is no range to it
+            let error = ed.commit().unwrap_err();
+            assert!(
+                matches!(
+                    error.downcast_ref::<SemanticEditError>(),
+                    Some(SemanticEditError::RangeNotFound { .. })
+                ),
+                "{error}"
+            );
         }
     }
 }
@@ -283,6 +296,8 @@ struct Config {
             operation: Operation::InsertAfter,
             anchor: "name: String,".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         // Try to insert invalid content in struct
@@ -332,6 +347,8 @@ mod stress_tests {
             operation: Operation::InsertAfter,
             anchor: "fn main() {".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -379,6 +396,8 @@ mod stress_tests {
             operation: Operation::InsertAfter,
             anchor: "println!(\"deep\");".to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -0,0 +1,57 @@
+//! Tests for reporting every location an edit failed at
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+#[test]
+fn failed_edits_list_each_location_tried() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let source = "fn main() {\n    let x = [a; 2];\n    let y = [a; 3];\n}\n";
+    let (report, staged) = Editor::from_source(
+        source,
+        "let b = 1;".into(),
+        Selector::builder(Operation::ReplaceExact, "a;").build()?,
+        registry.get_language(LanguageName::Rust)?,
+    )?
+    .with_format_mode(FormatMode::Never)
+    .preview_report()?;
+    assert!(staged.is_none());
+
+    let attempts = &report.edit.attempts;
+    assert_eq!(attempts.len(), 2, "{attempts:?}");
+    for (index, attempt) in attempts.iter().enumerate() {
+        assert_eq!(attempt.candidate, Some(index));
+        assert_eq!(
+            attempt.target.start.map(|start| start.line),
+            Some(index + 2)
+        );
+        assert!(!attempt.reason.is_empty());
+    }
+
+    let text = report.to_string();
+    assert!(
+        text.contains("The edit failed at each of the 2 locations tried:"),
+        "{text}"
+    );
+    assert!(text.contains("line 3, column 14 (candidate 1): "), "{text}");
+    Ok(())
+}
+
+#[test]
+fn successful_edits_list_no_attempts() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let (report, staged) = Editor::from_source(
+        "fn main() {\n    let x = [a; 2];\n    a;\n}\n",
+        "let b = 1;".into(),
+        Selector::builder(Operation::ReplaceExact, "a;").build()?,
+        registry.get_language(LanguageName::Rust)?,
+    )?
+    .with_format_mode(FormatMode::Never)
+    .preview_report()?;
+    assert!(staged.is_some());
+    assert!(report.edit.attempts.is_empty());
+    assert!(!serde_json::to_string(&report)?.contains("attempts"));
+    Ok(())
+}
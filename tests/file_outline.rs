@@ -0,0 +1,55 @@
+//! Tests for symbol outlines and returning them from open_files
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::outline::Outline;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::Tools;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = r#"fn first() {
+    println!("first");
+}
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+"#;
+
+#[test]
+fn outline_nests_definitions_with_their_lines() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let language = registry.get_language(LanguageName::Rust)?;
+    let tree = language.tree_sitter_parser()?.parse(SOURCE, None).unwrap();
+
+    let outline = Outline::new(language, &tree, SOURCE).unwrap();
+    assert_eq!(
+        outline.to_string(),
+        "function first (lines 1-3)\ntype Point (lines 5-8)\n  field x (line 6)\n  field y (line 7)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn open_files_appends_the_outline() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let rust_file = temp_dir.path().join("main.rs");
+    let text_file = temp_dir.path().join("notes.txt");
+    fs::write(&rust_file, SOURCE)?;
+    fs::write(&text_file, "just notes\n")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let tool: Tools = serde_json::from_value(json!({
+        "name": "open_files",
+        "arguments": { "file_paths": [rust_file, text_file], "outline": true, "head": 1 },
+    }))?;
+    let response = tool.execute(&mut state)?;
+    assert!(response.contains("main.rs OUTLINE"), "{response}");
+    assert!(response.contains("type Point (lines 5-8)\n  field x (line 6)"));
+    assert!(response.contains("Outlines are not available for this file type."));
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Tests for watching session contexts for changes made outside the server
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{RetargetStaged, StageOperation, Tools, ViewStagedDiff};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+fn selector() -> Selector {
+    Selector {
+        operation: Operation::InsertAfter,
+        anchor: "let x = 1;".into(),
+        end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
+    }
+}
+
+fn setup(state: &mut SemanticEditTools, dir: &Path) -> Result<String> {
+    fs::write(dir.join("main.rs"), SOURCE)?;
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": dir },
+    }))?;
+    let response = set_context.execute(state)?;
+    StageOperation {
+        file_path: "main.rs".into(),
+        language: None,
+        selector: selector(),
+        content: Some("\n    let y = 2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)?;
+    Ok(response)
+}
+
+/// Wait for the watcher to notice a change, giving up after a few seconds
+fn wait_for_change(state: &SemanticEditTools) -> Result<bool> {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if state.staged_target_changed(None)? {
+            return Ok(true);
+        }
+        sleep(Duration::from_millis(20));
+    }
+    Ok(false)
+}
+
+#[test]
+fn external_edits_flag_the_staged_operation() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = setup(&mut state, temp_dir.path())?;
+    assert!(
+        !response.contains("File watching is unavailable"),
+        "{response}"
+    );
+    assert!(!state.staged_target_changed(None)?);
+
+    fs::write(
+        temp_dir.path().join("main.rs"),
+        format!("// edited elsewhere\n{SOURCE}"),
+    )?;
+    assert!(wait_for_change(&state)?);
+
+    let diff = ViewStagedDiff {
+        diff_options: None,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(
+        diff.contains("changed on disk after this operation was staged"),
+        "{diff}"
+    );
+
+    // restaging against the current content clears the flag
+    RetargetStaged {
+        selector: selector(),
+        candidate: None,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(!state.staged_target_changed(None)?);
+    Ok(())
+}
+
+#[test]
+fn watching_can_be_disabled() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?.with_watch_files(false);
+    setup(&mut state, temp_dir.path())?;
+
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {}\n")?;
+    sleep(Duration::from_millis(200));
+    assert!(!state.staged_target_changed(None)?);
+    Ok(())
+}
@@ -22,11 +22,8 @@ mod filesystem_error_handling_tdd {
 
         // For now, just test that we need a safe version that returns Result
         // This method doesn't exist yet and will cause compilation failure
-        match ops.get_captured_writes_safe() {
-            // This method doesn't exist yet
-            Ok(_) => {}  // Normal case
-            Err(_) => {} // Error case - should handle poison without panic
-        }
+        // Either result is fine: an error should handle poison without panic
+        let _ = ops.get_captured_writes_safe();
     }
 
     /// TDD RED: Test that get_last_write_content handles poison gracefully
@@ -88,10 +85,7 @@ mod filesystem_error_handling_tdd {
         let result = ops.write_file(PathBuf::from("test.txt"), "content".to_string());
 
         // We want this to either succeed or return a proper error, never panic
-        match result {
-            Ok(()) => {} // Success case
-            Err(_) => {} // Error case is acceptable, panic is not
-        }
+        let _ = result;
     }
 
     /// TDD RED: Test concurrent access without panicking
@@ -8,6 +8,39 @@ use semantic_code_edit_mcp::filesystem::{
 };
 use std::path::PathBuf;
 
+/// TDD RED: Define the standardized trait we want to implement
+/// This trait represents the desired API for safe file test operations
+pub trait SafeFileTestOperations {
+    type Error;
+
+    fn safe_write_count(&self) -> Result<usize, Self::Error>;
+    fn safe_get_captured_writes(&self) -> Result<Vec<(PathBuf, String)>, Self::Error>;
+    fn safe_get_last_write_content(&self) -> Result<Option<String>, Self::Error>;
+    fn safe_clear_captures(&self) -> Result<(), Self::Error>;
+}
+
+// Implementation of the standardized trait
+impl SafeFileTestOperations for TestFileOperations {
+    type Error = TestFileOperationsError;
+
+    fn safe_write_count(&self) -> Result<usize, Self::Error> {
+        // This delegates to the existing safe method
+        self.write_count_safe()
+    }
+
+    fn safe_get_captured_writes(&self) -> Result<Vec<(PathBuf, String)>, Self::Error> {
+        self.get_captured_writes_safe()
+    }
+
+    fn safe_get_last_write_content(&self) -> Result<Option<String>, Self::Error> {
+        self.get_last_write_content_safe()
+    }
+
+    fn safe_clear_captures(&self) -> Result<(), Self::Error> {
+        self.clear_captures_safe()
+    }
+}
+
 #[cfg(test)]
 mod filesystem_standardization_tdd {
     use super::*;
@@ -117,36 +150,3 @@ mod filesystem_standardization_tdd {
         }
     }
 }
-
-/// TDD RED: Define the standardized trait we want to implement
-/// This trait represents the desired API for safe file test operations
-pub trait SafeFileTestOperations {
-    type Error;
-
-    fn safe_write_count(&self) -> Result<usize, Self::Error>;
-    fn safe_get_captured_writes(&self) -> Result<Vec<(PathBuf, String)>, Self::Error>;
-    fn safe_get_last_write_content(&self) -> Result<Option<String>, Self::Error>;
-    fn safe_clear_captures(&self) -> Result<(), Self::Error>;
-}
-
-// Implementation of the standardized trait
-impl SafeFileTestOperations for TestFileOperations {
-    type Error = TestFileOperationsError;
-
-    fn safe_write_count(&self) -> Result<usize, Self::Error> {
-        // This delegates to the existing safe method
-        self.write_count_safe()
-    }
-
-    fn safe_get_captured_writes(&self) -> Result<Vec<(PathBuf, String)>, Self::Error> {
-        self.get_captured_writes_safe()
-    }
-
-    fn safe_get_last_write_content(&self) -> Result<Option<String>, Self::Error> {
-        self.get_last_write_content_safe()
-    }
-
-    fn safe_clear_captures(&self) -> Result<(), Self::Error> {
-        self.clear_captures_safe()
-    }
-}
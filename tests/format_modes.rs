@@ -0,0 +1,95 @@
+//! Tests for per-session and per-call format modes
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{FormatMode, ResultFormat};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation, Tools};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn  untouched( )  {}\n\nfn main() {\n    let x = 1;\n}\n";
+
+fn edit_with_context(context: Value, format_mode: Option<FormatMode>) -> Result<String> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("lib.rs");
+    fs::write(&file_path, SOURCE)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let mut arguments = json!({ "path": temp_dir.path() });
+    arguments
+        .as_object_mut()
+        .unwrap()
+        .extend(context.as_object().unwrap().clone());
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": arguments,
+    }))?;
+    set_context.execute(&mut state)?;
+
+    StageOperation {
+        file_path: "lib.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("let   x =   2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+
+    Ok(fs::read_to_string(&file_path)?)
+}
+
+#[test]
+fn default_formats_the_whole_file() -> Result<()> {
+    let output = edit_with_context(json!({}), None)?;
+    assert!(output.contains("fn untouched() {}"));
+    assert!(output.contains("    let x = 2;"));
+    Ok(())
+}
+
+#[test]
+fn edited_region_leaves_untouched_code_alone() -> Result<()> {
+    let output = edit_with_context(json!({ "format_mode": "edited_region" }), None)?;
+    assert!(output.contains("fn  untouched( )  {}"));
+    assert!(output.contains("    let x = 2;"));
+    Ok(())
+}
+
+#[test]
+fn never_keeps_the_edit_as_written() -> Result<()> {
+    let output = edit_with_context(json!({ "format_mode": "never" }), None)?;
+    assert!(output.contains("fn  untouched( )  {}"));
+    assert!(output.contains("    let   x =   2;"));
+    Ok(())
+}
+
+#[test]
+fn per_call_format_mode_overrides_session() -> Result<()> {
+    let output = edit_with_context(json!({ "format_mode": "never" }), Some(FormatMode::Always))?;
+    assert!(output.contains("fn untouched() {}"));
+    assert!(output.contains("    let x = 2;"));
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Tests for honoring project formatter configuration files
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn edit(file_path: &Path, language: LanguageName, anchor: &str, content: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let editor = Editor::new(
+        content.into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(language)?,
+        file_path.to_path_buf(),
+        None,
+    )?;
+    editor.commit_to_string()
+}
+
+#[test]
+fn rustfmt_toml_takes_precedence_over_editorconfig() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(
+        temp_dir.path().join(".editorconfig"),
+        "root = true\n\n[*.rs]\nindent_style = space\nindent_size = 2\n",
+    )?;
+    fs::write(temp_dir.path().join("rustfmt.toml"), "hard_tabs = true\n")?;
+    let src = temp_dir.path().join("src");
+    fs::create_dir(&src)?;
+    let file_path = src.join("main.rs");
+    fs::write(&file_path, "fn main() {\n\tlet x = 1;\n}\n")?;
+
+    let output = edit(&file_path, LanguageName::Rust, "let x = 1;", "\nlet y = 2;")?;
+    assert_eq!(output, "fn main() {\n\tlet x = 1;\n\tlet y = 2;\n}\n");
+    Ok(())
+}
+
+#[test]
+fn taplo_toml_formatting_options_apply() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(
+        temp_dir.path().join("taplo.toml"),
+        "[formatting]\nalign_entries = true\n",
+    )?;
+    let file_path = temp_dir.path().join("config.toml");
+    fs::write(&file_path, "[package]\nname = \"demo\"\n")?;
+
+    let output = edit(
+        &file_path,
+        LanguageName::Toml,
+        "name = \"demo\"",
+        "\nversion = \"1.0.0\"",
+    )?;
+    assert_eq!(
+        output,
+        "[package]\nname    = \"demo\"\nversion = \"1.0.0\"\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn prettierrc_indentation_applies_to_json() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join(".prettierrc"), "{ \"tabWidth\": 3 }\n")?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, "{\n    \"a\": 1\n}\n")?;
+
+    let output = edit(&file_path, LanguageName::Json, "\"a\": 1", ",\n\"b\": 2")?;
+    assert_eq!(output, "{\n   \"a\": 1,\n   \"b\": 2\n}\n");
+    Ok(())
+}
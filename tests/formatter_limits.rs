@@ -0,0 +1,82 @@
+//! Tests for stopping formatters that run too long or write too much
+
+#![cfg(unix)]
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatLimits};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// A project whose prettier runs the given script
+fn project_with_prettier(script: &str) -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let bin = temp_dir.path().join("node_modules").join(".bin");
+    fs::create_dir_all(&bin)?;
+    fs::write(bin.join("prettier"), script)?;
+    fs::set_permissions(bin.join("prettier"), fs::Permissions::from_mode(0o755))?;
+    let file_path = temp_dir.path().join("main.js");
+    fs::write(&file_path, "const x = 1;\n")?;
+    Ok((temp_dir, file_path))
+}
+
+fn preview(file_path: &Path, limits: FormatLimits) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let (report, staged) = Editor::new(
+        "\nconst y = 2;".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "const x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(LanguageName::Javascript)?,
+        file_path.to_path_buf(),
+        None,
+    )?
+    .with_format_limits(limits)
+    .preview()?;
+    assert!(staged.is_some(), "the edit should be staged unformatted");
+    Ok(report)
+}
+
+#[test]
+fn formatters_that_run_too_long_are_stopped() -> Result<()> {
+    let (_temp_dir, file_path) = project_with_prettier("#!/bin/sh\nsleep 5\ncat\n")?;
+    let started = Instant::now();
+    let report = preview(
+        &file_path,
+        FormatLimits {
+            timeout: Duration::from_millis(200),
+            ..FormatLimits::default()
+        },
+    )?;
+    assert!(started.elapsed() < Duration::from_secs(4));
+    assert!(
+        report.contains("`prettier` did not finish formatting within 200ms and was stopped, so this edit has not been formatted"),
+        "{report}"
+    );
+    Ok(())
+}
+
+#[test]
+fn formatters_that_write_too_much_are_stopped() -> Result<()> {
+    let (_temp_dir, file_path) = project_with_prettier("#!/bin/sh\ncat >/dev/null\nyes\n")?;
+    let report = preview(
+        &file_path,
+        FormatLimits {
+            max_output_bytes: 1000,
+            ..FormatLimits::default()
+        },
+    )?;
+    assert!(
+        report.contains("`prettier` wrote more than 1000 bytes of output and was stopped"),
+        "{report}"
+    );
+    Ok(())
+}
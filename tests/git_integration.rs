@@ -0,0 +1,306 @@
+//! Tests for committing applied edits to git, snapshotting files before they are written, and
+//! warning about edits to dirty files
+
+mod common;
+
+use anyhow::Result;
+use common::call;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(directory: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()?;
+    assert!(output.status.success(), "git {args:?} failed: {output:?}");
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// A repository with one committed Rust file
+fn repository() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path();
+    git(path, &["init", "--quiet"])?;
+    git(path, &["config", "user.name", "Test"])?;
+    git(path, &["config", "user.email", "test@example.com"])?;
+    git(path, &["config", "commit.gpgsign", "false"])?;
+    fs::write(path.join("main.rs"), "fn main() {\n    let x = 1;\n}\n")?;
+    git(path, &["add", "main.rs"])?;
+    git(path, &["commit", "--quiet", "--message", "initial"])?;
+    Ok(temp_dir)
+}
+
+fn stage(state: &mut SemanticEditTools) -> Result<String> {
+    call(
+        state,
+        "stage_operation",
+        json!({
+            "file_path": "main.rs",
+            "operation": "replace_exact",
+            "anchor": "let x = 1;",
+            "content": "let x = 2;",
+        }),
+    )
+}
+
+fn edit(state: &mut SemanticEditTools) -> Result<String> {
+    stage(state)?;
+    call(state, "commit_staged", json!({}))
+}
+
+#[test]
+fn committed_edits_are_committed_to_git_when_enabled() -> Result<()> {
+    let repository = repository()?;
+    let path = repository.path();
+    // changes the user staged are not swept into the edit's commit
+    fs::write(path.join("notes.txt"), "work in progress\n")?;
+    git(path, &["add", "notes.txt"])?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = call(
+        &mut state,
+        "set_context",
+        json!({ "path": path, "git_commit": true }),
+    )?;
+    assert!(response.contains("Git auto-commit enabled"), "{response}");
+
+    let response = edit(&mut state)?;
+    let hash = git(path, &["rev-parse", "--short", "HEAD"])?;
+    assert!(
+        response.contains(&format!(
+            "Committed to git as {}: semantic-edit: replace exact in main.rs",
+            hash.trim()
+        )),
+        "{response}"
+    );
+
+    let message = git(path, &["log", "-1", "--format=%B"])?;
+    assert!(
+        message.starts_with("semantic-edit: replace exact in main.rs\n\nAnchor: `let x = 1;`\n"),
+        "{message}"
+    );
+    assert!(
+        message.contains("Diff stat: 1 insertion(+), 1 deletion(-)"),
+        "{message}"
+    );
+    let committed = git(path, &["show", "--name-only", "--format=", "HEAD"])?;
+    assert_eq!(committed.trim(), "main.rs");
+    let staged = git(path, &["diff", "--cached", "--name-only"])?;
+    assert_eq!(staged.trim(), "notes.txt");
+    Ok(())
+}
+
+#[test]
+fn edits_are_not_committed_to_git_by_default() -> Result<()> {
+    let repository = repository()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": repository.path() }),
+    )?;
+    let response = edit(&mut state)?;
+    assert!(!response.contains("Committed to git"), "{response}");
+    let log = git(repository.path(), &["log", "--format=%s"])?;
+    assert_eq!(log.trim(), "initial");
+    Ok(())
+}
+
+#[test]
+fn files_outside_a_repository_are_written_with_a_warning() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, "fn main() {\n    let x = 1;\n}\n")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": temp_dir.path(), "git_commit": true }),
+    )?;
+
+    let response = edit(&mut state)?;
+    assert!(
+        response.contains("the edit was written but not committed to git"),
+        "{response}"
+    );
+    assert!(fs::read_to_string(&file_path)?.contains("let x = 2;"));
+    Ok(())
+}
+
+#[test]
+fn uncommitted_content_is_snapshotted_before_it_is_overwritten() -> Result<()> {
+    let repository = repository()?;
+    let path = repository.path();
+    let work_in_progress = "fn main() {\n    let x = 1; // not committed yet\n}\n";
+    fs::write(path.join("main.rs"), work_in_progress)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": path, "git_snapshot": true }),
+    )?;
+    stage(&mut state)?;
+    let response = call(
+        &mut state,
+        "commit_staged",
+        json!({ "acknowledge_dirty": true }),
+    )?;
+    let reference = response
+        .split_whitespace()
+        .find(|word| word.starts_with("refs/semantic-edit/snapshots/"))
+        .expect("the response names the snapshot")
+        .trim_end_matches('.');
+
+    assert_eq!(
+        git(path, &["show", &format!("{reference}:main.rs")])?,
+        work_in_progress
+    );
+    // the index, branch, and working tree are left alone
+    assert!(git(path, &["diff", "--cached", "--name-only"])?.is_empty());
+    assert_eq!(git(path, &["log", "--format=%s"])?.trim(), "initial");
+    assert!(fs::read_to_string(path.join("main.rs"))?.contains("let x = 2;"));
+
+    git(path, &["checkout", reference, "--", "main.rs"])?;
+    assert_eq!(fs::read_to_string(path.join("main.rs"))?, work_in_progress);
+    Ok(())
+}
+
+#[test]
+fn files_that_cannot_be_snapshotted_are_not_written() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, "fn main() {\n    let x = 1;\n}\n")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": temp_dir.path(), "git_snapshot": true }),
+    )?;
+
+    let error = edit(&mut state).unwrap_err().to_string();
+    assert!(
+        error.contains("was not written because its content could not be saved in git first"),
+        "{error}"
+    );
+    assert!(fs::read_to_string(&file_path)?.contains("let x = 1;"));
+    Ok(())
+}
+
+#[test]
+fn edits_to_files_with_uncommitted_changes_need_acknowledging() -> Result<()> {
+    let repository = repository()?;
+    let path = repository.path();
+    fs::write(
+        path.join("main.rs"),
+        "fn main() {\n    let x = 1;\n    let y = 1;\n}\n",
+    )?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(&mut state, "set_context", json!({ "path": path }))?;
+    let preview = stage(&mut state)?;
+    assert!(
+        preview.contains("main.rs has uncommitted changes in git"),
+        "{preview}"
+    );
+
+    let error = call(&mut state, "commit_staged", json!({}))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("acknowledge_dirty: true"), "{error}");
+    assert!(fs::read_to_string(path.join("main.rs"))?.contains("let x = 1;"));
+
+    // the operation stays staged until it is acknowledged
+    call(
+        &mut state,
+        "commit_staged",
+        json!({ "acknowledge_dirty": true }),
+    )?;
+    assert!(fs::read_to_string(path.join("main.rs"))?.contains("let x = 2;"));
+    Ok(())
+}
+
+#[test]
+fn files_with_uncommitted_changes_are_written_but_not_committed() -> Result<()> {
+    let repository = repository()?;
+    let path = repository.path();
+    fs::write(
+        path.join("main.rs"),
+        "fn main() {\n    let x = 1;\n    let y = 1;\n}\n",
+    )?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": path, "git_commit": true }),
+    )?;
+    stage(&mut state)?;
+    let response = call(
+        &mut state,
+        "commit_staged",
+        json!({ "acknowledge_dirty": true }),
+    )?;
+    assert!(
+        response.contains("the edit was written but not committed to git")
+            && response.contains("already had uncommitted changes"),
+        "{response}"
+    );
+    assert!(fs::read_to_string(path.join("main.rs"))?.contains("let x = 2;"));
+    // the user's own change is not committed under the edit's name
+    let log = git(path, &["log", "--format=%s"])?;
+    assert_eq!(log.trim(), "initial");
+    Ok(())
+}
+
+#[test]
+fn clean_files_are_not_flagged() -> Result<()> {
+    let repository = repository()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": repository.path() }),
+    )?;
+    let preview = stage(&mut state)?;
+    assert!(!preview.contains("WARNING"), "{preview}");
+    call(&mut state, "commit_staged", json!({}))?;
+    Ok(())
+}
+
+#[test]
+fn conflict_markers_are_flagged_outside_a_repository() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(
+        temp_dir.path().join("main.rs"),
+        "fn main() {\n    let x = 1;\n}\n<<<<<<< HEAD\nfn a() {}\n=======\nfn b() {}\n>>>>>>> theirs\n",
+    )?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": temp_dir.path() }),
+    )?;
+    let message = call(
+        &mut state,
+        "stage_operation",
+        json!({
+            "file_path": "main.rs",
+            "operation": "replace_exact",
+            "anchor": "let x = 1;",
+            "content": "let x = 2;",
+            "format_mode": "never",
+        }),
+    )?;
+    assert!(
+        message.contains("contains merge conflict markers (line 4)"),
+        "{message}"
+    );
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Tests for opening files by glob pattern
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::Tools;
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+/// A project with Rust files in `src/` and `src/nested/`, one of them ignored by .gitignore
+fn setup() -> Result<(TempDir, SemanticEditTools)> {
+    let temp_dir = TempDir::new()?;
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(src.join("nested"))?;
+    fs::write(src.join("a.rs"), "fn a() {}\n")?;
+    fs::write(src.join("nested/b.rs"), "fn b() {}\n")?;
+    fs::write(src.join("generated.rs"), "fn generated() {}\n")?;
+    fs::write(src.join("notes.txt"), "notes\n")?;
+    fs::write(temp_dir.path().join(".gitignore"), "generated.rs\n")?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": temp_dir.path() },
+    }))?;
+    set_context.execute(&mut state)?;
+    Ok((temp_dir, state))
+}
+
+fn open(state: &mut SemanticEditTools, arguments: Value) -> Result<String> {
+    let tool: Tools = serde_json::from_value(json!({
+        "name": "open_files",
+        "arguments": arguments,
+    }))?;
+    tool.execute(state)
+}
+
+#[test]
+fn globs_open_matching_files_that_are_not_ignored() -> Result<()> {
+    let (_temp_dir, mut state) = setup()?;
+
+    let response = open(&mut state, json!({ "file_paths": ["src/**/*.rs"] }))?;
+    assert!(response.contains("fn a() {}"), "{response}");
+    assert!(response.contains("fn b() {}"));
+    assert!(!response.contains("fn generated() {}"));
+    assert!(!response.contains("notes"));
+
+    let response = open(&mut state, json!({ "file_paths": ["src/*.rs"] }))?;
+    assert!(response.contains("fn a() {}"), "{response}");
+    assert!(!response.contains("fn b() {}"));
+
+    let error = open(&mut state, json!({ "file_paths": ["src/**/*.py"] })).unwrap_err();
+    assert!(error.to_string().contains("no files match"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn glob_matches_beyond_the_caps_are_reported() -> Result<()> {
+    let (_temp_dir, mut state) = setup()?;
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": ["src/**/*.rs"], "max_files": 1 }),
+    )?;
+    assert!(response.contains("fn a() {}"), "{response}");
+    assert!(!response.contains("fn b() {}"));
+    assert!(response.contains("Opened 1 files matching glob patterns. 1 more matched"));
+    assert!(response.contains("b.rs"));
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": ["src/**/*.rs"], "max_bytes": 5 }),
+    )?;
+    assert!(response.contains("Opened 0 files"), "{response}");
+    Ok(())
+}
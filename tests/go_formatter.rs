@@ -0,0 +1,97 @@
+//! Tests for formatting and checking Go with gofmt or goimports
+
+#![cfg(all(unix, feature = "go"))]
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::go::GO_FORMATTER_ENV;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+use tree_sitter::Point;
+
+/// A stand-in for gofmt and goimports that records its name and arguments above the source,
+/// and reports a syntax error for any source mentioning `broken`
+const FAKE_FORMATTER: &str = r#"#!/bin/sh
+input=$(cat)
+case "$input" in
+  *broken*) echo "<standard input>:2:4: expected declaration, found broken" >&2; exit 2 ;;
+esac
+echo "// $(basename "$0")${*:+ $*}"
+printf '%s\n' "$input"
+"#;
+
+const SOURCE: &str = "package main\n\nfunc main() {\n\tx := 1\n}\n";
+
+fn edit(registry: &LanguageRegistry, file_path: &Path) -> Result<String> {
+    let editor = Editor::new(
+        "\n\ty := 2".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "x := 1".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(LanguageName::Go)?,
+        file_path.to_path_buf(),
+        None,
+    )?;
+    editor.commit_to_string()
+}
+
+#[test]
+fn go_is_formatted_and_checked_with_the_go_tools() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let bin = temp_dir.path().join("bin");
+    fs::create_dir(&bin)?;
+    for tool in ["gofmt", "goimports"] {
+        fs::write(bin.join(tool), FAKE_FORMATTER)?;
+        fs::set_permissions(bin.join(tool), fs::Permissions::from_mode(0o755))?;
+    }
+    let path = env::join_paths(
+        std::iter::once(bin).chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )?;
+    // SAFETY: this is the only test in this binary, so no other thread reads the environment
+    unsafe { env::set_var("PATH", path) };
+
+    let file_path = temp_dir.path().join("main.go");
+    fs::write(&file_path, SOURCE)?;
+    let registry = LanguageRegistry::new()?;
+    assert_eq!(
+        edit(&registry, &file_path)?,
+        "// gofmt\npackage main\n\nfunc main() {\n\tx := 1\n\ty := 2\n}\n"
+    );
+
+    // tree-sitter accepts the comment, so only gofmt can have reported the error
+    let language = registry.get_language(LanguageName::Go)?;
+    let content = "package main\n// broken\n";
+    let tree = language.tree_sitter_parser()?.parse(content, None).unwrap();
+    assert_eq!(
+        language.editor().collect_errors(&tree, content),
+        vec![Point { row: 1, column: 3 }..Point { row: 1, column: 4 }]
+    );
+
+    // SAFETY: as above
+    unsafe { env::set_var(GO_FORMATTER_ENV, "goimports") };
+    let registry = LanguageRegistry::new()?;
+    assert!(
+        edit(&registry, &file_path)?.starts_with(&format!(
+            "// goimports -srcdir {}\n",
+            temp_dir.path().display()
+        )),
+        "goimports should format when configured"
+    );
+
+    unsafe { env::set_var(GO_FORMATTER_ENV, "go fmt") };
+    // languages are built on first use, which is when the setting is read
+    let error = LanguageRegistry::new()?
+        .get_language(LanguageName::Go)
+        .unwrap_err();
+    assert!(error.to_string().contains("must be `gofmt` or `goimports`"));
+    Ok(())
+}
@@ -0,0 +1,471 @@
+//! Tests for serving MCP over streamable HTTP and WebSockets
+#![cfg(feature = "http")]
+
+use anyhow::Result;
+use mcplease::server_info;
+use semantic_code_edit_mcp::http::HttpServer;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::{Value, json};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use tempfile::TempDir;
+
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn json(&self) -> Result<Value> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
+}
+
+/// Start a server on a free port, returning its address
+fn start(token: Option<&str>) -> Result<SocketAddr> {
+    let server = HttpServer::bind("127.0.0.1:0")?
+        .with_token(token.map(String::from))
+        .with_allowed_hosts(vec!["edit.internal".into()]);
+    let address = server.local_addr()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    std::thread::spawn(move || server.serve(&mut state, server_info!(), None));
+    Ok(address)
+}
+
+fn send(
+    address: SocketAddr,
+    method: &str,
+    headers: &[(&str, &str)],
+    body: Option<&Value>,
+) -> Result<HttpResponse> {
+    let host = address.to_string();
+    let host = headers
+        .iter()
+        .find(|(name, _)| *name == "Host")
+        .map_or(host.as_str(), |(_, host)| *host);
+    let body = body.map(Value::to_string).unwrap_or_default();
+    let mut request = format!(
+        "{method} /mcp HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        body.len()
+    );
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "Host") {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    request.push_str(&body);
+
+    let mut stream = TcpStream::connect(address)?;
+    stream.write_all(request.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let mut lines = head.lines();
+    let status = lines.next().unwrap_or_default().split(' ').nth(1);
+    Ok(HttpResponse {
+        status: status.unwrap_or_default().parse()?,
+        headers: lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect(),
+        body: body.to_string(),
+    })
+}
+
+fn rpc(id: u64, method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+}
+
+fn initialize(address: SocketAddr) -> Result<String> {
+    let response = send(address, "POST", &[], Some(&rpc(1, "initialize", json!({}))))?;
+    assert_eq!(response.status, 200);
+    assert!(response.json()?["result"]["serverInfo"].is_object());
+    Ok(response.header("Mcp-Session-Id").unwrap().to_string())
+}
+
+fn call_tool(
+    address: SocketAddr,
+    session_id: &str,
+    name: &str,
+    arguments: Value,
+) -> Result<HttpResponse> {
+    send(
+        address,
+        "POST",
+        &[("Mcp-Session-Id", session_id)],
+        Some(&rpc(
+            2,
+            "tools/call",
+            json!({"name": name, "arguments": arguments}),
+        )),
+    )
+}
+
+#[test]
+fn sessions_are_isolated_between_clients() -> Result<()> {
+    let address = start(None)?;
+    let first = TempDir::new()?;
+    let second = TempDir::new()?;
+
+    let alice = initialize(address)?;
+    let bob = initialize(address)?;
+    assert_ne!(alice, bob);
+
+    let response = call_tool(
+        address,
+        &alice,
+        "set_context",
+        json!({"path": first.path()}),
+    )?;
+    assert_eq!(response.status, 200, "{}", response.body);
+    call_tool(address, &bob, "set_context", json!({"path": second.path()}))?;
+
+    std::fs::write(first.path().join("alice.txt"), "hello from alice")?;
+    let response = call_tool(
+        address,
+        &alice,
+        "open_files",
+        json!({"file_paths": ["alice.txt"]}),
+    )?;
+    assert!(
+        response.body.contains("hello from alice"),
+        "{}",
+        response.body
+    );
+
+    // bob's relative paths resolve against bob's context
+    let response = call_tool(
+        address,
+        &bob,
+        "open_files",
+        json!({"file_paths": ["alice.txt"]}),
+    )?;
+    assert!(
+        !response.body.contains("hello from alice"),
+        "{}",
+        response.body
+    );
+    Ok(())
+}
+
+#[test]
+fn requests_need_a_live_session() -> Result<()> {
+    let address = start(None)?;
+    let list = rpc(3, "tools/list", json!({}));
+
+    let response = send(address, "POST", &[], Some(&list))?;
+    assert_eq!(response.status, 400);
+    let response = send(address, "POST", &[("Mcp-Session-Id", "nope")], Some(&list))?;
+    assert_eq!(response.status, 404);
+
+    let session = initialize(address)?;
+    let response = send(
+        address,
+        "POST",
+        &[("Mcp-Session-Id", &session)],
+        Some(&list),
+    )?;
+    assert_eq!(response.status, 200);
+    assert!(
+        response.json()?["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .len()
+            > 5
+    );
+
+    let response = send(address, "DELETE", &[("Mcp-Session-Id", &session)], None)?;
+    assert_eq!(response.status, 200);
+    let response = send(
+        address,
+        "POST",
+        &[("Mcp-Session-Id", &session)],
+        Some(&list),
+    )?;
+    assert_eq!(response.status, 404);
+    Ok(())
+}
+
+#[test]
+fn notifications_are_accepted_and_sse_is_offered() -> Result<()> {
+    let address = start(None)?;
+    let session = initialize(address)?;
+
+    let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+    let response = send(
+        address,
+        "POST",
+        &[("Mcp-Session-Id", &session)],
+        Some(&notification),
+    )?;
+    assert_eq!(response.status, 202);
+
+    let response = send(
+        address,
+        "POST",
+        &[
+            ("Mcp-Session-Id", &session),
+            ("Accept", "text/event-stream"),
+        ],
+        Some(&rpc(4, "tools/list", json!({}))),
+    )?;
+    assert_eq!(response.header("Content-Type"), Some("text/event-stream"));
+    let data = response
+        .body
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .unwrap();
+    let message: Value = serde_json::from_str(data)?;
+    assert_eq!(message["id"], 4);
+
+    let response = send(address, "GET", &[], None)?;
+    assert_eq!(response.status, 405);
+    Ok(())
+}
+
+#[test]
+fn tokens_and_origins_are_checked() -> Result<()> {
+    let address = start(Some("secret"))?;
+    let initialize = rpc(1, "initialize", json!({}));
+
+    let response = send(address, "POST", &[], Some(&initialize))?;
+    assert_eq!(response.status, 401);
+    let response = send(
+        address,
+        "POST",
+        &[("Authorization", "Bearer wrong")],
+        Some(&initialize),
+    )?;
+    assert_eq!(response.status, 401);
+    let response = send(
+        address,
+        "POST",
+        &[("Authorization", "Bearer secret")],
+        Some(&initialize),
+    )?;
+    assert_eq!(response.status, 200);
+
+    let response = send(
+        address,
+        "POST",
+        &[
+            ("Authorization", "Bearer secret"),
+            ("Origin", "http://evil.example"),
+        ],
+        Some(&initialize),
+    )?;
+    assert_eq!(response.status, 403);
+    Ok(())
+}
+
+#[test]
+fn only_loopback_and_allowed_hosts_are_served() -> Result<()> {
+    let address = start(None)?;
+    let initialize = rpc(1, "initialize", json!({}));
+    let status = |headers: &[(&str, &str)]| -> Result<u16> {
+        Ok(send(address, "POST", headers, Some(&initialize))?.status)
+    };
+
+    assert_eq!(status(&[("Host", "localhost:8080")])?, 200);
+    assert_eq!(status(&[("Host", "[::1]:8080")])?, 200);
+    assert_eq!(
+        status(&[
+            ("Host", "edit.internal:8080"),
+            ("Origin", "http://edit.internal:8080")
+        ])?,
+        200
+    );
+    // a rebound domain names itself as both host and origin
+    assert_eq!(
+        status(&[
+            ("Host", "evil.example:8080"),
+            ("Origin", "http://evil.example:8080")
+        ])?,
+        403
+    );
+    assert_eq!(status(&[("Host", "evil.example")])?, 403);
+    assert_eq!(status(&[("Origin", "null")])?, 403);
+    Ok(())
+}
+
+/// A WebSocket client connection
+struct WebSocket {
+    stream: TcpStream,
+}
+
+impl WebSocket {
+    /// Connect to a server with the example key from RFC 6455
+    fn connect(address: SocketAddr, headers: &[(&str, &str)]) -> Result<(Self, String)> {
+        let mut request = format!(
+            "GET /mcp HTTP/1.1\r\nHost: {address}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n"
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        let mut stream = TcpStream::connect(address)?;
+        stream.write_all(request.as_bytes())?;
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0];
+            if stream.read(&mut byte)? == 0 {
+                break;
+            }
+            head.push(byte[0]);
+        }
+        Ok((Self { stream }, String::from_utf8(head)?))
+    }
+
+    fn send_frame(&mut self, fin: bool, opcode: u8, payload: &[u8]) -> Result<()> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+        if payload.len() < 126 {
+            frame.push(0x80 | payload.len() as u8);
+        } else {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(
+            payload
+                .iter()
+                .enumerate()
+                .map(|(index, byte)| byte ^ mask[index % 4]),
+        );
+        self.stream.write_all(&frame)?;
+        Ok(())
+    }
+
+    /// Read a frame's opcode and payload
+    fn receive_frame(&mut self) -> Result<(u8, Vec<u8>)> {
+        let mut header = [0; 2];
+        self.stream.read_exact(&mut header)?;
+        let length = match header[1] & 0x7F {
+            126 => {
+                let mut length = [0; 2];
+                self.stream.read_exact(&mut length)?;
+                u16::from_be_bytes(length) as usize
+            }
+            127 => {
+                let mut length = [0; 8];
+                self.stream.read_exact(&mut length)?;
+                u64::from_be_bytes(length) as usize
+            }
+            length => length as usize,
+        };
+        let mut payload = vec![0; length];
+        self.stream.read_exact(&mut payload)?;
+        Ok((header[0] & 0x0F, payload))
+    }
+
+    fn call(&mut self, message: &Value) -> Result<Value> {
+        self.send_frame(true, 0x1, message.to_string().as_bytes())?;
+        let (opcode, payload) = self.receive_frame()?;
+        assert_eq!(opcode, 0x1);
+        Ok(serde_json::from_slice(&payload)?)
+    }
+
+    fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value> {
+        self.call(&rpc(
+            2,
+            "tools/call",
+            json!({"name": name, "arguments": arguments}),
+        ))
+    }
+}
+
+#[test]
+fn websocket_connections_answer_requests() -> Result<()> {
+    let address = start(None)?;
+    let (mut socket, head) = WebSocket::connect(address, &[])?;
+    assert!(head.starts_with("HTTP/1.1 101"), "{head}");
+    assert!(
+        head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="),
+        "{head}"
+    );
+
+    let response = socket.call(&rpc(1, "initialize", json!({})))?;
+    assert!(response["result"]["serverInfo"].is_object());
+
+    // notifications get no answer, so the next frame answers the fragmented request
+    socket.send_frame(
+        true,
+        0x1,
+        json!({"jsonrpc": "2.0", "method": "notifications/initialized"})
+            .to_string()
+            .as_bytes(),
+    )?;
+    let list = rpc(5, "tools/list", json!({})).to_string();
+    let (first, second) = list.split_at(10);
+    socket.send_frame(false, 0x1, first.as_bytes())?;
+    socket.send_frame(true, 0x0, second.as_bytes())?;
+    let (_, payload) = socket.receive_frame()?;
+    let response: Value = serde_json::from_slice(&payload)?;
+    assert_eq!(response["id"], 5);
+
+    socket.send_frame(true, 0x9, b"still there?")?;
+    assert_eq!(socket.receive_frame()?, (0xA, b"still there?".to_vec()));
+
+    socket.send_frame(true, 0x8, &1000u16.to_be_bytes())?;
+    assert_eq!(socket.receive_frame()?.0, 0x8);
+    Ok(())
+}
+
+#[test]
+fn each_websocket_connection_is_its_own_session() -> Result<()> {
+    let address = start(Some("secret"))?;
+    let auth = [("Authorization", "Bearer secret")];
+    let first = TempDir::new()?;
+    let second = TempDir::new()?;
+    std::fs::write(first.path().join("notes.txt"), "first window")?;
+    std::fs::write(second.path().join("notes.txt"), "second window")?;
+
+    let (mut one, _) = WebSocket::connect(address, &auth)?;
+    let (mut two, _) = WebSocket::connect(address, &auth)?;
+    one.call_tool("set_context", json!({"path": first.path()}))?;
+    two.call_tool("set_context", json!({"path": second.path()}))?;
+
+    let opened = one.call_tool("open_files", json!({"file_paths": ["notes.txt"]}))?;
+    assert!(opened.to_string().contains("first window"), "{opened}");
+    let opened = two.call_tool("open_files", json!({"file_paths": ["notes.txt"]}))?;
+    assert!(opened.to_string().contains("second window"), "{opened}");
+
+    let (_, head) = WebSocket::connect(address, &[])?;
+    assert!(head.starts_with("HTTP/1.1 401"), "{head}");
+    Ok(())
+}
+
+#[test]
+fn websocket_subscribers_are_told_when_resources_change() -> Result<()> {
+    let address = start(None)?;
+    let context = TempDir::new()?;
+    let path = context.path().join("lib.rs");
+    std::fs::write(&path, "fn first() {}\n")?;
+
+    let (mut socket, _) = WebSocket::connect(address, &[])?;
+    socket.call_tool("set_context", json!({"path": context.path()}))?;
+    let response = socket.call(&rpc(
+        6,
+        "resources/subscribe",
+        json!({"uri": "outline://lib.rs"}),
+    ))?;
+    assert!(response["error"].is_null(), "{response}");
+
+    // the change is noticed while the client is idle
+    std::fs::write(&path, "fn first() {}\nfn second() {}\n")?;
+    let (_, payload) = socket.receive_frame()?;
+    let notification: Value = serde_json::from_slice(&payload)?;
+    assert_eq!(notification["method"], "notifications/resources/updated");
+    assert_eq!(notification["params"]["uri"], "outline://lib.rs");
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Tests for idempotent edits, which stage nothing when already applied
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{ResultFormat, already_applied};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "\
+use std::collections::HashMap;
+
+fn main() {
+    let count = 1;
+}
+";
+
+fn stage(
+    state: &mut SemanticEditTools,
+    dir: &TempDir,
+    selector: Selector,
+    content: &str,
+) -> Result<Value> {
+    Ok(serde_json::from_str(
+        &StageOperation {
+            file_path: dir.path().join("main.rs").display().to_string(),
+            language: None,
+            selector,
+            content: Some(content.into()),
+            buffer: None,
+            format: ResultFormat::Json,
+            diff_options: Default::default(),
+            format_mode: None,
+            cell: None,
+            candidate: None,
+            show_alternatives: false,
+            idempotent: true,
+        }
+        .execute(state)?,
+    )?)
+}
+
+fn commit(state: &mut SemanticEditTools) -> Result<()> {
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(state)?;
+    Ok(())
+}
+
+#[test]
+fn retried_inserts_are_not_duplicated() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let selector =
+        || Selector::builder(Operation::InsertAfter, "use std::collections::HashMap;").build();
+
+    let first = stage(&mut state, &dir, selector()?, "\nuse std::fs::File;")?;
+    assert_eq!(first["staged"], true);
+    commit(&mut state)?;
+    let applied = fs::read_to_string(dir.path().join("main.rs"))?;
+
+    let retry = stage(&mut state, &dir, selector()?, "\nuse  std::fs::File;")?;
+    assert_eq!(retry["staged"], false);
+    assert_eq!(retry["already_applied"], true, "{retry}");
+    assert!(state.get_staged_operation(None)?.is_none());
+    assert_eq!(fs::read_to_string(dir.path().join("main.rs"))?, applied);
+    Ok(())
+}
+
+#[test]
+fn replacements_are_applied_once_their_anchor_is_gone() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(dir.path().join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let selector = || Selector::builder(Operation::ReplaceExact, "let count = 1;").build();
+
+    assert_eq!(
+        stage(&mut state, &dir, selector()?, "let count = 2;")?["staged"],
+        true
+    );
+    commit(&mut state)?;
+    let retry = stage(&mut state, &dir, selector()?, "let count = 2;")?;
+    assert_eq!(retry["already_applied"], true, "{retry}");
+
+    // content that is elsewhere in the file is not mistaken for an applied edit
+    let registry = LanguageRegistry::new()?;
+    let applied = fs::read_to_string(dir.path().join("main.rs"))?;
+    assert_eq!(
+        already_applied(
+            registry.get_language(LanguageName::Rust)?,
+            &Selector::builder(Operation::InsertAfter, "use std::collections::HashMap;").build()?,
+            "let count = 2;",
+            &applied,
+        ),
+        None
+    );
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Tests for editing source held in memory through the library API
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::error::SemanticEditError;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const SOURCE: &str = "fn main() {\n    println!(\"hello\");\n}\n";
+
+fn selector(operation: Operation, anchor: &str) -> Selector {
+    Selector {
+        operation,
+        anchor: anchor.into(),
+        end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
+    }
+}
+
+#[test]
+fn commit_to_string_returns_edited_source() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+
+    let editor = Editor::from_source(
+        SOURCE,
+        "fn helper() {}".into(),
+        selector(Operation::InsertAfterNode, "fn main() {"),
+        rust,
+    )?;
+    let output = editor.commit_to_string()?;
+
+    assert!(output.contains("fn main()"));
+    assert!(output.contains("fn helper() {}"));
+    Ok(())
+}
+
+#[test]
+fn preview_reports_diff_for_in_memory_source() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+
+    let editor = Editor::from_source(
+        SOURCE,
+        "\"goodbye\"".into(),
+        selector(Operation::ReplaceExact, "\"hello\""),
+        rust,
+    )?;
+    let (report, _) = editor.preview_report()?;
+
+    assert!(report.staged);
+    assert!(
+        report
+            .edit
+            .diff
+            .unwrap()
+            .contains("+    println!(\"goodbye\");")
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_edit_returns_edit_not_applied() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+
+    let editor = Editor::from_source(
+        SOURCE,
+        "{{{".into(),
+        selector(Operation::ReplaceExact, "\"hello\""),
+        rust,
+    )?;
+    let error = editor.commit_to_string().unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<SemanticEditError>(),
+        Some(SemanticEditError::EditNotApplied { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn missing_anchor_is_reported_against_in_memory_source() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+
+    let error = Editor::from_source(
+        SOURCE,
+        "x".into(),
+        selector(Operation::ReplaceExact, "missing"),
+        rust,
+    )
+    .err()
+    .unwrap();
+    assert!(error.to_string().contains("<in-memory>"));
+    Ok(())
+}
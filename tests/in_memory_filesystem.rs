@@ -0,0 +1,90 @@
+//! Tests for running the stage/commit workflow against the in-memory TestFileOperations
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::filesystem::{FileOperations, TestFileOperations};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use std::path::Path;
+use std::sync::Arc;
+
+#[test]
+fn stage_and_commit_without_touching_disk() -> Result<()> {
+    let file_path = Path::new("/virtual/project/config.json");
+    assert!(!file_path.exists());
+
+    let file_operations =
+        Arc::new(TestFileOperations::new().with_file(file_path, "{\"key\": \"before\"}\n"));
+    let mut state =
+        SemanticEditTools::with_file_operations(None, Box::new(Arc::clone(&file_operations)))?;
+
+    let preview = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"before\"".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    assert!(preview.contains("STAGED"));
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+
+    assert_eq!(file_operations.write_count(), 1);
+    assert!(file_operations.read_file(file_path)?.contains("\"after\""));
+    assert!(!file_path.exists());
+    Ok(())
+}
+
+#[test]
+fn missing_in_memory_file_is_reported() -> Result<()> {
+    let mut state =
+        SemanticEditTools::with_file_operations(None, Box::new(TestFileOperations::new()))?;
+
+    let result = StageOperation {
+        file_path: "/virtual/project/missing.json".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "x".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: None,
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state);
+
+    assert!(result.is_err());
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! Tests for spacing inserted content from the text around it
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n    run(x);\n}\n";
+
+fn insert(operation: Operation, anchor: &str, content: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    Editor::builder(
+        content,
+        Selector::builder(operation, anchor).build()?,
+        registry.get_language(LanguageName::Rust)?,
+    )
+    .with_source(SOURCE)
+    .with_format_mode(FormatMode::Never)
+    .build()?
+    .commit_to_string()
+}
+
+#[test]
+fn inserts_at_line_ends_go_on_their_own_indented_line() -> Result<()> {
+    assert_eq!(
+        insert(Operation::InsertAfter, "let x = 1;", "let y = 2;")?,
+        "fn main() {\n    let x = 1;\n    let y = 2;\n    run(x);\n}\n"
+    );
+    assert_eq!(
+        insert(Operation::InsertBefore, "run(x);", "log(x);")?,
+        "fn main() {\n    let x = 1;\n    log(x);\n    run(x);\n}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn inserts_within_a_line_keep_words_apart() -> Result<()> {
+    assert_eq!(
+        insert(Operation::InsertAfter, "let", "mut")?,
+        "fn main() {\n    let mut x = 1;\n    run(x);\n}\n"
+    );
+    // content that is valid running into the word is taken as extending it
+    assert_eq!(
+        insert(Operation::InsertAfter, "run(x", "_sq")?,
+        "fn main() {\n    let x = 1;\n    run(x_sq);\n}\n"
+    );
+    // punctuation needs no space
+    assert_eq!(
+        insert(Operation::InsertAfter, "run(x", ", 2")?,
+        "fn main() {\n    let x = 1;\n    run(x, 2);\n}\n"
+    );
+    Ok(())
+}
@@ -37,6 +37,8 @@ fn test_editor_workflow_rust_insert_after() {
         operation: Operation::InsertAfter,
         anchor: r#"println!("Hello, world!");"#.to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     // Create editor with new content
@@ -90,6 +92,8 @@ def main():
         operation: Operation::ReplaceNode,
         anchor: "def greet(name):".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let new_content = r#"def greet(name, greeting="Hello"):
@@ -136,6 +140,8 @@ console.log(calculate(5, 3));
         operation: Operation::InsertBefore,
         anchor: "console.log".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let new_content = "// Test the calculate function\n";
@@ -191,6 +197,8 @@ fn test_editor_workflow_replace_range() {
         operation: Operation::ReplaceRange,
         anchor: "// START_REPLACE".to_string(),
         end: Some("// END_REPLACE".to_string()),
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let new_content = r#"// START_REPLACE
@@ -232,6 +240,8 @@ fn test_error_handling_invalid_syntax() {
         operation: Operation::ReplaceNode,
         anchor: "fn main()".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     // Invalid syntax
@@ -257,6 +267,8 @@ fn test_error_handling_anchor_not_found() {
         operation: Operation::InsertAfter,
         anchor: "nonexistent anchor".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let result = Editor::new("content".to_string(), selector, language, file_path, None);
@@ -286,6 +298,8 @@ fn second() {
         operation: Operation::InsertAfterNode,
         anchor: "fn first()".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let new_content = "\nfn between() {\n    println!(\"between\");\n}";
@@ -311,6 +325,8 @@ fn second() {
         operation: Operation::ReplaceExact,
         anchor: "between".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let editor = Editor::new(
@@ -349,6 +365,8 @@ fn test_multi_language_support() {
             operation: Operation::InsertAfter,
             anchor: content.lines().next().unwrap().to_string(),
             end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
         };
 
         let editor = Editor::new(
@@ -393,6 +411,8 @@ fn test_json_formatting_preservation() {
         operation: Operation::InsertAfter,
         anchor: r#""lodash": "4.17.21""#.to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let new_content = r#",
@@ -434,6 +454,8 @@ serde = "1.0"
         operation: Operation::InsertAfter,
         anchor: r#"serde = "1.0""#.to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     let editor = Editor::new(
@@ -465,6 +487,8 @@ fn also_keep() {}"#;
         operation: Operation::ReplaceNode,
         anchor: "fn delete()".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     // Empty content means delete
@@ -494,6 +518,8 @@ fn test_validation_query_rust() {
         operation: Operation::InsertAfter,
         anchor: "name: String,".to_string(),
         end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
     };
 
     // Try to add a function in struct fields (should fail validation)
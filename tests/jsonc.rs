@@ -0,0 +1,97 @@
+//! Tests for editing JSON with comments and trailing commas
+
+use std::path::Path;
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const TSCONFIG: &str = "{\n  // shared settings\n  \"compilerOptions\": {\n    \"strict\": true, /* keep on */\n    \"target\": \"es2022\",\n  },\n}\n";
+
+fn edit(
+    language: LanguageName,
+    operation: Operation,
+    anchor: &str,
+    content: &str,
+) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let selector = Selector::builder(operation, anchor).build()?;
+    let editor = Editor::from_source(
+        TSCONFIG,
+        content.into(),
+        selector,
+        registry.get_language(language)?,
+    )?;
+    let output = editor
+        .with_format_mode(FormatMode::Never)
+        .commit_to_string()?;
+    Ok(output)
+}
+
+#[test]
+fn configuration_files_with_comments_are_jsonc() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    for path in [
+        "tsconfig.json",
+        "packages/web/tsconfig.build.json",
+        "jsconfig.json",
+        ".vscode/settings.json",
+        ".devcontainer/devcontainer.json",
+        "notes.jsonc",
+    ] {
+        assert_eq!(
+            registry.detect_language_from_path(Path::new(path)),
+            Some(LanguageName::Jsonc),
+            "{path}"
+        );
+    }
+    for path in ["package.json", "settings.json", "data/tsconfig.yaml"] {
+        assert_ne!(
+            registry.detect_language_from_path(Path::new(path)),
+            Some(LanguageName::Jsonc),
+            "{path}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn jsonc_edits_keep_comments_and_trailing_commas() -> Result<()> {
+    let output = edit(
+        LanguageName::Jsonc,
+        Operation::ReplaceExact,
+        "\"es2022\"",
+        "\"es2024\"",
+    )?;
+    assert_eq!(output, TSCONFIG.replace("es2022", "es2024"));
+
+    let output = edit(
+        LanguageName::Jsonc,
+        Operation::InsertAfter,
+        "\"target\": \"es2022\",",
+        "\n    // emit declarations\n    \"declaration\": true,",
+    )?;
+    assert!(
+        output.contains(
+            "\"target\": \"es2022\",\n    // emit declarations\n    \"declaration\": true,\n  },"
+        ),
+        "{output}"
+    );
+    Ok(())
+}
+
+#[test]
+fn jsonc_still_rejects_broken_json() -> Result<()> {
+    let error = edit(
+        LanguageName::Jsonc,
+        Operation::ReplaceExact,
+        "\"es2022\"",
+        "es2022",
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("invalid syntax"), "{error}");
+    // reported on the line of the edit, past the comments before it
+    assert!(error.to_string().contains("Syntax error at 5:"), "{error}");
+    Ok(())
+}
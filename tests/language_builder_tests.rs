@@ -1,6 +1,8 @@
+#![cfg(all(feature = "go", feature = "json", feature = "python", feature = "rust"))]
+
 use anyhow::Result;
 use semantic_code_edit_mcp::languages::{
-    LanguageBuilder, LanguageName, LanguageRegistry, simple_language,
+    EmbeddedQuery, LanguageBuilder, LanguageName, LanguageRegistry, simple_language,
 };
 
 #[test]
@@ -145,3 +147,71 @@ fn test_docs_method_works() -> Result<()> {
     assert!(docs.contains("File extensions: go"));
     Ok(())
 }
+
+#[test]
+fn test_invalid_query_error_names_file_and_pattern() {
+    let error = LanguageBuilder::new(LanguageName::Go, &["go"], tree_sitter_go::LANGUAGE.into())
+        .with_validation_query(EmbeddedQuery {
+            path: "queries/go/broken.scm",
+            source: "(function_declaration) @ok\n\n(not_a_real_node) @invalid.typo\n",
+        })
+        .build()
+        .unwrap_err()
+        .to_string();
+
+    assert!(
+        error.contains("invalid embedded query `queries/go/broken.scm`"),
+        "{error}"
+    );
+    assert!(error.contains("line 3"), "{error}");
+    assert!(error.contains("(not_a_real_node) @invalid.typo"), "{error}");
+}
+
+#[test]
+fn test_embedded_queries_are_compiled_once() -> Result<()> {
+    let first = LanguageRegistry::new()?;
+    let second = LanguageRegistry::new()?;
+    let query = |registry: &LanguageRegistry| {
+        registry
+            .get_language(LanguageName::Rust)
+            .unwrap()
+            .validation_query()
+            .map(|query| query as *const _)
+    };
+
+    assert!(query(&first).is_some());
+    assert_eq!(query(&first), query(&second));
+    Ok(())
+}
+
+#[test]
+fn test_languages_are_built_on_first_use() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    assert!(!registry.is_initialized(LanguageName::Go));
+    assert_eq!(
+        registry.detect_language_from_path("main.go".as_ref()),
+        Some(LanguageName::Go),
+        "extensions are known before the language is built"
+    );
+    assert!(!registry.is_initialized(LanguageName::Go));
+
+    let go = registry.get_language(LanguageName::Go)?;
+    assert_eq!(go.file_extensions(), &["go"]);
+    assert!(registry.is_initialized(LanguageName::Go));
+    assert!(!registry.is_initialized(LanguageName::Python));
+
+    // building every language checks that all embedded queries compile
+    let languages = registry.languages()?;
+    assert_eq!(languages.len(), registry.language_names().count());
+    for language in languages {
+        for extension in language.file_extensions() {
+            assert_eq!(
+                registry.detect_language_from_path(format!("file.{extension}").as_ref()),
+                Some(language.name()),
+                "{extension} should be registered for {language}"
+            );
+        }
+    }
+    assert!(registry.is_initialized(LanguageName::Python));
+    Ok(())
+}
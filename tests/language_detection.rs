@@ -0,0 +1,112 @@
+//! Tests for detecting the language of files without a known extension from their content
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::StageOperation;
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+fn detect(registry: &LanguageRegistry, source: &str) -> Option<LanguageName> {
+    registry
+        .detect_language_from_source(source)
+        .map(|guess| guess.language)
+}
+
+#[test]
+fn config_files_and_scripts_are_detected() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    assert_eq!(
+        detect(&registry, "{\n  \"presets\": [\"@babel/env\"]\n}\n"),
+        Some(LanguageName::Json)
+    );
+    assert_eq!(
+        detect(&registry, "[packages]\nrequests = \"*\"\n"),
+        Some(LanguageName::Toml)
+    );
+    assert_eq!(
+        detect(
+            &registry,
+            "source \"https://rubygems.org\"\n\ngem \"rails\"\n"
+        ),
+        Some(LanguageName::Ruby)
+    );
+    assert_eq!(
+        detect(
+            &registry,
+            "import sys\n\ndef main():\n    print(sys.argv)\n"
+        ),
+        Some(LanguageName::Python)
+    );
+
+    let guess = registry
+        .detect_language_from_source("#!/usr/bin/env python3\nmain()\n")
+        .unwrap();
+    assert_eq!(guess.language, LanguageName::Python);
+    assert_eq!(guess.to_string(), "python, from the file's shebang line");
+    Ok(())
+}
+
+#[test]
+fn prose_and_unsupported_formats_stay_plain_text() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    for source in [
+        "This is a README.\n\nIt explains how to build the project.\n",
+        "Hello world\n",
+        "FROM rust:1.80\nRUN cargo build --release\n",
+        "#!/bin/bash\nx = 1\n",
+        "",
+    ] {
+        assert_eq!(detect(&registry, source), None, "{source:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn staged_edits_say_which_language_was_detected() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("manage");
+    fs::write(&path, "import sys\n\ndef main():\n    print(sys.argv)\n")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let mut stage = |content: &str, format| {
+        StageOperation {
+            file_path: path.display().to_string(),
+            language: None,
+            selector: Selector::builder(Operation::InsertAfter, "print(sys.argv)").build()?,
+            content: Some(content.into()),
+            buffer: None,
+            format,
+            diff_options: Default::default(),
+            format_mode: None,
+            cell: None,
+            candidate: None,
+            show_alternatives: false,
+            idempotent: false,
+        }
+        .execute(&mut state)
+    };
+
+    let preview: Value =
+        serde_json::from_str(&stage("\n    print(sys.path)", ResultFormat::Json)?)?;
+    assert_eq!(preview["staged"], true, "{preview}");
+    assert_eq!(
+        preview["detected_language"],
+        "python, since 100% of the file parses as python"
+    );
+
+    // the file is validated as python, so broken python is refused
+    let preview = stage("\n    print(sys.path", ResultFormat::Text)?;
+    assert!(preview.contains("invalid syntax"), "{preview}");
+    assert!(
+        preview.ends_with(
+            "The file was edited as python, since 100% of the file parses as python. Pass \
+             `language` to edit it as another language."
+        ),
+        "{preview}"
+    );
+    Ok(())
+}
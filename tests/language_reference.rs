@@ -0,0 +1,110 @@
+//! Tests for the grammar node kinds and fields listed by `language_reference`
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::grammar::GrammarReference;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{LanguageReference, Tools};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+const NO_UNWRAP: &str = r#"
+; the method name is a predicate argument, not a node kind
+((call_expression
+   function: (field_expression
+               field: (field_identifier) @_method)) @invalid.unwrap
+ (#eq? @_method "unwrap"))
+"#;
+
+#[test]
+fn built_in_queries_are_listed_as_references() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let reference = GrammarReference::new(rust, None);
+
+    let function_item = reference
+        .node_kinds
+        .iter()
+        .find(|kind| kind.name == "function_item")
+        .expect("function_item is a rust node kind");
+    assert!(function_item.named);
+    assert!(
+        function_item
+            .referenced_by
+            .contains(&"queries/rust/validation.scm".to_string()),
+        "{function_item:?}"
+    );
+    let body = reference
+        .fields
+        .iter()
+        .find(|field| field.name == "body")
+        .expect("body is a rust field");
+    assert!(!body.referenced_by.is_empty());
+    assert!(
+        reference
+            .node_kinds
+            .iter()
+            .any(|kind| kind.name == "fn" && !kind.named)
+    );
+    assert!(
+        reference
+            .node_kinds
+            .iter()
+            .any(|kind| kind.name == "_expression" && kind.supertype)
+    );
+
+    let text = reference.to_string();
+    assert!(text.starts_with("rust grammar: "), "{text}");
+    assert!(
+        text.contains("\n  function_item  (queries/rust/validation.scm"),
+        "{text}"
+    );
+    Ok(())
+}
+
+#[test]
+fn referenced_only_includes_custom_queries() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let queries = temp_dir.path().join(".semantic-edit/queries/rust");
+    fs::create_dir_all(&queries)?;
+    fs::write(queries.join("no_unwrap.scm"), NO_UNWRAP)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": temp_dir.path() },
+    }))?;
+    set_context.execute(&mut state)?;
+
+    let reference: Value = serde_json::from_str(
+        &LanguageReference {
+            language: LanguageName::Rust,
+            referenced_only: true,
+            format: ResultFormat::Json,
+        }
+        .execute(&mut state)?,
+    )?;
+    let node_kinds = reference["node_kinds"].as_array().unwrap();
+    assert!(
+        node_kinds
+            .iter()
+            .all(|kind| !kind["referenced_by"].as_array().unwrap().is_empty())
+    );
+    let call_expression = node_kinds
+        .iter()
+        .find(|kind| kind["name"] == "call_expression")
+        .expect("the custom query refers to call_expression");
+    assert!(
+        call_expression["referenced_by"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("no_unwrap.scm"),
+        "{call_expression}"
+    );
+    assert!(!node_kinds.iter().any(|kind| kind["name"] == "unwrap"));
+    let fields = reference["fields"].as_array().unwrap();
+    assert!(fields.iter().any(|field| field["name"] == "function"));
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Tests for fixing up separators around edits to comma- and semicolon-separated lists
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+fn edit(
+    language: LanguageName,
+    source: &str,
+    operation: Operation,
+    anchor: &str,
+    content: &str,
+) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let selector = Selector::builder(operation, anchor).build()?;
+    let editor = Editor::from_source(
+        source,
+        content.into(),
+        selector,
+        registry.get_language(language)?,
+    )?;
+    let output = editor
+        .with_format_mode(FormatMode::Never)
+        .commit_to_string()?;
+    Ok(output)
+}
+
+const CONFIG: &str = "{\n  \"name\": \"app\",\n  \"version\": \"1.0.0\",\n  \"private\": true\n}\n";
+
+#[test]
+fn deleting_json_members_removes_their_comma() -> Result<()> {
+    let delete = |anchor| {
+        edit(
+            LanguageName::Json,
+            CONFIG,
+            Operation::ReplaceNode,
+            anchor,
+            "",
+        )
+    };
+
+    assert_eq!(
+        delete("\"version\":")?,
+        "{\n  \"name\": \"app\",\n  \"private\": true\n}\n"
+    );
+    // the last member takes the comma before it
+    assert_eq!(
+        delete("\"private\":")?,
+        "{\n  \"name\": \"app\",\n  \"version\": \"1.0.0\"\n}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            "[1, 2, 3]\n",
+            Operation::ReplaceExact,
+            "1",
+            ""
+        )?,
+        "[2, 3]\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn replacements_neither_double_nor_drop_separators() -> Result<()> {
+    // content written with a trailing comma, in the middle and at the end of the object
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            CONFIG,
+            Operation::ReplaceExact,
+            "\"version\": \"1.0.0\"",
+            "\"version\": \"2.0.0\","
+        )?,
+        "{\n  \"name\": \"app\",\n  \"version\": \"2.0.0\",\n  \"private\": true\n}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            CONFIG,
+            Operation::ReplaceExact,
+            "\"private\": true",
+            "\"private\": false,"
+        )?,
+        "{\n  \"name\": \"app\",\n  \"version\": \"1.0.0\",\n  \"private\": false\n}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Python,
+            "run(a, b, c)\n",
+            Operation::ReplaceNode,
+            "b",
+            "d,"
+        )?,
+        "run(a, d, c)\n"
+    );
+    // replaced text that ended with its comma gets one back
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            CONFIG,
+            Operation::ReplaceExact,
+            "\"name\": \"app\",",
+            "\"name\": \"web\""
+        )?,
+        "{\n  \"name\": \"web\",\n  \"version\": \"1.0.0\",\n  \"private\": true\n}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn inserts_next_to_elements_get_a_separator() -> Result<()> {
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            CONFIG,
+            Operation::InsertAfterNode,
+            "\"private\":",
+            "\"license\": \"MIT\","
+        )?,
+        "{\n  \"name\": \"app\",\n  \"version\": \"1.0.0\",\n  \"private\": true,\n  \"license\": \"MIT\"\n}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Toml,
+            "deps = [\"serde\", \"tokio\"]\n",
+            Operation::InsertBefore,
+            "\"tokio\"",
+            "\"anyhow\""
+        )?,
+        "deps = [\"serde\", \"anyhow\", \"tokio\"]\n"
+    );
+    // after or before an existing separator, the separator goes on the content's other side
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            CONFIG,
+            Operation::InsertAfter,
+            "\"name\": \"app\",",
+            "\"license\": \"MIT\""
+        )?,
+        "{\n  \"name\": \"app\",\n  \"license\": \"MIT\",\n  \"version\": \"1.0.0\",\n  \"private\": true\n}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            "{\"a\": 1, \"b\": 2}\n",
+            Operation::InsertAfter,
+            "\"a\": 1,",
+            "\"z\": 0"
+        )?,
+        "{\"a\": 1, \"z\": 0, \"b\": 2}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Json,
+            "[\"a\", \"b\"]\n",
+            Operation::InsertBefore,
+            ", \"b\"",
+            "\"c\""
+        )?,
+        "[\"a\", \"c\", \"b\"]\n"
+    );
+    // content within a line that is valid as written extends the element instead
+    assert_eq!(
+        edit(
+            LanguageName::Python,
+            "run(a, b)\n",
+            Operation::InsertAfter,
+            "b",
+            "[0]"
+        )?,
+        "run(a, b[0])\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Python,
+            "run(a, b)\n",
+            Operation::InsertAfter,
+            "b",
+            "ar"
+        )?,
+        "run(a, bar)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn rust_fields_and_arguments_are_lists_but_for_loops_are_not() -> Result<()> {
+    assert_eq!(
+        edit(
+            LanguageName::Rust,
+            "fn main() {\n    run(1, 2, 3);\n}\n",
+            Operation::ReplaceExact,
+            "3",
+            ""
+        )?,
+        "fn main() {\n    run(1, 2);\n}\n"
+    );
+    assert_eq!(
+        edit(
+            LanguageName::Rust,
+            "struct Point {\n    x: i32,\n    y: i32,\n}\n",
+            Operation::ReplaceNode,
+            "x: i32",
+            ""
+        )?,
+        "struct Point {\n    y: i32,\n}\n"
+    );
+
+    // the separators of a C for loop are not list separators
+    let output = edit(
+        LanguageName::C,
+        "void f(void) {\n    for (int i = 0; i < 3; i++) {}\n}\n",
+        Operation::ReplaceExact,
+        "i < 3",
+        "i < 4",
+    )?;
+    assert!(output.contains("for (int i = 0; i < 4; i++)"), "{output}");
+    Ok(())
+}
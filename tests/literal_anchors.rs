@@ -0,0 +1,62 @@
+//! Tests for the caution shown when an anchor matches inside a string literal or comment
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, PreviewReport};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+fn preview(
+    language: LanguageName,
+    source: &str,
+    anchor: &str,
+    content: &str,
+) -> Result<PreviewReport> {
+    let registry = LanguageRegistry::new()?;
+    let language = registry.get_language(language)?;
+    let selector = Selector::builder(Operation::ReplaceExact, anchor).build()?;
+    let (report, _) =
+        Editor::from_source(source, content.into(), selector, language)?.preview_report()?;
+    Ok(report)
+}
+
+#[test]
+fn anchors_inside_strings_and_comments_are_cautioned() -> Result<()> {
+    let source = "fn main() {\n    println!(\"retry count\");\n}\n";
+    let report = preview(LanguageName::Rust, source, "count", "limit")?;
+    let literal = report.edit.enclosing_literal.as_ref().expect("a string");
+    assert_eq!(literal.kind, "string_literal");
+    assert_eq!(literal.start_line, 2);
+    let text = report.to_string();
+    assert!(
+        text.contains(
+            "⚠️ CAUTION: the anchor matched inside `string_literal` line 2 (bytes 25–38), so \
+this edit changes the text of a string rather than code"
+        ),
+        "{text}"
+    );
+
+    let source = "# compute the total\ndef total(items):\n    return sum(items)\n";
+    let report = preview(LanguageName::Python, source, "total", "sum")?;
+    assert_eq!(report.edit.enclosing_literal.unwrap().kind, "comment");
+    Ok(())
+}
+
+#[test]
+fn deliberate_literal_edits_are_not_cautioned() -> Result<()> {
+    // the anchor is the whole string
+    let source = "fn main() {\n    println!(\"retry count\");\n}\n";
+    let report = preview(LanguageName::Rust, source, "\"retry count\"", "\"retries\"")?;
+    assert!(report.staged);
+    assert_eq!(report.edit.enclosing_literal, None);
+
+    // strings are the data in JSON
+    let source = "{\n  \"name\": \"old name\"\n}\n";
+    let report = preview(LanguageName::Json, source, "old", "new")?;
+    assert!(report.staged);
+    assert_eq!(report.edit.enclosing_literal, None);
+
+    let source = "fn main() {\n    let count = 1;\n}\n";
+    let report = preview(LanguageName::Rust, source, "count", "total")?;
+    assert_eq!(report.edit.enclosing_literal, None);
+    Ok(())
+}
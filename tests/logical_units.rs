@@ -0,0 +1,171 @@
+//! Tests for node operations treating a declaration with its attributes, documentation
+//! comments, and trailing comment as one unit
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+fn insert_after_node(
+    language: LanguageName,
+    source: &str,
+    anchor: &str,
+    content: &str,
+) -> Result<String> {
+    let selector = Selector::builder(Operation::InsertAfterNode, anchor).build()?;
+    edit(language, source, selector, content)
+}
+
+fn replace_node_with_trivia(
+    language: LanguageName,
+    source: &str,
+    anchor: &str,
+    content: &str,
+) -> Result<String> {
+    let selector = Selector::builder(Operation::ReplaceNode, anchor)
+        .with_include_leading_trivia(true)
+        .build()?;
+    edit(language, source, selector, content)
+}
+
+fn edit(language: LanguageName, source: &str, selector: Selector, content: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let editor = Editor::from_source(
+        source,
+        content.into(),
+        selector,
+        registry.get_language(language)?,
+    )?;
+    let output = editor
+        .with_format_mode(FormatMode::Never)
+        .commit_to_string()?;
+    Ok(output)
+}
+
+#[test]
+fn rust_inserts_after_attributes_land_after_the_item() -> Result<()> {
+    let source = "/// A point\n#[derive(Debug)]\n#[allow(dead_code)]\nstruct Point; // origin\n\nfn main() {}\n";
+
+    for anchor in ["#[derive(Debug)]", "/// A point", "struct Point;"] {
+        let output = insert_after_node(LanguageName::Rust, source, anchor, "struct Size;")?;
+        assert_eq!(
+            output,
+            "/// A point\n#[derive(Debug)]\n#[allow(dead_code)]\nstruct Point; // origin\nstruct Size;\n\nfn main() {}\n",
+            "anchored on {anchor}"
+        );
+    }
+
+    // a comment separated by a blank line documents nothing, so it is its own unit
+    let output = insert_after_node(
+        LanguageName::Rust,
+        "// section\n\nfn main() {}\n",
+        "// section",
+        "// more",
+    )?;
+    assert_eq!(output, "// section\n// more\n\nfn main() {}\n");
+    Ok(())
+}
+
+#[test]
+fn java_annotations_belong_to_their_method() -> Result<()> {
+    let source = "class A {\n    /** Says hi */\n    @Override\n    @Deprecated(since = \"1\")\n    public String toString() { return \"hi\"; }\n}\n";
+
+    for anchor in ["@Override", "@Deprecated", "/** Says hi */"] {
+        let output =
+            insert_after_node(LanguageName::Java, source, anchor, "\n    void added() {}")?;
+        assert!(
+            output.ends_with("return \"hi\"; }\n    void added() {}\n}\n"),
+            "anchored on {anchor}:\n{output}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn python_and_typescript_decorators_belong_to_their_declaration() -> Result<()> {
+    let output = insert_after_node(
+        LanguageName::Python,
+        "@cache\n@trace(level=1)\ndef load():\n    pass\n",
+        "@trace(level=1)",
+        "\ndef save():\n    pass",
+    )?;
+    assert_eq!(
+        output,
+        "@cache\n@trace(level=1)\ndef load():\n    pass\ndef save():\n    pass\n"
+    );
+
+    // decorators on class members are siblings before the member
+    let output = insert_after_node(
+        LanguageName::Typescript,
+        "class A {\n  @log\n  run() {}\n}\n",
+        "@log",
+        "\n  stop() {}",
+    )?;
+    assert_eq!(output, "class A {\n  @log\n  run() {}\n  stop() {}\n}\n");
+    Ok(())
+}
+
+#[test]
+fn logical_units_span_attributes_and_comments() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let source = "fn main() {}\n\n// The size\n#[derive(Debug)]\nstruct Size; // in bytes\n";
+    let tree = rust.parser()?.parse(source, None).unwrap();
+    let start = source.find("derive").unwrap();
+    let node = tree
+        .root_node()
+        .named_descendant_for_byte_range(start, start + 6)
+        .unwrap();
+
+    let unit = rust.logical_unit(node);
+    assert_eq!(unit.declaration.kind(), "struct_item");
+    assert_eq!(
+        &source[unit.byte_range()],
+        "// The size\n#[derive(Debug)]\nstruct Size; // in bytes"
+    );
+    Ok(())
+}
+
+#[test]
+fn replace_node_can_replace_doc_comments_and_attributes() -> Result<()> {
+    let source = "use std::fmt;\n\n/// Parses the input\n#[inline]\nfn parse() -> u8 {\n    0\n}\n";
+    let replacement = "/// Parses the input, or fails\nfn parse() -> Option<u8> {\n    None\n}";
+
+    let output = replace_node_with_trivia(LanguageName::Rust, source, "fn parse()", replacement)?;
+    assert_eq!(output, format!("use std::fmt;\n\n{replacement}\n"));
+
+    // without the flag, the old doc comment and attribute stay above the replacement
+    let selector = Selector::builder(Operation::ReplaceNode, "fn parse()").build()?;
+    let output = edit(LanguageName::Rust, source, selector, replacement)?;
+    assert!(
+        output.contains("/// Parses the input\n#[inline]\n/// Parses the input, or fails"),
+        "{output}"
+    );
+    Ok(())
+}
+
+#[test]
+fn replace_node_with_trivia_replaces_python_decorators() -> Result<()> {
+    let output = replace_node_with_trivia(
+        LanguageName::Python,
+        "import os\n\n# Loads the file\n@cache\ndef load():\n    pass\n",
+        "def load",
+        "def load(path):\n    pass",
+    )?;
+    assert_eq!(output, "import os\n\ndef load(path):\n    pass\n");
+    Ok(())
+}
+
+#[test]
+fn include_leading_trivia_is_only_for_replace_node() {
+    let error = Selector::builder(Operation::ReplaceExact, "fn parse")
+        .with_include_leading_trivia(true)
+        .build()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("`include_leading_trivia` is only relevant for `replace_node`"),
+        "{error}"
+    );
+}
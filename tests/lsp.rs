@@ -0,0 +1,187 @@
+//! Tests for the language server run by `semantic-edit lsp`
+
+use anyhow::Result;
+use clap::Parser;
+use semantic_code_edit_mcp::cli::Cli;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+/// Frame messages the way an LSP client sends them
+fn frame(messages: &[Value]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let message = message.to_string();
+            format!("Content-Length: {}\r\n\r\n{message}", message.len())
+        })
+        .collect()
+}
+
+/// Run the language server on `messages`, returning whether it exited cleanly and the
+/// messages it sent
+fn serve(messages: &[Value]) -> Result<(bool, Vec<Value>)> {
+    let cli = Cli::try_parse_from(["semantic-edit", "lsp"])?;
+    let mut output = Vec::new();
+    let success = cli.run(&mut frame(messages).as_bytes(), &mut output)?;
+    let output = String::from_utf8(output)?;
+    let sent = output
+        .split("Content-Length: ")
+        .filter(|message| !message.is_empty())
+        .map(|message| serde_json::from_str(message.split_once("\r\n\r\n").unwrap().1))
+        .collect::<Result<_, _>>()?;
+    Ok((success, sent))
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn reply(sent: &[Value], id: u64) -> &Value {
+    sent.iter()
+        .find(|message| message["id"] == id && message.get("method").is_none())
+        .unwrap_or_else(|| panic!("no reply to {id} in {sent:#?}"))
+}
+
+fn uri(dir: &TempDir, name: &str) -> String {
+    format!("file://{}", dir.path().join(name).display())
+}
+
+#[test]
+fn commands_edit_open_documents_through_the_client() -> Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.path().join("main.rs"), "fn main() {}\n")?;
+    let uri = uri(&dir, "main.rs");
+    let unsaved = "fn main() {\n    let x = 1;\n}\n";
+    let edit = |operation: &str, content: &str| {
+        json!({
+            "uri": uri,
+            "operation": operation,
+            "anchor": "let x = 1;",
+            "content": content,
+            "format_mode": "never",
+        })
+    };
+
+    let (success, sent) = serve(&[
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": uri, "languageId": "rust", "version": 1, "text": unsaved,
+            } }),
+        ),
+        request(
+            2,
+            "workspace/executeCommand",
+            json!({
+                "command": "semantic-edit.preview",
+                "arguments": [edit("replace_exact", "let x = 2;")],
+            }),
+        ),
+        request(
+            3,
+            "workspace/executeCommand",
+            json!({
+                "command": "semantic-edit.apply",
+                "arguments": [edit("replace_exact", "let x = ;")],
+            }),
+        ),
+        request(
+            4,
+            "workspace/executeCommand",
+            json!({
+                "command": "semantic-edit.apply",
+                "arguments": [edit("replace_exact", "let x = 2;")],
+            }),
+        ),
+        // the client's answer to the server's workspace/applyEdit
+        json!({ "jsonrpc": "2.0", "id": 1, "result": { "applied": true } }),
+        request(5, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ])?;
+    assert!(success);
+
+    let capabilities = &reply(&sent, 1)["result"]["capabilities"];
+    assert_eq!(capabilities["documentFormattingProvider"], true);
+    assert_eq!(
+        capabilities["executeCommandProvider"]["commands"],
+        json!(["semantic-edit.preview", "semantic-edit.apply"])
+    );
+
+    // edits target the open document, not the file on disk
+    let preview = &reply(&sent, 2)["result"];
+    assert_eq!(preview["staged"], true, "{preview:#}");
+    assert!(
+        preview["diff"]
+            .as_str()
+            .unwrap()
+            .contains("+    let x = 2;"),
+        "{preview:#}"
+    );
+
+    let refused = &reply(&sent, 3)["result"];
+    assert_eq!(refused["applied"], false, "{refused:#}");
+
+    let apply_edit = sent
+        .iter()
+        .find(|message| message["method"] == "workspace/applyEdit")
+        .expect("no workspace/applyEdit request");
+    assert_eq!(
+        apply_edit["params"]["edit"]["changes"][&uri],
+        json!([{
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 3, "character": 0 },
+            },
+            "newText": "fn main() {\n    let x = 2;\n}\n",
+        }])
+    );
+    assert_eq!(reply(&sent, 4)["result"]["applied"], true);
+
+    // the server leaves writing the file to the client
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("main.rs"))?,
+        "fn main() {}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn formatting_replaces_unformatted_documents() -> Result<()> {
+    let dir = TempDir::new()?;
+    let uri = uri(&dir, "data.json");
+    std::fs::write(dir.path().join("data.json"), "{\"a\":1}")?;
+    let (success, sent) = serve(&[
+        request(1, "initialize", json!({ "capabilities": {} })),
+        request(
+            2,
+            "textDocument/formatting",
+            json!({
+                "textDocument": { "uri": uri },
+                "options": { "tabSize": 2, "insertSpaces": true },
+            }),
+        ),
+        request(3, "textDocument/unknown", json!({})),
+        notification("exit", Value::Null),
+    ])?;
+    // exiting without a shutdown request is an error
+    assert!(!success);
+
+    let edits = reply(&sent, 2)["result"].as_array().unwrap();
+    assert_eq!(edits.len(), 1, "{edits:#?}");
+    assert_eq!(
+        edits[0]["range"]["end"],
+        json!({ "line": 0, "character": 7 })
+    );
+    assert!(
+        edits[0]["newText"].as_str().unwrap().contains("\"a\": 1"),
+        "{edits:#?}"
+    );
+    assert_eq!(reply(&sent, 3)["error"]["code"], -32601);
+    Ok(())
+}
@@ -1,8 +1,11 @@
 mod snapshot_runner;
 use mcplease::traits::AsToolsList;
+use semantic_code_edit_mcp::snapshot::FixtureRecorder;
 use semantic_code_edit_mcp::tools::Tools;
-use snapshot_runner::SnapshotRunner;
+use serde_json::json;
+use snapshot_runner::{SnapshotRunner, SnapshotTest};
 use std::env;
+use tempfile::TempDir;
 
 #[test]
 fn run_snapshot_tests() {
@@ -34,3 +37,48 @@ fn run_snapshot_tests() {
 fn tools_doesnt_panic() {
     Tools::tools_list();
 }
+
+#[test]
+fn recorded_fixtures_replay_in_the_snapshot_runner() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let input = dir.path().join("lib.rs");
+    std::fs::write(&input, "fn one() -> i32 {\n    1\n}\n")?;
+    let fixture = dir.path().join("rust").join("insert_after");
+
+    let recorded = FixtureRecorder::new(&fixture)
+        .with_input(&input)
+        .record(json!([
+            {
+                "name": "stage_operation",
+                "arguments": {
+                    "file_path": input.display().to_string(),
+                    "operation": "insert_after",
+                    "anchor": "    1\n}",
+                    "content": "\n\nfn two() -> i32 {\n    2\n}",
+                },
+            },
+            {"name": "commit_staged", "arguments": {"acknowledge": true}},
+        ]))?;
+    assert_eq!(
+        recorded.files,
+        ["args.json", "input.rs", "response.txt", "output.rs"]
+    );
+    let args = std::fs::read_to_string(fixture.join("args.json"))?;
+    assert!(!args.contains("file_path"), "{args}");
+    assert!(std::fs::read_to_string(fixture.join("output.rs"))?.contains("fn two()"));
+
+    let mut runner = SnapshotRunner::new(false, None)?;
+    let result = runner.run_test(SnapshotTest {
+        name: "rust::insert_after".into(),
+        input_path: Some(fixture.join("input.rs")),
+        args_path: fixture.join("args.json"),
+        response_path: fixture.join("response.txt"),
+        output_path: Some(fixture.join("output.rs")),
+        base_path: fixture,
+    });
+    assert!(
+        result.response_matches && result.output_matches,
+        "{result:?}"
+    );
+    Ok(())
+}
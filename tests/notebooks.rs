@@ -0,0 +1,168 @@
+//! Tests for editing the code cells of Jupyter notebooks
+
+mod common;
+
+use anyhow::Result;
+use common::call;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+/// A notebook as Jupyter writes it: sorted keys and one space of indentation
+const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Analysis"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "import math\n",
+    "x = 1"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {
+    "tags": [
+     "plot"
+    ]
+   },
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "2\n"
+     ]
+    }
+   ],
+   "source": [
+    "y = x + 1\n",
+    "print(y)"
+   ]
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+fn notebook() -> Result<(SemanticEditTools, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join("analysis.ipynb"), NOTEBOOK)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    call(
+        &mut state,
+        "set_context",
+        json!({ "path": temp_dir.path() }),
+    )?;
+    Ok((state, temp_dir))
+}
+
+fn stage(state: &mut SemanticEditTools, mut arguments: Value) -> Result<String> {
+    arguments["file_path"] = json!("analysis.ipynb");
+    arguments["format_mode"] = json!("never");
+    call(state, "stage_operation", arguments)
+}
+
+#[test]
+fn cells_found_by_anchor_are_edited_in_place() -> Result<()> {
+    let (mut state, temp_dir) = notebook()?;
+    let preview = stage(
+        &mut state,
+        json!({ "operation": "replace_exact", "anchor": "x + 1", "content": "x + 2" }),
+    )?;
+    assert!(
+        preview.contains("STAGED: replace exact in cell 2"),
+        "{preview}"
+    );
+    assert!(preview.contains("+y = x + 2"), "{preview}");
+
+    call(&mut state, "commit_staged", json!({}))?;
+    // only the edited line changes; other cells, outputs, and metadata are kept as they were
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("analysis.ipynb"))?,
+        NOTEBOOK.replace(r#""y = x + 1\n""#, r#""y = x + 2\n""#)
+    );
+    Ok(())
+}
+
+#[test]
+fn ambiguous_anchors_need_a_cell_index() -> Result<()> {
+    let (mut state, temp_dir) = notebook()?;
+    let error = stage(
+        &mut state,
+        json!({ "operation": "insert_before", "anchor": "x", "content": "z = 0\n" }),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("cell 1, cell 2"), "{error}");
+
+    let preview = stage(
+        &mut state,
+        json!({ "operation": "insert_before", "anchor": "x", "content": "z = 0\n", "cell": 1 }),
+    )?;
+    assert!(
+        preview.contains("STAGED: insert before in cell 1"),
+        "{preview}"
+    );
+    call(&mut state, "commit_staged", json!({}))?;
+
+    let written: Value =
+        serde_json::from_str(&fs::read_to_string(temp_dir.path().join("analysis.ipynb"))?)?;
+    assert_eq!(
+        written["cells"][1]["source"],
+        json!(["import math\n", "z = 0\n", "x = 1"])
+    );
+    Ok(())
+}
+
+#[test]
+fn only_code_cells_can_be_edited() -> Result<()> {
+    let (mut state, _temp_dir) = notebook()?;
+    let error = stage(
+        &mut state,
+        json!({ "operation": "replace_exact", "anchor": "Analysis", "content": "Report", "cell": 0 }),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("cell 0 is a markdown cell"), "{error}");
+
+    let error = stage(
+        &mut state,
+        json!({ "operation": "replace_exact", "anchor": "Analysis", "content": "Report" }),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("not found in any code cell"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn cells_are_validated_in_the_kernel_language() -> Result<()> {
+    let (mut state, _temp_dir) = notebook()?;
+    let preview = stage(
+        &mut state,
+        json!({ "operation": "replace_exact", "anchor": "x + 1", "content": "(x +" }),
+    )?;
+    assert!(preview.contains("invalid syntax"), "{preview}");
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
@@ -0,0 +1,241 @@
+//! Tests for sorting and grouping imports with `organize_imports`
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::imports::organize_imports;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, OrganizeImports};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+fn organize(language: LanguageName, source: &str) -> Result<Option<String>> {
+    let registry = LanguageRegistry::new()?;
+    let language = registry.get_language(language)?;
+    Ok(organize_imports(language, source)?.map(|organized| organized.output))
+}
+
+#[test]
+fn imports_are_grouped_by_origin_and_sorted() -> Result<()> {
+    let rust = "\
+use crate::state::State;
+use serde::Serialize;
+use std::fmt;
+pub use crate::error::Error;
+use anyhow::Result;
+use std::collections::HashMap;
+use serde::Serialize;
+
+fn main() {}
+";
+    assert_eq!(
+        organize(LanguageName::Rust, rust)?.as_deref(),
+        Some(
+            "\
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::state::State;
+
+pub use crate::error::Error;
+
+fn main() {}
+"
+        )
+    );
+
+    let python = "\
+from .models import User
+import requests
+from typing import Any
+import os
+from __future__ import annotations
+
+print(os.name)
+";
+    assert_eq!(
+        organize(LanguageName::Python, python)?.as_deref(),
+        Some(
+            "\
+from __future__ import annotations
+
+import os
+from typing import Any
+
+import requests
+
+from .models import User
+
+print(os.name)
+"
+        )
+    );
+
+    let typescript = "\
+import { render } from \"./render\";
+import React from \"react\";
+import type { Props } from \"../types\";
+import { join } from \"node:path\";
+";
+    assert_eq!(
+        organize(LanguageName::Typescript, typescript)?.as_deref(),
+        Some(
+            "\
+import { join } from \"node:path\";
+import React from \"react\";
+
+import type { Props } from \"../types\";
+import { render } from \"./render\";
+"
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn go_blocks_are_merged_and_java_statics_come_first() -> Result<()> {
+    let go = "\
+package main
+
+import \"github.com/pkg/errors\"
+import (
+\t\"os\"
+\tlog \"github.com/sirupsen/logrus\"
+\t\"fmt\"
+)
+
+func main() {}
+";
+    assert_eq!(
+        organize(LanguageName::Go, go)?.as_deref(),
+        Some(
+            "\
+package main
+
+import (
+\t\"fmt\"
+\t\"os\"
+
+\t\"github.com/pkg/errors\"
+\tlog \"github.com/sirupsen/logrus\"
+)
+
+func main() {}
+"
+        )
+    );
+
+    let java = "\
+package app;
+
+import java.util.List;
+import static org.junit.Assert.assertEquals;
+import com.example.Service;
+
+class App {}
+";
+    assert_eq!(
+        organize(LanguageName::Java, java)?.as_deref(),
+        Some(
+            "\
+package app;
+
+import static org.junit.Assert.assertEquals;
+
+import com.example.Service;
+import java.util.List;
+
+class App {}
+"
+        )
+    );
+
+    // already organized
+    assert_eq!(
+        organize(LanguageName::Rust, "use std::fmt;\n\nuse anyhow::Result;\n")?,
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn commented_imports_stay_in_place() -> Result<()> {
+    let rust = "\
+use zeta::Z;
+// alpha must come after zeta for its side effects
+use alpha::A;
+use beta::B; // pinned
+#[cfg(test)]
+use gamma::G;
+use delta::D;
+";
+    assert_eq!(organize(LanguageName::Rust, rust)?, None);
+
+    let typescript = "\
+import \"./polyfills\";
+import { b } from \"b\";
+import { a } from \"a\";
+";
+    assert_eq!(
+        organize(LanguageName::Typescript, typescript)?.as_deref(),
+        Some(
+            "\
+import \"./polyfills\";
+import { a } from \"a\";
+import { b } from \"b\";
+"
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn organized_files_are_staged_as_a_codemod() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(
+        dir.path().join("app.py"),
+        "import sys\nimport os\n\nprint(sys.argv, os.sep)\n",
+    )?;
+    fs::write(dir.path().join("notes.txt"), "import b\nimport a\n")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let report: Value = serde_json::from_str(
+        &OrganizeImports {
+            file_paths: vec![
+                dir.path().join("app.py").display().to_string(),
+                dir.path().join("notes.txt").display().to_string(),
+            ],
+            diff_options: Default::default(),
+            format: ResultFormat::Json,
+        }
+        .execute(&mut state)?,
+    )?;
+    assert_eq!(report["staged"], true);
+    assert_eq!(report["files"].as_array().unwrap().len(), 1);
+    assert!(
+        report["skipped"][0]["file_path"]
+            .as_str()
+            .unwrap()
+            .ends_with("notes.txt"),
+        "{report}"
+    );
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert_eq!(
+        fs::read_to_string(dir.path().join("app.py"))?,
+        "import os\nimport sys\n\nprint(sys.argv, os.sep)\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! Tests for editing files over the parse limits as text
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode, ParseLimits};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::time::Duration;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+fn editor<'a>(
+    registry: &'a LanguageRegistry,
+    source: &str,
+    operation: Operation,
+    anchor: &str,
+    content: &str,
+    parse_limits: ParseLimits,
+) -> Result<Editor<'a>> {
+    Ok(Editor::from_source(
+        source,
+        content.into(),
+        Selector {
+            operation,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(LanguageName::Rust)?,
+    )?
+    .with_format_mode(FormatMode::Never)
+    .with_parse_limits(parse_limits))
+}
+
+#[test]
+fn files_over_the_size_limit_are_edited_as_text() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let limits = ParseLimits {
+        max_file_bytes: 16,
+        ..ParseLimits::default()
+    };
+
+    // invalid syntax goes through, since nothing is parsed or validated
+    let (report, staged) = editor(
+        &registry,
+        SOURCE,
+        Operation::InsertAfter,
+        "let x = 1;",
+        "\n    let y = ;",
+        limits,
+    )?
+    .preview_report()?;
+    assert!(staged.is_some());
+    let warning = report.edit.warning.unwrap();
+    assert!(
+        warning.contains("is 29 bytes, over the 16 byte limit for parsing"),
+        "{warning}"
+    );
+    assert!(warning.contains("without being parsed, validated, or formatted"));
+
+    let output = editor(
+        &registry,
+        SOURCE,
+        Operation::ReplaceExact,
+        "let x = 1;",
+        "let x = 2;",
+        limits,
+    )?
+    .commit_to_string()?;
+    assert_eq!(output, "fn main() {\n    let x = 2;\n}\n");
+
+    let error = editor(
+        &registry,
+        SOURCE,
+        Operation::ReplaceNode,
+        "fn main()",
+        "fn main() {}",
+        limits,
+    )?
+    .preview_report()
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("replace node needs a syntax tree, but"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn files_that_parse_too_slowly_are_edited_as_text() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let source = SOURCE.repeat(20_000);
+    let limits = ParseLimits {
+        timeout: Duration::ZERO,
+        ..ParseLimits::default()
+    };
+
+    let (report, staged) = editor(
+        &registry,
+        &source,
+        Operation::InsertBefore,
+        "fn main()",
+        "// first\n",
+        limits,
+    )?
+    .preview_report()?;
+    assert!(staged.is_some());
+    let warning = report.edit.warning.unwrap();
+    assert!(
+        warning.contains("took longer than the 0ns limit"),
+        "{warning}"
+    );
+    Ok(())
+}
+
+#[test]
+fn files_within_the_limits_are_still_validated() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let (report, staged) = editor(
+        &registry,
+        SOURCE,
+        Operation::InsertAfter,
+        "let x = 1;",
+        "\n    let y = ;",
+        ParseLimits::default(),
+    )?
+    .preview_report()?;
+    assert!(staged.is_none());
+    assert!(report.edit.warning.is_none());
+    Ok(())
+}
+
+#[test]
+fn edits_try_at_most_the_candidate_limit() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    // the first match is inside an array, where a statement is invalid
+    let source = "fn main() {\n    let x = [a; 2];\n    a;\n}\n";
+    let limits = ParseLimits {
+        max_candidates: 1,
+        ..ParseLimits::default()
+    };
+    let (_, staged) = editor(
+        &registry,
+        source,
+        Operation::ReplaceExact,
+        "a;",
+        "let b = 1;",
+        limits,
+    )?
+    .preview_report()?;
+    assert!(staged.is_none());
+
+    let (report, staged) = editor(
+        &registry,
+        source,
+        Operation::ReplaceExact,
+        "a;",
+        "let b = 1;",
+        ParseLimits::default(),
+    )?
+    .preview_report()?;
+    assert!(staged.is_some(), "{}", report.edit.message);
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Tests for reusing parsers across edits
+
+use anyhow::Result;
+use semantic_code_edit_mcp::languages::parser_pool::{MAX_IDLE_PARSERS, ParserPool};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use std::thread;
+
+#[test]
+fn parsers_are_returned_and_reused() -> Result<()> {
+    let pool = ParserPool::new(tree_sitter_rust::LANGUAGE.into());
+    assert_eq!(pool.idle_parsers(), 0);
+
+    for _ in 0..3 {
+        let mut parser = pool.checkout()?;
+        assert_eq!(pool.idle_parsers(), 0);
+        let tree = parser.parse("fn main() {}", None).unwrap();
+        assert!(!tree.root_node().has_error());
+        drop(parser);
+        // the same parser went back, rather than a new one being created each time
+        assert_eq!(pool.idle_parsers(), 1);
+    }
+
+    let parsers = (0..MAX_IDLE_PARSERS + 2)
+        .map(|_| pool.checkout())
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(pool.idle_parsers(), 0);
+    drop(parsers);
+    assert_eq!(pool.idle_parsers(), MAX_IDLE_PARSERS);
+    Ok(())
+}
+
+#[test]
+fn languages_share_their_pool_across_threads() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let language = registry.get_language(LanguageName::Rust)?;
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..10 {
+                    let mut parser = language.parser().unwrap();
+                    let tree = parser.parse("fn main() {}", None).unwrap();
+                    assert!(!tree.root_node().has_error());
+                }
+            });
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Tests for reading a line range or a named symbol with open_files
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::Tools;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const SOURCE: &str = r#"use std::fmt;
+
+fn first() {
+    println!("first");
+}
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn second() {
+    println!("second");
+}
+"#;
+
+fn setup() -> Result<(TempDir, PathBuf)> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, SOURCE)?;
+    Ok((temp_dir, file_path))
+}
+
+fn open(state: &mut SemanticEditTools, arguments: Value) -> Result<String> {
+    let tool: Tools = serde_json::from_value(json!({
+        "name": "open_files",
+        "arguments": arguments,
+    }))?;
+    tool.execute(state)
+}
+
+#[test]
+fn line_ranges_return_only_the_requested_lines() -> Result<()> {
+    let (_temp_dir, file_path) = setup()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "lines": "3-5" }),
+    )?;
+    assert!(response.contains("LINES 3-5 OF 14"), "{response}");
+    assert!(response.contains("fn first() {\n    println!(\"first\");\n}\n"));
+    assert!(!response.contains("use std::fmt"));
+    assert!(!response.contains("struct Point"));
+    assert!(!response.contains("SYNTAX"));
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "lines": "4", "context_lines": 1 }),
+    )?;
+    assert!(response.contains("LINES 3-5 OF 14"), "{response}");
+
+    let error = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "lines": "5-3" }),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("invalid line range"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn symbols_return_their_definition_with_context() -> Result<()> {
+    let (_temp_dir, file_path) = setup()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "symbol": "Point", "context_lines": 0 }),
+    )?;
+    assert!(
+        response.contains("LINES 7-10 OF 14: type `Point`"),
+        "{response}"
+    );
+    assert!(response.contains("struct Point {\n    x: i32,\n    y: i32,\n}\n"));
+    assert!(!response.contains("fn first"));
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "symbol": "second" }),
+    )?;
+    assert!(
+        response.contains("LINES 9-14 OF 14: function `second`"),
+        "{response}"
+    );
+
+    let error = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "symbol": "third" }),
+    )
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Definitions in this file: Point, first, second, x, y"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn pages_report_where_to_continue() -> Result<()> {
+    let (_temp_dir, file_path) = setup()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "max_lines": 5 }),
+    )?;
+    assert!(response.contains("LINES 1-5 OF 14: page"), "{response}");
+    assert!(response.contains("9 more lines follow. To read on, use \"offset\": 6"));
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "offset": 6, "max_lines": 5 }),
+    )?;
+    assert!(response.contains("LINES 6-10 OF 14: page"), "{response}");
+    assert!(response.contains("5 earlier lines are not shown."));
+    assert!(response.contains("struct Point {"));
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "offset": 11 }),
+    )?;
+    assert!(response.contains("LINES 11-14 OF 14: page"), "{response}");
+    assert!(!response.contains("more lines follow"));
+
+    let error = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "offset": 20 }),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("past the end"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn head_and_tail_read_the_ends_of_a_file() -> Result<()> {
+    let (_temp_dir, file_path) = setup()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let response = open(&mut state, json!({ "file_paths": [file_path], "head": 1 }))?;
+    assert!(
+        response.contains("LINES 1-1 OF 14: first 1 lines"),
+        "{response}"
+    );
+    assert!(response.contains("use std::fmt;\n13 more lines follow"));
+
+    let response = open(&mut state, json!({ "file_paths": [file_path], "tail": 3 }))?;
+    assert!(
+        response.contains("LINES 12-14 OF 14: last 3 lines"),
+        "{response}"
+    );
+    assert!(response.contains("11 earlier lines are not shown."));
+    assert!(response.contains("fn second() {"));
+
+    let response = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "tail": 100 }),
+    )?;
+    assert!(response.contains("LINES 1-14 OF 14"), "{response}");
+
+    let error = open(
+        &mut state,
+        json!({ "file_paths": [file_path], "head": 3, "tail": 3 }),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("only one of"), "{error}");
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Tests for confining file access to the session context or an allowlist
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::filesystem::FileOperations;
+use semantic_code_edit_mcp::sandbox::{PathSandbox, SandboxMode, SymlinkPolicy};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{StageOperation, Tools};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+/// A temp dir with `project/main.rs` and `secrets/main.rs`
+fn setup() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    for dir in ["project", "secrets"] {
+        fs::create_dir(temp_dir.path().join(dir))?;
+        fs::write(temp_dir.path().join(dir).join("main.rs"), SOURCE)?;
+    }
+    Ok(temp_dir)
+}
+
+fn set_context(state: &mut SemanticEditTools, path: &Path) -> Result<String> {
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": path },
+    }))?;
+    set_context.execute(state)
+}
+
+fn stage(state: &mut SemanticEditTools, file_path: &str) -> Result<String> {
+    StageOperation {
+        file_path: file_path.into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\n    let y = 2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+#[test]
+fn context_mode_refuses_paths_outside_the_context() -> Result<()> {
+    let temp_dir = setup()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_sandbox(PathSandbox::default().with_mode(SandboxMode::Context));
+
+    let error = stage(&mut state, "main.rs").unwrap_err();
+    assert!(
+        error.to_string().contains("Use set_context first"),
+        "{error}"
+    );
+
+    set_context(&mut state, &temp_dir.path().join("project"))?;
+    stage(&mut state, "main.rs")?;
+    stage(&mut state, "../project/main.rs")?;
+
+    let secrets = temp_dir.path().join("secrets/main.rs");
+    for path in [
+        "../secrets/main.rs".to_string(),
+        secrets.display().to_string(),
+    ] {
+        let error = stage(&mut state, &path).unwrap_err();
+        assert!(
+            error.to_string().contains("outside the session context"),
+            "{error}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn allowed_paths_confine_context_and_file_operations() -> Result<()> {
+    let temp_dir = setup()?;
+    let project = temp_dir.path().join("project");
+    let secrets = temp_dir.path().join("secrets");
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_sandbox(PathSandbox::default().with_allowed_paths(vec![project.clone()]));
+
+    let error = set_context(&mut state, &secrets).unwrap_err();
+    assert!(error.to_string().contains("outside the allowed paths"));
+
+    let file_operations = state.file_operations();
+    assert!(
+        file_operations
+            .write_file(secrets.join("main.rs"), "overwritten".into())
+            .is_err()
+    );
+    assert!(file_operations.read_file(&secrets.join("main.rs")).is_err());
+    assert!(!file_operations.exists(&secrets.join("main.rs")));
+    assert!(file_operations.exists(&project.join("main.rs")));
+    assert_eq!(fs::read_to_string(secrets.join("main.rs"))?, SOURCE);
+
+    file_operations.write_file(project.join("new.rs"), "fn new() {}\n".into())?;
+    assert_eq!(
+        file_operations.read_file(&project.join("new.rs"))?,
+        "fn new() {}\n"
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_out_of_the_sandbox_are_refused() -> Result<()> {
+    let temp_dir = setup()?;
+    let project = temp_dir.path().join("project");
+    std::os::unix::fs::symlink(temp_dir.path().join("secrets"), project.join("escape"))?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_sandbox(PathSandbox::default().with_mode(SandboxMode::Context));
+    set_context(&mut state, &project)?;
+
+    let error = stage(&mut state, "escape/main.rs").unwrap_err();
+    assert!(error.to_string().contains("outside the session context"));
+    let error = stage(&mut state, "escape/../../secrets/main.rs").unwrap_err();
+    assert!(error.to_string().contains("outside the session context"));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_policy_decides_on_links_within_the_sandbox() -> Result<()> {
+    let temp_dir = setup()?;
+    let project = temp_dir.path().join("project");
+    fs::create_dir(project.join("src"))?;
+    fs::write(project.join("src/lib.rs"), SOURCE)?;
+    std::os::unix::fs::symlink(project.join("src"), project.join("link"))?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_sandbox(PathSandbox::default().with_mode(SandboxMode::Context));
+    set_context(&mut state, &project)?;
+    stage(&mut state, "link/lib.rs")?;
+
+    state.set_sandbox(
+        PathSandbox::default()
+            .with_mode(SandboxMode::Context)
+            .with_symlinks(SymlinkPolicy::Deny),
+    );
+    let error = stage(&mut state, "link/lib.rs").unwrap_err();
+    assert!(error.to_string().contains("passes through the symlink"));
+    stage(&mut state, "src/lib.rs")?;
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Tests for formatting the web languages with a project-local prettier
+
+#![cfg(unix)]
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A stand-in for prettier that records its arguments above the source it was given
+const FAKE_PRETTIER: &str = "#!/bin/sh\necho \"// prettier $*\"\ncat\n";
+
+fn install_fake_prettier(project: &Path) -> Result<()> {
+    let bin = project.join("node_modules").join(".bin");
+    fs::create_dir_all(&bin)?;
+    let prettier = bin.join("prettier");
+    fs::write(&prettier, FAKE_PRETTIER)?;
+    fs::set_permissions(&prettier, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+fn edit(file_path: &Path, language: LanguageName, anchor: &str, content: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let editor = Editor::new(
+        content.into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(language)?,
+        file_path.to_path_buf(),
+        None,
+    )?;
+    editor.commit_to_string()
+}
+
+#[test]
+fn web_languages_are_formatted_with_the_project_prettier() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    install_fake_prettier(temp_dir.path())?;
+    let src = temp_dir.path().join("src");
+    fs::create_dir(&src)?;
+
+    for (file_name, language, parser) in [
+        ("main.js", LanguageName::Javascript, "babel"),
+        ("main.ts", LanguageName::Typescript, "typescript"),
+        ("main.tsx", LanguageName::Tsx, "typescript"),
+    ] {
+        let file_path = src.join(file_name);
+        fs::write(&file_path, "const x = 1;\n")?;
+        let output = edit(&file_path, language, "const x = 1;", "\nconst y = 2;")?;
+        assert_eq!(
+            output,
+            format!(
+                "// prettier --parser {parser} --stdin-filepath {}\nconst x = 1;\nconst y = 2;\n",
+                file_path.display()
+            )
+        );
+    }
+
+    let file_path = src.join("config.json");
+    fs::write(&file_path, "{\n    \"a\": 1\n}\n")?;
+    let output = edit(
+        &file_path,
+        LanguageName::Json,
+        "\"a\": 1",
+        ",\n    \"b\": 2",
+    )?;
+    assert!(
+        output.starts_with("// prettier --parser json --stdin-filepath"),
+        "{output}"
+    );
+    Ok(())
+}
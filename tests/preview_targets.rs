@@ -0,0 +1,73 @@
+//! Tests for the target node named in previews of node operations
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, PreviewReport, TargetNode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const SOURCE: &str = "\
+use std::fmt;
+
+fn helper(value: u32) -> u32 {
+    value + 1
+}
+
+fn main() {
+    println!(\"{}\", helper(1));
+}
+";
+
+fn preview(operation: Operation, anchor: &str, content: &str) -> Result<PreviewReport> {
+    let registry = LanguageRegistry::new()?;
+    let rust = registry.get_language(LanguageName::Rust)?;
+    let selector = Selector::builder(operation, anchor).build()?;
+    let (report, _) =
+        Editor::from_source(SOURCE, content.into(), selector, rust)?.preview_report()?;
+    Ok(report)
+}
+
+#[test]
+fn node_operations_name_their_target() -> Result<()> {
+    let report = preview(
+        Operation::ReplaceNode,
+        "fn helper(value: u32) -> u32 {",
+        "fn helper(value: u32) -> u32 {\n    value + 2\n}",
+    )?;
+    let start_byte = SOURCE.find("fn helper").unwrap();
+    assert_eq!(
+        report.edit.target_node,
+        Some(TargetNode {
+            kind: "function_item".into(),
+            start_byte,
+            end_byte: SOURCE.find("\n\nfn main").unwrap(),
+            start_line: 3,
+            end_line: 5,
+        })
+    );
+    let text = report.to_string();
+    assert!(
+        text.starts_with(&format!(
+            "STAGED: replace node targeting `function_item` lines 3–5 (bytes {start_byte}–"
+        )),
+        "{text}"
+    );
+
+    let report = preview(Operation::InsertAfterNode, "use std::fmt;", "use std::io;")?;
+    let text = report.to_string();
+    assert!(
+        text.starts_with(
+            "STAGED: insert after node targeting `use_declaration` line 1 (bytes 0–13)"
+        ),
+        "{text}"
+    );
+    Ok(())
+}
+
+#[test]
+fn text_operations_have_no_target_node() -> Result<()> {
+    let report = preview(Operation::ReplaceExact, "value + 1", "value + 2")?;
+    assert!(report.staged);
+    assert_eq!(report.edit.target_node, None);
+    assert!(report.to_string().starts_with("STAGED: replace exact\n\n"));
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Tests for progress notifications and cancellation of long operations
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use mcplease::types::McpRequest;
+use semantic_code_edit_mcp::error::SemanticEditError;
+use semantic_code_edit_mcp::progress::{Cancellations, Notify, Progress};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::Tools;
+use serde_json::{Value, json};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// A tools/call request, asking for progress if `progress_token` is given
+fn request(id: u64, progress_token: Option<&str>) -> Result<McpRequest> {
+    let mut params = json!({ "name": "open_files", "arguments": {} });
+    if let Some(token) = progress_token {
+        params["_meta"] = json!({ "progressToken": token });
+    }
+    Ok(serde_json::from_value(
+        json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call", "params": params }),
+    )?)
+}
+
+/// A notifier collecting what it is sent
+fn collector() -> (Notify, Arc<Mutex<Vec<Value>>>) {
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let notify: Notify = {
+        let sent = Arc::clone(&sent);
+        Arc::new(move |notification| sent.lock().unwrap().push(notification))
+    };
+    (notify, sent)
+}
+
+fn open_rust_files(progress: Progress) -> Result<String> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join("a.rs"), "fn a() {}\n")?;
+    fs::write(temp_dir.path().join("b.rs"), "fn b() {}\n")?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?.with_progress(progress);
+    let open_files: Tools = serde_json::from_value(json!({
+        "name": "open_files",
+        "arguments": { "file_paths": [temp_dir.path().join("*.rs")] },
+    }))?;
+    open_files.execute(&mut state)
+}
+
+#[test]
+fn opening_files_reports_progress_when_asked() -> Result<()> {
+    let (notify, sent) = collector();
+    let progress = Progress::new(&request(1, Some("open"))?, notify, Arc::default());
+    open_rust_files(progress)?;
+
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent.len(), 2, "{sent:?}");
+    for (step, notification) in sent.iter().enumerate() {
+        assert_eq!(notification["method"], "notifications/progress");
+        assert_eq!(notification["params"]["progressToken"], "open");
+        assert_eq!(notification["params"]["progress"], step + 1);
+        assert_eq!(notification["params"]["total"], 2);
+    }
+    assert!(
+        sent[1]["params"]["message"]
+            .as_str()
+            .unwrap()
+            .ends_with("b.rs")
+    );
+
+    // without a token, nothing is sent
+    let (notify, sent) = collector();
+    open_rust_files(Progress::new(&request(2, None)?, notify, Arc::default()))?;
+    assert!(sent.lock().unwrap().is_empty());
+    Ok(())
+}
+
+#[test]
+fn cancelled_requests_stop() -> Result<()> {
+    let (notify, sent) = collector();
+    let cancelled = Arc::new(AtomicBool::new(true));
+    let progress = Progress::new(&request(1, Some("open"))?, notify, cancelled);
+    let error = open_rust_files(progress).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(SemanticEditError::Cancelled)
+    ));
+    assert!(sent.lock().unwrap().is_empty());
+    Ok(())
+}
+
+#[test]
+fn cancellations_reach_the_named_request_in_its_session() {
+    let cancellations = Cancellations::default();
+    cancellations.observe(
+        "first",
+        br#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{}}"#,
+    );
+    let cancelled = cancellations.flag("first", &json!(7));
+    let other_session = cancellations.flag("second", &json!(7));
+
+    cancellations.observe(
+        "second",
+        br#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":8}}"#,
+    );
+    cancellations.observe(
+        "first",
+        br#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":7}}"#,
+    );
+    assert!(cancelled.load(Ordering::Relaxed));
+    assert!(!other_session.load(Ordering::Relaxed));
+
+    // finished requests are forgotten, and cancelling them does nothing
+    cancellations.finish("first", &json!(7));
+    cancellations.observe(
+        "first",
+        br#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":7}}"#,
+    );
+    assert!(
+        !cancellations
+            .flag("first", &json!(7))
+            .load(Ordering::Relaxed)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cancelled_requests_stop_their_formatter() -> Result<()> {
+    use semantic_code_edit_mcp::editor::Editor;
+    use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+    use semantic_code_edit_mcp::selector::{Operation, Selector};
+    use std::os::unix::fs::PermissionsExt;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    let temp_dir = TempDir::new()?;
+    let bin = temp_dir.path().join("node_modules").join(".bin");
+    fs::create_dir_all(&bin)?;
+    fs::write(bin.join("prettier"), "#!/bin/sh\nsleep 5\ncat\n")?;
+    fs::set_permissions(bin.join("prettier"), fs::Permissions::from_mode(0o755))?;
+    let file_path = temp_dir.path().join("main.js");
+    fs::write(&file_path, "const x = 1;\n")?;
+
+    let (notify, sent) = collector();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let progress = Progress::new(&request(1, Some("format"))?, notify, Arc::clone(&cancelled));
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        cancelled.store(true, Ordering::Relaxed);
+    });
+
+    let registry = LanguageRegistry::new()?;
+    let started = Instant::now();
+    let result = Editor::new(
+        "\nconst y = 2;".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "const x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(LanguageName::Javascript)?,
+        file_path,
+        None,
+    )?
+    .with_progress(progress)
+    .preview();
+    canceller.join().unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(4));
+    let error = result.unwrap_err();
+    assert!(
+        matches!(error.downcast_ref(), Some(SemanticEditError::Cancelled)),
+        "{error}"
+    );
+    assert_eq!(
+        sent.lock().unwrap()[0]["params"]["message"],
+        "Running prettier"
+    );
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Tests for the guided workflow MCP prompts
+
+use anyhow::Result;
+use mcplease::server_info;
+use mcplease::types::{McpRequest, McpResponse};
+use semantic_code_edit_mcp::resources::Subscriptions;
+use semantic_code_edit_mcp::server;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::{Value, json};
+
+fn request(method: &str, params: Value) -> Result<McpResponse> {
+    let request: McpRequest = serde_json::from_value(
+        json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}),
+    )?;
+    Ok(server::handle(
+        request,
+        &mut SemanticEditTools::with_standard_operations(None)?,
+        &mut Subscriptions::default(),
+        &server_info!(),
+        None,
+    ))
+}
+
+#[test]
+fn prompts_are_advertised_and_listed() -> Result<()> {
+    let initialized = request("initialize", json!({}))?.result.unwrap();
+    assert!(initialized["capabilities"]["prompts"].is_object());
+
+    let listed = request("prompts/list", json!({}))?.result.unwrap();
+    let names: Vec<_> = listed["prompts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|prompt| prompt["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "refactor_function",
+            "add_test_for_function",
+            "apply_codemod"
+        ]
+    );
+    assert_eq!(listed["prompts"][0]["arguments"][0]["name"], "file_path");
+    assert_eq!(listed["prompts"][0]["arguments"][0]["required"], true);
+    Ok(())
+}
+
+#[test]
+fn prompts_template_the_tool_sequence() -> Result<()> {
+    let prompt = request(
+        "prompts/get",
+        json!({
+            "name": "refactor_function",
+            "arguments": {"file_path": "src/lib.rs", "function": "parse", "goal": "return errors"},
+        }),
+    )?
+    .result
+    .unwrap();
+    let text = prompt["messages"][0]["content"]["text"].as_str().unwrap();
+    assert!(text.contains("Goal: return errors"), "{text}");
+    assert!(
+        text.contains("`file_paths: [\"src/lib.rs\"]` and `symbol: \"parse\"`"),
+        "{text}"
+    );
+    let stage = text.find("stage_operation").unwrap();
+    let retarget = text.find("retarget_staged").unwrap();
+    let commit = text.find("commit_staged").unwrap();
+    assert!(stage < retarget && retarget < commit, "{text}");
+
+    let prompt = request(
+        "prompts/get",
+        json!({
+            "name": "add_test_for_function",
+            "arguments": {"file_path": "src/lib.rs", "function": "parse", "test_file": "tests/parse.rs"},
+        }),
+    )?
+    .result
+    .unwrap();
+    let text = prompt["messages"][0]["content"]["text"].as_str().unwrap();
+    assert!(text.contains("`file_path: \"tests/parse.rs\"`"), "{text}");
+    Ok(())
+}
+
+#[test]
+fn missing_arguments_and_unknown_prompts_are_refused() -> Result<()> {
+    let response = request(
+        "prompts/get",
+        json!({"name": "apply_codemod", "arguments": {"files": "src/**/*.rs"}}),
+    )?;
+    let message = response.error.unwrap().message;
+    assert!(message.contains("needs the `change` argument"), "{message}");
+
+    let response = request("prompts/get", json!({"name": "rewrite_everything"}))?;
+    let message = response.error.unwrap().message;
+    assert!(
+        message.contains("unknown prompt `rewrite_everything`"),
+        "{message}"
+    );
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Tests for formatting Python with ruff or black
+
+#![cfg(unix)]
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A stand-in for a formatter that records its name and arguments above the source
+const FAKE_FORMATTER: &str = "#!/bin/sh\necho \"# $(basename \"$0\") $*\"\ncat\n";
+
+fn edit(file_path: &Path) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    let editor = Editor::new(
+        "\n    y = 2".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "x = 1".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(LanguageName::Python)?,
+        file_path.to_path_buf(),
+        None,
+    )?;
+    editor.commit_to_string()
+}
+
+#[test]
+fn ruff_is_preferred_unless_the_project_configures_black() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let bin = temp_dir.path().join("bin");
+    fs::create_dir(&bin)?;
+    for formatter in ["ruff", "black"] {
+        fs::write(bin.join(formatter), FAKE_FORMATTER)?;
+        fs::set_permissions(bin.join(formatter), fs::Permissions::from_mode(0o755))?;
+    }
+    let path = env::join_paths(
+        std::iter::once(bin).chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )?;
+    // SAFETY: this is the only test in this binary, so no other thread reads the environment
+    unsafe { env::set_var("PATH", path) };
+
+    let ruff_project = temp_dir.path().join("ruff_project");
+    fs::create_dir(&ruff_project)?;
+    let file_path = ruff_project.join("main.py");
+    fs::write(&file_path, "def main():\n    x = 1\n")?;
+    assert_eq!(
+        edit(&file_path)?,
+        format!(
+            "# ruff format --stdin-filename {} -\ndef main():\n    x = 1\n    y = 2\n",
+            file_path.display()
+        )
+    );
+
+    let black_project = temp_dir.path().join("black_project");
+    fs::create_dir(&black_project)?;
+    fs::write(
+        black_project.join("pyproject.toml"),
+        "[tool.black]\nline-length = 100\n",
+    )?;
+    let file_path = black_project.join("main.py");
+    fs::write(&file_path, "def main():\n    x = 1\n")?;
+    assert!(
+        edit(&file_path)?.starts_with("# black --quiet --stdin-filename"),
+        "black should format projects that configure it"
+    );
+    Ok(())
+}
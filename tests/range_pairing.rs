@@ -0,0 +1,84 @@
+//! Tests for pairing `replace_range` anchors with the nearest `end` after them
+
+mod common;
+
+use anyhow::Result;
+use common::call;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "\
+fn one() {
+    let a = 1;
+    if a > 0 {
+        log(a);
+    }
+}
+
+fn two() {}
+";
+
+/// The JSON preview of a `replace_range`, and the file after committing it
+fn replace_range(anchor: &str, end: &str, content: &str) -> Result<(Value, String)> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = call(
+        &mut state,
+        "stage_operation",
+        json!({
+            "file_path": file_path,
+            "operation": "replace_range",
+            "anchor": anchor,
+            "end": end,
+            "content": content,
+            "format": "json",
+            "format_mode": "never",
+        }),
+    )?;
+    call(&mut state, "commit_staged", json!({}))?;
+    Ok((
+        serde_json::from_str(&preview)?,
+        fs::read_to_string(file_path)?,
+    ))
+}
+
+#[test]
+fn ranges_end_at_the_nearest_end_that_keeps_other_code_whole() -> Result<()> {
+    // the nearest `}` closes the `if`, which would leave the function cut in half
+    let (preview, output) = replace_range("fn one() {", "}", "fn one() {\n    log(2);\n}")?;
+    assert_eq!(
+        preview["span"],
+        "lines 1-6, ending at the nearest `end` after the anchor at the same nesting level; \
+         nearer ones would cut through other code",
+        "{preview}"
+    );
+    assert_eq!(output, "fn one() {\n    log(2);\n}\n\nfn two() {}\n");
+    Ok(())
+}
+
+#[test]
+fn spans_report_ends_at_other_nesting_levels() -> Result<()> {
+    let (preview, output) = replace_range(
+        "let a = 1;",
+        "log(a);",
+        "let a = 2;\n    if a > 1 {\n        log(a);",
+    )?;
+    let span = preview["span"].as_str().unwrap_or_default();
+    assert!(span.starts_with("lines 2-4, "), "{preview}");
+    assert!(
+        span.contains("no `end` is at the anchor's nesting level"),
+        "{preview}"
+    );
+    assert!(output.contains("if a > 1 {"), "{output}");
+
+    let (preview, _) = replace_range("let a", "= 1;", "let a = 3;")?;
+    assert_eq!(
+        preview["span"],
+        "line 2, ending at the nearest `end` after the anchor, at the same nesting level"
+    );
+    Ok(())
+}
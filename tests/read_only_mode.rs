@@ -0,0 +1,117 @@
+//! Tests for server-wide and per-session read-only mode
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::filesystem::FileOperations;
+use semantic_code_edit_mcp::sandbox::PathSandbox;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation, Tools};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+fn set_context(state: &mut SemanticEditTools, arguments: Value) -> Result<String> {
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": arguments,
+    }))?;
+    set_context.execute(state)
+}
+
+fn stage(state: &mut SemanticEditTools) -> Result<String> {
+    StageOperation {
+        file_path: "main.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\n    let y = 2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+fn commit(state: &mut SemanticEditTools, dry_run: bool) -> Result<String> {
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run,
+        format: ResultFormat::Text,
+    }
+    .execute(state)
+}
+
+#[test]
+fn read_only_server_previews_but_does_not_commit() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?
+        .with_sandbox(PathSandbox::default().with_read_only(true));
+    set_context(&mut state, json!({ "path": temp_dir.path() }))?;
+
+    stage(&mut state)?;
+    assert!(commit(&mut state, true)?.contains("let y = 2;"));
+
+    let error = commit(&mut state, false).unwrap_err();
+    assert!(error.to_string().contains("read-only mode"), "{error}");
+    assert!(state.get_staged_operation(None)?.is_some());
+    assert_eq!(fs::read_to_string(&file_path)?, SOURCE);
+
+    // the file operations refuse writes however they are reached
+    assert!(
+        state
+            .file_operations()
+            .write_file(file_path.clone(), String::new())
+            .is_err()
+    );
+
+    // sessions cannot opt out of a read-only server
+    let response = set_context(
+        &mut state,
+        json!({ "path": temp_dir.path(), "read_only": false }),
+    )?;
+    assert!(response.contains("server is in read-only mode"));
+    assert!(commit(&mut state, false).is_err());
+    Ok(())
+}
+
+#[test]
+fn sessions_can_be_made_read_only() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    let response = set_context(
+        &mut state,
+        json!({ "path": temp_dir.path(), "read_only": true }),
+    )?;
+    assert!(response.contains("Read-only mode enabled"));
+    stage(&mut state)?;
+    assert!(commit(&mut state, false).is_err());
+
+    set_context(
+        &mut state,
+        json!({ "path": temp_dir.path(), "read_only": false }),
+    )?;
+    commit(&mut state, false)?;
+    assert!(fs::read_to_string(&file_path)?.contains("let y = 2;"));
+    Ok(())
+}
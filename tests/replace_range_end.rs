@@ -0,0 +1,72 @@
+//! Tests for `replace_range` with and without its end text
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+const SOURCE: &str = "\
+fn setup() {
+    todo!()
+}
+
+fn teardown() {}
+";
+
+fn replace(selector: Selector, content: &str) -> Result<String> {
+    let registry = LanguageRegistry::new()?;
+    Editor::builder(
+        content,
+        selector,
+        registry.get_language(LanguageName::Rust)?,
+    )
+    .with_source(SOURCE)
+    .with_format_mode(FormatMode::Never)
+    .build()?
+    .commit_to_string()
+}
+
+#[test]
+fn exclusive_ranges_keep_their_end_text() -> Result<()> {
+    let selector = Selector::builder(Operation::ReplaceRange, "fn setup() {")
+        .with_end("fn teardown() {")
+        .with_end_inclusive(false)
+        .build()?;
+    assert_eq!(
+        replace(selector, "fn setup() {\n    init_logging();\n}\n\n")?,
+        "\
+fn setup() {
+    init_logging();
+}
+
+fn teardown() {}
+"
+    );
+
+    // ranges include the end text by default
+    let selector = Selector::builder(Operation::ReplaceRange, "todo!()")
+        .with_end("}")
+        .build()?;
+    assert!(selector.end_inclusive);
+    assert_eq!(
+        replace(selector, "init_logging();\n}")?,
+        "fn setup() {\n    init_logging();\n}\n\nfn teardown() {}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn end_inclusive_defaults_to_true_and_only_applies_to_ranges() -> Result<()> {
+    let selector: Selector = serde_json::from_str(
+        r#"{"operation": "replace_range", "anchor": "fn setup", "end": "}"}"#,
+    )?;
+    assert!(selector.end_inclusive);
+    assert!(!serde_json::to_string(&selector)?.contains("end_inclusive"));
+
+    let error = Selector::builder(Operation::ReplaceExact, "fn setup")
+        .with_end_inclusive(false)
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("`end_inclusive`"), "{error}");
+    Ok(())
+}
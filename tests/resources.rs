@@ -0,0 +1,153 @@
+//! Tests for the staged diff and outline MCP resources
+
+use anyhow::Result;
+use mcplease::server_info;
+use mcplease::types::{McpRequest, McpResponse};
+use semantic_code_edit_mcp::resources::Subscriptions;
+use semantic_code_edit_mcp::server;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+struct Client {
+    state: SemanticEditTools,
+    subscriptions: Subscriptions,
+}
+
+impl Client {
+    fn new(context: &TempDir) -> Result<Self> {
+        let mut client = Self {
+            state: SemanticEditTools::with_standard_operations(None)?,
+            subscriptions: Subscriptions::default(),
+        };
+        client.call_tool("set_context", json!({"path": context.path()}))?;
+        Ok(client)
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<McpResponse> {
+        let request: McpRequest = serde_json::from_value(
+            json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}),
+        )?;
+        Ok(server::handle(
+            request,
+            &mut self.state,
+            &mut self.subscriptions,
+            &server_info!(),
+            None,
+        ))
+    }
+
+    fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value> {
+        let response = self.request("tools/call", json!({"name": name, "arguments": arguments}))?;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        Ok(response.result.unwrap())
+    }
+
+    fn read(&mut self, uri: &str) -> Result<String> {
+        let response = self.request("resources/read", json!({"uri": uri}))?;
+        let result = response.result.unwrap_or_default();
+        Ok(result["contents"][0]["text"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    fn updated(&mut self) -> Vec<Value> {
+        server::updated_notifications(&self.state, &mut self.subscriptions)
+            .into_iter()
+            .map(|notification| notification["params"]["uri"].clone())
+            .collect()
+    }
+}
+
+#[test]
+fn resources_are_advertised_and_listed() -> Result<()> {
+    let context = TempDir::new()?;
+    let mut client = Client::new(&context)?;
+
+    let initialized = client.request("initialize", json!({}))?.result.unwrap();
+    assert_eq!(
+        initialized["capabilities"]["resources"]["subscribe"],
+        json!(true)
+    );
+
+    let listed = client.request("resources/list", json!({}))?.result.unwrap();
+    assert_eq!(listed["resources"][0]["uri"], "staged://current");
+    let templates = client
+        .request("resources/templates/list", json!({}))?
+        .result
+        .unwrap();
+    assert_eq!(
+        templates["resourceTemplates"][0]["uriTemplate"],
+        "outline://{path}"
+    );
+
+    let unknown = client.request("resources/read", json!({"uri": "nope://x"}))?;
+    assert!(unknown.error.unwrap().message.contains("unknown resource"));
+    Ok(())
+}
+
+#[test]
+fn staged_diff_follows_the_staged_operation() -> Result<()> {
+    let context = TempDir::new()?;
+    std::fs::write(
+        context.path().join("main.rs"),
+        "fn main() {\n    let x = 1;\n}\n",
+    )?;
+    let mut client = Client::new(&context)?;
+
+    assert_eq!(
+        client.read("staged://current")?,
+        "No operation is staged.\n"
+    );
+    client.request("resources/subscribe", json!({"uri": "staged://current"}))?;
+    assert!(client.updated().is_empty());
+
+    client.call_tool(
+        "stage_operation",
+        json!({
+            "file_path": "main.rs",
+            "operation": "replace_exact",
+            "anchor": "let x = 1;",
+            "content": "let x = 2;",
+        }),
+    )?;
+    assert_eq!(client.updated(), [json!("staged://current")]);
+    let diff = client.read("staged://current")?;
+    assert!(diff.contains("+    let x = 2;"), "{diff}");
+
+    client.request("resources/unsubscribe", json!({"uri": "staged://current"}))?;
+    client.call_tool("commit_staged", json!({}))?;
+    assert!(client.updated().is_empty());
+    Ok(())
+}
+
+#[test]
+fn outlines_follow_the_file() -> Result<()> {
+    let context = TempDir::new()?;
+    let path = context.path().join("lib.rs");
+    std::fs::write(&path, "fn first() {}\n")?;
+    let mut client = Client::new(&context)?;
+
+    client.request("resources/subscribe", json!({"uri": "outline://lib.rs"}))?;
+    assert_eq!(
+        client.read("outline://lib.rs")?,
+        "function first (line 1)\n"
+    );
+
+    std::fs::write(
+        &path,
+        "fn first() {}\n\nstruct Second {\n    field: u8,\n}\n",
+    )?;
+    assert_eq!(client.updated(), [json!("outline://lib.rs")]);
+    let outline = client.read("outline://lib.rs")?;
+    assert!(outline.contains("Second (lines 3-5)"), "{outline}");
+
+    // edits that leave the definitions alone do not change the outline
+    std::fs::write(
+        &path,
+        "fn first() { }\n\nstruct Second {\n    field: u8,\n}\n",
+    )?;
+    assert!(client.updated().is_empty());
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Tests for applying edits to sources that span many rope chunks
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+/// About 150 kilobytes of Rust with multi-byte characters, so chunk boundaries fall
+/// in varied places
+fn large_source() -> String {
+    (0..3000)
+        .map(|index| {
+            format!("fn f{index}() -> &'static str {{\n    \"héllo wörld {index}\"\n}}\n\n")
+        })
+        .collect()
+}
+
+#[test]
+fn edits_near_the_end_of_a_large_file_are_applied_exactly() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let language = registry.get_language(LanguageName::Rust)?;
+    let source = large_source();
+
+    let output = Editor::from_source(
+        &source,
+        "fn f2998() -> &'static str {\n    \"ünïcode\"\n}".into(),
+        Selector {
+            operation: Operation::ReplaceNode,
+            anchor: "fn f2998()".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        language,
+    )?
+    .with_format_mode(FormatMode::Never)
+    .commit_to_string()?;
+
+    let expected = source.replace(
+        "fn f2998() -> &'static str {\n    \"héllo wörld 2998\"\n}",
+        "fn f2998() -> &'static str {\n    \"ünïcode\"\n}",
+    );
+    assert_eq!(output, expected);
+    Ok(())
+}
+
+#[test]
+fn invalid_edits_in_a_large_file_are_refused() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let language = registry.get_language(LanguageName::Rust)?;
+    let source = large_source();
+
+    let error = Editor::from_source(
+        &source,
+        "let broken = ;".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "fn f1500() -> &'static str {".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        language,
+    )?
+    .with_format_mode(FormatMode::Never)
+    .commit_to_string()
+    .unwrap_err();
+
+    assert!(error.to_string().contains("invalid syntax"), "{error}");
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Tests for formatting Rust when rustfmt is not available
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::Editor;
+use semantic_code_edit_mcp::languages::rust::RUST_FORMATTER_ENV;
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use std::env;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn editor<'a>(registry: &'a LanguageRegistry, file_path: &Path) -> Result<Editor<'a>> {
+    Editor::new(
+        "\nlet y=2;".into(),
+        Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        registry.get_language(LanguageName::Rust)?,
+        file_path.to_path_buf(),
+        None,
+    )
+}
+
+#[test]
+fn prettyplease_formats_unless_it_would_drop_comments() -> Result<()> {
+    // SAFETY: this is the only test in this binary, so no other thread reads the environment
+    unsafe { env::set_var(RUST_FORMATTER_ENV, "prettyplease") };
+    let registry = LanguageRegistry::new()?;
+    let temp_dir = TempDir::new()?;
+
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(
+        &file_path,
+        "/// Entry point\nfn main() {\n    let x = 1;\n}\n",
+    )?;
+    assert_eq!(
+        editor(&registry, &file_path)?.commit_to_string()?,
+        "/// Entry point\nfn main() {\n    let x = 1;\n    let y = 2;\n}\n"
+    );
+
+    // a plain comment would be lost, so the edit is staged unformatted with a warning
+    fs::write(&file_path, "fn main() {\n    // one\n    let x = 1;\n}\n")?;
+    let (preview, _) = editor(&registry, &file_path)?.preview()?;
+    assert!(
+        preview.contains(
+            "no Rust formatter is available (tried prettyplease), so this edit has not been formatted"
+        ),
+        "{preview}"
+    );
+    assert_eq!(
+        editor(&registry, &file_path)?.commit_to_string()?,
+        "fn main() {\n    // one\n    let x = 1;\nlet y=2;\n}\n"
+    );
+
+    // SAFETY: as above
+    unsafe { env::set_var(RUST_FORMATTER_ENV, "rustfmt,clippy") };
+    // languages are built on first use, which is when the setting is read
+    let error = LanguageRegistry::new()?
+        .get_language(LanguageName::Rust)
+        .unwrap_err();
+    assert!(error.to_string().contains("not `clippy`"), "{error}");
+    Ok(())
+}
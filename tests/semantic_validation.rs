@@ -18,3 +18,210 @@ fn validate_code(code: &str, language: LanguageName) -> Option<String> {
     println!("{}", &tree.root_node().to_string());
     Editor::validate(language, &tree, code)
 }
+
+#[test]
+fn go_import_after_declarations() {
+    let code = "package main\n\nfunc main() {}\n\nimport \"os\"\n";
+    let message = validate_code(code, LanguageName::Go).unwrap();
+    assert!(message.contains("Imports must come before all other declarations"));
+    assert_eq!(message.matches('•').count(), 1);
+}
+
+#[test]
+fn go_entrypoint_signatures() {
+    assert!(validate_code("package main\n\nfunc init(x int) {}\n", LanguageName::Go).is_some());
+    assert!(
+        validate_code(
+            "package main\n\nfunc main() int { return 1 }\n",
+            LanguageName::Go
+        )
+        .is_some()
+    );
+    assert!(
+        validate_code(
+            "package main\n\nfunc main() {\n\tinit()\n}\n",
+            LanguageName::Go
+        )
+        .is_some()
+    );
+    assert!(
+        validate_code(
+            "package main\n\nfunc main(args []string) {}\n",
+            LanguageName::Go
+        )
+        .is_some()
+    );
+    // outside package main, main is an ordinary function
+    assert!(
+        validate_code(
+            "package server\n\nfunc main(args []string) int { return len(args) }\n",
+            LanguageName::Go
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn go_misplaced_functions_and_imports_are_rejected() {
+    let function_in_struct = "package main\n\ntype User struct {\n\tfunc (u User) Hello() {}\n}\n";
+    assert!(validate_code(function_in_struct, LanguageName::Go).is_some());
+
+    let import_in_body = "package main\n\nfunc main() {\n\timport \"fmt\"\n}\n";
+    assert!(validate_code(import_in_body, LanguageName::Go).is_some());
+}
+
+#[test]
+fn go_valid_code_passes() {
+    let code = "package main\n\nimport \"os\"\n\nfunc helper(x int) int { return x }\n\nfunc main() {\n\tos.Exit(helper(0))\n}\n";
+    assert!(validate_code(code, LanguageName::Go).is_none());
+}
+
+#[test]
+fn java_misplacements() {
+    let cases = [
+        (
+            "void hello() {}\nclass A {}\n",
+            "Methods must be declared inside a type body",
+        ),
+        (
+            "class A {\n  void f() {\n    import java.util.List;\n  }\n}\n",
+            "Imports must be at the top of the file",
+        ),
+        (
+            "class A {}\nimport java.util.List;\n",
+            "Imports must come before all other declarations",
+        ),
+        (
+            "class A {}\npackage b;\n",
+            "The package declaration must come first in the file",
+        ),
+        (
+            "class A {\n  void f(boolean x) {\n    if (x) class B {}\n  }\n}\n",
+            "Local type declarations must be inside a block",
+        ),
+        (
+            "class A {\n  void f() {\n    while (true) interface I {}\n  }\n}\n",
+            "Local type declarations must be inside a block",
+        ),
+    ];
+    for (code, expected) in cases {
+        let message = validate_code(code, LanguageName::Java).unwrap();
+        assert!(message.contains(expected), "{code}\n{message}");
+    }
+}
+
+#[test]
+fn java_valid_code_passes() {
+    let code = "package a;\n\nimport java.util.List;\n\nclass A {\n  void f(List<String> items) {\n    class Local {}\n    if (items.isEmpty()) {\n      return;\n    }\n  }\n}\n";
+    assert!(validate_code(code, LanguageName::Java).is_none());
+}
+
+#[test]
+fn csharp_misplacements() {
+    let cases = [
+        (
+            "namespace N {\n  public void F() {}\n}\n",
+            "Methods must be declared inside a type body",
+        ),
+        (
+            "public void Hello() {}\nclass A {}\n",
+            "Methods must be declared inside a type body",
+        ),
+        (
+            "class A {\n  using System;\n}\n",
+            "Using directives cannot be placed inside type bodies",
+        ),
+        (
+            "class A {}\nusing System;\n",
+            "Using directives must come before all other declarations",
+        ),
+        (
+            "namespace N {\n  class A {}\n  using System;\n}\n",
+            "Using directives must come before all other declarations",
+        ),
+        (
+            "class A {\n  namespace M {}\n}\n",
+            "Namespaces cannot be declared inside type bodies",
+        ),
+    ];
+    for (code, expected) in cases {
+        let message = validate_code(code, LanguageName::CSharp).expect(code);
+        assert!(message.contains(expected), "{code}\n{message}");
+    }
+}
+
+#[test]
+fn csharp_valid_code_passes() {
+    let code = "using System;\n\nnamespace N {\n  using System.IO;\n\n  class A {\n    void F() {}\n    class Nested {}\n  }\n}\n";
+    assert!(validate_code(code, LanguageName::CSharp).is_none());
+
+    let top_level = "using System;\n\nstatic void Hello() {}\nHello();\n";
+    assert!(validate_code(top_level, LanguageName::CSharp).is_none());
+}
+
+#[test]
+fn tsx_react_mistakes() {
+    let cases = [
+        (
+            "const A = () => <div>\n  const x = 1;\n</div>;\n",
+            "Statements inside JSX children are rendered as text",
+        ),
+        (
+            "const A = () => <div>\n  return <span />;\n</div>;\n",
+            "Statements inside JSX children are rendered as text",
+        ),
+        (
+            "const A = () => <div>\n  import b from './b';\n</div>;\n",
+            "Statements inside JSX children are rendered as text",
+        ),
+        (
+            "const A = () => <div>\n  export const b = 1;\n</div>;\n",
+            "Statements inside JSX children are rendered as text",
+        ),
+        (
+            "const [a, setA] = useState(0);\n",
+            "Hooks can only be called inside function components or custom hooks",
+        ),
+        (
+            "useEffect(() => {});\n",
+            "Hooks can only be called inside function components or custom hooks",
+        ),
+        (
+            "class C extends React.Component {\n  render() {\n    const [a] = useState(0);\n    return <div />;\n  }\n}\n",
+            "Hooks cannot be called from class component methods",
+        ),
+        ("const A = () => <div><span>hi</div>;\n", "SYNTAX ERRORS"),
+    ];
+    for (code, expected) in cases {
+        let message = validate_code(code, LanguageName::Tsx).expect(code);
+        assert!(message.contains(expected), "{code}\n{message}");
+    }
+}
+
+#[test]
+fn tsx_valid_components_pass() {
+    let code = "import { useState } from \"react\";\n\nexport function Counter() {\n  const [count, setCount] = useState(0);\n  useEffect(() => {});\n  return (\n    <div>\n      Return policy: let us know within 30 days\n      export your data from the settings page\n      import photos from your phone\n      <button onClick={() => setCount(count + 1)}>{count}</button>\n    </div>\n  );\n}\n";
+    assert!(validate_code(code, LanguageName::Tsx).is_none());
+}
+
+#[test]
+fn python_assignments_to_literals_and_calls_are_rejected() {
+    for code in [
+        "y = 2 = None\n",
+        "def f():\n    for f() in items:\n        pass\n",
+        "count() += 1\n",
+        "a, 1 = pair\n",
+    ] {
+        let message = validate_code(code, LanguageName::Python);
+        assert!(
+            message.is_some_and(|message| message.contains("Syntax error")),
+            "{code}"
+        );
+    }
+}
+
+#[test]
+fn python_assignment_targets_pass() {
+    let code = "a = b = 1\nx.y, [z, *rest] = items\nd[0] += 1\nn: int = 0\nfor i, (j, k) in pairs:\n    pass\n";
+    assert_eq!(validate_code(code, LanguageName::Python), None);
+}
@@ -0,0 +1,92 @@
+//! Tests for session timestamps and stale session cleanup
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::state::{SemanticEditSessionData, SemanticEditTools};
+use semantic_code_edit_mcp::tools::CleanupSessions;
+use serde_json::{Value, json};
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn entry(last_used: Option<SystemTime>, metadata_last_used: SystemTime) -> Result<Value> {
+    let data = SemanticEditSessionData {
+        last_used,
+        ..Default::default()
+    };
+    Ok(json!({
+        "data": data,
+        "metadata": { "created_at": metadata_last_used, "last_used": metadata_last_used },
+    }))
+}
+
+#[test]
+fn sessions_record_when_they_were_created_and_used() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let storage_path = temp_dir.path().join("sessions.json");
+    let state = SemanticEditTools::with_standard_operations(storage_path.to_str())?;
+
+    state.set_context(None, temp_dir.path().to_path_buf())?;
+    let data = state.session_store().get_or_create("default")?;
+    let created_at = data.created_at.unwrap();
+    assert!(data.last_used.unwrap() >= created_at);
+
+    state.get_context(None)?;
+    let data = state.session_store().get_or_create("default")?;
+    assert_eq!(data.created_at, Some(created_at));
+    Ok(())
+}
+
+#[test]
+fn cleanup_removes_sessions_older_than_the_ttl() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let storage_path = temp_dir.path().join("sessions.json");
+    let now = SystemTime::now();
+    let old = now - 40 * DAY;
+    fs::write(
+        &storage_path,
+        serde_json::to_string(&json!({
+            "old": entry(Some(old), now)?,
+            "legacy": entry(None, old)?,
+            "recent": entry(Some(now - DAY), now)?,
+            "default": entry(Some(old), old)?,
+        }))?,
+    )?;
+
+    let mut state = SemanticEditTools::with_standard_operations(storage_path.to_str())?;
+    let response = CleanupSessions {
+        older_than_days: None,
+    }
+    .execute(&mut state)?;
+    assert_eq!(response, "Removed 2 sessions unused for more than 30 days.");
+
+    let sessions: Value = serde_json::from_str(&fs::read_to_string(&storage_path)?)?;
+    let mut remaining = sessions.as_object().unwrap().keys().collect::<Vec<_>>();
+    remaining.sort();
+    assert_eq!(remaining, ["default", "recent"]);
+
+    // the reloaded store keeps working and does not bring pruned sessions back
+    state.set_context(Some("recent"), temp_dir.path().to_path_buf())?;
+    let sessions: Value = serde_json::from_str(&fs::read_to_string(&storage_path)?)?;
+    assert!(sessions.get("old").is_none());
+
+    let response = CleanupSessions {
+        older_than_days: Some(0),
+    }
+    .execute(&mut state)?;
+    assert_eq!(response, "Removed 1 sessions unused for more than 0 days.");
+    Ok(())
+}
+
+#[test]
+fn disabled_ttl_requires_an_explicit_age() -> Result<()> {
+    let mut state = SemanticEditTools::with_standard_operations(None)?.with_session_ttl_days(0);
+    let response = CleanupSessions {
+        older_than_days: None,
+    }
+    .execute(&mut state)?;
+    assert!(response.contains("disabled"));
+    Ok(())
+}
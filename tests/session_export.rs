@@ -0,0 +1,138 @@
+//! Tests for exporting a session and resuming it elsewhere
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::sandbox::PathSandbox;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{
+    CommitStaged, ExportSession, ImportSession, StageOperation, Tools,
+};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+/// Stage an edit to `main.rs` in `dir` and export the session
+fn staged_export(dir: &Path) -> Result<String> {
+    fs::write(dir.join("main.rs"), SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": dir, "format_mode": "never" },
+    }))?;
+    set_context.execute(&mut state)?;
+    StageOperation {
+        file_path: "main.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\n    let y = 2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    ExportSession {
+        include_buffers: true,
+    }
+    .execute(&mut state)
+}
+
+fn commit(state: &mut SemanticEditTools) -> Result<String> {
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(state)
+}
+
+#[test]
+fn imported_session_commits_the_staged_operation() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let export = staged_export(temp_dir.path())?;
+    let exported: Value = serde_json::from_str(&export)?;
+    assert_eq!(exported["version"], 1);
+    assert_eq!(exported["session"]["format_mode"], "never");
+
+    // a fresh server, as after a restart
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = ImportSession {
+        session: export,
+        path: None,
+    }
+    .execute(&mut state)?;
+    assert!(response.contains("An operation on"), "{response}");
+
+    commit(&mut state)?;
+    assert!(fs::read_to_string(temp_dir.path().join("main.rs"))?.contains("let y = 2;"));
+    Ok(())
+}
+
+#[test]
+fn import_can_move_the_session_to_another_checkout() -> Result<()> {
+    let original = TempDir::new()?;
+    let export = staged_export(original.path())?;
+    let checkout = TempDir::new()?;
+    fs::write(checkout.path().join("main.rs"), SOURCE)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    ImportSession {
+        session: export,
+        path: Some(checkout.path().display().to_string()),
+    }
+    .execute(&mut state)?;
+    assert_eq!(state.get_context(None)?.as_deref(), Some(checkout.path()));
+
+    commit(&mut state)?;
+    assert!(fs::read_to_string(checkout.path().join("main.rs"))?.contains("let y = 2;"));
+    assert_eq!(fs::read_to_string(original.path().join("main.rs"))?, SOURCE);
+    Ok(())
+}
+
+#[test]
+fn import_refuses_other_versions_and_paths_outside_the_sandbox() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let export = staged_export(temp_dir.path())?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let mut future: Value = serde_json::from_str(&export)?;
+    future["version"] = json!(99);
+    let error = ImportSession {
+        session: future.to_string(),
+        path: None,
+    }
+    .execute(&mut state)
+    .unwrap_err();
+    assert!(error.to_string().contains("version 99 is not supported"));
+
+    let allowed = TempDir::new()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?.with_sandbox(
+        PathSandbox::default().with_allowed_paths(vec![allowed.path().to_path_buf()]),
+    );
+    let error = ImportSession {
+        session: export,
+        path: None,
+    }
+    .execute(&mut state)
+    .unwrap_err();
+    assert!(error.to_string().contains("outside the allowed paths"));
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
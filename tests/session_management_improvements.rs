@@ -122,6 +122,70 @@ mod cache_statistics_tests {
     }
 }
 
+#[cfg(test)]
+mod cache_budget_tests {
+    use super::*;
+    use semantic_code_edit_mcp::state::{DEFAULT_CACHE_MAX_BYTES, StatsLruCache};
+
+    #[test]
+    fn test_entries_are_evicted_to_fit_the_byte_budget() {
+        let mut cache: StatsLruCache = StatsLruCache::new(NonZeroUsize::new(10).unwrap());
+        cache.set_max_bytes(Some(25));
+
+        cache.put("a".into(), "x".repeat(9));
+        cache.put("b".into(), "x".repeat(9));
+        assert_eq!(cache.bytes(), 20);
+
+        // touching `a` makes `b` the least recently used entry
+        assert!(cache.get("a").is_some());
+        cache.put("c".into(), "x".repeat(9));
+        assert_eq!(cache.bytes(), 20);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+
+        // replacing an entry accounts for the old value
+        cache.put("a".into(), "x".repeat(4));
+        assert_eq!(cache.bytes(), 15);
+
+        // values larger than the whole budget are not cached
+        assert_eq!(cache.put("c".into(), "x".repeat(30)), Some("x".repeat(9)));
+        assert!(cache.get("c").is_none());
+        assert_eq!(cache.bytes(), 5);
+    }
+
+    #[test]
+    fn test_shrinking_the_budget_evicts_immediately() {
+        let mut cache: StatsLruCache = StatsLruCache::new(NonZeroUsize::new(10).unwrap());
+        for key in ["a", "b", "c"] {
+            cache.put(key.into(), "x".repeat(99));
+        }
+        assert_eq!(cache.bytes(), 300);
+        cache.set_max_bytes(Some(250));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.bytes(), 200);
+    }
+
+    #[test]
+    fn test_cache_info_reports_memory_usage() -> Result<()> {
+        let tools = SemanticEditTools::new(None, Box::new(StdFileOperations), None)?;
+        let stats = tools.cache_info()?;
+        assert_eq!(stats.bytes, 0);
+        assert_eq!(stats.max_bytes, Some(DEFAULT_CACHE_MAX_BYTES));
+
+        tools
+            .file_cache()
+            .lock()
+            .unwrap()
+            .put("key".into(), "value".into());
+        tools.set_cache_max_bytes(None)?;
+        let stats = tools.cache_info()?;
+        assert_eq!(stats.bytes, 8);
+        assert_eq!(stats.max_bytes, None);
+        assert_eq!(tools.source_cache().stats()?.max_bytes, None);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod auto_context_detection_tests {
 
@@ -55,23 +55,23 @@ impl ArgsDotJson {
         };
 
         for tool in &mut tool_calls {
-            if tool["name"] == "stage_operation" {
-                if let Some(input_path) = &input_path {
-                    tool.get_mut("arguments")
-                        .unwrap()
-                        .as_object_mut()
-                        .unwrap()
-                        .insert(
-                            "file_path".to_string(),
-                            Value::String(
-                                input_path
-                                    .file_name()
-                                    .unwrap()
-                                    .to_string_lossy()
-                                    .to_string(),
-                            ),
-                        );
-                }
+            if tool["name"] == "stage_operation"
+                && let Some(input_path) = &input_path
+            {
+                tool.get_mut("arguments")
+                    .unwrap()
+                    .as_object_mut()
+                    .unwrap()
+                    .insert(
+                        "file_path".to_string(),
+                        Value::String(
+                            input_path
+                                .file_name()
+                                .unwrap()
+                                .to_string_lossy()
+                                .to_string(),
+                        ),
+                    );
             }
         }
 
@@ -99,7 +99,7 @@ impl SnapshotRunner {
     }
 
     fn reset_state(&mut self, base_path: PathBuf) -> Result<()> {
-        self.test_file_operations.clear_captures();
+        self.test_file_operations = Arc::new(TestFileOperations::new());
         self.state = SemanticEditTools::with_file_operations(
             None,
             Box::new(Arc::clone(&self.test_file_operations)),
@@ -261,14 +261,13 @@ impl SnapshotRunner {
                 result.error = Some(format!("Failed to write expected output: {e}"));
                 return result;
             }
-        } else if let Some(output_path) = &result.test.output_path {
-            if let Ok(true) = output_path.try_exists() {
-                // If there is no expected output but the file exists, delete the file
-                if let Err(e) = std::fs::remove_file(output_path) {
-                    result.error =
-                        Some(format!("No output expected, but was unable to delete: {e}"));
-                    return result;
-                }
+        } else if let Some(output_path) = &result.test.output_path
+            && let Ok(true) = output_path.try_exists()
+        {
+            // If there is no expected output but the file exists, delete the file
+            if let Err(e) = std::fs::remove_file(output_path) {
+                result.error = Some(format!("No output expected, but was unable to delete: {e}"));
+                return result;
             }
         }
 
@@ -331,6 +330,10 @@ impl SnapshotRunner {
     #[allow(unused_assignments)]
     fn execute_test(&mut self, test: &SnapshotTest) -> Result<SnapshotExecutionResult> {
         self.reset_state(test.base_path.clone())?;
+        if let Some(input_path) = &test.input_path {
+            self.test_file_operations
+                .add_file(fs::canonicalize(input_path)?, fs::read(input_path)?);
+        }
 
         // Read the arguments
         let args_content = fs::read_to_string(&test.args_path)?;
@@ -0,0 +1,119 @@
+//! Tests for reusing source files and syntax trees between stage, retarget, and commit
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::filesystem::TestFileOperations;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, RetargetStaged, StageOperation};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n    let z = 3;\n}\n";
+
+fn selector(anchor: &str) -> Selector {
+    Selector {
+        operation: Operation::InsertAfter,
+        anchor: anchor.into(),
+        end: None,
+        end_inclusive: true,
+        include_leading_trivia: false,
+    }
+}
+
+fn stage(state: &mut SemanticEditTools, file_path: &Path) -> Result<String> {
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: selector("let x = 1;"),
+        content: Some("\n    let y = 2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+fn commit(state: &mut SemanticEditTools) -> Result<String> {
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(state)
+}
+
+#[test]
+fn stage_retarget_commit_reads_the_file_once() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    stage(&mut state, &file_path)?;
+    RetargetStaged {
+        selector: selector("let z = 3;"),
+        candidate: None,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    commit(&mut state)?;
+
+    let stats = state.source_cache().stats()?;
+    assert_eq!((stats.hits, stats.misses), (2, 1));
+    assert!(fs::read_to_string(&file_path)?.contains("let z = 3;\n    let y = 2;"));
+
+    // the committed file is read afresh
+    stage(&mut state, &file_path)?;
+    assert_eq!(state.source_cache().stats()?.misses, 2);
+    Ok(())
+}
+
+#[test]
+fn changed_files_are_read_again() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, SOURCE)?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    stage(&mut state, &file_path)?;
+    fs::write(&file_path, format!("{SOURCE}\nfn other() {{}}\n"))?;
+
+    // the stale check at commit sees the new content rather than the cached copy
+    let error = commit(&mut state).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("has changed since the operation was staged")
+    );
+    assert_eq!(state.source_cache().stats()?.misses, 2);
+    Ok(())
+}
+
+#[test]
+fn content_without_a_stamp_is_compared_by_hash() -> Result<()> {
+    let file_path = Path::new("/virtual/main.rs");
+    let file_operations = Arc::new(TestFileOperations::new().with_file(file_path, SOURCE));
+    let mut state =
+        SemanticEditTools::with_file_operations(None, Box::new(Arc::clone(&file_operations)))?;
+
+    stage(&mut state, file_path)?;
+    stage(&mut state, file_path)?;
+    assert_eq!(state.source_cache().stats()?.hits, 1);
+
+    file_operations.add_file(file_path, SOURCE.replace("let z = 3;", "let z = 4;"));
+    stage(&mut state, file_path)?;
+    let stats = state.source_cache().stats()?;
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+    Ok(())
+}
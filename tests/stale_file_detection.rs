@@ -0,0 +1,99 @@
+//! Tests that commit_staged refuses to apply operations against files that changed after staging
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn stage(state: &mut SemanticEditTools, file_path: &Path) -> Result<String> {
+    StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"before\"".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)
+}
+
+#[test]
+fn commit_refuses_when_file_changed_after_staging() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, "{\"key\": \"before\", \"other\": 1}\n")?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    stage(&mut state, &file_path)?;
+
+    let concurrent_edit = "{\"key\": \"before\", \"other\": 2}\n";
+    fs::write(&file_path, concurrent_edit)?;
+
+    let error = CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)
+    .unwrap_err();
+    assert!(error.to_string().contains("has changed since"));
+    assert!(error.to_string().contains("stage_operation"));
+    assert_eq!(fs::read_to_string(&file_path)?, concurrent_edit);
+
+    // the refused operation stays staged, and commits once the file is back as it was staged
+    assert!(state.get_staged_operation(None)?.is_some());
+    fs::write(&file_path, "{\"key\": \"before\", \"other\": 1}\n")?;
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn commit_succeeds_when_file_rewritten_with_same_content() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    let original = "{\"key\": \"before\"}\n";
+    fs::write(&file_path, original)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    stage(&mut state, &file_path)?;
+
+    fs::write(&file_path, original)?;
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(fs::read_to_string(&file_path)?.contains("\"after\""));
+    Ok(())
+}
@@ -0,0 +1,239 @@
+//! Tests for the structured JSON result format of stage_operation and commit_staged
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+fn stage(state: &mut SemanticEditTools, file_path: String, anchor: &str) -> Result<Value> {
+    let response = StageOperation {
+        file_path,
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Json,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)?;
+    Ok(serde_json::from_str(&response)?)
+}
+
+#[test]
+fn stage_and_commit_return_json_reports() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, "{\"key\": \"before\"}\n")?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = stage(&mut state, file_path.display().to_string(), "\"before\"")?;
+
+    assert_eq!(preview["staged"], true);
+    assert_eq!(preview["operation"], "replace exact");
+    assert_eq!(preview["target"]["start_byte"], 8);
+    assert_eq!(preview["target"]["end_byte"], 16);
+    let lines = preview["hunks"][0]["lines"].as_array().unwrap();
+    assert!(
+        lines.contains(&serde_json::json!({"kind": "delete", "text": "{\"key\": \"before\"}\n"}))
+    );
+    assert!(lines.iter().any(|line| line["kind"] == "insert"));
+
+    let commit: Value = serde_json::from_str(
+        &CommitStaged {
+            acknowledge: true,
+            acknowledge_dirty: false,
+            acknowledge_duplicate: false,
+            dry_run: false,
+            format: ResultFormat::Json,
+        }
+        .execute(&mut state)?,
+    )?;
+
+    assert_eq!(commit["applied"], true);
+    assert_eq!(commit["dry_run"], false);
+    assert!(commit["message"].as_str().unwrap().contains("Applied"));
+    assert!(fs::read_to_string(&file_path)?.contains("\"after\""));
+    Ok(())
+}
+
+#[test]
+fn failed_preview_reports_not_staged() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, "{\"key\": \"before\"}\n")?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = stage(&mut state, file_path.display().to_string(), "{")?;
+
+    assert_eq!(preview["staged"], false);
+    assert!(preview.get("target").is_none());
+    assert!(preview["hunks"].as_array().unwrap().is_empty());
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn efficiency_counts_added_removed_and_modified_lines() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("notes.txt");
+    let original: String = (0..12).map(|n| format!("line {n}\n")).collect();
+    fs::write(&file_path, format!("start\n{original}end\n"))?;
+
+    let content = original
+        .replace("line 3\n", "line three\n")
+        .replace("line 5\n", "")
+        .replace("line 8\n", "line 8\nline 8.5\n");
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceRange,
+            anchor: "line 0".into(),
+            end: Some("line 11\n".into()),
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content),
+        buffer: None,
+        format: ResultFormat::Json,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    let preview: Value = serde_json::from_str(&response)?;
+
+    let efficiency = &preview["efficiency"];
+    assert_eq!(efficiency["content_lines"], 12);
+    assert_eq!(efficiency["added"], 1);
+    assert_eq!(efficiency["removed"], 1);
+    assert_eq!(efficiency["modified"], 1);
+    assert_eq!(efficiency["changed_lines"], 2);
+    assert_eq!(efficiency["percent"], 16);
+    Ok(())
+}
+
+fn stage_rust(source: &str, operation: Operation, anchor: &str, content: &str) -> Result<Value> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("main.rs");
+    fs::write(&file_path, source)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let response = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation,
+            anchor: anchor.into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Json,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    Ok(serde_json::from_str(&response)?)
+}
+
+#[test]
+fn syntax_errors_are_reported_as_violations() -> Result<()> {
+    let preview = stage_rust(
+        "fn main() {\n    let x = 1;\n}\n",
+        Operation::ReplaceExact,
+        "let x = 1;",
+        "let x = ;",
+    )?;
+
+    assert_eq!(preview["staged"], false);
+    let violations = preview["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["rule"], "syntax");
+    assert_eq!(
+        violations[0]["start"],
+        serde_json::json!({"line": 2, "column": 11})
+    );
+    assert!(
+        preview["message"]
+            .as_str()
+            .unwrap()
+            .contains("===SYNTAX ERRORS===\nSyntax error at 2:11-2:12\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn context_violations_include_rule_suggestion_and_range() -> Result<()> {
+    let preview = stage_rust(
+        "fn main() {\n    let x = 1;\n}\n",
+        Operation::InsertAfter,
+        "let x = 1;",
+        "\n    impl Foo {}",
+    )?;
+
+    assert_eq!(preview["staged"], false);
+    let violation = &preview["violations"][0];
+    assert_eq!(violation["rule"], "invalid.impl.in.function.body");
+    assert_eq!(
+        violation["message"],
+        "Impl blocks cannot be placed inside function bodies"
+    );
+    assert_eq!(violation["suggestion"], "Move this to module level");
+    assert_eq!(
+        violation["start"],
+        serde_json::json!({"line": 3, "column": 5})
+    );
+    assert_eq!(
+        violation["end"],
+        serde_json::json!({"line": 3, "column": 16})
+    );
+    assert!(
+        preview["message"]
+            .as_str()
+            .unwrap()
+            .contains("• Impl blocks cannot be placed inside function bodies at 3:5-3:16:")
+    );
+    Ok(())
+}
+
+#[test]
+fn valid_edits_have_no_violations() -> Result<()> {
+    let preview = stage_rust(
+        "fn main() {\n    let x = 1;\n}\n",
+        Operation::ReplaceExact,
+        "let x = 1;",
+        "let x = 2;",
+    )?;
+
+    assert_eq!(preview["staged"], true);
+    assert!(preview.get("violations").is_none());
+    Ok(())
+}
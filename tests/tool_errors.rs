@@ -0,0 +1,235 @@
+//! Tests for the typed errors failed tool calls are answered with
+
+use anyhow::Result;
+use mcplease::server_info;
+use mcplease::types::{McpError, McpRequest};
+use semantic_code_edit_mcp::error::{SemanticEditError, ToolError, ToolErrorKind};
+use semantic_code_edit_mcp::resources::Subscriptions;
+use semantic_code_edit_mcp::server;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+struct Client {
+    state: SemanticEditTools,
+    subscriptions: Subscriptions,
+}
+
+impl Client {
+    fn new(context: &TempDir) -> Result<Self> {
+        let mut client = Self {
+            state: SemanticEditTools::with_standard_operations(None)?,
+            subscriptions: Subscriptions::default(),
+        };
+        assert!(
+            client
+                .call_tool("set_context", json!({ "path": context.path() }))?
+                .is_none()
+        );
+        Ok(client)
+    }
+
+    /// Call a tool, returning its error if it failed
+    fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Option<McpError>> {
+        let request: McpRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": arguments },
+        }))?;
+        let response = server::handle(
+            request,
+            &mut self.state,
+            &mut self.subscriptions,
+            &server_info!(),
+            None,
+        );
+        Ok(response.error)
+    }
+}
+
+#[test]
+fn failures_carry_their_kind_and_code() -> Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.path().join("main.rs"), "fn main() {}\n")?;
+    let mut client = Client::new(&dir)?;
+
+    let error = client
+        .call_tool(
+            "stage_operation",
+            json!({
+                "file_path": "main.rs",
+                "operation": "replace_exact",
+                "anchor": "fn missing()",
+                "content": "fn found() {}",
+            }),
+        )?
+        .expect("a missing anchor is an error");
+    assert_eq!(error.code, -32602);
+    assert_eq!(error.data.unwrap()["kind"], "anchor_not_found");
+    assert!(error.message.contains("fn missing()"), "{}", error.message);
+
+    let error = client
+        .call_tool("commit_staged", json!({}))?
+        .expect("committing nothing is an error");
+    assert_eq!(error.code, -32003);
+    assert_eq!(error.data.unwrap(), json!({ "kind": "not_staged" }));
+
+    let error = client
+        .call_tool("stage_operation", json!({ "file_path": "main.rs" }))?
+        .expect("missing arguments are an error");
+    assert_eq!(error.code, -32602);
+    assert_eq!(error.data.unwrap()["kind"], "invalid_params");
+    Ok(())
+}
+
+#[test]
+fn stale_files_are_reported_with_their_path() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("main.rs");
+    std::fs::write(&path, "fn main() {\n    let x = 1;\n}\n")?;
+    let mut client = Client::new(&dir)?;
+
+    let staged = client.call_tool(
+        "stage_operation",
+        json!({
+            "file_path": "main.rs",
+            "operation": "replace_exact",
+            "anchor": "let x = 1;",
+            "content": "let x = 2;",
+        }),
+    )?;
+    assert!(staged.is_none(), "{staged:?}");
+    std::fs::write(&path, "fn main() {\n    let x = 1;\n    let y = 2;\n}\n")?;
+
+    let error = client
+        .call_tool("commit_staged", json!({}))?
+        .expect("a stale file is an error");
+    let data = error.data.unwrap();
+    assert_eq!(data["kind"], "stale_file");
+    assert!(
+        data["path"].as_str().unwrap().ends_with("main.rs"),
+        "{data}"
+    );
+    Ok(())
+}
+
+#[test]
+fn edit_search_failures_keep_their_kind() -> Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("main.rs"),
+        "fn main() {\n    let x = 1;\n}\n",
+    )?;
+    let mut client = Client::new(&dir)?;
+
+    let error = client
+        .call_tool(
+            "stage_operation",
+            json!({
+                "file_path": "main.rs",
+                "operation": "replace_range",
+                "anchor": "let x",
+                "end": "let y",
+                "content": "let x = 2;",
+            }),
+        )?
+        .expect("a missing range end is an error");
+    let data = error.data.unwrap();
+    assert_eq!(data["kind"], "anchor_not_found");
+    assert!(
+        data["path"].as_str().unwrap().ends_with("main.rs"),
+        "{data}"
+    );
+
+    let error = client
+        .call_tool(
+            "stage_operation",
+            json!({
+                "file_path": "main.rs",
+                "operation": "insert_after",
+                "anchor": "let x = 1;",
+                "end": "}",
+                "content": "let y = 2;",
+            }),
+        )?
+        .expect("a malformed selector is an error");
+    assert_eq!(error.data.unwrap()["kind"], "invalid_params");
+    assert!(
+        error.message.contains("End is not relevant"),
+        "{}",
+        error.message
+    );
+    Ok(())
+}
+
+#[test]
+fn selector_issues_name_their_field_and_code() -> Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.path().join("main.rs"),
+        "fn main() {\n    let x = 1;\n}\n",
+    )?;
+    let mut client = Client::new(&dir)?;
+
+    let error = client
+        .call_tool(
+            "stage_operation",
+            json!({
+                "file_path": "main.rs",
+                "operation": "replace_exact",
+                "anchor": " ",
+                "end": "}",
+                "content": "let y = 2;",
+            }),
+        )?
+        .expect("a malformed selector is an error");
+    let data = error.data.unwrap();
+    assert_eq!(
+        data["issues"],
+        json!([
+            {
+                "field": "anchor",
+                "code": "required",
+                "message": "`anchor` cannot be empty",
+            },
+            {
+                "field": "end",
+                "code": "not_applicable",
+                "message": "`end` is not relevant for `replace_exact` operations. Did you intend to `replace_range`?",
+            },
+        ]),
+        "{data}"
+    );
+
+    let error = client
+        .call_tool(
+            "stage_operation",
+            json!({
+                "file_path": "main.rs",
+                "operation": "rewrite_pattern",
+                "anchor": "let :[name] = 1;",
+                "content": "let :[name]: :[ty] = 1;",
+            }),
+        )?
+        .expect("an uncaptured hole is an error");
+    let data = error.data.unwrap();
+    assert_eq!(data["issues"][0]["field"], "content", "{data}");
+    assert_eq!(data["issues"][0]["code"], "invalid", "{data}");
+    Ok(())
+}
+
+#[test]
+fn errors_are_classified_through_context() {
+    let error = anyhow::Error::from(SemanticEditError::ReadOnly {
+        path: "/project/main.rs".into(),
+    })
+    .context("could not commit");
+    let error = ToolError::from(error);
+    assert_eq!(error.kind(), ToolErrorKind::Forbidden);
+    assert_eq!(error.path(), Some("/project/main.rs"));
+
+    let error = ToolError::from(anyhow::anyhow!("something else"));
+    assert_eq!(error.kind(), ToolErrorKind::Internal);
+    assert_eq!(error.code(), -32603);
+}
@@ -0,0 +1,61 @@
+//! Tests for the examples listed with each tool
+
+use anyhow::Result;
+use mcplease::server_info;
+use mcplease::types::McpRequest;
+use semantic_code_edit_mcp::resources::Subscriptions;
+use semantic_code_edit_mcp::server;
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{self, Tools};
+use serde_json::{Value, json};
+
+#[test]
+fn every_example_is_a_valid_call() -> Result<()> {
+    for schema in tools::tools_list() {
+        let name = schema["name"].as_str().unwrap();
+        let examples = schema["inputSchema"]["examples"].as_array().unwrap();
+        assert!(examples.len() >= 2, "{name} has too few examples");
+        for example in examples {
+            let mut arguments = example.clone();
+            let description = arguments.as_object_mut().unwrap().remove("description");
+            assert!(
+                description.as_ref().and_then(Value::as_str).is_some(),
+                "{name} example without a description: {example}"
+            );
+            let call = json!({ "name": name, "arguments": arguments });
+            let tool = serde_json::from_value::<Tools>(call)
+                .map_err(|error| anyhow::anyhow!("{name} example {example}: {error}"))?;
+            assert_eq!(tool.name(), name);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn tools_list_includes_examples() -> Result<()> {
+    let request: McpRequest = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/list",
+    }))?;
+    let response = server::handle(
+        request,
+        &mut SemanticEditTools::with_standard_operations(None)?,
+        &mut Subscriptions::default(),
+        &server_info!(),
+        None,
+    );
+    let result = serde_json::to_value(response.result)?;
+    let retarget = result["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|tool| tool["name"] == "retarget_staged")
+        .expect("retarget_staged is listed");
+    let examples = retarget["inputSchema"]["examples"].as_array().unwrap();
+    assert!(
+        examples.iter().any(|example| example["candidate"] == 2),
+        "{retarget}"
+    );
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Tests for editing files that do not parse as their language as text
+
+use anyhow::Result;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+
+/// Python, misdetected as Rust
+const PYTHON: &str = "import os\n\ndef main():\n    print(os.getcwd())\n";
+
+fn editor<'a>(
+    registry: &'a LanguageRegistry,
+    source: &str,
+    operation: Operation,
+    anchor: &str,
+    content: &str,
+) -> Result<Editor<'a>> {
+    Ok(Editor::from_source(
+        source,
+        content.into(),
+        Selector::builder(operation, anchor).build()?,
+        registry.get_language(LanguageName::Rust)?,
+    )?
+    .with_format_mode(FormatMode::Never))
+}
+
+#[test]
+fn files_in_another_language_are_edited_as_text() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+
+    let (report, staged) = editor(
+        &registry,
+        PYTHON,
+        Operation::InsertAfter,
+        "import os",
+        "\nimport sys",
+    )?
+    .preview_report()?;
+    assert!(staged.is_some(), "{}", report.edit.message);
+    let warning = report.edit.warning.unwrap();
+    assert!(
+        warning.contains("does not parse as rust at all, so it may be in another language"),
+        "{warning}"
+    );
+    assert!(warning.contains("without being parsed, validated, or formatted"));
+
+    let output = editor(
+        &registry,
+        PYTHON,
+        Operation::ReplaceExact,
+        "os.getcwd()",
+        "os.getpid()",
+    )?
+    .commit_to_string()?;
+    assert_eq!(output, PYTHON.replace("getcwd", "getpid"));
+    Ok(())
+}
+
+#[test]
+fn syntax_operations_explain_why_there_is_no_tree() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let error = editor(
+        &registry,
+        PYTHON,
+        Operation::InsertAfterNode,
+        "def main",
+        "\n\ndef other():\n    pass",
+    )?
+    .preview_report()
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("insert after node needs a syntax tree, but"),
+        "{error}"
+    );
+    assert!(error.to_string().contains("does not parse as rust at all"));
+    Ok(())
+}
+
+#[test]
+fn files_with_some_syntax_errors_are_still_parsed() -> Result<()> {
+    let registry = LanguageRegistry::new()?;
+    let source = "fn broken() {\n    let x = ;\n}\n\nfn main() {}\n";
+
+    // the file is parsed, so a new syntax error is still refused
+    let (report, staged) = editor(
+        &registry,
+        source,
+        Operation::InsertAfter,
+        "fn main() {}",
+        "\n\nfn other( {}",
+    )?
+    .preview_report()?;
+    assert!(staged.is_none(), "{}", report.edit.message);
+    assert!(report.edit.warning.is_none());
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Tests for staging edits against a client's unsaved buffer
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation, Tools};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn open_file(state: &mut SemanticEditTools, file_path: &Path) -> Result<String> {
+    let tool: Tools = serde_json::from_value(json!({
+        "name": "open_files",
+        "arguments": { "file_paths": [file_path] }
+    }))?;
+    tool.execute(state)
+}
+
+#[test]
+fn edits_apply_to_the_unsaved_buffer() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file_path = temp_dir.path().join("config.json");
+    fs::write(&file_path, "{\"key\": \"saved\"}\n")?;
+    let file_path = file_path.canonicalize()?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let preview = StageOperation {
+        file_path: file_path.display().to_string(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "\"unsaved\"".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\"edited\"".into()),
+        buffer: Some("{\"key\": \"unsaved\", \"extra\": true}\n".into()),
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    assert!(preview.starts_with("STAGED"));
+
+    let opened = open_file(&mut state, &file_path)?;
+    assert!(opened.contains("\"unsaved\""));
+    assert!(!opened.contains("\"saved\""));
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+
+    let written = fs::read_to_string(&file_path)?;
+    assert!(written.contains("\"edited\""));
+    assert!(written.contains("\"extra\""));
+
+    // after commit the buffer is no longer overlaid on the file
+    assert!(!state.session_file_operations(None)?.has_buffer(&file_path));
+    assert!(open_file(&mut state, &file_path)?.contains("\"edited\""));
+    Ok(())
+}
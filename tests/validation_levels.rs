@@ -0,0 +1,163 @@
+//! Tests for per-session validation strictness levels
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{Editor, FormatMode, ResultFormat};
+use semantic_code_edit_mcp::languages::{LanguageName, LanguageRegistry};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation, Tools};
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+/// A partial file that does not parse cleanly
+const PARTIAL: &str = "fn main() {\n    let x = 1;\n";
+
+fn stage(
+    level: Option<&str>,
+    source: &str,
+    content: &str,
+) -> Result<(String, SemanticEditTools, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join("partial.rs"), source)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let mut arguments = json!({ "path": temp_dir.path() });
+    if let Some(level) = level {
+        arguments["validation_level"] = json!(level);
+    }
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": arguments,
+    }))?;
+    set_context.execute(&mut state)?;
+
+    let preview = StageOperation {
+        file_path: "partial.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::ReplaceExact,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    Ok((preview, state, temp_dir))
+}
+
+#[test]
+fn strict_refuses_edits_inside_existing_errors() -> Result<()> {
+    // the whole partial function is one error node, so the edit cannot be shown to be valid
+    let (preview, state, _temp_dir) = stage(None, PARTIAL, "let x = 2;")?;
+    assert!(preview.contains("invalid syntax"), "{preview}");
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn strict_ignores_unrelated_errors_elsewhere_in_the_file() -> Result<()> {
+    let source = "fn broken( {\n}\n\nfn main() {\n    let x = 1;\n}\n";
+    let (preview, state, _temp_dir) = stage(None, source, "let x = 2;")?;
+    assert!(preview.starts_with("STAGED"), "{preview}");
+    assert!(state.get_staged_operation(None)?.is_some());
+
+    let (preview, state, _temp_dir) = stage(None, source, "let x = ;")?;
+    assert!(preview.contains("invalid syntax"), "{preview}");
+    assert!(preview.contains("5 ->⎸    let x = ;"));
+    assert!(!preview.contains("1 ->⎸"));
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn strict_finds_new_errors_after_the_first_one_python_and_json_report() -> Result<()> {
+    // rustpython and serde_json only report the first syntax error in a file
+    let registry = LanguageRegistry::new()?;
+    let insert = |language, source: &str, anchor: &str, content: &str| {
+        Editor::from_source(
+            source,
+            content.into(),
+            Selector::builder(Operation::InsertAfter, anchor).build()?,
+            registry.get_language(language)?,
+        )?
+        .with_format_mode(FormatMode::Never)
+        .commit_to_string()
+    };
+    let python = "x = (1\n\ndef f():\n    pass\n";
+    let json = "{\n  \"a\": 1,,\n  \"c\": 3\n}\n";
+
+    let error = insert(LanguageName::Python, python, "pass", "\ny = = 2").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("The file already had syntax errors"),
+        "{error}"
+    );
+    assert!(insert(LanguageName::Python, python, "pass", "\ny = 2").is_ok());
+    assert!(insert(LanguageName::Json, json, "\"c\": 3", ",\n  \"b\": 2 3").is_err());
+    assert!(insert(LanguageName::Json, json, "\"c\": 3", ",\n  \"b\": 2").is_ok());
+    Ok(())
+}
+
+#[test]
+fn strict_refuses_edits_that_introduce_errors() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(None, "fn main() {\n    let x = 1;\n}\n", "let x = ;")?;
+    assert!(preview.contains("invalid syntax"));
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn warn_stages_invalid_edits_to_partial_files_with_a_warning() -> Result<()> {
+    let (preview, mut state, temp_dir) = stage(Some("warn"), PARTIAL, "let x = ;")?;
+    assert!(preview.contains("WARNING"), "{preview}");
+    assert!(preview.contains("+    let x = ;"));
+    assert!(state.get_staged_operation(None)?.is_some());
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("partial.rs"))?,
+        "fn main() {\n    let x = ;\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn warn_stages_edits_that_introduce_errors() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(
+        Some("warn"),
+        "fn main() {\n    let x = 1;\n}\n",
+        "let x = ;",
+    )?;
+    assert!(preview.contains("WARNING"));
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
+
+#[test]
+fn off_stages_without_validation() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(Some("off"), PARTIAL, "let x = 2;")?;
+    assert!(preview.starts_with("STAGED"));
+    assert!(!preview.contains("WARNING"));
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Tests for per-rule validation settings
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation, Tools};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+fn stage(
+    rules: Value,
+    content: &str,
+    format: ResultFormat,
+) -> Result<(String, SemanticEditTools, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join("main.rs"), SOURCE)?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": temp_dir.path(), "rules": rules },
+    }))?;
+    let response = set_context.execute(&mut state)?;
+    assert!(response.contains("Validation rules"), "{response}");
+
+    let preview = StageOperation {
+        file_path: "main.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    Ok((preview, state, temp_dir))
+}
+
+#[test]
+fn error_rules_refuse_the_edit() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(
+        json!([{ "rule": "invalid.use.in.function.body", "setting": "error" }]),
+        "\n    use std::fmt;",
+        ResultFormat::Text,
+    )?;
+    assert!(preview.contains("Invalid placement detected"), "{preview}");
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn disabled_rules_are_ignored() -> Result<()> {
+    let (preview, mut state, temp_dir) = stage(
+        json!([{ "rule": "invalid.use.in.function.body", "setting": "off" }]),
+        "\n    use std::fmt;",
+        ResultFormat::Text,
+    )?;
+    assert!(preview.starts_with("STAGED"), "{preview}");
+    assert!(!preview.contains("WARNING"));
+
+    CommitStaged {
+        acknowledge: true,
+        acknowledge_dirty: false,
+        acknowledge_duplicate: false,
+        dry_run: false,
+        format: ResultFormat::Text,
+    }
+    .execute(&mut state)?;
+    assert!(fs::read_to_string(temp_dir.path().join("main.rs"))?.contains("use std::fmt;"));
+    Ok(())
+}
+
+#[test]
+fn downgraded_rules_stage_formatted_edits_with_a_warning() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(
+        json!([{ "rule": "invalid.use.in.function.body", "setting": "warn" }]),
+        "\n    use   std::fmt;",
+        ResultFormat::Json,
+    )?;
+    let preview: Value = serde_json::from_str(&preview)?;
+    assert_eq!(preview["staged"], true);
+    assert!(
+        preview["warning"]
+            .as_str()
+            .unwrap()
+            .contains("do not block")
+    );
+    assert_eq!(
+        preview["violations"][0]["rule"],
+        "invalid.use.in.function.body"
+    );
+    assert!(preview["diff"].as_str().unwrap().contains("use std::fmt;"));
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
+
+#[test]
+fn other_rules_still_block_when_one_is_downgraded() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(
+        json!([{ "rule": "invalid.use.in.function.body", "setting": "warn" }]),
+        "\n    use std::fmt;\n    impl Foo {}",
+        ResultFormat::Json,
+    )?;
+    let preview: Value = serde_json::from_str(&preview)?;
+    assert_eq!(preview["staged"], false);
+    let violations = preview["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["rule"], "invalid.impl.in.function.body");
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Tests for `warning.*` and `info.*` validation captures
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{StageOperation, Tools};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::TempDir;
+
+const ADVISORY: &str = r#"
+((line_comment) @warning.todo.comment
+ (#match? @warning.todo.comment "TODO")
+ (#set! message "TODO comment inserted"))
+
+((macro_invocation
+   macro: (identifier) @_macro) @info.debug.print
+ (#eq? @_macro "dbg")
+ (#set! message "dbg! left in the code"))
+"#;
+
+fn stage(rules: Value, content: &str) -> Result<(Value, SemanticEditTools, TempDir)> {
+    let temp_dir = TempDir::new()?;
+    let queries = temp_dir.path().join(".semantic-edit/queries/rust");
+    fs::create_dir_all(&queries)?;
+    fs::write(queries.join("advisory.scm"), ADVISORY)?;
+    fs::write(
+        temp_dir.path().join("main.rs"),
+        "fn main() {\n    let x = 1;\n}\n",
+    )?;
+
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": temp_dir.path(), "rules": rules },
+    }))?;
+    set_context.execute(&mut state)?;
+
+    let preview = StageOperation {
+        file_path: "main.rs".into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some(content.into()),
+        buffer: None,
+        format: ResultFormat::Json,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+    Ok((serde_json::from_str(&preview)?, state, temp_dir))
+}
+
+#[test]
+fn warning_captures_do_not_block_the_edit() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(json!([]), "\n    // TODO: handle x")?;
+    assert_eq!(preview["staged"], true);
+    let warning = preview["warning"].as_str().unwrap();
+    assert!(warning.contains("⚠️ Validation warnings:"), "{warning}");
+    assert!(warning.contains("• warning: TODO comment inserted at 3:5"));
+    assert_eq!(preview["violations"][0]["rule"], "warning.todo.comment");
+    assert_eq!(preview["violations"][0]["severity"], "warning");
+    assert!(state.get_staged_operation(None)?.is_some());
+    Ok(())
+}
+
+#[test]
+fn info_captures_do_not_block_the_edit() -> Result<()> {
+    let (preview, _state, _temp_dir) = stage(json!([]), "\n    dbg!(x);")?;
+    assert_eq!(preview["staged"], true);
+    assert_eq!(preview["violations"][0]["rule"], "info.debug.print");
+    assert_eq!(preview["violations"][0]["severity"], "info");
+    Ok(())
+}
+
+#[test]
+fn errors_still_block_alongside_warnings() -> Result<()> {
+    let (preview, _state, _temp_dir) =
+        stage(json!([]), "\n    // TODO: handle x\n    impl Foo {}")?;
+    assert_eq!(preview["staged"], false);
+    let violations = preview["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["severity"], "error");
+    Ok(())
+}
+
+#[test]
+fn rule_settings_can_upgrade_warnings_to_errors() -> Result<()> {
+    let (preview, state, _temp_dir) = stage(
+        json!([{ "rule": "warning.todo.comment", "setting": "error" }]),
+        "\n    // TODO: handle x",
+    )?;
+    assert_eq!(preview["staged"], false);
+    assert!(state.get_staged_operation(None)?.is_none());
+    Ok(())
+}
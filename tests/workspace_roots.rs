@@ -0,0 +1,111 @@
+//! Tests for resolving paths against named workspace roots
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::ResultFormat;
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{StageOperation, Tools};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+/// A temp dir with `frontend/main.rs` and `backend/main.rs`
+fn setup() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    for dir in ["frontend", "backend"] {
+        fs::create_dir(temp_dir.path().join(dir))?;
+        fs::write(temp_dir.path().join(dir).join("main.rs"), SOURCE)?;
+    }
+    Ok(temp_dir)
+}
+
+fn add_root(state: &mut SemanticEditTools, path: &Path, root: &str) -> Result<String> {
+    let set_context: Tools = serde_json::from_value(json!({
+        "name": "set_context",
+        "arguments": { "path": path, "root": root },
+    }))?;
+    set_context.execute(state)
+}
+
+fn staged_path(state: &mut SemanticEditTools, file_path: &str) -> Result<PathBuf> {
+    StageOperation {
+        file_path: file_path.into(),
+        language: None,
+        selector: Selector {
+            operation: Operation::InsertAfter,
+            anchor: "let x = 1;".into(),
+            end: None,
+            end_inclusive: true,
+            include_leading_trivia: false,
+        },
+        content: Some("\n    let y = 2;".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: None,
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(state)?;
+    Ok(state.get_staged_operation(None)?.unwrap().file_path)
+}
+
+#[test]
+fn paths_resolve_against_the_named_root() -> Result<()> {
+    let temp_dir = setup()?;
+    let frontend = temp_dir.path().join("frontend").canonicalize()?;
+    let backend = temp_dir.path().join("backend").canonicalize()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+
+    add_root(&mut state, &frontend, "frontend")?;
+    let response = add_root(&mut state, &backend, "backend")?;
+    assert!(response.contains("Registered workspace root `backend`"));
+    assert!(response.contains("`frontend`"));
+
+    assert_eq!(
+        staged_path(&mut state, "backend:main.rs")?,
+        backend.join("main.rs")
+    );
+    assert_eq!(
+        staged_path(&mut state, "frontend:main.rs")?,
+        frontend.join("main.rs")
+    );
+    // the first root registered is the context for plain relative paths
+    assert_eq!(
+        staged_path(&mut state, "main.rs")?,
+        frontend.join("main.rs")
+    );
+    Ok(())
+}
+
+#[test]
+fn unknown_roots_and_invalid_names_are_refused() -> Result<()> {
+    let temp_dir = setup()?;
+    let mut state = SemanticEditTools::with_standard_operations(None)?;
+    add_root(&mut state, &temp_dir.path().join("frontend"), "frontend")?;
+
+    let error = staged_path(&mut state, "backend:main.rs").unwrap_err();
+    assert!(
+        error.to_string().contains("Available roots: `frontend`"),
+        "{error}"
+    );
+
+    let error = staged_path(&mut state, "frontend:/etc/passwd").unwrap_err();
+    assert!(
+        error.to_string().contains("relative to the workspace root"),
+        "{error}"
+    );
+
+    let error = add_root(&mut state, &temp_dir.path().join("backend"), "C").unwrap_err();
+    assert!(
+        error.to_string().contains("invalid workspace root name"),
+        "{error}"
+    );
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! Tests for reading files back after a commit writes them
+
+use anyhow::Result;
+use mcplease::traits::Tool;
+use semantic_code_edit_mcp::editor::{FormatMode, ResultFormat};
+use semantic_code_edit_mcp::encoding::TextEncoding;
+use semantic_code_edit_mcp::filesystem::{FileOperations, TestFileOperations};
+use semantic_code_edit_mcp::selector::{Operation, Selector};
+use semantic_code_edit_mcp::state::SemanticEditTools;
+use semantic_code_edit_mcp::tools::{CommitStaged, StageOperation};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Writes files through a rewrite, like a format-on-save hook
+struct RewriteOnWrite {
+    files: TestFileOperations,
+    rewrite: fn(String) -> String,
+}
+
+impl FileOperations for RewriteOnWrite {
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        self.files.read_bytes(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.exists(path)
+    }
+
+    fn write_file(&self, path: PathBuf, content: String) -> Result<()> {
+        self.files.write_file(path, (self.rewrite)(content))
+    }
+
+    fn write_bytes(&self, path: PathBuf, content: Vec<u8>) -> Result<()> {
+        self.files.write_bytes(path, content)
+    }
+}
+
+fn commit_edit(file_operations: Box<dyn FileOperations>, path: &Path) -> Result<Value> {
+    let mut state = SemanticEditTools::with_file_operations(None, file_operations)?;
+    StageOperation {
+        file_path: path.display().to_string(),
+        language: None,
+        selector: Selector::builder(Operation::ReplaceExact, "\"before\"").build()?,
+        content: Some("\"after\"".into()),
+        buffer: None,
+        format: ResultFormat::Text,
+        diff_options: Default::default(),
+        format_mode: Some(FormatMode::Never),
+        cell: None,
+        candidate: None,
+        show_alternatives: false,
+        idempotent: false,
+    }
+    .execute(&mut state)?;
+
+    Ok(serde_json::from_str(
+        &CommitStaged {
+            acknowledge: true,
+            acknowledge_dirty: false,
+            acknowledge_duplicate: false,
+            dry_run: false,
+            format: ResultFormat::Json,
+        }
+        .execute(&mut state)?,
+    )?)
+}
+
+#[test]
+fn files_in_every_encoding_read_back_as_written() -> Result<()> {
+    let path = Path::new("/virtual/project/config.json");
+    for encoding in [
+        TextEncoding::Utf8,
+        TextEncoding::Utf8Bom,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+    ] {
+        let files = Arc::new(TestFileOperations::new());
+        files.add_file(path, encoding.encode("{\r\n  \"key\": \"before\"\r\n}\r\n"));
+
+        let report = commit_edit(Box::new(Arc::clone(&files)), path)?;
+        assert_eq!(report["applied"], true, "{report}");
+        assert!(report.get("verification").is_none(), "{report}");
+        assert_eq!(
+            files.read_encoded(path)?,
+            (encoding, "{\r\n  \"key\": \"after\"\r\n}\r\n".to_string())
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn content_changed_on_write_is_reported() -> Result<()> {
+    let path = Path::new("/virtual/project/config.json");
+    let files = TestFileOperations::new().with_file(path, "{\"key\": \"before\"}\n");
+
+    let report = commit_edit(
+        Box::new(RewriteOnWrite {
+            files,
+            rewrite: |content| content + "}",
+        }),
+        path,
+    )?;
+    assert_eq!(report["applied"], true, "{report}");
+    let verification = report["verification"].as_str().unwrap_or_default();
+    assert!(
+        verification.contains("at line 2: expected \"<end of file>\", found \"}\""),
+        "{report}"
+    );
+    assert!(verification.ends_with("no longer parses"), "{report}");
+    Ok(())
+}
+
+#[test]
+fn changed_line_endings_are_reported_without_a_line() -> Result<()> {
+    let path = Path::new("/virtual/project/config.json");
+    let files = TestFileOperations::new().with_file(path, "{\"key\": \"before\"}\n");
+
+    let report = commit_edit(
+        Box::new(RewriteOnWrite {
+            files,
+            rewrite: |content| content.replace('\n', "\r\n"),
+        }),
+        path,
+    )?;
+    assert_eq!(
+        report["verification"],
+        "the file on disk differs from the edit's output in its line endings or final newline"
+    );
+    Ok(())
+}